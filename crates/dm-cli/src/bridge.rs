@@ -17,9 +17,8 @@ type Lines = tokio::io::Lines<BufReader<ReadHalf>>;
 // ── Unified task queue ──
 
 enum Task {
-    DoraEvent(Event),
+    DoraEvent(Box<Event>),
     SocketInput(String),
-    Stop,
 }
 
 // ── Spec types ──
@@ -106,11 +105,11 @@ pub async fn bridge_serve(home: &Path, run_id: &str) -> Result<()> {
     std::thread::spawn(move || loop {
         match events.recv() {
             Some(event @ Event::Stop(_)) => {
-                let _ = dora_tx.blocking_send(Task::DoraEvent(event));
+                let _ = dora_tx.blocking_send(Task::DoraEvent(Box::new(event)));
                 break;
             }
             Some(event) => {
-                let _ = dora_tx.blocking_send(Task::DoraEvent(event));
+                let _ = dora_tx.blocking_send(Task::DoraEvent(Box::new(event)));
             }
             None => break,
         }
@@ -120,14 +119,9 @@ pub async fn bridge_serve(home: &Path, run_id: &str) -> Result<()> {
     let socket_tx = tx.clone();
     let mut lines = lines;
     tokio::spawn(async move {
-        loop {
-            match lines.next_line().await {
-                Ok(Some(line)) => {
-                    if socket_tx.send(Task::SocketInput(line)).await.is_err() {
-                        break;
-                    }
-                }
-                _ => break,
+        while let Ok(Some(line)) = lines.next_line().await {
+            if socket_tx.send(Task::SocketInput(line)).await.is_err() {
+                break;
             }
         }
     });
@@ -138,33 +132,36 @@ pub async fn bridge_serve(home: &Path, run_id: &str) -> Result<()> {
     // ── Consumer: process tasks FIFO ──
     while let Some(task) = rx.recv().await {
         match task {
-            Task::DoraEvent(Event::Input { id, data, .. }) => {
-                let event_id: &str = id.as_ref();
-                if let Some(spec) = display_ports.get(event_id) {
-                    if let Some(decoded) = decode_display_payload(&data) {
-                        let tag = decoded
-                            .get("tag")
-                            .and_then(Value::as_str)
-                            .unwrap_or("text")
-                            .to_string();
-                        let payload = decoded.get("payload").cloned().unwrap_or_else(|| json!({}));
-                        let msg = json!({
-                            "action":"push",
-                            "from": spec.yaml_id,
-                            "tag": tag,
-                            "payload": payload,
-                        });
-                        write_line(&mut writer, &msg.to_string()).await?;
-                        writer.flush().await?;
-                        eprintln!("[{}] [bridge] display {tag} -> {}", now_ts(), spec.yaml_id);
+            Task::DoraEvent(event) => match *event {
+                Event::Input { id, data, .. } => {
+                    let event_id: &str = id.as_ref();
+                    if let Some(spec) = display_ports.get(event_id) {
+                        if let Some(decoded) = decode_display_payload(&data) {
+                            let tag = decoded
+                                .get("tag")
+                                .and_then(Value::as_str)
+                                .unwrap_or("text")
+                                .to_string();
+                            let payload =
+                                decoded.get("payload").cloned().unwrap_or_else(|| json!({}));
+                            let msg = json!({
+                                "action":"push",
+                                "from": spec.yaml_id,
+                                "tag": tag,
+                                "payload": payload,
+                            });
+                            write_line(&mut writer, &msg.to_string()).await?;
+                            writer.flush().await?;
+                            eprintln!("[{}] [bridge] display {tag} -> {}", now_ts(), spec.yaml_id);
+                        }
                     }
                 }
-            }
-            Task::DoraEvent(Event::Stop(_)) => {
-                eprintln!("[{}] [bridge] dora stop received", now_ts());
-                break;
-            }
-            Task::DoraEvent(_) => {}
+                Event::Stop(_) => {
+                    eprintln!("[{}] [bridge] dora stop received", now_ts());
+                    break;
+                }
+                _ => {}
+            },
             Task::SocketInput(line) => {
                 let notif: InputNotification = match serde_json::from_str(&line) {
                     Ok(n) => n,
@@ -185,7 +182,6 @@ pub async fn bridge_serve(home: &Path, run_id: &str) -> Result<()> {
                     notif.to
                 );
             }
-            Task::Stop => break,
         }
     }
 
@@ -0,0 +1,39 @@
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+
+pub fn set(home: &Path, name: &str, version: &str) -> Result<()> {
+    dm_core::set_version_alias(home, name, version)?;
+    println!(
+        "{} {} now points to dora {}",
+        "✅".green(),
+        name.bold(),
+        version.trim_start_matches('v').bold()
+    );
+    Ok(())
+}
+
+pub fn remove(home: &Path, name: &str) -> Result<()> {
+    if !dm_core::remove_version_alias(home, name)? {
+        bail!("No version alias named '{}'", name);
+    }
+    println!("{} Removed alias {}", "✅".green(), name.bold());
+    Ok(())
+}
+
+pub fn list(home: &Path) -> Result<()> {
+    let aliases = dm_core::version_aliases(home)?;
+    for name in dm_core::BUILTIN_ALIASES {
+        if aliases.contains_key(*name) {
+            continue;
+        }
+        let target = dm_core::resolve_installed_version(home, name)
+            .unwrap_or_else(|_| "(none installed)".to_string());
+        println!("{} -> {} {}", name, target, "(built-in)".dimmed());
+    }
+    for (name, version) in aliases {
+        println!("{} -> {}", name, version);
+    }
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use std::path::Path;
+
+use anyhow::Result;
+use colored::Colorize;
+
+use dm_core::events::{query_audit, AuditEntry, AuditFilter, EventStore};
+
+/// Print the newest audited API calls matching `filter`, oldest first.
+pub fn audit(home: &Path, mut filter: AuditFilter) -> Result<()> {
+    if let Some(since) = filter.since.take() {
+        filter.since = Some(super::logs::parse_since(&since)?);
+    }
+    let store = EventStore::open(home)?;
+    let mut entries = query_audit(&store, &filter)?;
+    if entries.is_empty() {
+        println!("No audited calls recorded.");
+        return Ok(());
+    }
+    entries.reverse();
+    for entry in &entries {
+        print_entry(entry);
+    }
+    Ok(())
+}
+
+fn print_entry(entry: &AuditEntry) {
+    let timestamp = entry.timestamp.get(..19).unwrap_or(&entry.timestamp);
+    let who = format!(
+        "{}@{}",
+        entry.user.as_deref().unwrap_or("anonymous"),
+        entry.client_ip.as_deref().unwrap_or("?")
+    );
+    let status = if entry.succeeded() {
+        entry.status.to_string().green()
+    } else {
+        entry.status.to_string().red()
+    };
+    println!(
+        "{} {:<24} {} {} {}",
+        timestamp.dimmed(),
+        who.cyan(),
+        entry.action.bold(),
+        entry.target.as_deref().unwrap_or("-"),
+        status
+    );
+}
@@ -0,0 +1,159 @@
+//! Shell completion: `dm completions <shell>` prints a script whose dynamic
+//! parts call the hidden `dm complete <kind>`, which asks a running
+//! dm-server for live names and falls back to the local dm home.
+
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::ValueEnum;
+
+/// dm-server address queried for candidates; override with `DM_SERVER_URL`.
+const DEFAULT_SERVER_URL: &str = "http://127.0.0.1:3210";
+/// Completion must feel instant, so a slow or absent server is skipped.
+const SERVER_TIMEOUT: Duration = Duration::from_millis(300);
+
+/// `dm node` subcommands whose arguments are node ids.
+const NODE_ID_SUBCOMMANDS: &[&str] = &["install", "uninstall", "pin", "unpin"];
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum CompleteKind {
+    Dataflows,
+    Nodes,
+}
+
+/// Print candidate names for `kind`, one per line.
+pub async fn complete(home: &Path, kind: CompleteKind) -> Result<()> {
+    let names = match from_server(kind).await {
+        Some(names) => names,
+        None => from_home(home, kind),
+    };
+    for name in names {
+        println!("{name}");
+    }
+    Ok(())
+}
+
+async fn from_server(kind: CompleteKind) -> Option<Vec<String>> {
+    let base = std::env::var("DM_SERVER_URL").unwrap_or_else(|_| DEFAULT_SERVER_URL.to_string());
+    let (path, key) = match kind {
+        CompleteKind::Dataflows => ("/api/dataflows", "name"),
+        CompleteKind::Nodes => ("/api/nodes", "id"),
+    };
+    let client = reqwest::Client::builder()
+        .timeout(SERVER_TIMEOUT)
+        .build()
+        .ok()?;
+    let resp = client
+        .get(format!("{}{}", base.trim_end_matches('/'), path))
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?;
+    let items: Vec<serde_json::Value> = resp.json().await.ok()?;
+    Some(
+        items
+            .iter()
+            .filter_map(|item| item.get(key).and_then(|v| v.as_str()))
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+fn from_home(home: &Path, kind: CompleteKind) -> Vec<String> {
+    let mut names: Vec<String> = match kind {
+        CompleteKind::Dataflows => std::fs::read_dir(dm_core::dataflow::dataflows_dir(home))
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter(|e| dm_core::dataflow::dataflow_yaml_path(&e.path()).exists())
+                    .filter_map(|e| e.file_name().to_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default(),
+        CompleteKind::Nodes => dm_core::node::list_nodes(home)
+            .map(|nodes| nodes.into_iter().map(|n| n.id).collect())
+            .unwrap_or_default(),
+    };
+    names.sort();
+    names
+}
+
+/// Print the completion script for `shell`.
+pub fn completions(cli: &clap::Command, shell: Shell) {
+    let subcommands = visible_subcommands(cli);
+    let node_subcommands = cli
+        .find_subcommand("node")
+        .map(visible_subcommands)
+        .unwrap_or_default();
+    let script = match shell {
+        Shell::Bash => bash_script(&subcommands, &node_subcommands),
+        Shell::Zsh => format!(
+            "autoload -U +X bashcompinit && bashcompinit\n{}",
+            bash_script(&subcommands, &node_subcommands)
+        ),
+        Shell::Fish => fish_script(&subcommands, &node_subcommands),
+    };
+    print!("{script}");
+}
+
+fn visible_subcommands(cmd: &clap::Command) -> Vec<String> {
+    cmd.get_subcommands()
+        .filter(|sub| !sub.is_hide_set() && !sub.get_name().starts_with('-'))
+        .map(|sub| sub.get_name().to_string())
+        .collect()
+}
+
+fn bash_script(subcommands: &[String], node_subcommands: &[String]) -> String {
+    format!(
+        r#"_dm() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+    if [ "$COMP_CWORD" -eq 1 ]; then
+        COMPREPLY=( $(compgen -W "{subs}" -- "$cur") )
+        return
+    fi
+    case "${{COMP_WORDS[1]}}" in
+        start)
+            COMPREPLY=( $(compgen -W "$(dm complete dataflows 2>/dev/null)" -- "$cur") $(compgen -f -- "$cur") )
+            ;;
+        node)
+            if [ "$COMP_CWORD" -eq 2 ]; then
+                COMPREPLY=( $(compgen -W "{node_subs}" -- "$cur") )
+            else
+                case "${{COMP_WORDS[2]}}" in
+                    {node_id_subs})
+                        COMPREPLY=( $(compgen -W "$(dm complete nodes 2>/dev/null)" -- "$cur") )
+                        ;;
+                esac
+            fi
+            ;;
+    esac
+}}
+complete -o default -F _dm dm
+"#,
+        subs = subcommands.join(" "),
+        node_subs = node_subcommands.join(" "),
+        node_id_subs = NODE_ID_SUBCOMMANDS.join("|"),
+    )
+}
+
+fn fish_script(subcommands: &[String], node_subcommands: &[String]) -> String {
+    format!(
+        r#"complete -c dm -f -n "__fish_use_subcommand" -a "{subs}"
+complete -c dm -n "__fish_seen_subcommand_from start" -a "(dm complete dataflows 2>/dev/null)"
+complete -c dm -f -n "__fish_seen_subcommand_from node; and not __fish_seen_subcommand_from {node_subs}" -a "{node_subs}"
+complete -c dm -f -n "__fish_seen_subcommand_from node; and __fish_seen_subcommand_from {node_id_subs}" -a "(dm complete nodes 2>/dev/null)"
+"#,
+        subs = subcommands.join(" "),
+        node_subs = node_subcommands.join(" "),
+        node_id_subs = NODE_ID_SUBCOMMANDS.join(" "),
+    )
+}
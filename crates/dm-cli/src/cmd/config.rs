@@ -0,0 +1,44 @@
+use std::path::Path;
+
+use anyhow::Result;
+use colored::Colorize;
+
+/// Strings print bare, everything else as JSON.
+fn display_value(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+pub fn get(home: &Path, key: &str) -> Result<()> {
+    match dm_core::config::config_value(home, key)? {
+        Some(value) => println!("{}", display_value(&value)),
+        None => println!("{}", "(not set)".dimmed()),
+    }
+    Ok(())
+}
+
+pub fn set(home: &Path, key: &str, value: &str) -> Result<()> {
+    dm_core::config::set_config_value(home, key, value)?;
+    let saved = dm_core::config::config_value(home, key)?;
+    let shown = match key {
+        "github_token" => dm_core::config::REDACTED.to_string(),
+        _ => saved.as_ref().map(display_value).unwrap_or_default(),
+    };
+    println!("{} {} = {}", "✅".green(), key.bold(), shown);
+    Ok(())
+}
+
+pub fn unset(home: &Path, key: &str) -> Result<()> {
+    dm_core::config::unset_config_value(home, key)?;
+    println!("{} Reset {} to its default", "✅".green(), key.bold());
+    Ok(())
+}
+
+pub fn list(home: &Path) -> Result<()> {
+    for (key, value) in dm_core::config::config_entries(home)? {
+        println!("{} = {}", key.bold(), display_value(&value));
+    }
+    Ok(())
+}
@@ -10,9 +10,18 @@ pub async fn import(home: &Path, sources: Vec<String>) -> Result<()> {
     for source in &sources {
         let source_path = Path::new(source);
         let is_url = source.starts_with("https://") || source.starts_with("http://");
-        let inferred_name = dm_core::dataflow::infer_import_name(source);
+        let mut inferred_name = dm_core::dataflow::infer_import_name(source);
 
-        let result = if is_url {
+        let is_bundle = source_path.is_file()
+            && source_path.extension().and_then(|ext| ext.to_str())
+                == Some(dm_core::dataflow::DATAFLOW_BUNDLE_EXTENSION);
+
+        let result = if is_bundle {
+            println!("{} Importing bundle {}...", "→".cyan(), source.bold());
+            import_bundle(home, source_path)
+                .await
+                .map(|name| inferred_name = name)
+        } else if is_url {
             println!(
                 "{} Importing dataflow {} from git...",
                 "→".cyan(),
@@ -62,3 +71,176 @@ pub async fn import(home: &Path, sources: Vec<String>) -> Result<()> {
     }
     Ok(())
 }
+
+/// Import a `.dmflow` bundle and report its nodes; returns the dataflow name.
+async fn import_bundle(home: &Path, path: &Path) -> Result<String> {
+    let bundle = dm_core::dataflow::read_bundle(path)?;
+    let report = dm_core::dataflow::import_bundle(home, &bundle, None).await?;
+    for outcome in &report.installed {
+        match (&outcome.version, &outcome.error) {
+            (Some(version), _) => println!(
+                "  {} Installed node {} {}",
+                "✅".green(),
+                outcome.node_id.bold(),
+                version.green()
+            ),
+            (None, error) => println!(
+                "  {} Could not install node {}: {}",
+                "⚠".yellow(),
+                outcome.node_id.bold(),
+                error.as_deref().unwrap_or("unknown error")
+            ),
+        }
+    }
+    for node_id in &report.configured {
+        println!("  Applied bundled config to {}", node_id.bold());
+    }
+    for warning in &report.warnings {
+        println!("  {} {}", "⚠".yellow(), warning);
+    }
+    for (node_id, fields) in &report.missing_secrets {
+        println!(
+            "  {} {} needs secret config: {} (not included in bundles)",
+            "⚠".yellow(),
+            node_id.bold(),
+            fields.join(", ")
+        );
+    }
+    Ok(report.name)
+}
+
+pub fn export(home: &Path, name: &str, output: Option<String>) -> Result<()> {
+    let out = output
+        .unwrap_or_else(|| format!("{}.{}", name, dm_core::dataflow::DATAFLOW_BUNDLE_EXTENSION));
+    let bundle = dm_core::dataflow::write_bundle(home, name, Path::new(&out))?;
+    println!(
+        "{} Exported {} into {}",
+        "✅".green(),
+        name.bold(),
+        bundle.display().to_string().bold()
+    );
+    println!(
+        "  Import elsewhere with: dm dataflow import {}",
+        bundle.display()
+    );
+    Ok(())
+}
+
+pub fn sync(home: &Path, message: Option<&str>, push: bool) -> Result<()> {
+    println!(
+        "{} Syncing {}...",
+        "→".cyan(),
+        dm_core::dataflow::dataflows_dir(home).display()
+    );
+    let report = dm_core::dataflow::sync(home, message, push)?;
+    if report.committed.is_empty() {
+        println!("  No local changes to commit.");
+    } else {
+        println!("  Committed {} file(s):", report.committed.len());
+        for file in &report.committed {
+            println!("    {}", file);
+        }
+    }
+    if report.pulled {
+        println!("  Pulled remote changes.");
+    }
+    if report.pushed {
+        println!("  Pushed to remote.");
+    } else if push {
+        println!(
+            "  {} No git remote configured; nothing pushed.",
+            "⚠".yellow()
+        );
+    }
+    println!("{} Dataflows synced.", "✅".green());
+    Ok(())
+}
+
+pub fn history(home: &Path, name: &str) -> Result<()> {
+    let versions = dm_core::dataflow::list_history(home, name)?;
+    if versions.is_empty() {
+        println!("No saved versions of {} yet.", name.bold());
+        return Ok(());
+    }
+    println!("{:<28} {:<28} {:>8}", "VERSION", "SAVED", "SIZE");
+    for entry in &versions {
+        println!(
+            "{:<28} {:<28} {:>8}",
+            entry.version, entry.modified_at, entry.size
+        );
+    }
+    Ok(())
+}
+
+pub fn diff(home: &Path, name: &str, version: &str, against: Option<&str>) -> Result<()> {
+    let result = dm_core::dataflow::diff_history_version(home, name, version, against)?;
+    if result.diff.is_empty() {
+        println!(
+            "{} {} is identical to {}.",
+            "✅".green(),
+            version,
+            result.against
+        );
+        return Ok(());
+    }
+    for line in result.diff.lines() {
+        if line.starts_with("+++") || line.starts_with("---") {
+            println!("{}", line.bold());
+        } else if line.starts_with('+') {
+            println!("{}", line.green());
+        } else if line.starts_with('-') {
+            println!("{}", line.red());
+        } else if line.starts_with("@@") {
+            println!("{}", line.cyan());
+        } else {
+            println!("{}", line);
+        }
+    }
+    println!("{} added, {} removed", result.added, result.removed);
+    Ok(())
+}
+
+pub fn restore(home: &Path, name: &str, version: &str) -> Result<()> {
+    dm_core::dataflow::restore_history_version(home, name, version)?;
+    println!(
+        "{} Restored {} to {}. The replaced YAML is in `dm dataflow history {}`.",
+        "✅".green(),
+        name.bold(),
+        version,
+        name
+    );
+    Ok(())
+}
+
+/// Print the golden rendering of `file`, or with `check` compare it against
+/// a golden file and fail on any difference.
+pub fn transpile(home: &Path, file: &Path, check: Option<&Path>) -> Result<()> {
+    if !file.exists() {
+        bail!("Graph file '{}' not found.", file.display());
+    }
+    let Some(golden) = check else {
+        print!("{}", dm_core::dataflow::render_golden(home, file)?);
+        return Ok(());
+    };
+
+    let result = dm_core::dataflow::check_golden(home, file, golden)?;
+    if result.matches() {
+        println!(
+            "{} {} matches {}",
+            "✅".green(),
+            file.display(),
+            golden.display()
+        );
+        return Ok(());
+    }
+    println!(
+        "{} {} differs from {}:",
+        "❌".red(),
+        file.display(),
+        golden.display()
+    );
+    for line in &result.diff {
+        println!("{}", line);
+    }
+    bail!("Transpile output does not match {}", golden.display())
+}
@@ -0,0 +1,57 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+
+/// Validate a dataflow file, editor JSON or dora YAML, and print its errors
+/// and warnings.
+pub fn validate(home: &Path, file: &Path) -> Result<()> {
+    let text = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read {}", file.display()))?;
+    let report = dm_core::dataflow::validate_graph(home, &text);
+
+    for (mark, issues) in [
+        ("✗".red(), &report.errors),
+        ("⚠".yellow(), &report.warnings),
+    ] {
+        for issue in issues {
+            match &issue.node {
+                Some(node) => println!("  {} {}: {}", mark, node.bold(), issue.message),
+                None => println!("  {} {}", mark, issue.message),
+            }
+        }
+    }
+    if !report.valid {
+        bail!("{} has {} error(s)", file.display(), report.errors.len());
+    }
+    println!(
+        "{} {} is valid{}",
+        "✅".green(),
+        file.display(),
+        match report.warnings.len() {
+            0 => String::new(),
+            n => format!(" ({} warning(s))", n),
+        }
+    );
+    Ok(())
+}
+
+/// Convert a graph file to `to`, printing it or writing it to `output`.
+pub fn convert(
+    file: &Path,
+    to: dm_core::dataflow::GraphFormat,
+    output: Option<&Path>,
+) -> Result<()> {
+    let text = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read {}", file.display()))?;
+    let converted = dm_core::dataflow::convert_graph(&text, to)?;
+    match output {
+        Some(output) => {
+            std::fs::write(output, &converted)
+                .with_context(|| format!("Failed to write {}", output.display()))?;
+            println!("{} Wrote {}", "✅".green(), output.display());
+        }
+        None => println!("{}", converted.trim_end()),
+    }
+    Ok(())
+}
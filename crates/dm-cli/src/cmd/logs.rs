@@ -0,0 +1,110 @@
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+
+use dm_core::events::{Event, EventFilter, EventStore};
+
+/// How often `--follow` checks for new events.
+const FOLLOW_INTERVAL: Duration = Duration::from_millis(500);
+/// Events fetched per poll while following.
+const FOLLOW_BATCH: i64 = 500;
+
+/// Print the newest `tail` events matching `filter`, oldest first, then
+/// with `follow` keep printing new ones until interrupted.
+pub async fn logs(home: &Path, mut filter: EventFilter, tail: i64, follow: bool) -> Result<()> {
+    if let Some(since) = filter.since.take() {
+        filter.since = Some(parse_since(&since)?);
+    }
+    let store = EventStore::open(home)?;
+
+    let mut events = store.query(&EventFilter {
+        limit: Some(tail),
+        ..filter.clone()
+    })?;
+    events.reverse();
+    let mut last_id = events.last().map(|event| event.id);
+    for event in &events {
+        print_event(event);
+    }
+    if !follow {
+        return Ok(());
+    }
+
+    // Start after the newest stored event even when nothing matched yet.
+    if last_id.is_none() {
+        last_id = store
+            .query(&EventFilter {
+                limit: Some(1),
+                ..Default::default()
+            })?
+            .first()
+            .map(|event| event.id);
+    }
+    loop {
+        let batch = store.query(&EventFilter {
+            after_id: Some(last_id.unwrap_or(0)),
+            limit: Some(FOLLOW_BATCH),
+            ..filter.clone()
+        })?;
+        for event in &batch {
+            print_event(event);
+        }
+        if let Some(event) = batch.last() {
+            last_id = Some(event.id);
+            std::io::stdout().flush()?;
+        }
+        if (batch.len() as i64) < FOLLOW_BATCH {
+            tokio::time::sleep(FOLLOW_INTERVAL).await;
+        }
+    }
+}
+
+/// `--since` as an RFC 3339 timestamp, or a duration back from now such as
+/// `30s`, `10m`, `2h` or `1d`.
+pub(crate) fn parse_since(raw: &str) -> Result<String> {
+    if let Ok(ts) = chrono::DateTime::parse_from_rfc3339(raw) {
+        return Ok(ts.with_timezone(&chrono::Utc).to_rfc3339());
+    }
+    let (amount, unit) = raw.split_at(raw.len().saturating_sub(1));
+    let seconds = match (amount.parse::<i64>(), unit) {
+        (Ok(n), "s") => n,
+        (Ok(n), "m") => n * 60,
+        (Ok(n), "h") => n * 3600,
+        (Ok(n), "d") => n * 86400,
+        _ => bail!(
+            "Invalid --since '{}': use an RFC 3339 timestamp or a duration like 10m, 2h, 1d",
+            raw
+        ),
+    };
+    Ok((chrono::Utc::now() - chrono::Duration::seconds(seconds)).to_rfc3339())
+}
+
+fn print_event(event: &Event) {
+    let timestamp = event.timestamp.get(..19).unwrap_or(&event.timestamp);
+    let level = match event.level.as_str() {
+        "error" => "ERROR".red().bold(),
+        "warn" => "WARN ".yellow(),
+        "debug" | "trace" => event.level.to_uppercase().dimmed(),
+        _ => "INFO ".normal(),
+    };
+    let scope = match &event.node_id {
+        Some(node) => format!("{}/{}", event.case_id, node),
+        None => event.case_id.clone(),
+    };
+    println!(
+        "{} {} {:<9} {} {}{}",
+        timestamp.dimmed(),
+        level,
+        event.source,
+        scope.cyan(),
+        event.activity.bold(),
+        event
+            .message
+            .as_deref()
+            .map(|message| format!(": {}", message))
+            .unwrap_or_default()
+    );
+}
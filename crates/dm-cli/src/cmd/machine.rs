@@ -0,0 +1,24 @@
+use std::path::Path;
+
+use anyhow::Result;
+use colored::Colorize;
+
+pub fn add(home: &Path, name: &str, addr: &str) -> Result<()> {
+    dm_core::add_machine(home, name, addr)?;
+    println!(
+        "{} Registered machine {} at {}",
+        "✅".green(),
+        name.trim().bold(),
+        addr.trim()
+    );
+    Ok(())
+}
+
+pub async fn list(home: &Path) {
+    let machines = dm_core::machine_statuses(home).await;
+    if machines.is_empty() {
+        println!("No machines registered. Use `dm machine add <name> <addr>`.");
+        return;
+    }
+    crate::display::print_machines(&machines);
+}
@@ -1,3 +1,18 @@
+pub mod alias;
+pub mod audit;
+pub mod complete;
+pub mod config;
 pub mod dataflow;
+pub mod graph;
+pub mod logs;
+pub mod machine;
 pub mod node;
+pub mod plugin;
+pub mod quickstart;
 pub mod runs;
+pub mod runtime;
+pub mod secret;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod shim;
+pub mod tui;
@@ -3,39 +3,112 @@ use std::path::Path;
 use anyhow::{bail, Context, Result};
 use colored::Colorize;
 
-pub async fn install(home: &Path, ids: Vec<String>) -> Result<()> {
-    let total = ids.len();
-    let mut ok = 0u32;
-    let mut failed: Vec<(String, String)> = Vec::new();
-    for id in &ids {
+pub async fn install(
+    home: &Path,
+    ids: Vec<String>,
+    skip_sysdeps: bool,
+    jobs: Option<usize>,
+) -> Result<()> {
+    let options = dm_core::node::NodeInstallOptions { skip_sysdeps };
+    let ids = dm_core::node::hub::expand_node_ids(home, &ids)?;
+    if let [id] = ids.as_slice() {
         println!("{} Installing node {}...", "→".cyan(), id.bold());
-        match dm_core::node::install_node(home, id).await {
-            Ok(entry) => {
-                println!(
-                    "{} Installed {} ({})",
-                    "✅".green(),
-                    entry.id.bold(),
-                    entry.version.green()
-                );
-                println!("  Path: {}", entry.path.display().to_string().dimmed());
-                ok += 1;
-            }
+        let entry = match dm_core::node::install_node_with_options(home, id, options, None).await {
+            Ok(entry) => entry,
             Err(e) => {
                 println!("{} Failed to install {}: {}", "❌".red(), id.bold(), e);
-                failed.push((id.clone(), format!("{}", e)));
+                bail!("1 node(s) failed to install");
+            }
+        };
+        println!(
+            "{} Installed {} ({})",
+            "✅".green(),
+            entry.id.bold(),
+            entry.version.green()
+        );
+        println!("  Path: {}", entry.path.display().to_string().dimmed());
+        return Ok(());
+    }
+
+    println!(
+        "{} Installing {} nodes: {}...",
+        "→".cyan(),
+        ids.len(),
+        ids.join(", ").bold()
+    );
+    let outcomes = dm_core::node::install_nodes(home, &ids, options, jobs).await?;
+    let mut failed = 0;
+    for outcome in &outcomes {
+        match (&outcome.version, &outcome.error) {
+            (Some(version), _) => println!(
+                "{} Installed {} ({})",
+                "✅".green(),
+                outcome.node_id.bold(),
+                version.green()
+            ),
+            (None, error) => {
+                failed += 1;
+                println!(
+                    "{} Failed to install {}: {}",
+                    "❌".red(),
+                    outcome.node_id.bold(),
+                    error.as_deref().unwrap_or("unknown error")
+                );
             }
         }
     }
-    if total > 1 {
-        println!();
-        println!("Done: {}/{} succeeded.", ok, total);
+    println!();
+    println!(
+        "Done: {}/{} succeeded.",
+        outcomes.len() - failed,
+        outcomes.len()
+    );
+    if failed > 0 {
+        bail!("{} node(s) failed to install", failed);
     }
-    if !failed.is_empty() {
-        bail!("{} node(s) failed to install", failed.len());
+    Ok(())
+}
+
+/// Install the nodes a dataflow uses that are not installed yet.
+pub async fn install_missing(home: &Path, yaml: &str) -> Result<()> {
+    let outcomes = dm_core::runs::install_missing_nodes(home, yaml).await?;
+    for outcome in &outcomes {
+        match (&outcome.version, &outcome.error) {
+            (Some(version), _) => println!(
+                "{} Installed missing node {} ({})",
+                "✅".green(),
+                outcome.node_id.bold(),
+                version.green()
+            ),
+            (None, error) => println!(
+                "{} Could not install missing node {}: {}",
+                "⚠".yellow(),
+                outcome.node_id.bold(),
+                error.as_deref().unwrap_or("unknown error")
+            ),
+        }
     }
     Ok(())
 }
 
+pub fn pack(home: &Path, id: &str, output: Option<String>) -> Result<()> {
+    let out = output.unwrap_or_else(|| format!("{}.{}", id, dm_core::node::NODE_ARCHIVE_EXTENSION));
+    let archive = dm_core::node::pack_node(home, id, Path::new(&out))?;
+    let size = std::fs::metadata(&archive).map(|m| m.len()).unwrap_or(0);
+    println!(
+        "{} Packed {} into {} ({})",
+        "✅".green(),
+        id.bold(),
+        archive.display().to_string().bold(),
+        dm_core::util::human_size(size).dimmed()
+    );
+    println!(
+        "  Import elsewhere with: dm node import {}",
+        archive.display()
+    );
+    Ok(())
+}
+
 pub fn list(home: &Path) -> Result<()> {
     let nodes = dm_core::node::list_nodes(home).context("Failed to list installed nodes")?;
 
@@ -70,12 +143,17 @@ pub fn list(home: &Path) -> Result<()> {
             } else {
                 format!(" [{}]", node.display.category)
             };
+            let pinned = match &node.pinned {
+                Some(version) => format!(" 📌 pinned {}", version),
+                None => "".to_string(),
+            };
             println!(
-                "  {} {}{}{} {}",
+                "  {} {}{}{}{} {}",
                 status,
                 name.bold(),
                 version.dimmed(),
                 category.dimmed(),
+                pinned.cyan(),
                 if installed { "" } else { "(not installed)" }.yellow()
             );
             if !node.description.is_empty() {
@@ -86,6 +164,165 @@ pub fn list(home: &Path) -> Result<()> {
     Ok(())
 }
 
+pub async fn search(home: &Path, query: &str, offline: bool) -> Result<()> {
+    let listing = if offline {
+        dm_core::node::hub::registry_listing(home)
+    } else {
+        dm_core::node::hub::registry_listing_online(home).await
+    };
+    let source = listing.source.as_str();
+    let listing = listing.search(query);
+
+    if listing.nodes.is_empty() {
+        println!(
+            "{} No registry nodes match '{}' (registry: {}).",
+            "ℹ".cyan(),
+            query,
+            source
+        );
+        return Ok(());
+    }
+
+    println!(
+        "🔎 Registry nodes ({}) {}",
+        listing.nodes.len(),
+        format!("[registry: {}]", source).dimmed()
+    );
+    println!();
+    for node in &listing.nodes {
+        let installed =
+            dm_core::node::resolve_dm_json_path(home, &node.id).is_some_and(|path| path.exists());
+        let version = if node.version.is_empty() {
+            String::new()
+        } else {
+            format!(" v{}", node.version)
+        };
+        let category = if node.category.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", node.category)
+        };
+        println!(
+            "  {} {}{}{}",
+            if installed { "✅" } else { "⬇" },
+            node.id.bold(),
+            version.dimmed(),
+            category.dimmed()
+        );
+        if !node.description.is_empty() {
+            println!("    {}", node.description.dimmed());
+        }
+    }
+    Ok(())
+}
+
+pub fn collections(home: &Path) -> Result<()> {
+    let collections = dm_core::node::hub::list_collections(home);
+    if collections.is_empty() {
+        println!("{} No node collections defined.", "ℹ".cyan());
+        return Ok(());
+    }
+    for collection in &collections {
+        let origin = match collection.origin {
+            dm_core::node::hub::CollectionOrigin::Registry => "registry",
+            dm_core::node::hub::CollectionOrigin::Local => "local",
+        };
+        println!(
+            "📦 {} {}",
+            format!("@{}", collection.name).bold(),
+            format!("[{}]", origin).dimmed()
+        );
+        if !collection.description.is_empty() {
+            println!("    {}", collection.description.dimmed());
+        }
+        println!("    {}", collection.nodes.join(", "));
+    }
+    Ok(())
+}
+
+pub async fn outdated(home: &Path) -> Result<()> {
+    let checks = dm_core::node::check_outdated_nodes(home).await?;
+    let stale: Vec<_> = checks.iter().filter(|c| c.outdated).collect();
+    for check in checks.iter().filter(|c| c.error.is_some()) {
+        println!(
+            "{} {}: {}",
+            "⚠".yellow(),
+            check.node_id.bold(),
+            check.error.as_deref().unwrap_or_default()
+        );
+    }
+    if stale.is_empty() {
+        println!(
+            "{} All {} checked node(s) are up to date.",
+            "✅".green(),
+            checks.len()
+        );
+        return Ok(());
+    }
+
+    println!(
+        "{:<24} {:<12} {:<12} {:<10} Note",
+        "Node", "Installed", "Latest", "Index"
+    );
+    println!("{}", "─".repeat(72));
+    for check in &stale {
+        let note = match &check.pinned {
+            Some(version) => format!("pinned to {}", version),
+            None => String::new(),
+        };
+        println!(
+            "{:<24} {:<12} {:<12} {:<10} {}",
+            check.node_id.bold(),
+            check.installed,
+            check.latest.as_deref().unwrap_or("?").green(),
+            check.index,
+            note.dimmed()
+        );
+    }
+    println!();
+    println!("  Upgrade with {}", "dm node update-all".bold());
+    Ok(())
+}
+
+pub async fn update_all(home: &Path) -> Result<()> {
+    let updates = dm_core::node::update_outdated_nodes(home).await?;
+    if updates.is_empty() {
+        println!("{} All nodes are up to date.", "✅".green());
+        return Ok(());
+    }
+    let mut failed = 0usize;
+    for update in &updates {
+        match (&update.to, &update.skipped, &update.error) {
+            (Some(to), _, _) => println!(
+                "{} {} {} → {}",
+                "✅".green(),
+                update.node_id.bold(),
+                update.from,
+                to.green()
+            ),
+            (_, Some(reason), _) => println!(
+                "{} {} skipped: {}",
+                "⏭".dimmed(),
+                update.node_id.bold(),
+                reason
+            ),
+            (_, _, error) => {
+                failed += 1;
+                println!(
+                    "{} {} failed: {}",
+                    "❌".red(),
+                    update.node_id.bold(),
+                    error.as_deref().unwrap_or_default()
+                );
+            }
+        }
+    }
+    if failed > 0 {
+        bail!("{} node(s) failed to update", failed);
+    }
+    Ok(())
+}
+
 pub async fn import(home: &Path, sources: Vec<String>) -> Result<()> {
     let total = sources.len();
     let mut ok = 0u32;
@@ -108,7 +345,14 @@ pub async fn import(home: &Path, sources: Vec<String>) -> Result<()> {
                 .to_string()
         };
 
-        let result = if is_url {
+        let is_archive = source_path.is_file()
+            && source_path.extension().and_then(|ext| ext.to_str())
+                == Some(dm_core::node::NODE_ARCHIVE_EXTENSION);
+
+        let result = if is_archive {
+            println!("{} Unpacking {}...", "→".cyan(), source.bold());
+            dm_core::node::import_archive(home, source_path, None)
+        } else if is_url {
             println!(
                 "{} Importing {} from git...",
                 "→".cyan(),
@@ -161,14 +405,34 @@ pub async fn import(home: &Path, sources: Vec<String>) -> Result<()> {
     Ok(())
 }
 
-pub fn uninstall(home: &Path, ids: Vec<String>) -> Result<()> {
+pub fn uninstall(home: &Path, ids: Vec<String>, tag_dataflows: bool) -> Result<()> {
     let total = ids.len();
     let mut ok = 0u32;
     let mut failed: Vec<(String, String)> = Vec::new();
     for id in &ids {
-        match dm_core::node::uninstall_node(home, id) {
-            Ok(()) => {
+        match dm_core::node::uninstall_node_with(home, id, tag_dataflows) {
+            Ok(report) => {
                 println!("{} Node {} removed.", "✅".green(), id.bold());
+                if !report.referencing_dataflows.is_empty() {
+                    println!(
+                        "   {} Still referenced by: {}",
+                        "⚠️".yellow(),
+                        report.referencing_dataflows.join(", ")
+                    );
+                    if report.tagged_dataflows.is_empty() && !tag_dataflows {
+                        println!(
+                            "   {}",
+                            "Re-run with --tag-dataflows to mark them, or reinstall the node."
+                                .dimmed()
+                        );
+                    } else if !report.tagged_dataflows.is_empty() {
+                        println!(
+                            "   Tagged {} as missing-node:{}",
+                            report.tagged_dataflows.join(", "),
+                            id
+                        );
+                    }
+                }
                 ok += 1;
             }
             Err(e) => {
@@ -186,3 +450,114 @@ pub fn uninstall(home: &Path, ids: Vec<String>) -> Result<()> {
     }
     Ok(())
 }
+
+/// Read a JSON, or YAML (`.yml` / `.yaml`), document; `what` names it in errors.
+fn read_json_or_yaml(file: &str, what: &str) -> Result<serde_json::Value> {
+    let content = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read {} file '{}'", what, file))?;
+    if file.ends_with(".yml") || file.ends_with(".yaml") {
+        serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse YAML {} '{}'", what, file))
+    } else {
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse JSON {} '{}'", what, file))
+    }
+}
+
+pub fn set_schema(home: &Path, id: &str, file: &str) -> Result<()> {
+    let schema = read_json_or_yaml(file, "schema")?;
+
+    let stored = dm_core::node::set_config_schema(home, id, schema)?;
+    let fields = stored.as_object().map(|fields| fields.len()).unwrap_or(0);
+    println!(
+        "{} Config schema for {} updated ({} field(s)).",
+        "✅".green(),
+        id.bold(),
+        fields
+    );
+    Ok(())
+}
+
+pub fn validate_config(home: &Path, id: &str, file: Option<&str>) -> Result<()> {
+    let (config, source) = match file {
+        Some(file) => (read_json_or_yaml(file, "config")?, file.to_string()),
+        None => (
+            dm_core::node::get_node_config(home, id)?,
+            "saved config.json".to_string(),
+        ),
+    };
+    let validation = dm_core::node::validate_node_config(home, id, &config)?;
+    if validation.valid {
+        println!(
+            "{} {} matches the config schema of {}.",
+            "✅".green(),
+            source,
+            id.bold()
+        );
+        return Ok(());
+    }
+    println!(
+        "{} {} does not match the config schema of {}:",
+        "❌".red(),
+        source,
+        id.bold()
+    );
+    for error in &validation.errors {
+        println!("  {} {}", "•".red(), error);
+    }
+    bail!("{} config error(s)", validation.errors.len())
+}
+
+pub fn create(home: &Path, id: &str, scaffold: &dm_core::node::NodeScaffold) -> Result<()> {
+    let node = dm_core::node::create_node_with(home, id, scaffold)?;
+    println!(
+        "{} Created node {} at {}",
+        "✅".green(),
+        node.id.bold(),
+        node.path.display()
+    );
+    Ok(())
+}
+
+pub fn pin(home: &Path, id: &str, version: &str) -> Result<()> {
+    let node = dm_core::node::pin_node(home, id, version)?;
+    println!(
+        "{} Pinned {} to {}. Upgrades will skip it until `dm node unpin {}`.",
+        "📌".cyan(),
+        node.id.bold(),
+        node.pinned.unwrap_or_default().green(),
+        node.id
+    );
+    Ok(())
+}
+
+pub async fn test(home: &Path, id: &str, timeout: u64) -> Result<()> {
+    println!("{} Starting {} outside dora...", "→".cyan(), id.bold());
+    let probe = dm_core::node::test_node(home, id, std::time::Duration::from_secs(timeout)).await?;
+    if probe.ok {
+        println!(
+            "{} {} starts: {}",
+            "✅".green(),
+            id.bold(),
+            probe.detail.dimmed()
+        );
+        return Ok(());
+    }
+    println!(
+        "{} {} is broken: {}",
+        "❌".red(),
+        id.bold(),
+        probe.detail.red()
+    );
+    println!(
+        "  Reinstall it with `dm node install {}`, or run `dm doctor` for details.",
+        id
+    );
+    bail!("node test failed")
+}
+
+pub fn unpin(home: &Path, id: &str) -> Result<()> {
+    let node = dm_core::node::unpin_node(home, id)?;
+    println!("{} Unpinned {}.", "✅".green(), node.id.bold());
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+
+use dm_core::plugins::{self, PluginOrigin};
+
+pub fn list(home: &Path) -> Result<()> {
+    let plugins = plugins::list_plugins(home)?;
+    if plugins.is_empty() {
+        println!(
+            "No plugins found. Put a `{}<name>` executable on PATH or add it under [plugins] in config.toml.",
+            plugins::PLUGIN_PREFIX
+        );
+        return Ok(());
+    }
+    for plugin in plugins {
+        let origin = match plugin.origin {
+            PluginOrigin::Config => "(config)",
+            PluginOrigin::Path => "(PATH)",
+        };
+        println!(
+            "{} -> {} {}",
+            plugin.name.bold(),
+            plugin.path.display(),
+            origin.dimmed()
+        );
+    }
+    Ok(())
+}
+
+/// Run `dm <name> args...` as a plugin and return its exit code.
+pub fn run(home: &Path, mut args: Vec<String>) -> Result<i32> {
+    if args.is_empty() {
+        bail!("No command given");
+    }
+    let name = args.remove(0);
+    match plugins::find_plugin(home, &name)? {
+        Some(plugin) => plugins::run_plugin(home, &plugin, &args),
+        None => bail!(
+            "Unknown command '{}'. Run `dm --help` for built-in commands or `dm plugins` for installed plugins.",
+            name
+        ),
+    }
+}
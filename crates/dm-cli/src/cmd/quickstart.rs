@@ -0,0 +1,156 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use colored::Colorize;
+use dora_node_api::{DoraNode, Event};
+
+use dm_core::runs::{RunIsolation, RunSource, RunStatus, StartConflictStrategy};
+use dm_core::types::SetupPhase;
+
+/// Ticks the hello-world printer waits for before it exits.
+const HELLO_TICKS: u32 = 3;
+/// Upper bound for the hello-world run, including node start-up.
+const HELLO_TIMEOUT: Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// `dm quickstart`: setup → dora → runtime → hello-world, stopping at the
+/// first step that fails.
+pub async fn quickstart(home: &Path, verbose: bool) -> Result<()> {
+    let started = Instant::now();
+    crate::display::print_header("Dora Manager — Quickstart");
+
+    step(1, "Checking prerequisites");
+    let report = dm_core::setup(home, verbose, None).await?;
+    if !report.ok(SetupPhase::Python) {
+        bail!("Python 3.11+ is required. Install it and re-run `dm quickstart`.");
+    }
+    println!(
+        "  {} Python found, uv {}",
+        "✅".green(),
+        if report.ok(SetupPhase::Uv) {
+            "ready"
+        } else {
+            "missing (Python nodes will be slower to install)"
+        }
+    );
+
+    step(2, "Installing dora");
+    let dora_ready = report.ok(SetupPhase::Dora);
+    let Some(version) = report.dora_version.filter(|_| dora_ready) else {
+        bail!("Could not install dora. Run `dm install --verbose` to see why.");
+    };
+    println!("  {} dora {} active", "✅".green(), version.bold());
+
+    step(3, "Starting the dora runtime");
+    dm_core::ensure_runtime_up(home, verbose).await?;
+    println!("  {} coordinator + daemon running", "✅".green());
+
+    step(4, "Running the hello-world dataflow");
+    let file = write_hello_dataflow(home)?;
+    let result = dm_core::runs::start_run_from_file_with_isolation(
+        home,
+        &file,
+        None,
+        RunSource::Cli,
+        StartConflictStrategy::AllowMultiple,
+        RunIsolation::Shared,
+    )
+    .await?;
+    let run_id = result.run.run_id;
+    println!("  {} run {} started", "→".cyan(), run_id.dimmed());
+    let run = wait_for_run(home, &run_id).await?;
+    if run.status != RunStatus::Succeeded {
+        bail!(
+            "hello-world run {} ended as {}: {}. Inspect it with `dm runs logs {} printer`.",
+            run_id,
+            run.status.as_str(),
+            run.failure_message.as_deref().unwrap_or("no details"),
+            run_id
+        );
+    }
+    let log = dm_core::runs::read_run_log(home, &run_id, "printer").unwrap_or_default();
+    let ticks = log.lines().filter(|line| line.contains("tick #")).count();
+    println!("  {} printer received {} ticks", "✅".green(), ticks);
+
+    println!();
+    println!(
+        "  {} dora {} is working end-to-end ({:.1}s).",
+        "🎉".green(),
+        version,
+        started.elapsed().as_secs_f64()
+    );
+    println!("  Next steps:");
+    println!("    dm node list             browse installable nodes");
+    println!("    dm start <dataflow.yml>  run your own dataflow");
+    println!("    dm-server                open the web UI at http://127.0.0.1:3210");
+    Ok(())
+}
+
+fn step(n: u32, title: &str) {
+    println!("\n{} {}", format!("[{}/4]", n).cyan(), title.bold());
+}
+
+/// Timer → printer, where the printer is this `dm` binary acting as a node.
+fn write_hello_dataflow(home: &Path) -> Result<std::path::PathBuf> {
+    let dir = home.join("quickstart");
+    std::fs::create_dir_all(&dir)?;
+    let dm = std::env::current_exe().context("Failed to locate the dm executable")?;
+    let yaml = serde_yaml::to_string(&serde_json::json!({
+        "nodes": [{
+            "id": "printer",
+            "path": dm,
+            "args": format!("hello-printer --ticks {}", HELLO_TICKS),
+            "inputs": { "tick": "dora/timer/millis/500" },
+        }]
+    }))?;
+    let file = dir.join("hello-world.yml");
+    std::fs::write(&file, yaml)?;
+    Ok(file)
+}
+
+async fn wait_for_run(home: &Path, run_id: &str) -> Result<dm_core::runs::RunInstance> {
+    let deadline = Instant::now() + HELLO_TIMEOUT;
+    loop {
+        let refreshed = dm_core::runs::refresh_run_statuses(home)?
+            .into_iter()
+            .find(|run| run.run_id == run_id);
+        let run = match refreshed {
+            Some(run) => run,
+            None => dm_core::runs::load_run(home, run_id)?,
+        };
+        if !run.status.is_running() {
+            return Ok(run);
+        }
+        if Instant::now() >= deadline {
+            let _ = dm_core::runs::stop_run(home, run_id).await;
+            bail!(
+                "hello-world run {} did not finish within {}s",
+                run_id,
+                HELLO_TIMEOUT.as_secs()
+            );
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// The quickstart's printer node: print each input, exit after `ticks`.
+pub fn hello_printer(ticks: u32) -> Result<()> {
+    let (_node, mut events) =
+        DoraNode::init_from_env().map_err(|e| anyhow::anyhow!("Failed to init node: {e}"))?;
+    let mut received = 0;
+    while let Some(event) = events.recv() {
+        match event {
+            Event::Input { id, .. } => {
+                received += 1;
+                println!("hello from dora: {} tick #{}", id, received);
+                if received >= ticks {
+                    break;
+                }
+            }
+            Event::Stop(_) => break,
+            _ => {}
+        }
+    }
+    Ok(())
+}
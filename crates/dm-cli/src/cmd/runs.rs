@@ -4,6 +4,34 @@ use std::path::Path;
 use anyhow::Result;
 use colored::Colorize;
 
+/// `dm ps`: print the running dataflows once, or with `watch` redraw them
+/// every that many seconds until interrupted.
+pub async fn ps(home: &Path, verbose: bool, watch: Option<u64>) -> Result<()> {
+    let Some(interval) = watch else {
+        crate::display::print_processes(&dm_core::ps(home, verbose).await?);
+        return Ok(());
+    };
+
+    let interval = std::time::Duration::from_secs(interval.max(1));
+    loop {
+        let processes = dm_core::ps(home, verbose).await;
+        // Clear the screen and home the cursor before redrawing.
+        print!("\x1b[2J\x1b[H");
+        println!(
+            "{} every {}s, Ctrl-C to quit  {}",
+            "dm ps".bold(),
+            interval.as_secs(),
+            chrono::Local::now().format("%H:%M:%S").to_string().dimmed()
+        );
+        match processes {
+            Ok(processes) => crate::display::print_processes(&processes),
+            Err(e) => println!("  {} {}", "●".red(), e),
+        }
+        std::io::stdout().flush()?;
+        tokio::time::sleep(interval).await;
+    }
+}
+
 pub async fn list(home: &Path) -> Result<()> {
     let result = dm_core::runs::list_runs(home, 20, 0)?;
     if result.runs.is_empty() {
@@ -53,6 +81,80 @@ pub async fn stop(home: &Path, run_id: String) -> Result<()> {
     Ok(())
 }
 
+/// `dm start --tag <tag>`: start every saved dataflow with the tag.
+pub async fn start_tagged(
+    home: &Path,
+    verbose: bool,
+    tag: &str,
+    force: bool,
+    isolation: dm_core::runs::RunIsolation,
+) -> Result<()> {
+    if isolation == dm_core::runs::RunIsolation::Shared {
+        dm_core::ensure_runtime_up(home, verbose).await?;
+    }
+    let strategy = if force {
+        dm_core::runs::StartConflictStrategy::StopAndRestart
+    } else {
+        dm_core::runs::StartConflictStrategy::Fail
+    };
+    println!(
+        "{} Starting dataflows tagged {}...",
+        "🚀".green(),
+        tag.bold()
+    );
+    let report = dm_core::runs::start_tagged(
+        home,
+        tag,
+        dm_core::runs::RunSource::Cli,
+        strategy,
+        isolation,
+    )
+    .await?;
+    print_group_report(&report, "started")
+}
+
+/// `dm stop --tag <tag>`: stop the runs of every saved dataflow with the tag.
+pub async fn stop_tagged(home: &Path, tag: &str) -> Result<()> {
+    let report = dm_core::runs::stop_tagged(home, tag).await?;
+    print_group_report(&report, "stopped")
+}
+
+fn print_group_report(report: &dm_core::runs::GroupRunReport, verb: &str) -> Result<()> {
+    for entry in &report.results {
+        if entry.ok {
+            let detail = if entry.run_ids.is_empty() {
+                entry.message.clone()
+            } else {
+                entry.run_ids.join(", ")
+            };
+            println!(
+                "  {} {:<24} {}",
+                "✅".green(),
+                entry.dataflow.bold(),
+                detail.dimmed()
+            );
+        } else {
+            println!(
+                "  {} {:<24} {}",
+                "❌".red(),
+                entry.dataflow.bold(),
+                entry.message.red()
+            );
+        }
+    }
+    println!(
+        "\n{} of {} dataflow(s) tagged {} {}.",
+        report.succeeded,
+        report.results.len(),
+        report.tag.bold(),
+        verb
+    );
+    if report.failed > 0 {
+        anyhow::bail!("{} dataflow(s) failed", report.failed);
+    }
+    Ok(())
+}
+
 pub fn delete(home: &Path, run_ids: Vec<String>) -> Result<()> {
     let total = run_ids.len();
     let mut deleted = 0usize;
@@ -132,6 +234,25 @@ pub async fn logs(
     Ok(())
 }
 
+pub fn import_events(home: &Path, run_id: &str) -> Result<()> {
+    let report = dm_core::runs::import_run_events(home, run_id)?;
+    println!(
+        "{} Imported {} log event(s) for run {}",
+        "✅".green(),
+        report.imported,
+        run_id.bold()
+    );
+    for (node, count) in &report.nodes {
+        let note = if report.truncated.contains(node) {
+            " (oldest lines dropped)"
+        } else {
+            ""
+        };
+        println!("  {:<24} {}{}", node, count, note.dimmed());
+    }
+    Ok(())
+}
+
 pub fn clean(home: &Path, keep: usize) -> Result<()> {
     let deleted = dm_core::runs::clean_runs(home, keep)?;
     println!(
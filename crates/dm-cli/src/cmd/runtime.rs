@@ -0,0 +1,50 @@
+use std::path::Path;
+
+use anyhow::Result;
+use colored::Colorize;
+use dm_core::{RuntimeSupervisor, SupervisorAction, SupervisorOptions};
+
+/// `dm up --supervise`: keep the runtime up until interrupted, reporting
+/// every restart.
+pub async fn supervise(home: &Path, verbose: bool, options: SupervisorOptions) -> Result<()> {
+    println!(
+        "{} Supervising dora coordinator + daemon (checking every {}s, Ctrl-C to stop)...",
+        "→".cyan(),
+        options.interval.as_secs()
+    );
+    let mut supervisor = RuntimeSupervisor::new(home, verbose, options);
+    let mut healthy = false;
+    loop {
+        match supervisor.check().await? {
+            SupervisorAction::Healthy => {
+                if !healthy {
+                    println!("{} Dora runtime is up.", "✅".green());
+                }
+                healthy = true;
+            }
+            SupervisorAction::Restarted { attempt } => {
+                println!(
+                    "{} Dora runtime was down; restarted (attempt {}).",
+                    "⟳".yellow(),
+                    attempt
+                );
+                healthy = true;
+            }
+            SupervisorAction::RestartFailed {
+                attempt,
+                message,
+                retry_in,
+            } => {
+                println!(
+                    "{} Restart attempt {} failed: {}. Retrying in {}s.",
+                    "❌".red(),
+                    attempt,
+                    message,
+                    retry_in.as_secs()
+                );
+                healthy = false;
+            }
+        }
+        tokio::time::sleep(supervisor.next_check_in()).await;
+    }
+}
@@ -0,0 +1,67 @@
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{bail, Result};
+use colored::Colorize;
+use dm_core::secrets::SecretStore;
+
+/// Store a secret; the value is read from stdin when not given, so it
+/// stays out of shell history.
+pub fn set(home: &Path, name: &str, value: Option<String>) -> Result<()> {
+    let value = match value {
+        Some(value) => value,
+        None => {
+            let mut buf = String::new();
+            std::io::stdin().read_to_string(&mut buf)?;
+            buf.trim_end_matches(['\r', '\n']).to_string()
+        }
+    };
+    SecretStore::open(home)?.set(name, &value)?;
+    println!(
+        "{} Stored secret {} (reference it as {})",
+        "✅".green(),
+        name.bold(),
+        format!("secret:{}", name).cyan()
+    );
+    Ok(())
+}
+
+pub fn get(home: &Path, name: &str) -> Result<()> {
+    match SecretStore::open(home)?.get(name)? {
+        Some(value) => println!("{}", value),
+        None => bail!("No secret named '{}'", name),
+    }
+    Ok(())
+}
+
+pub fn remove(home: &Path, name: &str) -> Result<()> {
+    if !SecretStore::open(home)?.remove(name)? {
+        bail!("No secret named '{}'", name);
+    }
+    println!("{} Removed secret {}", "✅".green(), name.bold());
+    Ok(())
+}
+
+pub fn list(home: &Path) -> Result<()> {
+    let names = SecretStore::open(home)?.names()?;
+    if names.is_empty() {
+        println!("No secrets stored.");
+    }
+    for name in names {
+        println!("{}", name);
+    }
+    Ok(())
+}
+
+pub fn encrypt(home: &Path) -> Result<()> {
+    let converted = dm_core::encryption::encrypt_home(home)?;
+    for name in &converted {
+        println!("  {} {}", "🔒".green(), name);
+    }
+    println!(
+        "{} Home encrypted. Keep {} set (or the key in your keychain) to use dm.",
+        "✅".green(),
+        dm_core::encryption::KEY_ENV.bold()
+    );
+    Ok(())
+}
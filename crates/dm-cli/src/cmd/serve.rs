@@ -0,0 +1,23 @@
+use std::path::Path;
+
+use anyhow::Result;
+use colored::Colorize;
+
+/// `dm serve`: run dm-server's router (API, events, web UI and background
+/// services) in this process.
+pub async fn serve(home: &Path, addr: &str) -> Result<()> {
+    let mut config = dm_server::ServeConfig::new(home).addr(addr);
+    // Bridge nodes spawned by the server run this very binary.
+    if std::env::var_os(dm_core::util::DM_CLI_BIN_ENV_KEY).is_none() {
+        if let Ok(exe) = std::env::current_exe() {
+            config = config.dm_cli(exe);
+        }
+    }
+
+    println!(
+        "{} dm serve listening on {}",
+        "🚀".green(),
+        format!("http://{}", addr).bold()
+    );
+    dm_server::serve(config).await
+}
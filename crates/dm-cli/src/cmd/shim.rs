@@ -0,0 +1,65 @@
+use std::path::Path;
+
+use anyhow::Result;
+use colored::Colorize;
+
+use dm_core::types::ShimStatus;
+
+pub fn install(home: &Path) -> Result<()> {
+    let status = dm_core::shim::install_shim(home)?;
+    println!(
+        "{} Installed dora shim at {}",
+        "✅".green(),
+        status.path.bold()
+    );
+    if status.target.is_none() {
+        println!(
+            "{} No active dora version yet; run `dm install` before using it.",
+            "⚠".yellow()
+        );
+    }
+    if !status.on_path {
+        print_path_hint(&status);
+    }
+    Ok(())
+}
+
+pub fn remove(home: &Path) -> Result<()> {
+    if dm_core::shim::remove_shim(home)? {
+        println!("{} Removed dora shim", "✅".green());
+    } else {
+        println!("No dora shim installed");
+    }
+    Ok(())
+}
+
+pub fn status(home: &Path) {
+    let status = dm_core::shim::shim_status(home);
+    if !status.installed {
+        println!("No dora shim installed. Run `dm shim install`.");
+        return;
+    }
+    println!("shim:    {}", status.path);
+    println!(
+        "runs:    {}",
+        status.target.as_deref().unwrap_or("(no active version)")
+    );
+    println!("on PATH: {}", if status.on_path { "yes" } else { "no" });
+    if !status.on_path {
+        print_path_hint(&status);
+    }
+}
+
+fn print_path_hint(status: &ShimStatus) {
+    println!();
+    if cfg!(windows) {
+        println!("Add {} to your PATH:", status.dir);
+        println!("  setx PATH \"{};%PATH%\"", status.dir);
+    } else {
+        println!(
+            "Add {} to your PATH, e.g. in ~/.bashrc or ~/.zshrc:",
+            status.dir
+        );
+        println!("  export PATH=\"{}:$PATH\"", status.dir);
+    }
+}
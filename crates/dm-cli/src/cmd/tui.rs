@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::Result;
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+
+use dm_core::events::{in_flight_operations, EventStore, OperationProgress};
+
+/// How often the event store is polled.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Operations silent for longer than this are no longer shown.
+const STALE_AFTER: Duration = Duration::from_secs(60);
+
+/// Show one live bar per in-flight operation (installs, builds, downloads)
+/// from the `<activity>.progress` events, until interrupted.
+pub async fn tui(home: &Path) -> Result<()> {
+    let store = EventStore::open(home)?;
+    let bars = MultiProgress::new();
+    let idle = bars.add(ProgressBar::new_spinner());
+    idle.enable_steady_tick(Duration::from_millis(120));
+    let mut shown: BTreeMap<String, ProgressBar> = BTreeMap::new();
+
+    loop {
+        let since = chrono::Utc::now() - chrono::Duration::from_std(STALE_AFTER)?;
+        let operations = in_flight_operations(&store, &since.to_rfc3339())?;
+
+        shown.retain(|case_id, bar| {
+            let active = operations.iter().any(|op| &op.case_id == case_id);
+            if !active {
+                bar.finish_and_clear();
+                bars.remove(bar);
+            }
+            active
+        });
+        for op in &operations {
+            let bar = shown
+                .entry(op.case_id.clone())
+                .or_insert_with(|| bars.insert_before(&idle, ProgressBar::new(100)));
+            render(bar, op);
+        }
+        idle.set_message(match operations.len() {
+            0 => "Waiting for operations... (Ctrl-C to quit)".to_string(),
+            n => format!("{} operation(s) in flight (Ctrl-C to quit)", n),
+        });
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+fn render(bar: &ProgressBar, op: &OperationProgress) {
+    let template = match op.pct {
+        Some(_) => "{prefix:.bold} [{bar:30.cyan/dim}] {pos:>3}% {msg}",
+        None => "{prefix:.bold} {spinner:.cyan} {msg}",
+    };
+    bar.set_style(
+        ProgressStyle::default_bar()
+            .template(template)
+            .unwrap()
+            .progress_chars("█▓░"),
+    );
+    if op.pct.is_none() {
+        bar.enable_steady_tick(Duration::from_millis(120));
+    }
+    bar.set_prefix(format!("{} {}", op.activity, op.phase));
+    bar.set_position(op.pct.unwrap_or_default().into());
+    bar.set_message(op.message.clone());
+}
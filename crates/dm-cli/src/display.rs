@@ -21,6 +21,22 @@ pub fn print_env_item(item: &EnvItem) {
 }
 
 /// Print the full doctor report
+pub fn print_permission_fixes(fixed: &[PermissionIssue]) {
+    if fixed.is_empty() {
+        println!("  {} File permissions already restricted.", "✅".green());
+        return;
+    }
+    for issue in fixed {
+        println!(
+            "  {} {} {} → {}",
+            "🔒".green(),
+            issue.path,
+            issue.mode.dimmed(),
+            issue.expected
+        );
+    }
+}
+
 pub fn print_doctor_report(report: &DoctorReport) {
     print_header("Dora Manager — Environment Check");
 
@@ -39,7 +55,22 @@ pub fn print_doctor_report(report: &DoctorReport) {
     } else {
         for v in &report.installed_versions {
             let marker = if v.active { " ← active" } else { "" };
-            println!("  ✅  {}{}", v.version, marker.green());
+            let provenance = match &v.manifest {
+                Some(m) => {
+                    let method = format!("{:?}", m.method).to_lowercase();
+                    match &m.asset_digest {
+                        Some(digest) => format!(" ({}, {})", method, short_digest(digest)),
+                        None => format!(" ({})", method),
+                    }
+                }
+                None => String::new(),
+            };
+            println!(
+                "  ✅  {}{}{}",
+                v.version,
+                marker.green(),
+                provenance.dimmed()
+            );
         }
     }
 
@@ -52,6 +83,89 @@ pub fn print_doctor_report(report: &DoctorReport) {
         println!("\n  {} Active: {} ({})", "→".cyan(), ver.bold(), status);
     }
 
+    if !report.permission_issues.is_empty() {
+        print_header("Permissions");
+        for issue in &report.permission_issues {
+            println!(
+                "  ⚠️  {} is {} (expected {})",
+                issue.path,
+                issue.mode.yellow(),
+                issue.expected
+            );
+        }
+        println!(
+            "      {} {}",
+            "→".cyan(),
+            "Run `dm doctor --fix` to restrict them to your user.".dimmed()
+        );
+    }
+
+    if !report.node_probes.is_empty() {
+        print_header("Node launch checks");
+        for probe in &report.node_probes {
+            let mark = if probe.ok { "✅" } else { "❌" };
+            let detail = if probe.ok {
+                probe.detail.dimmed()
+            } else {
+                probe.detail.red()
+            };
+            println!("  {}  {:<24} {}", mark, probe.node_id.bold(), detail);
+        }
+    }
+
+    if !report.sdk_mismatches.is_empty() {
+        print_header("Python SDK");
+        for mismatch in &report.sdk_mismatches {
+            println!(
+                "  ⚠️  {:<24} dora-rs {} (expected {})",
+                mismatch.node_id.bold(),
+                mismatch.installed.yellow(),
+                mismatch.expected
+            );
+        }
+        println!(
+            "      {} {}",
+            "→".cyan(),
+            "Reinstall them with `dm node install <id>` to match the active dora.".dimmed()
+        );
+    }
+
+    if !report.nodes.is_empty() {
+        print_header("Node environments");
+        for node in &report.nodes {
+            let mark = if node.ok { "✅" } else { "❌" };
+            println!(
+                "  {}  {:<24} {}",
+                mark,
+                node.node_id.bold(),
+                node.kind.dimmed()
+            );
+            for check in node.checks.iter().filter(|check| !check.ok) {
+                println!(
+                    "      {} {}: {}",
+                    "→".cyan(),
+                    check.name,
+                    check.detail.red()
+                );
+            }
+        }
+    }
+
+    if !report.threshold_alerts.is_empty() {
+        print_header("Thresholds");
+        for alert in &report.threshold_alerts {
+            println!("  ⚠️  {}", alert.message.yellow());
+        }
+    }
+
+    if !report.recent_problems.is_empty() {
+        print_header("Recent problems (24h)");
+        for problem in &report.recent_problems {
+            println!("  ⚠️  {}", problem.summary);
+            println!("      {} {}", "→".cyan(), problem.suggestion.dimmed());
+        }
+    }
+
     println!();
     if report.all_ok {
         println!("  {} Environment is ready.", "✅".green());
@@ -76,13 +190,33 @@ pub fn print_versions_report(report: &VersionsReport) {
         }
     }
 
-    print_header("Available (recent)");
+    print_header(if report.more_available {
+        "Available (recent)"
+    } else {
+        "Available"
+    });
     if report.available.is_empty() {
         println!("  {} Could not fetch releases.", "⚠️".yellow());
     } else {
         for r in &report.available {
             let installed_marker = if r.installed { " (installed)" } else { "" };
-            println!("  • {}{}", r.tag, installed_marker.dimmed());
+            let published = r
+                .published_at
+                .as_deref()
+                .map(|date| format!("  {}", date.get(..10).unwrap_or(date)))
+                .unwrap_or_default();
+            println!(
+                "  • {:<12}{}{}",
+                r.tag,
+                published.dimmed(),
+                installed_marker.dimmed()
+            );
+        }
+        if report.more_available {
+            println!(
+                "  {}",
+                "Older releases not shown; run `dm versions --all`.".dimmed()
+            );
         }
     }
 }
@@ -101,8 +235,22 @@ pub fn print_status_report(report: &StatusReport) {
         }
     }
     println!("  dm home:        {}", report.dm_home.dimmed());
+    if let Some(robot_id) = &report.robot_id {
+        println!("  robot id:       {}", robot_id.bold());
+    }
+    if !report.labels.is_empty() {
+        let labels: Vec<String> = report
+            .labels
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        println!("  labels:         {}", labels.join(", ").dimmed());
+    }
 
     print_header("Runtime");
+    if let Some(addr) = &report.coordinator_addr {
+        println!("  coordinator:    {} {}", addr.bold(), "(remote)".dimmed());
+    }
     if report.runtime_running {
         for line in report.runtime_output.lines() {
             let trimmed = line.trim();
@@ -118,16 +266,47 @@ pub fn print_status_report(report: &StatusReport) {
             "dm up".bold()
         );
     }
+    for process in &report.processes {
+        if process.alive {
+            println!(
+                "  {} {:<12} pid {:<7}  up {}, cpu {:.1}%, mem {}",
+                "●".green(),
+                process.role,
+                process.pid,
+                process
+                    .uptime_secs
+                    .map(|secs| format_uptime(secs as i64))
+                    .unwrap_or_else(|| "-".to_string()),
+                process.cpu_percent.unwrap_or_default(),
+                process
+                    .memory_bytes
+                    .map(format_bytes)
+                    .unwrap_or_else(|| "-".to_string()),
+            );
+        } else {
+            println!(
+                "  {} {:<12} pid {:<7}  {}",
+                "●".red(),
+                process.role,
+                process.pid,
+                "not running".dimmed()
+            );
+        }
+    }
 
     print_header("Active Runs");
     if report.active_runs.is_empty() {
         println!("  (no active runs)");
     } else {
         println!(
-            "  {:<8}  {:<20}  {:<10}  {:<11}  {:<6}  Started",
+            "  {:<8}  {:<20}  {:<10}  {:<11}  {:<6}  Running",
             "Run", "Dataflow", "Status", "Nodes", "Dora"
         );
         for item in &report.active_runs {
+            let running = match item.uptime_secs {
+                Some(secs) => format!("{}, started via {}", format_uptime(secs), item.source),
+                None => format!("since {}", trim_ts(&item.started_at)),
+            };
             println!(
                 "  {:<8}  {:<20}  {:<10}  {:<11}  {:<6}  {}",
                 short_id(&item.run_id).dimmed(),
@@ -139,7 +318,23 @@ pub fn print_status_report(report: &StatusReport) {
                     .map(short_id)
                     .unwrap_or("-")
                     .dimmed(),
-                trim_ts(&item.started_at).dimmed(),
+                running.dimmed(),
+            );
+        }
+    }
+
+    if !report.untracked_dataflows.is_empty() {
+        print_header("Untracked Dataflows");
+        println!("  Running in dora but not started through dm:");
+        for item in &report.untracked_dataflows {
+            println!(
+                "  {:<36}  {:<20}  {}",
+                item.id.dimmed(),
+                item.runtime_name
+                    .as_deref()
+                    .unwrap_or(&item.dataflow_name)
+                    .bold(),
+                item.status.as_str(),
             );
         }
     }
@@ -168,6 +363,11 @@ pub fn print_status_report(report: &StatusReport) {
         }
     }
 
+    if !report.machines.is_empty() {
+        print_header("Machines");
+        print_machines(&report.machines);
+    }
+
     if !report.dora_probe.is_empty() {
         print_header("Dora Probe");
         println!(
@@ -193,25 +393,131 @@ pub fn print_status_report(report: &StatusReport) {
     println!();
 }
 
+/// Print the running dataflows of `dm ps`, each followed by its nodes
+pub fn print_processes(processes: &[DataflowProcess]) {
+    if processes.is_empty() {
+        println!("  (no running dataflows)");
+        return;
+    }
+    println!(
+        "  {:<36}  {:<20}  {:<8}  {:<7}  {:<7}  Memory",
+        "UUID", "Dataflow", "Run", "Nodes", "CPU"
+    );
+    for process in processes {
+        let item = &process.dataflow;
+        println!(
+            "  {:<36}  {:<20}  {:<8}  {:<7}  {:<7}  {}",
+            item.id.dimmed(),
+            item.dataflow_name.bold(),
+            item.run_id.as_deref().map(short_id).unwrap_or("-"),
+            item.observed_nodes,
+            item.cpu.as_deref().unwrap_or("-"),
+            item.memory.as_deref().unwrap_or("-"),
+        );
+        for node in &process.nodes {
+            println!(
+                "    {:<34}  {:<20}  {:<8}  {:<7}  {:<7}  {}",
+                format!("└ {}", node.id),
+                node.status.as_str().dimmed(),
+                node.pid.as_deref().unwrap_or("-"),
+                "",
+                node.cpu.as_deref().unwrap_or("-"),
+                node.memory.as_deref().unwrap_or("-"),
+            );
+        }
+    }
+}
+
+/// Print registered remote daemons with their reachability
+pub fn print_machines(machines: &[MachineStatus]) {
+    for machine in machines {
+        let state = match machine.reachable {
+            Some(true) => format!("{} reachable", "●".green()),
+            Some(false) => format!("{} unreachable", "●".red()),
+            None => format!("{} no port to probe", "●".dimmed()),
+        };
+        println!(
+            "  {:<20}  {:<22}  {}",
+            machine.name.bold(),
+            machine.addr,
+            state
+        );
+    }
+}
+
+fn format_uptime(secs: i64) -> String {
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m", s / 60),
+        s if s < 86_400 => format!("{}h {}m", s / 3600, (s % 3600) / 60),
+        s => format!("{}d {}h", s / 86_400, (s % 86_400) / 3600),
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const MIB: u64 = 1024 * 1024;
+    match bytes {
+        b if b < MIB => format!("{} KiB", b / 1024),
+        b if b < 1024 * MIB => format!("{:.1} MiB", b as f64 / MIB as f64),
+        b => format!("{:.2} GiB", b as f64 / (1024 * MIB) as f64),
+    }
+}
+
 fn short_id(id: &str) -> &str {
     id.get(..8).unwrap_or(id)
 }
 
+/// `sha256:0123abcd…` → `sha256:0123abcd`
+fn short_digest(digest: &str) -> &str {
+    digest.get(..15).unwrap_or(digest)
+}
+
 fn trim_ts(ts: &str) -> &str {
     ts.get(..19).unwrap_or(ts)
 }
 
-/// Print setup report
+/// Print setup report: a checklist with one line per phase
 pub fn print_setup_report(report: &SetupReport) {
-    println!();
-    println!("  {} Setup complete! Try:", "🎉".green());
-    println!("    dm status");
-    println!("    dm doctor");
-    println!("    dm -- run dataflow.yml --uv");
-    if let Some(ref ver) = report.dora_version {
-        println!("  Active dora: {}", ver.bold());
+    print_header("Summary");
+    for phase in &report.phases {
+        let (mark, status) = match phase.status {
+            SetupStatus::Found => ("✅", "found".green()),
+            SetupStatus::Installed => ("✅", "installed".green()),
+            SetupStatus::Running => ("…", "running".cyan()),
+            SetupStatus::Skipped => ("⏭️", "skipped".yellow()),
+            SetupStatus::Failed => ("❌", "failed".red()),
+        };
+        let note = match (&phase.error, &phase.detail) {
+            (Some(error), _) => error.yellow(),
+            (None, Some(detail)) => detail.dimmed(),
+            (None, None) => "".normal(),
+        };
+        println!(
+            "  {}  {:<8} {:<10} {} {}",
+            mark,
+            phase.phase.label().bold(),
+            status,
+            note,
+            format!("({:.1}s)", phase.duration_ms as f64 / 1000.0).dimmed()
+        );
     }
+    if !report.ok(SetupPhase::Python) {
+        println!("\n  {} Python 3.11+ is required.", "❌".red());
+        println!("    macOS:   brew install python@3.11");
+        println!("    Linux:   sudo apt install python3.11");
+    }
+
     println!();
+    if report.all_ok() {
+        println!("  {} Setup complete! Try:", "🎉".green());
+        println!("    dm status");
+        println!("    dm doctor");
+        println!("    dm -- run dataflow.yml --uv");
+        if let Some(ref ver) = report.dora_version {
+            println!("  Active dora: {}", ver.bold());
+        }
+        println!();
+    }
 }
 
 /// Print install result
@@ -229,6 +535,19 @@ pub fn print_install_result(result: &InstallResult) {
         result.version.bold(),
         method.dimmed()
     );
+    match (&result.actual_version, result.verified) {
+        (Some(actual), true) if actual != &result.version => println!(
+            "  {} The installed binary reports dora {}.",
+            "⚠".yellow(),
+            actual
+        ),
+        (_, false) => println!(
+            "  {} dora {} does not run on this machine; reinstall it with `dm install --method source`.",
+            "⚠".yellow(),
+            result.version
+        ),
+        _ => {}
+    }
 }
 
 /// Print runtime result (for up/down)
@@ -245,3 +564,64 @@ pub fn print_runtime_result(action: &str, result: &RuntimeResult) {
         }
     }
 }
+
+/// Print troubleshooting guidance from `dm explain <code>`
+pub fn print_explanation(explanation: &Explanation) {
+    print_header(&format!("{} ({})", explanation.title, explanation.code));
+    println!("  {}", explanation.summary);
+
+    if let Some(ref diagnosis) = explanation.diagnosis {
+        println!("\n  {} {}", "🔎".cyan(), diagnosis.yellow());
+    }
+
+    println!();
+    for (i, step) in explanation.steps.iter().enumerate() {
+        println!("  {}. {}", i + 1, step);
+    }
+}
+
+/// Print the codes known to `dm explain`
+pub fn print_migration_report(report: &MigrationReport) {
+    if report.changes.is_empty() {
+        println!("  {} dm home is up to date.", "✅".green());
+        return;
+    }
+
+    let title = if report.dry_run {
+        "Pending migrations (dry run)"
+    } else {
+        "Migrated"
+    };
+    print_header(title);
+    let mut last_path = "";
+    for change in &report.changes {
+        if change.path != last_path {
+            println!("  {}", change.path.bold());
+            last_path = &change.path;
+        }
+        println!("    • {}", change.description);
+    }
+    if let Some(dir) = &report.backup_dir {
+        println!("\n  {} Originals backed up to {}", "→".cyan(), dir.dimmed());
+    }
+    if report.dry_run {
+        println!("\n  {} Run {} to apply.", "→".cyan(), "dm migrate".bold());
+    }
+}
+
+pub fn print_explain_topics(topics: &[ExplainTopic]) {
+    print_header("Known error codes");
+    for topic in topics {
+        println!(
+            "  {:<24} {} {}",
+            topic.code.bold(),
+            topic.title,
+            format!("[{}]", topic.activity).dimmed()
+        );
+    }
+    println!(
+        "\n  {} Pass a code or an activity, e.g. {}",
+        "→".cyan(),
+        "dm explain runtime.up".bold()
+    );
+}
@@ -3,7 +3,7 @@ mod cmd;
 mod display;
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
 use colored::Colorize;
 use futures_util::StreamExt;
 use indicatif::{ProgressBar, ProgressStyle};
@@ -24,9 +24,22 @@ struct Cli {
     #[arg(long, global = true)]
     home: Option<String>,
 
-    /// Verbose output
+    /// Verbose output (default: `verbose` in config.toml)
     #[arg(short, long, global = true)]
     verbose: bool,
+
+    /// Wait up to SECS (default 60) for a resource locked by dm-server or another dm
+    #[arg(long, global = true, value_name = "SECS", num_args = 0..=1, default_missing_value = "60")]
+    wait: Option<u64>,
+
+    /// Take over resource locks even if another live process holds them
+    #[arg(long, global = true)]
+    force_lock: bool,
+
+    /// Serve dora commands from an in-process fake runtime (no dora needed)
+    #[cfg(feature = "mock-runtime")]
+    #[arg(long, global = true)]
+    mock_runtime: bool,
 }
 
 #[derive(Subcommand)]
@@ -34,32 +47,109 @@ enum Commands {
     /// One-click bootstrap: install Python, uv, and dora
     Setup,
 
+    /// First run: setup, install dora, start the runtime and run a
+    /// hello-world dataflow to check everything works end-to-end
+    Quickstart,
+
     /// Check environment health & diagnose issues
-    Doctor,
+    Doctor {
+        /// Skip the "Recent problems" scan of the event history
+        #[arg(long)]
+        no_history: bool,
+        /// Restrict sensitive files other users can read to their owner first
+        #[arg(long)]
+        fix: bool,
+        /// Also launch each installed node briefly to catch missing libraries
+        /// and broken interpreters
+        #[arg(long)]
+        deep: bool,
+    },
+
+    /// Upgrade node metadata, config and dataflows written by older dm versions
+    Migrate {
+        /// Only report what would change
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Troubleshooting steps for an error code or a failed activity
+    Explain {
+        /// Error code (e.g. runtime.timeout) or activity (e.g. runtime.up). Omit to list codes.
+        topic: Option<String>,
+    },
 
     /// Install a dora version (default: latest)
     Install {
-        /// Version to install, e.g. "0.3.9". Omit for latest.
+        /// Version, alias or semver range to install, e.g. "0.3.9", "latest"
+        /// (pre-releases included), "stable" or "^0.3" (newest matching
+        /// release). Omit for the latest stable release.
         version: Option<String>,
+        /// auto, binary or source (default: `install.method` in config.toml)
+        #[arg(long, value_parser = parse_install_method)]
+        method: Option<dm_core::config::InstallMethodPreference>,
+        /// Skip checksum and signature verification of the downloaded binary
+        #[arg(long)]
+        no_verify: bool,
+        /// Install a pre-downloaded release archive (.tar.gz, .tar.xz or
+        /// .zip); checksum and signature files next to it are verified
+        #[arg(long, value_name = "ARCHIVE", conflicts_with_all = ["method", "mirror"])]
+        from_file: Option<std::path::PathBuf>,
+        /// Download release assets from this mirror of https://github.com
+        /// (default: `github_mirror` in config.toml)
+        #[arg(long, value_name = "URL")]
+        mirror: Option<String>,
     },
 
     /// Remove an installed dora version
     Uninstall {
-        /// Version to remove
+        /// Version or alias to remove
         version: String,
     },
 
     /// Switch active dora version
     Use {
-        /// Version to activate
+        /// Version, alias or semver range to activate, e.g. "0.3.9",
+        /// "latest", "stable" or "^0.3" (newest installed match)
         version: String,
     },
 
+    /// Manage version aliases usable wherever a dora version is accepted
+    Alias {
+        #[command(subcommand)]
+        command: AliasCommands,
+    },
+
+    /// Manage the `dora` shim in ~/.dm/bin that runs the active version
+    Shim {
+        #[command(subcommand)]
+        command: ShimCommands,
+    },
+
     /// Show installed & available dora versions
-    Versions,
+    Versions {
+        /// List every dora release, not just the most recent ones
+        #[arg(long)]
+        all: bool,
+    },
 
     /// Start dora coordinator + daemon
-    Up,
+    Up {
+        /// Extra environment for the coordinator and daemon (repeatable)
+        #[arg(long = "env", value_name = "KEY=VAL", value_parser = parse_env)]
+        env: Vec<(String, String)>,
+        /// Inherit dm's environment (overrides `runtime_inherit_env` in config.toml)
+        #[arg(long, value_name = "BOOL")]
+        inherit_env: Option<bool>,
+        /// Stay in the foreground and restart the runtime whenever it dies
+        #[arg(long)]
+        supervise: bool,
+        /// Seconds between health checks when supervising
+        #[arg(long, value_name = "SECS", default_value = "5")]
+        interval: u64,
+        /// Longest delay between failed restart attempts, in seconds
+        #[arg(long, value_name = "SECS", default_value = "60")]
+        max_backoff: u64,
+    },
 
     /// Stop dora coordinator + daemon
     Down,
@@ -67,6 +157,30 @@ enum Commands {
     /// Live overview of runtime & dataflows
     Status,
 
+    /// List the dataflows running in dora and their nodes
+    Ps {
+        /// Keep redrawing the table until interrupted
+        #[arg(long, short)]
+        watch: bool,
+        /// Seconds between refreshes in watch mode
+        #[arg(long, value_name = "SECS", default_value = "2")]
+        interval: u64,
+    },
+
+    /// Serve the HTTP API and web UI, the same as `dm-server`
+    #[cfg(feature = "serve")]
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = dm_server::DEFAULT_ADDR)]
+        addr: String,
+    },
+
+    /// Register remote dora daemons for distributed deployments
+    Machine {
+        #[command(subcommand)]
+        command: MachineCommands,
+    },
+
     /// Manage installed dora nodes
     Node {
         #[command(subcommand)]
@@ -79,13 +193,62 @@ enum Commands {
         command: DataflowCommands,
     },
 
+    /// Check dataflow graphs in editor JSON or dora YAML form
+    Graph {
+        #[command(subcommand)]
+        command: GraphCommands,
+    },
+
     /// Start a dataflow on the running dora runtime
     Start {
-        /// Path to dataflow YAML file
-        file: String,
+        /// Path to dataflow YAML file, URL, or name of a saved dataflow
+        #[arg(required_unless_present = "tag", conflicts_with = "tag")]
+        file: Option<String>,
+        /// Start every saved dataflow tagged TAG instead
+        #[arg(long)]
+        tag: Option<String>,
         /// Stop an active run with the same dataflow name before starting
         #[arg(long)]
         force: bool,
+        /// Start even if a run with the same dataflow name is already active
+        #[arg(long, conflicts_with = "force")]
+        allow_multiple: bool,
+        /// Give the run its own coordinator/daemon on free ports (default: `isolate_runs`)
+        #[arg(long)]
+        isolated: bool,
+        /// Installed dora version or alias for the dedicated runtime (implies --isolated)
+        #[arg(long, value_name = "VERSION")]
+        dora_version: Option<String>,
+        /// Download and install nodes the dataflow uses that are not installed yet
+        #[arg(long, conflicts_with = "tag")]
+        auto_install: bool,
+    },
+
+    /// Stop every running dataflow tagged TAG
+    Stop {
+        #[arg(long)]
+        tag: String,
+    },
+
+    /// Run a dataflow in the foreground with `dora run` (not tracked in run history)
+    Run {
+        /// Path to dataflow YAML file
+        file: String,
+        /// Sync one uv project for all Python nodes instead of per-node venvs
+        #[arg(long)]
+        uv: bool,
+        /// Override a variable declared under `variables:` (repeatable)
+        #[arg(long = "set", value_name = "NAME=VALUE", value_parser = parse_env)]
+        set: Vec<(String, String)>,
+    },
+
+    /// Print the dora YAML a dataflow transpiles to, with the dm home shown as `$DM_HOME`
+    Transpile {
+        /// Path to dataflow YAML file
+        file: String,
+        /// Compare against a golden file and fail if the output differs
+        #[arg(long, value_name = "GOLDEN")]
+        check: Option<String>,
     },
 
     /// View dataflow execution history
@@ -94,6 +257,82 @@ enum Commands {
         command: Option<RunsCommands>,
     },
 
+    /// Show recorded events, newest last; `-f` keeps printing new ones
+    Logs {
+        /// Event source: core, dataflow, server, frontend or ci
+        #[arg(long)]
+        source: Option<String>,
+        /// Case id, e.g. a run id or dataflow name
+        #[arg(long = "case", value_name = "CASE_ID")]
+        case_id: Option<String>,
+        /// Node id within the case
+        #[arg(long)]
+        node: Option<String>,
+        /// trace, debug, info, warn or error
+        #[arg(long)]
+        level: Option<String>,
+        /// RFC 3339 timestamp or a duration back from now (30s, 10m, 2h, 1d)
+        #[arg(long)]
+        since: Option<String>,
+        /// Number of recent events to print before following
+        #[arg(short = 'n', long, default_value_t = 100)]
+        tail: i64,
+        /// Keep printing events as they are written
+        #[arg(short, long)]
+        follow: bool,
+    },
+
+    /// Show audited API calls, newest last: who changed what, and when
+    Audit {
+        /// Action or action prefix, e.g. `node` or `dataflow.save`
+        #[arg(long)]
+        action: Option<String>,
+        /// Token name from `[[ingest.tokens]]`, or `anonymous`
+        #[arg(long)]
+        user: Option<String>,
+        /// Node id, dataflow name, dora version, ...
+        #[arg(long)]
+        target: Option<String>,
+        /// RFC 3339 timestamp or a duration back from now (30s, 10m, 2h, 1d)
+        #[arg(long)]
+        since: Option<String>,
+        /// Number of recent entries to print
+        #[arg(short = 'n', long, default_value_t = 100)]
+        tail: i64,
+    },
+
+    /// Watch in-flight installs, builds and downloads as live progress bars
+    Tui,
+
+    /// Manage secrets referenced from `runtime_env` as `secret:NAME`
+    Secret {
+        #[command(subcommand)]
+        command: SecretCommands,
+    },
+
+    /// Read and change settings in config.toml
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+
+    /// Encrypt events.db and secrets.db with the key from DM_HOME_KEY or the keychain
+    Encrypt,
+
+    /// Print a shell completion script (e.g. `source <(dm completions bash)`)
+    Completions { shell: cmd::complete::Shell },
+
+    /// List completion candidates, from dm-server when it is running
+    #[command(hide = true)]
+    Complete { kind: cmd::complete::CompleteKind },
+
+    /// Printer node of the quickstart hello-world dataflow
+    #[command(hide = true)]
+    HelloPrinter {
+        #[arg(long, default_value = "3")]
+        ticks: u32,
+    },
+
     #[command(hide = true)]
     Bridge {
         /// Run ID to serve bridge for
@@ -101,6 +340,13 @@ enum Commands {
         run_id: String,
     },
 
+    /// List plugins: `dm-<name>` executables on PATH and `[plugins]` in config.toml
+    Plugins,
+
+    /// `dm <name> ...` runs the plugin `<name>`
+    #[command(external_subcommand)]
+    Plugin(Vec<String>),
+
     /// Pass-through: run any dora CLI command with the active version
     #[command(
         name = "--",
@@ -137,6 +383,11 @@ enum RunsCommands {
         #[arg(long)]
         follow: bool,
     },
+    /// Import a finished run's dora logs into the event store
+    ImportEvents {
+        /// Run ID
+        run_id: String,
+    },
     /// Clean old run history
     Clean {
         /// Number of recent runs to keep (default: 10)
@@ -145,38 +396,256 @@ enum RunsCommands {
     },
 }
 
+#[derive(Subcommand)]
+enum AliasCommands {
+    /// Point an alias at a dora version, e.g. `dm alias set demo 0.3.9`
+    Set { name: String, version: String },
+    /// Delete an alias
+    Rm { name: String },
+    /// List built-in and user-defined aliases
+    List,
+}
+
+#[derive(Subcommand)]
+enum MachineCommands {
+    /// Register a daemon by its machine id, e.g. `dm machine add arm 192.168.1.21`
+    Add {
+        name: String,
+        /// IP or IP:PORT the machine is reachable at
+        addr: String,
+    },
+    /// List registered machines and whether they are reachable
+    List,
+}
+
+#[derive(Subcommand)]
+enum ShimCommands {
+    /// Install the shim so `dora` always runs the active version
+    Install,
+    /// Remove the shim
+    Remove,
+    /// Show where the shim is and what it runs
+    Status,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Print a setting by dotted key (e.g. `install.verify`)
+    Get { key: String },
+    /// Set a setting; VALUE is read as TOML (`true`, `5`, `["a"]`) or a plain string
+    Set { key: String, value: String },
+    /// Reset a setting to its default
+    Unset { key: String },
+    /// List every setting with its current value
+    List,
+}
+
+#[derive(Subcommand)]
+enum SecretCommands {
+    /// Store a secret (reads the value from stdin when omitted)
+    Set { name: String, value: Option<String> },
+    /// Print a secret's value
+    Get { name: String },
+    /// Delete a secret
+    Rm { name: String },
+    /// List stored secret names
+    List,
+}
+
 #[derive(Subcommand)]
 enum DataflowCommands {
-    /// Import dataflow project(s) from local paths or GitHub URLs
+    /// Import dataflow project(s) from local paths, GitHub URLs or `.dmflow` bundles
+    /// (bundles also install the nodes they need)
     Import {
-        /// Local path(s) or GitHub URL(s)
+        /// Local path(s), GitHub URL(s) or `.dmflow` file(s)
         #[arg(required = true)]
         sources: Vec<String>,
     },
+    /// Write a dataflow, its node versions, pins, sources and configs (without
+    /// secrets) to a `.dmflow` bundle
+    Export {
+        name: String,
+        /// Bundle path (default: ./<name>.dmflow)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Commit, pull and push the dataflows directory (`dataflows_dir` must be a git checkout)
+    Sync {
+        /// Commit message for local changes
+        #[arg(short, long)]
+        message: Option<String>,
+        /// Commit and pull only
+        #[arg(long)]
+        no_push: bool,
+    },
+    /// List the saved versions of a dataflow, newest first
+    History { name: String },
+    /// Show what changed between a saved version and the current YAML
+    Diff {
+        name: String,
+        version: String,
+        /// Compare with another saved version instead
+        #[arg(long)]
+        against: Option<String>,
+    },
+    /// Make a saved version the current YAML (the replaced YAML is kept in history)
+    Restore { name: String, version: String },
+}
+
+#[derive(Subcommand)]
+enum GraphCommands {
+    /// Report what would stop a graph from starting: unknown nodes and
+    /// ports, duplicate ids, cycles and transpile errors
+    Validate {
+        /// Editor JSON or dora YAML file
+        file: String,
+    },
+    /// Convert a graph between dora YAML and the editor's node/edge JSON
+    Convert {
+        /// Editor JSON or dora YAML file
+        file: String,
+        /// Output format: yaml or json
+        #[arg(long)]
+        to: dm_core::dataflow::GraphFormat,
+        /// Write here instead of stdout
+        #[arg(short, long)]
+        output: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
 enum NodeCommands {
     /// Install node(s) dependencies and build
     Install {
-        /// Node id(s) (e.g. dora-yolo dora-keyboard)
+        /// Node id(s) or @collection(s) (e.g. dora-yolo @speech-stack)
         #[arg(required = true)]
         ids: Vec<String>,
+        /// Install even when the node's declared system packages are missing
+        #[arg(long)]
+        skip_sysdeps: bool,
+        /// Nodes to install at once (default: `install.node_parallelism`)
+        #[arg(short, long)]
+        jobs: Option<usize>,
     },
-    /// Import node(s) from local directories or git URLs
+    /// Import node(s) from local directories, git URLs or `.dmnode` archives
     Import {
-        /// Local path(s) or git URL(s)
+        /// Local path(s), git URL(s) or `.dmnode` file(s)
         #[arg(required = true)]
         sources: Vec<String>,
     },
+    /// Pack a node's source and dm.json (without its venv) into a `.dmnode` archive
+    Pack {
+        /// Node id
+        id: String,
+        /// Archive path (default: ./<id>.dmnode)
+        #[arg(short, long)]
+        output: Option<String>,
+    },
+    /// Scaffold a new Python node in the managed node directory
+    Create {
+        /// Node id
+        id: String,
+        /// One-line description
+        #[arg(long, short, default_value = "")]
+        description: String,
+        /// Author, as `Name` or `Name <email>`
+        #[arg(long)]
+        author: Option<String>,
+        /// SPDX license identifier, e.g. Apache-2.0
+        #[arg(long)]
+        license: Option<String>,
+        /// Python requirement, e.g. 3.11 or ">=3.10,<3.13"
+        #[arg(long)]
+        python: Option<String>,
+        /// Registry category, e.g. Vision
+        #[arg(long)]
+        category: Option<String>,
+    },
     /// List installed nodes
     List,
+    /// Search the node registry (works offline from the bundled snapshot)
+    Search {
+        /// Match against id, description, category or tags; omit to list all
+        query: Option<String>,
+        /// Skip the remote registry and use the local copy only
+        #[arg(long)]
+        offline: bool,
+    },
+    /// List node collections installable as `dm node install @name`
+    Collections,
+    /// Compare installed nodes with the newest release on PyPI / crates.io
+    Outdated,
+    /// Upgrade every outdated node that is not pinned
+    UpdateAll,
     /// Uninstall node(s)
     Uninstall {
         /// Node id(s)
         #[arg(required = true)]
         ids: Vec<String>,
+        /// Tag saved dataflows that still use the node as `missing-node:<id>`
+        #[arg(long)]
+        tag_dataflows: bool,
+    },
+    /// Hold a node at a version; upgrades skip it and reinstalls use it
+    Pin {
+        /// Node id
+        id: String,
+        /// Version to hold
+        version: String,
+    },
+    /// Release a version hold set by `dm node pin`
+    Unpin {
+        /// Node id
+        id: String,
     },
+    /// Start a node outside dora to check that it launches and imports its deps
+    Test {
+        /// Node id
+        id: String,
+        /// Seconds the node must keep running (or exit cleanly) to pass
+        #[arg(long, default_value_t = dm_core::node::DEFAULT_TEST_WINDOW.as_secs())]
+        timeout: u64,
+    },
+    /// Manage a node's config schema
+    Schema {
+        #[command(subcommand)]
+        command: NodeSchemaCommands,
+    },
+    /// Check node config against its config schema
+    Config {
+        #[command(subcommand)]
+        command: NodeConfigCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum NodeConfigCommands {
+    /// Validate the saved config.json, or FILE, against the node's config_schema
+    Validate {
+        /// Node id
+        id: String,
+        /// Config file (JSON or YAML) to check instead of the saved config
+        file: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum NodeSchemaCommands {
+    /// Attach a JSON Schema (JSON or YAML file) to the node's dm.json
+    Set {
+        /// Node id
+        id: String,
+        /// Schema file
+        file: String,
+    },
+}
+
+fn parse_env(raw: &str) -> Result<(String, String), String> {
+    dm_core::dora::parse_env_assignment(raw).map_err(|e| e.to_string())
+}
+
+fn parse_install_method(raw: &str) -> Result<dm_core::config::InstallMethodPreference, String> {
+    raw.parse().map_err(|e: anyhow::Error| e.to_string())
 }
 
 // ---------------------------------------------------------------------------
@@ -185,16 +654,75 @@ enum NodeCommands {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
+    let result = run(Cli::parse()).await;
+    dm_core::events::flush_exports();
+    result
+}
+
+/// Exit with `code` once queued event exports are sent.
+fn exit(code: i32) -> ! {
+    dm_core::events::flush_exports();
+    std::process::exit(code)
+}
+
+async fn run(cli: Cli) -> Result<()> {
     let home = dm_core::config::resolve_home(cli.home)?;
+    dm_core::lock::set_policy(dm_core::lock::LockPolicy {
+        wait: std::time::Duration::from_secs(cli.wait.unwrap_or(0)),
+        force: cli.force_lock,
+    });
+    #[cfg(feature = "mock-runtime")]
+    if cli.mock_runtime {
+        dm_core::mock_runtime::enable();
+    }
+    let verbose = cli.verbose
+        || dm_core::config::load_config(&home)
+            .ok()
+            .and_then(|cfg| cfg.verbose)
+            .unwrap_or(false);
 
     match cli.command {
-        Commands::Setup => cmd_setup(&home, cli.verbose).await?,
-        Commands::Doctor => {
-            let report = dm_core::doctor(&home).await?;
+        Commands::Setup => cmd_setup(&home, verbose).await?,
+        Commands::Quickstart => cmd::quickstart::quickstart(&home, verbose).await?,
+        Commands::Doctor {
+            no_history,
+            fix,
+            deep,
+        } => {
+            if fix {
+                let fixed = dm_core::permissions::fix_permissions(&home)?;
+                display::print_permission_fixes(&fixed);
+            }
+            let options = dm_core::DoctorOptions {
+                history: !no_history,
+                deep,
+            };
+            let report = dm_core::doctor_with_options(&home, options).await?;
             display::print_doctor_report(&report);
         }
-        Commands::Install { version } => cmd_install(&home, cli.verbose, version).await?,
+        Commands::Migrate { dry_run } => {
+            let report = dm_core::migrate(&home, dry_run)?;
+            display::print_migration_report(&report);
+        }
+        Commands::Explain { topic } => match topic {
+            Some(topic) => display::print_explanation(&dm_core::explain(&home, &topic)?),
+            None => display::print_explain_topics(&dm_core::explain_topics()),
+        },
+        Commands::Install {
+            version,
+            method,
+            no_verify,
+            from_file,
+            mirror,
+        } => {
+            let options = dm_core::install::InstallOptions {
+                method,
+                verify: no_verify.then_some(false),
+                mirror,
+                from_file,
+            };
+            cmd_install(&home, verbose, version, options).await?
+        }
         Commands::Uninstall { version } => {
             dm_core::uninstall(&home, &version).await?;
             println!("  {} dora {} removed.", "✅".green(), version.bold());
@@ -208,38 +736,247 @@ async fn main() -> Result<()> {
                 actual.dimmed()
             );
         }
-        Commands::Versions => {
-            let report = dm_core::versions(&home).await?;
+        Commands::Alias { command } => match command {
+            AliasCommands::Set { name, version } => cmd::alias::set(&home, &name, &version)?,
+            AliasCommands::Rm { name } => cmd::alias::remove(&home, &name)?,
+            AliasCommands::List => cmd::alias::list(&home)?,
+        },
+        Commands::Shim { command } => match command {
+            ShimCommands::Install => cmd::shim::install(&home)?,
+            ShimCommands::Remove => cmd::shim::remove(&home)?,
+            ShimCommands::Status => cmd::shim::status(&home),
+        },
+        Commands::Versions { all } => {
+            let report = dm_core::versions_with(&home, all).await?;
             display::print_versions_report(&report);
         }
-        Commands::Up => {
-            println!("{} Starting dora coordinator + daemon...", "→".cyan());
-            let result = dm_core::up(&home, cli.verbose).await?;
-            display::print_runtime_result("Start", &result);
+        Commands::Up {
+            env,
+            inherit_env,
+            supervise,
+            interval,
+            max_backoff,
+        } => {
+            let env = env.into_iter().collect();
+            if supervise {
+                let options = dm_core::SupervisorOptions {
+                    interval: std::time::Duration::from_secs(interval.max(1)),
+                    max_backoff: std::time::Duration::from_secs(max_backoff.max(1)),
+                    env,
+                    inherit_env,
+                };
+                cmd::runtime::supervise(&home, verbose, options).await?
+            } else {
+                println!("{} Starting dora coordinator + daemon...", "→".cyan());
+                let result = dm_core::up_with_env(&home, verbose, &env, inherit_env).await?;
+                display::print_runtime_result("Start", &result);
+            }
         }
         Commands::Down => {
             println!("{} Stopping dora coordinator + daemon...", "→".cyan());
-            let result = dm_core::down(&home, cli.verbose).await?;
+            let result = dm_core::down(&home, verbose).await?;
             display::print_runtime_result("Stop", &result);
         }
+        Commands::Logs {
+            source,
+            case_id,
+            node,
+            level,
+            since,
+            tail,
+            follow,
+        } => {
+            let filter = dm_core::events::EventFilter {
+                source,
+                case_id,
+                node_id: node,
+                level,
+                since,
+                ..Default::default()
+            };
+            cmd::logs::logs(&home, filter, tail, follow).await?
+        }
+        Commands::Audit {
+            action,
+            user,
+            target,
+            since,
+            tail,
+        } => {
+            let filter = dm_core::events::AuditFilter {
+                action,
+                user,
+                target,
+                since,
+                limit: Some(tail),
+                ..Default::default()
+            };
+            cmd::audit::audit(&home, filter)?
+        }
+        Commands::Tui => cmd::tui::tui(&home).await?,
+        Commands::Secret { command } => match command {
+            SecretCommands::Set { name, value } => cmd::secret::set(&home, &name, value)?,
+            SecretCommands::Get { name } => cmd::secret::get(&home, &name)?,
+            SecretCommands::Rm { name } => cmd::secret::remove(&home, &name)?,
+            SecretCommands::List => cmd::secret::list(&home)?,
+        },
+        Commands::Config { command } => match command {
+            ConfigCommands::Get { key } => cmd::config::get(&home, &key)?,
+            ConfigCommands::Set { key, value } => cmd::config::set(&home, &key, &value)?,
+            ConfigCommands::Unset { key } => cmd::config::unset(&home, &key)?,
+            ConfigCommands::List => cmd::config::list(&home)?,
+        },
+        Commands::Encrypt => cmd::secret::encrypt(&home)?,
+        Commands::Completions { shell } => {
+            cmd::complete::completions(&Cli::command(), shell);
+        }
+        Commands::Complete { kind } => cmd::complete::complete(&home, kind).await?,
         Commands::Status => {
-            let report = dm_core::status(&home, cli.verbose).await?;
+            let report = dm_core::status(&home, verbose).await?;
             display::print_status_report(&report);
         }
+        Commands::Ps { watch, interval } => {
+            cmd::runs::ps(&home, verbose, watch.then_some(interval)).await?
+        }
+        #[cfg(feature = "serve")]
+        Commands::Serve { addr } => cmd::serve::serve(&home, &addr).await?,
+        Commands::Machine { command } => match command {
+            MachineCommands::Add { name, addr } => cmd::machine::add(&home, &name, &addr)?,
+            MachineCommands::List => cmd::machine::list(&home).await,
+        },
 
         // --- Delegated command groups ---
         Commands::Node { command } => match command {
-            NodeCommands::Install { ids } => cmd::node::install(&home, ids).await?,
+            NodeCommands::Install {
+                ids,
+                skip_sysdeps,
+                jobs,
+            } => cmd::node::install(&home, ids, skip_sysdeps, jobs).await?,
             NodeCommands::List => cmd::node::list(&home)?,
+            NodeCommands::Collections => cmd::node::collections(&home)?,
+            NodeCommands::Outdated => cmd::node::outdated(&home).await?,
+            NodeCommands::UpdateAll => cmd::node::update_all(&home).await?,
+            NodeCommands::Search { query, offline } => {
+                cmd::node::search(&home, query.as_deref().unwrap_or(""), offline).await?
+            }
             NodeCommands::Import { sources } => cmd::node::import(&home, sources).await?,
-            NodeCommands::Uninstall { ids } => cmd::node::uninstall(&home, ids)?,
+            NodeCommands::Pack { id, output } => cmd::node::pack(&home, &id, output)?,
+            NodeCommands::Create {
+                id,
+                description,
+                author,
+                license,
+                python,
+                category,
+            } => cmd::node::create(
+                &home,
+                &id,
+                &dm_core::node::NodeScaffold {
+                    description,
+                    author,
+                    license,
+                    python,
+                    category,
+                },
+            )?,
+            NodeCommands::Uninstall { ids, tag_dataflows } => {
+                cmd::node::uninstall(&home, ids, tag_dataflows)?
+            }
+            NodeCommands::Pin { id, version } => cmd::node::pin(&home, &id, &version)?,
+            NodeCommands::Unpin { id } => cmd::node::unpin(&home, &id)?,
+            NodeCommands::Test { id, timeout } => cmd::node::test(&home, &id, timeout).await?,
+            NodeCommands::Schema {
+                command: NodeSchemaCommands::Set { id, file },
+            } => cmd::node::set_schema(&home, &id, &file)?,
+            NodeCommands::Config {
+                command: NodeConfigCommands::Validate { id, file },
+            } => cmd::node::validate_config(&home, &id, file.as_deref())?,
         },
 
         Commands::Dataflow { command } => match command {
             DataflowCommands::Import { sources } => cmd::dataflow::import(&home, sources).await?,
+            DataflowCommands::Export { name, output } => {
+                cmd::dataflow::export(&home, &name, output)?
+            }
+            DataflowCommands::Sync { message, no_push } => {
+                cmd::dataflow::sync(&home, message.as_deref(), !no_push)?
+            }
+            DataflowCommands::History { name } => cmd::dataflow::history(&home, &name)?,
+            DataflowCommands::Diff {
+                name,
+                version,
+                against,
+            } => cmd::dataflow::diff(&home, &name, &version, against.as_deref())?,
+            DataflowCommands::Restore { name, version } => {
+                cmd::dataflow::restore(&home, &name, &version)?
+            }
         },
 
-        Commands::Start { file, force } => cmd_start(&home, cli.verbose, &file, force).await?,
+        Commands::Graph { command } => match command {
+            GraphCommands::Validate { file } => {
+                cmd::graph::validate(&home, std::path::Path::new(&file))?
+            }
+            GraphCommands::Convert { file, to, output } => cmd::graph::convert(
+                std::path::Path::new(&file),
+                to,
+                output.as_deref().map(std::path::Path::new),
+            )?,
+        },
+
+        Commands::Start {
+            file,
+            tag,
+            force,
+            allow_multiple,
+            isolated,
+            dora_version,
+            auto_install,
+        } => {
+            let isolation = if isolated || dora_version.is_some() {
+                dm_core::runs::RunIsolation::Dedicated { dora_version }
+            } else {
+                dm_core::runs::RunIsolation::from_config(&home)
+            };
+            if let Some(tag) = tag {
+                cmd::runs::start_tagged(&home, verbose, &tag, force, isolation).await?
+            } else {
+                let file = file.context("Pass a dataflow file or --tag")?;
+                cmd_start(
+                    &home,
+                    verbose,
+                    &file,
+                    force,
+                    allow_multiple,
+                    isolation,
+                    auto_install,
+                )
+                .await?
+            }
+        }
+
+        Commands::Stop { tag } => cmd::runs::stop_tagged(&home, &tag).await?,
+
+        Commands::Run { file, uv, set } => {
+            let path = std::path::PathBuf::from(&file);
+            if !path.exists() {
+                anyhow::bail!("Graph file '{}' not found.", path.display());
+            }
+            if uv {
+                println!(
+                    "{} Preparing shared uv environment for Python nodes...",
+                    "→".cyan()
+                );
+            }
+            let variables = set.into_iter().collect();
+            let code = dm_core::dataflow::run_local(&home, &path, uv, verbose, &variables).await?;
+            exit(code);
+        }
+
+        Commands::Transpile { file, check } => cmd::dataflow::transpile(
+            &home,
+            std::path::Path::new(&file),
+            check.as_deref().map(std::path::Path::new),
+        )?,
 
         Commands::Runs { command } => match command {
             None => cmd::runs::list(&home).await?,
@@ -250,14 +987,23 @@ async fn main() -> Result<()> {
                 node_id,
                 follow,
             }) => cmd::runs::logs(&home, run_id, node_id, follow).await?,
+            Some(RunsCommands::ImportEvents { run_id }) => {
+                cmd::runs::import_events(&home, &run_id)?
+            }
             Some(RunsCommands::Clean { keep }) => cmd::runs::clean(&home, keep)?,
         },
 
+        Commands::HelloPrinter { ticks } => cmd::quickstart::hello_printer(ticks)?,
         Commands::Bridge { run_id } => bridge::bridge_serve(&home, &run_id).await?,
 
+        Commands::Plugins => cmd::plugin::list(&home)?,
+        Commands::Plugin(args) => {
+            let code = cmd::plugin::run(&home, args)?;
+            exit(code);
+        }
         Commands::Passthrough { args } => {
-            let code = dm_core::passthrough(&home, &args, cli.verbose).await?;
-            std::process::exit(code);
+            let code = dm_core::passthrough(&home, &args, verbose).await?;
+            exit(code);
         }
     }
 
@@ -270,27 +1016,6 @@ async fn main() -> Result<()> {
 
 async fn cmd_setup(home: &std::path::Path, verbose: bool) -> Result<()> {
     display::print_header("Dora Manager — Setup");
-    println!("  Checking prerequisites...\n");
-
-    let python = dm_core::env::check_python().await;
-    display::print_env_item(&python);
-    if !python.found {
-        println!("\n  {} Python 3.11+ is required.", "❌".red());
-        println!("    macOS:   brew install python@3.11");
-        println!("    Linux:   sudo apt install python3.11");
-        anyhow::bail!("Python not found. Install it and re-run `dm setup`.");
-    }
-
-    let uv = dm_core::env::check_uv().await;
-    display::print_env_item(&uv);
-    if !uv.found {
-        println!("\n  {} Installing uv...", "→".cyan());
-    }
-
-    let rust = dm_core::env::check_rust().await;
-    display::print_env_item(&rust);
-
-    display::print_header("Dora CLI");
     let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
 
     let home_clone = home.to_path_buf();
@@ -299,25 +1024,54 @@ async fn cmd_setup(home: &std::path::Path, verbose: bool) -> Result<()> {
 
     while let Some(progress) = progress_rx.recv().await {
         match &progress.phase {
-            InstallPhase::Fetching => println!("  {} {}", "→".cyan(), progress.message),
-            InstallPhase::Downloading { .. } => {}
-            InstallPhase::Extracting => println!("  {} {}", "→".cyan(), progress.message),
-            InstallPhase::Building => println!("  {} {}", "→".cyan(), progress.message),
-            InstallPhase::Done => println!("  {} {}", "✅".green(), progress.message),
+            InstallPhase::Setup {
+                status: SetupStatus::Running,
+                ..
+            } => println!("  {} {}...", "→".cyan(), progress.message),
+            InstallPhase::Setup { .. } | InstallPhase::Downloading { .. } => {}
+            InstallPhase::Done => println!("    {} {}", "✅".green(), progress.message),
+            InstallPhase::Fetching
+            | InstallPhase::Verifying
+            | InstallPhase::Extracting
+            | InstallPhase::Building => println!("    {} {}", "→".cyan(), progress.message),
         }
     }
 
     let report = handle.await??;
     display::print_setup_report(&report);
+    let failed: Vec<&str> = report
+        .phases
+        .iter()
+        .filter(|phase| !phase.status.is_ok())
+        .map(|phase| phase.phase.label())
+        .collect();
+    if !failed.is_empty() {
+        anyhow::bail!(
+            "Setup incomplete ({} not ready). Fix the above and re-run `dm setup`.",
+            failed.join(", ")
+        );
+    }
     Ok(())
 }
 
-async fn cmd_install(home: &std::path::Path, verbose: bool, version: Option<String>) -> Result<()> {
+async fn cmd_install(
+    home: &std::path::Path,
+    verbose: bool,
+    version: Option<String>,
+    options: dm_core::install::InstallOptions,
+) -> Result<()> {
     let (progress_tx, mut progress_rx) = tokio::sync::mpsc::unbounded_channel();
 
     let home_clone = home.to_path_buf();
     let handle = tokio::spawn(async move {
-        dm_core::install::install(&home_clone, version, verbose, Some(progress_tx)).await
+        dm_core::install::install_with_options(
+            &home_clone,
+            version,
+            options,
+            verbose,
+            Some(progress_tx),
+        )
+        .await
     });
 
     let pb = ProgressBar::hidden();
@@ -346,12 +1100,12 @@ async fn cmd_install(home: &std::path::Path, verbose: bool, version: Option<Stri
                 }
                 pb.set_position(*bytes_done);
             }
-            InstallPhase::Extracting => {
+            InstallPhase::Verifying | InstallPhase::Extracting => {
                 pb.finish_and_clear();
                 println!("{} {}", "→".cyan(), progress.message);
             }
             InstallPhase::Building => println!("{} {}", "→".cyan(), progress.message),
-            InstallPhase::Done => {}
+            InstallPhase::Done | InstallPhase::Setup { .. } => {}
         }
     }
     pb.finish_and_clear();
@@ -361,11 +1115,21 @@ async fn cmd_install(home: &std::path::Path, verbose: bool, version: Option<Stri
     Ok(())
 }
 
-async fn cmd_start(home: &std::path::Path, verbose: bool, file: &str, force: bool) -> Result<()> {
-    if !dm_core::is_runtime_running(home, verbose).await {
-        println!("{} Dora runtime not running, starting...", "→".cyan());
+async fn cmd_start(
+    home: &std::path::Path,
+    verbose: bool,
+    file: &str,
+    force: bool,
+    allow_multiple: bool,
+    isolation: dm_core::runs::RunIsolation,
+    auto_install: bool,
+) -> Result<()> {
+    if isolation == dm_core::runs::RunIsolation::Shared {
+        if !dm_core::is_runtime_running(home, verbose).await {
+            println!("{} Dora runtime not running, starting...", "→".cyan());
+        }
+        dm_core::ensure_runtime_up(home, verbose).await?;
     }
-    dm_core::ensure_runtime_up(home, verbose).await?;
 
     // Handle URL downloads
     let file_path = if file.starts_with("http://") || file.starts_with("https://") {
@@ -382,7 +1146,9 @@ async fn cmd_start(home: &std::path::Path, verbose: bool, file: &str, force: boo
             .context("Failed to create temporary file")?;
 
         // Download the file
-        let response = reqwest::get(file)
+        let response = dm_core::http::client(home)?
+            .get(file)
+            .send()
             .await
             .context("Failed to download file from URL")?;
 
@@ -432,25 +1198,41 @@ async fn cmd_start(home: &std::path::Path, verbose: bool, file: &str, force: boo
             .keep()
             .context("Failed to persist downloaded file")?
     } else {
-        std::path::PathBuf::from(file)
+        // A saved dataflow name works too (as offered by `dm completions`).
+        let path = std::path::PathBuf::from(file);
+        let saved =
+            dm_core::dataflow::dataflow_yaml_path(&dm_core::dataflow::dataflow_dir(home, file));
+        if !path.exists() && !file.contains(['/', '\\']) && saved.exists() {
+            saved
+        } else {
+            path
+        }
     };
 
     if !file_path.exists() {
         anyhow::bail!("Graph file '{}' not found.", file_path.display());
     }
+    if auto_install {
+        let yaml = std::fs::read_to_string(&file_path)
+            .with_context(|| format!("Failed to read graph file '{}'", file_path.display()))?;
+        cmd::node::install_missing(home, &yaml).await?;
+    }
 
     println!("{} Starting dataflow...", "🚀".green());
     let strategy = if force {
         dm_core::runs::StartConflictStrategy::StopAndRestart
+    } else if allow_multiple {
+        dm_core::runs::StartConflictStrategy::AllowMultiple
     } else {
         dm_core::runs::StartConflictStrategy::Fail
     };
-    let result = dm_core::runs::start_run_from_file_with_source_and_strategy(
+    let result = dm_core::runs::start_run_from_file_with_isolation(
         home,
         &file_path,
         None,
         dm_core::runs::RunSource::Cli,
         strategy,
+        isolation,
     )
     .await?;
     println!("{} Run created: {}", "✅".green(), result.run.run_id.bold());
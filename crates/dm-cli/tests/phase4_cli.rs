@@ -3,6 +3,8 @@ use std::fs;
 
 use assert_cmd::cargo::cargo_bin_cmd;
 use assert_cmd::Command;
+#[cfg(not(target_os = "windows"))]
+use dm_test_utils::{MockDora, MockResponse};
 use predicates::prelude::*;
 use tempfile::tempdir;
 
@@ -12,75 +14,16 @@ fn dm_cmd() -> Command {
     cmd
 }
 
-#[cfg(not(target_os = "windows"))]
-const FAKE_DORA_UUID: &str = "019cc181-adad-7654-aa78-63502362337b";
-
 #[cfg(not(target_os = "windows"))]
 fn setup_fake_runtime(home: &std::path::Path, active_version: &str) {
-    let version_dir = home.join("versions").join(active_version);
-    fs::create_dir_all(&version_dir).unwrap();
-
-    #[cfg(target_os = "windows")]
-    let bin_name = "dora.exe";
-    #[cfg(not(target_os = "windows"))]
-    let bin_name = "dora";
-
-    let bin = version_dir.join(bin_name);
-    let state_file = home.join("active_dataflow_id");
-    fs::write(
-        &bin,
-        format!(
-            r#"#!/bin/sh
-cmd="$1"
-case "$cmd" in
-  check)
-    exit 0
-    ;;
-  list)
-    if [ -f "{state_file}" ]; then
-      echo "UUID Name Status Nodes CPU Memory"
-      printf "%s test-flow Running 1 0.0%% 0.0\\ GB\\n" "$(cat "{state_file}")"
-    fi
-    exit 0
-    ;;
-  start)
-    run_yaml="$2"
-    run_dir="$(dirname "$run_yaml")"
-    mkdir -p "$run_dir/out/{fake_uuid}"
-    echo "worker log line" > "$run_dir/out/{fake_uuid}/log_worker.txt"
-    echo "{fake_uuid}" > "{state_file}"
-    echo "dataflow started: {fake_uuid}"
-    exit 0
-    ;;
-  stop)
-    rm -f "{state_file}"
-    echo "stopped"
-    exit 0
-    ;;
-  *)
-    exit 0
-    ;;
-esac
-"#,
-            state_file = state_file.display(),
-            fake_uuid = FAKE_DORA_UUID
-        ),
-    )
-    .unwrap();
-
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = fs::metadata(&bin).unwrap().permissions();
-        perms.set_mode(0o755);
-        fs::set_permissions(&bin, perms).unwrap();
-    }
-
-    fs::write(
-        home.join("config.toml"),
-        format!("active_version = \"{}\"\n", active_version),
-    )
-    .unwrap();
+    MockDora::new(active_version)
+        .respond("check", MockResponse::ok(""))
+        .respond(
+            "stop",
+            MockResponse::script("rm -f \"$DM_MOCK_STATE\"\necho stopped\nexit 0"),
+        )
+        .install(home)
+        .unwrap();
 }
 
 #[test]
@@ -279,6 +222,39 @@ fn start_rejects_conflicting_active_run_without_force() {
         .stderr(predicate::str::contains("already running as run"));
 }
 
+#[test]
+#[cfg(not(target_os = "windows"))]
+fn start_allow_multiple_bypasses_conflict_check() {
+    let home = tempdir().unwrap();
+    setup_fake_runtime(home.path(), "0.4.1");
+    let graph_file = home.path().join("ok.yml");
+    fs::write(&graph_file, "nodes: []\n").unwrap();
+
+    for extra in [None, Some("--allow-multiple")] {
+        let mut args = vec![
+            "--home",
+            home.path().to_str().unwrap(),
+            "start",
+            graph_file.to_str().unwrap(),
+        ];
+        args.extend(extra);
+        dm_cmd().args(args).assert().success();
+    }
+
+    dm_cmd()
+        .args([
+            "--home",
+            home.path().to_str().unwrap(),
+            "start",
+            "--force",
+            "--allow-multiple",
+            graph_file.to_str().unwrap(),
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
 #[test]
 #[cfg(not(target_os = "windows"))]
 fn runs_refresh_marks_stale_running_run_as_stopped() {
@@ -307,3 +283,256 @@ fn runs_refresh_marks_stale_running_run_as_stopped() {
         .stdout(predicate::str::contains("✅"))
         .stdout(predicate::str::contains("ok"));
 }
+
+#[test]
+fn completions_script_queries_live_names() {
+    dm_cmd()
+        .args(["completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("complete -o default -F _dm dm"))
+        .stdout(predicate::str::contains("dm complete nodes"))
+        .stdout(predicate::str::contains("uninstall"));
+}
+
+#[test]
+fn complete_falls_back_to_local_home_without_server() {
+    let home = tempdir().unwrap();
+    let flow_dir = home.path().join("dataflows").join("saved-flow");
+    std::fs::create_dir_all(&flow_dir).unwrap();
+    std::fs::write(flow_dir.join("dataflow.yml"), "nodes: []\n").unwrap();
+
+    dm_cmd()
+        .env("DM_SERVER_URL", "http://127.0.0.1:9")
+        .args([
+            "--home",
+            home.path().to_str().unwrap(),
+            "complete",
+            "dataflows",
+        ])
+        .assert()
+        .success()
+        .stdout("saved-flow\n");
+
+    dm_cmd()
+        .env("DM_SERVER_URL", "http://127.0.0.1:9")
+        .args(["--home", home.path().to_str().unwrap(), "complete", "nodes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("dm-test-media-capture"));
+}
+
+#[test]
+fn transpile_check_compares_against_golden_file() {
+    let home = tempdir().unwrap();
+    let graph = home.path().join("graph.yml");
+    let golden = home.path().join("graph.golden.yml");
+    std::fs::write(&graph, "nodes:\n  - id: a\n    path: ./a.py\n").unwrap();
+    std::fs::write(&golden, "nodes:\n- id: a\n  path: ./a.py\n").unwrap();
+    let home_arg = home.path().to_str().unwrap();
+
+    dm_cmd()
+        .args(["--home", home_arg, "transpile", graph.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("path: ./a.py"));
+    dm_cmd()
+        .args(["--home", home_arg, "transpile", graph.to_str().unwrap()])
+        .args(["--check", golden.to_str().unwrap()])
+        .assert()
+        .success();
+
+    std::fs::write(&golden, "nodes:\n- id: a\n  path: ./b.py\n").unwrap();
+    dm_cmd()
+        .args(["--home", home_arg, "transpile", graph.to_str().unwrap()])
+        .args(["--check", golden.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("+   path: ./a.py"))
+        .stderr(predicate::str::contains("does not match"));
+}
+
+#[test]
+fn alias_set_list_and_use() {
+    let home = tempdir().unwrap();
+    setup_fake_runtime(home.path(), "0.4.1");
+    let home_arg = home.path().to_str().unwrap();
+
+    dm_cmd()
+        .args(["--home", home_arg, "alias", "set", "demo", "0.4.1"])
+        .assert()
+        .success();
+    dm_cmd()
+        .args(["--home", home_arg, "alias", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("demo -> 0.4.1"))
+        .stdout(predicate::str::contains("stable -> 0.4.1"));
+    dm_cmd()
+        .args(["--home", home_arg, "alias", "set", "stable", "0.4.1"])
+        .assert()
+        .success();
+    dm_cmd()
+        .args(["--home", home_arg, "alias", "set", "latest", "0.4.1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("built-in alias"));
+    dm_cmd()
+        .args(["--home", home_arg, "alias", "rm", "demo"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn shim_install_status_remove() {
+    let home = tempdir().unwrap();
+    setup_fake_runtime(home.path(), "0.4.1");
+    let home_arg = home.path().to_str().unwrap();
+
+    dm_cmd()
+        .args(["--home", home_arg, "shim", "install"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Installed dora shim"))
+        .stdout(predicate::str::contains("PATH"));
+    dm_cmd()
+        .args(["--home", home_arg, "shim", "status"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("0.4.1"));
+    dm_cmd()
+        .args(["--home", home_arg, "shim", "remove"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed dora shim"));
+}
+
+#[test]
+fn logs_filters_recorded_events() {
+    use dm_core::events::{EventBuilder, EventLevel, EventSource, EventStore};
+
+    let home = tempdir().unwrap();
+    let store = EventStore::open(home.path()).unwrap();
+    store
+        .emit(
+            &EventBuilder::new(EventSource::Dataflow, "dora.log")
+                .case_id("run-1")
+                .node_id("camera")
+                .level(EventLevel::Error)
+                .message("camera not found")
+                .build(),
+        )
+        .unwrap();
+    store
+        .emit(
+            &EventBuilder::new(EventSource::Core, "runtime.up")
+                .message("runtime started")
+                .build(),
+        )
+        .unwrap();
+    let home_arg = home.path().to_str().unwrap();
+
+    dm_cmd()
+        .args(["--home", home_arg, "logs", "--source", "dataflow"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("run-1/camera"))
+        .stdout(predicate::str::contains("camera not found"))
+        .stdout(predicate::str::contains("runtime started").not());
+    dm_cmd()
+        .args([
+            "--home", home_arg, "logs", "--since", "1h", "--level", "info",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("runtime.up"))
+        .stdout(predicate::str::contains("camera not found").not());
+    dm_cmd()
+        .args(["--home", home_arg, "logs", "--since", "yesterday"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid --since"));
+}
+
+#[cfg(not(target_os = "windows"))]
+#[test]
+fn unknown_commands_dispatch_to_plugins() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let home = tempdir().unwrap();
+    let bin = tempdir().unwrap();
+    let plugin = bin.path().join("dm-hello");
+    std::fs::write(
+        &plugin,
+        "#!/bin/sh\necho \"args: $*\"\necho \"home: $DM_HOME\"\ncat\nexit 3\n",
+    )
+    .unwrap();
+    std::fs::set_permissions(&plugin, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        bin.path().display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let home_arg = home.path().to_str().unwrap();
+
+    dm_cmd()
+        .env("PATH", &path)
+        .args(["--home", home_arg, "hello", "world", "--flag"])
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains("args: world --flag"))
+        .stdout(predicate::str::contains(format!("home: {}", home_arg)))
+        .stdout(predicate::str::contains("\"args\":[\"world\",\"--flag\"]"));
+    dm_cmd()
+        .env("PATH", &path)
+        .args(["--home", home_arg, "plugins"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hello ->"));
+    dm_cmd()
+        .env("PATH", &path)
+        .args(["--home", home_arg, "no-such-plugin"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown command 'no-such-plugin'"));
+}
+
+#[cfg(feature = "serve")]
+#[test]
+fn serve_exposes_the_dm_server_api() {
+    let home = tempdir().unwrap();
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let addr = format!("127.0.0.1:{port}");
+    let mut server = std::process::Command::new(assert_cmd::cargo::cargo_bin!("dm"))
+        .args([
+            "--home",
+            home.path().to_str().unwrap(),
+            "serve",
+            "--addr",
+            &addr,
+        ])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let mut spec = None;
+    for _ in 0..50 {
+        if let Ok(resp) = reqwest::blocking::get(format!("http://{addr}/api/openapi.json")) {
+            spec = resp.json::<serde_json::Value>().ok();
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(200));
+    }
+    let events = reqwest::blocking::get(format!("http://{addr}/api/events/count"));
+    server.kill().unwrap();
+    let _ = server.wait();
+
+    let spec = spec.expect("dm serve did not answer");
+    assert!(spec["paths"]["/api/dataflows"].is_object());
+    assert!(events.unwrap().status().is_success());
+}
@@ -0,0 +1,217 @@
+//! Version aliases and ranges: `latest`, `stable`, user-defined names such
+//! as `demo = "0.3.9"` (`[version_aliases]` in config.toml) and semver
+//! ranges such as `^0.3`, `~0.3.9` or `>=0.3, <0.4`.
+//!
+//! They are resolved wherever a dora version is accepted. For versions
+//! that must already be installed (`dm use`, `dm uninstall`, dedicated
+//! runtimes) `latest` is the newest installed version, `stable` the newest
+//! installed non-prerelease and a range its newest installed match. For
+//! `dm install` they pick from the releases on GitHub instead. `stable` can
+//! be pinned with `dm alias set stable 0.3.9`; `latest` cannot be
+//! redefined. The resolved version, never the alias, is what gets stored
+//! as `active_version`.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
+use crate::config;
+use crate::events::{EventSource, OperationEvent};
+
+/// Built-in aliases; a user-defined alias of the same name takes precedence.
+pub const BUILTIN_ALIASES: &[&str] = &["latest", "stable"];
+
+/// Built-in aliases that cannot be redefined.
+pub const RESERVED_ALIASES: &[&str] = &["latest"];
+
+/// User-defined aliases, by name.
+pub fn version_aliases(home: &Path) -> Result<BTreeMap<String, String>> {
+    Ok(config::load_config(home)?.version_aliases)
+}
+
+/// Point alias `name` at `version`, replacing any previous target.
+pub fn set_version_alias(home: &Path, name: &str, version: &str) -> Result<()> {
+    let op = OperationEvent::new(home, EventSource::Core, "version.alias.set")
+        .attr("alias", name)
+        .attr("version", version);
+    op.emit_start();
+
+    let result = (|| {
+        validate_alias_name(name)?;
+        let version = version.trim().trim_start_matches('v');
+        if !looks_like_version(version) {
+            bail!(
+                "Alias target '{}' is not a dora version; aliases must point at a version such as 0.3.9",
+                version
+            );
+        }
+        config::modify_config(home, |cfg| {
+            cfg.version_aliases
+                .insert(name.to_string(), version.to_string());
+            Ok(())
+        })
+    })();
+
+    op.emit_result(&result);
+    result
+}
+
+/// Delete alias `name`; returns whether it existed.
+pub fn remove_version_alias(home: &Path, name: &str) -> Result<bool> {
+    let op =
+        OperationEvent::new(home, EventSource::Core, "version.alias.remove").attr("alias", name);
+    op.emit_start();
+
+    let result = (|| {
+        if !config::load_config(home)?
+            .version_aliases
+            .contains_key(name)
+        {
+            return Ok(false);
+        }
+        config::modify_config(home, |cfg| Ok(cfg.version_aliases.remove(name).is_some()))
+    })();
+
+    op.emit_result(&result);
+    result
+}
+
+/// Resolve `spec` to an installed version. Plain versions are returned
+/// as-is (minus a leading `v`) so callers keep their own "not installed"
+/// errors.
+pub fn resolve_installed_version(home: &Path, spec: &str) -> Result<String> {
+    let spec = spec.trim();
+    if let Some(version) = config::load_config(home)?.version_aliases.get(spec) {
+        return Ok(version.clone());
+    }
+    let range = version_range(spec);
+    if range.is_none() && !BUILTIN_ALIASES.contains(&spec) {
+        return Ok(spec.trim_start_matches('v').to_string());
+    }
+    installed_versions(home)
+        .into_iter()
+        .filter(|(_, v)| spec_matches(spec, range.as_ref(), v))
+        .max_by(|a, b| a.1.cmp(&b.1))
+        .map(|(name, _)| name)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "No installed dora version matches '{}'. Run `dm install {}` first.",
+                spec,
+                spec
+            )
+        })
+}
+
+/// Resolve `spec` to a release to install; `None` means GitHub's latest
+/// (non-prerelease) release.
+pub(crate) async fn resolve_release_version(
+    home: &Path,
+    spec: Option<&str>,
+) -> Result<Option<String>> {
+    let Some(spec) = spec.map(str::trim) else {
+        return Ok(None);
+    };
+    if let Some(version) = config::load_config(home)?.version_aliases.get(spec) {
+        return Ok(Some(version.clone()));
+    }
+    match spec {
+        "stable" => Ok(None),
+        "latest" => {
+            let (releases, _) = super::version::fetch_cached_releases(
+                &crate::http::client(home)?,
+                &config::github_api_url(home),
+                config::github_token(home).as_deref(),
+                Some(1),
+            )
+            .await?;
+            match releases.into_iter().next() {
+                Some(release) => Ok(Some(release.tag.trim_start_matches('v').to_string())),
+                None => bail!("No dora releases found"),
+            }
+        }
+        _ => match version_range(spec) {
+            Some(range) => {
+                let (releases, _) = super::version::fetch_cached_releases(
+                    &crate::http::client(home)?,
+                    &config::github_api_url(home),
+                    config::github_token(home).as_deref(),
+                    None,
+                )
+                .await?;
+                releases
+                    .iter()
+                    .filter_map(|release| {
+                        semver::Version::parse(release.tag.trim_start_matches('v')).ok()
+                    })
+                    .filter(|v| range.matches(v))
+                    .max()
+                    .map(|v| Some(v.to_string()))
+                    .ok_or_else(|| anyhow::anyhow!("No dora release matches '{}'", spec))
+            }
+            None => Ok(Some(spec.to_string())),
+        },
+    }
+}
+
+/// `spec` as a semver range (`^0.3`, `~0.3.9`, `>=0.3, <0.4`, `0.3`);
+/// `None` for a complete version such as `0.3.9`, which stays exact.
+fn version_range(spec: &str) -> Option<semver::VersionReq> {
+    if semver::Version::parse(spec.trim_start_matches('v')).is_ok() {
+        return None;
+    }
+    semver::VersionReq::parse(spec).ok()
+}
+
+/// Whether `version` satisfies `latest`, `stable` or `range`.
+fn spec_matches(spec: &str, range: Option<&semver::VersionReq>, version: &semver::Version) -> bool {
+    match (spec, range) {
+        ("latest", _) => true,
+        ("stable", _) => version.pre.is_empty(),
+        (_, Some(range)) => range.matches(version),
+        _ => false,
+    }
+}
+
+fn validate_alias_name(name: &str) -> Result<()> {
+    if RESERVED_ALIASES.contains(&name) {
+        bail!("'{}' is a built-in alias and cannot be redefined", name);
+    }
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+    {
+        bail!(
+            "Invalid alias '{}': use letters, digits, '-', '_' or '.'",
+            name
+        );
+    }
+    if looks_like_version(name.trim_start_matches('v')) {
+        bail!(
+            "Alias '{}' looks like a version number and would shadow it",
+            name
+        );
+    }
+    Ok(())
+}
+
+fn looks_like_version(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Installed version directories that parse as semver.
+fn installed_versions(home: &Path) -> Vec<(String, semver::Version)> {
+    let Ok(entries) = std::fs::read_dir(config::versions_dir(home)) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| {
+            let name = e.file_name().to_str()?.to_string();
+            let version = semver::Version::parse(&name).ok()?;
+            Some((name, version))
+        })
+        .collect()
+}
@@ -1,13 +1,58 @@
 use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
 
 use anyhow::Result;
 
 use crate::events::{EventSource, OperationEvent};
+use crate::node::Node;
 use crate::{config, env, types::*};
 
-/// Check environment health
+/// How far back `doctor` looks for failure events, in hours.
+const RECENT_PROBLEM_HOURS: i64 = 24;
+/// How long a probed node may run before it counts as started and is killed.
+const PROBE_WINDOW: Duration = Duration::from_secs(2);
+/// How long `pip check` or `--version` may take for one node.
+const NODE_CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+/// Which optional checks `doctor_with_options` runs.
+#[derive(Debug, Clone, Copy)]
+pub struct DoctorOptions {
+    /// Group error events of the last 24h into `recent_problems`
+    pub history: bool,
+    /// Launch every installed node briefly (see [`probe_nodes`])
+    pub deep: bool,
+}
+
+impl Default for DoctorOptions {
+    fn default() -> Self {
+        Self {
+            history: true,
+            deep: false,
+        }
+    }
+}
+
+/// Check environment health, including recent problems from the event store
 pub async fn doctor(home: &Path) -> Result<DoctorReport> {
-    let op = OperationEvent::new(home, EventSource::Core, "doctor");
+    doctor_with_history(home, true).await
+}
+
+/// Check environment health. With `history`, error events of the last 24h
+/// are grouped into `recent_problems` with a suggested fix for each.
+pub async fn doctor_with_history(home: &Path, history: bool) -> Result<DoctorReport> {
+    doctor_with_options(
+        home,
+        DoctorOptions {
+            history,
+            ..Default::default()
+        },
+    )
+    .await
+}
+
+/// Check environment health with the checks selected by `options`.
+pub async fn doctor_with_options(home: &Path, options: DoctorOptions) -> Result<DoctorReport> {
+    let op = OperationEvent::new(home, EventSource::Core, "doctor").attr("deep", options.deep);
     op.emit_start();
 
     let result = async {
@@ -27,6 +72,7 @@ pub async fn doctor(home: &Path) -> Result<DoctorReport> {
                             installed.push(InstalledVersion {
                                 version: name.to_string(),
                                 active: cfg.active_version.as_deref() == Some(name),
+                                manifest: crate::install::read_manifest(&entry.path()),
                             });
                         }
                     }
@@ -42,7 +88,33 @@ pub async fn doctor(home: &Path) -> Result<DoctorReport> {
             false
         };
 
-        let all_ok = python.found && uv.found && cfg.active_version.is_some() && active_binary_ok;
+        let node_probes = if options.deep {
+            probe_nodes(home).await?
+        } else {
+            Vec::new()
+        };
+
+        let sdk_mismatches = crate::node::check_sdk_versions(home)?;
+        let nodes = check_nodes_with(home, uv.found).await?;
+
+        let all_ok = python.found
+            && uv.found
+            && cfg.active_version.is_some()
+            && active_binary_ok
+            && node_probes.iter().all(|probe| probe.ok)
+            && sdk_mismatches.is_empty()
+            && nodes.iter().all(|node| node.ok);
+        let recent_problems = if options.history {
+            let since = chrono::Utc::now() - chrono::Duration::hours(RECENT_PROBLEM_HOURS);
+            super::explain::recent_problems(home, &since.to_rfc3339())
+        } else {
+            Vec::new()
+        };
+
+        let threshold_alerts = super::thresholds::check_thresholds(home);
+        super::thresholds::emit_threshold_alerts(home, &threshold_alerts).await;
+
+        let permission_issues = crate::permissions::check_permissions(home);
 
         Ok(DoctorReport {
             python,
@@ -52,6 +124,12 @@ pub async fn doctor(home: &Path) -> Result<DoctorReport> {
             active_version: cfg.active_version,
             active_binary_ok,
             all_ok,
+            recent_problems,
+            threshold_alerts,
+            permission_issues,
+            node_probes,
+            sdk_mismatches,
+            nodes,
         })
     }
     .await;
@@ -59,3 +137,254 @@ pub async fn doctor(home: &Path) -> Result<DoctorReport> {
     op.emit_result(&result);
     result
 }
+
+/// Check the environment of every installed node: its executable resolves,
+/// a Python node has a `.venv` whose requirements are consistent
+/// (`pip check`), a Rust node answers `--version`.
+pub async fn check_nodes(home: &Path) -> Result<Vec<NodeHealth>> {
+    let uv = env::check_uv().await;
+    check_nodes_with(home, uv.found).await
+}
+
+async fn check_nodes_with(home: &Path, use_uv: bool) -> Result<Vec<NodeHealth>> {
+    let mut checks = tokio::task::JoinSet::new();
+    for node in crate::node::list_nodes(home)?
+        .into_iter()
+        .filter(|node| !node.executable.is_empty())
+    {
+        checks.spawn(check_node(node, use_uv));
+    }
+    let mut results = Vec::new();
+    while let Some(health) = checks.join_next().await {
+        results.push(health?);
+    }
+    results.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+    Ok(results)
+}
+
+async fn check_node(node: Node, use_uv: bool) -> NodeHealth {
+    let build = node.source.build.trim().to_lowercase();
+    let kind = if build.starts_with("cargo") {
+        "cargo"
+    } else if build.starts_with("pip") || build.starts_with("uv") {
+        "python"
+    } else {
+        "other"
+    };
+
+    let executable = node.path.join(&node.executable);
+    let executable_ok = executable.is_file();
+    let mut checks = vec![NodeCheck {
+        name: "executable".to_string(),
+        ok: executable_ok,
+        detail: if executable_ok {
+            executable.display().to_string()
+        } else {
+            format!("{} not found; reinstall the node", executable.display())
+        },
+    }];
+
+    match kind {
+        "python" => {
+            let venv = node.path.join(".venv");
+            let venv_ok = venv.join("pyvenv.cfg").is_file();
+            checks.push(NodeCheck {
+                name: "venv".to_string(),
+                ok: venv_ok,
+                detail: if venv_ok {
+                    venv.display().to_string()
+                } else {
+                    format!("no virtualenv at {}; reinstall the node", venv.display())
+                },
+            });
+            if venv_ok {
+                let python = venv.join("bin/python");
+                let mut command = if use_uv {
+                    let mut command = tokio::process::Command::new("uv");
+                    command.args(["pip", "check", "--python"]).arg(&python);
+                    command
+                } else {
+                    let mut command = tokio::process::Command::new(&python);
+                    command.args(["-m", "pip", "check"]);
+                    command
+                };
+                checks.push(run_node_check("pip_check", &mut command).await);
+            }
+        }
+        "cargo" if executable_ok => {
+            let mut command = tokio::process::Command::new(&executable);
+            command.arg("--version").current_dir(&node.path);
+            checks.push(run_node_check("version", &mut command).await);
+        }
+        _ => {}
+    }
+
+    NodeHealth {
+        node_id: node.id,
+        kind: kind.to_string(),
+        ok: checks.iter().all(|check| check.ok),
+        checks,
+    }
+}
+
+/// Run `command`; the check passes when it exits successfully within
+/// `NODE_CHECK_TIMEOUT`. The detail is the first line of its output.
+async fn run_node_check(name: &str, command: &mut tokio::process::Command) -> NodeCheck {
+    let output = command
+        .env_remove("DORA_NODE_CONFIG")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output();
+    let (ok, detail) = match tokio::time::timeout(NODE_CHECK_TIMEOUT, output).await {
+        Err(_) => (
+            false,
+            format!("timed out after {}s", NODE_CHECK_TIMEOUT.as_secs()),
+        ),
+        Ok(Err(e)) => (false, format!("failed to run: {e}")),
+        Ok(Ok(output)) => {
+            let text = format!(
+                "{}\n{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            );
+            let first_line = text
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map(|line| line.chars().take(300).collect());
+            let detail = first_line.unwrap_or_else(|| match output.status.code() {
+                Some(code) => format!("exited with {code}"),
+                None => "terminated by a signal".to_string(),
+            });
+            (output.status.success(), detail)
+        }
+    };
+    NodeCheck {
+        name: name.to_string(),
+        ok,
+        detail,
+    }
+}
+
+/// Launch every installed node's executable with `--help` and outside dora
+/// (no `DORA_NODE_CONFIG`), to catch missing shared libraries and broken
+/// interpreters that static checks miss. Each node is judged as in
+/// [`crate::node::probe_executable`], with a `PROBE_WINDOW` window.
+pub async fn probe_nodes(home: &Path) -> Result<Vec<NodeProbe>> {
+    let mut probes = tokio::task::JoinSet::new();
+    // Nodes without an executable have not been installed yet.
+    for node in crate::node::list_nodes(home)?
+        .into_iter()
+        .filter(|node| !node.executable.is_empty())
+    {
+        probes.spawn(async move {
+            crate::node::probe_executable(&node, &["--help"], &Default::default(), PROBE_WINDOW)
+                .await
+        });
+    }
+    let mut results = Vec::new();
+    while let Some(probe) = probes.join_next().await {
+        results.push(probe?);
+    }
+    results.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+    Ok(results)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    fn node(home: &Path, id: &str, build: &str, executable: &str) -> std::path::PathBuf {
+        dm_test_utils::fake_node(
+            home,
+            id,
+            serde_json::json!({ "source": { "build": build }, "executable": executable }),
+        )
+        .unwrap()
+    }
+
+    fn script(path: &Path, body: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, format!("#!/bin/sh\n{body}\n")).unwrap();
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn check_nodes_covers_executables_venvs_and_versions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+
+        let dir = node(home, "rusty", "cargo install dora-rusty", "bin/dora-rusty");
+        script(&dir.join("bin/dora-rusty"), "echo 'dora-rusty 0.2.0'");
+        let dir = node(
+            home,
+            "crashing",
+            "cargo install dora-crashing",
+            "bin/dora-crashing",
+        );
+        script(&dir.join("bin/dora-crashing"), "exit 3");
+        node(home, "no-venv", "pip install no-venv", ".venv/bin/no-venv");
+        let dir = node(
+            home,
+            "conflicted",
+            "pip install conflicted",
+            ".venv/bin/conflicted",
+        );
+        script(&dir.join(".venv/bin/conflicted"), "exit 0");
+        std::fs::write(dir.join(".venv/pyvenv.cfg"), "version = 3.11.4\n").unwrap();
+        script(
+            &dir.join(".venv/bin/python"),
+            "echo 'foo 1.0 has requirement bar>=2, but you have bar 1.0.'; exit 1",
+        );
+        // Not installed yet: skipped.
+        node(home, "downloaded", "pip install downloaded", "");
+
+        // Built-in nodes are listed too; only look at the ones created here.
+        let ids = ["conflicted", "crashing", "no-venv", "rusty"];
+        let nodes: Vec<NodeHealth> = check_nodes_with(home, false)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|n| ids.contains(&n.node_id.as_str()))
+            .collect();
+        let summary: Vec<(&str, &str, bool)> = nodes
+            .iter()
+            .map(|n| (n.node_id.as_str(), n.kind.as_str(), n.ok))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("conflicted", "python", false),
+                ("crashing", "cargo", false),
+                ("no-venv", "python", false),
+                ("rusty", "cargo", true),
+            ]
+        );
+
+        let check = |id: &str, name: &str| {
+            nodes
+                .iter()
+                .find(|n| n.node_id == id)
+                .and_then(|n| n.checks.iter().find(|c| c.name == name))
+                .cloned()
+        };
+        let pip = check("conflicted", "pip_check").unwrap();
+        assert!(!pip.ok);
+        assert!(pip.detail.contains("has requirement bar>=2"));
+        assert_eq!(
+            check("crashing", "version").unwrap().detail,
+            "exited with 3"
+        );
+        assert!(!check("no-venv", "executable").unwrap().ok);
+        assert!(!check("no-venv", "venv").unwrap().ok);
+        assert!(check("no-venv", "pip_check").is_none());
+        assert_eq!(
+            check("rusty", "version").unwrap().detail,
+            "dora-rusty 0.2.0"
+        );
+    }
+}
@@ -0,0 +1,464 @@
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::events::{Event, EventFilter, EventStore};
+use crate::types::*;
+
+/// How many recent failures of an activity are scanned for a match.
+const RECENT_FAILURE_SCAN: i64 = 50;
+
+/// How many failures of any activity `recent_problems` groups.
+const RECENT_PROBLEM_SCAN: i64 = 500;
+
+struct Topic {
+    code: &'static str,
+    title: &'static str,
+    /// Activity whose failure events this topic explains.
+    activity: &'static str,
+    /// Lowercase fragments of the error message that identify this topic.
+    patterns: &'static [&'static str],
+    summary: &'static str,
+    steps: &'static [&'static str],
+}
+
+const TOPICS: &[Topic] = &[
+    Topic {
+        code: "install.rate_limit",
+        title: "GitHub API rate limit exceeded",
+        activity: "version.install",
+        patterns: &["rate limit"],
+        summary: "GitHub refused to list dora releases because the anonymous API quota is used up.",
+        steps: &[
+            "Set GITHUB_TOKEN to a personal access token to raise the limit.",
+            "Or wait for the quota to reset (usually within an hour).",
+        ],
+    },
+    Topic {
+        code: "install.network",
+        title: "Dora download failed",
+        activity: "version.install",
+        patterns: &[
+            "error sending request",
+            "dns error",
+            "connection",
+            "timed out",
+            "failed to download",
+            "failed to fetch",
+        ],
+        summary: "dm could not reach GitHub to fetch the dora release.",
+        steps: &[
+            "Check connectivity: `curl -I https://api.github.com`.",
+            "Behind a proxy? Export HTTPS_PROXY before running dm.",
+            "Using GitHub Enterprise or a mirror? Set `github_api_url` in config.toml or DM_GITHUB_API_URL.",
+            "Retry with `dm install -v` to see each request.",
+        ],
+    },
+    Topic {
+        code: "runtime.port_in_use",
+        title: "Dora runtime port already in use",
+        activity: "runtime.up",
+        patterns: &[
+            "address already in use",
+            "address in use",
+            "os error 98",
+            "os error 48",
+        ],
+        summary: "The coordinator or daemon could not bind its port because another process holds it.",
+        steps: &[
+            "A previous runtime may still be alive: run `dm down`, then `dm up`.",
+            "Otherwise stop the process holding the port (see diagnosis below).",
+        ],
+    },
+    Topic {
+        code: "runtime.not_installed",
+        title: "No usable dora binary",
+        activity: "runtime.up",
+        patterns: &["no active dora version", "dora binary not found"],
+        summary: "dm has no active dora version, or its binary was removed.",
+        steps: &[
+            "Run `dm versions` to see what is installed.",
+            "Install and activate one with `dm install` (or `dm use <version>`).",
+        ],
+    },
+    Topic {
+        code: "runtime.timeout",
+        title: "Dora runtime did not come up",
+        activity: "runtime.up",
+        patterns: &["timed out"],
+        summary: "`dora up` was spawned but `dora check` never reported a healthy runtime.",
+        steps: &[
+            "Run `dm -- check` to see what the coordinator reports.",
+            "Clear stale state with `dm down`, then retry `dm up -v`.",
+            "Make sure the dora version matches your nodes: `dm doctor`.",
+        ],
+    },
+    Topic {
+        code: "node.not_found",
+        title: "Node not installed",
+        activity: "node.install",
+        patterns: &["not found"],
+        summary: "The node has not been downloaded or created in this dm home.",
+        steps: &[
+            "List local nodes with `dm node list`.",
+            "Import it with `dm node import <path-or-git-url>`.",
+        ],
+    },
+    Topic {
+        code: "node.pip_failed",
+        title: "Python node install failed",
+        activity: "node.install",
+        patterns: &["pip install", "install package", "virtual environment"],
+        summary: "Creating the node's virtualenv or installing its Python package failed.",
+        steps: &[
+            "Check Python and uv with `dm doctor`; run `dm setup` if either is missing.",
+            "Inspect the node's pyproject.toml for dependencies that need system libraries.",
+            "Retry with `dm node install <id>` and read the pip output above the error.",
+        ],
+    },
+];
+
+/// List the error codes `explain()` knows about.
+pub fn explain_topics() -> Vec<ExplainTopic> {
+    TOPICS
+        .iter()
+        .map(|topic| ExplainTopic {
+            code: topic.code.to_string(),
+            title: topic.title.to_string(),
+            activity: topic.activity.to_string(),
+        })
+        .collect()
+}
+
+/// Troubleshooting guidance for an error code (`runtime.timeout`) or an
+/// activity (`runtime.up`).
+///
+/// Recent failure events are inspected so the answer can name the actual
+/// cause, e.g. which process holds the port the runtime needed.
+pub fn explain(home: &Path, query: &str) -> Result<Explanation> {
+    let query = query.trim();
+
+    if let Some(topic) = TOPICS.iter().find(|topic| topic.code == query) {
+        let event = recent_failures(home, topic.activity)
+            .into_iter()
+            .find(|event| classify(event).map(|t| t.code) == Some(topic.code));
+        return Ok(build_explanation(topic, event.as_ref()));
+    }
+
+    let failures = recent_failures(home, query);
+    let Some(event) = failures.first() else {
+        if TOPICS.iter().any(|topic| topic.activity == query) {
+            anyhow::bail!(
+                "No recent '{}' failures recorded. Nothing to explain.",
+                query
+            );
+        }
+        anyhow::bail!(
+            "Unknown error code or activity '{}'. Run `dm explain` to list known codes.",
+            query
+        );
+    };
+
+    match classify(event) {
+        Some(topic) => Ok(build_explanation(topic, Some(event))),
+        None => Ok(Explanation {
+            code: event.activity.clone(),
+            title: format!("'{}' failed", event.activity),
+            summary: "No guided steps exist for this failure yet.".to_string(),
+            steps: vec![format!(
+                "Inspect related events via `GET /api/events?case_id={}` on dm-server.",
+                event.case_id
+            )],
+            diagnosis: Some(describe_failure(event)),
+            event_id: Some(event.id),
+            event_at: Some(event.timestamp.clone()),
+        }),
+    }
+}
+
+/// Error events since `since` (RFC3339), grouped by cause, most frequent first.
+///
+/// Failures matching a known topic are grouped by its code and suggest its
+/// first step; others are grouped by activity and point at `dm explain`.
+pub(crate) fn recent_problems(home: &Path, since: &str) -> Vec<RecentProblem> {
+    let Ok(store) = EventStore::open(home) else {
+        return Vec::new();
+    };
+    let failures = store
+        .query(&EventFilter {
+            level: Some("error".to_string()),
+            since: Some(since.to_string()),
+            limit: Some(RECENT_PROBLEM_SCAN),
+            ..Default::default()
+        })
+        .unwrap_or_default();
+
+    let mut problems: Vec<RecentProblem> = Vec::new();
+    for event in &failures {
+        let topic = classify(event);
+        let code = topic
+            .map(|topic| topic.code.to_string())
+            .unwrap_or_else(|| event.activity.clone());
+        // Events arrive newest first, so the first one seen is the latest.
+        let occurrences = event.repeat_count() as usize;
+        if let Some(problem) = problems.iter_mut().find(|p| p.code == code) {
+            problem.count += occurrences;
+            continue;
+        }
+        let (title, suggestion) = match topic {
+            Some(topic) => (
+                topic.title.to_string(),
+                topic.steps.first().copied().unwrap_or_default().to_string(),
+            ),
+            None => (
+                event
+                    .message
+                    .as_deref()
+                    .and_then(|message| message.lines().next())
+                    .unwrap_or("(no message)")
+                    .trim()
+                    .to_string(),
+                format!("Run `dm explain {}` for details.", event.activity),
+            ),
+        };
+        problems.push(RecentProblem {
+            code,
+            activity: event.activity.clone(),
+            count: occurrences,
+            summary: title,
+            suggestion,
+            last_seen: event.timestamp.clone(),
+        });
+    }
+
+    for problem in &mut problems {
+        problem.summary = format!(
+            "{} failed {}×: {}",
+            short_activity(&problem.activity),
+            problem.count,
+            problem.summary
+        );
+    }
+    problems.sort_by_key(|problem| std::cmp::Reverse(problem.count));
+    problems
+}
+
+/// Most recent error-level events for `activity`, newest first.
+fn recent_failures(home: &Path, activity: &str) -> Vec<Event> {
+    let Ok(store) = EventStore::open(home) else {
+        return Vec::new();
+    };
+    store
+        .query(&EventFilter {
+            activity: Some(activity.to_string()),
+            level: Some("error".to_string()),
+            limit: Some(RECENT_FAILURE_SCAN),
+            ..Default::default()
+        })
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|event| event.activity == activity)
+        .collect()
+}
+
+fn classify(event: &Event) -> Option<&'static Topic> {
+    let message = event.message.as_deref()?.to_lowercase();
+    TOPICS.iter().find(|topic| {
+        topic.activity == event.activity
+            && topic
+                .patterns
+                .iter()
+                .any(|pattern| message.contains(pattern))
+    })
+}
+
+fn build_explanation(topic: &Topic, event: Option<&Event>) -> Explanation {
+    Explanation {
+        code: topic.code.to_string(),
+        title: topic.title.to_string(),
+        summary: topic.summary.to_string(),
+        steps: topic.steps.iter().map(|step| step.to_string()).collect(),
+        diagnosis: event.map(|event| diagnose(topic, event)),
+        event_id: event.map(|event| event.id),
+        event_at: event.map(|event| event.timestamp.clone()),
+    }
+}
+
+fn diagnose(topic: &Topic, event: &Event) -> String {
+    if topic.code == "runtime.port_in_use" {
+        if let Some(port) = event.message.as_deref().and_then(extract_port) {
+            return match port_holder(port) {
+                Some(pid) => format!(
+                    "Last `{}` failed because port {} is held by pid {}.",
+                    short_activity(&event.activity),
+                    port,
+                    pid
+                ),
+                None => format!(
+                    "Last `{}` failed because port {} was in use (no process holds it now; retrying may work).",
+                    short_activity(&event.activity),
+                    port
+                ),
+            };
+        }
+    }
+    describe_failure(event)
+}
+
+fn describe_failure(event: &Event) -> String {
+    format!(
+        "Last `{}` failed at {}: {}",
+        short_activity(&event.activity),
+        event.timestamp,
+        event.message.as_deref().unwrap_or("(no message)").trim()
+    )
+}
+
+fn short_activity(activity: &str) -> &str {
+    activity.rsplit('.').next().unwrap_or(activity)
+}
+
+/// Pull a port number out of messages like `bind 0.0.0.0:53290: Address
+/// already in use` or `port 6012 is already in use`.
+pub(crate) fn extract_port(message: &str) -> Option<u16> {
+    let lower = message.to_lowercase();
+    let after_marker = |marker: &str| {
+        lower.match_indices(marker).find_map(|(idx, _)| {
+            let digits: String = lower[idx + marker.len()..]
+                .chars()
+                .take_while(char::is_ascii_digit)
+                .collect();
+            digits.parse::<u16>().ok().filter(|port| *port > 0)
+        })
+    };
+    after_marker("port ").or_else(|| after_marker(":"))
+}
+
+#[cfg(unix)]
+fn port_holder(port: u16) -> Option<u32> {
+    let output = std::process::Command::new("lsof")
+        .args(["-nP", "-t", &format!("-iTCP:{}", port), "-sTCP:LISTEN"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.trim().parse().ok())
+}
+
+#[cfg(not(unix))]
+fn port_holder(_port: u16) -> Option<u32> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{EventBuilder, EventLevel, EventSource};
+
+    fn emit_failure(home: &Path, activity: &str, message: &str) {
+        EventStore::open(home)
+            .unwrap()
+            .emit(
+                &EventBuilder::new(EventSource::Core, activity)
+                    .case_id("case")
+                    .level(EventLevel::Error)
+                    .message(message)
+                    .build(),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn extract_port_handles_socket_addresses_and_prose() {
+        assert_eq!(
+            extract_port("failed to bind 0.0.0.0:53290: Address already in use"),
+            Some(53290)
+        );
+        assert_eq!(extract_port("Port 6012 is already in use"), Some(6012));
+        assert_eq!(extract_port("address already in use"), None);
+    }
+
+    #[test]
+    fn explain_known_code_without_events_returns_static_guidance() {
+        let tmp = tempfile::tempdir().unwrap();
+        let explanation = explain(tmp.path(), "runtime.timeout").unwrap();
+
+        assert_eq!(explanation.code, "runtime.timeout");
+        assert!(!explanation.steps.is_empty());
+        assert!(explanation.diagnosis.is_none());
+    }
+
+    #[test]
+    fn explain_activity_classifies_latest_failure() {
+        let tmp = tempfile::tempdir().unwrap();
+        emit_failure(
+            tmp.path(),
+            "runtime.up",
+            "Timed out waiting for dora runtime to start.",
+        );
+        emit_failure(
+            tmp.path(),
+            "runtime.up",
+            "failed to bind 127.0.0.1:1: Address already in use (os error 98)",
+        );
+
+        let explanation = explain(tmp.path(), "runtime.up").unwrap();
+
+        assert_eq!(explanation.code, "runtime.port_in_use");
+        assert!(explanation.diagnosis.unwrap().contains("port 1"));
+    }
+
+    #[test]
+    fn explain_code_picks_matching_event_not_just_latest() {
+        let tmp = tempfile::tempdir().unwrap();
+        emit_failure(
+            tmp.path(),
+            "node.install",
+            "Failed to install package: opencv-python",
+        );
+        emit_failure(
+            tmp.path(),
+            "node.install",
+            "Node 'x' not found. Download or create it first.",
+        );
+
+        let explanation = explain(tmp.path(), "node.pip_failed").unwrap();
+
+        assert!(explanation
+            .diagnosis
+            .unwrap()
+            .contains("Failed to install package"));
+    }
+
+    #[test]
+    fn explain_unknown_query_errors() {
+        let tmp = tempfile::tempdir().unwrap();
+        let err = explain(tmp.path(), "bogus.thing").unwrap_err();
+        assert!(err.to_string().contains("dm explain"));
+    }
+
+    #[test]
+    fn recent_problems_groups_failures_by_cause() {
+        let tmp = tempfile::tempdir().unwrap();
+        for _ in 0..3 {
+            emit_failure(
+                tmp.path(),
+                "version.install",
+                "GitHub API rate limit exceeded for 1.2.3.4",
+            );
+        }
+        emit_failure(tmp.path(), "dataflow.start", "dataflow 'demo' is invalid");
+
+        let problems = recent_problems(tmp.path(), "1970-01-01T00:00:00Z");
+
+        assert_eq!(problems.len(), 2);
+        assert_eq!(problems[0].code, "install.rate_limit");
+        assert_eq!(problems[0].count, 3);
+        assert!(problems[0].summary.starts_with("install failed 3×"));
+        assert!(problems[0].suggestion.contains("GITHUB_TOKEN"));
+        assert_eq!(problems[1].code, "dataflow.start");
+        assert!(problems[1].suggestion.contains("dm explain dataflow.start"));
+
+        assert!(recent_problems(tmp.path(), "2999-01-01T00:00:00Z").is_empty());
+    }
+}
@@ -0,0 +1,73 @@
+//! Remote dora daemons (`[machines]` in config.toml) for deployments whose
+//! nodes run on several machines. A machine is registered under the id its
+//! daemon was started with (`dora daemon --machine-id`) together with the
+//! address it is reachable at, and shows up in `dm status`.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+
+use crate::events::{EventSource, OperationEvent};
+use crate::types::MachineStatus;
+use crate::{config, dora};
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Registered machines, by id.
+pub fn machines(home: &Path) -> Result<BTreeMap<String, String>> {
+    Ok(config::load_config(home)?.machines)
+}
+
+/// Register machine `name` at `addr` (`IP` or `IP:PORT`), replacing any
+/// previous address.
+pub fn add_machine(home: &Path, name: &str, addr: &str) -> Result<()> {
+    let op = OperationEvent::new(home, EventSource::Core, "machine.add")
+        .attr("machine", name)
+        .attr("addr", addr);
+    op.emit_start();
+
+    let result = (|| {
+        let name = name.trim();
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            bail!(
+                "Invalid machine name '{}': must be non-empty without whitespace",
+                name
+            );
+        }
+        dora::parse_machine_addr(addr)?;
+        config::modify_config(home, |cfg| {
+            cfg.machines
+                .insert(name.to_string(), addr.trim().to_string());
+            Ok(())
+        })
+    })();
+
+    op.emit_result(&result);
+    result
+}
+
+/// Every registered machine, probed over TCP when its address has a port.
+pub async fn machine_statuses(home: &Path) -> Vec<MachineStatus> {
+    let machines = machines(home).unwrap_or_default();
+    futures_util::future::join_all(machines.into_iter().map(|(name, addr)| async move {
+        let reachable = match dora::parse_machine_addr(&addr) {
+            Ok((ip, Some(port))) => Some(probe((ip, port)).await),
+            _ => None,
+        };
+        MachineStatus {
+            name,
+            addr,
+            reachable,
+        }
+    }))
+    .await
+}
+
+async fn probe(addr: (std::net::IpAddr, u16)) -> bool {
+    matches!(
+        tokio::time::timeout(PROBE_TIMEOUT, tokio::net::TcpStream::connect(addr)).await,
+        Ok(Ok(_))
+    )
+}
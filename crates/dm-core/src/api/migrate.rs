@@ -0,0 +1,415 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde_json::{json, Map, Value};
+
+use crate::events::{EventSource, OperationEvent};
+use crate::types::*;
+use crate::{config, node};
+
+/// Upgrade node metadata, config and dataflow layout written by older dm
+/// versions.
+///
+/// Every file is copied to `<home>/backups/migrate-<timestamp>/` before it
+/// is rewritten. With `dry_run` nothing is touched and the report lists what
+/// would change. Running it again on a migrated home is a no-op.
+pub fn migrate(home: &Path, dry_run: bool) -> Result<MigrationReport> {
+    let op = OperationEvent::new(home, EventSource::Core, "home.migrate").attr("dry_run", dry_run);
+    op.emit_start();
+
+    let result = (|| {
+        let mut migrator = Migrator::new(home, dry_run);
+        migrate_nodes(&mut migrator)?;
+        migrate_config(&mut migrator)?;
+        migrate_dataflows(&mut migrator)?;
+        Ok(migrator.into_report())
+    })();
+
+    op.emit_result(&result);
+    result
+}
+
+struct Migrator<'a> {
+    home: &'a Path,
+    dry_run: bool,
+    backup_dir: PathBuf,
+    backed_up: bool,
+    changes: Vec<MigrationChange>,
+}
+
+impl<'a> Migrator<'a> {
+    fn new(home: &'a Path, dry_run: bool) -> Self {
+        let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ");
+        Self {
+            home,
+            dry_run,
+            backup_dir: home.join("backups").join(format!("migrate-{}", stamp)),
+            backed_up: false,
+            changes: Vec::new(),
+        }
+    }
+
+    fn relative(&self, path: &Path) -> PathBuf {
+        path.strip_prefix(self.home).unwrap_or(path).to_path_buf()
+    }
+
+    fn record(&mut self, path: &Path, description: impl Into<String>) {
+        self.changes.push(MigrationChange {
+            path: self.relative(path).display().to_string(),
+            description: description.into(),
+        });
+    }
+
+    /// Copy `path` into the backup directory, keeping its home-relative path.
+    fn backup(&mut self, path: &Path) -> Result<()> {
+        if self.dry_run || !path.exists() {
+            return Ok(());
+        }
+        let target = self.backup_dir.join(self.relative(path));
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::copy(path, &target)
+            .with_context(|| format!("Failed to back up {}", path.display()))?;
+        self.backed_up = true;
+        Ok(())
+    }
+
+    fn write(&mut self, path: &Path, content: &str) -> Result<()> {
+        if self.dry_run {
+            return Ok(());
+        }
+        self.backup(path)?;
+        std::fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    fn into_report(self) -> MigrationReport {
+        MigrationReport {
+            dry_run: self.dry_run,
+            backup_dir: self
+                .backed_up
+                .then(|| self.backup_dir.display().to_string()),
+            changes: self.changes,
+        }
+    }
+}
+
+fn migrate_nodes(m: &mut Migrator) -> Result<()> {
+    let dir = node::nodes_dir(m.home);
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Ok(());
+    };
+    let mut node_dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.join("dm.json").is_file())
+        .collect();
+    node_dirs.sort();
+
+    for node_dir in node_dirs {
+        let id = node_dir
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let meta_path = node_dir.join("dm.json");
+        let content = std::fs::read_to_string(&meta_path)
+            .with_context(|| format!("Failed to read {}", meta_path.display()))?;
+        let Ok(mut meta) = serde_json::from_str::<Value>(&content) else {
+            m.record(&meta_path, "not valid JSON; left untouched");
+            continue;
+        };
+
+        let notes = upgrade_node_meta(&id, &node_dir, &mut meta);
+        if notes.is_empty() {
+            continue;
+        }
+        let json = serde_json::to_string_pretty(&meta).context("Failed to serialize dm.json")?;
+        m.write(&meta_path, &json)?;
+        for note in notes {
+            m.record(&meta_path, note);
+        }
+    }
+    Ok(())
+}
+
+/// Bring a raw `dm.json` document up to the current schema.
+///
+/// Returns one note per applied fix; an empty list means it was current.
+pub(crate) fn upgrade_node_meta(id: &str, node_dir: &Path, meta: &mut Value) -> Vec<String> {
+    let Some(obj) = meta.as_object_mut() else {
+        return Vec::new();
+    };
+    let mut notes = Vec::new();
+
+    if obj.get("id").and_then(Value::as_str) != Some(id) {
+        obj.insert("id".to_string(), json!(id));
+        notes.push(format!("set id to '{}'", id));
+    }
+    for (key, default) in [
+        ("version", json!("")),
+        ("installed_at", json!("")),
+        ("source", json!({ "build": "", "github": null })),
+    ] {
+        if !obj.contains_key(key) {
+            obj.insert(key.to_string(), default);
+            notes.push(format!("added missing '{}'", key));
+        }
+    }
+    if let Some(source) = obj.get_mut("source").and_then(Value::as_object_mut) {
+        if !source.contains_key("build") {
+            source.insert("build".to_string(), json!(""));
+            notes.push("added missing 'source.build'".to_string());
+        }
+    }
+
+    if let Some(secs) = obj.get("installed_at").and_then(normalize_timestamp) {
+        notes.push(format!(
+            "converted installed_at {} to unix seconds {}",
+            obj["installed_at"], secs
+        ));
+        obj.insert("installed_at".to_string(), json!(secs));
+    }
+
+    let executable = obj
+        .get("executable")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    if executable.is_empty() {
+        if let Some(found) = infer_executable(id, node_dir, obj) {
+            notes.push(format!("inferred executable '{}'", found));
+            obj.insert("executable".to_string(), json!(found));
+        }
+    } else if Path::new(&executable).is_absolute() {
+        if let Some(relative) = relativize_executable(id, node_dir, &executable) {
+            notes.push(format!(
+                "rewrote executable '{}' relative to the node: '{}'",
+                executable, relative
+            ));
+            obj.insert("executable".to_string(), json!(relative));
+        }
+    }
+
+    notes
+}
+
+/// Returns unix seconds when `value` is in an older timestamp format
+/// (JSON number, milliseconds, RFC 3339 or `YYYY-MM-DD HH:MM:SS`).
+fn normalize_timestamp(value: &Value) -> Option<String> {
+    let from_number = |n: i64| if n >= 1_000_000_000_000 { n / 1000 } else { n };
+    match value {
+        Value::Number(n) => n.as_i64().map(|n| from_number(n).to_string()),
+        Value::String(s) if s.is_empty() => None,
+        Value::String(s) if s.chars().all(|c| c.is_ascii_digit()) => {
+            let n: i64 = s.parse().ok()?;
+            (from_number(n) != n).then(|| from_number(n).to_string())
+        }
+        Value::String(s) => chrono::DateTime::parse_from_rfc3339(s)
+            .map(|ts| ts.timestamp())
+            .or_else(|_| {
+                chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+                    .map(|ts| ts.and_utc().timestamp())
+            })
+            .ok()
+            .map(|secs| secs.to_string()),
+        _ => None,
+    }
+}
+
+/// Older dm versions left `executable` empty; look for the usual locations.
+fn infer_executable(id: &str, node_dir: &Path, meta: &Map<String, Value>) -> Option<String> {
+    let build = meta
+        .get("source")
+        .and_then(|source| source.get("build"))
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let tokens: Vec<&str> = build.split_whitespace().collect();
+    let package = if tokens.contains(&"install") {
+        tokens.last().map(|last| {
+            last.split(|c: char| "<>=!~[;@".contains(c))
+                .next()
+                .unwrap_or(last)
+                .to_string()
+        })
+    } else {
+        None
+    };
+
+    let mut candidates = vec![format!(".venv/bin/{}", id)];
+    if let Some(package) = package.filter(|p| !p.starts_with('.') && !p.starts_with('-')) {
+        candidates.push(format!(".venv/bin/{}", package));
+    }
+    candidates.push(format!("bin/{}", id));
+    candidates.push(format!("bin/dora-{}", id));
+
+    candidates
+        .into_iter()
+        .find(|candidate| node_dir.join(candidate).is_file())
+}
+
+/// Absolute executables recorded under an old home (e.g. `~/.dora/nodes/<id>/...`)
+/// are rewritten relative to the node directory when the file exists there.
+fn relativize_executable(id: &str, node_dir: &Path, executable: &str) -> Option<String> {
+    let path = Path::new(executable);
+    if let Ok(rest) = path.strip_prefix(node_dir) {
+        return Some(rest.display().to_string());
+    }
+    let marker = format!("/nodes/{}/", id);
+    let idx = executable.find(&marker)?;
+    let rest = &executable[idx + marker.len()..];
+    node_dir.join(rest).is_file().then(|| rest.to_string())
+}
+
+fn migrate_config(m: &mut Migrator) -> Result<()> {
+    let path = config::config_path(m.home);
+    if !path.exists() {
+        return Ok(());
+    }
+    let mut cfg = config::load_config(m.home)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    // Early releases stored tags like `v0.4.1`; versions/ uses bare semver.
+    let Some(active) = cfg.active_version.clone() else {
+        return Ok(());
+    };
+    let bare = active.trim_start_matches('v');
+    let versions = config::versions_dir(m.home);
+    if bare != active && !versions.join(&active).exists() && versions.join(bare).exists() {
+        cfg.active_version = Some(bare.to_string());
+        let content = toml::to_string_pretty(&cfg).context("Failed to serialize config")?;
+        m.write(&path, &content)?;
+        m.record(
+            &path,
+            format!("active_version '{}' renamed to '{}'", active, bare),
+        );
+    }
+    Ok(())
+}
+
+fn migrate_dataflows(m: &mut Migrator) -> Result<()> {
+    let dir = crate::dataflow::dataflows_dir(m.home);
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Ok(());
+    };
+    let mut legacy: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && matches!(
+                    path.extension().and_then(|ext| ext.to_str()),
+                    Some("yml" | "yaml")
+                )
+        })
+        .collect();
+    if legacy.is_empty() {
+        return Ok(());
+    }
+    legacy.sort();
+
+    for path in &legacy {
+        m.backup(path)?;
+        let name = path.file_stem().unwrap_or_default().to_string_lossy();
+        m.record(
+            path,
+            format!("moved into project layout dataflows/{}/dataflow.yml", name),
+        );
+    }
+    if !m.dry_run {
+        crate::dataflow::migrate_legacy_layout(m.home)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_legacy_node(home: &Path) -> PathBuf {
+        let node_dir = node::node_dir(home, "old-node");
+        std::fs::create_dir_all(node_dir.join(".venv/bin")).unwrap();
+        std::fs::write(node_dir.join(".venv/bin/dora-old-node"), "#!/bin/sh\n").unwrap();
+        std::fs::write(
+            node_dir.join("dm.json"),
+            json!({
+                "id": "old-node",
+                "version": "0.1.0",
+                "installed_at": 1700000000123i64,
+                "source": { "build": "pip install dora-old-node" }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        node_dir
+    }
+
+    #[test]
+    fn migrate_upgrades_node_metadata_with_backup() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        let node_dir = write_legacy_node(home);
+
+        let report = migrate(home, false).unwrap();
+
+        assert!(!report.dry_run);
+        assert_eq!(report.changes.len(), 2, "{:?}", report.changes);
+        let meta: node::Node =
+            serde_json::from_str(&std::fs::read_to_string(node_dir.join("dm.json")).unwrap())
+                .unwrap();
+        assert_eq!(meta.installed_at, "1700000000");
+        assert_eq!(meta.executable, ".venv/bin/dora-old-node");
+
+        let backup = PathBuf::from(report.backup_dir.unwrap()).join("nodes/old-node/dm.json");
+        assert!(std::fs::read_to_string(backup)
+            .unwrap()
+            .contains("1700000000123"));
+
+        // A migrated home is left alone.
+        assert!(migrate(home, false).unwrap().changes.is_empty());
+    }
+
+    #[test]
+    fn migrate_dry_run_reports_without_writing() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        let node_dir = write_legacy_node(home);
+        std::fs::create_dir_all(home.join("dataflows")).unwrap();
+        std::fs::write(home.join("dataflows/demo.yml"), "nodes: []\n").unwrap();
+        let before = std::fs::read_to_string(node_dir.join("dm.json")).unwrap();
+
+        let report = migrate(home, true).unwrap();
+
+        assert!(report.dry_run);
+        assert!(report.backup_dir.is_none());
+        assert!(report
+            .changes
+            .iter()
+            .any(|c| c.path == "dataflows/demo.yml"));
+        assert_eq!(
+            std::fs::read_to_string(node_dir.join("dm.json")).unwrap(),
+            before
+        );
+        assert!(home.join("dataflows/demo.yml").exists());
+    }
+
+    #[test]
+    fn upgrade_node_meta_relativizes_old_home_paths() {
+        let tmp = tempfile::tempdir().unwrap();
+        let node_dir = tmp.path().join("nodes/cam");
+        std::fs::create_dir_all(node_dir.join("bin")).unwrap();
+        std::fs::write(node_dir.join("bin/dora-cam"), "").unwrap();
+        let mut meta = json!({
+            "id": "cam",
+            "version": "1.0.0",
+            "installed_at": "2024-05-01T10:00:00Z",
+            "source": { "build": "cargo install dora-cam" },
+            "executable": "/home/someone/.dora/nodes/cam/bin/dora-cam"
+        });
+
+        let notes = upgrade_node_meta("cam", &node_dir, &mut meta);
+
+        assert_eq!(notes.len(), 2, "{:?}", notes);
+        assert_eq!(meta["executable"], "bin/dora-cam");
+        assert_eq!(meta["installed_at"], "1714557600");
+    }
+}
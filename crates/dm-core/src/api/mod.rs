@@ -1,11 +1,34 @@
+mod alias;
 mod doctor;
+mod explain;
+mod machine;
+mod migrate;
+mod rules;
 mod runtime;
 mod setup;
+mod supervise;
+mod thresholds;
 mod version;
 
-pub use doctor::doctor;
+pub(crate) use alias::resolve_release_version;
+pub use alias::{
+    remove_version_alias, resolve_installed_version, set_version_alias, version_aliases,
+    BUILTIN_ALIASES, RESERVED_ALIASES,
+};
+pub use doctor::{
+    check_nodes, doctor, doctor_with_history, doctor_with_options, probe_nodes, DoctorOptions,
+};
+pub use explain::{explain, explain_topics};
+pub use machine::{add_machine, machine_statuses, machines};
+pub use migrate::migrate;
+pub use rules::{matching_rules, run_rule};
 pub use runtime::{
-    auto_down_if_idle, down, ensure_runtime_up, is_runtime_running, passthrough, status, up,
+    auto_down_if_idle, down, ensure_runtime_up, is_runtime_running, passthrough, ps, status, up,
+    up_with_env,
 };
 pub use setup::setup;
-pub use version::{uninstall, use_version, versions};
+pub use supervise::{
+    RuntimeSupervisor, SupervisorAction, SupervisorOptions, RUNTIME_RESTART_ACTIVITY,
+};
+pub use thresholds::{check_thresholds, emit_threshold_alerts};
+pub use version::{uninstall, use_version, versions, versions_with};
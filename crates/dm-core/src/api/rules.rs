@@ -0,0 +1,239 @@
+//! `[[rules]]`: event-driven automations.
+//!
+//! dm-server feeds every event it stores through [`matching_rules`] and runs
+//! the actions of the rules that match with [`run_rule`]. Each run is
+//! recorded as a `rule.run` operation, and `rule.*` events never trigger
+//! rules, so a rule cannot feed itself.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use crate::config::{RuleAction, RuleConfig, RuleTrigger};
+use crate::events::{Event, EventSource, OperationEvent};
+use crate::runs::{RunSource, StartConflictStrategy};
+
+/// Enabled rules whose trigger matches `event`.
+pub fn matching_rules<'a>(rules: &'a [RuleConfig], event: &Event) -> Vec<&'a RuleConfig> {
+    if event.activity.starts_with("rule.") {
+        return Vec::new();
+    }
+    rules
+        .iter()
+        .filter(|rule| rule.enabled && trigger_matches(&rule.when, event))
+        .collect()
+}
+
+fn trigger_matches(trigger: &RuleTrigger, event: &Event) -> bool {
+    let eq = |want: &Option<String>, have: &str| want.as_deref().is_none_or(|w| w == have);
+    let activity = trigger
+        .activity
+        .as_deref()
+        .is_none_or(|want| match want.strip_suffix('*') {
+            Some(prefix) => event.activity.starts_with(prefix),
+            None => event.activity == want,
+        });
+    activity
+        && eq(&trigger.level, &event.level)
+        && eq(&trigger.source, &event.source)
+        && eq(&trigger.case_id, &event.case_id)
+        && trigger
+            .node_id
+            .as_deref()
+            .is_none_or(|node| event.node_id.as_deref() == Some(node))
+        && attrs_match(trigger, event)
+}
+
+fn attrs_match(trigger: &RuleTrigger, event: &Event) -> bool {
+    if trigger.attrs.is_empty() {
+        return true;
+    }
+    let Some(attrs) = event
+        .attributes
+        .as_deref()
+        .and_then(|raw| serde_json::from_str::<serde_json::Value>(raw).ok())
+    else {
+        return false;
+    };
+    trigger
+        .attrs
+        .iter()
+        .all(|(key, want)| match attrs.get(key) {
+            Some(serde_json::Value::String(have)) => have == want,
+            Some(other) => want
+                .parse::<serde_json::Value>()
+                .is_ok_and(|want| &want == other),
+            None => false,
+        })
+}
+
+/// Run the action of `rule`, fired by `event`. Returns a short description
+/// of what was done.
+pub async fn run_rule(home: &Path, rule: &RuleConfig, event: &Event) -> Result<String> {
+    let op = OperationEvent::new(home, EventSource::Core, "rule.run")
+        .attr("rule", &rule.name)
+        .attr("action", &rule.then)
+        .attr("trigger_event_id", event.id)
+        .attr("trigger_activity", &event.activity);
+    op.emit_start();
+
+    let result = run_action(home, &rule.then).await;
+
+    op.emit_result(&result);
+    result
+}
+
+async fn run_action(home: &Path, action: &RuleAction) -> Result<String> {
+    match action {
+        RuleAction::Up => {
+            let result = super::up(home, false).await?;
+            if !result.success {
+                bail!("dm up failed: {}", result.message);
+            }
+            Ok(result.message)
+        }
+        RuleAction::Down => {
+            let result = super::down(home, false).await?;
+            if !result.success {
+                bail!("dm down failed: {}", result.message);
+            }
+            Ok(result.message)
+        }
+        RuleAction::StartDataflow { dataflow } => {
+            let project = crate::dataflow::get(home, dataflow)?;
+            super::ensure_runtime_up(home, false).await?;
+            let started = crate::runs::start_run_from_yaml_with_source_and_strategy(
+                home,
+                &project.yaml,
+                dataflow,
+                None,
+                RunSource::Server,
+                StartConflictStrategy::Fail,
+            )
+            .await?;
+            Ok(format!("started run {}", started.run.run_id))
+        }
+        RuleAction::StopDataflow { dataflow } => {
+            let runs: Vec<_> = crate::runs::list_active_runs(home)?
+                .into_iter()
+                .filter(|run| run.dataflow_name == *dataflow)
+                .collect();
+            for run in &runs {
+                crate::runs::stop_run(home, &run.run_id).await?;
+            }
+            Ok(format!("stopped {} run(s) of {}", runs.len(), dataflow))
+        }
+        RuleAction::Dm { args } => {
+            let output = tokio::process::Command::new(crate::util::dm_cli_exe(home))
+                .arg("--home")
+                .arg(home)
+                .args(args)
+                .output()
+                .await
+                .context("Failed to run the dm CLI")?;
+            if !output.status.success() {
+                bail!(
+                    "dm {} failed: {}",
+                    args.join(" "),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                );
+            }
+            Ok(format!("ran dm {}", args.join(" ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventBuilder;
+
+    fn rule(name: &str, when: RuleTrigger) -> RuleConfig {
+        RuleConfig {
+            name: name.to_string(),
+            when,
+            then: RuleAction::Up,
+            cooldown_secs: 0,
+            enabled: true,
+        }
+    }
+
+    #[test]
+    fn matches_activity_level_and_attrs() {
+        let rules = vec![
+            rule(
+                "runtime",
+                RuleTrigger {
+                    activity: Some("runtime.down".into()),
+                    level: Some("error".into()),
+                    ..Default::default()
+                },
+            ),
+            rule(
+                "camera-finished",
+                RuleTrigger {
+                    activity: Some("run.*".into()),
+                    attrs: [("dataflow".to_string(), "camera".to_string())].into(),
+                    ..Default::default()
+                },
+            ),
+        ];
+
+        let failed_down = EventBuilder::new(EventSource::Core, "runtime.down")
+            .level(crate::events::EventLevel::Error)
+            .build();
+        let names = |event: &Event| -> Vec<String> {
+            matching_rules(&rules, event)
+                .into_iter()
+                .map(|r| r.name.clone())
+                .collect()
+        };
+        assert_eq!(names(&failed_down), vec!["runtime"]);
+
+        let ok_down = EventBuilder::new(EventSource::Core, "runtime.down").build();
+        assert!(names(&ok_down).is_empty());
+
+        let finished = |dataflow: &str| {
+            EventBuilder::new(EventSource::Core, "run.finished")
+                .attr("dataflow", dataflow)
+                .build()
+        };
+        assert_eq!(names(&finished("camera")), vec!["camera-finished"]);
+        assert!(names(&finished("lidar")).is_empty());
+    }
+
+    #[test]
+    fn rule_events_and_disabled_rules_never_match() {
+        let mut rules = vec![rule("any", RuleTrigger::default())];
+        let audit = EventBuilder::new(EventSource::Core, "rule.run").build();
+        assert!(matching_rules(&rules, &audit).is_empty());
+
+        let event = EventBuilder::new(EventSource::Core, "runtime.up").build();
+        assert_eq!(matching_rules(&rules, &event).len(), 1);
+        rules[0].enabled = false;
+        assert!(matching_rules(&rules, &event).is_empty());
+    }
+
+    #[test]
+    fn empty_attr_matches_only_events_that_carry_it() {
+        let rules = vec![rule(
+            "no-reason",
+            RuleTrigger {
+                attrs: [("reason".to_string(), String::new())].into(),
+                ..Default::default()
+            },
+        )];
+
+        let empty = EventBuilder::new(EventSource::Core, "run.finished")
+            .attr("reason", "")
+            .build();
+        assert_eq!(matching_rules(&rules, &empty).len(), 1);
+
+        let missing = EventBuilder::new(EventSource::Core, "run.finished").build();
+        assert!(matching_rules(&rules, &missing).is_empty());
+        let other = EventBuilder::new(EventSource::Core, "run.finished")
+            .attr("reason", "crash")
+            .build();
+        assert!(matching_rules(&rules, &other).is_empty());
+    }
+}
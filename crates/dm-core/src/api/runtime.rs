@@ -1,42 +1,56 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use anyhow::{Context, Result};
 
+use crate::error::DmError;
 use crate::events::{EventSource, OperationEvent};
 use crate::runs::RunInstance;
-use crate::{config, dora, types::*};
+use crate::{config, dora, runtime_pids, types::*};
 
 /// Get runtime status overview
 pub async fn status(home: &Path, verbose: bool) -> Result<StatusReport> {
     let cfg = config::load_config(home)?;
     let dm_home = home.display().to_string();
+    let identity = cfg.identity();
 
-    if cfg.active_version.is_none() {
+    if cfg.active_version.is_none() && !dora::mock_enabled() {
         return Ok(StatusReport {
             active_version: None,
             actual_version: None,
             dm_home,
+            robot_id: identity.robot_id,
+            labels: identity.labels,
             runtime_running: false,
             runtime_output: String::new(),
             active_runs: Vec::new(),
             recent_runs: Vec::new(),
+            untracked_dataflows: Vec::new(),
             dora_probe: Vec::new(),
+            processes: runtime_pids::inspect(home).await,
+            coordinator_addr: cfg.coordinator_addr,
+            machines: super::machine_statuses(home).await,
         });
     }
 
-    let ver = cfg.active_version.as_ref().unwrap().clone();
-    let bin = config::versions_dir(home).join(&ver);
+    // Under the mock runtime there may be no installed version at all.
+    let ver = cfg.active_version.as_deref().unwrap_or_default();
+    let bin = config::versions_dir(home).join(ver);
     let dora_bin = config::dora_bin_path(&bin);
 
     let check_args = vec!["check".to_string()];
-    let list_args = vec!["list".to_string()];
-    let (version_result, check_result, list_result) = tokio::join!(
+    let (version_result, check_result, list_result, processes, machines) = tokio::join!(
         dora::get_dora_version(&dora_bin),
         dora::run_dora(home, &check_args, verbose),
-        dora::run_dora(home, &list_args, verbose),
+        dora::run_dora_list(home, verbose),
+        runtime_pids::inspect(home),
+        super::machine_statuses(home),
     );
 
-    let actual_version = version_result.ok();
+    let actual_version = match dora::mocked(home, &["--version".to_string()]) {
+        Some((_, stdout, _)) => stdout.split_whitespace().last().map(str::to_string),
+        None => version_result.ok(),
+    };
 
     let (runtime_running, runtime_output) = match check_result {
         Ok((code, stdout, stderr)) => (
@@ -67,30 +81,59 @@ pub async fn status(home: &Path, verbose: bool) -> Result<StatusReport> {
         .cloned()
         .map(to_status_run_entry)
         .collect();
-    let dora_probe = if verbose {
-        match list_result {
-            Ok((0, stdout, _)) => build_dora_probe(&stdout, &runs),
-            _ => Vec::new(),
-        }
+    let probe = match list_result {
+        Ok((0, stdout, _)) => build_dora_probe(dora::parse_runtime_infos(&stdout), &runs),
+        _ => Vec::new(),
+    };
+    let untracked_dataflows = if runtime_running {
+        probe
+            .iter()
+            .filter(|item| item.run_id.is_none())
+            .cloned()
+            .collect()
     } else {
         Vec::new()
     };
+    let dora_probe = if verbose { probe } else { Vec::new() };
 
     Ok(StatusReport {
-        active_version: Some(ver),
+        active_version: cfg.active_version,
         actual_version,
         dm_home,
+        robot_id: identity.robot_id,
+        labels: identity.labels,
         runtime_running,
         runtime_output,
         active_runs,
         recent_runs,
+        untracked_dataflows,
         dora_probe,
+        processes,
+        coordinator_addr: cfg.coordinator_addr,
+        machines,
     })
 }
 
-fn build_dora_probe(stdout: &str, runs: &[RunInstance]) -> Vec<RuntimeDataflowStatus> {
-    let runtime_infos = dora::parse_runtime_infos(stdout);
+/// Dataflows currently running in dora with their nodes, whether or not a
+/// dm run tracks them (`dm ps`).
+pub async fn ps(home: &Path, verbose: bool) -> Result<Vec<DataflowProcess>> {
+    let infos = dora::list_dataflows(home, verbose).await?;
+    let runs = crate::runs::list_run_instances(home).unwrap_or_default();
 
+    Ok(build_dora_probe(infos, &runs)
+        .into_iter()
+        .filter(|item| item.status.eq_ignore_ascii_case("running"))
+        .map(|dataflow| DataflowProcess {
+            nodes: crate::runs::list_dataflow_nodes(home, &dataflow.id).unwrap_or_default(),
+            dataflow,
+        })
+        .collect())
+}
+
+fn build_dora_probe(
+    runtime_infos: Vec<dora::DataflowRuntimeInfo>,
+    runs: &[RunInstance],
+) -> Vec<RuntimeDataflowStatus> {
     runtime_infos
         .into_iter()
         .map(|item| {
@@ -113,13 +156,25 @@ fn build_dora_probe(stdout: &str, runs: &[RunInstance]) -> Vec<RuntimeDataflowSt
                     .unwrap_or(0),
                 cpu: item.cpu,
                 memory: item.memory,
+                run_id: run.map(|run| run.run_id.clone()),
+                source: run.map(|run| run.source.as_str().to_string()),
             }
         })
         .collect()
 }
 
 fn to_status_run_entry(run: RunInstance) -> StatusRunEntry {
+    let uptime_secs = if run.status.is_running() {
+        chrono::DateTime::parse_from_rfc3339(&run.started_at)
+            .ok()
+            .map(|started| (chrono::Utc::now() - started.with_timezone(&chrono::Utc)).num_seconds())
+            .map(|secs| secs.max(0))
+    } else {
+        None
+    };
     StatusRunEntry {
+        source: run.source.as_str().to_string(),
+        uptime_secs,
         run_id: run.run_id,
         dataflow_name: run.dataflow_name,
         status: run.status.as_str().to_string(),
@@ -134,16 +189,40 @@ fn to_status_run_entry(run: RunInstance) -> StatusRunEntry {
 
 /// Start dora coordinator + daemon
 pub async fn up(home: &Path, verbose: bool) -> Result<RuntimeResult> {
-    let op = OperationEvent::new(home, EventSource::Core, "runtime.up");
+    up_with_env(home, verbose, &BTreeMap::new(), None).await
+}
+
+/// Start dora coordinator + daemon with extra environment on top of the
+/// configured `runtime_env`. `inherit_env` overrides `runtime_inherit_env`.
+/// The coordinator and daemon pass this environment on to spawned nodes.
+pub async fn up_with_env(
+    home: &Path,
+    verbose: bool,
+    env: &BTreeMap<String, String>,
+    inherit_env: Option<bool>,
+) -> Result<RuntimeResult> {
+    let op = OperationEvent::new(home, EventSource::Core, "runtime.up")
+        .attr("env_keys", env.keys().collect::<Vec<_>>());
     op.emit_start();
 
     let result = async {
+        if let Some((code, stdout, stderr)) = dora::mocked(home, &["up".to_string()]) {
+            return Ok(RuntimeResult {
+                success: code == 0,
+                message: if code == 0 { stdout } else { stderr }.trim().to_string(),
+            });
+        }
         let bin = dora::active_dora_bin(home)?;
         if verbose {
             eprintln!("[dm] exec: {} up", bin.display());
         }
 
-        let mut child = tokio::process::Command::new(&bin)
+        let mut cmd = tokio::process::Command::new(&bin);
+        dora::RuntimeEnv::from_config(home)
+            .with_overrides(env, inherit_env)
+            .with_companions(&bin)
+            .apply(cmd.as_std_mut());
+        let mut child = cmd
             .arg("up")
             .current_dir(home)
             .stdout(std::process::Stdio::null())
@@ -172,6 +251,11 @@ pub async fn up(home: &Path, verbose: bool) -> Result<RuntimeResult> {
             }
 
             if is_runtime_running(home, verbose).await {
+                match runtime_pids::record(home) {
+                    Ok(roles) if verbose => eprintln!("[dm] Recorded pids for {:?}", roles),
+                    Ok(_) => {}
+                    Err(err) => eprintln!("[dm] Failed to record runtime pids: {:#}", err),
+                }
                 return Ok(RuntimeResult {
                     success: true,
                     message: "Dora runtime started successfully.".to_string(),
@@ -192,7 +276,14 @@ pub async fn up(home: &Path, verbose: bool) -> Result<RuntimeResult> {
     }
     .await;
 
-    op.emit_result(&result);
+    // A runtime that failed to start is still an Ok(RuntimeResult); record it
+    // as an error so it shows up in failure queries (e.g. `dm explain`).
+    match &result {
+        Ok(outcome) if !outcome.success => {
+            op.emit_result::<()>(&Err(anyhow::anyhow!(outcome.message.clone())))
+        }
+        _ => op.emit_result(&result),
+    }
     result
 }
 
@@ -257,6 +348,9 @@ pub async fn down(home: &Path, verbose: bool) -> Result<RuntimeResult> {
     }
     .await;
 
+    if matches!(&result, Ok(outcome) if outcome.success) {
+        runtime_pids::clear(home);
+    }
     op.emit_result(&result);
     result
 }
@@ -275,7 +369,11 @@ pub async fn ensure_runtime_up(home: &Path, verbose: bool) -> Result<()> {
     if !is_runtime_running(home, verbose).await {
         let result = up(home, verbose).await?;
         if !result.success {
-            anyhow::bail!("Failed to start dora runtime: {}", result.message);
+            return Err(DmError::External(format!(
+                "Failed to start dora runtime: {}",
+                result.message
+            ))
+            .into());
         }
     }
     Ok(())
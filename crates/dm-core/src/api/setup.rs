@@ -1,49 +1,98 @@
 use std::path::Path;
+use std::time::Instant;
 
 use anyhow::Result;
+use tokio::sync::mpsc::UnboundedSender;
 
 use crate::events::{EventSource, OperationEvent};
 use crate::{config, env, install, types::*};
 
-/// Setup: check and install prerequisites
+/// Setup: check and install prerequisites (Python, uv, dora), one phase
+/// after another. A failed phase is recorded in the report, not returned as
+/// an error; later phases that depend on it are skipped.
 pub async fn setup(
     home: &Path,
     verbose: bool,
-    progress_tx: Option<tokio::sync::mpsc::UnboundedSender<InstallProgress>>,
+    progress_tx: Option<UnboundedSender<InstallProgress>>,
 ) -> Result<SetupReport> {
     let op = OperationEvent::new(home, EventSource::Core, "setup");
     op.emit_start();
 
     let result = async {
+        let mut phases = Vec::new();
+
+        let started = begin(&progress_tx, SetupPhase::Python);
         let python = env::check_python().await;
-        let uv_check = env::check_uv().await;
+        phases.push(if python.found {
+            finish(
+                &progress_tx,
+                SetupPhase::Python,
+                started,
+                SetupStatus::Found,
+            )
+            .detail(python.version.clone().or(python.path.clone()))
+        } else {
+            finish(
+                &progress_tx,
+                SetupPhase::Python,
+                started,
+                SetupStatus::Failed,
+            )
+            .error(python.suggestion.clone())
+        });
 
-        let mut uv_installed = uv_check.found;
-        if !uv_installed && python.found {
+        let started = begin(&progress_tx, SetupPhase::Uv);
+        let uv = env::check_uv().await;
+        phases.push(if uv.found {
+            finish(&progress_tx, SetupPhase::Uv, started, SetupStatus::Found)
+                .detail(uv.version.clone())
+        } else if !python.found {
+            finish(&progress_tx, SetupPhase::Uv, started, SetupStatus::Skipped)
+                .error(Some("needs Python to install uv with pip".to_string()))
+        } else {
             let status = tokio::process::Command::new("pip3")
                 .args(["install", "uv"])
                 .status()
                 .await;
-            if let Ok(s) = status {
-                uv_installed = s.success();
+            match status {
+                Ok(s) if s.success() => finish(
+                    &progress_tx,
+                    SetupPhase::Uv,
+                    started,
+                    SetupStatus::Installed,
+                )
+                .detail(Some("via pip3".to_string())),
+                Ok(s) => finish(&progress_tx, SetupPhase::Uv, started, SetupStatus::Failed)
+                    .error(Some(format!("`pip3 install uv` exited with {}", s))),
+                Err(e) => finish(&progress_tx, SetupPhase::Uv, started, SetupStatus::Failed)
+                    .error(Some(format!("could not run pip3: {}", e))),
             }
-        }
+        });
 
+        let started = begin(&progress_tx, SetupPhase::Dora);
         let cfg = config::load_config(home)?;
-        let mut dora_installed = cfg.active_version.is_some();
         let mut dora_version = cfg.active_version.clone();
-
-        if !dora_installed {
-            if let Ok(result) = install::install(home, None, verbose, progress_tx).await {
-                dora_installed = true;
-                dora_version = Some(result.version);
-            }
-        }
+        phases.push(match &cfg.active_version {
+            Some(version) => finish(&progress_tx, SetupPhase::Dora, started, SetupStatus::Found)
+                .detail(Some(version.clone())),
+            None => match install::install(home, None, verbose, progress_tx.clone()).await {
+                Ok(result) => {
+                    dora_version = Some(result.version.clone());
+                    finish(
+                        &progress_tx,
+                        SetupPhase::Dora,
+                        started,
+                        SetupStatus::Installed,
+                    )
+                    .detail(Some(result.version))
+                }
+                Err(e) => finish(&progress_tx, SetupPhase::Dora, started, SetupStatus::Failed)
+                    .error(Some(format!("{:#}", e))),
+            },
+        });
 
         Ok(SetupReport {
-            python_installed: python.found,
-            uv_installed,
-            dora_installed,
+            phases,
             dora_version,
         })
     }
@@ -52,3 +101,60 @@ pub async fn setup(
     op.emit_result(&result);
     result
 }
+
+fn begin(progress_tx: &Option<UnboundedSender<InstallProgress>>, phase: SetupPhase) -> Instant {
+    send(
+        progress_tx,
+        phase,
+        SetupStatus::Running,
+        format!("Checking {}", phase.label()),
+    );
+    Instant::now()
+}
+
+fn finish(
+    progress_tx: &Option<UnboundedSender<InstallProgress>>,
+    phase: SetupPhase,
+    started: Instant,
+    status: SetupStatus,
+) -> SetupPhaseReport {
+    send(
+        progress_tx,
+        phase,
+        status,
+        format!("{}: {:?}", phase.label(), status).to_lowercase(),
+    );
+    SetupPhaseReport {
+        phase,
+        status,
+        duration_ms: started.elapsed().as_millis() as u64,
+        detail: None,
+        error: None,
+    }
+}
+
+fn send(
+    progress_tx: &Option<UnboundedSender<InstallProgress>>,
+    phase: SetupPhase,
+    status: SetupStatus,
+    message: String,
+) {
+    if let Some(tx) = progress_tx {
+        let _ = tx.send(InstallProgress {
+            phase: InstallPhase::Setup { phase, status },
+            message,
+        });
+    }
+}
+
+impl SetupPhaseReport {
+    fn detail(mut self, detail: Option<String>) -> Self {
+        self.detail = detail;
+        self
+    }
+
+    fn error(mut self, error: Option<String>) -> Self {
+        self.error = error;
+        self
+    }
+}
@@ -0,0 +1,200 @@
+//! Runtime supervision (`dm up --supervise`): `dora up` starts the
+//! coordinator and daemon and forgets them, so a supervisor keeps checking
+//! their health and restarts them with exponential backoff when they die.
+//! Every restart attempt is recorded as a `runtime.restart` event.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::events::{try_emit, EventBuilder, EventLevel, EventSource};
+
+use super::runtime::{is_runtime_running, up_with_env};
+
+/// Activity of the events recorded for each restart attempt.
+pub const RUNTIME_RESTART_ACTIVITY: &str = "runtime.restart";
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// How a [`RuntimeSupervisor`] checks and restarts the runtime.
+#[derive(Debug, Clone)]
+pub struct SupervisorOptions {
+    /// Time between health checks while the runtime is up
+    pub interval: Duration,
+    /// Upper bound of the delay between failed restart attempts
+    pub max_backoff: Duration,
+    /// Extra environment for the coordinator and daemon (see `up_with_env`)
+    pub env: BTreeMap<String, String>,
+    pub inherit_env: Option<bool>,
+}
+
+impl Default for SupervisorOptions {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(5),
+            max_backoff: Duration::from_secs(60),
+            env: BTreeMap::new(),
+            inherit_env: None,
+        }
+    }
+}
+
+/// Outcome of one [`RuntimeSupervisor::check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SupervisorAction {
+    /// The runtime answered `dora check`.
+    Healthy,
+    /// The runtime was down and `dora up` brought it back.
+    Restarted { attempt: u32 },
+    /// The runtime is down and could not be started; the next attempt
+    /// follows after `retry_in`.
+    RestartFailed {
+        attempt: u32,
+        message: String,
+        retry_in: Duration,
+    },
+}
+
+/// Keeps the shared dora runtime alive. Call [`check`] in a loop, sleeping
+/// [`next_check_in`] in between.
+///
+/// [`check`]: RuntimeSupervisor::check
+/// [`next_check_in`]: RuntimeSupervisor::next_check_in
+pub struct RuntimeSupervisor {
+    home: PathBuf,
+    verbose: bool,
+    options: SupervisorOptions,
+    backoff: Backoff,
+    /// Consecutive failed restart attempts
+    failures: u32,
+    /// Delay before retrying after the last failed attempt
+    retry_in: Option<Duration>,
+}
+
+impl RuntimeSupervisor {
+    pub fn new(home: &Path, verbose: bool, options: SupervisorOptions) -> Self {
+        Self {
+            home: home.to_path_buf(),
+            verbose,
+            backoff: Backoff::new(INITIAL_BACKOFF, options.max_backoff),
+            options,
+            failures: 0,
+            retry_in: None,
+        }
+    }
+
+    /// Check the runtime once, restarting it when it is down.
+    pub async fn check(&mut self) -> Result<SupervisorAction> {
+        if is_runtime_running(&self.home, self.verbose).await {
+            self.reset();
+            return Ok(SupervisorAction::Healthy);
+        }
+
+        let attempt = self.failures + 1;
+        let result = up_with_env(
+            &self.home,
+            self.verbose,
+            &self.options.env,
+            self.options.inherit_env,
+        )
+        .await;
+        let failure = match result {
+            Ok(outcome) if outcome.success => None,
+            Ok(outcome) => Some(outcome.message),
+            Err(err) => Some(format!("{:#}", err)),
+        };
+
+        let event = EventBuilder::new(EventSource::Core, RUNTIME_RESTART_ACTIVITY)
+            .attr("attempt", attempt)
+            .attr("success", failure.is_none());
+        let action = match failure {
+            None => {
+                self.reset();
+                try_emit(
+                    &self.home,
+                    event
+                        .message("Dora runtime was down and has been restarted")
+                        .build(),
+                );
+                SupervisorAction::Restarted { attempt }
+            }
+            Some(message) => {
+                let retry_in = self.backoff.next_delay();
+                self.failures = attempt;
+                self.retry_in = Some(retry_in);
+                try_emit(
+                    &self.home,
+                    event
+                        .level(EventLevel::Error)
+                        .message(format!("Failed to restart dora runtime: {}", message))
+                        .attr("retry_in_ms", retry_in.as_millis() as u64)
+                        .build(),
+                );
+                SupervisorAction::RestartFailed {
+                    attempt,
+                    message,
+                    retry_in,
+                }
+            }
+        };
+        Ok(action)
+    }
+
+    /// How long to wait before the next [`check`](Self::check).
+    pub fn next_check_in(&self) -> Duration {
+        self.retry_in.unwrap_or(self.options.interval)
+    }
+
+    fn reset(&mut self) {
+        self.failures = 0;
+        self.retry_in = None;
+        self.backoff.reset();
+    }
+}
+
+/// Exponential backoff doubling from `initial` up to `max`.
+#[derive(Debug, Clone)]
+struct Backoff {
+    initial: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl Backoff {
+    fn new(initial: Duration, max: Duration) -> Self {
+        let initial = initial.min(max);
+        Self {
+            initial,
+            max,
+            current: initial,
+        }
+    }
+
+    /// The delay before the next attempt; doubles the one after it.
+    fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        self.current = (self.current * 2).min(self.max);
+        delay
+    }
+
+    fn reset(&mut self) {
+        self.current = self.initial;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_max_and_resets() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(5));
+        let delays: Vec<_> = (0..5).map(|_| backoff.next_delay().as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 5, 5]);
+
+        backoff.reset();
+        assert_eq!(backoff.next_delay(), Duration::from_secs(1));
+    }
+}
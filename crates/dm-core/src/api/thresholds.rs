@@ -0,0 +1,216 @@
+use std::path::Path;
+
+use crate::config::{self, ThresholdConfig};
+use crate::events::{try_emit, EventBuilder, EventLevel, EventSource};
+use crate::types::ThresholdAlert;
+
+/// Measure the dm home against the `[thresholds]` in config.toml.
+pub fn check_thresholds(home: &Path) -> Vec<ThresholdAlert> {
+    let limits = config::load_config(home)
+        .map(|cfg| cfg.thresholds)
+        .unwrap_or_default();
+    evaluate(&limits, &measure(home))
+}
+
+/// Emit a `threshold.exceeded` warning for each alert and, when
+/// `thresholds.webhook_url` is set, POST it there as JSON.
+pub async fn emit_threshold_alerts(home: &Path, alerts: &[ThresholdAlert]) {
+    if alerts.is_empty() {
+        return;
+    }
+    for alert in alerts {
+        try_emit(
+            home,
+            EventBuilder::new(EventSource::Core, "threshold.exceeded")
+                .level(EventLevel::Warn)
+                .message(alert.message.clone())
+                .attr("metric", &alert.metric)
+                .attr("value", alert.value)
+                .attr("limit", alert.limit)
+                .build(),
+        );
+    }
+
+    let cfg = config::load_config(home).unwrap_or_default();
+    let Some(url) = cfg
+        .thresholds
+        .webhook_url
+        .clone()
+        .filter(|url| !url.trim().is_empty())
+    else {
+        return;
+    };
+    let identity = cfg.identity();
+    let client = match crate::http::client(home) {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("[dm] threshold webhook failed: {e:#}");
+            return;
+        }
+    };
+    for alert in alerts {
+        let payload = serde_json::json!({
+            "event": "threshold.exceeded",
+            "robot_id": identity.robot_id,
+            "labels": identity.labels,
+            "alert": alert,
+        });
+        if let Err(e) = client
+            .post(&url)
+            .timeout(std::time::Duration::from_secs(10))
+            .json(&payload)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+        {
+            eprintln!("[dm] threshold webhook failed: {e}");
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Usage {
+    disk_free_pct: Option<f64>,
+    events_db_bytes: u64,
+    versions: usize,
+}
+
+fn measure(home: &Path) -> Usage {
+    let versions = std::fs::read_dir(config::versions_dir(home))
+        .map(|entries| entries.flatten().filter(|e| e.path().is_dir()).count())
+        .unwrap_or(0);
+    Usage {
+        disk_free_pct: disk_free_pct(home),
+        events_db_bytes: std::fs::metadata(home.join("events.db"))
+            .map(|m| m.len())
+            .unwrap_or(0),
+        versions,
+    }
+}
+
+fn evaluate(limits: &ThresholdConfig, usage: &Usage) -> Vec<ThresholdAlert> {
+    let mut alerts = Vec::new();
+
+    if let Some(free) = usage.disk_free_pct {
+        if limits.disk_free_min_pct > 0.0 && free < limits.disk_free_min_pct {
+            alerts.push(ThresholdAlert {
+                metric: "disk_free_pct".to_string(),
+                value: free,
+                limit: limits.disk_free_min_pct,
+                message: format!(
+                    "Only {:.1}% disk free (minimum {}%)",
+                    free, limits.disk_free_min_pct
+                ),
+            });
+        }
+    }
+
+    let db_mb = usage.events_db_bytes as f64 / (1024.0 * 1024.0);
+    if limits.events_db_max_mb > 0 && db_mb > limits.events_db_max_mb as f64 {
+        alerts.push(ThresholdAlert {
+            metric: "events_db_mb".to_string(),
+            value: db_mb,
+            limit: limits.events_db_max_mb as f64,
+            message: format!(
+                "events.db is {:.0} MiB (limit {} MiB); prune old events",
+                db_mb, limits.events_db_max_mb
+            ),
+        });
+    }
+
+    if limits.versions_max > 0 && usage.versions > limits.versions_max {
+        alerts.push(ThresholdAlert {
+            metric: "versions".to_string(),
+            value: usage.versions as f64,
+            limit: limits.versions_max as f64,
+            message: format!(
+                "{} dora versions installed (limit {}); remove old ones with `dm uninstall`",
+                usage.versions, limits.versions_max
+            ),
+        });
+    }
+
+    alerts
+}
+
+/// Free space on the filesystem holding `path`, via `df -Pk`.
+#[cfg(unix)]
+fn disk_free_pct(path: &Path) -> Option<f64> {
+    let output = std::process::Command::new("df")
+        .arg("-Pk")
+        .arg(path)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_df(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(not(unix))]
+fn disk_free_pct(_path: &Path) -> Option<f64> {
+    None
+}
+
+/// Parse POSIX `df -P` output: `Filesystem blocks used available capacity mount`.
+#[cfg_attr(not(unix), allow(dead_code))]
+fn parse_df(output: &str) -> Option<f64> {
+    let fields: Vec<&str> = output.lines().nth(1)?.split_whitespace().collect();
+    let used: f64 = fields.get(2)?.parse().ok()?;
+    let available: f64 = fields.get(3)?.parse().ok()?;
+    let total = used + available;
+    (total > 0.0).then(|| available / total * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_df_reads_posix_output() {
+        let out = "Filesystem 1024-blocks Used Available Capacity Mounted on\n\
+                   /dev/sda1 1000 900 100 90% /\n";
+        assert_eq!(parse_df(out), Some(10.0));
+        assert_eq!(parse_df("garbage"), None);
+    }
+
+    #[test]
+    fn evaluate_flags_only_exceeded_limits() {
+        let limits = ThresholdConfig::default();
+        let usage = Usage {
+            disk_free_pct: Some(4.5),
+            events_db_bytes: 10 * 1024 * 1024,
+            versions: 7,
+        };
+        let alerts = evaluate(&limits, &usage);
+        let metrics: Vec<&str> = alerts.iter().map(|a| a.metric.as_str()).collect();
+        assert_eq!(metrics, vec!["disk_free_pct", "versions"]);
+
+        let disabled = ThresholdConfig {
+            disk_free_min_pct: 0.0,
+            versions_max: 0,
+            ..ThresholdConfig::default()
+        };
+        assert!(evaluate(&disabled, &usage).is_empty());
+    }
+
+    #[test]
+    fn check_thresholds_counts_installed_versions() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        for v in ["0.3.0", "0.3.1"] {
+            std::fs::create_dir_all(config::versions_dir(home).join(v)).unwrap();
+        }
+        let mut cfg = config::load_config(home).unwrap();
+        cfg.thresholds = ThresholdConfig {
+            disk_free_min_pct: 0.0,
+            versions_max: 1,
+            ..ThresholdConfig::default()
+        };
+        config::save_config(home, &cfg).unwrap();
+
+        let alerts = check_thresholds(home);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].value, 2.0);
+    }
+}
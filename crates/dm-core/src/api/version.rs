@@ -3,11 +3,18 @@ use std::path::Path;
 use anyhow::Result;
 
 use crate::events::{EventSource, OperationEvent};
+use crate::lock::HomeLock;
 use crate::{config, dora, types::*};
 
-/// List installed and available versions
+/// List installed and the most recent available versions
 pub async fn versions(home: &Path) -> Result<VersionsReport> {
-    let op = OperationEvent::new(home, EventSource::Core, "versions");
+    versions_with(home, false).await
+}
+
+/// List installed and available versions; `all` pages through every
+/// release instead of only the most recent ones.
+pub async fn versions_with(home: &Path, all: bool) -> Result<VersionsReport> {
+    let op = OperationEvent::new(home, EventSource::Core, "versions").attr("all", all);
     op.emit_start();
 
     let result = async {
@@ -24,6 +31,7 @@ pub async fn versions(home: &Path) -> Result<VersionsReport> {
                             installed.push(InstalledVersion {
                                 version: name.to_string(),
                                 active: name == active,
+                                manifest: crate::install::read_manifest(&entry.path()),
                             });
                         }
                     }
@@ -34,23 +42,36 @@ pub async fn versions(home: &Path) -> Result<VersionsReport> {
 
         let installed_names: Vec<&str> = installed.iter().map(|i| i.version.as_str()).collect();
 
-        let available = match fetch_cached_releases().await {
-            Ok(tags) => tags
-                .into_iter()
-                .map(|tag| {
-                    let clean = tag.trim_start_matches('v').to_string();
-                    AvailableVersion {
-                        installed: installed_names.contains(&clean.as_str()),
-                        tag: clean,
-                    }
-                })
-                .collect(),
-            Err(_) => Vec::new(),
+        let want = (!all).then_some(RECENT_RELEASES);
+        let (available, more_available) = match fetch_cached_releases(
+            &crate::http::client(home)?,
+            &config::github_api_url(home),
+            config::github_token(home).as_deref(),
+            want,
+        )
+        .await
+        {
+            Ok((releases, more)) => (
+                releases
+                    .into_iter()
+                    .map(|release| {
+                        let clean = release.tag.trim_start_matches('v').to_string();
+                        AvailableVersion {
+                            installed: installed_names.contains(&clean.as_str()),
+                            tag: clean,
+                            published_at: release.published_at,
+                        }
+                    })
+                    .collect(),
+                more,
+            ),
+            Err(_) => (Vec::new(), false),
         };
 
         Ok(VersionsReport {
             installed,
             available,
+            more_available,
         })
     }
     .await;
@@ -66,13 +87,15 @@ pub async fn uninstall(home: &Path, version: &str) -> Result<()> {
     op.emit_start();
 
     let result = async {
+        let _lock = HomeLock::acquire_async(home, "install", "dora uninstall").await?;
+        let version = &super::resolve_installed_version(home, version)?;
         let version_dir = config::versions_dir(home).join(version);
         if !version_dir.exists() {
             anyhow::bail!("Version {} is not installed.", version);
         }
 
         let cfg = config::load_config(home)?;
-        if cfg.active_version.as_deref() == Some(version) {
+        if cfg.active_version.as_deref() == Some(version.as_str()) {
             anyhow::bail!(
                 "Cannot uninstall active version {}. Run `dm use <other>` first.",
                 version
@@ -95,6 +118,8 @@ pub async fn use_version(home: &Path, version: &str) -> Result<String> {
     op.emit_start();
 
     let result = async {
+        let _lock = HomeLock::acquire_async(home, "install", "dora use").await?;
+        let version = &super::resolve_installed_version(home, version)?;
         let version_dir = config::versions_dir(home).join(version);
         let dora_bin = config::dora_bin_path(&version_dir);
 
@@ -106,9 +131,12 @@ pub async fn use_version(home: &Path, version: &str) -> Result<String> {
             );
         }
 
-        let mut cfg = config::load_config(home)?;
-        cfg.active_version = Some(version.to_string());
-        config::save_config(home, &cfg)?;
+        config::modify_config_async(home, |cfg| {
+            cfg.active_version = Some(version.clone());
+            Ok(())
+        })
+        .await?;
+        crate::shim::activate(home, version)?;
 
         let actual_ver = dora::get_dora_version(&dora_bin).await.unwrap_or_default();
 
@@ -120,19 +148,62 @@ pub async fn use_version(home: &Path, version: &str) -> Result<String> {
     result
 }
 
+/// Releases listed by `versions()` unless `all` is requested.
+const RECENT_RELEASES: usize = 10;
+/// Upper bound on pages walked for `--all`, in case the API never runs dry.
+const MAX_RELEASE_PAGES: u32 = 20;
+
 #[derive(serde::Deserialize)]
-struct GithubReleaseTag {
+struct GithubReleaseSummary {
     tag_name: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    published_at: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct ReleaseInfo {
+    pub tag: String,
+    pub published_at: Option<String>,
+}
+
+/// Releases fetched so far; `complete` once every page has been read.
+#[derive(Debug, Clone, Default)]
+struct ReleaseListing {
+    releases: Vec<ReleaseInfo>,
+    complete: bool,
+}
+
+impl ReleaseListing {
+    fn covers(&self, want: Option<usize>) -> bool {
+        self.complete || want.is_some_and(|n| self.releases.len() >= n)
+    }
+
+    fn take(&self, want: Option<usize>) -> (Vec<ReleaseInfo>, bool) {
+        match want {
+            Some(n) if self.releases.len() > n => (self.releases[..n].to_vec(), true),
+            _ => (self.releases.clone(), !self.complete),
+        }
+    }
 }
 
 struct CachedReleases {
-    tags: Vec<String>,
+    api_base: String,
+    listing: ReleaseListing,
     fetched_at: std::time::Instant,
 }
 
 const CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(600);
 
-async fn fetch_cached_releases() -> Result<Vec<String>> {
+/// The newest `want` CLI releases (all of them for `None`), plus whether
+/// more exist beyond those returned.
+pub(super) async fn fetch_cached_releases(
+    client: &reqwest::Client,
+    api_base: &str,
+    token: Option<&str>,
+    want: Option<usize>,
+) -> Result<(Vec<ReleaseInfo>, bool)> {
     use std::sync::{Mutex, OnceLock};
 
     static CACHE: OnceLock<Mutex<Option<CachedReleases>>> = OnceLock::new();
@@ -141,25 +212,30 @@ async fn fetch_cached_releases() -> Result<Vec<String>> {
     {
         let guard = cache.lock().unwrap();
         if let Some(ref cached) = *guard {
-            if cached.fetched_at.elapsed() < CACHE_TTL {
-                return Ok(cached.tags.clone());
+            if cached.api_base == api_base
+                && cached.fetched_at.elapsed() < CACHE_TTL
+                && cached.listing.covers(want)
+            {
+                return Ok(cached.listing.take(want));
             }
         }
     }
 
-    match fetch_recent_releases().await {
-        Ok(tags) => {
+    match fetch_releases(client, api_base, token, want).await {
+        Ok(listing) => {
+            let result = listing.take(want);
             let mut guard = cache.lock().unwrap();
             *guard = Some(CachedReleases {
-                tags: tags.clone(),
+                api_base: api_base.to_string(),
+                listing,
                 fetched_at: std::time::Instant::now(),
             });
-            Ok(tags)
+            Ok(result)
         }
         Err(e) => {
             let guard = cache.lock().unwrap();
-            if let Some(ref cached) = *guard {
-                Ok(cached.tags.clone())
+            if let Some(cached) = guard.as_ref().filter(|c| c.api_base == api_base) {
+                Ok(cached.listing.take(want))
             } else {
                 Err(e)
             }
@@ -167,32 +243,193 @@ async fn fetch_cached_releases() -> Result<Vec<String>> {
     }
 }
 
-async fn fetch_recent_releases() -> Result<Vec<String>> {
-    let client = reqwest::Client::new();
-    let mut req = client
-        .get("https://api.github.com/repos/dora-rs/dora/releases?per_page=10")
-        .header("User-Agent", "dm/0.1")
-        .header("Accept", "application/vnd.github+json");
+/// Whether a release tag is a dora CLI release (`v0.3.9`, `v0.4.0-rc.1`)
+/// rather than a tag for some other artifact of the repo.
+fn is_cli_release_tag(tag: &str) -> bool {
+    let version = tag.strip_prefix('v').unwrap_or(tag);
+    let core = version.split(['-', '+']).next().unwrap_or("");
+    let parts: Vec<&str> = core.split('.').collect();
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|p| !p.is_empty() && p.bytes().all(|b| b.is_ascii_digit()))
+}
 
-    if let Ok(token) = std::env::var("GITHUB_TOKEN") {
-        if !token.is_empty() {
+/// Page through the release list until `want` CLI releases are collected
+/// or the API runs out. Pages are small when only the newest few are
+/// wanted and as large as GitHub allows for a full listing.
+async fn fetch_releases(
+    client: &reqwest::Client,
+    api_base: &str,
+    token: Option<&str>,
+    want: Option<usize>,
+) -> Result<ReleaseListing> {
+    let per_page = if want.is_some() { 30 } else { 100 };
+    let mut listing = ReleaseListing::default();
+
+    for page in 1..=MAX_RELEASE_PAGES {
+        let mut req = client
+            .get(format!(
+                "{api_base}/repos/dora-rs/dora/releases?per_page={per_page}&page={page}"
+            ))
+            .header("Accept", "application/vnd.github+json");
+
+        if let Some(token) = token {
             req = req.header("Authorization", format!("Bearer {token}"));
         }
+
+        let resp = req.send().await?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            if status.as_u16() == 403 || status.as_u16() == 429 {
+                anyhow::bail!(
+                    "GitHub API returned {} (rate limit exceeded). Set GITHUB_TOKEN to increase your limit:\n  export GITHUB_TOKEN=ghp_your_token_here\n  or: dm config set github_token ghp_your_token_here",
+                    status
+                );
+            }
+            anyhow::bail!("GitHub API returned {}", status);
+        }
+
+        let releases: Vec<GithubReleaseSummary> = resp.json().await?;
+        let last_page = releases.len() < per_page;
+        listing.releases.extend(
+            releases
+                .into_iter()
+                .filter(|r| !r.draft && is_cli_release_tag(&r.tag_name))
+                .map(|r| ReleaseInfo {
+                    tag: r.tag_name,
+                    published_at: r.published_at,
+                }),
+        );
+
+        if last_page {
+            listing.complete = true;
+            break;
+        }
+        if listing.covers(want) {
+            break;
+        }
     }
 
-    let resp = req.send().await?;
+    Ok(listing)
+}
 
-    if !resp.status().is_success() {
-        let status = resp.status();
-        if status.as_u16() == 403 || status.as_u16() == 429 {
-            anyhow::bail!(
-                "GitHub API returned {} (rate limit exceeded). Set GITHUB_TOKEN to increase your limit:\n  export GITHUB_TOKEN=ghp_your_token_here",
-                status
-            );
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::{path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::{fetch_releases, is_cli_release_tag, ReleaseInfo};
+
+    /// Serve one canned JSON body per page, each expected to be fetched once.
+    async fn serve_pages(pages: Vec<String>) -> MockServer {
+        let server = MockServer::start().await;
+        for (index, body) in pages.into_iter().enumerate() {
+            Mock::given(path("/repos/dora-rs/dora/releases"))
+                .and(query_param("page", (index + 1).to_string()))
+                .respond_with(ResponseTemplate::new(200).set_body_raw(body, "application/json"))
+                .expect(1)
+                .mount(&server)
+                .await;
         }
-        anyhow::bail!("GitHub API returned {}", status);
+        server
     }
 
-    let releases: Vec<GithubReleaseTag> = resp.json().await?;
-    Ok(releases.into_iter().map(|r| r.tag_name).collect())
+    fn release_page(tags: &[String]) -> String {
+        let items: Vec<String> = tags
+            .iter()
+            .map(|t| format!(r#"{{"tag_name":"{t}","published_at":"2024-01-01T00:00:00Z"}}"#))
+            .collect();
+        format!("[{}]", items.join(","))
+    }
+
+    #[test]
+    fn cli_release_tags_exclude_other_artifacts() {
+        assert!(is_cli_release_tag("v0.3.9"));
+        assert!(is_cli_release_tag("v0.4.0-rc.1"));
+        assert!(!is_cli_release_tag("dora-ros2-bridge-v0.1.0"));
+        assert!(!is_cli_release_tag("nightly"));
+        assert!(!is_cli_release_tag("v0.4"));
+    }
+
+    #[tokio::test]
+    async fn fetch_releases_filters_and_keeps_publish_dates() {
+        let body = r#"[{"tag_name":"v0.4.1","published_at":"2025-02-01T00:00:00Z"},{"tag_name":"v0.4.2","draft":true},{"tag_name":"python-v0.4.1"},{"tag_name":"v0.4.0"}]"#;
+        let server = serve_pages(vec![body.to_string()]).await;
+
+        let listing = fetch_releases(&reqwest::Client::new(), &server.uri(), None, Some(10))
+            .await
+            .unwrap();
+        let requests = server.received_requests().await.unwrap();
+
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].url.query(), Some("per_page=30&page=1"));
+        assert!(listing.complete);
+        assert_eq!(
+            listing.releases,
+            vec![
+                ReleaseInfo {
+                    tag: "v0.4.1".into(),
+                    published_at: Some("2025-02-01T00:00:00Z".into()),
+                },
+                ReleaseInfo {
+                    tag: "v0.4.0".into(),
+                    published_at: None,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn fetch_releases_pages_until_exhausted_for_full_listing() {
+        let first: Vec<String> = (0..100).map(|i| format!("v0.{}.0", 200 - i)).collect();
+        let second: Vec<String> = vec!["v0.0.1".into()];
+        let server = serve_pages(vec![release_page(&first), release_page(&second)]).await;
+
+        let listing = fetch_releases(&reqwest::Client::new(), &server.uri(), None, None)
+            .await
+            .unwrap();
+        let requests = server.received_requests().await.unwrap();
+
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].url.query(), Some("per_page=100&page=2"));
+        assert!(listing.complete);
+        assert_eq!(listing.releases.len(), 101);
+        assert_eq!(
+            listing.take(Some(10)),
+            (listing.releases[..10].to_vec(), true)
+        );
+        assert!(!listing.take(None).1);
+    }
+
+    #[test]
+    fn release_ranges_resolve_to_the_newest_matching_release() {
+        let _guard = crate::test_support::env_lock();
+        let home = tempfile::tempdir().unwrap();
+        let tags: Vec<String> = ["v0.4.1", "v0.4.0-rc.1", "v0.3.9", "v0.3.8"]
+            .iter()
+            .map(|t| t.to_string())
+            .collect();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let server = rt.block_on(serve_pages(vec![release_page(&tags)]));
+        crate::config::update_config(
+            home.path(),
+            &serde_json::json!({ "github_api_url": server.uri() }),
+        )
+        .unwrap();
+
+        let resolve = |spec: &str| {
+            rt.block_on(super::super::resolve_release_version(
+                home.path(),
+                Some(spec),
+            ))
+        };
+        assert_eq!(resolve("^0.3").unwrap().as_deref(), Some("0.3.9"));
+        // Served from the cached listing.
+        assert_eq!(resolve("<0.3.9").unwrap().as_deref(), Some("0.3.8"));
+        assert!(resolve("^0.5").is_err());
+        assert_eq!(resolve("0.3.7").unwrap().as_deref(), Some("0.3.7"));
+        rt.block_on(server.verify());
+    }
 }
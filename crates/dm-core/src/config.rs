@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use anyhow::Result;
@@ -6,10 +7,152 @@ use serde::{Deserialize, Serialize};
 /// Persistent configuration stored at <DM_HOME>/config.toml
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct DmConfig {
+    /// Layout version of this file, stamped on every save (see [`CONFIG_VERSION`])
+    #[serde(default)]
+    pub config_version: u32,
     /// Currently active dora version
     pub active_version: Option<String>,
+    /// GitHub API base URL, e.g. `https://ghe.example.com/api/v3` for GitHub Enterprise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github_api_url: Option<String>,
+    /// Personal access token sent with GitHub requests to raise the rate
+    /// limit; the `GITHUB_TOKEN` environment variable takes precedence
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github_token: Option<String>,
+    /// Base URL that replaces `https://github.com` in release downloads,
+    /// e.g. an internal mirror or `https://ghproxy.example/https://github.com`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github_mirror: Option<String>,
+    /// PEM bundle of extra root certificates trusted for every HTTPS request,
+    /// e.g. a corporate TLS-inspection CA. Relative paths resolve against
+    /// the dm home, `~/` against the user's home.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_bundle: Option<String>,
+    /// Remote node registry JSON, overlaid on the bundled registry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry_url: Option<String>,
+    /// Where dataflow projects live instead of `<DM_HOME>/dataflows`, e.g. a
+    /// git checkout shared with a team (`dm dataflow sync`). Relative paths
+    /// resolve against the dm home, `~/` against the user's home.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dataflows_dir: Option<String>,
+    /// Upper bound on the unpacked size of a downloaded dora archive, in MiB
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_extract_mb: Option<u64>,
+    /// Whether spawned dora processes inherit dm's environment (default: true).
+    /// When false, only a few essentials such as PATH and HOME are kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime_inherit_env: Option<bool>,
+    /// Start every run on its own dedicated coordinator/daemon instead of
+    /// the shared runtime (default: false)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isolate_runs: Option<bool>,
+    /// Coordinator dora commands talk to when it runs on another machine, as
+    /// `IP` or `IP:PORT` (e.g. `192.168.1.10:6012`); passed to dora as
+    /// `--coordinator-addr` / `--coordinator-port`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub coordinator_addr: Option<String>,
+    /// Extra environment for spawned dora processes, e.g. `RUST_LOG = "debug"`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub runtime_env: BTreeMap<String, String>,
+    /// Identifier of this machine, stamped onto every event
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub robot_id: Option<String>,
+    /// Free-form `key = value` labels (site, fleet, ...), stamped onto every event
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+    /// OpenTelemetry collector (OTLP/HTTP, e.g. `http://localhost:4318`) that
+    /// every stored event is also forwarded to as a log record
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub otel_endpoint: Option<String>,
+    /// Make every `dm` command verbose, as if `--verbose` were passed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verbose: Option<bool>,
+    #[serde(default)]
+    pub proxy: ProxyConfig,
     #[serde(default)]
     pub media: MediaConfig,
+    #[serde(default)]
+    pub thresholds: ThresholdConfig,
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+    #[serde(default)]
+    pub event_dedupe: EventDedupeConfig,
+    #[serde(default)]
+    pub install: InstallConfig,
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
+    #[serde(default)]
+    pub ingest: IngestConfig,
+    /// Local node collections (`[collections.my-stack]`), installable as
+    /// `dm node install @my-stack`; they shadow registry collections
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub collections: BTreeMap<String, CollectionConfig>,
+    /// Version aliases (`[version_aliases]`, e.g. `demo = "0.3.9"`), usable
+    /// wherever a dora version is accepted
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub version_aliases: BTreeMap<String, String>,
+    /// `dora-rs` SDK specifier per dora version (`[sdk_constraints]`, e.g.
+    /// `"0.3.9" = ">=0.3.5,<0.4"`), applied to Python node installs instead
+    /// of the default `==<major>.<minor>.*`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sdk_constraints: BTreeMap<String, String>,
+    /// Custom subcommands (`[plugins]`, e.g. `deploy = "~/bin/deploy.sh"`),
+    /// run as `dm deploy`; they shadow `dm-<name>` executables on PATH
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub plugins: BTreeMap<String, String>,
+    /// Remote dora daemons by machine id (`[machines]`, e.g.
+    /// `arm = "192.168.1.21"`), registered with `dm machine add`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub machines: BTreeMap<String, String>,
+    /// Event-driven automations (`[[rules]]`), evaluated by dm-server
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<RuleConfig>,
+}
+
+impl DmConfig {
+    /// This config with secrets masked, for display and API responses.
+    pub fn redacted(&self) -> Self {
+        let mut cfg = self.clone();
+        if cfg.github_token.is_some() {
+            cfg.github_token = Some(REDACTED.to_string());
+        }
+        cfg
+    }
+
+    pub fn identity(&self) -> RobotIdentity {
+        RobotIdentity {
+            robot_id: self
+                .robot_id
+                .as_deref()
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(str::to_string),
+            labels: self.labels.clone(),
+        }
+    }
+}
+
+/// Who emitted an event: `robot_id` and `labels` from config.toml.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+pub struct RobotIdentity {
+    pub robot_id: Option<String>,
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+}
+
+impl RobotIdentity {
+    pub fn load(home: &Path) -> Self {
+        load_config(home)
+            .map(|cfg| cfg.identity())
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.robot_id.is_none() && self.labels.is_empty()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -32,6 +175,401 @@ impl Default for MediaConfig {
     }
 }
 
+/// Proxies for dm's own HTTP requests (GitHub, registry, downloads). The
+/// `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` environment variables take
+/// precedence.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct ProxyConfig {
+    /// Proxy for `http://` URLs, e.g. `http://proxy.corp:3128`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http: Option<String>,
+    /// Proxy for `https://` URLs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub https: Option<String>,
+    /// Comma-separated hosts reached directly, e.g. `localhost,.corp`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub no_proxy: Option<String>,
+}
+
+/// Limits checked by `dm doctor` and periodically by dm-server; crossing one
+/// emits a `threshold.exceeded` warning event. A limit of 0 disables it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ThresholdConfig {
+    /// Minimum free space on the dm home's filesystem, in percent
+    #[serde(default = "default_disk_free_min_pct")]
+    pub disk_free_min_pct: f64,
+    /// Maximum size of `events.db`, in MiB
+    #[serde(default = "default_events_db_max_mb")]
+    pub events_db_max_mb: u64,
+    /// Maximum number of installed dora versions
+    #[serde(default = "default_versions_max")]
+    pub versions_max: usize,
+    /// How often dm-server re-checks, in seconds
+    #[serde(default = "default_threshold_check_secs")]
+    pub check_interval_secs: u64,
+    /// Receives a JSON POST for every newly exceeded threshold
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+}
+
+impl Default for ThresholdConfig {
+    fn default() -> Self {
+        Self {
+            disk_free_min_pct: default_disk_free_min_pct(),
+            events_db_max_mb: default_events_db_max_mb(),
+            versions_max: default_versions_max(),
+            check_interval_secs: default_threshold_check_secs(),
+            webhook_url: None,
+        }
+    }
+}
+
+/// How long run artifacts (`runs/<run_id>/` and the per-invocation
+/// directories of `dm run`) are kept, counted separately. Applied whenever
+/// a run starts; started runs still in progress are never removed. A limit
+/// of 0 disables it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RetentionConfig {
+    /// Number of most recent finished runs to keep
+    #[serde(default = "default_retention_keep_runs")]
+    pub keep_runs: usize,
+    /// Remove finished runs older than this many days
+    #[serde(default)]
+    pub max_age_days: u64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            keep_runs: default_retention_keep_runs(),
+            max_age_days: 0,
+        }
+    }
+}
+
+fn default_retention_keep_runs() -> usize {
+    100
+}
+
+fn default_disk_free_min_pct() -> f64 {
+    10.0
+}
+
+fn default_events_db_max_mb() -> u64 {
+    512
+}
+
+fn default_versions_max() -> usize {
+    5
+}
+
+fn default_threshold_check_secs() -> u64 {
+    300
+}
+
+/// One `[[rules]]` entry: when an event matching `when` is stored by
+/// dm-server, run `then`. For example
+///
+/// ```toml
+/// [[rules]]
+/// name = "restart-runtime"
+/// when = { activity = "runtime.down", level = "error" }
+/// then = { action = "up" }
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RuleConfig {
+    pub name: String,
+    pub when: RuleTrigger,
+    pub then: RuleAction,
+    /// Minimum time between two runs of this rule, in seconds
+    #[serde(default = "default_rule_cooldown_secs")]
+    pub cooldown_secs: u64,
+    #[serde(default = "default_rule_enabled")]
+    pub enabled: bool,
+}
+
+fn default_rule_cooldown_secs() -> u64 {
+    60
+}
+
+fn default_rule_enabled() -> bool {
+    true
+}
+
+/// Event fields a rule matches on; unset fields match anything. Values are
+/// compared exactly, except that a trailing `*` in `activity` matches any
+/// suffix (`run.*`).
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct RuleTrigger {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    /// Top-level event attributes, e.g. `{ dataflow = "camera" }`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attrs: BTreeMap<String, String>,
+}
+
+/// What a rule does when it fires.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RuleAction {
+    /// `dm up`
+    Up,
+    /// `dm down`
+    Down,
+    /// Start the saved dataflow `dataflow`
+    StartDataflow { dataflow: String },
+    /// Stop every active run of `dataflow`
+    StopDataflow { dataflow: String },
+    /// Run the `dm` CLI with `args`, e.g. `["node", "update", "--all"]`
+    Dm { args: Vec<String> },
+}
+
+/// How `dm install` obtains dora.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InstallConfig {
+    #[serde(default)]
+    pub method: InstallMethodPreference,
+    /// Check downloaded binaries against the release's published SHA-256
+    /// checksums; `dm install --no-verify` skips this for one install
+    #[serde(default = "default_install_verify")]
+    pub verify: bool,
+    /// minisign public key; when set, release binaries must carry a valid
+    /// `<asset>.minisig` signature
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minisign_public_key: Option<String>,
+    /// Require a `<asset>.asc` / `<asset>.sig` signature that `gpg --verify`
+    /// accepts with the local keyring
+    #[serde(default)]
+    pub gpg_verify: bool,
+    /// How many nodes `dm node install a b c` installs at once
+    #[serde(default = "default_node_parallelism")]
+    pub node_parallelism: usize,
+}
+
+impl Default for InstallConfig {
+    fn default() -> Self {
+        Self {
+            method: InstallMethodPreference::default(),
+            verify: default_install_verify(),
+            minisign_public_key: None,
+            gpg_verify: false,
+            node_parallelism: default_node_parallelism(),
+        }
+    }
+}
+
+fn default_install_verify() -> bool {
+    true
+}
+
+fn default_node_parallelism() -> usize {
+    4
+}
+
+/// `auto` uses the platform's release binary and builds from source only
+/// when there is none; `binary` and `source` never fall back.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallMethodPreference {
+    #[default]
+    Auto,
+    Binary,
+    Source,
+}
+
+impl std::str::FromStr for InstallMethodPreference {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "binary" => Ok(Self::Binary),
+            "source" => Ok(Self::Source),
+            _ => anyhow::bail!(
+                "Unknown install method '{}': expected auto, binary or source",
+                s
+            ),
+        }
+    }
+}
+
+/// A named set of node ids.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CollectionConfig {
+    #[serde(default)]
+    pub description: String,
+    pub nodes: Vec<String>,
+}
+
+/// Encryption at rest for the home's databases; see `crate::encryption`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct EncryptionConfig {
+    /// Open events.db and secrets.db with SQLCipher. Needs a `sqlcipher`
+    /// build and a key from `DM_HOME_KEY` or the OS keychain.
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// What dm records about its own activity in the event store.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TelemetryConfig {
+    /// Set to false to opt out of telemetry: no `dora.exec` events are
+    /// recorded and nothing is forwarded to `otel_endpoint`
+    #[serde(default = "default_telemetry_enabled")]
+    pub enabled: bool,
+    /// Emit a `dora.exec` event for every dora CLI invocation
+    #[serde(default = "default_trace_dora")]
+    pub trace_dora: bool,
+    /// How much of a traced invocation's stderr is kept, in bytes (0 drops it)
+    #[serde(default = "default_trace_stderr_bytes")]
+    pub trace_stderr_bytes: usize,
+    /// Subcommands traced only when they fail; dm-server polls these every
+    /// few seconds and would otherwise flood the event store
+    #[serde(default = "default_trace_quiet_commands")]
+    pub trace_quiet_commands: Vec<String>,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_telemetry_enabled(),
+            trace_dora: default_trace_dora(),
+            trace_stderr_bytes: default_trace_stderr_bytes(),
+            trace_quiet_commands: default_trace_quiet_commands(),
+        }
+    }
+}
+
+fn default_telemetry_enabled() -> bool {
+    true
+}
+
+fn default_trace_dora() -> bool {
+    true
+}
+
+fn default_trace_stderr_bytes() -> usize {
+    2048
+}
+
+fn default_trace_quiet_commands() -> Vec<String> {
+    vec!["check".to_string(), "list".to_string()]
+}
+
+/// Collapsing of repeated error events: an error with the same source,
+/// activity and message as one stored within the window is not stored
+/// again; the earlier event's `count` and `last_seen` attributes are
+/// updated instead.
+///
+/// ```toml
+/// [event_dedupe]
+/// window_secs = 10
+/// sources = { dataflow = 60, ci = 0 }
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EventDedupeConfig {
+    /// Window for sources without their own entry, in seconds (0 disables)
+    #[serde(default = "default_dedupe_window_secs")]
+    pub window_secs: u64,
+    /// Per-source windows, in seconds
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub sources: BTreeMap<String, u64>,
+}
+
+impl Default for EventDedupeConfig {
+    fn default() -> Self {
+        Self {
+            window_secs: default_dedupe_window_secs(),
+            sources: BTreeMap::new(),
+        }
+    }
+}
+
+impl EventDedupeConfig {
+    /// Dedupe window for events from `source`, `None` when disabled.
+    pub fn window(&self, source: &str) -> Option<std::time::Duration> {
+        let secs = self
+            .sources
+            .get(source)
+            .copied()
+            .unwrap_or(self.window_secs);
+        (secs > 0).then_some(std::time::Duration::from_secs(secs))
+    }
+}
+
+fn default_dedupe_window_secs() -> u64 {
+    10
+}
+
+/// Which event sources clients may write through `POST /api/events`.
+///
+/// ```toml
+/// [ingest]
+/// anonymous_sources = ["frontend"]
+///
+/// [[ingest.tokens]]
+/// name = "ci"
+/// token = "..."
+/// sources = ["ci"]
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IngestConfig {
+    /// Sources accepted from requests without a bearer token (default: frontend)
+    #[serde(default = "default_anonymous_sources")]
+    pub anonymous_sources: Vec<String>,
+    /// Bearer tokens and the sources each may write; `"*"` allows any source
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tokens: Vec<IngestToken>,
+}
+
+impl Default for IngestConfig {
+    fn default() -> Self {
+        Self {
+            anonymous_sources: default_anonymous_sources(),
+            tokens: Vec::new(),
+        }
+    }
+}
+
+impl IngestConfig {
+    /// Sources a request presenting `token` may write, or `None` when the
+    /// token is not configured.
+    pub fn allowed_sources(&self, token: Option<&str>) -> Option<&[String]> {
+        match token {
+            None => Some(&self.anonymous_sources),
+            Some(token) => self.token(token).map(|t| t.sources.as_slice()),
+        }
+    }
+
+    /// The configured entry for a bearer `token`, if any.
+    pub fn token(&self, token: &str) -> Option<&IngestToken> {
+        self.tokens
+            .iter()
+            .find(|t| !t.token.is_empty() && t.token == token)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct IngestToken {
+    /// Label shown in rejections and audit entries, e.g. `ci`
+    pub name: String,
+    pub token: String,
+    #[serde(default)]
+    pub sources: Vec<String>,
+}
+
+fn default_anonymous_sources() -> Vec<String> {
+    vec!["frontend".to_string()]
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum MediaBackend {
@@ -102,6 +640,85 @@ fn default_mediamtx_host() -> String {
     "127.0.0.1".to_string()
 }
 
+/// Layout version written to `config_version`. Files with a newer version
+/// come from a newer dm and are refused rather than silently rewritten.
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Stand-in for secrets in [`DmConfig::redacted`]; writing it back through
+/// [`update_config`] keeps the stored value.
+pub const REDACTED: &str = "********";
+
+pub const DEFAULT_GITHUB_API_URL: &str = "https://api.github.com";
+
+/// Resolve the GitHub API base URL.
+/// Priority: DM_GITHUB_API_URL env > config.toml `github_api_url` > api.github.com
+pub fn github_api_url(home: &Path) -> String {
+    let url = std::env::var("DM_GITHUB_API_URL")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .or_else(|| load_config(home).ok().and_then(|cfg| cfg.github_api_url))
+        .unwrap_or_else(|| DEFAULT_GITHUB_API_URL.to_string());
+    url.trim().trim_end_matches('/').to_string()
+}
+
+/// Resolve the GitHub token, if any.
+/// Priority: GITHUB_TOKEN env > config.toml `github_token`
+pub fn github_token(home: &Path) -> Option<String> {
+    std::env::var("GITHUB_TOKEN")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .or_else(|| load_config(home).ok().and_then(|cfg| cfg.github_token))
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Resolve the GitHub release download mirror, if one is configured.
+/// Priority: DM_GITHUB_MIRROR env > config.toml `github_mirror`
+pub fn github_mirror(home: &Path) -> Option<String> {
+    std::env::var("DM_GITHUB_MIRROR")
+        .ok()
+        .or_else(|| load_config(home).ok().and_then(|cfg| cfg.github_mirror))
+        .map(|v| v.trim().trim_end_matches('/').to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Resolve the extra CA bundle, if one is configured.
+/// Priority: DM_CA_BUNDLE env > config.toml `ca_bundle`
+pub fn ca_bundle(home: &Path) -> Option<PathBuf> {
+    let path = std::env::var("DM_CA_BUNDLE")
+        .ok()
+        .or_else(|| load_config(home).ok().and_then(|cfg| cfg.ca_bundle))
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())?;
+    Some(match path.strip_prefix("~/").zip(dirs::home_dir()) {
+        Some((rest, user_home)) => user_home.join(rest),
+        None => home.join(path),
+    })
+}
+
+pub const DEFAULT_MAX_EXTRACT_MB: u64 = 1024;
+
+/// Resolve the archive extraction limit in bytes.
+/// Priority: DM_MAX_EXTRACT_MB env > config.toml `max_extract_mb` > 1024 MiB
+pub fn max_extract_bytes(home: &Path) -> u64 {
+    let mb = std::env::var("DM_MAX_EXTRACT_MB")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .or_else(|| load_config(home).ok().and_then(|cfg| cfg.max_extract_mb))
+        .unwrap_or(DEFAULT_MAX_EXTRACT_MB);
+    mb.saturating_mul(1024 * 1024)
+}
+
+/// Resolve the remote node registry URL, if one is configured.
+/// Priority: DM_REGISTRY_URL env > config.toml `registry_url`
+pub fn registry_url(home: &Path) -> Option<String> {
+    std::env::var("DM_REGISTRY_URL")
+        .ok()
+        .or_else(|| load_config(home).ok().and_then(|cfg| cfg.registry_url))
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
 /// Resolve the dm home directory.
 /// Priority: --home flag > DM_HOME env > ~/.dm
 pub fn resolve_home(flag: Option<String>) -> Result<PathBuf> {
@@ -136,6 +753,20 @@ pub fn dora_bin_path(version_dir: &Path) -> PathBuf {
     version_dir.join(dora_bin_name())
 }
 
+/// Runtime binaries some releases ship next to the CLI, kept in the version
+/// directory when present
+pub const COMPANION_BINARIES: &[&str] = &["dora-coordinator", "dora-daemon", "dora-runtime"];
+
+/// Full path to a companion binary (see [`COMPANION_BINARIES`]) inside a
+/// version directory
+pub fn companion_bin_path(version_dir: &Path, name: &str) -> PathBuf {
+    if cfg!(windows) {
+        version_dir.join(format!("{}.exe", name))
+    } else {
+        version_dir.join(name)
+    }
+}
+
 pub fn active_link(home: &Path) -> PathBuf {
     home.join("active")
 }
@@ -150,17 +781,238 @@ pub fn load_config(home: &Path) -> Result<DmConfig> {
     if path.exists() {
         let content = std::fs::read_to_string(&path)?;
         let cfg: DmConfig = toml::from_str(&content)?;
+        if cfg.config_version > CONFIG_VERSION {
+            anyhow::bail!(
+                "{} has config_version {}, but this dm only understands up to {}; upgrade dm",
+                path.display(),
+                cfg.config_version,
+                CONFIG_VERSION
+            );
+        }
         Ok(cfg)
     } else {
         Ok(DmConfig::default())
     }
 }
 
-/// Save config
+/// Save config, replacing whatever is on disk. Prefer [`modify_config`]
+/// when the new config is derived from the current one.
 pub fn save_config(home: &Path, cfg: &DmConfig) -> Result<()> {
+    let _lock = lock_config(home)?;
+    write_config(home, cfg)
+}
+
+/// Load, change and save config.toml while holding the `config` lock, so
+/// concurrent read-modify-write cycles from dm-cli and dm-server do not
+/// drop each other's changes. Nothing is written when `change` fails.
+pub fn modify_config<T>(home: &Path, change: impl FnOnce(&mut DmConfig) -> Result<T>) -> Result<T> {
+    let _lock = lock_config(home)?;
+    modify_locked(home, change)
+}
+
+/// [`modify_config`] for async callers: waits for the `config` lock
+/// without blocking the runtime's worker thread.
+pub async fn modify_config_async<T>(
+    home: &Path,
+    change: impl FnOnce(&mut DmConfig) -> Result<T>,
+) -> Result<T> {
+    let _lock = crate::lock::HomeLock::acquire_with_async(
+        home,
+        "config",
+        "config write",
+        config_lock_policy(home)?,
+    )
+    .await?;
+    modify_locked(home, change)
+}
+
+fn modify_locked<T>(home: &Path, change: impl FnOnce(&mut DmConfig) -> Result<T>) -> Result<T> {
+    let mut cfg = load_config(home)?;
+    let out = change(&mut cfg)?;
+    write_config(home, &cfg)?;
+    Ok(out)
+}
+
+fn lock_config(home: &Path) -> Result<crate::lock::HomeLock> {
+    crate::lock::HomeLock::acquire_with(home, "config", "config write", config_lock_policy(home)?)
+}
+
+fn config_lock_policy(home: &Path) -> Result<crate::lock::LockPolicy> {
+    if !home.exists() {
+        crate::permissions::create_private_dir(home)?;
+    }
+    let mut policy = crate::lock::policy();
+    policy.wait = policy.wait.max(crate::lock::CONFIG_LOCK_WAIT);
+    Ok(policy)
+}
+
+/// Write through a temporary file and rename it into place, so a crash
+/// mid-write never leaves a truncated config.toml behind.
+fn write_config(home: &Path, cfg: &DmConfig) -> Result<()> {
     let path = config_path(home);
-    std::fs::create_dir_all(home)?;
-    let content = toml::to_string_pretty(cfg)?;
-    std::fs::write(&path, content)?;
+    let cfg = DmConfig {
+        config_version: CONFIG_VERSION,
+        ..cfg.clone()
+    };
+    let content = toml::to_string_pretty(&cfg)?;
+    let tmp = home.join(".config.toml.tmp");
+    crate::permissions::write_private(&tmp, content)?;
+    std::fs::rename(&tmp, &path)?;
     Ok(())
 }
+
+/// Apply a JSON merge patch (RFC 7396) to the saved config: objects merge
+/// key by key, `null` resets a key to its default, anything else replaces
+/// it. The result must deserialize into [`DmConfig`] and every key the
+/// patch sets must exist in it, so typos are rejected instead of being
+/// silently dropped. Returns the saved config.
+pub fn update_config(home: &Path, patch: &serde_json::Value) -> Result<DmConfig> {
+    if !patch.is_object() {
+        anyhow::bail!("Config update must be a JSON object");
+    }
+    modify_config(home, |current| apply_patch(current, patch))
+}
+
+/// [`update_config`] for async callers such as dm-server handlers.
+pub async fn update_config_async(home: &Path, patch: &serde_json::Value) -> Result<DmConfig> {
+    if !patch.is_object() {
+        anyhow::bail!("Config update must be a JSON object");
+    }
+    modify_config_async(home, |current| apply_patch(current, patch)).await
+}
+
+fn apply_patch(current: &mut DmConfig, patch: &serde_json::Value) -> Result<DmConfig> {
+    let mut merged = serde_json::to_value(&*current)?;
+    merge_patch(&mut merged, patch);
+    let mut cfg: DmConfig =
+        serde_json::from_value(merged).map_err(|e| anyhow::anyhow!("Invalid config: {}", e))?;
+    if cfg.github_token.as_deref() == Some(REDACTED) {
+        cfg.github_token = current.github_token.take();
+    }
+    check_known_keys(patch, &serde_json::to_value(&cfg)?, "")?;
+    *current = cfg.clone();
+    Ok(cfg)
+}
+
+/// Value at a dotted key such as `install.verify`, defaults included.
+pub fn config_value(home: &Path, key: &str) -> Result<Option<serde_json::Value>> {
+    let mut value = serde_json::to_value(load_config(home)?)?;
+    for part in key.split('.') {
+        match value.get_mut(part) {
+            Some(child) => value = child.take(),
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(value))
+}
+
+/// Set a dotted key from command-line text. The text is read as a TOML
+/// value (`true`, `5`, `["a", "b"]`) and falls back to a plain string.
+pub fn set_config_value(home: &Path, key: &str, raw: &str) -> Result<DmConfig> {
+    let parsed = toml::from_str::<toml::Table>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .and_then(|value| serde_json::to_value(value).ok());
+    let text = serde_json::Value::String(raw.to_string());
+    match parsed.filter(|value| *value != text) {
+        Some(value) => update_config(home, &dotted_patch(key, value))
+            .or_else(|e| update_config(home, &dotted_patch(key, text)).map_err(|_| e)),
+        None => update_config(home, &dotted_patch(key, text)),
+    }
+}
+
+/// Reset a dotted key to its default.
+pub fn unset_config_value(home: &Path, key: &str) -> Result<DmConfig> {
+    update_config(home, &dotted_patch(key, serde_json::Value::Null))
+}
+
+/// Every leaf setting as `(dotted key, value)`, defaults included and
+/// secrets redacted, sorted by key.
+pub fn config_entries(home: &Path) -> Result<Vec<(String, serde_json::Value)>> {
+    let mut entries = Vec::new();
+    flatten_value(
+        "",
+        serde_json::to_value(load_config(home)?.redacted())?,
+        &mut entries,
+    );
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(entries)
+}
+
+fn merge_patch(target: &mut serde_json::Value, patch: &serde_json::Value) {
+    let serde_json::Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = serde_json::Value::Object(Default::default());
+    }
+    let serde_json::Value::Object(target) = target else {
+        return;
+    };
+    for (key, value) in patch {
+        if value.is_null() {
+            target.remove(key);
+        } else {
+            merge_patch(
+                target.entry(key.clone()).or_insert(serde_json::Value::Null),
+                value,
+            );
+        }
+    }
+}
+
+/// Fail on a key of `patch` that does not survive the round trip through
+/// [`DmConfig`]. Empty values are skipped: they are not serialized back.
+fn check_known_keys(
+    patch: &serde_json::Value,
+    saved: &serde_json::Value,
+    prefix: &str,
+) -> Result<()> {
+    let serde_json::Value::Object(patch) = patch else {
+        return Ok(());
+    };
+    for (key, value) in patch {
+        let empty = match value {
+            serde_json::Value::Null => true,
+            serde_json::Value::Object(map) => map.is_empty(),
+            serde_json::Value::Array(items) => items.is_empty(),
+            _ => false,
+        };
+        if empty {
+            continue;
+        }
+        let path = format!("{}{}", prefix, key);
+        let Some(saved) = saved.get(key) else {
+            anyhow::bail!("Unknown config key '{}'", path);
+        };
+        check_known_keys(value, saved, &format!("{}.", path))?;
+    }
+    Ok(())
+}
+
+fn dotted_patch(key: &str, value: serde_json::Value) -> serde_json::Value {
+    key.rsplit('.').fold(value, |value, part| {
+        serde_json::Value::Object([(part.to_string(), value)].into_iter().collect())
+    })
+}
+
+fn flatten_value(
+    prefix: &str,
+    value: serde_json::Value,
+    out: &mut Vec<(String, serde_json::Value)>,
+) {
+    match value {
+        serde_json::Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                let key = if prefix.is_empty() {
+                    key
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten_value(&key, value, out);
+            }
+        }
+        value => out.push((prefix.to_string(), value)),
+    }
+}
@@ -0,0 +1,404 @@
+//! `.dmflow` bundles: a dataflow plus the node ids, versions, pins, sources
+//! and saved configs it depends on, as one JSON file for sharing complete
+//! pipelines between machines.
+//!
+//! Config fields a node's schema marks `"secret": true` are never written
+//! to a bundle; importing reports them so they can be set locally.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+use crate::error::DmError;
+use crate::events::{EventSource, OperationEvent};
+use crate::node::{hub, resolve_dm_json_path, resolve_node_dir, Node, NodeInstallOutcome};
+
+use super::inspect::inspect_yaml;
+use super::model::{BundledNode, DataflowBundle, DataflowBundleImport, FlowMeta};
+use super::paths::dataflow_dir;
+use super::repo::{read_meta, read_view, read_yaml, write_meta, write_view, write_yaml};
+
+/// File extension of dataflow bundles.
+pub const DATAFLOW_BUNDLE_EXTENSION: &str = "dmflow";
+
+/// Layout version written by this build.
+const BUNDLE_FORMAT: u32 = 1;
+
+/// Collect dataflow `name` and the nodes it uses into a bundle.
+pub fn export_bundle(home: &Path, name: &str) -> Result<DataflowBundle> {
+    let yaml = read_yaml(home, name)?;
+    let meta = read_meta(home, name).unwrap_or_default();
+    let view = read_view(home, name)
+        .ok()
+        .filter(|view| view.as_object().is_some_and(|map| !map.is_empty()));
+
+    let mut seen = BTreeSet::new();
+    let mut nodes = Vec::new();
+    for resolution in inspect_yaml(home, &yaml).nodes {
+        if resolution.source != "managed_node" || !seen.insert(resolution.node_id.clone()) {
+            continue;
+        }
+        let id = resolution.node_id;
+        let node = read_node(home, &id);
+        let secrets = node.as_ref().map(secret_fields).unwrap_or_default();
+        let mut config = match crate::node::get_node_config(home, &id)? {
+            Value::Object(map) => map,
+            _ => serde_json::Map::new(),
+        };
+        config.retain(|key, _| !secrets.contains(key));
+
+        let source = node
+            .as_ref()
+            .and_then(|node| {
+                node.source
+                    .github
+                    .clone()
+                    .or_else(|| node.repository.as_ref().map(|repo| repo.url.clone()))
+            })
+            .or(resolution.source_git_url)
+            .or_else(|| registry_git_url(home, &id));
+        nodes.push(BundledNode {
+            version: node
+                .as_ref()
+                .map(|node| node.version.clone())
+                .filter(|version| !version.is_empty()),
+            pinned: node.as_ref().and_then(|node| node.pinned.clone()),
+            source,
+            config,
+            secrets: secrets.into_iter().collect(),
+            id,
+        });
+    }
+
+    Ok(DataflowBundle {
+        format: BUNDLE_FORMAT,
+        name: name.to_string(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        yaml,
+        meta,
+        view,
+        nodes,
+    })
+}
+
+/// Write the bundle of dataflow `name` to `out`.
+pub fn write_bundle(home: &Path, name: &str, out: &Path) -> Result<PathBuf> {
+    let op = OperationEvent::new(home, EventSource::Core, "dataflow.export")
+        .attr("name", name)
+        .attr("out", out.display().to_string());
+    op.emit_start();
+
+    let result = (|| {
+        let bundle = export_bundle(home, name)?;
+        if let Some(out_dir) = out.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(out_dir)
+                .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+        }
+        let json = serde_json::to_string_pretty(&bundle).context("Failed to serialize bundle")?;
+        std::fs::write(out, json).with_context(|| format!("Failed to write {}", out.display()))?;
+        Ok(out.to_path_buf())
+    })();
+
+    op.emit_result(&result);
+    result
+}
+
+pub fn read_bundle(path: &Path) -> Result<DataflowBundle> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read bundle '{}'", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("'{}' is not a dataflow bundle", path.display()))
+}
+
+/// Register the bundled dataflow as `name` (default: its exported name),
+/// download and install the nodes it uses that are missing here, and fill
+/// in node config values that are not set locally.
+pub async fn import_bundle(
+    home: &Path,
+    bundle: &DataflowBundle,
+    name: Option<&str>,
+) -> Result<DataflowBundleImport> {
+    let name = name.unwrap_or(&bundle.name).trim().to_string();
+    let op = OperationEvent::new(home, EventSource::Core, "dataflow.import_bundle")
+        .attr("name", &name)
+        .attr("nodes", bundle.nodes.len());
+    op.emit_start();
+
+    let result = import_bundle_inner(home, bundle, &name, &op).await;
+
+    op.emit_result(&result);
+    result
+}
+
+async fn import_bundle_inner(
+    home: &Path,
+    bundle: &DataflowBundle,
+    name: &str,
+    op: &OperationEvent,
+) -> Result<DataflowBundleImport> {
+    if bundle.format > BUNDLE_FORMAT {
+        bail!(
+            "Bundle format {} is newer than this dm supports ({}); upgrade dm to import it",
+            bundle.format,
+            BUNDLE_FORMAT
+        );
+    }
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        bail!("Invalid dataflow name '{}'", name);
+    }
+    if dataflow_dir(home, name).exists() {
+        return Err(DmError::Conflict(format!("Dataflow '{}' already exists", name)).into());
+    }
+
+    write_yaml(home, name, &bundle.yaml)?;
+    write_meta(
+        home,
+        name,
+        &FlowMeta {
+            id: name.to_string(),
+            ..bundle.meta.clone()
+        },
+    )?;
+    if let Some(view) = &bundle.view {
+        write_view(home, name, view)?;
+    }
+
+    let mut report = DataflowBundleImport {
+        name: name.to_string(),
+        installed: Vec::new(),
+        configured: Vec::new(),
+        missing_secrets: BTreeMap::new(),
+        warnings: Vec::new(),
+    };
+    for (done, bundled) in bundle.nodes.iter().enumerate() {
+        let id = &bundled.id;
+        let installed_here = read_node(home, id).filter(|_| resolve_node_dir(home, id).is_some());
+        match installed_here {
+            Some(node) => {
+                if let Some(version) = bundled.version.as_deref() {
+                    if !node.version.is_empty() && node.version != version {
+                        report.warnings.push(format!(
+                            "{} is at {} here; the bundle was exported with {}",
+                            id, node.version, version
+                        ));
+                    }
+                }
+            }
+            None => {
+                op.emit_progress(
+                    "install",
+                    Some((done * 100 / bundle.nodes.len()) as u8),
+                    format!("Installing {}", id),
+                );
+                let outcome = install_bundled_node(home, bundled).await;
+                let failed = outcome.error.is_some();
+                report.installed.push(outcome);
+                if failed {
+                    continue;
+                }
+            }
+        }
+
+        match merge_config(home, bundled) {
+            Ok(true) => report.configured.push(id.clone()),
+            Ok(false) => {}
+            Err(e) => report
+                .warnings
+                .push(format!("Config for {} was not applied: {:#}", id, e)),
+        }
+
+        let local = crate::node::get_node_config(home, id).unwrap_or_default();
+        let unset: Vec<String> = bundled
+            .secrets
+            .iter()
+            .filter(|key| local.get(key.as_str()).is_none())
+            .cloned()
+            .collect();
+        if !unset.is_empty() {
+            report.missing_secrets.insert(id.clone(), unset);
+        }
+    }
+    Ok(report)
+}
+
+/// Download `bundled` from its source (or the registry), apply its pin and
+/// install it.
+async fn install_bundled_node(home: &Path, bundled: &BundledNode) -> NodeInstallOutcome {
+    let id = &bundled.id;
+    let installed = async {
+        let url = bundled
+            .source
+            .clone()
+            .or_else(|| registry_git_url(home, id))
+            .with_context(|| {
+                format!("no known source for '{id}'; run `dm node import <git-url>`")
+            })?;
+        crate::node::import_git(home, id, &url).await?;
+        if let Some(version) = bundled.pinned.as_deref() {
+            crate::node::pin_node(home, id, version)?;
+        }
+        crate::node::install_node(home, id).await
+    }
+    .await;
+    match installed {
+        Ok(node) => NodeInstallOutcome {
+            node_id: id.clone(),
+            version: Some(node.version),
+            error: None,
+        },
+        Err(e) => NodeInstallOutcome {
+            node_id: id.clone(),
+            version: None,
+            error: Some(format!("{:#}", e)),
+        },
+    }
+}
+
+/// Add bundled config values for keys the local config does not set.
+/// Returns whether anything was written.
+fn merge_config(home: &Path, bundled: &BundledNode) -> Result<bool> {
+    if bundled.config.is_empty() {
+        return Ok(false);
+    }
+    let mut local = match crate::node::get_node_config(home, &bundled.id)? {
+        Value::Object(map) => map,
+        _ => serde_json::Map::new(),
+    };
+    let mut changed = false;
+    for (key, value) in &bundled.config {
+        if !local.contains_key(key) {
+            local.insert(key.clone(), value.clone());
+            changed = true;
+        }
+    }
+    if changed {
+        crate::node::save_node_config(home, &bundled.id, &Value::Object(local))?;
+    }
+    Ok(changed)
+}
+
+fn read_node(home: &Path, id: &str) -> Option<Node> {
+    let content = std::fs::read_to_string(resolve_dm_json_path(home, id)?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn registry_git_url(home: &Path, id: &str) -> Option<String> {
+    match hub::resolve_node_source(home, id)? {
+        hub::NodeSource::Git(url) => Some(url),
+        _ => None,
+    }
+}
+
+/// Config fields marked `"secret": true` in the node's schema.
+fn secret_fields(node: &Node) -> BTreeSet<String> {
+    let Some(schema) = node.config_schema.clone() else {
+        return BTreeSet::new();
+    };
+    let schema = crate::node::normalize_config_schema(schema.clone()).unwrap_or(schema);
+    schema
+        .as_object()
+        .map(|fields| {
+            fields
+                .iter()
+                .filter(|(_, field)| field.get("secret") == Some(&Value::Bool(true)))
+                .map(|(key, _)| key.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLOW: &str = "nodes:\n  - id: cam\n    node: cfg-node\n";
+
+    fn write_node(home: &Path, config: Option<Value>) {
+        let dir = crate::node::node_dir(home, "cfg-node");
+        std::fs::create_dir_all(&dir).unwrap();
+        let dm_json = serde_json::json!({
+            "id": "cfg-node",
+            "version": "1.2.0",
+            "installed_at": "123",
+            "source": { "build": "pip install cfg-node", "github": "https://github.com/acme/cfg-node" },
+            "pinned": "1.2.0",
+            "config_schema": {
+                "mode": { "default": "fast" },
+                "api_key": { "type": "string", "secret": true }
+            }
+        });
+        std::fs::write(dir.join("dm.json"), dm_json.to_string()).unwrap();
+        if let Some(config) = config {
+            std::fs::write(dir.join("config.json"), config.to_string()).unwrap();
+        }
+    }
+
+    #[test]
+    fn export_records_node_versions_and_leaves_out_secrets() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        write_node(
+            home,
+            Some(serde_json::json!({ "mode": "accurate", "api_key": "sk-live" })),
+        );
+        crate::dataflow::save(home, "demo", FLOW).unwrap();
+
+        let bundle = export_bundle(home, "demo").unwrap();
+        assert_eq!(bundle.yaml, FLOW);
+        assert_eq!(bundle.nodes.len(), 1);
+        let node = &bundle.nodes[0];
+        assert_eq!(node.id, "cfg-node");
+        assert_eq!(node.version.as_deref(), Some("1.2.0"));
+        assert_eq!(node.pinned.as_deref(), Some("1.2.0"));
+        assert_eq!(
+            node.source.as_deref(),
+            Some("https://github.com/acme/cfg-node")
+        );
+        assert_eq!(
+            node.config.get("mode"),
+            Some(&serde_json::json!("accurate"))
+        );
+        assert_eq!(node.secrets, vec!["api_key"]);
+        assert!(!serde_json::to_string(&bundle).unwrap().contains("sk-live"));
+    }
+
+    #[tokio::test]
+    async fn import_registers_flow_and_fills_unset_config() {
+        let source = tempfile::tempdir().unwrap();
+        write_node(
+            source.path(),
+            Some(serde_json::json!({ "mode": "accurate", "api_key": "sk-live" })),
+        );
+        crate::dataflow::save(source.path(), "demo", FLOW).unwrap();
+        let bundle = export_bundle(source.path(), "demo").unwrap();
+
+        let target = tempfile::tempdir().unwrap();
+        let home = target.path();
+        write_node(home, None);
+
+        let report = import_bundle(home, &bundle, Some("shared")).await.unwrap();
+        assert_eq!(report.name, "shared");
+        assert!(report.installed.is_empty());
+        assert_eq!(report.configured, vec!["cfg-node"]);
+        assert_eq!(
+            report.missing_secrets.get("cfg-node"),
+            Some(&vec!["api_key".to_string()])
+        );
+        assert_eq!(read_yaml(home, "shared").unwrap(), FLOW);
+        assert_eq!(read_meta(home, "shared").unwrap().id, "shared");
+        assert_eq!(
+            crate::node::get_node_config(home, "cfg-node").unwrap(),
+            serde_json::json!({ "mode": "accurate" })
+        );
+
+        let err = import_bundle(home, &bundle, Some("shared"))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            crate::error::error_code(&err),
+            Some(crate::error::ErrorCode::Conflict)
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Line-based unified diff for comparing saved dataflow versions.
+//!
+//! Dataflow YAML is small (tens to a few hundred lines), so a plain LCS
+//! table is fast enough and keeps the output stable across platforms.
+
+/// Lines of unchanged context kept around each change.
+const CONTEXT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Keep,
+    Remove,
+    Add,
+}
+
+/// Unified diff of `old` against `new`, with `---`/`+++` headers and `@@`
+/// hunks. Returns the text plus the number of added and removed lines;
+/// the text is empty when both sides are equal.
+pub fn unified_diff(
+    old: &str,
+    new: &str,
+    old_label: &str,
+    new_label: &str,
+) -> (String, usize, usize) {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let script = edit_script(&old_lines, &new_lines);
+
+    let added = script.iter().filter(|(op, _, _)| *op == Op::Add).count();
+    let removed = script.iter().filter(|(op, _, _)| *op == Op::Remove).count();
+    if added == 0 && removed == 0 {
+        return (String::new(), 0, 0);
+    }
+
+    let mut out = format!("--- {old_label}\n+++ {new_label}\n");
+    let mut i = 0;
+    while i < script.len() {
+        let Some(first_change) = (i..script.len()).find(|&k| script[k].0 != Op::Keep) else {
+            break;
+        };
+        let start = first_change.saturating_sub(CONTEXT).max(i);
+
+        // Extend the hunk while the next change is within 2 * CONTEXT lines.
+        let mut end = first_change;
+        let mut k = first_change;
+        while k < script.len() {
+            if script[k].0 != Op::Keep {
+                end = k;
+                k += 1;
+                continue;
+            }
+            let next_change = (k..script.len()).find(|&j| script[j].0 != Op::Keep);
+            match next_change {
+                Some(j) if j - end <= 2 * CONTEXT => k = j,
+                _ => break,
+            }
+        }
+        let stop = (end + 1 + CONTEXT).min(script.len());
+
+        let hunk = &script[start..stop];
+        let old_count = hunk.iter().filter(|(op, _, _)| *op != Op::Add).count();
+        let new_count = hunk.iter().filter(|(op, _, _)| *op != Op::Remove).count();
+        let (_, old_start, new_start) = hunk[0];
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            range(old_start, old_count),
+            range(new_start, new_count)
+        ));
+        for &(op, old_idx, new_idx) in hunk {
+            match op {
+                Op::Keep => out.push_str(&format!(" {}\n", old_lines[old_idx])),
+                Op::Remove => out.push_str(&format!("-{}\n", old_lines[old_idx])),
+                Op::Add => out.push_str(&format!("+{}\n", new_lines[new_idx])),
+            }
+        }
+        i = stop;
+    }
+    (out, added, removed)
+}
+
+/// Hunk range in unified-diff form: 1-based start, and an empty range
+/// points at the line before it.
+fn range(start: usize, count: usize) -> String {
+    match count {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{count}", start + 1),
+    }
+}
+
+/// Edit script as `(op, old index, new index)`, where the indices are the
+/// positions in each side at that step.
+fn edit_script(old: &[&str], new: &[&str]) -> Vec<(Op, usize, usize)> {
+    let (n, m) = (old.len(), new.len());
+    // lcs[i][j] = length of the LCS of old[i..] and new[j..].
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut script = Vec::with_capacity(n.max(m));
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            script.push((Op::Keep, i, j));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            script.push((Op::Remove, i, j));
+            i += 1;
+        } else {
+            script.push((Op::Add, i, j));
+            j += 1;
+        }
+    }
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_inputs_produce_no_diff() {
+        assert_eq!(
+            unified_diff("a\nb\n", "a\nb\n", "old", "new"),
+            (String::new(), 0, 0)
+        );
+    }
+
+    #[test]
+    fn changed_line_is_shown_with_context() {
+        let old = "nodes:\n  - id: a\n    path: x\n";
+        let new = "nodes:\n  - id: a\n    path: y\n  - id: b\n";
+        let (diff, added, removed) = unified_diff(old, new, "v1", "current");
+        assert_eq!((added, removed), (2, 1));
+        assert_eq!(
+            diff,
+            "--- v1\n+++ current\n@@ -1,3 +1,4 @@\n nodes:\n   - id: a\n-    path: x\n+    path: y\n+  - id: b\n"
+        );
+    }
+
+    #[test]
+    fn distant_changes_are_split_into_hunks() {
+        let old: String = (1..=20).map(|n| format!("{n}\n")).collect();
+        let new: String = (1..=20)
+            .map(|n| match n {
+                2 => "two\n".to_string(),
+                19 => "nineteen\n".to_string(),
+                _ => format!("{n}\n"),
+            })
+            .collect();
+        let (diff, _, _) = unified_diff(&old, &new, "a", "b");
+        assert_eq!(diff.matches("@@ -").count(), 2);
+        assert!(diff.contains("@@ -1,5 +1,5 @@\n"));
+        assert!(diff.contains("@@ -16,5 +16,5 @@\n"));
+    }
+}
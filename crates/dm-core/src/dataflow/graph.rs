@@ -0,0 +1,672 @@
+//! The visual editor's node/edge model of a dataflow, and structural checks
+//! that apply to a graph whatever format it arrives in.
+//!
+//! The editor speaks SvelteFlow JSON: `nodes` carrying a `data` payload and
+//! `edges` between `out-<port>` and `in-<port>` handles, with `dora/timer/*`
+//! sources drawn as virtual nodes. Everything else speaks dora YAML. Both are
+//! validated as dora YAML.
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+use crate::node::{self, hub};
+
+use super::transpile::{diagnose_yaml, DiagnosticKind};
+
+/// A dataflow as the visual editor models it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DataflowGraph {
+    pub nodes: Vec<GraphNode>,
+    #[serde(default)]
+    pub edges: Vec<GraphEdge>,
+    /// Top-level YAML keys other than `nodes` (e.g. `communication`)
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub id: String,
+    /// SvelteFlow node type; always `dmNode` for dm graphs
+    #[serde(rename = "type", default = "dm_node_type")]
+    pub node_type: String,
+    #[serde(default)]
+    pub position: GraphPosition,
+    #[serde(default)]
+    pub data: GraphNodeData,
+}
+
+fn dm_node_type() -> String {
+    "dmNode".to_string()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphPosition {
+    pub x: f64,
+    pub y: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphNodeData {
+    #[serde(default)]
+    pub label: String,
+    /// Managed node id, a path for external nodes, or the `dora/timer/...`
+    /// source of a virtual timer node.
+    #[serde(default)]
+    pub node_type: String,
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    #[serde(default)]
+    pub outputs: Vec<String>,
+    /// Drawn by the editor only (timers, panel widgets); not a dora node.
+    #[serde(default)]
+    pub is_virtual: bool,
+    /// `timer` or `panel` for virtual nodes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub virtual_kind: Option<String>,
+    /// The node's other YAML keys (`path`, `config`, `env`, `operators`, …),
+    /// carried so YAML → JSON → YAML keeps them
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphEdge {
+    #[serde(default)]
+    pub id: String,
+    pub source: String,
+    pub target: String,
+    /// `out-<port>` on the source node
+    #[serde(default)]
+    pub source_handle: Option<String>,
+    /// `in-<port>` on the target node
+    #[serde(default)]
+    pub target_handle: Option<String>,
+}
+
+impl DataflowGraph {
+    /// Build the editor model of a dora YAML dataflow, the way the editor's
+    /// `yamlToGraph` does: one node per YAML node, a virtual node per timer
+    /// and one for panel widgets, and an edge per input. Positions are left
+    /// at the origin for the editor to lay out.
+    pub fn from_dora_yaml(yaml: &str) -> Result<Self> {
+        let dataflow: Value =
+            serde_yaml::from_str(yaml).context("Failed to parse dataflow yaml")?;
+        let root = dataflow.as_mapping().cloned().unwrap_or_default();
+        let mut graph = DataflowGraph::default();
+        for (key, value) in &root {
+            if let Some(key) = key.as_str().filter(|key| *key != "nodes") {
+                graph
+                    .fields
+                    .insert(key.to_string(), serde_json::to_value(value)?);
+            }
+        }
+        let entries = root
+            .get("nodes")
+            .and_then(Value::as_sequence)
+            .cloned()
+            .unwrap_or_default();
+
+        for entry in &entries {
+            let Some(map) = entry.as_mapping() else {
+                continue;
+            };
+            let id = entry.get("id").and_then(Value::as_str).unwrap_or_default();
+            let mut data = GraphNodeData {
+                label: id.to_string(),
+                node_type: entry
+                    .get("node")
+                    .or_else(|| entry.get("path"))
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+                ..Default::default()
+            };
+            if let Some(inputs) = entry.get("inputs").and_then(Value::as_mapping) {
+                data.inputs = inputs
+                    .keys()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect();
+            }
+            if let Some(outputs) = entry.get("outputs").and_then(Value::as_sequence) {
+                data.outputs = outputs
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .map(str::to_string)
+                    .collect();
+            }
+            for (key, value) in map {
+                let Some(key) = key.as_str() else {
+                    continue;
+                };
+                if !matches!(key, "id" | "node" | "inputs" | "outputs") {
+                    data.fields
+                        .insert(key.to_string(), serde_json::to_value(value)?);
+                }
+            }
+            graph.nodes.push(GraphNode {
+                id: id.to_string(),
+                node_type: dm_node_type(),
+                position: GraphPosition::default(),
+                data,
+            });
+        }
+
+        for entry in &entries {
+            let target = entry.get("id").and_then(Value::as_str).unwrap_or_default();
+            let Some(inputs) = entry.get("inputs").and_then(Value::as_mapping) else {
+                continue;
+            };
+            for (input, source) in inputs {
+                let (Some(input), Some(source)) = (input.as_str(), input_source(source)) else {
+                    continue;
+                };
+                let edge = if let Some(widget) = source.strip_prefix("panel/") {
+                    let virtual_id = "__virtual_panel";
+                    let panel = graph.virtual_node(virtual_id, "Panel Inputs", "panel", "panel");
+                    if !panel.data.outputs.iter().any(|port| port == widget) {
+                        panel.data.outputs.push(widget.to_string());
+                    }
+                    GraphEdge {
+                        id: format!("e-panel-{}-{}-{}", widget, target, input),
+                        source: virtual_id.to_string(),
+                        target: target.to_string(),
+                        source_handle: Some(format!("out-{}", widget)),
+                        target_handle: Some(format!("in-{}", input)),
+                    }
+                } else if let Some((source_id, port)) =
+                    source.split_once('/').filter(|(node, _)| *node != "dora")
+                {
+                    GraphEdge {
+                        id: format!("e-{}-{}-{}-{}", source_id, port, target, input),
+                        source: source_id.to_string(),
+                        target: target.to_string(),
+                        source_handle: Some(format!("out-{}", port)),
+                        target_handle: Some(format!("in-{}", input)),
+                    }
+                } else {
+                    let virtual_id = format!("__virtual_{}", source.replace('/', "_"));
+                    let parts: Vec<&str> = source.split('/').collect();
+                    let label = match parts.as_slice() {
+                        [_, _, _, interval, ..] => format!("Timer {}ms", interval),
+                        _ => source.to_string(),
+                    };
+                    let timer = graph.virtual_node(&virtual_id, &label, source, "timer");
+                    timer.data.outputs = vec!["tick".to_string()];
+                    GraphEdge {
+                        id: format!("e-{}-tick-{}-{}", virtual_id, target, input),
+                        source: virtual_id,
+                        target: target.to_string(),
+                        source_handle: Some("out-tick".to_string()),
+                        target_handle: Some(format!("in-{}", input)),
+                    }
+                };
+                graph.edges.push(edge);
+            }
+        }
+        Ok(graph)
+    }
+
+    fn virtual_node(
+        &mut self,
+        id: &str,
+        label: &str,
+        node_type: &str,
+        kind: &str,
+    ) -> &mut GraphNode {
+        let index = match self.nodes.iter().position(|node| node.id == id) {
+            Some(index) => index,
+            None => {
+                self.nodes.push(GraphNode {
+                    id: id.to_string(),
+                    node_type: dm_node_type(),
+                    position: GraphPosition::default(),
+                    data: GraphNodeData {
+                        label: label.to_string(),
+                        node_type: node_type.to_string(),
+                        is_virtual: true,
+                        virtual_kind: Some(kind.to_string()),
+                        ..Default::default()
+                    },
+                });
+                self.nodes.len() - 1
+            }
+        };
+        &mut self.nodes[index]
+    }
+
+    /// The dora YAML this graph stands for.
+    pub fn to_dora_yaml(&self) -> Result<String> {
+        Ok(serde_yaml::to_string(&self.to_yaml_value()?)?)
+    }
+
+    /// Edges become `inputs:` on their target; virtual nodes are not
+    /// emitted.
+    fn to_yaml_value(&self) -> Result<Value> {
+        let by_id: HashMap<&str, &GraphNode> = self
+            .nodes
+            .iter()
+            .map(|node| (node.id.as_str(), node))
+            .collect();
+
+        let mut inputs: HashMap<&str, serde_yaml::Mapping> = HashMap::new();
+        for edge in &self.edges {
+            let port = strip_handle(edge.source_handle.as_deref(), "out-");
+            let source = match by_id.get(edge.source.as_str()) {
+                Some(node) if node.data.is_virtual => match node.data.virtual_kind.as_deref() {
+                    Some("timer") => node.data.node_type.clone(),
+                    Some("panel") => format!("panel/{}", port),
+                    _ => continue,
+                },
+                // Unknown sources are kept so validation can report them.
+                _ => format!("{}/{}", edge.source, port),
+            };
+            inputs.entry(edge.target.as_str()).or_default().insert(
+                strip_handle(edge.target_handle.as_deref(), "in-").into(),
+                source.into(),
+            );
+        }
+
+        let mut nodes = Vec::new();
+        for node in self.nodes.iter().filter(|node| !node.data.is_virtual) {
+            let mut entry = serde_yaml::Mapping::new();
+            entry.insert("id".into(), node.id.clone().into());
+            let node_type = &node.data.node_type;
+            if node.data.fields.contains_key("path") || looks_like_path(node_type) {
+                if !node_type.is_empty() {
+                    entry.insert("path".into(), node_type.clone().into());
+                }
+            } else if !node_type.is_empty() {
+                entry.insert("node".into(), node_type.clone().into());
+            }
+            for (key, value) in &node.data.fields {
+                if !entry.contains_key(key.as_str()) {
+                    entry.insert(key.clone().into(), serde_yaml::to_value(value)?);
+                }
+            }
+            if let Some(inputs) = inputs.remove(node.id.as_str()) {
+                entry.insert("inputs".into(), Value::Mapping(inputs));
+            }
+            if !node.data.outputs.is_empty() {
+                entry.insert(
+                    "outputs".into(),
+                    Value::Sequence(
+                        node.data
+                            .outputs
+                            .iter()
+                            .map(|port| port.clone().into())
+                            .collect(),
+                    ),
+                );
+            }
+            nodes.push(Value::Mapping(entry));
+        }
+
+        let mut root = serde_yaml::Mapping::new();
+        for (key, value) in &self.fields {
+            root.insert(key.clone().into(), serde_yaml::to_value(value)?);
+        }
+        root.insert("nodes".into(), Value::Sequence(nodes));
+        Ok(Value::Mapping(root))
+    }
+}
+
+fn strip_handle<'a>(handle: Option<&'a str>, prefix: &str) -> &'a str {
+    let handle = handle.unwrap_or_default();
+    handle.strip_prefix(prefix).unwrap_or(handle)
+}
+
+fn looks_like_path(node_type: &str) -> bool {
+    node_type.contains(['/', '\\']) || node_type.ends_with(".py")
+}
+
+/// Parse `text` as editor JSON (an object with an `edges` list) or as dora
+/// YAML, returning the dora YAML either way.
+pub fn parse_graph(text: &str) -> Result<Value> {
+    let value: Value =
+        serde_yaml::from_str(text).context("Failed to parse graph as YAML or JSON")?;
+    if is_editor_graph(&value) {
+        let graph: DataflowGraph =
+            serde_yaml::from_value(value).context("Failed to parse editor graph JSON")?;
+        return graph.to_yaml_value();
+    }
+    Ok(value)
+}
+
+fn is_editor_graph(value: &Value) -> bool {
+    value.get("edges").is_some_and(Value::is_sequence)
+}
+
+/// Target of [`convert_graph`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    /// dora YAML
+    Yaml,
+    /// The editor's node/edge JSON
+    Json,
+}
+
+impl std::str::FromStr for GraphFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "yaml" | "yml" => Ok(Self::Yaml),
+            "json" => Ok(Self::Json),
+            _ => anyhow::bail!("Unknown graph format '{}' (expected yaml or json)", s),
+        }
+    }
+}
+
+/// Convert `text`, editor JSON or dora YAML, to `to`. Text already in the
+/// target format is normalized rather than passed through.
+pub fn convert_graph(text: &str, to: GraphFormat) -> Result<String> {
+    let yaml = serde_yaml::to_string(&parse_graph(text)?)?;
+    match to {
+        GraphFormat::Yaml => Ok(yaml),
+        GraphFormat::Json => Ok(serde_json::to_string_pretty(
+            &DataflowGraph::from_dora_yaml(&yaml)?,
+        )?),
+    }
+}
+
+/// One finding about a graph, tied to the YAML node id when there is one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GraphIssue {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GraphValidation {
+    /// No errors; warnings don't stop a graph from starting
+    pub valid: bool,
+    pub errors: Vec<GraphIssue>,
+    pub warnings: Vec<GraphIssue>,
+}
+
+impl GraphValidation {
+    fn error(&mut self, node: Option<&str>, message: String) {
+        self.errors.push(GraphIssue {
+            node: node.map(str::to_string),
+            message,
+        });
+    }
+
+    fn warning(&mut self, node: Option<&str>, message: String) {
+        self.warnings.push(GraphIssue {
+            node: node.map(str::to_string),
+            message,
+        });
+    }
+
+    fn finish(mut self) -> Self {
+        self.valid = self.errors.is_empty();
+        self
+    }
+}
+
+/// Check `text` (editor JSON or dora YAML) for everything that would stop it
+/// from starting: duplicate or missing ids, inputs from unknown nodes or
+/// undeclared outputs, nodes that are neither installed nor in the registry,
+/// and transpile errors. Cycles and registry nodes that `dm start` would
+/// still have to install are warnings.
+pub fn validate_graph(home: &Path, text: &str) -> GraphValidation {
+    let mut report = GraphValidation::default();
+    let dataflow = match parse_graph(text) {
+        Ok(dataflow) => dataflow,
+        Err(e) => {
+            report.error(None, format!("{:#}", e));
+            return report.finish();
+        }
+    };
+    let Some(nodes) = dataflow.get("nodes").and_then(Value::as_sequence) else {
+        report.error(None, "graph has no `nodes:` list".to_string());
+        return report.finish();
+    };
+
+    // yaml id → outputs declared in the YAML; `None` for operator runtimes,
+    // whose sources are `<node>/<operator>/<output>`.
+    let mut outputs: HashMap<&str, Option<Vec<&str>>> = HashMap::new();
+    for (index, entry) in nodes.iter().enumerate() {
+        let Some(id) = entry
+            .get("id")
+            .and_then(Value::as_str)
+            .filter(|id| !id.is_empty())
+        else {
+            report.error(None, format!("node #{} has no `id`", index + 1));
+            continue;
+        };
+        let declared = if entry.get("operators").is_some() || entry.get("operator").is_some() {
+            None
+        } else {
+            Some(
+                entry
+                    .get("outputs")
+                    .and_then(Value::as_sequence)
+                    .map(|ports| ports.iter().filter_map(Value::as_str).collect())
+                    .unwrap_or_default(),
+            )
+        };
+        match outputs.entry(id) {
+            Entry::Occupied(_) => report.error(Some(id), format!("duplicate node id '{}'", id)),
+            Entry::Vacant(slot) => {
+                slot.insert(declared);
+            }
+        }
+
+        let managed = entry.get("node").and_then(Value::as_str);
+        let runnable = managed.is_some()
+            || entry.get("path").is_some()
+            || entry.get("operators").is_some()
+            || entry.get("operator").is_some();
+        if !runnable {
+            report.error(
+                Some(id),
+                "needs one of `node:`, `path:` or `operators:`".to_string(),
+            );
+        }
+        let Some(node_id) = managed else {
+            continue;
+        };
+        if node::resolve_node_dir(home, node_id).is_some() {
+            continue;
+        }
+        let has_git_source = entry
+            .get("source")
+            .and_then(|source| source.get("git"))
+            .is_some();
+        if has_git_source || hub::is_in_registry(home, node_id) {
+            report.warning(
+                Some(id),
+                format!(
+                    "node '{}' is not installed; `dm start` will install it",
+                    node_id
+                ),
+            );
+        } else {
+            report.error(
+                Some(id),
+                format!(
+                    "node '{}' is not installed and not in the registry",
+                    node_id
+                ),
+            );
+        }
+    }
+
+    for entry in nodes {
+        let Some(id) = entry.get("id").and_then(Value::as_str) else {
+            continue;
+        };
+        let Some(inputs) = entry.get("inputs") else {
+            continue;
+        };
+        let Some(inputs) = inputs.as_mapping() else {
+            report.error(Some(id), "`inputs:` must be a mapping".to_string());
+            continue;
+        };
+        for (input, source) in inputs {
+            let input = input.as_str().unwrap_or_default();
+            let Some(source) = input_source(source) else {
+                report.error(Some(id), format!("input '{}' has no source", input));
+                continue;
+            };
+            let Some((source_id, port)) = source.split_once('/') else {
+                report.error(
+                    Some(id),
+                    format!(
+                        "input '{}' source '{}' is not `<node>/<output>`",
+                        input, source
+                    ),
+                );
+                continue;
+            };
+            if source_id == "dora" {
+                continue;
+            }
+            match outputs.get(source_id) {
+                None => report.error(
+                    Some(id),
+                    format!(
+                        "input '{}' reads from '{}', which is not a node in this graph",
+                        input, source
+                    ),
+                ),
+                Some(Some(declared)) if !declared.contains(&port) => report.error(
+                    Some(id),
+                    format!(
+                        "input '{}' reads '{}', but '{}' does not list '{}' in its outputs",
+                        input, source, source_id, port
+                    ),
+                ),
+                Some(_) => {}
+            }
+        }
+    }
+
+    for cycle in detect_cycles(&dataflow) {
+        let mut path = cycle.clone();
+        path.push(cycle[0].clone());
+        report.warning(Some(&cycle[0]), format!("cycle: {}", path.join(" → ")));
+    }
+
+    // Missing nodes and unknown sources are reported above, with more context.
+    match serde_yaml::to_string(&dataflow)
+        .map_err(anyhow::Error::from)
+        .and_then(|yaml| diagnose_yaml(home, &yaml))
+    {
+        Ok(diags) => {
+            for diag in diags {
+                if matches!(
+                    diag.kind,
+                    DiagnosticKind::NodeNotInstalled | DiagnosticKind::UnknownInputSource { .. }
+                ) {
+                    continue;
+                }
+                let message = diag.to_string();
+                if diag.is_error() {
+                    report.error(Some(&diag.yaml_id), message);
+                } else {
+                    report.warning(Some(&diag.yaml_id), message);
+                }
+            }
+        }
+        Err(e) => report.error(None, format!("{:#}", e)),
+    }
+
+    report.finish()
+}
+
+fn input_source(value: &Value) -> Option<&str> {
+    value
+        .as_str()
+        .or_else(|| value.get("source").and_then(Value::as_str))
+}
+
+/// Cycles in the node → node data flow of a dora YAML graph, each as the
+/// node ids along it starting from the first one reached. A node reading its
+/// own output is a cycle of one.
+pub fn detect_cycles(dataflow: &Value) -> Vec<Vec<String>> {
+    let mut edges: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for entry in dataflow
+        .get("nodes")
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+    {
+        if let Some(id) = entry.get("id").and_then(Value::as_str) {
+            edges.entry(id).or_default();
+        }
+    }
+    for entry in dataflow
+        .get("nodes")
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+    {
+        let Some(target) = entry.get("id").and_then(Value::as_str) else {
+            continue;
+        };
+        let Some(inputs) = entry.get("inputs").and_then(Value::as_mapping) else {
+            continue;
+        };
+        for source in inputs.values().filter_map(input_source) {
+            let Some((source_id, _)) = source.split_once('/') else {
+                continue;
+            };
+            if let Some(targets) = edges.get_mut(source_id) {
+                targets.insert(target);
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        Unvisited,
+        OnStack,
+        Done,
+    }
+
+    fn visit<'a>(
+        node: &'a str,
+        edges: &BTreeMap<&'a str, BTreeSet<&'a str>>,
+        marks: &mut HashMap<&'a str, Mark>,
+        stack: &mut Vec<&'a str>,
+        cycles: &mut Vec<Vec<String>>,
+    ) {
+        marks.insert(node, Mark::OnStack);
+        stack.push(node);
+        for &next in &edges[node] {
+            match marks[next] {
+                Mark::Unvisited => visit(next, edges, marks, stack, cycles),
+                Mark::OnStack => {
+                    let start = stack.iter().position(|&n| n == next).unwrap_or(0);
+                    cycles.push(stack[start..].iter().map(|n| n.to_string()).collect());
+                }
+                Mark::Done => {}
+            }
+        }
+        stack.pop();
+        marks.insert(node, Mark::Done);
+    }
+
+    let mut marks: HashMap<&str, Mark> = edges.keys().map(|&id| (id, Mark::Unvisited)).collect();
+    let mut cycles = Vec::new();
+    for &node in edges.keys() {
+        if marks[node] == Mark::Unvisited {
+            visit(node, &edges, &mut marks, &mut Vec::new(), &mut cycles);
+        }
+    }
+    cycles
+}
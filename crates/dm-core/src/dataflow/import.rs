@@ -5,6 +5,8 @@ use std::process::Command;
 use anyhow::{Context, Result};
 use fs_extra::dir::{copy as dir_copy, CopyOptions};
 
+use crate::error::DmError;
+
 use super::paths::{
     dataflow_dir, dataflow_yaml_path, flow_config_path, flow_meta_path, FLOW_CONFIG_FILE,
     FLOW_META_FILE,
@@ -44,7 +46,7 @@ pub fn import_local(home: &Path, name: &str, source: &Path) -> Result<()> {
     }
     let project_dir = dataflow_dir(home, name);
     if project_dir.exists() {
-        anyhow::bail!("Dataflow '{}' already exists", name);
+        return Err(DmError::Conflict(format!("Dataflow '{}' already exists", name)).into());
     }
 
     if source.is_file() {
@@ -59,7 +61,7 @@ pub fn import_local(home: &Path, name: &str, source: &Path) -> Result<()> {
 pub async fn import_git(home: &Path, name: &str, git_url: &str) -> Result<()> {
     let project_dir = dataflow_dir(home, name);
     if project_dir.exists() {
-        anyhow::bail!("Dataflow '{}' already exists", name);
+        return Err(DmError::Conflict(format!("Dataflow '{}' already exists", name)).into());
     }
 
     let nanos = std::time::SystemTime::now()
@@ -17,8 +17,12 @@ pub fn inspect(home: &Path, name: &str) -> Result<DataflowExecutableDetail> {
     Ok(inspect_yaml(home, &yaml))
 }
 
+/// Inspect `yaml` as it would run, with its `variables:` at their defaults.
 pub fn inspect_yaml(home: &Path, yaml: &str) -> DataflowExecutableDetail {
-    match serde_yaml::from_str::<serde_yaml::Value>(yaml) {
+    let graph = super::render(yaml, &Default::default()).and_then(|rendered| {
+        serde_yaml::from_str::<serde_yaml::Value>(&rendered).map_err(anyhow::Error::from)
+    });
+    match graph {
         Ok(graph) => inspect_graph(home, &graph),
         Err(err) => DataflowExecutableDetail {
             summary: DataflowExecutableSummary {
@@ -34,7 +38,7 @@ pub fn inspect_yaml(home: &Path, yaml: &str) -> DataflowExecutableDetail {
                 requires_media_backend: false,
                 media_node_count: 0,
                 media_nodes: Vec::new(),
-                error: Some(err.to_string()),
+                error: Some(format!("{:#}", err)),
             },
             nodes: Vec::new(),
         },
@@ -64,7 +68,16 @@ fn inspect_graph(home: &Path, graph: &serde_yaml::Value) -> DataflowExecutableDe
                 .and_then(|git| git.as_str())
                 .map(|s| s.to_string());
 
-            if let Some(node_id) = entry.get("node").and_then(|value| value.as_str()) {
+            let path_value = entry.get("path").and_then(|value| value.as_str());
+            // Same rule as transpile's resolve_paths: a bare registry id
+            // without a `build:` step is a dm node, not an executable.
+            let node_id = entry
+                .get("node")
+                .and_then(|value| value.as_str())
+                .or(path_value.filter(|path| {
+                    entry.get("build").is_none() && hub::names_registry_node(home, path)
+                }));
+            if let Some(node_id) = node_id {
                 let resolved = resolve_node_dir(home, node_id).is_some();
                 let configurable = resolved && resolve_dm_json_path(home, node_id).is_some();
                 if resolved && node_requires_media_backend(home, node_id) {
@@ -75,7 +88,7 @@ fn inspect_graph(home: &Path, graph: &serde_yaml::Value) -> DataflowExecutableDe
                 } else {
                     // Check if we have a git URL from source.git or registry
                     let git_url = source_git_url.clone().or_else(|| {
-                        hub::resolve_node_source(node_id).and_then(|s| match s {
+                        hub::resolve_node_source(home, node_id).and_then(|s| match s {
                             hub::NodeSource::Git(url) => Some(url),
                             _ => None,
                         })
@@ -94,7 +107,7 @@ fn inspect_graph(home: &Path, graph: &serde_yaml::Value) -> DataflowExecutableDe
                     source: "managed_node".to_string(),
                     source_git_url,
                 });
-            } else if let Some(path_value) = entry.get("path").and_then(|value| value.as_str()) {
+            } else if let Some(path_value) = path_value {
                 nodes.push(DataflowNodeResolution {
                     yaml_id,
                     node_id: path_value.to_string(),
@@ -221,6 +234,44 @@ nodes:
         );
     }
 
+    #[test]
+    fn inspect_yaml_treats_a_bare_registry_path_as_a_node() {
+        let tmp = tempdir().unwrap();
+        let home = tmp.path();
+        std::fs::write(
+            home.join("registry.json"),
+            r#"{"nodes": {"dm-test-remote": {"source": {"type": "git", "url": "https://github.com/example/remote.git"}}}}"#,
+        )
+        .unwrap();
+
+        let yaml = r#"
+nodes:
+  - id: remote
+    path: dm-test-remote
+  - id: script
+    path: ./script.py
+  - id: unknown
+    path: not-a-registry-node
+"#;
+
+        let detail = inspect_yaml(home, yaml);
+        assert_eq!(detail.summary.missing_nodes, vec!["dm-test-remote"]);
+        assert_eq!(
+            detail.summary.missing_nodes_with_git_url.unwrap()["dm-test-remote"],
+            "https://github.com/example/remote.git"
+        );
+        let sources: Vec<&str> = detail.nodes.iter().map(|n| n.source.as_str()).collect();
+        assert_eq!(sources, ["managed_node", "external_path", "external_path"]);
+
+        let diags = crate::dataflow::diagnose_yaml(home, yaml).unwrap();
+        assert_eq!(diags.len(), 1);
+        assert_eq!(diags[0].node_id, "dm-test-remote");
+        assert!(matches!(
+            diags[0].kind,
+            crate::dataflow::DiagnosticKind::NodeNotInstalled
+        ));
+    }
+
     #[test]
     fn inspect_yaml_without_git_url() {
         let tmp = tempdir().unwrap();
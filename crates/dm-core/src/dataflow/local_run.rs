@@ -0,0 +1,367 @@
+//! Foreground runs via `dora run` (`dm run`).
+//!
+//! In uv mode the per-node virtualenvs are bypassed: every Python node of the
+//! graph is folded into one generated `pyproject.toml`, synced once with
+//! `uv sync`, and the transpiled graph points at that shared environment.
+//! Python nodes then only need to be downloaded, not installed: missing ones
+//! with a known git source are fetched without building their venvs.
+//!
+//! Every invocation writes its artifacts (original and transpiled YAML,
+//! captured output) to its own directory, so concurrent runs of the same
+//! dataflow never overwrite each other; old ones are pruned by the
+//! `[retention]` policy.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+use crate::dora::{self, RuntimeEnv};
+use crate::events::{EventSource, OperationEvent};
+use crate::node::{self, Node};
+use crate::runs::OutputCapture;
+
+use super::paths;
+use super::transpile::{transpile_graph_for_uv, transpile_graph_with_variables};
+
+const PYPROJECT_FILE: &str = "pyproject.toml";
+const TRANSPILED_FILE: &str = "dataflow.transpiled.yml";
+const OUTPUT_LOG_FILE: &str = "output.log";
+
+/// A prepared `dm run` working directory.
+#[derive(Debug, Clone)]
+pub struct LocalRunPlan {
+    /// Dataflow name (the YAML file stem).
+    pub name: String,
+    /// Id of this invocation.
+    pub run_id: String,
+    /// Directory `dora run` is invoked from, holding the shared uv project
+    /// (`<home>/local-runs/<name>`).
+    pub dir: PathBuf,
+    /// This invocation's artifacts (`<dir>/runs/<run_id>`).
+    pub run_dir: PathBuf,
+    /// Transpiled dataflow inside `run_dir`.
+    pub dataflow: PathBuf,
+    /// Python packages of the consolidated uv project (empty without uv).
+    pub packages: Vec<String>,
+}
+
+/// A Python node's contribution to the consolidated uv project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct UvDependency {
+    /// Distribution requirement listed under `[project].dependencies`.
+    pub requirement: String,
+    /// Local source tree, installed editable through `[tool.uv.sources]`.
+    pub path: Option<PathBuf>,
+    pub requires_python: Option<String>,
+}
+
+/// Run a dataflow in the foreground with `dora run`, returning its exit code.
+///
+/// Runs started this way are not recorded in run history; their output is
+/// recorded as `dora.output` events with the dataflow name as case id.
+/// Ctrl-C stops dora and every node it started (see `exec_dora_with_env`).
+/// `variables` override the defaults of the graph's `variables:` section.
+pub async fn run_local(
+    home: &Path,
+    yaml_path: &Path,
+    uv: bool,
+    verbose: bool,
+    variables: &BTreeMap<String, String>,
+) -> Result<i32> {
+    let op = OperationEvent::new(home, EventSource::Dataflow, "dataflow.run")
+        .attr("path", yaml_path.display().to_string())
+        .attr("uv", uv);
+    op.emit_start();
+
+    let result = async {
+        if uv {
+            fetch_missing_nodes(home, yaml_path, variables, &op).await?;
+        }
+        let plan = prepare_local_run(home, yaml_path, uv, variables)?;
+        // Marks the directory as in use for retention run by other invocations.
+        let pid_file = paths::local_run_pid_path(&plan.run_dir);
+        std::fs::write(&pid_file, format!("{}\n", std::process::id()))
+            .with_context(|| format!("Failed to write {}", pid_file.display()))?;
+        let mut args = vec!["run".to_string(), plan.dataflow.display().to_string()];
+        let mut env = RuntimeEnv::from_config(home);
+
+        if uv {
+            op.emit_progress(
+                "sync",
+                None,
+                format!("Syncing {} Python package(s) with uv", plan.packages.len()),
+            );
+            uv_sync(&plan.dir, verbose).await?;
+            args.push("--uv".to_string());
+            env.vars.insert(
+                "VIRTUAL_ENV".to_string(),
+                plan.dir.join(".venv").display().to_string(),
+            );
+        }
+
+        op.emit_progress("run", None, "Running dataflow with dora run");
+        let mut capture =
+            OutputCapture::new(home, &plan.name, OutputCapture::nodes_of(&plan.dataflow))
+                .with_log_file(&plan.run_dir.join(OUTPUT_LOG_FILE));
+        let code = dora::exec_dora_with_env(
+            home,
+            &args,
+            Some(&plan.dir),
+            &env,
+            Some(&mut capture),
+            verbose,
+        )
+        .await;
+        let _ = std::fs::remove_file(&pid_file);
+        if let Err(e) = crate::runs::apply_retention(home) {
+            eprintln!("Warning: failed to prune old runs: {}", e);
+        }
+        code
+    }
+    .await;
+
+    op.emit_result(&result);
+    result
+}
+
+/// Transpile `yaml_path` into a fresh artifacts directory of its `dm run`
+/// directory, next to a copy of the original YAML.
+///
+/// With `uv`, a consolidated `pyproject.toml` is written to the dataflow's
+/// `dm run` directory and Python nodes are resolved into its `.venv`, so
+/// they need not be installed.
+pub fn prepare_local_run(
+    home: &Path,
+    yaml_path: &Path,
+    uv: bool,
+    variables: &BTreeMap<String, String>,
+) -> Result<LocalRunPlan> {
+    let name = yaml_path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let dir = paths::local_run_dir(home, &name);
+    let run_id = uuid::Uuid::new_v4().to_string();
+    let mut packages = Vec::new();
+
+    let yaml = if uv {
+        let source = std::fs::read_to_string(yaml_path)
+            .with_context(|| format!("Failed to read graph yaml at {}", yaml_path.display()))?;
+        let source = super::render(&source, variables)?;
+        let deps = python_dependencies(home, &source)?;
+        if deps.is_empty() {
+            bail!(
+                "'{}' has no Python nodes; run it without --uv.",
+                yaml_path.display()
+            );
+        }
+
+        let yaml =
+            transpile_graph_for_uv(home, yaml_path, &run_id, variables, &dir.join(".venv"))?.yaml;
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create run directory {}", dir.display()))?;
+        std::fs::write(
+            dir.join(PYPROJECT_FILE),
+            consolidated_pyproject(&name, &deps)?,
+        )
+        .with_context(|| format!("Failed to write {}", dir.join(PYPROJECT_FILE).display()))?;
+        packages = deps.into_iter().map(|d| d.requirement).collect();
+        yaml
+    } else {
+        transpile_graph_with_variables(home, yaml_path, &run_id, variables)?.yaml
+    };
+
+    let run_dir = paths::local_run_artifacts_dir(home, &name, &run_id);
+    std::fs::create_dir_all(&run_dir)
+        .with_context(|| format!("Failed to create run directory {}", run_dir.display()))?;
+    std::fs::copy(yaml_path, paths::dataflow_yaml_path(&run_dir))
+        .with_context(|| format!("Failed to copy {}", yaml_path.display()))?;
+    let dataflow = run_dir.join(TRANSPILED_FILE);
+    let content = serde_yaml::to_string(&yaml).context("Failed to serialize dataflow")?;
+    std::fs::write(&dataflow, content)
+        .with_context(|| format!("Failed to write {}", dataflow.display()))?;
+
+    Ok(LocalRunPlan {
+        name,
+        run_id,
+        dir,
+        run_dir,
+        dataflow,
+        packages,
+    })
+}
+
+/// Fetch the downloadable nodes `yaml_path` uses that are missing, without
+/// installing them: `uv sync` provides their environment.
+async fn fetch_missing_nodes(
+    home: &Path,
+    yaml_path: &Path,
+    variables: &BTreeMap<String, String>,
+    op: &OperationEvent,
+) -> Result<()> {
+    let source = std::fs::read_to_string(yaml_path)
+        .with_context(|| format!("Failed to read graph yaml at {}", yaml_path.display()))?;
+    let source = super::render(&source, variables)?;
+    let missing = super::inspect_yaml(home, &source)
+        .summary
+        .missing_nodes_with_git_url
+        .unwrap_or_default();
+    for (node_id, url) in missing {
+        op.emit_progress("fetch", None, format!("Fetching {} from {}", node_id, url));
+        node::import_git(home, &node_id, &url)
+            .await
+            .with_context(|| format!("Failed to fetch node '{}'", node_id))?;
+    }
+    Ok(())
+}
+
+/// Requirements of the managed Python nodes in a DM graph, without
+/// duplicates.
+fn python_dependencies(home: &Path, source: &str) -> Result<Vec<UvDependency>> {
+    let graph: serde_yaml::Value =
+        serde_yaml::from_str(source).context("Failed to parse graph yaml")?;
+    let mut found: Vec<UvDependency> = Vec::new();
+
+    for entry in graph
+        .get("nodes")
+        .and_then(|nodes| nodes.as_sequence())
+        .into_iter()
+        .flatten()
+    {
+        let Some(node_id) = entry.get("node").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let (Some(node_dir), Some(meta_path)) = (
+            node::resolve_node_dir(home, node_id),
+            node::resolve_dm_json_path(home, node_id),
+        ) else {
+            continue;
+        };
+        let Some(meta) = std::fs::read_to_string(&meta_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Node>(&content).ok())
+        else {
+            continue;
+        };
+        if meta.runtime.language != "python" {
+            continue;
+        }
+
+        let dep = python_dependency(&meta, &node_dir);
+        if !found.iter().any(|d| d.requirement == dep.requirement) {
+            found.push(dep);
+        }
+    }
+
+    Ok(found)
+}
+
+/// Entry point a Python node runs from in the shared uv environment: the
+/// file name of its executable, or its distribution name before install.
+pub(crate) fn uv_script(meta: &Node, node_dir: &Path) -> String {
+    Path::new(&meta.executable)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| {
+            distribution_name(&python_dependency(meta, node_dir).requirement).to_string()
+        })
+}
+
+fn python_dependency(meta: &Node, node_dir: &Path) -> UvDependency {
+    match node::init::parse_pyproject(node_dir).filter(|py| !py.name.is_empty()) {
+        Some(py) => UvDependency {
+            requirement: py.name,
+            path: Some(node_dir.to_path_buf()),
+            requires_python: py.requires_python.or_else(|| meta.runtime.python.clone()),
+        },
+        None => UvDependency {
+            requirement: node::package_spec_from_build(meta),
+            path: None,
+            requires_python: meta.runtime.python.clone(),
+        },
+    }
+}
+
+/// `dora-yolo>=0.3` → `dora-yolo`.
+fn distribution_name(requirement: &str) -> &str {
+    requirement
+        .split(|c: char| "<>=!~[;@ ".contains(c))
+        .next()
+        .unwrap_or(requirement)
+}
+
+/// Render the `pyproject.toml` of the shared uv environment.
+pub(crate) fn consolidated_pyproject(name: &str, deps: &[UvDependency]) -> Result<String> {
+    let slug: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+
+    let mut project = toml::Table::new();
+    project.insert("name".into(), format!("dm-run-{}", slug).into());
+    project.insert("version".into(), "0.0.0".into());
+    let requires_python = deps
+        .iter()
+        .filter_map(|d| d.requires_python.as_deref())
+        .collect::<BTreeSet<_>>();
+    if !requires_python.is_empty() {
+        project.insert(
+            "requires-python".into(),
+            requires_python
+                .into_iter()
+                .collect::<Vec<_>>()
+                .join(",")
+                .into(),
+        );
+    }
+    project.insert(
+        "dependencies".into(),
+        toml::Value::Array(deps.iter().map(|d| d.requirement.clone().into()).collect()),
+    );
+
+    let mut sources = toml::Table::new();
+    for dep in deps {
+        if let Some(path) = &dep.path {
+            let mut source = toml::Table::new();
+            source.insert("path".into(), path.display().to_string().into());
+            source.insert("editable".into(), true.into());
+            sources.insert(dep.requirement.clone(), source.into());
+        }
+    }
+
+    let mut uv = toml::Table::new();
+    uv.insert("package".into(), false.into());
+    if !sources.is_empty() {
+        uv.insert("sources".into(), sources.into());
+    }
+    let mut tool = toml::Table::new();
+    tool.insert("uv".into(), uv.into());
+
+    let mut doc = toml::Table::new();
+    doc.insert("project".into(), project.into());
+    doc.insert("tool".into(), tool.into());
+
+    let body = toml::to_string(&doc).context("Failed to render pyproject.toml")?;
+    Ok(format!(
+        "# Generated by `dm run --uv` from the graph's Python nodes. Do not edit.\n{}",
+        body
+    ))
+}
+
+async fn uv_sync(dir: &Path, verbose: bool) -> Result<()> {
+    if verbose {
+        eprintln!("[dm] exec: uv sync (in {})", dir.display());
+    }
+    let status = tokio::process::Command::new("uv")
+        .arg("sync")
+        .current_dir(dir)
+        .status()
+        .await
+        .context("Failed to run `uv sync`. Is uv installed? Try `dm setup`.")?;
+    if !status.success() {
+        bail!("`uv sync` failed in {}", dir.display());
+    }
+    Ok(())
+}
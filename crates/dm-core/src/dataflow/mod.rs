@@ -1,22 +1,51 @@
+mod bundle;
+mod diff;
+mod graph;
 mod import;
 mod inspect;
+mod local_run;
 mod model;
 mod paths;
+mod render;
 mod repo;
 mod service;
+mod sync;
 mod transpile;
+mod validate;
 
+pub use bundle::{
+    export_bundle, import_bundle, read_bundle, write_bundle, DATAFLOW_BUNDLE_EXTENSION,
+};
+pub use graph::{
+    convert_graph, detect_cycles, parse_graph, validate_graph, DataflowGraph, GraphEdge,
+    GraphFormat, GraphIssue, GraphNode, GraphNodeData, GraphPosition, GraphValidation,
+};
 pub use import::infer_import_name;
 pub use inspect::{inspect, inspect_yaml};
+pub use local_run::{prepare_local_run, run_local, LocalRunPlan};
 pub use model::{
-    AggregatedConfigField, AggregatedConfigNode, DataflowConfigAggregation,
-    DataflowExecutableDetail, DataflowExecutableStatus, DataflowExecutableSummary,
-    DataflowHistoryEntry, DataflowImportFailure, DataflowImportReport, DataflowImportSuccess,
-    DataflowListEntry, DataflowMeta, DataflowNodeResolution, DataflowProject, FlowMeta,
+    AggregatedConfigField, AggregatedConfigNode, BundledNode, DataflowBundle, DataflowBundleImport,
+    DataflowConfigAggregation, DataflowExecutableDetail, DataflowExecutableStatus,
+    DataflowExecutableSummary, DataflowHistoryDiff, DataflowHistoryEntry, DataflowImportFailure,
+    DataflowImportReport, DataflowImportSuccess, DataflowListEntry, DataflowMeta,
+    DataflowNodeResolution, DataflowProject, DataflowSyncReport, FlowMeta,
 };
+pub use paths::{
+    dataflow_dir, dataflow_yaml_path, dataflows_dir, local_run_artifacts_dir, local_run_pid_path,
+    local_runs_dir,
+};
+pub use render::render;
 pub use service::{
-    delete, get, get_flow_meta, get_flow_view, get_history_version, import_git, import_local,
-    import_sources, inspect_config, list, list_history, migrate_legacy_layout,
-    restore_history_version, save, save_flow_meta, save_flow_view,
+    dataflows_using_node, dataflows_with_tag, delete, diff_history_version, get, get_flow_meta,
+    get_flow_view, get_history_version, import_git, import_local, import_sources, inspect_config,
+    list, list_history, migrate_legacy_layout, restore_history_version, save, save_flow_meta,
+    save_flow_view, tag_missing_node, MISSING_NODE_TAG_PREFIX,
+};
+pub use sync::sync;
+pub use transpile::{
+    check_golden, check_yaml, diagnose_yaml, preview_node_env, render_golden, transpile_graph,
+    transpile_graph_for_run, transpile_graph_with_variables, DiagnosticKind, GoldenCheck,
+    NodeEnvPreview, TranspileDiagnostic, TranspileError, TranspileResult, GOLDEN_HOME_PLACEHOLDER,
+    GOLDEN_RUN_ID, PREVIEW_RUN_ID,
 };
-pub use transpile::{transpile_graph, transpile_graph_for_run, TranspileResult};
+pub use validate::{validate_all, DataflowValidation, DataflowValidationReport};
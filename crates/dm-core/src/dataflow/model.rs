@@ -16,7 +16,21 @@ pub struct DataflowHistoryEntry {
     pub size: u64,
 }
 
+/// Unified diff between a saved version and the current YAML or another
+/// saved version.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DataflowHistoryDiff {
+    pub version: String,
+    /// The version compared against, or `"current"` for the live YAML.
+    pub against: String,
+    /// Empty when both sides are identical.
+    pub diff: String,
+    pub added: usize,
+    pub removed: usize,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct FlowMeta {
     pub id: String,
     #[serde(default)]
@@ -137,6 +151,19 @@ pub struct DataflowImportReport {
     pub failed: Vec<DataflowImportFailure>,
 }
 
+/// Outcome of `dm dataflow sync`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataflowSyncReport {
+    /// The synced dataflows directory
+    pub dir: String,
+    /// Files committed, relative to the repository root
+    pub committed: Vec<String>,
+    /// Remote changes were pulled (the branch tracks an upstream)
+    pub pulled: bool,
+    /// Local commits were pushed
+    pub pushed: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AggregatedConfigField {
     pub schema: serde_json::Value,
@@ -169,3 +196,56 @@ pub struct DataflowConfigAggregation {
     #[serde(default)]
     pub nodes: Vec<AggregatedConfigNode>,
 }
+
+/// A dataflow with what it needs from the node side, written by
+/// `dm dataflow export` as a `.dmflow` file and read by `dm dataflow import`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataflowBundle {
+    /// Bundle layout version; newer layouts are rejected on import
+    pub format: u32,
+    pub name: String,
+    /// RFC 3339 timestamp
+    pub exported_at: String,
+    pub yaml: String,
+    #[serde(default)]
+    pub meta: FlowMeta,
+    /// Editor layout (`view.json`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub view: Option<serde_json::Value>,
+    #[serde(default)]
+    pub nodes: Vec<BundledNode>,
+}
+
+/// A managed node the bundled dataflow uses.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundledNode {
+    pub id: String,
+    /// Installed version on the exporting machine; `None` when it was missing there
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned: Option<String>,
+    /// Git URL the node can be downloaded from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Saved node config without its secret fields
+    #[serde(default)]
+    pub config: serde_json::Map<String, serde_json::Value>,
+    /// Config fields marked `secret` in the node's schema, left out of `config`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secrets: Vec<String>,
+}
+
+/// What `import_bundle` did.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataflowBundleImport {
+    pub name: String,
+    /// Nodes that were missing and were downloaded and installed (or failed to)
+    pub installed: Vec<crate::node::NodeInstallOutcome>,
+    /// Nodes whose saved config gained values from the bundle
+    pub configured: Vec<String>,
+    /// Secret config fields still unset, per node
+    pub missing_secrets: BTreeMap<String, Vec<String>>,
+    /// Version differences and config that could not be applied
+    pub warnings: Vec<String>,
+}
@@ -5,9 +5,22 @@ pub const FLOW_META_FILE: &str = "flow.json";
 pub const FLOW_CONFIG_FILE: &str = "config.json";
 pub const FLOW_VIEW_FILE: &str = "view.json";
 pub const FLOW_HISTORY_DIR: &str = ".history";
+pub const LOCAL_RUN_PID_FILE: &str = "dm.pid";
 
+/// `dataflows_dir` from config.toml, or `<home>/dataflows` when unset.
 pub fn dataflows_dir(home: &Path) -> PathBuf {
-    home.join("dataflows")
+    let configured = crate::config::load_config(home)
+        .ok()
+        .and_then(|cfg| cfg.dataflows_dir)
+        .map(|dir| dir.trim().to_string())
+        .filter(|dir| !dir.is_empty());
+    let Some(dir) = configured else {
+        return home.join("dataflows");
+    };
+    match dir.strip_prefix("~/").zip(dirs::home_dir()) {
+        Some((rest, user_home)) => user_home.join(rest),
+        None => home.join(dir),
+    }
 }
 
 pub fn dataflow_dir(home: &Path, name: &str) -> PathBuf {
@@ -33,3 +46,25 @@ pub fn flow_history_dir(dir: &Path) -> PathBuf {
 pub fn flow_view_path(dir: &Path) -> PathBuf {
     dir.join(FLOW_VIEW_FILE)
 }
+
+/// Root of the working directories of foreground `dm run` invocations.
+pub fn local_runs_dir(home: &Path) -> PathBuf {
+    home.join("local-runs")
+}
+
+/// Working directory for foreground `dm run` invocations of a dataflow.
+pub fn local_run_dir(home: &Path, name: &str) -> PathBuf {
+    local_runs_dir(home).join(name)
+}
+
+/// Artifacts of one `dm run` invocation: the original and transpiled YAML
+/// and the captured output.
+pub fn local_run_artifacts_dir(home: &Path, name: &str, run_id: &str) -> PathBuf {
+    local_run_dir(home, name).join("runs").join(run_id)
+}
+
+/// Pid of the `dm run` process that owns an artifacts directory, present
+/// while it runs.
+pub fn local_run_pid_path(run_dir: &Path) -> PathBuf {
+    run_dir.join(LOCAL_RUN_PID_FILE)
+}
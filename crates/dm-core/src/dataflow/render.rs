@@ -0,0 +1,186 @@
+//! Dataflow templates: `${name}` placeholders filled from a top-level
+//! `variables:` section, whose defaults can be overridden per start
+//! (`--set name=value`, or `variables` in the start request body).
+//!
+//! Only graphs with a `variables:` section are templates; anything else is
+//! passed through untouched, so `${...}` in ordinary graphs keeps its
+//! meaning. Rendering drops the section, which makes it idempotent.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::{bail, Context, Result};
+use serde_yaml::Value;
+
+const VARIABLES_KEY: &str = "variables";
+
+/// Substitute the placeholders of `yaml` and drop its `variables:` section.
+///
+/// A value that is exactly `${name}` takes the variable's YAML type
+/// (`fps: ${fps}` stays a number); placeholders inside longer strings are
+/// replaced by the value's text. Errors on placeholders that name no
+/// variable or one without a value, and on overrides for undeclared names.
+pub fn render(yaml: &str, overrides: &BTreeMap<String, String>) -> Result<String> {
+    // Unparsable text is left for the transpile/inspect parse step to report.
+    let mut graph: Value = match serde_yaml::from_str(yaml) {
+        Ok(graph) => graph,
+        Err(_) if overrides.is_empty() => return Ok(yaml.to_string()),
+        Err(e) => return Err(e).context("Failed to parse dataflow yaml"),
+    };
+    let Some(root) = graph.as_mapping_mut() else {
+        return passthrough(yaml, overrides);
+    };
+    let Some(declared) = root.remove(VARIABLES_KEY) else {
+        return passthrough(yaml, overrides);
+    };
+
+    let mut values: BTreeMap<String, Option<Value>> = BTreeMap::new();
+    match declared {
+        Value::Null => {}
+        Value::Mapping(declared) => {
+            for (name, value) in declared {
+                let name = name
+                    .as_str()
+                    .context("Variable names must be strings")?
+                    .to_string();
+                let value = match value {
+                    Value::Null => None,
+                    Value::Sequence(_) | Value::Mapping(_) => {
+                        bail!("Variable '{}' must be a scalar", name)
+                    }
+                    scalar => Some(scalar),
+                };
+                values.insert(name, value);
+            }
+        }
+        _ => bail!("`variables:` must be a mapping of name to default value"),
+    }
+    for (name, value) in overrides {
+        let Some(slot) = values.get_mut(name) else {
+            bail!(
+                "Unknown variable '{}' (declared: {})",
+                name,
+                declared_names(&values)
+            );
+        };
+        *slot = Some(parse_scalar(value));
+    }
+
+    let mut undefined = BTreeSet::new();
+    substitute(&mut graph, &values, &mut undefined);
+    if !undefined.is_empty() {
+        let names: Vec<_> = undefined.into_iter().collect();
+        bail!(
+            "Undefined variable(s): {} (declare them under `variables:` or pass --set NAME=VALUE)",
+            names.join(", ")
+        );
+    }
+    serde_yaml::to_string(&graph).context("Failed to serialize rendered dataflow")
+}
+
+fn passthrough(yaml: &str, overrides: &BTreeMap<String, String>) -> Result<String> {
+    if let Some(name) = overrides.keys().next() {
+        bail!(
+            "Unknown variable '{}': the dataflow declares no `variables:`",
+            name
+        );
+    }
+    Ok(yaml.to_string())
+}
+
+fn declared_names(values: &BTreeMap<String, Option<Value>>) -> String {
+    if values.is_empty() {
+        return "none".to_string();
+    }
+    values.keys().cloned().collect::<Vec<_>>().join(", ")
+}
+
+/// `--set` values are typed like YAML scalars: `30` is a number, `true` a
+/// bool, anything else a string.
+fn parse_scalar(value: &str) -> Value {
+    match serde_yaml::from_str::<Value>(value) {
+        Ok(scalar @ (Value::Bool(_) | Value::Number(_))) => scalar,
+        _ => Value::String(value.to_string()),
+    }
+}
+
+fn substitute(
+    value: &mut Value,
+    values: &BTreeMap<String, Option<Value>>,
+    undefined: &mut BTreeSet<String>,
+) {
+    match value {
+        Value::String(text) => {
+            if let Some(replaced) = render_string(text, values, undefined) {
+                *value = replaced;
+            }
+        }
+        Value::Sequence(items) => {
+            for item in items {
+                substitute(item, values, undefined);
+            }
+        }
+        Value::Mapping(map) => {
+            for (_, item) in map.iter_mut() {
+                substitute(item, values, undefined);
+            }
+        }
+        Value::Tagged(tagged) => substitute(&mut tagged.value, values, undefined),
+        Value::Null | Value::Bool(_) | Value::Number(_) => {}
+    }
+}
+
+/// The rendered form of `text`, or `None` when it has no placeholders.
+fn render_string(
+    text: &str,
+    values: &BTreeMap<String, Option<Value>>,
+    undefined: &mut BTreeSet<String>,
+) -> Option<Value> {
+    if !text.contains("${") {
+        return None;
+    }
+    let lookup = |name: &str, undefined: &mut BTreeSet<String>| match values.get(name) {
+        Some(Some(value)) => Some(value.clone()),
+        _ => {
+            undefined.insert(name.to_string());
+            None
+        }
+    };
+
+    if let Some(name) = text
+        .strip_prefix("${")
+        .and_then(|rest| rest.strip_suffix('}'))
+        .filter(|name| !name.contains(['{', '}']))
+    {
+        return Some(lookup(name, undefined).unwrap_or(Value::Null));
+    }
+
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let Some(len) = rest[start + 2..].find('}') else {
+            out.push_str(&rest[start..]);
+            rest = "";
+            break;
+        };
+        let name = &rest[start + 2..start + 2 + len];
+        if let Some(value) = lookup(name, undefined) {
+            out.push_str(&scalar_text(&value));
+        }
+        rest = &rest[start + 2 + len + 1..];
+    }
+    out.push_str(rest);
+    Some(Value::String(out))
+}
+
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Bool(flag) => flag.to_string(),
+        Value::Number(number) => number.to_string(),
+        other => serde_yaml::to_string(other)
+            .unwrap_or_default()
+            .trim_end()
+            .to_string(),
+    }
+}
@@ -3,7 +3,10 @@ use std::path::Path;
 
 use anyhow::{Context, Result};
 
-use super::model::{DataflowHistoryEntry, DataflowMeta, FlowMeta};
+use crate::error::DmError;
+
+use super::diff::unified_diff;
+use super::model::{DataflowHistoryDiff, DataflowHistoryEntry, DataflowMeta, FlowMeta};
 use super::paths::{
     dataflow_dir, dataflow_yaml_path, dataflows_dir, flow_history_dir, flow_meta_path,
     flow_view_path, DATAFLOW_FILE,
@@ -58,6 +61,9 @@ pub fn list_projects(home: &Path) -> Result<Vec<DataflowMeta>> {
 
 pub fn read_yaml(home: &Path, name: &str) -> Result<String> {
     let path = dataflow_yaml_path(&dataflow_dir(home, name));
+    if !path.exists() {
+        return Err(DmError::not_found("dataflow", name).into());
+    }
     fs::read_to_string(&path).with_context(|| format!("Failed to read dataflow '{}'", name))
 }
 
@@ -153,7 +159,11 @@ pub fn write_meta(home: &Path, name: &str, meta: &FlowMeta) -> Result<()> {
 }
 
 pub fn list_history_versions(home: &Path, name: &str) -> Result<Vec<DataflowHistoryEntry>> {
-    let history_dir = flow_history_dir(&dataflow_dir(home, name));
+    let dir = dataflow_dir(home, name);
+    if !dataflow_yaml_path(&dir).exists() {
+        return Err(DmError::not_found("dataflow", name).into());
+    }
+    let history_dir = flow_history_dir(&dir);
     if !history_dir.exists() {
         return Ok(Vec::new());
     }
@@ -198,7 +208,25 @@ pub fn list_history_versions(home: &Path, name: &str) -> Result<Vec<DataflowHist
 }
 
 pub fn read_history_version(home: &Path, name: &str, version: &str) -> Result<String> {
-    let path = flow_history_dir(&dataflow_dir(home, name)).join(format!("{version}.yml"));
+    let dir = dataflow_dir(home, name);
+    if !dataflow_yaml_path(&dir).exists() {
+        return Err(DmError::not_found("dataflow", name).into());
+    }
+    // Versions are file stems inside `.history/`; anything that could step
+    // outside it is treated as unknown rather than joined onto the path.
+    let valid = !version.is_empty()
+        && !version.starts_with('.')
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+    let history_dir = flow_history_dir(&dir);
+    let path = ["yml", "yaml"]
+        .iter()
+        .map(|ext| history_dir.join(format!("{version}.{ext}")))
+        .find(|path| valid && path.is_file());
+    let Some(path) = path else {
+        return Err(DmError::not_found("dataflow version", format!("{name}@{version}")).into());
+    };
     fs::read_to_string(&path).with_context(|| {
         format!(
             "Failed to read history version '{}' for dataflow '{}'",
@@ -207,6 +235,29 @@ pub fn read_history_version(home: &Path, name: &str, version: &str) -> Result<St
     })
 }
 
+/// Diff `version` against `against`, or against the current YAML when
+/// `against` is `None`.
+pub fn diff_history_version(
+    home: &Path,
+    name: &str,
+    version: &str,
+    against: Option<&str>,
+) -> Result<DataflowHistoryDiff> {
+    let old = read_history_version(home, name, version)?;
+    let (against, new) = match against {
+        Some(other) => (other.to_string(), read_history_version(home, name, other)?),
+        None => ("current".to_string(), read_yaml(home, name)?),
+    };
+    let (diff, added, removed) = unified_diff(&old, &new, version, &against);
+    Ok(DataflowHistoryDiff {
+        version: version.to_string(),
+        against,
+        diff,
+        added,
+        removed,
+    })
+}
+
 pub fn restore_history_version(home: &Path, name: &str, version: &str) -> Result<()> {
     let content = read_history_version(home, name, version)?;
     write_yaml(home, name, &content)
@@ -314,8 +365,15 @@ pub fn touch_flow_meta(dir: &Path, name: &str) -> Result<()> {
 fn write_history_snapshot(dir: &Path, content: &str) -> Result<()> {
     let history_dir = flow_history_dir(dir);
     fs::create_dir_all(&history_dir)?;
-    let version_id = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
-    let history_path = history_dir.join(format!("{version_id}.yml"));
+    // Millisecond ids sort chronologically; the suffix keeps two saves in
+    // the same millisecond from overwriting each other.
+    let version_id = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ").to_string();
+    let mut history_path = history_dir.join(format!("{version_id}.yml"));
+    let mut suffix = 1;
+    while history_path.exists() {
+        history_path = history_dir.join(format!("{version_id}-{suffix}.yml"));
+        suffix += 1;
+    }
     fs::write(&history_path, content).with_context(|| {
         format!(
             "Failed to write history snapshot '{}'",
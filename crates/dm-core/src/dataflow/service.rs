@@ -4,11 +4,12 @@ use std::path::Path;
 use anyhow::Result;
 
 use crate::events::{EventSource, OperationEvent};
+use crate::lock::HomeLock;
 use crate::node::{resolve_dm_json_path, resolve_node_dir, Node};
 
 use super::import;
 use super::inspect;
-use super::model::{DataflowHistoryEntry, FlowMeta};
+use super::model::{DataflowHistoryDiff, DataflowHistoryEntry, FlowMeta};
 use super::repo;
 use super::{
     AggregatedConfigField, AggregatedConfigNode, DataflowConfigAggregation, DataflowImportFailure,
@@ -67,6 +68,7 @@ pub fn save(home: &Path, name: &str, yaml: &str) -> Result<DataflowProject> {
     let op = OperationEvent::new(home, EventSource::Core, "dataflow.save").attr("name", name);
     op.emit_start();
     let result = (|| {
+        let _lock = HomeLock::acquire(home, &format!("dataflow-{}", name), "dataflow save")?;
         repo::write_yaml(home, name, yaml)?;
         get(home, name)
     })();
@@ -77,7 +79,8 @@ pub fn save(home: &Path, name: &str, yaml: &str) -> Result<DataflowProject> {
 pub fn delete(home: &Path, name: &str) -> Result<()> {
     let op = OperationEvent::new(home, EventSource::Core, "dataflow.delete").attr("name", name);
     op.emit_start();
-    let result = repo::delete_project(home, name);
+    let result = HomeLock::acquire(home, &format!("dataflow-{}", name), "dataflow delete")
+        .and_then(|_lock| repo::delete_project(home, name));
     op.emit_result(&result);
     result
 }
@@ -90,6 +93,62 @@ pub fn save_flow_meta(home: &Path, name: &str, meta: &FlowMeta) -> Result<()> {
     repo::write_meta(home, name, meta)
 }
 
+/// Tag prefix marking a saved dataflow whose node `<id>` was uninstalled.
+pub const MISSING_NODE_TAG_PREFIX: &str = "missing-node:";
+
+/// Saved dataflows whose graph references managed node `node_id`.
+pub fn dataflows_using_node(home: &Path, node_id: &str) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    for file in repo::list_projects(home)? {
+        let Ok(yaml) = repo::read_yaml(home, &file.name) else {
+            continue;
+        };
+        let Ok(graph) = serde_yaml::from_str::<serde_yaml::Value>(&yaml) else {
+            continue;
+        };
+        let uses_node = graph
+            .get("nodes")
+            .and_then(|nodes| nodes.as_sequence())
+            .is_some_and(|nodes| {
+                nodes
+                    .iter()
+                    .any(|entry| entry.get("node").and_then(|v| v.as_str()) == Some(node_id))
+            });
+        if uses_node {
+            names.push(file.name);
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Saved dataflows tagged `tag` in their meta, sorted by name.
+pub fn dataflows_with_tag(home: &Path, tag: &str) -> Result<Vec<String>> {
+    let mut names: Vec<String> = repo::list_projects(home)?
+        .into_iter()
+        .filter(|file| {
+            repo::read_meta(home, &file.name).is_ok_and(|meta| meta.tags.iter().any(|t| t == tag))
+        })
+        .map(|file| file.name)
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+/// Add a `missing-node:<id>` tag to dataflow `name`. Returns false when the
+/// tag was already present.
+pub fn tag_missing_node(home: &Path, name: &str, node_id: &str) -> Result<bool> {
+    let _lock = HomeLock::acquire(home, &format!("dataflow-{}", name), "dataflow tag")?;
+    let mut meta = repo::read_meta(home, name).unwrap_or_default();
+    let tag = format!("{MISSING_NODE_TAG_PREFIX}{node_id}");
+    if meta.tags.contains(&tag) {
+        return Ok(false);
+    }
+    meta.tags.push(tag);
+    repo::write_meta(home, name, &meta)?;
+    Ok(true)
+}
+
 pub fn get_flow_view(home: &Path, name: &str) -> Result<serde_json::Value> {
     repo::read_view(home, name)
 }
@@ -222,8 +281,28 @@ pub fn get_history_version(home: &Path, name: &str, version: &str) -> Result<Str
     repo::read_history_version(home, name, version)
 }
 
+pub fn diff_history_version(
+    home: &Path,
+    name: &str,
+    version: &str,
+    against: Option<&str>,
+) -> Result<DataflowHistoryDiff> {
+    repo::diff_history_version(home, name, version, against)
+}
+
+/// Make `version` the current YAML. The YAML it replaces is snapshotted
+/// like any other save, so a restore can itself be undone.
 pub fn restore_history_version(home: &Path, name: &str, version: &str) -> Result<()> {
-    repo::restore_history_version(home, name, version)
+    let op = OperationEvent::new(home, EventSource::Core, "dataflow.restore")
+        .attr("name", name)
+        .attr("version", version);
+    op.emit_start();
+    let result = (|| {
+        let _lock = HomeLock::acquire(home, &format!("dataflow-{}", name), "dataflow restore")?;
+        repo::restore_history_version(home, name, version)
+    })();
+    op.emit_result(&result);
+    result
 }
 
 pub fn migrate_legacy_layout(home: &Path) -> Result<usize> {
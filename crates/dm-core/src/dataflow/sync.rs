@@ -0,0 +1,90 @@
+//! `dm dataflow sync`: share dataflow projects through git.
+//!
+//! When `dataflows_dir` points into a git checkout, sync commits local
+//! changes under it, then pulls (rebasing on top of the remote) and pushes
+//! when the checkout has a remote. Only paths inside the dataflows
+//! directory are staged, so it may be a subfolder of a larger repository.
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+
+use crate::events::{EventSource, OperationEvent};
+use crate::lock::HomeLock;
+
+use super::model::DataflowSyncReport;
+use super::paths::dataflows_dir;
+
+const DEFAULT_MESSAGE: &str = "Update dataflows via dm";
+
+/// Commit, pull and (unless `push` is false) push the dataflows directory.
+pub fn sync(home: &Path, message: Option<&str>, push: bool) -> Result<DataflowSyncReport> {
+    let op = OperationEvent::new(home, EventSource::Core, "dataflow.sync").attr("push", push);
+    op.emit_start();
+
+    let result = (|| {
+        let _lock = HomeLock::acquire(home, "dataflow-sync", "dataflow sync")?;
+        let dir = dataflows_dir(home);
+        if git(&dir, &["rev-parse", "--is-inside-work-tree"]).is_err() {
+            bail!(
+                "{} is not inside a git repository. Set `dataflows_dir` in config.toml to a git checkout, or run `git init` there.",
+                dir.display()
+            );
+        }
+
+        git(&dir, &["add", "--all", "--", "."])?;
+        let committed: Vec<String> = git(&dir, &["diff", "--cached", "--name-only", "--", "."])?
+            .lines()
+            .map(str::to_string)
+            .collect();
+        if !committed.is_empty() {
+            let message = message.unwrap_or(DEFAULT_MESSAGE);
+            git(&dir, &["commit", "--quiet", "-m", message, "--", "."])?;
+        }
+
+        let remote = git(&dir, &["remote"])?.lines().next().map(str::to_string);
+        let has_upstream = git(&dir, &["rev-parse", "--abbrev-ref", "@{upstream}"]).is_ok();
+        let pulled = has_upstream;
+        if pulled {
+            git(&dir, &["pull", "--rebase", "--quiet"])?;
+        }
+        let pushed = push && remote.is_some();
+        match remote.as_deref() {
+            Some(_) if pushed && has_upstream => {
+                git(&dir, &["push", "--quiet"])?;
+            }
+            Some(remote) if pushed => {
+                git(&dir, &["push", "--quiet", "--set-upstream", remote, "HEAD"])?;
+            }
+            _ => {}
+        }
+
+        Ok(DataflowSyncReport {
+            dir: dir.display().to_string(),
+            committed,
+            pulled,
+            pushed,
+        })
+    })();
+
+    op.emit_result(&result);
+    result
+}
+
+/// Run git in `dir`, returning stdout or failing with git's stderr.
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .context("Failed to run git; is it installed?")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,184 @@
+//! `adapter:` blocks — wiring a managed node under port ids other than the
+//! ones its `dm.json` declares, without forking the node.
+//!
+//! ```yaml
+//! - id: cam
+//!   node: opencv-video-capture
+//!   adapter:
+//!     outputs:
+//!       image: frame        # declared port: id used in this graph
+//! - id: viewer
+//!   node: dora-rerun
+//!   inputs:
+//!     rgb: cam/frame
+//!   adapter:
+//!     inputs:
+//!       image: rgb
+//! ```
+//!
+//! dora has no port aliases, so the graph is rewritten back to declared ids:
+//! the adapted node's input keys and `outputs:` list, and every
+//! `<node>/<output>` source that refers to a renamed output.
+
+use std::collections::HashMap;
+
+use crate::node::{Node, NodePortDirection};
+
+use super::model::ManagedNode;
+
+/// Parsed `adapter:` block as `(declared port, graph id)` pairs.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct PortAdapter {
+    pub inputs: Vec<(String, String)>,
+    pub outputs: Vec<(String, String)>,
+}
+
+/// Renamed outputs: `(yaml_id, graph id)` → declared port.
+pub(crate) type OutputRenames = HashMap<(String, String), String>;
+
+pub(crate) fn parse_adapter(value: &serde_yaml::Value) -> Result<PortAdapter, String> {
+    let mapping = value
+        .as_mapping()
+        .ok_or_else(|| "expected a mapping with `inputs` and/or `outputs`".to_string())?;
+    let mut adapter = PortAdapter::default();
+    for (key, ports) in mapping {
+        let pairs = match key.as_str() {
+            Some("inputs") => &mut adapter.inputs,
+            Some("outputs") => &mut adapter.outputs,
+            _ => {
+                return Err(format!(
+                    "unknown key '{}' (expected `inputs` or `outputs`)",
+                    super::bridge::yaml_value_to_string(key)
+                ))
+            }
+        };
+        let ports = ports
+            .as_mapping()
+            .ok_or_else(|| "port renames must map declared ports to ids".to_string())?;
+        for (declared, alias) in ports {
+            let (Some(declared), Some(alias)) = (declared.as_str(), alias.as_str()) else {
+                return Err("port ids must be strings".to_string());
+            };
+            if pairs.iter().any(|(_, existing)| existing == alias) {
+                return Err(format!("'{}' is used for more than one port", alias));
+            }
+            pairs.push((declared.to_string(), alias.to_string()));
+        }
+    }
+    Ok(adapter)
+}
+
+/// Declared ports named by `adapter` that `meta` does not declare in that
+/// direction, as `(direction, port)`. Nodes with `dynamic_ports` accept any.
+pub(crate) fn unknown_ports(meta: &Node, adapter: &PortAdapter) -> Vec<(&'static str, String)> {
+    if meta.dynamic_ports {
+        return Vec::new();
+    }
+    let declared = |port: &str, direction: NodePortDirection| {
+        meta.ports
+            .iter()
+            .any(|p| p.id == port && p.direction == direction)
+    };
+    let inputs = adapter
+        .inputs
+        .iter()
+        .filter(|(port, _)| !declared(port, NodePortDirection::Input))
+        .map(|(port, _)| ("input", port.clone()));
+    let outputs = adapter
+        .outputs
+        .iter()
+        .filter(|(port, _)| !declared(port, NodePortDirection::Output))
+        .map(|(port, _)| ("output", port.clone()));
+    inputs.chain(outputs).collect()
+}
+
+/// Rename the node's own input keys and `outputs:` entries to declared ids.
+pub(crate) fn apply_to_node(managed: &mut ManagedNode, adapter: &PortAdapter) {
+    let inputs_key = serde_yaml::Value::String("inputs".to_string());
+    if let Some(inputs) = managed
+        .extra_fields
+        .get_mut(&inputs_key)
+        .and_then(|v| v.as_mapping_mut())
+    {
+        *inputs = std::mem::take(inputs)
+            .into_iter()
+            .map(|(key, source)| (rename(key, &adapter.inputs), source))
+            .collect();
+    }
+
+    let outputs_key = serde_yaml::Value::String("outputs".to_string());
+    if let Some(outputs) = managed
+        .extra_fields
+        .get_mut(&outputs_key)
+        .and_then(|v| v.as_sequence_mut())
+    {
+        for output in outputs.iter_mut() {
+            *output = rename(std::mem::take(output), &adapter.outputs);
+        }
+    }
+}
+
+fn rename(id: serde_yaml::Value, pairs: &[(String, String)]) -> serde_yaml::Value {
+    match pairs.iter().find(|(_, alias)| id.as_str() == Some(alias)) {
+        Some((declared, _)) => serde_yaml::Value::String(declared.clone()),
+        None => id,
+    }
+}
+
+/// Point sources in an `inputs:` mapping at declared output ids. Sources are
+/// either `node/output` strings or mappings with a `source:` key.
+pub(crate) fn rewrite_sources(inputs: &mut serde_yaml::Mapping, renames: &OutputRenames) {
+    let source_key = serde_yaml::Value::String("source".to_string());
+    for (_, value) in inputs.iter_mut() {
+        let source = match value {
+            serde_yaml::Value::Mapping(map) => match map.get_mut(&source_key) {
+                Some(source) => source,
+                None => continue,
+            },
+            other => other,
+        };
+        let Some((node, output)) = source.as_str().and_then(|s| s.split_once('/')) else {
+            continue;
+        };
+        if let Some(declared) = renames.get(&(node.to_string(), output.to_string())) {
+            *source = serde_yaml::Value::String(format!("{}/{}", node, declared));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_adapter_rejects_malformed_blocks() {
+        let parse = |yaml: &str| parse_adapter(&serde_yaml::from_str(yaml).unwrap());
+
+        assert_eq!(
+            parse("inputs: {image: rgb}\noutputs: {boxes: detections}").unwrap(),
+            PortAdapter {
+                inputs: vec![("image".into(), "rgb".into())],
+                outputs: vec![("boxes".into(), "detections".into())],
+            }
+        );
+        assert!(parse("[image]").is_err());
+        assert!(parse("ports: {image: rgb}").is_err());
+        assert!(parse("inputs: {image: rgb, depth: rgb}").is_err());
+    }
+
+    #[test]
+    fn rewrite_sources_handles_plain_and_detailed_inputs() {
+        let mut inputs: serde_yaml::Mapping = serde_yaml::from_str(
+            "a: cam/frame\nb: {source: cam/frame, queue_size: 1}\nc: dora/timer/millis/20\nd: cam/other",
+        )
+        .unwrap();
+        let renames: OutputRenames = [(("cam".into(), "frame".into()), "image".into())].into();
+        rewrite_sources(&mut inputs, &renames);
+
+        let expected: serde_yaml::Mapping = serde_yaml::from_str(
+            "a: cam/image\nb: {source: cam/image, queue_size: 1}\nc: dora/timer/millis/20\nd: cam/other",
+        )
+        .unwrap();
+        assert_eq!(inputs, expected);
+    }
+}
@@ -190,6 +190,8 @@ mod tests {
             examples: Vec::new(),
             config_schema: None,
             dynamic_ports: false,
+            pinned: None,
+            last_test: None,
             path: Default::default(),
         }
     }
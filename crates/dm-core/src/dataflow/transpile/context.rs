@@ -6,4 +6,7 @@ pub(crate) struct TranspileContext<'a> {
     pub home: &'a Path,
     /// Unique run identifier for generic runtime env injection.
     pub run_id: &'a str,
+    /// Shared virtualenv of `dm run --uv`, which Python nodes run from
+    /// instead of their own.
+    pub uv_env: Option<&'a Path>,
 }
@@ -1,18 +1,22 @@
 use std::fmt;
 use std::path::PathBuf;
 
+use serde::Serialize;
+
 /// A single diagnostic emitted during transpilation.
 ///
 /// Diagnostics are collected (not short-circuited) so that the user sees
 /// all issues at once rather than fixing them one by one.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TranspileDiagnostic {
     pub yaml_id: String,
     pub node_id: String,
+    #[serde(flatten)]
     pub kind: DiagnosticKind,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum DiagnosticKind {
     /// The node directory does not exist in `~/.dm/nodes/`.
     NodeNotInstalled,
@@ -28,6 +32,24 @@ pub enum DiagnosticKind {
         input_port: String,
         reason: String,
     },
+    /// An `adapter:` block is malformed.
+    InvalidAdapter { reason: String },
+    /// An `adapter:` block renames a port that `dm.json` does not declare.
+    UnknownAdapterPort {
+        port_id: String,
+        direction: &'static str,
+    },
+    /// An input names a source node that is not part of the graph.
+    UnknownInputSource { input_port: String, source: String },
+    /// An input uses a port that the node's `dm.json` does not declare:
+    /// either its own input or the source node's output.
+    UndeclaredPort {
+        port_id: String,
+        direction: &'static str,
+    },
+    /// The node's config (inline `config:` over its saved config.json)
+    /// breaks its `config_schema`.
+    InvalidConfig { violation: String },
     /// Hidden bridge injection needs the `dm` CLI runtime but no installed CLI was found.
     BridgeCliUnavailable,
 }
@@ -39,7 +61,10 @@ impl fmt::Display for TranspileDiagnostic {
             DiagnosticKind::MetadataUnreadable { path } => {
                 format!("metadata unreadable at {}", path.display())
             }
-            DiagnosticKind::MissingExecutable => "dm.json has empty executable field".to_string(),
+            DiagnosticKind::MissingExecutable => format!(
+                "dm.json has empty executable field; run `dm node install {}`",
+                self.node_id
+            ),
             DiagnosticKind::InvalidPortSchema { port_id, reason } => {
                 format!("port '{}' has an invalid schema: {}", port_id, reason)
             }
@@ -53,6 +78,27 @@ impl fmt::Display for TranspileDiagnostic {
                     output_port, input_port, reason
                 )
             }
+            DiagnosticKind::InvalidAdapter { reason } => {
+                format!("invalid adapter: {}", reason)
+            }
+            DiagnosticKind::UnknownAdapterPort { port_id, direction } => {
+                format!(
+                    "adapter renames {} port '{}', which dm.json does not declare",
+                    direction, port_id
+                )
+            }
+            DiagnosticKind::UnknownInputSource { input_port, source } => {
+                format!(
+                    "input '{}' reads from '{}', which is not a node in this graph",
+                    input_port, source
+                )
+            }
+            DiagnosticKind::UndeclaredPort { port_id, direction } => {
+                format!("{} port '{}' is not declared in dm.json", direction, port_id)
+            }
+            DiagnosticKind::InvalidConfig { violation } => {
+                format!("invalid config: {}", violation)
+            }
             DiagnosticKind::BridgeCliUnavailable => {
                 "interaction bridge requires the dm CLI binary, but it was not found in PATH or next to the current executable; install dm or set DM_CLI_BIN".to_string()
             }
@@ -64,3 +110,43 @@ impl fmt::Display for TranspileDiagnostic {
         )
     }
 }
+
+impl TranspileDiagnostic {
+    /// Whether this diagnostic makes the graph unrunnable. Everything else
+    /// is reported as a warning and transpilation proceeds.
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self.kind,
+            DiagnosticKind::NodeNotInstalled
+                | DiagnosticKind::MetadataUnreadable { .. }
+                | DiagnosticKind::MissingExecutable
+                | DiagnosticKind::UnknownInputSource { .. }
+                | DiagnosticKind::UndeclaredPort { .. }
+                // `save_node_config` refuses the same config.
+                | DiagnosticKind::InvalidConfig { .. }
+        )
+    }
+}
+
+/// Transpilation refused to emit a graph; `diagnostics` lists every
+/// blocking problem found.
+#[derive(Debug, Clone)]
+pub struct TranspileError {
+    pub diagnostics: Vec<TranspileDiagnostic>,
+}
+
+impl fmt::Display for TranspileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} problem(s) must be fixed before this dataflow can run:",
+            self.diagnostics.len()
+        )?;
+        for diagnostic in &self.diagnostics {
+            write!(f, "\n  - {}", diagnostic)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TranspileError {}
@@ -0,0 +1,66 @@
+/// Stable rendering of transpile output for golden-file comparisons.
+///
+/// Transpiled YAML embeds the dm home and the run id, which differ between
+/// machines and runs. The golden form pins the run id and replaces the home
+/// path with a placeholder, so the same graph always renders to the same
+/// text and any change to the emitted schema shows up as a diff.
+use std::path::Path;
+
+use anyhow::{Context, Result};
+
+use super::transpile_graph_for_run;
+
+/// Run id used when rendering golden output.
+pub const GOLDEN_RUN_ID: &str = "golden-run";
+/// Stands in for the dm home path in golden output.
+pub const GOLDEN_HOME_PLACEHOLDER: &str = "$DM_HOME";
+
+/// Outcome of comparing a graph's transpile output with its golden file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenCheck {
+    /// The freshly rendered output.
+    pub actual: String,
+    /// `-`/`+` lines for every line that differs, empty when they match.
+    pub diff: Vec<String>,
+}
+
+impl GoldenCheck {
+    pub fn matches(&self) -> bool {
+        self.diff.is_empty()
+    }
+}
+
+/// Transpile `yaml_path` and render it in golden form.
+pub fn render_golden(home: &Path, yaml_path: &Path) -> Result<String> {
+    let result = transpile_graph_for_run(home, yaml_path, GOLDEN_RUN_ID)?;
+    let text = serde_yaml::to_string(&result.yaml)?;
+    Ok(text.replace(&home.display().to_string(), GOLDEN_HOME_PLACEHOLDER))
+}
+
+/// Compare the golden rendering of `yaml_path` with `golden_path`.
+pub fn check_golden(home: &Path, yaml_path: &Path, golden_path: &Path) -> Result<GoldenCheck> {
+    let expected = std::fs::read_to_string(golden_path)
+        .with_context(|| format!("Failed to read golden file at {}", golden_path.display()))?;
+    let actual = render_golden(home, yaml_path)?;
+    let diff = line_diff(&expected, &actual);
+    Ok(GoldenCheck { actual, diff })
+}
+
+fn line_diff(expected: &str, actual: &str) -> Vec<String> {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let mut diff = Vec::new();
+    for i in 0..expected.len().max(actual.len()) {
+        let (old, new) = (expected.get(i), actual.get(i));
+        if old == new {
+            continue;
+        }
+        if let Some(old) = old {
+            diff.push(format!("{:>4} - {}", i + 1, old));
+        }
+        if let Some(new) = new {
+            diff.push(format!("{:>4} + {}", i + 1, new));
+        }
+    }
+    diff
+}
@@ -1,19 +1,33 @@
 /// Transpile module — transforms DM-flavoured YAML into standard dora-rs YAML.
 ///
 /// The pipeline:
+/// 0. **render**                 — fill `${var}` placeholders (see [`super::render`])
 /// 1. **parse**                  — YAML text  →  typed `DmGraph` IR
 /// 2. **validate_reserved**      — check for reserved node ID conflicts
-/// 3. **resolve_paths**          — `node:` → absolute `path:` via `dm.json`
-/// 4. **validate_port_schemas**  — check port schema compatibility
-/// 5. **merge_config**           — four-layer config merge → `env:`
-/// 6. **inject_dm_bridge**       — lower DM capability bindings into a hidden bridge node
-/// 7. **emit**                   — `DmGraph` → `serde_yaml::Value`
+/// 3. **resolve_paths**          — `node:` (or a `path:` naming a registry node) → absolute `path:` via `dm.json`
+/// 4. **apply_port_adapters**    — `adapter:` port renames → declared port ids
+/// 5. **validate_inputs**        — input sources exist and use declared ports
+/// 6. **validate_port_schemas**  — check port schema compatibility
+/// 7. **merge_config**           — four-layer config merge → `env:`
+/// 8. **inject_dm_bridge**       — lower DM capability bindings into a hidden bridge node
+/// 9. **emit**                   — `DmGraph` → `serde_yaml::Value`
+///
+/// Diagnostics are collected across all passes. If any of them is an error
+/// (see [`TranspileDiagnostic::is_error`]) nothing is emitted and the whole
+/// list comes back as a [`TranspileError`]; the rest are logged as warnings.
+///
+/// `golden` renders the output in a machine-independent form for
+/// `dm transpile --check` and the fixture tests.
+mod adapter;
 mod bridge;
 mod context;
 mod error;
+mod golden;
 mod model;
 mod passes;
+mod preview;
 
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use anyhow::{Context, Result};
@@ -22,6 +36,12 @@ use crate::events::{EventSource, OperationEvent};
 
 use context::TranspileContext;
 
+pub use error::{DiagnosticKind, TranspileDiagnostic, TranspileError};
+pub use golden::{
+    check_golden, render_golden, GoldenCheck, GOLDEN_HOME_PLACEHOLDER, GOLDEN_RUN_ID,
+};
+pub use preview::{preview_node_env, NodeEnvPreview, PREVIEW_RUN_ID};
+
 /// Result of a transpilation, containing the dora-compatible YAML.
 #[derive(Debug)]
 pub struct TranspileResult {
@@ -38,7 +58,41 @@ pub fn transpile_graph(home: &Path, yaml_path: &Path) -> Result<TranspileResult>
 pub fn transpile_graph_for_run(
     home: &Path,
     yaml_path: &Path,
-    _run_id: &str,
+    run_id: &str,
+) -> Result<TranspileResult> {
+    transpile_graph_with_variables(home, yaml_path, run_id, &BTreeMap::new())
+}
+
+/// Transpile a DM graph YAML with an explicit run-id, rendering its
+/// `variables:` with `variables` overriding the defaults.
+pub fn transpile_graph_with_variables(
+    home: &Path,
+    yaml_path: &Path,
+    run_id: &str,
+    variables: &BTreeMap<String, String>,
+) -> Result<TranspileResult> {
+    transpile(home, yaml_path, run_id, variables, None)
+}
+
+/// Transpile for `dm run --uv`: Python nodes run from the shared virtualenv
+/// at `uv_env` and only need their `dm.json` and requirements, not an
+/// installed executable.
+pub(crate) fn transpile_graph_for_uv(
+    home: &Path,
+    yaml_path: &Path,
+    run_id: &str,
+    variables: &BTreeMap<String, String>,
+    uv_env: &Path,
+) -> Result<TranspileResult> {
+    transpile(home, yaml_path, run_id, variables, Some(uv_env))
+}
+
+fn transpile(
+    home: &Path,
+    yaml_path: &Path,
+    run_id: &str,
+    variables: &BTreeMap<String, String>,
+    uv_env: Option<&Path>,
 ) -> Result<TranspileResult> {
     let op = OperationEvent::new(home, EventSource::Dataflow, "dataflow.transpile")
         .attr("path", yaml_path.display().to_string());
@@ -47,10 +101,13 @@ pub fn transpile_graph_for_run(
     let result = (|| {
         let content = std::fs::read_to_string(yaml_path)
             .with_context(|| format!("Failed to read graph yaml at {}", yaml_path.display()))?;
+        let content = super::render(&content, variables)
+            .with_context(|| format!("Failed to render {}", yaml_path.display()))?;
 
         let ctx = TranspileContext {
             home,
-            run_id: _run_id,
+            run_id,
+            uv_env,
         };
         let mut diags = Vec::new();
 
@@ -63,15 +120,24 @@ pub fn transpile_graph_for_run(
 
         // Transform
         passes::resolve_paths(&ctx, &mut graph, &mut diags);
+        passes::apply_port_adapters(&ctx, &mut graph, &mut diags);
+        passes::validate_inputs(&ctx, &graph, &mut diags);
         passes::validate_port_schemas(&ctx, &graph, &mut diags);
         passes::merge_config(&ctx, &mut graph, &mut diags);
         passes::inject_runtime_env(&ctx, &mut graph);
         passes::inject_dm_bridge(&ctx, &mut graph, &mut diags);
 
-        // Log diagnostics as warnings
-        for d in &diags {
+        let (errors, warnings): (Vec<_>, Vec<_>) =
+            diags.into_iter().partition(TranspileDiagnostic::is_error);
+        for d in &warnings {
             eprintln!("[dm-core] transpile warning: {}", d);
         }
+        if !errors.is_empty() {
+            return Err(TranspileError {
+                diagnostics: errors,
+            }
+            .into());
+        }
 
         // Emit
         Ok(TranspileResult {
@@ -82,3 +148,31 @@ pub fn transpile_graph_for_run(
     op.emit_result(&result);
     result
 }
+
+/// Diagnostics transpiling `yaml` would report, without writing anything or
+/// recording a transpile event.
+pub fn check_yaml(home: &Path, yaml: &str) -> Result<Vec<String>> {
+    Ok(diagnose_yaml(home, yaml)?
+        .iter()
+        .map(ToString::to_string)
+        .collect())
+}
+
+/// Structured form of [`check_yaml`].
+pub fn diagnose_yaml(home: &Path, yaml: &str) -> Result<Vec<TranspileDiagnostic>> {
+    let ctx = TranspileContext {
+        home,
+        run_id: PREVIEW_RUN_ID,
+        uv_env: None,
+    };
+    let mut diags = Vec::new();
+    let mut graph = passes::parse(&super::render(yaml, &BTreeMap::new())?)?;
+    passes::validate_reserved(&ctx, &graph, &mut diags);
+    passes::resolve_paths(&ctx, &mut graph, &mut diags);
+    passes::apply_port_adapters(&ctx, &mut graph, &mut diags);
+    passes::validate_inputs(&ctx, &graph, &mut diags);
+    passes::validate_port_schemas(&ctx, &graph, &mut diags);
+    passes::merge_config(&ctx, &mut graph, &mut diags);
+    passes::inject_dm_bridge(&ctx, &mut graph, &mut diags);
+    Ok(diags)
+}
@@ -1,5 +1,7 @@
+use crate::dataflow::local_run;
 use crate::node::{self, Node};
 
+use super::adapter::{self, parse_adapter, rewrite_sources, unknown_ports, OutputRenames};
 use super::bridge::{
     bridge_specs_json, build_bridge_node_spec, ensure_input_mapping, ensure_output_port,
     DM_BRIDGE_INPUT_ENV_KEY, DM_BRIDGE_OUTPUT_ENV_KEY, DM_CAPABILITIES_ENV_KEY,
@@ -22,7 +24,7 @@ pub(crate) fn parse(content: &str) -> anyhow::Result<DmGraph> {
     let raw_mapping = raw.as_mapping().cloned().unwrap_or_default();
 
     let mut extra_fields = raw_mapping.clone();
-    extra_fields.remove(serde_yaml::Value::String("nodes".to_string()));
+    extra_fields.shift_remove(serde_yaml::Value::String("nodes".to_string()));
 
     let mut nodes = Vec::new();
     if let Some(entries) = raw_mapping
@@ -51,36 +53,13 @@ pub(crate) fn parse(content: &str) -> anyhow::Result<DmGraph> {
 
             let node_id = node_field.as_deref().or(path_field.as_deref());
 
-            // Build extra_fields: everything except id, node, path, config
-            let mut node_extra = mapping.clone();
-            for key in &["id", "node", "path", "config"] {
-                node_extra.remove(serde_yaml::Value::String(key.to_string()));
-            }
+            // `restart:` is a dm-only extension consumed by the run supervisor.
+            let mut mapping = mapping.clone();
+            mapping.shift_remove(serde_yaml::Value::String("restart".to_string()));
 
             match node_id {
                 Some(id) if node_field.is_some() => {
-                    let inline_config = mapping
-                        .get(serde_yaml::Value::String("config".to_string()))
-                        .and_then(|v| serde_json::to_value(v).ok())
-                        .unwrap_or(serde_json::json!({}));
-
-                    // Preserve existing env from YAML
-                    let existing_env = mapping
-                        .get(serde_yaml::Value::String("env".to_string()))
-                        .and_then(|v| v.as_mapping().cloned())
-                        .unwrap_or_default();
-
-                    // Remove env from extra_fields since we manage it separately
-                    node_extra.remove(serde_yaml::Value::String("env".to_string()));
-
-                    nodes.push(DmNode::Managed(ManagedNode {
-                        yaml_id,
-                        node_id: id.to_string(),
-                        inline_config,
-                        resolved_path: None,
-                        merged_env: existing_env,
-                        extra_fields: node_extra,
-                    }));
+                    nodes.push(DmNode::Managed(managed_node(yaml_id, id, &mapping)));
                 }
                 _ => {
                     // External node or node without node:/path: — pass through as-is
@@ -99,6 +78,36 @@ pub(crate) fn parse(content: &str) -> anyhow::Result<DmGraph> {
     })
 }
 
+/// A managed node for `node_id` from its YAML entry.
+fn managed_node(yaml_id: String, node_id: &str, mapping: &serde_yaml::Mapping) -> ManagedNode {
+    let inline_config = mapping
+        .get(serde_yaml::Value::String("config".to_string()))
+        .and_then(|v| serde_json::to_value(v).ok())
+        .unwrap_or(serde_json::json!({}));
+
+    // Preserve existing env from YAML
+    let existing_env = mapping
+        .get(serde_yaml::Value::String("env".to_string()))
+        .and_then(|v| v.as_mapping().cloned())
+        .unwrap_or_default();
+
+    // Build extra_fields: everything except id, node, path, config and env,
+    // which is managed separately
+    let mut node_extra = mapping.clone();
+    for key in &["id", "node", "path", "config", "env"] {
+        node_extra.shift_remove(serde_yaml::Value::String(key.to_string()));
+    }
+
+    ManagedNode {
+        yaml_id,
+        node_id: node_id.to_string(),
+        inline_config,
+        resolved_path: None,
+        merged_env: existing_env,
+        extra_fields: node_extra,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Pass 1.5: Validate Reserved — check for conflicts
 // ---------------------------------------------------------------------------
@@ -112,6 +121,184 @@ pub(crate) fn validate_reserved(
 ) {
 }
 
+// ---------------------------------------------------------------------------
+// Pass 1.55: Apply Port Adapters — `adapter:` renames → declared port ids
+// ---------------------------------------------------------------------------
+
+/// Rewrite port ids renamed by managed nodes' `adapter:` blocks back to the
+/// ids declared in `dm.json`, checking each renamed port is declared.
+///
+/// Runs before schema validation so that it sees declared ports only.
+pub(crate) fn apply_port_adapters(
+    ctx: &TranspileContext,
+    graph: &mut DmGraph,
+    diags: &mut Vec<TranspileDiagnostic>,
+) {
+    let adapter_key = serde_yaml::Value::String("adapter".to_string());
+    let mut renames = OutputRenames::new();
+
+    for node in &mut graph.nodes {
+        let DmNode::Managed(managed) = node else {
+            continue;
+        };
+        let Some(raw) = managed.extra_fields.remove(&adapter_key) else {
+            continue;
+        };
+        let adapter = match parse_adapter(&raw) {
+            Ok(adapter) => adapter,
+            Err(reason) => {
+                diags.push(TranspileDiagnostic {
+                    yaml_id: managed.yaml_id.clone(),
+                    node_id: managed.node_id.clone(),
+                    kind: DiagnosticKind::InvalidAdapter { reason },
+                });
+                continue;
+            }
+        };
+
+        if let Some(meta) = load_node_meta(ctx, &managed.node_id) {
+            for (direction, port_id) in unknown_ports(&meta, &adapter) {
+                diags.push(TranspileDiagnostic {
+                    yaml_id: managed.yaml_id.clone(),
+                    node_id: managed.node_id.clone(),
+                    kind: DiagnosticKind::UnknownAdapterPort { port_id, direction },
+                });
+            }
+        }
+
+        adapter::apply_to_node(managed, &adapter);
+        for (declared, alias) in adapter.outputs {
+            renames.insert((managed.yaml_id.clone(), alias), declared);
+        }
+    }
+
+    if renames.is_empty() {
+        return;
+    }
+    let inputs_key = serde_yaml::Value::String("inputs".to_string());
+    for node in &mut graph.nodes {
+        let fields = match node {
+            DmNode::Managed(managed) => &mut managed.extra_fields,
+            DmNode::External { raw, .. } => raw,
+        };
+        if let Some(inputs) = fields.get_mut(&inputs_key).and_then(|v| v.as_mapping_mut()) {
+            rewrite_sources(inputs, &renames);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Pass 1.55: Validate Inputs — sources exist, ports are declared
+// ---------------------------------------------------------------------------
+
+/// Check that every managed node's `inputs:` read from a node in the graph
+/// and use ports that the involved `dm.json` files declare.
+///
+/// Port ids are only checked against nodes that declare at least one port in
+/// that direction and don't set `dynamic_ports`.
+pub(crate) fn validate_inputs(
+    ctx: &TranspileContext,
+    graph: &DmGraph,
+    diags: &mut Vec<TranspileDiagnostic>,
+) {
+    let mut yaml_to_node: std::collections::HashMap<&str, Option<&str>> =
+        std::collections::HashMap::new();
+    for node in &graph.nodes {
+        match node {
+            DmNode::Managed(managed) => {
+                yaml_to_node.insert(&managed.yaml_id, Some(&managed.node_id));
+            }
+            DmNode::External { _yaml_id, .. } => {
+                yaml_to_node.insert(_yaml_id, None);
+            }
+        }
+    }
+    let declared = |meta: &Node, direction: node::NodePortDirection, port_id: &str| {
+        let mut ports = meta
+            .ports
+            .iter()
+            .filter(|p| p.direction == direction)
+            .peekable();
+        meta.dynamic_ports || ports.peek().is_none() || ports.any(|p| p.id == port_id)
+    };
+
+    for node in &graph.nodes {
+        let DmNode::Managed(managed) = node else {
+            continue;
+        };
+        let Some(inputs) = managed
+            .extra_fields
+            .get(serde_yaml::Value::String("inputs".to_string()))
+            .and_then(|v| v.as_mapping())
+        else {
+            continue;
+        };
+        let this_meta = load_node_meta(ctx, &managed.node_id);
+
+        for (input_key, source_val) in inputs {
+            let Some(input_port_id) = input_key.as_str() else {
+                continue;
+            };
+            let source = source_val
+                .as_str()
+                .or_else(|| source_val.get("source").and_then(serde_yaml::Value::as_str));
+            let Some(source) = source else {
+                continue;
+            };
+
+            if let Some(meta) = &this_meta {
+                if !declared(meta, node::NodePortDirection::Input, input_port_id) {
+                    diags.push(TranspileDiagnostic {
+                        yaml_id: managed.yaml_id.clone(),
+                        node_id: managed.node_id.clone(),
+                        kind: DiagnosticKind::UndeclaredPort {
+                            port_id: input_port_id.to_string(),
+                            direction: "input",
+                        },
+                    });
+                }
+            }
+
+            let Some((source_yaml_id, source_output_id)) = source.split_once('/') else {
+                continue;
+            };
+            if source_yaml_id == "dora" {
+                continue;
+            }
+            let Some(source_node_id) = yaml_to_node.get(source_yaml_id) else {
+                diags.push(TranspileDiagnostic {
+                    yaml_id: managed.yaml_id.clone(),
+                    node_id: managed.node_id.clone(),
+                    kind: DiagnosticKind::UnknownInputSource {
+                        input_port: input_port_id.to_string(),
+                        source: source.to_string(),
+                    },
+                });
+                continue;
+            };
+            let Some(source_node_id) = source_node_id else {
+                continue; // External node — outputs are not declared anywhere
+            };
+            if let Some(source_meta) = load_node_meta(ctx, source_node_id) {
+                if !declared(
+                    &source_meta,
+                    node::NodePortDirection::Output,
+                    source_output_id,
+                ) {
+                    diags.push(TranspileDiagnostic {
+                        yaml_id: source_yaml_id.to_string(),
+                        node_id: source_node_id.to_string(),
+                        kind: DiagnosticKind::UndeclaredPort {
+                            port_id: source_output_id.to_string(),
+                            direction: "output",
+                        },
+                    });
+                }
+            }
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Pass 1.6: Validate Port Schemas — check connection type compatibility
 // ---------------------------------------------------------------------------
@@ -274,11 +461,32 @@ fn load_node_meta(ctx: &TranspileContext, node_id: &str) -> Option<Node> {
 // ---------------------------------------------------------------------------
 
 /// Resolve managed node IDs to absolute executable paths via `dm.json`.
+///
+/// A `path:` that names a node by id (see [`node::hub::names_registry_node`])
+/// and has no `build:` for dora to run is resolved like `node:`, so an
+/// uninstalled registry node is reported instead of being handed to dora
+/// as a bare path.
 pub(crate) fn resolve_paths(
     ctx: &TranspileContext,
     graph: &mut DmGraph,
     diags: &mut Vec<TranspileDiagnostic>,
 ) {
+    let path_key = serde_yaml::Value::String("path".to_string());
+    for node in &mut graph.nodes {
+        let DmNode::External { _yaml_id, raw } = node else {
+            continue;
+        };
+        let Some(path) = raw.get(&path_key).and_then(|v| v.as_str()) else {
+            continue;
+        };
+        if raw.contains_key(serde_yaml::Value::String("build".to_string())) {
+            continue;
+        }
+        if node::hub::names_registry_node(ctx.home, path) {
+            *node = DmNode::Managed(managed_node(_yaml_id.clone(), path, raw));
+        }
+    }
+
     for node in &mut graph.nodes {
         let DmNode::Managed(managed) = node else {
             continue;
@@ -326,7 +534,11 @@ pub(crate) fn resolve_paths(
             continue;
         };
 
-        if meta.executable.is_empty() {
+        if let Some(uv_env) = ctx.uv_env.filter(|_| meta.runtime.language == "python") {
+            let script = local_run::uv_script(&meta, &node_cache_dir);
+            let abs_exec = uv_env.join("bin").join(script);
+            managed.resolved_path = Some(abs_exec.display().to_string());
+        } else if meta.executable.is_empty() {
             diags.push(TranspileDiagnostic {
                 yaml_id: managed.yaml_id.clone(),
                 node_id: managed.node_id.clone(),
@@ -354,7 +566,7 @@ pub(crate) fn resolve_paths(
 pub(crate) fn merge_config(
     ctx: &TranspileContext,
     graph: &mut DmGraph,
-    _diags: &mut Vec<TranspileDiagnostic>,
+    diags: &mut Vec<TranspileDiagnostic>,
 ) {
     for node in &mut graph.nodes {
         let DmNode::Managed(managed) = node else {
@@ -392,6 +604,22 @@ pub(crate) fn merge_config(
         let config_defaults =
             node::get_node_config(ctx.home, &managed.node_id).unwrap_or(serde_json::json!({}));
 
+        let mut effective = config_defaults.clone();
+        if let (Some(effective), Some(inline)) =
+            (effective.as_object_mut(), managed.inline_config.as_object())
+        {
+            effective.extend(inline.clone());
+        }
+        for violation in node::validate_config(schema, &effective) {
+            diags.push(TranspileDiagnostic {
+                yaml_id: managed.yaml_id.clone(),
+                node_id: managed.node_id.clone(),
+                kind: DiagnosticKind::InvalidConfig {
+                    violation: violation.to_string(),
+                },
+            });
+        }
+
         for (key, field_schema) in schema_obj {
             let Some(env_name) = field_schema.get("env").and_then(|e| e.as_str()) else {
                 continue;
@@ -516,8 +744,8 @@ pub(crate) fn inject_dm_bridge(
         return;
     }
 
-    let bridge_exe = crate::util::resolve_dm_cli_exe();
-    if bridge_exe == std::path::PathBuf::from("dm")
+    let bridge_exe = crate::util::dm_cli_exe(ctx.home);
+    if bridge_exe.as_os_str() == "dm"
         && std::env::var(crate::util::DM_CLI_BIN_ENV_KEY)
             .ok()
             .map(|value| value.trim().is_empty())
@@ -0,0 +1,122 @@
+//! What a managed node's process sees once transpiled: the same resolve,
+//! config-merge and runtime-env passes a real dataflow goes through, run on
+//! a one-node graph.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use super::context::TranspileContext;
+use super::model::DmNode;
+use super::passes;
+use crate::{config, node};
+
+/// Stands in for the run id in run-specific values such as `DM_RUN_OUT_DIR`.
+pub const PREVIEW_RUN_ID: &str = "<run-id>";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeEnvPreview {
+    pub node_id: String,
+    /// Executable transpile writes as the node's `path:`
+    pub executable: Option<String>,
+    /// Python virtualenv the executable belongs to, if any. dm does not
+    /// activate it; the executable's own shebang selects its interpreter.
+    pub venv: Option<String>,
+    /// Python version the venv was created with, e.g. `3.11`
+    pub python_version: Option<String>,
+    /// The venv's `site-packages` for that version
+    pub site_packages: Option<String>,
+    /// The node's `env:` block as transpile emits it: config-derived
+    /// variables plus `DM_RUN_ID` / `DM_NODE_ID` / `DM_RUN_OUT_DIR`
+    pub node_env: BTreeMap<String, String>,
+    /// `runtime_env` from config.toml, set on the dora processes that spawn
+    /// nodes; `secret:NAME` references are shown unresolved
+    pub runtime_env: BTreeMap<String, String>,
+    /// Whether nodes inherit dm's own environment (`runtime_inherit_env`)
+    pub inherit_env: bool,
+    /// Effective `PATH`: node env, else `runtime_env`, else inherited
+    pub path: Option<String>,
+    /// Effective `PYTHONPATH`, resolved the same way
+    pub pythonpath: Option<String>,
+    /// Transpile warnings for this node, e.g. a missing executable
+    pub warnings: Vec<String>,
+}
+
+/// Preview the environment of installed node `node_id`, or `None` when no
+/// such node is installed.
+pub fn preview_node_env(home: &Path, node_id: &str) -> Result<Option<NodeEnvPreview>> {
+    if node::resolve_dm_json_path(home, node_id).is_none() {
+        return Ok(None);
+    }
+    let yaml = serde_yaml::to_string(&serde_json::json!({
+        "nodes": [{ "id": node_id, "node": node_id }]
+    }))?;
+    let ctx = TranspileContext {
+        home,
+        run_id: PREVIEW_RUN_ID,
+        uv_env: None,
+    };
+    let mut diags = Vec::new();
+    let mut graph = passes::parse(&yaml)?;
+    passes::resolve_paths(&ctx, &mut graph, &mut diags);
+    passes::merge_config(&ctx, &mut graph, &mut diags);
+    passes::inject_runtime_env(&ctx, &mut graph);
+
+    let Some(DmNode::Managed(managed)) = graph.nodes.into_iter().next() else {
+        return Ok(None);
+    };
+    let node_env: BTreeMap<String, String> = managed
+        .merged_env
+        .iter()
+        .filter_map(|(key, value)| {
+            Some((
+                key.as_str()?.to_string(),
+                super::bridge::yaml_value_to_string(value),
+            ))
+        })
+        .collect();
+    let venv = managed.resolved_path.as_deref().and_then(|exe| {
+        Path::new(exe)
+            .ancestors()
+            .find(|dir| dir.join("pyvenv.cfg").is_file())
+            .map(Path::to_path_buf)
+    });
+    let python_version = venv.as_deref().and_then(node::venv_python_version);
+    let site_packages = venv
+        .as_deref()
+        .and_then(node::site_packages)
+        .map(|dir| dir.display().to_string());
+    let venv = venv.map(|dir| dir.display().to_string());
+
+    let cfg = config::load_config(home)?;
+    let inherit_env = cfg.runtime_inherit_env.unwrap_or(true);
+    let effective = |key: &str, always_kept: bool| {
+        node_env
+            .get(key)
+            .or_else(|| cfg.runtime_env.get(key))
+            .cloned()
+            .or_else(|| {
+                (inherit_env || always_kept)
+                    .then(|| std::env::var(key).ok())
+                    .flatten()
+            })
+    };
+    let path = effective("PATH", true);
+    let pythonpath = effective("PYTHONPATH", false);
+
+    Ok(Some(NodeEnvPreview {
+        node_id: node_id.to_string(),
+        executable: managed.resolved_path,
+        venv,
+        python_version,
+        site_packages,
+        node_env,
+        path,
+        pythonpath,
+        runtime_env: cfg.runtime_env,
+        inherit_env,
+        warnings: diags.iter().map(ToString::to_string).collect(),
+    }))
+}
@@ -0,0 +1,121 @@
+//! Checking every saved dataflow against the installed nodes and the active
+//! dora version, so breakage from an upgrade shows up before someone
+//! presses Run.
+
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::events::{try_emit, EventBuilder, EventLevel, EventSource, OperationEvent};
+
+use super::repo;
+
+/// Why one saved dataflow would fail to start.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataflowValidation {
+    pub name: String,
+    pub ok: bool,
+    /// Invalid YAML, missing nodes and transpile diagnostics
+    pub problems: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataflowValidationReport {
+    pub active_version: Option<String>,
+    /// Set when no dora version is active or its binary is missing; then
+    /// no dataflow can start, whatever its own problems
+    pub runtime_problem: Option<String>,
+    pub dataflows: Vec<DataflowValidation>,
+    /// Dataflows with at least one problem
+    pub failing: usize,
+}
+
+/// Validate all saved dataflows, emitting a Warn event for each one that
+/// would fail to start.
+pub fn validate_all(home: &Path) -> Result<DataflowValidationReport> {
+    let op = OperationEvent::new(home, EventSource::Dataflow, "dataflow.validate_all");
+    op.emit_start();
+
+    let result = (|| {
+        let cfg = config::load_config(home)?;
+        let runtime_problem = match &cfg.active_version {
+            None => Some("no active dora version; run `dm use <version>`".to_string()),
+            Some(version)
+                if !config::dora_bin_path(&config::versions_dir(home).join(version)).exists() =>
+            {
+                Some(format!(
+                    "dora {} is active but its binary is missing; run `dm install {}`",
+                    version, version
+                ))
+            }
+            Some(_) => None,
+        };
+
+        let mut dataflows = Vec::new();
+        for project in repo::list_projects(home)? {
+            let problems = match repo::read_yaml(home, &project.name) {
+                Ok(yaml) => problems(home, &yaml),
+                Err(e) => vec![format!("{:#}", e)],
+            };
+            dataflows.push(DataflowValidation {
+                name: project.name,
+                ok: problems.is_empty(),
+                problems,
+            });
+        }
+        dataflows.sort_by(|a, b| a.name.cmp(&b.name));
+
+        for failing in dataflows.iter().filter(|flow| !flow.ok) {
+            try_emit(
+                home,
+                EventBuilder::new(EventSource::Dataflow, "dataflow.invalid")
+                    .level(EventLevel::Warn)
+                    .message(format!(
+                        "Dataflow '{}' would fail to start: {}",
+                        failing.name,
+                        failing.problems.join("; ")
+                    ))
+                    .attr("dataflow", &failing.name)
+                    .attr("problems", &failing.problems)
+                    .build(),
+            );
+        }
+
+        Ok(DataflowValidationReport {
+            failing: dataflows.iter().filter(|flow| !flow.ok).count(),
+            active_version: cfg.active_version,
+            runtime_problem,
+            dataflows,
+        })
+    })();
+
+    op.emit_result(&result);
+    result
+}
+
+fn problems(home: &Path, yaml: &str) -> Vec<String> {
+    let executable = super::inspect_yaml(home, yaml).summary;
+    if executable.invalid_yaml {
+        return vec![format!(
+            "invalid yaml: {}",
+            executable.error.unwrap_or_default()
+        )];
+    }
+    let mut problems: Vec<String> = executable
+        .missing_nodes
+        .iter()
+        .map(|node| format!("node '{}' is not installed", node))
+        .collect();
+    match super::check_yaml(home, yaml) {
+        // Missing nodes are already reported above.
+        Ok(diags) => problems.extend(
+            diags
+                .into_iter()
+                .filter(|diag| !diag.ends_with(": not installed")),
+        ),
+        Err(e) => problems.push(format!("{:#}", e)),
+    }
+    problems
+}
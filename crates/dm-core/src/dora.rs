@@ -1,11 +1,17 @@
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
 use std::path::{Path, PathBuf};
 use std::process::Command as StdCommand;
 use std::process::Stdio;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
 use tokio::process::Command;
 
 use crate::config;
+use crate::error::DmError;
+use crate::events::{try_emit, EventBuilder, EventLevel, EventSource};
+use crate::runs::OutputCapture;
 
 #[derive(Debug, Clone)]
 pub struct DataflowRuntimeInfo {
@@ -17,23 +23,205 @@ pub struct DataflowRuntimeInfo {
     pub memory: Option<String>,
 }
 
+/// How long output still arriving after dora exits is collected.
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Variables kept for dora processes even when inheritance is disabled.
+const ESSENTIAL_ENV_VARS: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LANG",
+    "TMPDIR",
+    "TEMP",
+    "TMP",
+    "SYSTEMROOT",
+    "USERPROFILE",
+    "DM_HOME",
+];
+
+/// Environment applied to every dora process dm spawns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeEnv {
+    /// Start from dm's own environment instead of a minimal one.
+    pub inherit: bool,
+    /// Variables set on top, overriding inherited values.
+    pub vars: BTreeMap<String, String>,
+}
+
+impl Default for RuntimeEnv {
+    fn default() -> Self {
+        Self {
+            inherit: true,
+            vars: BTreeMap::new(),
+        }
+    }
+}
+
+impl RuntimeEnv {
+    /// Load `runtime_env` / `runtime_inherit_env` from config.toml,
+    /// resolving `secret:NAME` values from the secret store.
+    pub fn from_config(home: &Path) -> Self {
+        let cfg = config::load_config(home).unwrap_or_default();
+        Self {
+            inherit: cfg.runtime_inherit_env.unwrap_or(true),
+            vars: crate::secrets::resolve_env_refs(home, cfg.runtime_env),
+        }
+    }
+
+    /// Layer per-invocation overrides (e.g. `dm up --env`) on top.
+    pub fn with_overrides(
+        mut self,
+        vars: &BTreeMap<String, String>,
+        inherit: Option<bool>,
+    ) -> Self {
+        self.vars
+            .extend(vars.iter().map(|(k, v)| (k.clone(), v.clone())));
+        if let Some(inherit) = inherit {
+            self.inherit = inherit;
+        }
+        self
+    }
+
+    /// Put the version directory of `bin` first on `PATH` when it holds
+    /// companion binaries, so dora finds `dora-coordinator`, `dora-daemon`
+    /// and `dora-runtime` the way upstream multi-binary deployments expect.
+    pub fn with_companions(mut self, bin: &Path) -> Self {
+        let Some(dir) = bin.parent() else {
+            return self;
+        };
+        let has_companion = config::COMPANION_BINARIES
+            .iter()
+            .any(|name| config::companion_bin_path(dir, name).exists());
+        if !has_companion {
+            return self;
+        }
+        let current = self
+            .vars
+            .get("PATH")
+            .map(std::ffi::OsString::from)
+            .or_else(|| std::env::var_os("PATH"))
+            .unwrap_or_default();
+        let paths = std::iter::once(dir.to_path_buf()).chain(std::env::split_paths(&current));
+        if let Ok(joined) = std::env::join_paths(paths) {
+            self.vars
+                .insert("PATH".to_string(), joined.to_string_lossy().into_owned());
+        }
+        self
+    }
+
+    pub fn apply(&self, cmd: &mut StdCommand) {
+        if !self.inherit {
+            cmd.env_clear();
+            for key in ESSENTIAL_ENV_VARS {
+                if let Some(value) = std::env::var_os(key) {
+                    cmd.env(key, value);
+                }
+            }
+        }
+        cmd.envs(&self.vars);
+    }
+}
+
+/// dora subcommands that talk to a coordinator and accept
+/// `--coordinator-addr` / `--coordinator-port`.
+const COORDINATOR_SUBCOMMANDS: &[&str] = &[
+    "build", "check", "destroy", "list", "logs", "node", "start", "stop", "topic",
+];
+
+/// Parse a coordinator or daemon address given as `IP` or `IP:PORT`.
+pub fn parse_machine_addr(raw: &str) -> Result<(IpAddr, Option<u16>)> {
+    let raw = raw.trim();
+    if let Ok(addr) = raw.parse::<SocketAddr>() {
+        return Ok((addr.ip(), Some(addr.port())));
+    }
+    raw.parse::<IpAddr>()
+        .map(|ip| (ip, None))
+        .map_err(|_| anyhow::anyhow!("Invalid address '{}': expected IP or IP:PORT", raw))
+}
+
+/// `--coordinator-addr` / `--coordinator-port` for `coordinator_addr` in
+/// config.toml; empty when dora talks to the local coordinator.
+pub fn coordinator_args(home: &Path) -> Result<Vec<String>> {
+    let Some(raw) = config::load_config(home)
+        .ok()
+        .and_then(|cfg| cfg.coordinator_addr)
+    else {
+        return Ok(Vec::new());
+    };
+    let (ip, port) =
+        parse_machine_addr(&raw).context("Invalid `coordinator_addr` in config.toml")?;
+    let mut args = vec!["--coordinator-addr".to_string(), ip.to_string()];
+    if let Some(port) = port {
+        args.extend(["--coordinator-port".to_string(), port.to_string()]);
+    }
+    Ok(args)
+}
+
+/// `args` with the configured coordinator appended when the subcommand talks
+/// to one and does not name a coordinator itself.
+fn with_coordinator(home: &Path, args: &[String]) -> Result<Vec<String>> {
+    let mut args = args.to_vec();
+    let targets_coordinator = args
+        .first()
+        .is_some_and(|cmd| COORDINATOR_SUBCOMMANDS.contains(&cmd.as_str()));
+    if targets_coordinator && !args.iter().any(|arg| arg == "--coordinator-addr") {
+        args.extend(coordinator_args(home)?);
+    }
+    Ok(args)
+}
+
+/// Parse a `KEY=VALUE` assignment as given to `--env`.
+pub fn parse_env_assignment(raw: &str) -> Result<(String, String)> {
+    let (key, value) = raw
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("Invalid env '{}': expected KEY=VALUE", raw))?;
+    let key = key.trim();
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        anyhow::bail!("Invalid env '{}': variable name must be non-empty", raw);
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
 /// Resolve the path to the active dora binary managed by dm.
 pub fn active_dora_bin(home: &Path) -> Result<PathBuf> {
     let cfg = config::load_config(home)?;
-    let version = cfg
-        .active_version
-        .ok_or_else(|| anyhow::anyhow!("No active dora version. Run `dm install` first."))?;
+    let version = cfg.active_version.ok_or_else(|| {
+        DmError::DependencyMissing("No active dora version. Run `dm install` first.".into())
+    })?;
     let bin = config::dora_bin_path(&config::versions_dir(home).join(&version));
     if !bin.exists() {
-        anyhow::bail!(
+        return Err(DmError::DependencyMissing(format!(
             "dora binary not found at {}. Run `dm install {}` to fix.",
             bin.display(),
             version
-        );
+        ))
+        .into());
     }
     Ok(bin)
 }
 
+/// Whether dora invocations are answered by the in-process mock runtime
+/// (`mock-runtime` feature) instead of a dora binary.
+pub fn mock_enabled() -> bool {
+    #[cfg(feature = "mock-runtime")]
+    return crate::mock_runtime::is_enabled();
+    #[cfg(not(feature = "mock-runtime"))]
+    false
+}
+
+/// `(exit_code, stdout, stderr)` of `dora <args>` from the mock runtime,
+/// or `None` when the real binary should run.
+#[cfg(feature = "mock-runtime")]
+pub(crate) fn mocked(home: &Path, args: &[String]) -> Option<(i32, String, String)> {
+    crate::mock_runtime::is_enabled().then(|| crate::mock_runtime::run(home, args))
+}
+
+#[cfg(not(feature = "mock-runtime"))]
+pub(crate) fn mocked(_home: &Path, _args: &[String]) -> Option<(i32, String, String)> {
+    None
+}
+
 /// Run a dora subcommand using the active managed binary.
 /// Returns (exit_code, stdout, stderr).
 pub async fn run_dora(
@@ -41,63 +229,200 @@ pub async fn run_dora(
     args: &[String],
     verbose: bool,
 ) -> Result<(i32, String, String)> {
+    if let Some((code, stdout, stderr)) = mocked(home, args) {
+        trace_dora(home, args, Instant::now(), Ok(code), Some(&stderr));
+        return Ok((code, stdout, stderr));
+    }
+    let args = &with_coordinator(home, args)?;
     let bin = active_dora_bin(home)?;
     if verbose {
         eprintln!("[dm] exec: {} {}", bin.display(), args.join(" "));
     }
-    let output = Command::new(&bin)
+    let mut cmd = Command::new(&bin);
+    RuntimeEnv::from_config(home).apply(cmd.as_std_mut());
+    let started = Instant::now();
+    let output = cmd
         .args(args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
-        .await
+        .await;
+    let output = trace_spawn(home, args, started, output)
         .with_context(|| format!("Failed to run dora at {}", bin.display()))?;
 
     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
     let stderr = String::from_utf8_lossy(&output.stderr).to_string();
     let code = output.status.code().unwrap_or(-1);
+    trace_dora(home, args, started, Ok(code), Some(&stderr));
     Ok((code, stdout, stderr))
 }
 
 /// Run dora with inherited stdio (for interactive / pass-through commands).
+/// Ctrl-C is forwarded to dora's process group rather than killing dm.
 pub async fn exec_dora(home: &Path, args: &[String], verbose: bool) -> Result<i32> {
+    exec_dora_with_env(
+        home,
+        args,
+        None,
+        &RuntimeEnv::from_config(home),
+        None,
+        verbose,
+    )
+    .await
+}
+
+/// Like [`exec_dora`], but with an explicit environment and working directory.
+///
+/// With a `capture`, dora's stdout/stderr are piped instead of inherited:
+/// every line is still echoed to the terminal and is also recorded as a
+/// `dora.output` event.
+pub async fn exec_dora_with_env(
+    home: &Path,
+    args: &[String],
+    cwd: Option<&Path>,
+    env: &RuntimeEnv,
+    capture: Option<&mut OutputCapture>,
+    verbose: bool,
+) -> Result<i32> {
+    if let Some((code, stdout, stderr)) = mocked(home, args) {
+        print!("{stdout}");
+        eprint!("{stderr}");
+        if let Some(capture) = capture {
+            stdout.lines().for_each(|line| capture.push("stdout", line));
+            stderr.lines().for_each(|line| capture.push("stderr", line));
+            capture.flush();
+        }
+        return Ok(code);
+    }
+    let args = &with_coordinator(home, args)?;
     let bin = active_dora_bin(home)?;
     if verbose {
         eprintln!("[dm] exec: {} {}", bin.display(), args.join(" "));
     }
-    let status = Command::new(&bin)
-        .args(args)
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .stdin(Stdio::inherit())
-        .status()
-        .await
+    let mut cmd = Command::new(&bin);
+    env.clone().with_companions(&bin).apply(cmd.as_std_mut());
+    crate::process::own_process_group(cmd.as_std_mut());
+    if let Some(cwd) = cwd {
+        cmd.current_dir(cwd);
+    }
+    let output = if capture.is_some() {
+        Stdio::piped
+    } else {
+        Stdio::inherit
+    };
+    let started = Instant::now();
+    cmd.args(args)
+        .stdout(output())
+        .stderr(output())
+        .stdin(Stdio::inherit());
+    let status = match cmd.spawn() {
+        Ok(mut child) => match capture {
+            Some(capture) => wait_capturing(&mut child, capture).await,
+            None => crate::process::wait_forwarding_signals(&mut child).await,
+        },
+        Err(err) => Err(err),
+    };
+    let status = trace_spawn(home, args, started, status)
         .with_context(|| format!("Failed to run dora at {}", bin.display()))?;
 
-    Ok(status.code().unwrap_or(-1))
+    let code = crate::process::exit_code(&status);
+    // stderr went to the terminal (and the capture), so there is none to record.
+    trace_dora(home, args, started, Ok(code), None);
+    Ok(code)
 }
 
-pub async fn list_dataflow_ids(home: &Path, verbose: bool) -> Result<Vec<String>> {
-    let (code, stdout, stderr) = run_dora(home, &["list".to_string()], verbose).await?;
-    if code != 0 {
-        anyhow::bail!(stderr.trim().to_string());
-    }
+/// Wait for `child` like `wait_forwarding_signals`, echoing its piped
+/// stdout/stderr and feeding every line to `capture`.
+async fn wait_capturing(
+    child: &mut tokio::process::Child,
+    capture: &mut OutputCapture,
+) -> std::io::Result<std::process::ExitStatus> {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    forward_lines(child.stdout.take(), "stdout", tx.clone());
+    forward_lines(child.stderr.take(), "stderr", tx);
+
+    let wait = crate::process::wait_forwarding_signals(child);
+    tokio::pin!(wait);
+    let status = loop {
+        tokio::select! {
+            status = &mut wait => break status,
+            Some((stream, line)) = rx.recv() => capture.push(stream, &line),
+        }
+    };
+    // Nodes left in the process group may hold the pipes open; don't wait
+    // on them for long.
+    let _ = tokio::time::timeout(OUTPUT_DRAIN_TIMEOUT, async {
+        while let Some((stream, line)) = rx.recv().await {
+            capture.push(stream, &line);
+        }
+    })
+    .await;
+    capture.flush();
+    status
+}
+
+/// Echo each line of `pipe` to the matching terminal stream and send it on.
+fn forward_lines(
+    pipe: Option<impl tokio::io::AsyncRead + Unpin + Send + 'static>,
+    stream: &'static str,
+    tx: tokio::sync::mpsc::UnboundedSender<(&'static str, String)>,
+) {
+    use tokio::io::{AsyncBufReadExt, BufReader};
 
-    Ok(parse_runtime_infos(&stdout)
+    let Some(pipe) = pipe else {
+        return;
+    };
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(pipe).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if stream == "stdout" {
+                println!("{line}");
+            } else {
+                eprintln!("{line}");
+            }
+            if tx.send((stream, line)).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+pub async fn list_dataflow_ids(home: &Path, verbose: bool) -> Result<Vec<String>> {
+    Ok(list_dataflows(home, verbose)
+        .await?
         .into_iter()
         .map(|item| item.id)
         .collect())
 }
 
 pub async fn list_dataflows(home: &Path, verbose: bool) -> Result<Vec<DataflowRuntimeInfo>> {
-    let (code, stdout, stderr) = run_dora(home, &["list".to_string()], verbose).await?;
+    let (code, stdout, stderr) = run_dora_list(home, verbose).await?;
     if code != 0 {
-        anyhow::bail!(stderr.trim().to_string());
+        return Err(DmError::External(stderr.trim().to_string()).into());
     }
 
     Ok(parse_runtime_infos(&stdout))
 }
 
+/// `dora list` asking for one JSON object per dataflow.
+pub(crate) const LIST_JSON_ARGS: [&str; 3] = ["list", "--format", "json"];
+
+/// Whether `dora list` failed because the dora release predates `--format`;
+/// callers then fall back to the plain table.
+pub(crate) fn list_format_unsupported(code: i32, stderr: &str) -> bool {
+    code != 0 && stderr.contains("--format")
+}
+
+/// Run `dora list`, preferring its JSON output. Returns (exit_code, stdout,
+/// stderr); either output format parses with [`parse_runtime_infos`].
+pub async fn run_dora_list(home: &Path, verbose: bool) -> Result<(i32, String, String)> {
+    let (code, stdout, stderr) = run_dora(home, &LIST_JSON_ARGS.map(String::from), verbose).await?;
+    if list_format_unsupported(code, &stderr) {
+        return run_dora(home, &["list".to_string()], verbose).await;
+    }
+    Ok((code, stdout, stderr))
+}
+
 pub fn list_dataflow_ids_blocking(home: &Path, verbose: bool) -> Result<Vec<String>> {
     Ok(list_dataflows_blocking(home, verbose)?
         .into_iter()
@@ -106,39 +431,80 @@ pub fn list_dataflow_ids_blocking(home: &Path, verbose: bool) -> Result<Vec<Stri
 }
 
 pub fn list_dataflows_blocking(home: &Path, verbose: bool) -> Result<Vec<DataflowRuntimeInfo>> {
+    let (code, stdout, stderr) = match run_dora_blocking(home, &LIST_JSON_ARGS, verbose)? {
+        (code, _, stderr) if list_format_unsupported(code, &stderr) => {
+            run_dora_blocking(home, &["list"], verbose)?
+        }
+        output => output,
+    };
+    if code != 0 {
+        return Err(DmError::External(stderr.trim().to_string()).into());
+    }
+
+    Ok(parse_runtime_infos(&stdout))
+}
+
+fn run_dora_blocking(home: &Path, args: &[&str], verbose: bool) -> Result<(i32, String, String)> {
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    if let Some(output) = mocked(home, &args) {
+        return Ok(output);
+    }
+    let args = with_coordinator(home, &args)?;
     let bin = active_dora_bin(home)?;
     if verbose {
-        eprintln!("[dm] exec: {} list", bin.display());
+        eprintln!("[dm] exec: {} {}", bin.display(), args.join(" "));
     }
 
-    let output = StdCommand::new(&bin)
-        .arg("list")
+    let mut cmd = StdCommand::new(&bin);
+    RuntimeEnv::from_config(home).apply(&mut cmd);
+    let started = Instant::now();
+    let output = cmd
+        .args(&args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
-        .output()
+        .output();
+    let output = trace_spawn(home, &args, started, output)
         .with_context(|| format!("Failed to run dora at {}", bin.display()))?;
+    let code = output.status.code().unwrap_or(-1);
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    trace_dora(home, &args, started, Ok(code), Some(&stderr));
 
-    if !output.status.success() {
-        anyhow::bail!(String::from_utf8_lossy(&output.stderr).trim().to_string());
-    }
-
-    Ok(parse_runtime_infos(&String::from_utf8_lossy(
-        &output.stdout,
-    )))
+    Ok((
+        code,
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr,
+    ))
 }
 
 pub fn check_runtime_blocking(home: &Path, verbose: bool) -> Result<(bool, String)> {
+    let args = ["check".to_string()];
+    if let Some((code, stdout, stderr)) = mocked(home, &args) {
+        let output = if code == 0 { stdout } else { stderr };
+        return Ok((code == 0, output.trim().to_string()));
+    }
+    let args = with_coordinator(home, &args)?;
     let bin = active_dora_bin(home)?;
     if verbose {
         eprintln!("[dm] exec: {} check", bin.display());
     }
 
-    let output = StdCommand::new(&bin)
-        .arg("check")
+    let mut cmd = StdCommand::new(&bin);
+    RuntimeEnv::from_config(home).apply(&mut cmd);
+    let started = Instant::now();
+    let output = cmd
+        .args(&args)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
-        .output()
+        .output();
+    let output = trace_spawn(home, &args, started, output)
         .with_context(|| format!("Failed to run dora at {}", bin.display()))?;
+    trace_dora(
+        home,
+        &args,
+        started,
+        Ok(output.status.code().unwrap_or(-1)),
+        Some(&String::from_utf8_lossy(&output.stderr)),
+    );
 
     let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
     let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
@@ -152,6 +518,79 @@ pub fn check_runtime_blocking(home: &Path, verbose: bool) -> Result<(bool, Strin
     ))
 }
 
+/// Record a failed spawn before handing the error back.
+fn trace_spawn<T>(
+    home: &Path,
+    args: &[String],
+    started: Instant,
+    result: std::io::Result<T>,
+) -> std::io::Result<T> {
+    if let Err(e) = &result {
+        trace_dora(home, args, started, Err(e.to_string()), None);
+    }
+    result
+}
+
+/// Emit a `dora.exec` event describing one dora invocation, unless
+/// telemetry or `telemetry.trace_dora` is off or it is a successful quiet
+/// command.
+/// Non-zero exits are warnings and spawn failures errors.
+fn trace_dora(
+    home: &Path,
+    args: &[String],
+    started: Instant,
+    outcome: std::result::Result<i32, String>,
+    stderr: Option<&str>,
+) {
+    let telemetry = config::load_config(home)
+        .map(|cfg| cfg.telemetry)
+        .unwrap_or_default();
+    let command = args.first().map(String::as_str).unwrap_or("");
+    let quiet = outcome == Ok(0) && telemetry.trace_quiet_commands.iter().any(|c| c == command);
+    if !telemetry.enabled || !telemetry.trace_dora || quiet {
+        return;
+    }
+
+    let duration_ms = started.elapsed().as_millis() as u64;
+    let mut event = EventBuilder::new(EventSource::Core, "dora.exec")
+        .attr("argv", args)
+        .attr("duration_ms", duration_ms);
+    event = match &outcome {
+        Ok(0) => event.message(format!("dora {command} ({duration_ms} ms)")),
+        Ok(code) => event
+            .level(EventLevel::Warn)
+            .message(format!("dora {command} exited with {code}")),
+        Err(e) => event
+            .level(EventLevel::Error)
+            .message(format!("dora {command} failed to start: {e}")),
+    };
+    if let Ok(code) = outcome {
+        event = event.attr("exit_code", code);
+    }
+    if let Some(stderr) = stderr
+        .map(|text| truncate_tail(text.trim(), telemetry.trace_stderr_bytes))
+        .filter(|text| !text.is_empty())
+    {
+        event = event.attr("stderr", stderr);
+    }
+    try_emit(home, event.build());
+}
+
+/// The last `max_bytes` of `text`, cut on a char boundary; errors are
+/// usually at the end of stderr.
+fn truncate_tail(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut start = text.len() - max_bytes;
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    &text[start..]
+}
+
+/// Dataflows in `dora list` output: JSON lines (`--format json`) or the
+/// plain table of older dora releases. Rows without a UUID are skipped.
 pub(crate) fn parse_runtime_infos(stdout: &str) -> Vec<DataflowRuntimeInfo> {
     stdout
         .lines()
@@ -159,28 +598,72 @@ pub(crate) fn parse_runtime_infos(stdout: &str) -> Vec<DataflowRuntimeInfo> {
         .filter(|line| !line.is_empty())
         .filter(|line| !line.starts_with("UUID"))
         .filter_map(|line| {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            let id = parts.first()?.to_string();
-            if uuid::Uuid::parse_str(&id).is_err() {
-                return None;
-            }
-            let name = parts.get(1).map(|value| value.to_string());
-            let status = parts.get(2).map(|value| value.to_string());
-            let nodes = parts.get(3).and_then(|value| value.parse::<u32>().ok());
-            let cpu = parts.get(4).map(|value| value.to_string());
-            let memory = parts.get(5).map(|value| value.to_string());
-            Some(DataflowRuntimeInfo {
-                id,
-                name,
-                status,
-                nodes,
-                cpu,
-                memory,
-            })
+            let info = if line.starts_with('{') {
+                parse_runtime_json_row(line)?
+            } else {
+                parse_runtime_table_row(line)?
+            };
+            uuid::Uuid::parse_str(&info.id).is_ok().then_some(info)
         })
         .collect()
 }
 
+/// `{"uuid":..,"name":..,"status":..,"nodes":3,"cpu":0.5,"memory":1.8}`,
+/// with CPU in percent and memory in GB like the table shows them.
+fn parse_runtime_json_row(line: &str) -> Option<DataflowRuntimeInfo> {
+    let row: serde_json::Value = serde_json::from_str(line).ok()?;
+    let text = |key: &str| {
+        row.get(key)
+            .and_then(|value| value.as_str())
+            .map(str::to_string)
+            .filter(|value| !value.is_empty())
+    };
+    let number = |key: &str, unit: &str| match row.get(key)? {
+        serde_json::Value::Number(value) => Some(format!("{:.1}{unit}", value.as_f64()?)),
+        serde_json::Value::String(value) => Some(value.clone()),
+        _ => None,
+    };
+    Some(DataflowRuntimeInfo {
+        id: text("uuid").or_else(|| text("id"))?,
+        name: text("name"),
+        status: text("status"),
+        nodes: row
+            .get("nodes")
+            .and_then(|value| value.as_u64())
+            .and_then(|nodes| u32::try_from(nodes).ok()),
+        cpu: number("cpu", "%"),
+        memory: number("memory", " GB"),
+    })
+}
+
+/// `UUID Name Status Nodes CPU Memory`; unnamed dataflows leave the name
+/// column empty and the memory column carries a unit (`0.0 GB`).
+fn parse_runtime_table_row(line: &str) -> Option<DataflowRuntimeInfo> {
+    let mut parts: Vec<&str> = line.split_whitespace().collect();
+    let id = parts.first()?.to_string();
+    let unnamed = parts
+        .get(2)
+        .is_some_and(|value| value.parse::<u32>().is_ok());
+    if unnamed {
+        parts.insert(1, "");
+    }
+    let column = |index: usize| {
+        parts
+            .get(index)
+            .map(|value| value.to_string())
+            .filter(|value| !value.is_empty())
+    };
+    let memory = (parts.len() > 5).then(|| parts[5..].join(" "));
+    Some(DataflowRuntimeInfo {
+        id,
+        name: column(1),
+        status: column(2),
+        nodes: parts.get(3).and_then(|value| value.parse::<u32>().ok()),
+        cpu: column(4),
+        memory,
+    })
+}
+
 /// Get the version string from a dora binary.
 pub async fn get_dora_version(bin_path: &Path) -> Result<String> {
     let output = Command::new(bin_path)
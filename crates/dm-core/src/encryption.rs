@@ -0,0 +1,297 @@
+//! Optional encryption at rest for the SQLite databases in the dm home
+//! (`events.db`, `secrets.db`, run interaction stores).
+//!
+//! Enabled by `[encryption] enabled = true` in config.toml and only
+//! available in builds with the `sqlcipher` feature. The key is never
+//! written to the home: it comes from `DM_HOME_KEY` or, failing that, the
+//! OS keychain (service `dora-manager`, account = the dm home path), and is
+//! resolved once per home and process.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use anyhow::{bail, Context, Result};
+use rusqlite::Connection;
+
+use crate::config;
+
+/// Environment variable holding the home encryption key.
+pub const KEY_ENV: &str = "DM_HOME_KEY";
+/// Keychain service name the key is looked up under.
+pub const KEYCHAIN_SERVICE: &str = "dora-manager";
+
+/// Databases covered by `encrypt_home`, relative to the dm home.
+const HOME_DATABASES: &[&str] = &["events.db", "secrets.db"];
+
+/// Whether this build can open encrypted databases.
+pub fn supported() -> bool {
+    cfg!(feature = "sqlcipher")
+}
+
+/// Keys resolved so far, by home. Databases are opened per event, so this
+/// spares a config.toml parse and a keychain lookup each time.
+fn resolved_keys() -> &'static Mutex<HashMap<PathBuf, Option<String>>> {
+    static KEYS: OnceLock<Mutex<HashMap<PathBuf, Option<String>>>> = OnceLock::new();
+    KEYS.get_or_init(Default::default)
+}
+
+/// The key for an encrypted home, or `None` when encryption is off.
+pub fn home_key(home: &Path) -> Result<Option<String>> {
+    if let Some(key) = resolved_keys().lock().unwrap().get(home) {
+        return Ok(key.clone());
+    }
+    let key = resolve_home_key(home)?;
+    resolved_keys()
+        .lock()
+        .unwrap()
+        .insert(home.to_path_buf(), key.clone());
+    Ok(key)
+}
+
+fn resolve_home_key(home: &Path) -> Result<Option<String>> {
+    let cfg = config::load_config(home).unwrap_or_default();
+    if !cfg.encryption.enabled {
+        return Ok(None);
+    }
+    if !supported() {
+        bail!(
+            "The dm home is encrypted but this dm build lacks SQLCipher support. \
+             Rebuild with `--features sqlcipher`."
+        );
+    }
+    match resolve_key(home) {
+        Some(key) => Ok(Some(key)),
+        None => bail!(
+            "The dm home is encrypted but no key was found. Set {} or store the key \
+             in the keychain under service '{}'.",
+            KEY_ENV,
+            KEYCHAIN_SERVICE
+        ),
+    }
+}
+
+fn resolve_key(home: &Path) -> Option<String> {
+    std::env::var(KEY_ENV)
+        .ok()
+        .filter(|key| !key.is_empty())
+        .or_else(|| keychain_key(home))
+}
+
+#[cfg(target_os = "macos")]
+fn keychain_key(home: &Path) -> Option<String> {
+    let output = std::process::Command::new("security")
+        .args(["find-generic-password", "-s", KEYCHAIN_SERVICE, "-a"])
+        .arg(home)
+        .arg("-w")
+        .output()
+        .ok()?;
+    keychain_output(output)
+}
+
+#[cfg(target_os = "linux")]
+fn keychain_key(home: &Path) -> Option<String> {
+    let output = std::process::Command::new("secret-tool")
+        .args(["lookup", "service", KEYCHAIN_SERVICE, "home"])
+        .arg(home)
+        .output()
+        .ok()?;
+    keychain_output(output)
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn keychain_key(_home: &Path) -> Option<String> {
+    None
+}
+
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn keychain_output(output: std::process::Output) -> Option<String> {
+    if !output.status.success() {
+        return None;
+    }
+    let key = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!key.is_empty()).then_some(key)
+}
+
+/// Open a database in the dm home, keyed when the home is encrypted. The
+/// file is restricted to its owner.
+pub fn open_db(home: &Path, path: &Path) -> Result<Connection> {
+    let key = home_key(home)?;
+    let conn = Connection::open(path)
+        .with_context(|| format!("Failed to open database at {}", path.display()))?;
+    if path.exists() {
+        crate::permissions::restrict_file(path)
+            .with_context(|| format!("Failed to restrict permissions of {}", path.display()))?;
+    }
+    if let Some(key) = key {
+        apply_key(&conn, &key)
+            .with_context(|| format!("Failed to unlock {} (wrong key?)", path.display()))?;
+    }
+    Ok(conn)
+}
+
+fn apply_key(conn: &Connection, key: &str) -> Result<()> {
+    conn.pragma_update(None, "key", key)?;
+    // SQLCipher only checks the key on first access.
+    conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| {
+        row.get::<_, i64>(0)
+    })?;
+    Ok(())
+}
+
+/// Encrypt the existing plaintext databases of `home` with the resolved key
+/// and turn on `encryption.enabled`. Returns the databases converted.
+pub fn encrypt_home(home: &Path) -> Result<Vec<String>> {
+    let cfg = config::load_config(home)?;
+    if cfg.encryption.enabled {
+        bail!("The dm home is already encrypted");
+    }
+    // Its open connections would keep writing to the replaced files.
+    if let Some(server) = crate::lock::running_servers(home).first() {
+        bail!(
+            "dm-server (pid {}) is using this dm home; stop it before encrypting",
+            server.pid
+        );
+    }
+    if !supported() {
+        bail!("This dm build lacks SQLCipher support. Rebuild with `--features sqlcipher`.");
+    }
+    let Some(key) = resolve_key(home) else {
+        bail!(
+            "No encryption key found. Set {} or store it in the keychain under service '{}'.",
+            KEY_ENV,
+            KEYCHAIN_SERVICE
+        );
+    };
+
+    let _lock = crate::lock::HomeLock::acquire(home, "encryption", "dm encrypt")?;
+    let mut converted = Vec::new();
+    for name in HOME_DATABASES {
+        let path = home.join(name);
+        if !path.exists() {
+            continue;
+        }
+        encrypt_db(&path, &key)?;
+        converted.push(name.to_string());
+    }
+
+    config::modify_config(home, |cfg| {
+        cfg.encryption.enabled = true;
+        Ok(())
+    })?;
+    resolved_keys()
+        .lock()
+        .unwrap()
+        .insert(home.to_path_buf(), Some(key));
+    Ok(converted)
+}
+
+/// Rewrite `path` as an encrypted copy via `sqlcipher_export`, then swap it
+/// into place so an interrupted run leaves the original untouched.
+fn encrypt_db(path: &Path, key: &str) -> Result<()> {
+    let tmp = path.with_extension("db.encrypting");
+    let _ = std::fs::remove_file(&tmp);
+    {
+        let conn =
+            Connection::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")?;
+        conn.execute(
+            "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+            rusqlite::params![tmp.to_string_lossy(), key],
+        )?;
+        conn.query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))
+            .with_context(|| format!("Failed to encrypt {}", path.display()))?;
+        conn.execute_batch("DETACH DATABASE encrypted;")?;
+    }
+    for suffix in ["-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+    std::fs::rename(&tmp, path).with_context(|| {
+        format!(
+            "Failed to replace {} with its encrypted copy",
+            path.display()
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::env_lock;
+
+    #[test]
+    fn plaintext_home_opens_without_key() {
+        let tmp = tempfile::tempdir().unwrap();
+        let conn = open_db(tmp.path(), &tmp.path().join("plain.db")).unwrap();
+        conn.execute_batch("CREATE TABLE t (x INTEGER);").unwrap();
+        assert_eq!(home_key(tmp.path()).unwrap(), None);
+    }
+
+    #[test]
+    fn encrypted_home_requires_support_and_key() {
+        let _guard = env_lock();
+        std::env::remove_var(KEY_ENV);
+        let tmp = tempfile::tempdir().unwrap();
+        let mut cfg = config::load_config(tmp.path()).unwrap();
+        cfg.encryption.enabled = true;
+        config::save_config(tmp.path(), &cfg).unwrap();
+
+        let err = open_db(tmp.path(), &tmp.path().join("events.db"))
+            .unwrap_err()
+            .to_string();
+        if supported() {
+            assert!(err.contains(KEY_ENV), "{err}");
+        } else {
+            assert!(err.contains("--features sqlcipher"), "{err}");
+        }
+    }
+
+    #[test]
+    fn home_key_is_resolved_once_per_home() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert_eq!(home_key(tmp.path()).unwrap(), None);
+
+        let mut cfg = config::load_config(tmp.path()).unwrap();
+        cfg.encryption.enabled = true;
+        config::save_config(tmp.path(), &cfg).unwrap();
+        assert_eq!(home_key(tmp.path()).unwrap(), None);
+    }
+
+    #[test]
+    fn encrypt_home_refuses_while_a_server_is_running() {
+        let tmp = tempfile::tempdir().unwrap();
+        let server = crate::lock::HomeLock::acquire(
+            tmp.path(),
+            &crate::lock::server_resource(std::process::id()),
+            "dm-server",
+        )
+        .unwrap();
+
+        let err = encrypt_home(tmp.path()).unwrap_err().to_string();
+        assert!(err.contains("stop it before encrypting"), "{err}");
+        drop(server);
+        assert!(crate::lock::running_servers(tmp.path()).is_empty());
+    }
+
+    #[cfg(feature = "sqlcipher")]
+    #[test]
+    fn encrypt_home_converts_events_and_rejects_wrong_key() {
+        let _guard = env_lock();
+        let tmp = tempfile::tempdir().unwrap();
+        std::env::remove_var(KEY_ENV);
+        crate::events::EventStore::open(tmp.path())
+            .unwrap()
+            .emit(&crate::events::EventBuilder::new(crate::events::EventSource::Core, "x").build())
+            .unwrap();
+
+        std::env::set_var(KEY_ENV, "correct horse");
+        assert_eq!(encrypt_home(tmp.path()).unwrap(), vec!["events.db"]);
+        let store = crate::events::EventStore::open(tmp.path()).unwrap();
+        assert_eq!(store.count(&Default::default()).unwrap(), 1);
+
+        std::env::set_var(KEY_ENV, "wrong");
+        resolved_keys().lock().unwrap().clear();
+        assert!(crate::events::EventStore::open(tmp.path()).is_err());
+        std::env::remove_var(KEY_ENV);
+    }
+}
@@ -0,0 +1,215 @@
+//! Categorized failures.
+//!
+//! dm-core functions keep returning `anyhow::Result`; failures a caller may
+//! want to react to are raised as a [`DmError`] inside it. [`ErrorReport`]
+//! classifies any `anyhow::Error` by searching its chain, which is how
+//! dm-server picks the HTTP status and the `code` of its error bodies.
+
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::lock::ResourceBusy;
+
+#[derive(Debug)]
+pub enum DmError {
+    /// A named resource (`node`, `run`, `dataflow`, ...) does not exist.
+    NotFound {
+        resource: &'static str,
+        id: String,
+    },
+    /// The operation clashes with the current state, e.g. the name is taken.
+    Conflict(String),
+    /// Something dm needs is not installed (dora, a system package, ...).
+    DependencyMissing(String),
+    /// An external program or service (dora, git, GitHub) failed.
+    External(String),
+    Io(std::io::Error),
+}
+
+impl DmError {
+    pub fn not_found(resource: &'static str, id: impl Into<String>) -> Self {
+        Self::NotFound {
+            resource,
+            id: id.into(),
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Self::NotFound { .. } => ErrorCode::NotFound,
+            Self::Conflict(_) => ErrorCode::Conflict,
+            Self::DependencyMissing(_) => ErrorCode::DependencyMissing,
+            Self::External(_) => ErrorCode::External,
+            Self::Io(_) => ErrorCode::Io,
+        }
+    }
+
+    fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            Self::NotFound { resource, id } => {
+                Some(serde_json::json!({ "resource": resource, "id": id }))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for DmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound { resource, id } => {
+                let mut chars = resource.chars();
+                let first = chars.next().map(|c| c.to_ascii_uppercase());
+                write!(
+                    f,
+                    "{}{} '{}' not found",
+                    first.unwrap_or_default(),
+                    chars.as_str(),
+                    id
+                )
+            }
+            Self::Conflict(message)
+            | Self::DependencyMissing(message)
+            | Self::External(message) => f.write_str(message),
+            Self::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for DmError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for DmError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+/// Machine-readable category of a failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    NotFound,
+    Conflict,
+    DependencyMissing,
+    External,
+    Io,
+    /// The request itself was rejected (bad input).
+    Invalid,
+    /// Anything not categorized.
+    Internal,
+}
+
+/// A failure as reported to API clients.
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct ErrorReport {
+    pub code: ErrorCode,
+    /// The full message, context included.
+    pub error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl ErrorReport {
+    pub fn new(code: ErrorCode, error: impl Into<String>) -> Self {
+        Self {
+            code,
+            error: error.into(),
+            details: None,
+        }
+    }
+
+    /// Classify `e` by the first categorized error in its chain: a
+    /// [`DmError`], a [`ResourceBusy`] lock (conflict) or an I/O error
+    /// (always `io`, even when a file is missing).
+    pub fn from_error(e: &anyhow::Error) -> Self {
+        let (code, details) = classify(e).unwrap_or((ErrorCode::Internal, None));
+        Self {
+            code,
+            error: format!("{:#}", e),
+            details,
+        }
+    }
+}
+
+/// The category of `e`, if anything in its chain carries one.
+pub fn error_code(e: &anyhow::Error) -> Option<ErrorCode> {
+    classify(e).map(|(code, _)| code)
+}
+
+fn classify(e: &anyhow::Error) -> Option<(ErrorCode, Option<serde_json::Value>)> {
+    for cause in e.chain() {
+        if let Some(dm) = cause.downcast_ref::<DmError>() {
+            return Some((dm.code(), dm.details()));
+        }
+        if let Some(busy) = cause.downcast_ref::<ResourceBusy>() {
+            let details = serde_json::json!({
+                "resource": busy.resource,
+                "owner": busy.owner,
+            });
+            return Some((ErrorCode::Conflict, Some(details)));
+        }
+        // Only a missing node, run, dataflow, ... is NotFound; a file or
+        // program that is not there is a failure of dm itself.
+        if cause.is::<std::io::Error>() {
+            return Some((ErrorCode::Io, None));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+
+    use super::*;
+
+    #[test]
+    fn reports_classify_through_context() {
+        let e =
+            anyhow::Error::new(DmError::not_found("node", "camera")).context("Failed to open node");
+        let report = ErrorReport::from_error(&e);
+        assert_eq!(report.code, ErrorCode::NotFound);
+        assert_eq!(report.error, "Failed to open node: Node 'camera' not found");
+        assert_eq!(
+            report.details,
+            Some(serde_json::json!({ "resource": "node", "id": "camera" }))
+        );
+
+        let io: anyhow::Result<()> =
+            Err(std::io::Error::from(std::io::ErrorKind::PermissionDenied))
+                .context("Failed to write config");
+        assert_eq!(error_code(&io.unwrap_err()), Some(ErrorCode::Io));
+
+        let spawn: anyhow::Result<std::process::Child> =
+            std::process::Command::new("dm-test-no-such-program")
+                .spawn()
+                .context("Failed to run dm-test-no-such-program");
+        let spawn = spawn.unwrap_err();
+        assert_eq!(error_code(&spawn), Some(ErrorCode::Io));
+        assert_eq!(ErrorReport::from_error(&spawn).code, ErrorCode::Io);
+
+        let plain = anyhow::anyhow!("boom");
+        assert_eq!(error_code(&plain), None);
+        assert_eq!(ErrorReport::from_error(&plain).code, ErrorCode::Internal);
+
+        let json = serde_json::to_value(ErrorReport::new(
+            ErrorCode::DependencyMissing,
+            "No active dora version",
+        ))
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "code": "dependency_missing", "error": "No active dora version" })
+        );
+    }
+}
@@ -0,0 +1,243 @@
+//! Audit trail of mutating API calls: one `audit.<action>` event per call,
+//! recording who made it (client address and token name), what it changed
+//! and whether it succeeded.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use super::{Event, EventBuilder, EventFilter, EventLevel, EventSource, EventStore};
+
+/// Activity prefix of audit events, e.g. `audit.node.install`.
+pub const AUDIT_ACTIVITY_PREFIX: &str = "audit.";
+
+/// Entries returned by [`query_audit`] when the filter sets no limit.
+const DEFAULT_AUDIT_LIMIT: i64 = 100;
+/// Events scanned per query while filtering on attributes.
+const SCAN_BATCH: i64 = 500;
+
+/// One audited API call.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct AuditEntry {
+    /// Event id; 0 until recorded
+    #[serde(default)]
+    pub id: i64,
+    /// RFC 3339; set when recorded
+    #[serde(default)]
+    pub timestamp: String,
+    /// What was done, e.g. `node.install`
+    pub action: String,
+    /// What it was done to: a node id, dataflow name, dora version, ...
+    pub target: Option<String>,
+    /// Name of the `[[ingest.tokens]]` entry the client authenticated
+    /// with; `None` for anonymous clients
+    pub user: Option<String>,
+    pub client_ip: Option<String>,
+    pub method: String,
+    pub path: String,
+    /// HTTP status of the response
+    pub status: u16,
+}
+
+impl AuditEntry {
+    pub fn succeeded(&self) -> bool {
+        (200..400).contains(&self.status)
+    }
+
+    fn from_event(event: &Event) -> Option<Self> {
+        let action = event.activity.strip_prefix(AUDIT_ACTIVITY_PREFIX)?;
+        let attrs: serde_json::Value =
+            serde_json::from_str(event.attributes.as_deref().unwrap_or("{}")).ok()?;
+        let text = |key: &str| attrs[key].as_str().map(str::to_string);
+        Some(Self {
+            id: event.id,
+            timestamp: event.timestamp.clone(),
+            action: action.to_string(),
+            target: text("target"),
+            user: text("user"),
+            client_ip: text("client_ip"),
+            method: text("method").unwrap_or_default(),
+            path: text("path").unwrap_or_default(),
+            status: attrs["status"].as_u64().unwrap_or_default() as u16,
+        })
+    }
+}
+
+/// Which audit entries [`query_audit`] returns.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
+pub struct AuditFilter {
+    /// Action or action prefix, e.g. `node` or `dataflow.save`
+    pub action: Option<String>,
+    /// Token name; `anonymous` matches clients without one
+    pub user: Option<String>,
+    /// Exact target, e.g. a node id or dataflow name
+    pub target: Option<String>,
+    /// RFC 3339 timestamps bounding the entries
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// Newest entries to return (default 100)
+    pub limit: Option<i64>,
+}
+
+impl AuditFilter {
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        self.action.as_deref().is_none_or(|action| {
+            entry.action == action || entry.action.starts_with(&format!("{action}."))
+        }) && self
+            .user
+            .as_deref()
+            .is_none_or(|user| entry.user.as_deref().unwrap_or("anonymous") == user)
+    }
+}
+
+/// Store `entry` as an `audit.<action>` event; failed calls are recorded
+/// at warn level. The target is the event's case id.
+pub fn record_audit(store: &EventStore, entry: &AuditEntry) -> Result<i64> {
+    let level = if entry.succeeded() {
+        EventLevel::Info
+    } else {
+        EventLevel::Warn
+    };
+    let mut event = EventBuilder::new(
+        EventSource::Server,
+        format!("{AUDIT_ACTIVITY_PREFIX}{}", entry.action),
+    )
+    .level(level)
+    .message(format!(
+        "{} {} -> {}",
+        entry.method, entry.path, entry.status
+    ))
+    .attr("method", &entry.method)
+    .attr("path", &entry.path)
+    .attr("status", entry.status);
+    if let Some(target) = &entry.target {
+        event = event.case_id(target).attr("target", target);
+    }
+    if let Some(user) = &entry.user {
+        event = event.attr("user", user);
+    }
+    if let Some(ip) = &entry.client_ip {
+        event = event.attr("client_ip", ip);
+    }
+    store.emit(&event.build())
+}
+
+/// Audit entries matching `filter`, newest first.
+pub fn query_audit(store: &EventStore, filter: &AuditFilter) -> Result<Vec<AuditEntry>> {
+    let limit = filter.limit.unwrap_or(DEFAULT_AUDIT_LIMIT).max(0) as usize;
+    let base = EventFilter {
+        source: Some(EventSource::Server.to_string()),
+        case_id: filter.target.clone(),
+        activity: Some(match &filter.action {
+            Some(action) => format!("{AUDIT_ACTIVITY_PREFIX}{action}"),
+            None => AUDIT_ACTIVITY_PREFIX.to_string(),
+        }),
+        since: filter.since.clone(),
+        until: filter.until.clone(),
+        limit: Some(SCAN_BATCH),
+        ..Default::default()
+    };
+
+    let mut entries = Vec::new();
+    let mut before_id = None;
+    while entries.len() < limit {
+        let batch = store.query(&EventFilter {
+            before_id,
+            ..base.clone()
+        })?;
+        let Some(last) = batch.last() else {
+            break;
+        };
+        before_id = Some(last.id);
+        entries.extend(
+            batch
+                .iter()
+                .filter_map(AuditEntry::from_event)
+                .filter(|entry| filter.matches(entry)),
+        );
+        if (batch.len() as i64) < SCAN_BATCH {
+            break;
+        }
+    }
+    entries.truncate(limit);
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(action: &str, target: &str, user: Option<&str>, status: u16) -> AuditEntry {
+        AuditEntry {
+            action: action.to_string(),
+            target: Some(target.to_string()),
+            user: user.map(str::to_string),
+            client_ip: Some("127.0.0.1".to_string()),
+            method: "POST".to_string(),
+            path: format!("/api/{target}"),
+            status,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn audit_entries_round_trip_and_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = EventStore::open(dir.path()).unwrap();
+        record_audit(&store, &entry("node.install", "camera", Some("ci"), 200)).unwrap();
+        record_audit(&store, &entry("node.uninstall", "camera", None, 200)).unwrap();
+        record_audit(&store, &entry("dataflow.save", "demo", Some("ci"), 400)).unwrap();
+        // Other server events are not audit entries.
+        store
+            .emit(&EventBuilder::new(EventSource::Server, "http.request").build())
+            .unwrap();
+
+        let all = query_audit(&store, &AuditFilter::default()).unwrap();
+        let actions: Vec<_> = all.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(actions, ["dataflow.save", "node.uninstall", "node.install"]);
+        assert_eq!(all[2].user.as_deref(), Some("ci"));
+        assert_eq!(all[2].client_ip.as_deref(), Some("127.0.0.1"));
+        assert!(!all[0].succeeded());
+
+        let query = |filter: AuditFilter| -> Vec<String> {
+            query_audit(&store, &filter)
+                .unwrap()
+                .into_iter()
+                .map(|e| e.action)
+                .collect()
+        };
+        assert_eq!(
+            query(AuditFilter {
+                action: Some("node".into()),
+                ..Default::default()
+            }),
+            ["node.uninstall", "node.install"]
+        );
+        assert_eq!(
+            query(AuditFilter {
+                user: Some("anonymous".into()),
+                ..Default::default()
+            }),
+            ["node.uninstall"]
+        );
+        assert_eq!(
+            query(AuditFilter {
+                target: Some("demo".into()),
+                ..Default::default()
+            }),
+            ["dataflow.save"]
+        );
+        assert_eq!(
+            query(AuditFilter {
+                limit: Some(1),
+                ..Default::default()
+            }),
+            ["dataflow.save"]
+        );
+    }
+}
@@ -0,0 +1,216 @@
+//! Process-mining summaries over stored events, treating each `case_id` as
+//! a trace: a directly-follows graph (DFG) plus per-case duration
+//! statistics, enough for a process map without exporting to PM4Py.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+
+use super::{Event, EventFilter, EventStore};
+
+/// Events scanned when the filter sets no `limit`.
+const DEFAULT_MINING_LIMIT: i64 = 50_000;
+
+/// Directly-follows graph of the matching events.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DirectlyFollowsGraph {
+    /// Activities by occurrence count, most frequent first
+    pub activities: Vec<ActivityCount>,
+    /// `from` immediately followed by `to` within a case, most frequent first
+    pub edges: Vec<DfgEdge>,
+    /// How many cases start / end with each activity
+    pub start_activities: BTreeMap<String, usize>,
+    pub end_activities: BTreeMap<String, usize>,
+    pub cases: CaseDurationStats,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ActivityCount {
+    pub activity: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DfgEdge {
+    pub from: String,
+    pub to: String,
+    pub count: usize,
+    /// Mean time between the two events, in milliseconds
+    pub mean_ms: f64,
+}
+
+/// Case count and durations (first to last event) in milliseconds; the
+/// duration fields are 0 when there are no cases.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CaseDurationStats {
+    pub count: usize,
+    pub min_ms: i64,
+    pub max_ms: i64,
+    pub mean_ms: f64,
+    pub median_ms: i64,
+}
+
+/// Mine the events matching `filter` (newest `limit`, default 50 000).
+pub fn discover_dfg(store: &EventStore, filter: &EventFilter) -> Result<DirectlyFollowsGraph> {
+    let events = store.query(&EventFilter {
+        limit: Some(filter.limit.unwrap_or(DEFAULT_MINING_LIMIT)),
+        ..filter.clone()
+    })?;
+    Ok(directly_follows(&events))
+}
+
+/// An event with its parsed timestamp, if it has a valid one.
+type TimedEvent<'a> = (Option<DateTime<FixedOffset>>, &'a Event);
+
+/// Build the DFG for `events` in any order; each case is ordered by
+/// timestamp, then id.
+pub fn directly_follows(events: &[Event]) -> DirectlyFollowsGraph {
+    let mut traces: BTreeMap<&str, Vec<TimedEvent>> = BTreeMap::new();
+    for event in events {
+        let at = DateTime::parse_from_rfc3339(&event.timestamp).ok();
+        traces
+            .entry(event.case_id.as_str())
+            .or_default()
+            .push((at, event));
+    }
+
+    let mut graph = DirectlyFollowsGraph::default();
+    let mut activities: BTreeMap<&str, usize> = BTreeMap::new();
+    // (from, to) -> (count, summed gap, gaps measured)
+    let mut edges: BTreeMap<(&str, &str), (usize, i64, usize)> = BTreeMap::new();
+    let mut durations = Vec::new();
+    for trace in traces.values_mut() {
+        trace.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.id.cmp(&b.1.id)));
+        for (_, event) in trace.iter() {
+            *activities.entry(&event.activity).or_default() += 1;
+        }
+        for pair in trace.windows(2) {
+            let ((from_at, from), (to_at, to)) = (&pair[0], &pair[1]);
+            let edge = edges.entry((&from.activity, &to.activity)).or_default();
+            edge.0 += 1;
+            if let (Some(from_at), Some(to_at)) = (from_at, to_at) {
+                edge.1 += (*to_at - *from_at).num_milliseconds();
+                edge.2 += 1;
+            }
+        }
+        let (Some((first_at, first)), Some((last_at, last))) = (trace.first(), trace.last()) else {
+            continue;
+        };
+        *graph
+            .start_activities
+            .entry(first.activity.clone())
+            .or_default() += 1;
+        *graph
+            .end_activities
+            .entry(last.activity.clone())
+            .or_default() += 1;
+        if let (Some(first_at), Some(last_at)) = (first_at, last_at) {
+            durations.push((*last_at - *first_at).num_milliseconds());
+        }
+    }
+
+    graph.activities = activities
+        .into_iter()
+        .map(|(activity, count)| ActivityCount {
+            activity: activity.to_string(),
+            count,
+        })
+        .collect();
+    graph
+        .activities
+        .sort_by_key(|activity| std::cmp::Reverse(activity.count));
+    graph.edges = edges
+        .into_iter()
+        .map(|((from, to), (count, gap_ms, measured))| DfgEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            count,
+            mean_ms: if measured > 0 {
+                gap_ms as f64 / measured as f64
+            } else {
+                0.0
+            },
+        })
+        .collect();
+    graph
+        .edges
+        .sort_by_key(|edge| std::cmp::Reverse(edge.count));
+    graph.cases = case_stats(traces.len(), durations);
+    graph
+}
+
+fn case_stats(count: usize, mut durations: Vec<i64>) -> CaseDurationStats {
+    if durations.is_empty() {
+        return CaseDurationStats {
+            count,
+            ..Default::default()
+        };
+    }
+    durations.sort_unstable();
+    CaseDurationStats {
+        count,
+        min_ms: durations[0],
+        max_ms: durations[durations.len() - 1],
+        mean_ms: durations.iter().sum::<i64>() as f64 / durations.len() as f64,
+        median_ms: durations[durations.len() / 2],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{EventBuilder, EventSource};
+
+    fn event(id: i64, case_id: &str, activity: &str, second: u32) -> Event {
+        let mut event = EventBuilder::new(EventSource::Core, activity)
+            .case_id(case_id)
+            .build();
+        event.id = id;
+        event.timestamp = format!("2025-01-01T00:00:{:02}+00:00", second);
+        event
+    }
+
+    #[test]
+    fn directly_follows_counts_edges_and_case_durations() {
+        // Deliberately out of order: the store returns newest first.
+        let events = vec![
+            event(6, "b", "done", 9),
+            event(5, "b", "build", 5),
+            event(4, "b", "fetch", 4),
+            event(3, "a", "done", 4),
+            event(2, "a", "build", 2),
+            event(1, "a", "fetch", 0),
+        ];
+
+        let graph = directly_follows(&events);
+
+        assert_eq!(graph.cases.count, 2);
+        assert_eq!(graph.cases.min_ms, 4_000);
+        assert_eq!(graph.cases.max_ms, 5_000);
+        assert_eq!(graph.cases.mean_ms, 4_500.0);
+        assert_eq!(graph.start_activities.get("fetch"), Some(&2));
+        assert_eq!(graph.end_activities.get("done"), Some(&2));
+        assert_eq!(graph.activities.len(), 3);
+
+        let edge = |from: &str, to: &str| {
+            graph
+                .edges
+                .iter()
+                .find(|e| e.from == from && e.to == to)
+                .cloned()
+        };
+        let fetch_build = edge("fetch", "build").unwrap();
+        assert_eq!(fetch_build.count, 2);
+        assert_eq!(fetch_build.mean_ms, 1_500.0);
+        assert_eq!(edge("build", "done").unwrap().mean_ms, 3_000.0);
+        assert!(edge("done", "fetch").is_none());
+    }
+
+    #[test]
+    fn directly_follows_of_nothing_is_empty() {
+        let graph = directly_follows(&[]);
+        assert_eq!(graph, DirectlyFollowsGraph::default());
+    }
+}
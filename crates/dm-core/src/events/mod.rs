@@ -3,15 +3,23 @@
 //! All observability data (system logs, dataflow execution logs, HTTP request logs,
 //! frontend analytics, CI metrics) is stored as events in a single SQLite table.
 
+mod audit;
 mod builder;
 mod export;
+pub mod mining;
 mod model;
 mod op;
+mod otlp;
 mod store;
 
+pub use audit::{query_audit, record_audit, AuditEntry, AuditFilter, AUDIT_ACTIVITY_PREFIX};
 pub use builder::EventBuilder;
-pub use model::{Event, EventFilter, EventLevel, EventSource};
-pub use op::{try_emit, OperationEvent};
+pub use model::{
+    Event, EventBucket, EventFilter, EventGroupBy, EventLevel, EventPage, EventSource,
+    REPEAT_COUNT_ATTR,
+};
+pub use op::{in_flight_operations, try_emit, OperationEvent, OperationProgress};
+pub use otlp::flush_exports;
 pub use store::EventStore;
 
 #[cfg(test)]
@@ -189,4 +197,356 @@ mod tests {
         assert_eq!(attrs["file"], "src/main.rs");
         assert_eq!(attrs["line"], 42);
     }
+
+    fn bulk_events(count: usize) -> Vec<Event> {
+        (0..count)
+            .map(|i| {
+                EventBuilder::new(EventSource::Dataflow, "node.output")
+                    .case_id("run_bulk")
+                    .node_id("camera")
+                    .message(format!("frame {}", i))
+                    .build()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn emit_many_inserts_every_event_across_batches() {
+        let (_dir, store) = test_store();
+
+        let written = store.emit_many(&bulk_events(12_345)).unwrap();
+        assert_eq!(written, 12_345);
+
+        let filter = EventFilter {
+            case_id: Some("run_bulk".into()),
+            ..Default::default()
+        };
+        assert_eq!(store.count(&filter).unwrap(), 12_345);
+
+        let latest = store
+            .query(&EventFilter {
+                limit: Some(1),
+                ..filter
+            })
+            .unwrap();
+        assert_eq!(latest[0].message.as_deref(), Some("frame 12344"));
+    }
+
+    #[test]
+    fn query_page_walks_forward_from_after_id() {
+        let (_dir, store) = test_store();
+        store.emit_many(&bulk_events(5)).unwrap();
+        let first = store
+            .query(&EventFilter::default())
+            .unwrap()
+            .last()
+            .unwrap()
+            .id;
+
+        let page = store
+            .query_page(&EventFilter {
+                after_id: Some(first),
+                limit: Some(2),
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert_eq!(page.total, 5);
+        let messages: Vec<_> = page
+            .events
+            .iter()
+            .filter_map(|event| event.message.as_deref())
+            .collect();
+        assert_eq!(messages, vec!["frame 2", "frame 1"]);
+        assert_eq!(page.next_cursor, Some(first + 2));
+    }
+
+    #[test]
+    fn emit_many_with_no_events_is_noop() {
+        let (_dir, store) = test_store();
+        assert_eq!(store.emit_many(&[]).unwrap(), 0);
+        assert_eq!(store.count(&EventFilter::default()).unwrap(), 0);
+    }
+
+    #[test]
+    fn events_are_stamped_with_configured_identity() {
+        let dir = tempdir().unwrap();
+        let cfg = crate::config::DmConfig {
+            robot_id: Some("rover-7".into()),
+            labels: [("site".to_string(), "lab-a".to_string())].into(),
+            ..Default::default()
+        };
+        crate::config::save_config(dir.path(), &cfg).unwrap();
+        let store = EventStore::open(dir.path()).unwrap();
+
+        store
+            .emit(
+                &EventBuilder::new(EventSource::Core, "node.install")
+                    .case_id("s1")
+                    .attr("version", "0.4.1")
+                    .build(),
+            )
+            .unwrap();
+        store
+            .emit_many(&[EventBuilder::new(EventSource::Server, "ingest")
+                .case_id("s2")
+                .attr("robot_id", "rover-9")
+                .build()])
+            .unwrap();
+
+        let attrs = |case_id: &str| -> serde_json::Value {
+            let events = store
+                .query(&EventFilter {
+                    case_id: Some(case_id.into()),
+                    ..Default::default()
+                })
+                .unwrap();
+            serde_json::from_str(events[0].attributes.as_deref().unwrap()).unwrap()
+        };
+        let own = attrs("s1");
+        assert_eq!(own["robot_id"], "rover-7");
+        assert_eq!(own["labels"]["site"], "lab-a");
+        assert_eq!(own["version"], "0.4.1");
+        // Events from another robot keep their origin.
+        assert_eq!(attrs("s2")["robot_id"], "rover-9");
+    }
+
+    #[tokio::test]
+    async fn subscribers_receive_stored_events_with_ids() {
+        let (_dir, store) = test_store();
+        let mut rx = EventStore::subscribe();
+        let filter = EventFilter {
+            case_id: Some("live_case".into()),
+            ..Default::default()
+        };
+
+        let id = store
+            .emit(
+                &EventBuilder::new(EventSource::Core, "node.install")
+                    .case_id("live_case")
+                    .build(),
+            )
+            .unwrap();
+        store
+            .emit_many(&[EventBuilder::new(EventSource::Dataflow, "node.spawn")
+                .case_id("live_case")
+                .build()])
+            .unwrap();
+
+        // Other tests share the process-wide channel; skip their events.
+        let mut received = Vec::new();
+        while received.len() < 2 {
+            let event = rx.recv().await.unwrap();
+            if filter.matches(&event) {
+                received.push(event);
+            }
+        }
+        assert_eq!(received[0].id, id);
+        assert_eq!(received[0].activity, "node.install");
+        assert_eq!(received[1].activity, "node.spawn");
+        assert!(received[1].id > id);
+    }
+
+    #[test]
+    fn filter_matches_like_query() {
+        let event = EventBuilder::new(EventSource::Dataflow, "node.output")
+            .case_id("run_1")
+            .node_id("camera")
+            .message("frame ready")
+            .build();
+        let matches = |filter: EventFilter| filter.matches(&event);
+
+        assert!(matches(EventFilter::default()));
+        assert!(matches(EventFilter {
+            source: Some("dataflow".into()),
+            activity: Some("output".into()),
+            node_id: Some("camera".into()),
+            ..Default::default()
+        }));
+        assert!(matches(EventFilter {
+            search: Some("ready".into()),
+            ..Default::default()
+        }));
+        assert!(!matches(EventFilter {
+            case_id: Some("run_2".into()),
+            ..Default::default()
+        }));
+        assert!(!matches(EventFilter {
+            node_id: Some("detector".into()),
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn aggregate_counts_errors_per_group() {
+        let (_dir, store) = test_store();
+        let event = |activity: &str, level: EventLevel, timestamp: &str| {
+            let mut event = EventBuilder::new(EventSource::Core, activity)
+                .level(level)
+                .build();
+            event.timestamp = timestamp.to_string();
+            event
+        };
+        store
+            .emit_many(&[
+                event(
+                    "node.install",
+                    EventLevel::Info,
+                    "2025-01-01T10:05:00+00:00",
+                ),
+                event(
+                    "node.install",
+                    EventLevel::Error,
+                    "2025-01-01T10:40:00+00:00",
+                ),
+                event("runtime.up", EventLevel::Info, "2025-01-01T11:00:00+00:00"),
+                event("runtime.up", EventLevel::Info, "2025-01-01T12:30:00+02:00"),
+            ])
+            .unwrap();
+
+        let by_hour = store
+            .aggregate(&EventFilter::default(), &[EventGroupBy::Hour])
+            .unwrap();
+        let hours: Vec<_> = by_hour
+            .iter()
+            .map(|b| (b.hour.as_deref().unwrap(), b.count, b.errors))
+            .collect();
+        assert_eq!(
+            hours,
+            [
+                ("2025-01-01T10:00:00Z", 3, 1),
+                ("2025-01-01T11:00:00Z", 1, 0),
+            ]
+        );
+        assert!((by_hour[0].error_rate - 1.0 / 3.0).abs() < 1e-9);
+
+        let by_activity = store
+            .aggregate(
+                &EventFilter {
+                    activity: Some("install".into()),
+                    ..Default::default()
+                },
+                &EventGroupBy::parse_list("activity,level").unwrap(),
+            )
+            .unwrap();
+        assert_eq!(by_activity.len(), 2);
+        assert_eq!(by_activity[0].level.as_deref(), Some("error"));
+        assert!(by_activity[0].source.is_none());
+
+        let total = store.aggregate(&EventFilter::default(), &[]).unwrap();
+        assert_eq!(total[0].count, 4);
+        assert!(EventGroupBy::parse_list("hour,node").is_err());
+    }
+
+    #[test]
+    fn emit_collapses_repeated_errors_within_the_window() {
+        let (dir, store) = test_store();
+        let event = |source: EventSource, level: EventLevel, message: &str| {
+            EventBuilder::new(source, "node.poll")
+                .level(level)
+                .message(message)
+                .build()
+        };
+
+        let first = store
+            .emit(&event(EventSource::Server, EventLevel::Error, "timeout"))
+            .unwrap();
+        for _ in 0..2 {
+            let id = store
+                .emit(&event(EventSource::Server, EventLevel::Error, "timeout"))
+                .unwrap();
+            assert_eq!(id, first);
+        }
+        store
+            .emit(&event(EventSource::Server, EventLevel::Error, "refused"))
+            .unwrap();
+        store
+            .emit(&event(EventSource::Server, EventLevel::Info, "ok"))
+            .unwrap();
+        store
+            .emit(&event(EventSource::Server, EventLevel::Info, "ok"))
+            .unwrap();
+
+        let events = store.query(&EventFilter::default()).unwrap();
+        assert_eq!(events.len(), 4);
+        let collapsed = events.iter().find(|e| e.id == first).unwrap();
+        assert_eq!(collapsed.repeat_count(), 3);
+        assert!(collapsed
+            .attributes
+            .as_deref()
+            .unwrap()
+            .contains("last_seen"));
+        let stats = store
+            .aggregate(
+                &EventFilter {
+                    level: Some("error".into()),
+                    ..Default::default()
+                },
+                &[],
+            )
+            .unwrap();
+        assert_eq!(stats[0].count, 4);
+
+        std::fs::write(
+            crate::config::config_path(dir.path()),
+            "[event_dedupe]\nsources = { ci = 0 }\n",
+        )
+        .unwrap();
+        let store = EventStore::open(dir.path()).unwrap();
+        for _ in 0..2 {
+            store
+                .emit(&event(EventSource::Ci, EventLevel::Error, "flaky"))
+                .unwrap();
+        }
+        let ci = store
+            .count(&EventFilter {
+                source: Some("ci".into()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(ci, 2);
+    }
+
+    #[test]
+    fn in_flight_operations_keep_latest_progress_until_the_result() {
+        let (dir, store) = test_store();
+        let since = chrono::Utc::now() - chrono::Duration::minutes(1);
+
+        let running = OperationEvent::new(dir.path(), EventSource::Core, "version.install");
+        running.emit_start();
+        running.emit_progress("downloading", Some(10), "Downloading: 1 MB/10 MB");
+        running.emit_progress("downloading", Some(40), "Downloading: 4 MB/10 MB");
+        let done = OperationEvent::new(dir.path(), EventSource::Core, "node.install");
+        done.emit_start();
+        done.emit_progress("building", None, "Building");
+        done.emit_result(&Ok(()));
+
+        let ops = in_flight_operations(&store, &since.to_rfc3339()).unwrap();
+        assert_eq!(ops.len(), 1);
+        assert_eq!(ops[0].activity, "version.install");
+        assert_eq!(ops[0].phase, "downloading");
+        assert_eq!(ops[0].pct, Some(40));
+        assert_eq!(ops[0].message, "Downloading: 4 MB/10 MB");
+
+        running.emit_result::<()>(&Err(anyhow::anyhow!("checksum mismatch")));
+        assert!(in_flight_operations(&store, &since.to_rfc3339())
+            .unwrap()
+            .is_empty());
+    }
+
+    /// Throughput check for the bulk path. Timing-sensitive, so opt-in:
+    /// `cargo test --release -p dm-core emit_many_throughput -- --ignored`
+    #[test]
+    #[ignore = "benchmark; run in release mode with --ignored"]
+    fn emit_many_throughput_exceeds_50k_per_sec() {
+        let (_dir, store) = test_store();
+        let events = bulk_events(200_000);
+
+        let started = std::time::Instant::now();
+        store.emit_many(&events).unwrap();
+        let rate = events.len() as f64 / started.elapsed().as_secs_f64();
+
+        eprintln!("emit_many: {:.0} events/sec", rate);
+        assert!(rate >= 50_000.0, "only {:.0} events/sec", rate);
+    }
 }
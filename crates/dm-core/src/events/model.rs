@@ -79,6 +79,7 @@ impl std::str::FromStr for EventLevel {
 
 /// A single observability event (XES-compatible)
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct Event {
     pub id: i64,
     pub timestamp: String,
@@ -91,8 +92,29 @@ pub struct Event {
     pub attributes: Option<String>,
 }
 
+/// Attribute holding how many identical errors a collapsed event stands for.
+pub const REPEAT_COUNT_ATTR: &str = "count";
+
+impl Event {
+    /// How many occurrences this event stands for: its `count` attribute
+    /// when repeats were collapsed into it, otherwise 1.
+    pub fn repeat_count(&self) -> i64 {
+        self.attributes
+            .as_deref()
+            .and_then(|attrs| serde_json::from_str::<serde_json::Value>(attrs).ok())
+            .and_then(|attrs| attrs.get(REPEAT_COUNT_ATTR)?.as_i64())
+            .filter(|count| *count > 0)
+            .unwrap_or(1)
+    }
+}
+
 /// Filter for querying events
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(
+    feature = "openapi",
+    derive(utoipa::IntoParams),
+    into_params(parameter_in = Query)
+)]
 pub struct EventFilter {
     pub source: Option<String>,
     pub case_id: Option<String>,
@@ -104,4 +126,116 @@ pub struct EventFilter {
     pub limit: Option<i64>,
     pub offset: Option<i64>,
     pub search: Option<String>,
+    /// Keyset cursor: only events with a smaller id (older)
+    pub before_id: Option<i64>,
+    /// Keyset cursor: only events with a larger id (newer)
+    pub after_id: Option<i64>,
+}
+
+impl EventFilter {
+    /// Whether `event` passes this filter the way `EventStore::query` would
+    /// select it, except that substring matches are case-sensitive. Paging
+    /// fields (cursors, limit, offset) are ignored.
+    pub fn matches(&self, event: &Event) -> bool {
+        let eq = |want: &Option<String>, have: &str| want.as_deref().is_none_or(|w| w == have);
+        eq(&self.source, &event.source)
+            && eq(&self.case_id, &event.case_id)
+            && eq(&self.level, &event.level)
+            && self
+                .node_id
+                .as_deref()
+                .is_none_or(|node| event.node_id.as_deref() == Some(node))
+            && self
+                .activity
+                .as_deref()
+                .is_none_or(|activity| event.activity.contains(activity))
+            && self
+                .since
+                .as_deref()
+                .is_none_or(|since| event.timestamp.as_str() >= since)
+            && self
+                .until
+                .as_deref()
+                .is_none_or(|until| event.timestamp.as_str() <= until)
+            && self.search.as_deref().is_none_or(|search| {
+                event.activity.contains(search)
+                    || event.source.contains(search)
+                    || event
+                        .message
+                        .as_deref()
+                        .is_some_and(|message| message.contains(search))
+            })
+    }
+}
+
+/// One page of events with pagination metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventPage {
+    pub events: Vec<Event>,
+    /// Events matching the filter, ignoring cursor/limit/offset
+    pub total: i64,
+    /// Id to pass as `before_id` (or `after_id` when paging forward) to
+    /// fetch the next page; `None` once the end is reached
+    pub next_cursor: Option<i64>,
+}
+
+/// Dimension `EventStore::aggregate` groups events by
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EventGroupBy {
+    Source,
+    Activity,
+    Level,
+    /// UTC hour bucket, e.g. `2025-01-01T13:00:00Z`
+    Hour,
+}
+
+impl EventGroupBy {
+    /// Parse a comma-separated list such as `hour,source`.
+    pub fn parse_list(s: &str) -> Result<Vec<Self>> {
+        let mut dims = Vec::new();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let dim = part.parse()?;
+            if !dims.contains(&dim) {
+                dims.push(dim);
+            }
+        }
+        Ok(dims)
+    }
+}
+
+impl std::str::FromStr for EventGroupBy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "source" => Ok(Self::Source),
+            "activity" => Ok(Self::Activity),
+            "level" => Ok(Self::Level),
+            "hour" => Ok(Self::Hour),
+            _ => anyhow::bail!(
+                "Unknown group-by '{}' (expected source, activity, level or hour)",
+                s
+            ),
+        }
+    }
+}
+
+/// Event counts for one combination of the grouped dimensions; dimensions
+/// that were not grouped by are omitted.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct EventBucket {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hour: Option<String>,
+    pub count: i64,
+    /// Events with level `error`
+    pub errors: i64,
+    /// `errors / count`
+    pub error_rate: f64,
 }
@@ -4,7 +4,7 @@ use anyhow::Result;
 use serde::Serialize;
 use uuid::Uuid;
 
-use super::{Event, EventBuilder, EventLevel, EventSource, EventStore};
+use super::{Event, EventBuilder, EventFilter, EventLevel, EventSource, EventStore};
 
 /// Try to emit an event, silently ignoring failures.
 pub fn try_emit(home: &Path, event: Event) {
@@ -64,4 +64,89 @@ impl OperationEvent {
         };
         try_emit(&self.home, builder.build());
     }
+
+    /// Emit an intermediate `<activity>.progress` event for this operation.
+    ///
+    /// Progress events share the operation's case id and carry a `phase`
+    /// attribute plus an optional `pct` (0-100), so any consumer can render
+    /// in-flight work by querying `activity=progress`.
+    pub fn emit_progress(&self, phase: &str, pct: Option<u8>, message: impl Into<String>) {
+        let mut builder =
+            EventBuilder::new(self.source.clone(), format!("{}.progress", self.activity))
+                .case_id(self.case_id.clone())
+                .level(EventLevel::Info)
+                .message(message)
+                .attr("phase", phase);
+        if let Some(pct) = pct {
+            builder = builder.attr("pct", pct.min(100));
+        }
+        for (key, value) in &self.attrs {
+            builder = builder.attr(key, value.clone());
+        }
+        try_emit(&self.home, builder.build());
+    }
+}
+
+/// Latest progress of an operation that has not finished yet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OperationProgress {
+    pub case_id: String,
+    /// The operation's activity, without the `.progress` suffix.
+    pub activity: String,
+    pub phase: String,
+    pub pct: Option<u8>,
+    pub message: String,
+    pub timestamp: String,
+}
+
+/// Operations that reported progress since `since` (RFC 3339) and have not
+/// emitted their result yet, most recently updated first.
+pub fn in_flight_operations(store: &EventStore, since: &str) -> Result<Vec<OperationProgress>> {
+    let events = store.query(&EventFilter {
+        activity: Some(".progress".to_string()),
+        since: Some(since.to_string()),
+        limit: Some(1000),
+        ..Default::default()
+    })?;
+
+    let mut operations: Vec<OperationProgress> = Vec::new();
+    // Newest first, so the first event of a case is its latest progress.
+    for event in events {
+        let Some(activity) = event.activity.strip_suffix(".progress") else {
+            continue;
+        };
+        if operations.iter().any(|op| op.case_id == event.case_id) {
+            continue;
+        }
+        let attrs: serde_json::Value = event
+            .attributes
+            .as_deref()
+            .and_then(|attrs| serde_json::from_str(attrs).ok())
+            .unwrap_or_default();
+        operations.push(OperationProgress {
+            activity: activity.to_string(),
+            phase: attrs["phase"].as_str().unwrap_or_default().to_string(),
+            pct: attrs["pct"].as_u64().map(|pct| pct.min(100) as u8),
+            message: event.message.unwrap_or_default(),
+            timestamp: event.timestamp,
+            case_id: event.case_id,
+        });
+    }
+
+    // The result event is the last one an operation writes.
+    let mut in_flight = Vec::new();
+    for op in operations {
+        let last = store.query(&EventFilter {
+            case_id: Some(op.case_id.clone()),
+            limit: Some(1),
+            ..Default::default()
+        })?;
+        let finished = last.first().is_some_and(|event| {
+            event.activity == op.activity && event.message.as_deref() != Some("START")
+        });
+        if !finished {
+            in_flight.push(op);
+        }
+    }
+    Ok(in_flight)
 }
@@ -0,0 +1,322 @@
+//! Forwarding of stored events to an OpenTelemetry collector as OTLP/HTTP
+//! JSON log records, enabled by `otel_endpoint` in config.toml (unless
+//! `telemetry.enabled` is false).
+//!
+//! Export happens on one background thread per endpoint, so `emit` never
+//! waits on the network. Events that do not fit in the queue are dropped
+//! from the export (they are still stored locally). Short-lived processes
+//! call [`flush_exports`] before exiting so queued events are not lost.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use super::Event;
+
+/// Events waiting for export before new ones are dropped.
+const QUEUE_CAPACITY: usize = 4_096;
+/// Log records sent per request.
+const BATCH_SIZE: usize = 512;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+/// How long [`flush_exports`] waits for queued events to be sent.
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub(super) struct OtlpExporter {
+    tx: SyncSender<Event>,
+    pending: Mutex<usize>,
+    idle: Condvar,
+}
+
+impl OtlpExporter {
+    /// The process-wide exporter for `endpoint`, started on first use with
+    /// a client from `client`.
+    pub(super) fn for_endpoint(
+        endpoint: &str,
+        client: reqwest::blocking::ClientBuilder,
+    ) -> Arc<Self> {
+        let url = logs_url(endpoint);
+        let mut exporters = exporters().lock().unwrap_or_else(|e| e.into_inner());
+        exporters
+            .entry(url.clone())
+            .or_insert_with(|| {
+                let (tx, rx) = mpsc::sync_channel(QUEUE_CAPACITY);
+                let exporter = Arc::new(OtlpExporter {
+                    tx,
+                    pending: Mutex::new(0),
+                    idle: Condvar::new(),
+                });
+                let worker = Arc::clone(&exporter);
+                std::thread::spawn(move || worker.run(&url, client, rx));
+                exporter
+            })
+            .clone()
+    }
+
+    /// Queue a stored event (with its id) for export.
+    pub(super) fn export(&self, event: Event) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        if self.tx.try_send(event).is_ok() {
+            *pending += 1;
+        }
+    }
+
+    /// Wait until every queued event has been sent or `timeout` passes.
+    fn flush(&self, timeout: Duration) {
+        let pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = self
+            .idle
+            .wait_timeout_while(pending, timeout, |pending| *pending > 0);
+    }
+
+    fn run(&self, url: &str, client: reqwest::blocking::ClientBuilder, rx: Receiver<Event>) {
+        let client = client.timeout(REQUEST_TIMEOUT).build().unwrap_or_default();
+        let warned = AtomicBool::new(false);
+        while let Ok(first) = rx.recv() {
+            let mut batch = vec![first];
+            while batch.len() < BATCH_SIZE {
+                match rx.try_recv() {
+                    Ok(event) => batch.push(event),
+                    Err(_) => break,
+                }
+            }
+            let result = client
+                .post(url)
+                .json(&export_request(&batch))
+                .send()
+                .and_then(|resp| resp.error_for_status());
+            if let Err(e) = result {
+                if !warned.swap(true, Ordering::Relaxed) {
+                    eprintln!("warning: exporting events to {} failed: {}", url, e);
+                }
+            }
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            *pending = pending.saturating_sub(batch.len());
+            if *pending == 0 {
+                self.idle.notify_all();
+            }
+        }
+    }
+}
+
+/// Exporters started in this process, by logs URL.
+fn exporters() -> &'static Mutex<HashMap<String, Arc<OtlpExporter>>> {
+    static EXPORTERS: OnceLock<Mutex<HashMap<String, Arc<OtlpExporter>>>> = OnceLock::new();
+    EXPORTERS.get_or_init(Default::default)
+}
+
+/// Wait up to two seconds for events queued for OTLP export to be sent.
+/// Blocks; call it once when the process is about to exit.
+pub fn flush_exports() {
+    let exporters: Vec<_> = exporters()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .values()
+        .cloned()
+        .collect();
+    let deadline = Instant::now() + FLUSH_TIMEOUT;
+    for exporter in exporters {
+        exporter.flush(deadline.saturating_duration_since(Instant::now()));
+    }
+}
+
+/// OTLP/HTTP logs URL for a collector base address such as
+/// `http://localhost:4318`; a full `/v1/logs` URL is used as given.
+fn logs_url(endpoint: &str) -> String {
+    let endpoint = endpoint.trim().trim_end_matches('/');
+    if endpoint.ends_with("/v1/logs") {
+        endpoint.to_string()
+    } else {
+        format!("{}/v1/logs", endpoint)
+    }
+}
+
+fn export_request(events: &[Event]) -> Value {
+    json!({
+        "resourceLogs": [{
+            "resource": {
+                "attributes": [
+                    attribute("service.name", &json!("dora-manager")),
+                    attribute("service.version", &json!(env!("CARGO_PKG_VERSION"))),
+                ]
+            },
+            "scopeLogs": [{
+                "scope": { "name": "dm-core" },
+                "logRecords": events.iter().map(log_record).collect::<Vec<_>>(),
+            }]
+        }]
+    })
+}
+
+fn log_record(event: &Event) -> Value {
+    let time_unix_nano = chrono::DateTime::parse_from_rfc3339(&event.timestamp)
+        .ok()
+        .and_then(|ts| ts.timestamp_nanos_opt())
+        .unwrap_or_default();
+    let (severity_number, severity_text) = match event.level.as_str() {
+        "trace" => (1, "TRACE"),
+        "debug" => (5, "DEBUG"),
+        "warn" => (13, "WARN"),
+        "error" => (17, "ERROR"),
+        _ => (9, "INFO"),
+    };
+
+    let mut attributes = vec![
+        attribute("dm.event.id", &json!(event.id)),
+        attribute("dm.case_id", &json!(event.case_id)),
+        attribute("dm.activity", &json!(event.activity)),
+        attribute("dm.source", &json!(event.source)),
+    ];
+    if let Some(node_id) = &event.node_id {
+        attributes.push(attribute("dm.node_id", &json!(node_id)));
+    }
+    if let Some(Ok(Value::Object(extra))) = event
+        .attributes
+        .as_deref()
+        .map(serde_json::from_str::<Value>)
+    {
+        attributes.extend(extra.iter().map(|(key, value)| attribute(key, value)));
+    }
+
+    json!({
+        "timeUnixNano": time_unix_nano.to_string(),
+        "severityNumber": severity_number,
+        "severityText": severity_text,
+        "body": { "stringValue": event.message.as_deref().unwrap_or(&event.activity) },
+        "attributes": attributes,
+    })
+}
+
+fn attribute(key: &str, value: &Value) -> Value {
+    json!({ "key": key, "value": any_value(value) })
+}
+
+/// OTLP `AnyValue`; 64-bit integers are strings in the JSON encoding.
+fn any_value(value: &Value) -> Value {
+    match value {
+        Value::String(s) => json!({ "stringValue": s }),
+        Value::Bool(b) => json!({ "boolValue": b }),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => json!({ "intValue": i.to_string() }),
+            None => json!({ "doubleValue": n.as_f64() }),
+        },
+        Value::Null => json!({}),
+        Value::Array(items) => {
+            json!({ "arrayValue": { "values": items.iter().map(any_value).collect::<Vec<_>>() } })
+        }
+        Value::Object(_) => json!({ "stringValue": value.to_string() }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+    use crate::events::{EventBuilder, EventLevel, EventSource};
+
+    #[test]
+    fn logs_url_appends_the_logs_path_once() {
+        assert_eq!(
+            logs_url("http://localhost:4318"),
+            "http://localhost:4318/v1/logs"
+        );
+        assert_eq!(
+            logs_url("http://localhost:4318/v1/logs/"),
+            "http://localhost:4318/v1/logs"
+        );
+    }
+
+    #[test]
+    fn log_record_maps_event_fields() {
+        let mut event = EventBuilder::new(EventSource::Dataflow, "dora.log")
+            .case_id("run-1")
+            .node_id("camera")
+            .level(EventLevel::Warn)
+            .message("frame dropped")
+            .attr("fps", 12)
+            .build();
+        event.id = 7;
+
+        let record = log_record(&event);
+        assert_eq!(record["severityNumber"], 13);
+        assert_eq!(record["body"]["stringValue"], "frame dropped");
+        assert_ne!(record["timeUnixNano"], "0");
+        let attrs = record["attributes"].as_array().unwrap();
+        let value = |key: &str| {
+            attrs
+                .iter()
+                .find(|a| a["key"] == key)
+                .map(|a| a["value"].clone())
+                .unwrap()
+        };
+        assert_eq!(value("dm.event.id"), json!({ "intValue": "7" }));
+        assert_eq!(value("dm.node_id"), json!({ "stringValue": "camera" }));
+        assert_eq!(value("fps"), json!({ "intValue": "12" }));
+    }
+
+    #[test]
+    fn store_forwards_events_to_the_collector() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let collector = rt.block_on(MockServer::start());
+        rt.block_on(
+            Mock::given(method("POST"))
+                .and(path("/v1/logs"))
+                .respond_with(ResponseTemplate::new(200))
+                .mount(&collector),
+        );
+
+        let home = tempfile::tempdir().unwrap();
+        let config = crate::config::DmConfig {
+            otel_endpoint: Some(collector.uri()),
+            ..Default::default()
+        };
+        crate::config::save_config(home.path(), &config).unwrap();
+        let store = crate::events::EventStore::open(home.path()).unwrap();
+        store
+            .emit(
+                &EventBuilder::new(EventSource::Core, "runtime.up")
+                    .message("runtime started")
+                    .build(),
+            )
+            .unwrap();
+
+        // Export happens on a background thread.
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        let requests = loop {
+            let requests = rt.block_on(collector.received_requests()).unwrap();
+            if !requests.is_empty() || std::time::Instant::now() > deadline {
+                break requests;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        };
+        let body: Value = requests[0].body_json().unwrap();
+        let record = &body["resourceLogs"][0]["scopeLogs"][0]["logRecords"][0];
+        assert_eq!(record["body"]["stringValue"], "runtime started");
+    }
+
+    #[test]
+    fn dropping_a_store_does_not_wait_for_the_collector() {
+        // Accepts connections (via the backlog) but never answers them.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let home = tempfile::tempdir().unwrap();
+        let config = crate::config::DmConfig {
+            otel_endpoint: Some(format!("http://{}", listener.local_addr().unwrap())),
+            ..Default::default()
+        };
+        crate::config::save_config(home.path(), &config).unwrap();
+
+        let started = std::time::Instant::now();
+        for _ in 0..3 {
+            let store = crate::events::EventStore::open(home.path()).unwrap();
+            store
+                .emit(&EventBuilder::new(EventSource::Core, "runtime.up").build())
+                .unwrap();
+        }
+        assert!(started.elapsed() < FLUSH_TIMEOUT);
+    }
+}
@@ -1,14 +1,46 @@
+use std::borrow::Cow;
 use std::path::Path;
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
 
 use anyhow::{Context, Result};
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
+use tokio::sync::broadcast;
 
-use super::{export::render_xes, Event, EventFilter};
+use super::otlp::OtlpExporter;
+use super::{
+    export::render_xes, Event, EventBucket, EventFilter, EventGroupBy, EventPage, REPEAT_COUNT_ATTR,
+};
+use crate::config::{EventDedupeConfig, RobotIdentity};
+
+const INSERT_EVENT_SQL: &str =
+    "INSERT INTO events (timestamp, case_id, activity, source, level, node_id, message, attributes)
+     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)";
+
+/// Rows written per transaction by `emit_many`, so one huge batch does not
+/// hold the connection lock (and block readers) for too long.
+const EMIT_BATCH_SIZE: usize = 5_000;
+
+/// Occurrences a row stands for (see `Event::repeat_count`), in SQL.
+const REPEAT_COUNT_SQL: &str = "COALESCE(CASE WHEN json_valid(attributes) \
+     THEN json_extract(attributes, '$.count') END, 1)";
+
+/// Events buffered per live subscriber before it starts missing some.
+const LIVE_CHANNEL_CAPACITY: usize = 1_024;
+
+/// Every event stored by any `EventStore` in this process, as written
+/// (with its id and identity stamp). Events written by other processes,
+/// e.g. the `dm` CLI, only show up through `query`.
+fn live_channel() -> &'static broadcast::Sender<Event> {
+    static LIVE: OnceLock<broadcast::Sender<Event>> = OnceLock::new();
+    LIVE.get_or_init(|| broadcast::channel(LIVE_CHANNEL_CAPACITY).0)
+}
 
 /// Thread-safe SQLite-backed event store
 pub struct EventStore {
     conn: Mutex<Connection>,
+    identity: RwLock<RobotIdentity>,
+    otlp: Option<Arc<OtlpExporter>>,
+    dedupe: EventDedupeConfig,
 }
 
 impl EventStore {
@@ -16,10 +48,11 @@ impl EventStore {
     pub fn open(home: &Path) -> Result<Self> {
         std::fs::create_dir_all(home)?;
         let db_path = home.join("events.db");
-        let conn = Connection::open(&db_path)
+        let conn = crate::encryption::open_db(home, &db_path)
             .with_context(|| format!("Failed to open events.db at {}", db_path.display()))?;
 
-        conn.execute_batch("PRAGMA journal_mode=WAL;")?;
+        // NORMAL is durable enough under WAL and avoids an fsync per commit.
+        conn.execute_batch("PRAGMA journal_mode=WAL; PRAGMA synchronous=NORMAL;")?;
         conn.execute_batch(
             "CREATE TABLE IF NOT EXISTS events (
                 id          INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -38,83 +71,136 @@ impl EventStore {
             CREATE INDEX IF NOT EXISTS idx_events_activity ON events(activity);",
         )?;
 
+        let config = crate::config::load_config(home).unwrap_or_default();
+        let otlp = config
+            .otel_endpoint
+            .as_deref()
+            .filter(|endpoint| config.telemetry.enabled && !endpoint.trim().is_empty())
+            .map(|endpoint| {
+                let client =
+                    crate::http::blocking_client_builder(home, &config).unwrap_or_else(|e| {
+                        eprintln!("[dm] OTLP export uses default HTTP settings: {e:#}");
+                        reqwest::blocking::Client::builder()
+                    });
+                OtlpExporter::for_endpoint(endpoint, client)
+            });
         Ok(Self {
             conn: Mutex::new(conn),
+            identity: RwLock::new(config.identity()),
+            otlp,
+            dedupe: config.event_dedupe,
         })
     }
 
-    /// Insert a single event
+    /// Receive events as they are stored from now on, process-wide.
+    pub fn subscribe() -> broadcast::Receiver<Event> {
+        live_channel().subscribe()
+    }
+
+    /// Replace the identity stamped onto new events (after a config change).
+    pub fn set_identity(&self, identity: RobotIdentity) {
+        if let Ok(mut current) = self.identity.write() {
+            *current = identity;
+        }
+    }
+
+    fn identity(&self) -> RobotIdentity {
+        self.identity
+            .read()
+            .map(|identity| identity.clone())
+            .unwrap_or_default()
+    }
+
+    /// Insert a single event.
+    ///
+    /// An error that repeats one stored within its source's dedupe window
+    /// (`[event_dedupe]`) is collapsed into it instead: the earlier event's
+    /// `count` and `last_seen` attributes are updated and its id returned.
     pub fn emit(&self, event: &Event) -> Result<i64> {
         let conn = self
             .conn
             .lock()
             .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
-        conn.execute(
-            "INSERT INTO events (timestamp, case_id, activity, source, level, node_id, message, attributes)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            params![
-                event.timestamp,
-                event.case_id,
-                event.activity,
-                event.source,
-                event.level,
-                event.node_id,
-                event.message,
-                event.attributes,
-            ],
-        )?;
-        Ok(conn.last_insert_rowid())
+        let identity = self.identity();
+        let event = stamp_identity(event, &identity);
+        if let Some(id) = collapse_repeat(&conn, &event, &self.dedupe)? {
+            return Ok(id);
+        }
+        let mut stmt = conn.prepare_cached(INSERT_EVENT_SQL)?;
+        let id = stmt.insert(event_params(&event))?;
+        self.forward(id, &event);
+        publish(id, event);
+        Ok(id)
     }
 
-    /// Query events with optional filters
+    /// Insert many events with one prepared statement and one transaction
+    /// per `EMIT_BATCH_SIZE` rows. Returns the number of rows written.
+    /// Every event gets its own row; repeats are not collapsed.
+    ///
+    /// Use this for high-volume producers (e.g. mirroring dataflow logs);
+    /// a failing row rolls back its whole batch.
+    pub fn emit_many(&self, events: &[Event]) -> Result<usize> {
+        let mut conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let identity = self.identity();
+        let live = live_channel().receiver_count() > 0;
+        let keep = live || self.otlp.is_some();
+        for chunk in events.chunks(EMIT_BATCH_SIZE) {
+            let tx = conn.transaction()?;
+            let mut written = Vec::new();
+            {
+                let mut stmt = tx.prepare_cached(INSERT_EVENT_SQL)?;
+                for event in chunk {
+                    let event = stamp_identity(event, &identity);
+                    let id = stmt.insert(event_params(&event))?;
+                    if keep {
+                        written.push((id, event));
+                    }
+                }
+            }
+            tx.commit()?;
+            for (id, event) in written {
+                self.forward(id, &event);
+                publish(id, event);
+            }
+        }
+        Ok(events.len())
+    }
+
+    /// Hand a stored event to the OTLP exporter, if one is configured.
+    fn forward(&self, id: i64, event: &Event) {
+        if let Some(otlp) = &self.otlp {
+            let mut event = event.clone();
+            event.id = id;
+            otlp.export(event);
+        }
+    }
+
+    /// Query events with optional filters, newest first.
+    ///
+    /// `before_id` / `after_id` select a keyset page: with `after_id` alone
+    /// the `limit` events immediately following the cursor are returned
+    /// (still newest first), so polling forward never skips rows.
     pub fn query(&self, filter: &EventFilter) -> Result<Vec<Event>> {
         let conn = self
             .conn
             .lock()
             .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
 
-        let mut sql = String::from(
-            "SELECT id, timestamp, case_id, activity, source, level, node_id, message, attributes FROM events WHERE 1=1",
+        let (where_sql, mut param_values) = filter_clause(filter, true);
+        let forward = filter.after_id.is_some() && filter.before_id.is_none();
+        let mut sql = format!(
+            "SELECT id, timestamp, case_id, activity, source, level, node_id, message, attributes FROM events WHERE 1=1{}",
+            where_sql
         );
-        let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
 
-        if let Some(ref source) = filter.source {
-            sql.push_str(" AND source = ?");
-            param_values.push(Box::new(source.clone()));
-        }
-        if let Some(ref case_id) = filter.case_id {
-            sql.push_str(" AND case_id = ?");
-            param_values.push(Box::new(case_id.clone()));
-        }
-        if let Some(ref activity) = filter.activity {
-            sql.push_str(" AND activity LIKE ?");
-            param_values.push(Box::new(format!("%{}%", activity)));
-        }
-        if let Some(ref level) = filter.level {
-            sql.push_str(" AND level = ?");
-            param_values.push(Box::new(level.clone()));
-        }
-        if let Some(ref node_id) = filter.node_id {
-            sql.push_str(" AND node_id = ?");
-            param_values.push(Box::new(node_id.clone()));
-        }
-        if let Some(ref since) = filter.since {
-            sql.push_str(" AND timestamp >= ?");
-            param_values.push(Box::new(since.clone()));
-        }
-        if let Some(ref until) = filter.until {
-            sql.push_str(" AND timestamp <= ?");
-            param_values.push(Box::new(until.clone()));
-        }
-        if let Some(ref search) = filter.search {
-            sql.push_str(" AND (activity LIKE ? OR message LIKE ? OR source LIKE ?)");
-            let st = format!("%{}%", search);
-            param_values.push(Box::new(st.clone()));
-            param_values.push(Box::new(st.clone()));
-            param_values.push(Box::new(st));
-        }
-
-        sql.push_str(" ORDER BY id DESC");
+        sql.push_str(if forward {
+            " ORDER BY id ASC"
+        } else {
+            " ORDER BY id DESC"
+        });
 
         let limit = filter.limit.unwrap_or(500);
         sql.push_str(" LIMIT ?");
@@ -147,60 +233,126 @@ impl EventStore {
         for row in rows {
             events.push(row?);
         }
+        if forward {
+            events.reverse();
+        }
         Ok(events)
     }
 
-    /// Count events matching a filter
+    /// Query one page of events together with the total match count and
+    /// the cursor for the next page in the same direction.
+    pub fn query_page(&self, filter: &EventFilter) -> Result<EventPage> {
+        let events = self.query(filter)?;
+        let total = self.count(filter)?;
+        let limit = filter.limit.unwrap_or(500);
+        let forward = filter.after_id.is_some() && filter.before_id.is_none();
+        let next_cursor = if (events.len() as i64) < limit {
+            None
+        } else if forward {
+            events.first().map(|event| event.id)
+        } else {
+            events.last().map(|event| event.id)
+        };
+        Ok(EventPage {
+            events,
+            total,
+            next_cursor,
+        })
+    }
+
+    /// Count events matching a filter (cursor fields are ignored)
     pub fn count(&self, filter: &EventFilter) -> Result<i64> {
         let conn = self
             .conn
             .lock()
             .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
 
-        let mut sql = String::from("SELECT COUNT(*) FROM events WHERE 1=1");
-        let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+        let (where_sql, param_values) = filter_clause(filter, false);
+        let sql = format!("SELECT COUNT(*) FROM events WHERE 1=1{}", where_sql);
 
-        if let Some(ref source) = filter.source {
-            sql.push_str(" AND source = ?");
-            param_values.push(Box::new(source.clone()));
-        }
-        if let Some(ref case_id) = filter.case_id {
-            sql.push_str(" AND case_id = ?");
-            param_values.push(Box::new(case_id.clone()));
-        }
-        if let Some(ref activity) = filter.activity {
-            sql.push_str(" AND activity LIKE ?");
-            param_values.push(Box::new(format!("%{}%", activity)));
-        }
-        if let Some(ref level) = filter.level {
-            sql.push_str(" AND level = ?");
-            param_values.push(Box::new(level.clone()));
-        }
-        if let Some(ref node_id) = filter.node_id {
-            sql.push_str(" AND node_id = ?");
-            param_values.push(Box::new(node_id.clone()));
-        }
-        if let Some(ref since) = filter.since {
-            sql.push_str(" AND timestamp >= ?");
-            param_values.push(Box::new(since.clone()));
-        }
-        if let Some(ref until) = filter.until {
-            sql.push_str(" AND timestamp <= ?");
-            param_values.push(Box::new(until.clone()));
+        let params_refs: Vec<&dyn rusqlite::types::ToSql> =
+            param_values.iter().map(|p| p.as_ref()).collect();
+
+        let count: i64 = conn.query_row(&sql, params_refs.as_slice(), |row| row.get(0))?;
+        Ok(count)
+    }
+
+    /// Count matching events per combination of `group_by` dimensions,
+    /// with how many of them are errors. Buckets are ordered by the
+    /// grouped values, so hour buckets come out oldest first. With no
+    /// dimensions the result is a single bucket covering every match.
+    /// Collapsed repeats count once per occurrence.
+    pub fn aggregate(
+        &self,
+        filter: &EventFilter,
+        group_by: &[EventGroupBy],
+    ) -> Result<Vec<EventBucket>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+
+        let columns: Vec<&str> = group_by
+            .iter()
+            .map(|dim| match dim {
+                EventGroupBy::Source => "source",
+                EventGroupBy::Activity => "activity",
+                EventGroupBy::Level => "level",
+                EventGroupBy::Hour => "strftime('%Y-%m-%dT%H:00:00Z', timestamp)",
+            })
+            .collect();
+        let (where_sql, param_values) = filter_clause(filter, false);
+        let mut sql = format!(
+            "SELECT SUM({0}), SUM(CASE WHEN level = 'error' THEN {0} ELSE 0 END)",
+            REPEAT_COUNT_SQL
+        );
+        for column in &columns {
+            sql.push_str(", ");
+            sql.push_str(column);
         }
-        if let Some(ref search) = filter.search {
-            sql.push_str(" AND (activity LIKE ? OR message LIKE ? OR source LIKE ?)");
-            let st = format!("%{}%", search);
-            param_values.push(Box::new(st.clone()));
-            param_values.push(Box::new(st.clone()));
-            param_values.push(Box::new(st));
+        sql.push_str(&format!(" FROM events WHERE 1=1{}", where_sql));
+        if !columns.is_empty() {
+            let positions: Vec<String> = (3..3 + columns.len()).map(|i| i.to_string()).collect();
+            sql.push_str(&format!(" GROUP BY {0} ORDER BY {0}", positions.join(", ")));
         }
 
         let params_refs: Vec<&dyn rusqlite::types::ToSql> =
             param_values.iter().map(|p| p.as_ref()).collect();
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(params_refs.as_slice(), |row| {
+            let count: i64 = row.get::<_, Option<i64>>(0)?.unwrap_or(0);
+            let errors: i64 = row.get::<_, Option<i64>>(1)?.unwrap_or(0);
+            let mut bucket = EventBucket {
+                count,
+                errors,
+                error_rate: if count > 0 {
+                    errors as f64 / count as f64
+                } else {
+                    0.0
+                },
+                ..Default::default()
+            };
+            for (i, dim) in group_by.iter().enumerate() {
+                let value: Option<String> = row.get(i + 2)?;
+                match dim {
+                    EventGroupBy::Source => bucket.source = value,
+                    EventGroupBy::Activity => bucket.activity = value,
+                    EventGroupBy::Level => bucket.level = value,
+                    EventGroupBy::Hour => bucket.hour = value,
+                }
+            }
+            Ok(bucket)
+        })?;
 
-        let count: i64 = conn.query_row(&sql, params_refs.as_slice(), |row| row.get(0))?;
-        Ok(count)
+        let mut buckets = Vec::new();
+        for row in rows {
+            let bucket = row?;
+            // An ungrouped aggregate over no rows still returns one row.
+            if bucket.count > 0 || !group_by.is_empty() {
+                buckets.push(bucket);
+            }
+        }
+        Ok(buckets)
     }
 
     /// Export events as XES XML (PM4Py compatible)
@@ -218,4 +370,182 @@ impl EventStore {
         let deleted = conn.execute("DELETE FROM events WHERE case_id = ?1", params![case_id])?;
         Ok(deleted as u64)
     }
+
+    /// Delete the events of one activity within a case
+    pub fn delete_by_case_and_activity(&self, case_id: &str, activity: &str) -> Result<u64> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|e| anyhow::anyhow!("Lock poisoned: {}", e))?;
+        let deleted = conn.execute(
+            "DELETE FROM events WHERE case_id = ?1 AND activity = ?2",
+            params![case_id, activity],
+        )?;
+        Ok(deleted as u64)
+    }
+}
+
+/// Build the `AND ...` conditions for a filter. Cursor conditions are only
+/// included when `with_cursor` is set, so totals cover the whole result set.
+fn filter_clause(
+    filter: &EventFilter,
+    with_cursor: bool,
+) -> (String, Vec<Box<dyn rusqlite::types::ToSql>>) {
+    let mut sql = String::new();
+    let mut param_values: Vec<Box<dyn rusqlite::types::ToSql>> = Vec::new();
+
+    if let Some(ref source) = filter.source {
+        sql.push_str(" AND source = ?");
+        param_values.push(Box::new(source.clone()));
+    }
+    if let Some(ref case_id) = filter.case_id {
+        sql.push_str(" AND case_id = ?");
+        param_values.push(Box::new(case_id.clone()));
+    }
+    if let Some(ref activity) = filter.activity {
+        sql.push_str(" AND activity LIKE ?");
+        param_values.push(Box::new(format!("%{}%", activity)));
+    }
+    if let Some(ref level) = filter.level {
+        sql.push_str(" AND level = ?");
+        param_values.push(Box::new(level.clone()));
+    }
+    if let Some(ref node_id) = filter.node_id {
+        sql.push_str(" AND node_id = ?");
+        param_values.push(Box::new(node_id.clone()));
+    }
+    if let Some(ref since) = filter.since {
+        sql.push_str(" AND timestamp >= ?");
+        param_values.push(Box::new(since.clone()));
+    }
+    if let Some(ref until) = filter.until {
+        sql.push_str(" AND timestamp <= ?");
+        param_values.push(Box::new(until.clone()));
+    }
+    if let Some(ref search) = filter.search {
+        sql.push_str(" AND (activity LIKE ? OR message LIKE ? OR source LIKE ?)");
+        let st = format!("%{}%", search);
+        param_values.push(Box::new(st.clone()));
+        param_values.push(Box::new(st.clone()));
+        param_values.push(Box::new(st));
+    }
+    if with_cursor {
+        if let Some(before_id) = filter.before_id {
+            sql.push_str(" AND id < ?");
+            param_values.push(Box::new(before_id));
+        }
+        if let Some(after_id) = filter.after_id {
+            sql.push_str(" AND id > ?");
+            param_values.push(Box::new(after_id));
+        }
+    }
+
+    (sql, param_values)
+}
+
+/// If `event` is an error repeating one stored within its source's dedupe
+/// window, bump that event's `count` / `last_seen` and return its id.
+fn collapse_repeat(
+    conn: &Connection,
+    event: &Event,
+    dedupe: &EventDedupeConfig,
+) -> Result<Option<i64>> {
+    if event.level != "error" {
+        return Ok(None);
+    }
+    let Some(window) = dedupe.window(&event.source) else {
+        return Ok(None);
+    };
+    let at = chrono::DateTime::parse_from_rfc3339(&event.timestamp)
+        .map(|ts| ts.with_timezone(&chrono::Utc))
+        .unwrap_or_else(|_| chrono::Utc::now());
+    let cutoff = (at - chrono::Duration::from_std(window)?).to_rfc3339();
+    let previous = conn
+        .query_row(
+            "SELECT id, attributes FROM events
+             WHERE source = ?1 AND activity = ?2 AND level = 'error' AND message IS ?3
+               AND timestamp >= ?4
+             ORDER BY id DESC LIMIT 1",
+            params![event.source, event.activity, event.message, cutoff],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?)),
+        )
+        .optional()?;
+    let Some((id, attributes)) = previous else {
+        return Ok(None);
+    };
+    let mut attrs = match attributes
+        .as_deref()
+        .map(serde_json::from_str::<serde_json::Value>)
+    {
+        None => serde_json::Map::new(),
+        Some(Ok(serde_json::Value::Object(map))) => map,
+        // Nowhere to record the count; store the repeat as its own event.
+        Some(_) => return Ok(None),
+    };
+    let count = attrs
+        .get(REPEAT_COUNT_ATTR)
+        .and_then(|count| count.as_i64())
+        .unwrap_or(1);
+    attrs.insert(REPEAT_COUNT_ATTR.to_string(), (count + 1).into());
+    attrs.insert("last_seen".to_string(), event.timestamp.clone().into());
+    conn.execute(
+        "UPDATE events SET attributes = ?1 WHERE id = ?2",
+        params![serde_json::Value::Object(attrs).to_string(), id],
+    )?;
+    Ok(Some(id))
+}
+
+/// Send a stored event to live subscribers, if there are any.
+fn publish(id: i64, event: Cow<'_, Event>) {
+    let live = live_channel();
+    if live.receiver_count() == 0 {
+        return;
+    }
+    let mut event = event.into_owned();
+    event.id = id;
+    let _ = live.send(event);
+}
+
+/// Add `robot_id` / `labels` attributes unless the event already carries
+/// them (e.g. events ingested from another machine).
+fn stamp_identity<'a>(event: &'a Event, identity: &RobotIdentity) -> Cow<'a, Event> {
+    if identity.is_empty() {
+        return Cow::Borrowed(event);
+    }
+    let mut attrs = match event
+        .attributes
+        .as_deref()
+        .map(serde_json::from_str::<serde_json::Value>)
+    {
+        None => serde_json::Map::new(),
+        Some(Ok(serde_json::Value::Object(map))) => map,
+        // Leave attributes we cannot extend untouched.
+        Some(_) => return Cow::Borrowed(event),
+    };
+    if let Some(robot_id) = &identity.robot_id {
+        attrs
+            .entry("robot_id")
+            .or_insert_with(|| robot_id.clone().into());
+    }
+    if !identity.labels.is_empty() {
+        attrs
+            .entry("labels")
+            .or_insert_with(|| serde_json::json!(identity.labels));
+    }
+    let mut stamped = event.clone();
+    stamped.attributes = Some(serde_json::Value::Object(attrs).to_string());
+    Cow::Owned(stamped)
+}
+
+fn event_params(event: &Event) -> impl rusqlite::Params + '_ {
+    (
+        &event.timestamp,
+        &event.case_id,
+        &event.activity,
+        &event.source,
+        &event.level,
+        &event.node_id,
+        &event.message,
+        &event.attributes,
+    )
 }
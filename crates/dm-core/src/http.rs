@@ -0,0 +1,177 @@
+//! Construction of every outbound HTTP client, so proxy settings and an
+//! extra CA bundle apply to GitHub, registry, package index and webhook
+//! requests alike.
+//!
+//! Proxies come from `HTTP_PROXY`, `HTTPS_PROXY`, `ALL_PROXY` and
+//! `NO_PROXY` (either case) when any of them is set, and from the
+//! `[proxy]` section of config.toml otherwise. `ca_bundle` adds root
+//! certificates on top of the system ones.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use reqwest::{Certificate, NoProxy, Proxy};
+
+use crate::config::{self, DmConfig};
+
+pub const USER_AGENT: &str = "dm/0.1";
+
+const PROXY_ENV_VARS: &[&str] = &[
+    "HTTP_PROXY",
+    "http_proxy",
+    "HTTPS_PROXY",
+    "https_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+];
+
+/// Proxies and certificates shared by the async and blocking builders.
+struct Settings {
+    proxies: Vec<Proxy>,
+    certificates: Vec<Certificate>,
+}
+
+impl Settings {
+    fn load(home: &Path, cfg: &DmConfig) -> Result<Self> {
+        Ok(Self {
+            proxies: configured_proxies(cfg)?,
+            certificates: match config::ca_bundle(home) {
+                Some(path) => load_certificates(&path)?,
+                None => Vec::new(),
+            },
+        })
+    }
+}
+
+/// Proxies from `[proxy]`, or none when the environment configures them;
+/// reqwest then picks up the environment itself.
+fn configured_proxies(cfg: &DmConfig) -> Result<Vec<Proxy>> {
+    let env_configured = PROXY_ENV_VARS
+        .iter()
+        .any(|var| std::env::var(var).is_ok_and(|v| !v.trim().is_empty()));
+    if env_configured {
+        return Ok(Vec::new());
+    }
+    let no_proxy = cfg.proxy.no_proxy.as_deref().and_then(NoProxy::from_string);
+    let mut proxies = Vec::new();
+    if let Some(url) = non_empty(&cfg.proxy.http) {
+        proxies.push(
+            Proxy::http(url)
+                .with_context(|| format!("Invalid proxy.http '{}'", url))?
+                .no_proxy(no_proxy.clone()),
+        );
+    }
+    if let Some(url) = non_empty(&cfg.proxy.https) {
+        proxies.push(
+            Proxy::https(url)
+                .with_context(|| format!("Invalid proxy.https '{}'", url))?
+                .no_proxy(no_proxy),
+        );
+    }
+    Ok(proxies)
+}
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+fn load_certificates(path: &Path) -> Result<Vec<Certificate>> {
+    let pem = std::fs::read(path)
+        .with_context(|| format!("Failed to read CA bundle {}", path.display()))?;
+    let certificates = Certificate::from_pem_bundle(&pem)
+        .with_context(|| format!("Invalid CA bundle {}", path.display()))?;
+    if certificates.is_empty() {
+        anyhow::bail!("CA bundle {} contains no certificates", path.display());
+    }
+    Ok(certificates)
+}
+
+/// A client builder with dm's proxy, CA and user-agent settings applied;
+/// callers add their own timeouts.
+pub fn client_builder(home: &Path) -> Result<reqwest::ClientBuilder> {
+    let cfg = config::load_config(home).unwrap_or_default();
+    let settings = Settings::load(home, &cfg)?;
+    let mut builder = reqwest::Client::builder().user_agent(USER_AGENT);
+    for proxy in settings.proxies {
+        builder = builder.proxy(proxy);
+    }
+    for certificate in settings.certificates {
+        builder = builder.add_root_certificate(certificate);
+    }
+    Ok(builder)
+}
+
+/// A client with dm's proxy, CA and user-agent settings applied.
+pub fn client(home: &Path) -> Result<reqwest::Client> {
+    client_builder(home)?
+        .build()
+        .context("Failed to build HTTP client")
+}
+
+/// [`client_builder`] for the blocking client, from an already loaded config.
+pub(crate) fn blocking_client_builder(
+    home: &Path,
+    cfg: &DmConfig,
+) -> Result<reqwest::blocking::ClientBuilder> {
+    let settings = Settings::load(home, cfg)?;
+    let mut builder = reqwest::blocking::Client::builder().user_agent(USER_AGENT);
+    for proxy in settings.proxies {
+        builder = builder.proxy(proxy);
+    }
+    for certificate in settings.certificates {
+        builder = builder.add_root_certificate(certificate);
+    }
+    Ok(builder)
+}
+
+/// `url` with `https://github.com` swapped for the configured
+/// `github_mirror`; other URLs are returned unchanged.
+pub fn github_asset_url(home: &Path, url: &str) -> String {
+    mirrored_url(config::github_mirror(home).as_deref(), url)
+}
+
+/// `url` with `https://github.com` swapped for `mirror`, when given.
+pub fn mirrored_url(mirror: Option<&str>, url: &str) -> String {
+    match (mirror, url.strip_prefix("https://github.com/")) {
+        (Some(mirror), Some(rest)) => format!("{}/{}", mirror.trim_end_matches('/'), rest),
+        _ => url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn github_asset_url_uses_configured_mirror() {
+        let home = tempfile::tempdir().unwrap();
+        let url = "https://github.com/dora-rs/dora/releases/download/v0.3.9/dora.zip";
+        assert_eq!(github_asset_url(home.path(), url), url);
+
+        config::update_config(
+            home.path(),
+            &serde_json::json!({ "github_mirror": "https://mirror.example/gh/" }),
+        )
+        .unwrap();
+        assert_eq!(
+            github_asset_url(home.path(), url),
+            "https://mirror.example/gh/dora-rs/dora/releases/download/v0.3.9/dora.zip"
+        );
+        assert_eq!(
+            github_asset_url(home.path(), "https://example.com/a.zip"),
+            "https://example.com/a.zip"
+        );
+    }
+
+    #[test]
+    fn ca_bundle_is_resolved_against_home_and_must_hold_certificates() {
+        let home = tempfile::tempdir().unwrap();
+        config::update_config(home.path(), &serde_json::json!({ "ca_bundle": "corp.pem" }))
+            .unwrap();
+        let err = client(home.path()).unwrap_err();
+        assert!(format!("{err:#}").contains("corp.pem"), "{err:#}");
+
+        std::fs::write(home.path().join("corp.pem"), "not a certificate").unwrap();
+        assert!(client(home.path()).is_err());
+    }
+}
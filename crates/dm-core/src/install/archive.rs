@@ -1,73 +1,236 @@
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 
-pub(super) fn extract_tar(data: &[u8], target_dir: &Path) -> Result<()> {
-    use std::process::{Command, Stdio};
+use crate::util;
 
-    // tar is available on all Unix systems and Windows 10+ (bsdtar)
-    let tar_cmd = if cfg!(windows) { "tar.exe" } else { "tar" };
+/// Extract a `.tar`, `.tar.gz` or `.tar.xz`, dropping the top-level
+/// directory release archives wrap their files in.
+pub(crate) fn extract_tar(archive: &Path, target_dir: &Path, max_bytes: u64) -> Result<()> {
+    // Vet every member (path, type, running size) before anything
+    // touches the disk, stopping at the first one that breaks a rule.
+    let result = vet_tar(archive, max_bytes)
+        .and_then(|members| unpack_tar(archive, target_dir, members.strip_prefix.as_deref()));
+    if result.is_err() {
+        let _ = std::fs::remove_dir_all(target_dir);
+    }
+    result
+}
 
-    let mut child = Command::new(tar_cmd)
-        .args(["xzf", "-", "--strip-components=1", "-C"])
-        .arg(target_dir)
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()?;
+struct TarMembers {
+    /// The single top-level directory every member sits under, if any
+    strip_prefix: Option<PathBuf>,
+}
+
+fn vet_tar(archive: &Path, max_bytes: u64) -> Result<TarMembers> {
+    let mut tar = tar::Archive::new(tar_reader(archive)?);
+    let mut total = 0u64;
+    let mut top: Option<Option<PathBuf>> = None;
+    for entry in tar.entries().context("tar extraction failed")? {
+        let entry = entry.context("tar extraction failed")?;
+        let name = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+        let path = safe_entry_path(&name)?;
+        let header = entry.header();
+        let kind = header.entry_type();
+        // Links are refused outright, as in zips: a link that stays inside
+        // the archive can still redirect a later member through it.
+        if kind.is_symlink() || kind.is_hard_link() {
+            bail!("Refusing link '{}' in archive", name);
+        }
 
-    if let Some(mut stdin) = child.stdin.take() {
-        use std::io::Write;
-        stdin.write_all(data)?;
+        total = total.saturating_add(header.size().context("tar extraction failed")?);
+        if total > max_bytes {
+            bail!(
+                "Archive exceeds the {} extraction limit (max_extract_mb) at '{}'",
+                util::human_size(max_bytes),
+                name
+            );
+        }
+
+        if path.as_os_str().is_empty() {
+            continue;
+        }
+        let first = path
+            .components()
+            .next()
+            .map(|c| PathBuf::from(c.as_os_str()));
+        let is_dir = kind.is_dir();
+        let under_top = first.filter(|_| is_dir || path.components().count() > 1);
+        top = Some(match top {
+            None => under_top,
+            Some(current) => current.filter(|dir| under_top.as_ref() == Some(dir)),
+        });
     }
+    Ok(TarMembers {
+        strip_prefix: top.flatten(),
+    })
+}
 
-    let output = child.wait_with_output()?;
-    if !output.status.success() {
-        let mut child = Command::new(tar_cmd)
-            .args(["xzf", "-", "-C"])
-            .arg(target_dir)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()?;
-        if let Some(mut stdin) = child.stdin.take() {
-            use std::io::Write;
-            stdin.write_all(data)?;
+fn unpack_tar(archive: &Path, target_dir: &Path, strip_prefix: Option<&Path>) -> Result<()> {
+    let strip = |path: PathBuf| match strip_prefix {
+        Some(prefix) => path
+            .strip_prefix(prefix)
+            .map(Path::to_path_buf)
+            .unwrap_or(path),
+        None => path,
+    };
+    let mut tar = tar::Archive::new(tar_reader(archive)?);
+    for entry in tar.entries().context("tar extraction failed")? {
+        let mut entry = entry.context("tar extraction failed")?;
+        let name = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+        let path = strip(safe_entry_path(&name)?);
+        if path.as_os_str().is_empty() {
+            continue;
         }
-        let output2 = child.wait_with_output()?;
-        if !output2.status.success() {
-            let err = String::from_utf8_lossy(&output2.stderr);
-            anyhow::bail!("tar extraction failed: {}", err);
+        let out_path = target_dir.join(&path);
+        if let Some(parent) = out_path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
         }
+        entry
+            .unpack(&out_path)
+            .with_context(|| format!("Failed to extract {}", name))?;
     }
     Ok(())
 }
 
-pub(super) fn extract_zip(data: &[u8], target_dir: &Path) -> Result<()> {
-    use std::io::Cursor;
+/// The archive as a tar stream, decompressing gzip or xz by magic bytes.
+fn tar_reader(archive: &Path) -> Result<Box<dyn std::io::Read>> {
+    use std::io::{Read, Seek};
 
-    let reader = Cursor::new(data);
+    let mut file = std::fs::File::open(archive)
+        .with_context(|| format!("Failed to open {}", archive.display()))?;
+    let mut magic = Vec::with_capacity(6);
+    (&mut file).take(6).read_to_end(&mut magic)?;
+    file.rewind()?;
+    Ok(match magic.as_slice() {
+        [0x1f, 0x8b, ..] => Box::new(flate2::read::MultiGzDecoder::new(file)),
+        [0xfd, b'7', b'z', b'X', b'Z', 0x00] => Box::new(xz2::read::XzDecoder::new(file)),
+        _ => Box::new(file),
+    })
+}
+
+pub(super) fn extract_zip(archive: &Path, target_dir: &Path, max_bytes: u64) -> Result<()> {
+    use std::io::Read;
+
+    let reader = std::fs::File::open(archive)
+        .with_context(|| format!("Failed to open {}", archive.display()))?;
     let mut archive = zip::ZipArchive::new(reader)?;
-    archive.extract(target_dir)?;
+
+    // Vet every entry (and the declared total size) before writing any file.
+    let mut declared = 0u64;
+    for i in 0..archive.len() {
+        let file = archive.by_index_raw(i)?;
+        safe_entry_path(file.name())?;
+        if file.unix_mode().is_some_and(is_symlink_mode) {
+            bail!("Refusing symlink '{}' in archive", file.name());
+        }
+        declared = declared.saturating_add(file.size());
+    }
+    if declared > max_bytes {
+        bail!(
+            "Archive would extract to {} which exceeds the {} extraction limit (max_extract_mb)",
+            util::human_size(declared),
+            util::human_size(max_bytes)
+        );
+    }
+
+    let mut written = 0u64;
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let out_path = target_dir.join(safe_entry_path(file.name())?);
+        if file.is_dir() {
+            std::fs::create_dir_all(&out_path)?;
+            continue;
+        }
+        // Stop before an entry whose declared size crosses the limit.
+        if written.saturating_add(file.size()) > max_bytes {
+            let _ = std::fs::remove_dir_all(target_dir);
+            bail!(
+                "Archive exceeds the {} extraction limit (max_extract_mb) at '{}'",
+                util::human_size(max_bytes),
+                file.name()
+            );
+        }
+        if let Some(parent) = out_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut out = std::fs::File::create(&out_path)
+            .with_context(|| format!("Failed to create {}", out_path.display()))?;
+        // Sizes in the central directory can lie; cap the real bytes too.
+        let remaining = max_bytes.saturating_sub(written);
+        let copied = std::io::copy(&mut (&mut file).take(remaining + 1), &mut out)?;
+        written += copied;
+        if copied > remaining {
+            drop(out);
+            let _ = std::fs::remove_dir_all(target_dir);
+            bail!(
+                "Archive exceeds the {} extraction limit (max_extract_mb)",
+                util::human_size(max_bytes)
+            );
+        }
+        #[cfg(unix)]
+        if let Some(mode) = file.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&out_path, std::fs::Permissions::from_mode(mode & 0o777))?;
+        }
+    }
     Ok(())
 }
 
+/// Relative path of an archive member, rejecting absolute paths and `..`
+/// components (zip-slip). Backslashes count as separators so Windows-style
+/// names cannot sneak past.
+pub(super) fn safe_entry_path(name: &str) -> Result<PathBuf> {
+    let normalized = name.replace('\\', "/");
+    let mut path = PathBuf::new();
+    for component in Path::new(&normalized).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                bail!("Unsafe path '{}' in archive (path traversal)", name)
+            }
+        }
+    }
+    if normalized.len() >= 2
+        && normalized.as_bytes()[0].is_ascii_alphabetic()
+        && normalized.as_bytes()[1] == b':'
+    {
+        bail!("Unsafe path '{}' in archive (path traversal)", name);
+    }
+    Ok(path)
+}
+
+fn is_symlink_mode(mode: u32) -> bool {
+    mode & 0o170000 == 0o120000
+}
+
+/// Whether `name` is an archive format dm can extract.
+pub(super) fn is_supported_archive(name: &str) -> bool {
+    name.ends_with(".tar.gz") || name.ends_with(".tar.xz") || name.ends_with(".zip")
+}
+
 pub(super) fn find_dora_binary(dir: &Path) -> Option<PathBuf> {
+    find_binary(dir, &["dora", "dora.exe"])
+}
+
+/// First file under `dir` named one of `names`, skipping virtualenvs.
+pub(super) fn find_binary(dir: &Path, names: &[&str]) -> Option<PathBuf> {
     if let Ok(entries) = std::fs::read_dir(dir) {
         for entry in entries.flatten() {
             let path = entry.path();
             if path.is_file() {
                 let name = path.file_name().map(|n| n.to_string_lossy().into_owned());
-                match name.as_deref() {
-                    Some("dora") | Some("dora.exe") => return Some(path),
-                    _ => {}
+                if name.is_some_and(|name| names.contains(&name.as_str())) {
+                    return Some(path);
                 }
             }
             if path.is_dir() {
                 if path.file_name().map(|n| n == ".venv").unwrap_or(false) {
                     continue;
                 }
-                if let Some(found) = find_dora_binary(&path) {
+                if let Some(found) = find_binary(&path, names) {
                     return Some(found);
                 }
             }
@@ -1,28 +1,41 @@
 use std::path::Path;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use reqwest::Client;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::sync::mpsc;
 
 use crate::types::{InstallPhase, InstallProgress};
 use crate::util;
 
 use super::archive::{extract_tar, extract_zip, find_dora_binary};
-use super::github::GithubAsset;
-use super::progress::send_progress;
+use super::github::{local_path, GithubAsset};
+use super::manifest::finish_digest;
+use super::progress::{report_progress, send_progress};
+use super::verify::{Verified, VerifyPlan};
+use crate::events::OperationEvent;
 
-pub(super) async fn install_from_binary(
+/// Stream a release asset to `dest`, reporting byte progress. Returns the
+/// asset's `sha256:<hex>`, hashed as it arrives.
+pub(super) async fn download_asset(
     client: &Client,
     asset: &GithubAsset,
-    target_dir: &Path,
+    dest: &Path,
     verbose: bool,
     progress_tx: &Option<mpsc::UnboundedSender<InstallProgress>>,
-) -> Result<()> {
+    op: &OperationEvent,
+) -> Result<String> {
     if verbose {
-        eprintln!("[dm] Downloading asset: {}", asset.name);
+        eprintln!(
+            "[dm] Downloading asset: {} -> {}",
+            asset.name,
+            dest.display()
+        );
     }
 
-    send_progress(
+    report_progress(
+        op,
         progress_tx,
         InstallPhase::Downloading {
             bytes_done: 0,
@@ -35,53 +48,134 @@ pub(super) async fn install_from_binary(
         ),
     );
 
-    let resp = client
-        .get(&asset.browser_download_url)
-        .header("User-Agent", "dm/0.1")
-        .send()
-        .await?;
+    let mut source = match local_path(&asset.browser_download_url) {
+        Some(path) => AssetSource::Local(
+            tokio::fs::File::open(path)
+                .await
+                .with_context(|| format!("Failed to open {}", path.display()))?,
+        ),
+        None => {
+            let resp = client
+                .get(&asset.browser_download_url)
+                .header("User-Agent", "dm/0.1")
+                .send()
+                .await?;
+            if !resp.status().is_success() {
+                anyhow::bail!("Failed to download {} ({})", asset.name, resp.status());
+            }
+            AssetSource::Remote(resp)
+        }
+    };
 
-    let bytes = {
-        let mut buf = Vec::with_capacity(asset.size as usize);
-        let mut stream = resp.bytes_stream();
-        use futures_util::StreamExt;
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk?;
-            buf.extend_from_slice(&chunk);
-            send_progress(
-                progress_tx,
-                InstallPhase::Downloading {
-                    bytes_done: buf.len() as u64,
-                    bytes_total: asset.size,
-                },
-                &format!(
-                    "Downloading: {}/{}",
-                    util::human_size(buf.len() as u64),
-                    util::human_size(asset.size)
-                ),
-            );
+    let mut file = tokio::fs::File::create(dest)
+        .await
+        .with_context(|| format!("Failed to create {}", dest.display()))?;
+    let mut hasher = Sha256::new();
+    let mut bytes_done = 0u64;
+    let mut last_pct = Some(0);
+    while let Some(chunk) = source.next_chunk().await? {
+        file.write_all(&chunk)
+            .await
+            .with_context(|| format!("Failed to write {}", dest.display()))?;
+        hasher.update(&chunk);
+        bytes_done += chunk.len() as u64;
+        let phase = InstallPhase::Downloading {
+            bytes_done,
+            bytes_total: asset.size,
+        };
+        let message = format!(
+            "Downloading: {}/{}",
+            util::human_size(bytes_done),
+            util::human_size(asset.size)
+        );
+        // Only persist whole-percent steps; the channel still gets every chunk.
+        if phase.pct() != last_pct {
+            last_pct = phase.pct();
+            op.emit_progress(phase.name(), last_pct, &message);
+        }
+        send_progress(progress_tx, phase, &message);
+    }
+    file.flush().await?;
+    Ok(finish_digest(hasher))
+}
+
+/// Where an asset's bytes come from: GitHub (or a mirror), or a file on
+/// disk for `dm install --from-file`.
+enum AssetSource {
+    Remote(reqwest::Response),
+    Local(tokio::fs::File),
+}
+
+impl AssetSource {
+    async fn next_chunk(&mut self) -> Result<Option<Vec<u8>>> {
+        match self {
+            Self::Remote(resp) => Ok(resp.chunk().await?.map(|chunk| chunk.to_vec())),
+            Self::Local(file) => {
+                let mut buf = vec![0; 64 * 1024];
+                let n = file.read(&mut buf).await?;
+                buf.truncate(n);
+                Ok((n > 0).then_some(buf))
+            }
         }
-        buf
+    }
+}
+
+/// Check a downloaded asset against `plan`; `None` (`--no-verify`) only
+/// records its digest.
+pub(super) fn verify_asset(
+    plan: Option<&VerifyPlan>,
+    asset: &GithubAsset,
+    archive: &Path,
+    digest: String,
+    progress_tx: &Option<mpsc::UnboundedSender<InstallProgress>>,
+    op: &OperationEvent,
+) -> Result<Verified> {
+    let Some(plan) = plan else {
+        return Ok(Verified {
+            digest,
+            verified_by: Vec::new(),
+        });
     };
+    let message = if plan.is_empty() {
+        format!(
+            "No checksum published for {}; recording its digest only",
+            asset.name
+        )
+    } else {
+        format!("Verifying {}...", asset.name)
+    };
+    report_progress(op, progress_tx, InstallPhase::Verifying, &message);
+    plan.check(archive, digest, &asset.name)
+}
 
-    send_progress(
+/// Extract the downloaded `archive` into `target_dir`.
+pub(super) fn extract_asset(
+    asset: &GithubAsset,
+    archive: &Path,
+    target_dir: &Path,
+    max_extract_bytes: u64,
+    progress_tx: &Option<mpsc::UnboundedSender<InstallProgress>>,
+    op: &OperationEvent,
+) -> Result<()> {
+    report_progress(
+        op,
         progress_tx,
         InstallPhase::Extracting,
-        &format!("Extracting to {}...", target_dir.display()),
+        &format!("Extracting {} to {}...", asset.name, target_dir.display()),
     );
     std::fs::create_dir_all(target_dir)?;
 
     if asset.name.ends_with(".tar.gz") || asset.name.ends_with(".tar.xz") {
-        extract_tar(&bytes, target_dir)?;
+        extract_tar(archive, target_dir, max_extract_bytes)?;
     } else if asset.name.ends_with(".zip") {
-        extract_zip(&bytes, target_dir)?;
+        extract_zip(archive, target_dir, max_extract_bytes)?;
     }
+    Ok(())
+}
 
-    #[cfg(windows)]
-    let dora_bin = target_dir.join("dora.exe");
-    #[cfg(not(windows))]
-    let dora_bin = target_dir.join("dora");
-
+/// Put the extracted dora binary at the root of `target_dir`.
+pub(super) fn place_dora_binary(target_dir: &Path) -> Result<()> {
+    let dora_bin = crate::config::dora_bin_path(target_dir);
     if !dora_bin.exists() {
         if let Some(found_bin) = find_dora_binary(target_dir) {
             std::fs::rename(&found_bin, &dora_bin)?;
@@ -92,14 +186,18 @@ pub(super) async fn install_from_binary(
             );
         }
     }
+    make_executable(&dora_bin)
+}
 
+pub(super) fn make_executable(path: &Path) -> Result<()> {
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
-        let mut perms = std::fs::metadata(&dora_bin)?.permissions();
+        let mut perms = std::fs::metadata(path)?.permissions();
         perms.set_mode(0o755);
-        std::fs::set_permissions(&dora_bin, perms)?;
+        std::fs::set_permissions(path, perms)?;
     }
-
+    #[cfg(not(unix))]
+    let _ = path;
     Ok(())
 }
@@ -0,0 +1,109 @@
+//! Companion runtime binaries (`dora-coordinator`, `dora-daemon`,
+//! `dora-runtime`).
+//!
+//! Some releases ship these next to the CLI, either inside the CLI archive
+//! or as separate `dora-<component>-<platform>` assets. They are kept at
+//! the root of the version directory, where `dm up` and dedicated runtimes
+//! pick them up instead of the `dora` subcommands.
+
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::config::{self, COMPANION_BINARIES};
+
+use super::archive::{find_binary, is_supported_archive};
+use super::binary::make_executable;
+use super::github::{platform_asset_patterns, GithubAsset, GithubRelease};
+
+/// Move companion binaries found anywhere under `from` to the root of
+/// `version_dir`. Returns every companion now present there.
+pub(super) fn collect(from: &Path, version_dir: &Path) -> Result<Vec<String>> {
+    let mut present = Vec::new();
+    for name in COMPANION_BINARIES {
+        let dest = config::companion_bin_path(version_dir, name);
+        if !dest.exists() {
+            let file_name = dest.file_name().unwrap_or_default().to_string_lossy();
+            let Some(found) = find_binary(from, &[file_name.as_ref()]) else {
+                continue;
+            };
+            std::fs::rename(&found, &dest)?;
+        }
+        make_executable(&dest)?;
+        present.push(name.to_string());
+    }
+    Ok(present)
+}
+
+/// Separate release assets for this platform that carry companions not in
+/// `present`.
+pub(super) fn missing_assets<'a>(
+    release: &'a GithubRelease,
+    present: &[String],
+) -> Vec<&'a GithubAsset> {
+    let patterns = platform_asset_patterns();
+    COMPANION_BINARIES
+        .iter()
+        .filter(|name| !present.iter().any(|p| p == *name))
+        .filter_map(|name| {
+            let prefix = format!("{}-", name);
+            patterns.iter().find_map(|pattern| {
+                release.assets.iter().find(|a| {
+                    a.name.starts_with(&prefix)
+                        && a.name.contains(pattern)
+                        && is_supported_archive(&a.name)
+                })
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn asset(name: &str) -> GithubAsset {
+        GithubAsset {
+            name: name.to_string(),
+            browser_download_url: format!("https://example.invalid/{name}"),
+            size: 1,
+            digest: None,
+        }
+    }
+
+    #[test]
+    fn missing_assets_match_platform_companions_only() {
+        let platform = platform_asset_patterns()[0];
+        let release = GithubRelease {
+            tag_name: "v0.4.1".to_string(),
+            assets: vec![
+                asset(&format!("dora-cli-{platform}.tar.gz")),
+                asset(&format!("dora-coordinator-{platform}.tar.gz")),
+                asset(&format!("dora-daemon-{platform}.zip")),
+                asset(&format!("dora-daemon-{platform}.tar.gz.sha256")),
+                asset("dora-runtime-other-platform.tar.gz"),
+            ],
+        };
+
+        let names: Vec<_> = missing_assets(&release, &["dora-daemon".to_string()])
+            .into_iter()
+            .map(|a| a.name.clone())
+            .collect();
+        assert_eq!(names, vec![format!("dora-coordinator-{platform}.tar.gz")]);
+    }
+
+    #[test]
+    fn collect_moves_nested_companions_to_version_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        let version_dir = tmp.path().join("0.4.1");
+        let nested = version_dir.join("dora-0.4.1").join("bin");
+        std::fs::create_dir_all(&nested).unwrap();
+        let daemon = config::companion_bin_path(&nested, "dora-daemon");
+        std::fs::write(&daemon, "daemon").unwrap();
+
+        let present = collect(&version_dir, &version_dir).unwrap();
+        assert_eq!(present, vec!["dora-daemon"]);
+        assert!(config::companion_bin_path(&version_dir, "dora-daemon").exists());
+        assert!(!daemon.exists());
+    }
+}
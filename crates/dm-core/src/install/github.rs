@@ -1,23 +1,20 @@
-use std::env;
+use std::path::Path;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use reqwest::Client;
 use serde::Deserialize;
 
 /// Build common GitHub API headers, attaching an Authorization token
-/// when the `GITHUB_TOKEN` environment variable is set.
-fn github_headers(req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
-    let mut req = req
+/// when one is configured (see `config::github_token`).
+fn github_headers(req: reqwest::RequestBuilder, token: Option<&str>) -> reqwest::RequestBuilder {
+    let req = req
         .header("User-Agent", "dm/0.1")
         .header("Accept", "application/vnd.github+json");
 
-    if let Ok(token) = env::var("GITHUB_TOKEN") {
-        if !token.is_empty() {
-            req = req.header("Authorization", format!("Bearer {token}"));
-        }
+    match token {
+        Some(token) => req.header("Authorization", format!("Bearer {token}")),
+        None => req,
     }
-
-    req
 }
 
 #[derive(Debug, Deserialize)]
@@ -31,6 +28,50 @@ pub(super) struct GithubAsset {
     pub name: String,
     pub browser_download_url: String,
     pub size: u64,
+    /// `sha256:<hex>`, published by GitHub for newer release assets
+    #[serde(default)]
+    pub digest: Option<String>,
+}
+
+/// URL prefix of assets that [`local_release`] found on disk.
+const LOCAL_SCHEME: &str = "file://";
+
+/// A stand-in release for an archive on disk: the archive plus every file
+/// next to it, so `.sha256` sidecars, checksum lists, signatures and
+/// companion archives are picked up as they would be from GitHub.
+pub(super) fn local_release(tag: &str, archive: &Path) -> Result<(GithubRelease, String)> {
+    let archive = archive
+        .canonicalize()
+        .with_context(|| format!("Cannot read {}", archive.display()))?;
+    let name = archive
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let dir = archive.parent().unwrap_or(Path::new("."));
+    let mut assets = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let meta = entry.metadata()?;
+        if !meta.is_file() {
+            continue;
+        }
+        assets.push(GithubAsset {
+            name: entry.file_name().to_string_lossy().into_owned(),
+            browser_download_url: format!("{}{}", LOCAL_SCHEME, entry.path().display()),
+            size: meta.len(),
+            digest: None,
+        });
+    }
+    let release = GithubRelease {
+        tag_name: tag.to_string(),
+        assets,
+    };
+    Ok((release, name))
+}
+
+/// The file behind an asset from [`local_release`].
+pub(super) fn local_path(url: &str) -> Option<&Path> {
+    url.strip_prefix(LOCAL_SCHEME).map(Path::new)
 }
 
 pub(super) fn platform_asset_patterns() -> Vec<&'static str> {
@@ -71,10 +112,6 @@ pub(super) fn platform_asset_patterns() -> Vec<&'static str> {
     }
 }
 
-pub(super) async fn fetch_release(client: &Client, version: Option<&str>) -> Result<GithubRelease> {
-    fetch_release_from_base_url(client, "https://api.github.com", version).await
-}
-
 fn release_url(api_base: &str, version: Option<&str>) -> String {
     match version {
         Some(v) => {
@@ -89,21 +126,22 @@ fn release_url(api_base: &str, version: Option<&str>) -> String {
     }
 }
 
-async fn fetch_release_from_base_url(
+pub(super) async fn fetch_release(
     client: &Client,
     api_base: &str,
+    token: Option<&str>,
     version: Option<&str>,
 ) -> Result<GithubRelease> {
     let url = release_url(api_base, version);
 
-    let resp = github_headers(client.get(&url)).send().await?;
+    let resp = github_headers(client.get(&url), token).send().await?;
 
     if !resp.status().is_success() {
         let status = resp.status();
         let body = resp.text().await.unwrap_or_default();
         if status.as_u16() == 403 || status.as_u16() == 429 {
             anyhow::bail!(
-                "GitHub API error ({}): {}\n\n  Hint: You may have hit the API rate limit.\n  Set a GitHub personal access token to increase your limit:\n    export GITHUB_TOKEN=ghp_your_token_here\n\n  Or download the release manually from:\n    https://github.com/dora-rs/dora/releases",
+                "GitHub API error ({}): {}\n\n  Hint: You may have hit the API rate limit.\n  Set a GitHub personal access token to increase your limit:\n    export GITHUB_TOKEN=ghp_your_token_here\n  or save it with `dm config set github_token ghp_your_token_here`\n\n  Or download the release manually from:\n    https://github.com/dora-rs/dora/releases",
                 status, body
             );
         }
@@ -116,13 +154,11 @@ async fn fetch_release_from_base_url(
 
 #[cfg(test)]
 mod tests {
-    use std::io::{Read, Write};
-    use std::net::TcpListener;
-    use std::sync::mpsc;
-
     use reqwest::Client;
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
-    use super::{fetch_release_from_base_url, release_url};
+    use super::{fetch_release, release_url};
 
     #[test]
     fn release_url_defaults_to_latest_endpoint() {
@@ -147,66 +183,40 @@ mod tests {
 
     #[tokio::test]
     async fn fetch_release_uses_latest_endpoint_and_parses_response() {
-        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
-        let addr = listener.local_addr().unwrap();
-        let (tx, rx) = mpsc::channel();
-        let server = std::thread::spawn(move || {
-            let (mut stream, _) = listener.accept().unwrap();
-            let mut buf = [0_u8; 2048];
-            let len = stream.read(&mut buf).unwrap();
-            let request = String::from_utf8_lossy(&buf[..len]).into_owned();
-            tx.send(request).unwrap();
-            let body = r#"{"tag_name":"v0.9.0","assets":[{"name":"dora-cli.zip","browser_download_url":"https://example.invalid/dora-cli.zip","size":42}]}"#;
-            let header = format!(
-                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nContent-Type: application/json\r\nConnection: close\r\n\r\n",
-                body.len()
-            );
-            stream.write_all(header.as_bytes()).unwrap();
-            stream.write_all(body.as_bytes()).unwrap();
-        });
-
-        let base = format!("http://{addr}");
-        let release = fetch_release_from_base_url(&Client::new(), &base, None)
+        let server = MockServer::start().await;
+        Mock::given(path("/repos/dora-rs/dora/releases/latest"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                r#"{"tag_name":"v0.9.0","assets":[{"name":"dora-cli.zip","browser_download_url":"https://example.invalid/dora-cli.zip","size":42,"digest":"sha256:abc"}]}"#,
+                "application/json",
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let release = fetch_release(&Client::new(), &server.uri(), None, None)
             .await
             .unwrap();
-        server.join().unwrap();
 
-        let request = rx.recv().unwrap();
-        assert!(request.starts_with("GET /repos/dora-rs/dora/releases/latest "));
         assert_eq!(release.tag_name, "v0.9.0");
         assert_eq!(release.assets.len(), 1);
         assert_eq!(release.assets[0].size, 42);
+        assert_eq!(release.assets[0].digest.as_deref(), Some("sha256:abc"));
     }
 
     #[tokio::test]
     async fn fetch_release_normalizes_version_and_surfaces_api_errors() {
-        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
-        let addr = listener.local_addr().unwrap();
-        let (tx, rx) = mpsc::channel();
-        let server = std::thread::spawn(move || {
-            let (mut stream, _) = listener.accept().unwrap();
-            let mut buf = [0_u8; 2048];
-            let len = stream.read(&mut buf).unwrap();
-            let request = String::from_utf8_lossy(&buf[..len]).into_owned();
-            tx.send(request).unwrap();
-            let body = "not found";
-            let header = format!(
-                "HTTP/1.1 404 Not Found\r\nContent-Length: {}\r\nContent-Type: text/plain\r\nConnection: close\r\n\r\n",
-                body.len()
-            );
-            stream.write_all(header.as_bytes()).unwrap();
-            stream.write_all(body.as_bytes()).unwrap();
-        });
-
-        let base = format!("http://{addr}");
-        let err = fetch_release_from_base_url(&Client::new(), &base, Some("0.9.0"))
+        let server = MockServer::start().await;
+        Mock::given(path("/repos/dora-rs/dora/releases/tags/v0.9.0"))
+            .respond_with(ResponseTemplate::new(404).set_body_string("not found"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let err = fetch_release(&Client::new(), &server.uri(), None, Some("0.9.0"))
             .await
             .unwrap_err()
             .to_string();
-        server.join().unwrap();
 
-        let request = rx.recv().unwrap();
-        assert!(request.starts_with("GET /repos/dora-rs/dora/releases/tags/v0.9.0 "));
         assert!(err.contains("GitHub API error (404 Not Found): not found"));
     }
 }
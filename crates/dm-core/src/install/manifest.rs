@@ -0,0 +1,91 @@
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use sha2::{Digest, Sha256};
+
+use crate::types::InstallManifest;
+
+const MANIFEST_FILE: &str = "install.json";
+
+/// Read `install.json` from an installed version directory.
+pub fn read_manifest(version_dir: &Path) -> Option<InstallManifest> {
+    let content = std::fs::read_to_string(version_dir.join(MANIFEST_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+pub(super) fn write_manifest(version_dir: &Path, manifest: &InstallManifest) -> Result<()> {
+    let path = version_dir.join(MANIFEST_FILE);
+    let json = serde_json::to_string_pretty(manifest).context("Failed to serialize manifest")?;
+    std::fs::write(&path, json).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+pub(super) fn sha256_digest(data: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    finish_digest(hasher)
+}
+
+/// `sha256:<hex>` of everything fed to `hasher`.
+pub(super) fn finish_digest(hasher: Sha256) -> String {
+    format!("sha256:{:x}", hasher.finalize())
+}
+
+/// Check an asset's `sha256:<hex>` against a GitHub asset digest. Digests in
+/// other algorithms cannot be checked here and are reported as unverified.
+pub(super) fn verify_digest(actual: &str, expected: &str) -> Result<Option<String>> {
+    let Some(hex) = expected.strip_prefix("sha256:") else {
+        return Ok(None);
+    };
+    if !actual[7..].eq_ignore_ascii_case(hex.trim()) {
+        bail!(
+            "Downloaded asset does not match its published digest (expected {}, got {})",
+            expected,
+            actual
+        );
+    }
+    Ok(Some(actual.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::InstallMethod;
+
+    #[test]
+    fn verify_digest_accepts_match_and_rejects_mismatch() {
+        let digest = sha256_digest(b"dora");
+        assert_eq!(
+            verify_digest(&digest, &digest.to_uppercase().replace("SHA256", "sha256")).unwrap(),
+            Some(digest.clone())
+        );
+        let err = verify_digest(&sha256_digest(b"tampered"), &digest)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("does not match"));
+        assert_eq!(verify_digest(&digest, "sha512:abc").unwrap(), None);
+    }
+
+    #[test]
+    fn manifest_roundtrips_through_install_json() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(read_manifest(dir.path()).is_none());
+
+        let manifest = InstallManifest {
+            version: "0.3.9".into(),
+            method: InstallMethod::Binary,
+            source_url: "https://example.invalid/dora-cli.zip".into(),
+            asset_name: Some("dora-cli.zip".into()),
+            asset_digest: Some(sha256_digest(b"dora")),
+            verified_by: vec!["github-digest".into()],
+            companions: vec!["dora-daemon".into()],
+            installed_at: "2026-01-01T00:00:00Z".into(),
+        };
+        write_manifest(dir.path(), &manifest).unwrap();
+
+        let read = read_manifest(dir.path()).unwrap();
+        assert_eq!(read.version, "0.3.9");
+        assert_eq!(read.asset_digest, manifest.asset_digest);
+        assert_eq!(read.companions, vec!["dora-daemon"]);
+    }
+}
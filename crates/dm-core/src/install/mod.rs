@@ -1,18 +1,26 @@
-mod archive;
+pub(crate) mod archive;
 mod binary;
+mod companion;
 mod github;
+mod manifest;
 mod progress;
+mod smoke;
 mod source;
+mod verify;
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use reqwest::Client;
 use tokio::sync::mpsc;
 
-use crate::config;
+use crate::config::{self, InstallMethodPreference};
+use crate::events::{EventSource, OperationEvent};
+use crate::lock::HomeLock;
 use crate::types::*;
 
+pub use manifest::read_manifest;
+
 /// Install a dora version.
 /// Progress updates are sent through the optional `progress_tx` channel.
 pub async fn install(
@@ -21,63 +29,393 @@ pub async fn install(
     verbose: bool,
     progress_tx: Option<mpsc::UnboundedSender<InstallProgress>>,
 ) -> Result<InstallResult> {
-    let client = Client::new();
+    install_with(home, version, None, None, verbose, progress_tx).await
+}
+
+/// Knobs for [`install_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct InstallOptions {
+    /// Overrides `install.method` from config.toml
+    pub method: Option<InstallMethodPreference>,
+    /// Overrides `install.verify` from config.toml
+    pub verify: Option<bool>,
+    /// Overrides `github_mirror` for the release downloads
+    pub mirror: Option<String>,
+    /// Install this pre-downloaded release archive instead of downloading
+    /// one; checksum files and signatures next to it are used for
+    /// verification
+    pub from_file: Option<PathBuf>,
+}
+
+/// Like [`install`], with an explicit method and checksum policy overriding
+/// `install.method` and `install.verify` from config.toml.
+pub async fn install_with(
+    home: &Path,
+    version: Option<String>,
+    method: Option<InstallMethodPreference>,
+    verify: Option<bool>,
+    verbose: bool,
+    progress_tx: Option<mpsc::UnboundedSender<InstallProgress>>,
+) -> Result<InstallResult> {
+    let options = InstallOptions {
+        method,
+        verify,
+        ..Default::default()
+    };
+    install_with_options(home, version, options, verbose, progress_tx).await
+}
+
+/// Install a dora version with `options`, reporting progress like
+/// [`install`].
+pub async fn install_with_options(
+    home: &Path,
+    version: Option<String>,
+    options: InstallOptions,
+    verbose: bool,
+    progress_tx: Option<mpsc::UnboundedSender<InstallProgress>>,
+) -> Result<InstallResult> {
+    let mut install_cfg = config::load_config(home)?.install;
+    if let Some(method) = options.method {
+        install_cfg.method = method;
+    }
+    if let Some(verify) = options.verify {
+        install_cfg.verify = verify;
+    }
+    let mut op = OperationEvent::new(home, EventSource::Core, "version.install")
+        .attr("version", version.as_deref().unwrap_or("latest"))
+        .attr("method", install_cfg.method)
+        .attr("verify", install_cfg.verify);
+    if let Some(archive) = &options.from_file {
+        op = op.attr("from_file", archive.display().to_string());
+    }
+    op.emit_start();
+
+    let result = async {
+        let _lock = HomeLock::acquire_async(home, "install", "dora install").await?;
+        let ctx = InstallContext {
+            home,
+            install_cfg: &install_cfg,
+            verbose,
+            progress_tx: &progress_tx,
+            op: &op,
+        };
+        match &options.from_file {
+            Some(archive) => install_local(&ctx, archive, version.as_deref()).await,
+            None => {
+                let version = crate::api::resolve_release_version(home, version.as_deref()).await?;
+                let mirror = options.mirror.or_else(|| config::github_mirror(home));
+                install_inner(&ctx, version, mirror.as_deref()).await
+            }
+        }
+    }
+    .await;
+
+    op.emit_result(&result);
+    result
+}
+
+/// What every step of one install shares.
+struct InstallContext<'a> {
+    home: &'a Path,
+    install_cfg: &'a config::InstallConfig,
+    verbose: bool,
+    progress_tx: &'a Option<mpsc::UnboundedSender<InstallProgress>>,
+    op: &'a OperationEvent,
+}
+
+async fn install_inner(
+    ctx: &InstallContext<'_>,
+    version: Option<String>,
+    mirror: Option<&str>,
+) -> Result<InstallResult> {
+    let InstallContext {
+        home,
+        install_cfg,
+        verbose,
+        progress_tx,
+        op,
+    } = *ctx;
+    let preference = install_cfg.method;
+    let client = crate::http::client(home)?;
     let ver_str = version.as_deref();
 
-    progress::send_progress(
-        &progress_tx,
+    progress::report_progress(
+        op,
+        progress_tx,
         InstallPhase::Fetching,
         "Fetching release info...",
     );
 
-    let release = github::fetch_release(&client, ver_str).await?;
+    let mut release = github::fetch_release(
+        &client,
+        &config::github_api_url(home),
+        config::github_token(home).as_deref(),
+        ver_str,
+    )
+    .await?;
+    for asset in &mut release.assets {
+        asset.browser_download_url = crate::http::mirrored_url(mirror, &asset.browser_download_url);
+    }
     let tag = release.tag_name.trim_start_matches('v').to_string();
 
     let target_dir = config::versions_dir(home).join(&tag);
     if config::dora_bin_path(&target_dir).exists() {
-        return Ok(InstallResult {
-            version: tag,
-            method: InstallMethod::Binary,
-            set_active: false,
-        });
+        return Ok(already_installed(&target_dir, tag).await);
     }
 
     let patterns = github::platform_asset_patterns();
-    let asset = patterns.iter().find_map(|pattern| {
-        release.assets.iter().find(|a| {
-            a.name.contains(pattern)
-                && a.name.contains("dora-cli")
-                && (a.name.ends_with(".tar.gz")
-                    || a.name.ends_with(".tar.xz")
-                    || a.name.ends_with(".zip"))
+    let asset = if preference == InstallMethodPreference::Source {
+        None
+    } else {
+        patterns.iter().find_map(|pattern| {
+            release.assets.iter().find(|a| {
+                a.name.contains(pattern)
+                    && a.name.contains("dora-cli")
+                    && archive::is_supported_archive(&a.name)
+            })
         })
-    });
+    };
+    if asset.is_none() && preference == InstallMethodPreference::Binary {
+        anyhow::bail!(
+            "dora {} has no release binary for this platform and install method is `binary`. \
+             Use `--method source` (or `auto`) to build it from source.",
+            tag
+        );
+    }
 
-    let method = match asset {
+    let (method, source_url, asset_name, asset_digest, verified_by, companions) = match asset {
         Some(asset) => {
-            binary::install_from_binary(&client, asset, &target_dir, verbose, &progress_tx).await?;
-            InstallMethod::Binary
+            let fetch = AssetFetch {
+                client: &client,
+                ctx,
+            };
+            let verified = fetch.unpack(&release, asset, &target_dir).await?;
+            binary::place_dora_binary(&target_dir)?;
+            let mut companions = companion::collect(&target_dir, &target_dir)?;
+            for extra in companion::missing_assets(&release, &companions) {
+                let staging = target_dir.join(format!(".{}", extra.name));
+                let found = match fetch.unpack(&release, extra, &staging).await {
+                    Ok(_) => companion::collect(&staging, &target_dir),
+                    Err(err) => Err(err),
+                };
+                let _ = std::fs::remove_dir_all(&staging);
+                companions = found?;
+            }
+            (
+                InstallMethod::Binary,
+                asset.browser_download_url.clone(),
+                Some(asset.name.clone()),
+                Some(verified.digest),
+                verified.verified_by,
+                companions,
+            )
         }
         None => {
-            progress::send_progress(
-                &progress_tx,
-                InstallPhase::Building,
-                "No binary release for this platform. Building from source...",
-            );
-            source::install_from_source(&release.tag_name, &target_dir, verbose).await?;
-            InstallMethod::Source
+            let message = if preference == InstallMethodPreference::Source {
+                "Building from source..."
+            } else {
+                "No binary release for this platform. Building from source..."
+            };
+            progress::report_progress(op, progress_tx, InstallPhase::Building, message);
+            source::install_from_source(&release.tag_name, &target_dir, verbose, op).await?;
+            (
+                InstallMethod::Source,
+                format!("https://github.com/dora-rs/dora/tree/{}", release.tag_name),
+                None,
+                None,
+                Vec::new(),
+                Vec::new(),
+            )
+        }
+    };
+
+    let actual_version = match smoke_test(ctx, &target_dir, &tag).await {
+        Ok(actual_version) => actual_version,
+        Err(err) => {
+            let _ = std::fs::remove_dir_all(&target_dir);
+            return Err(err);
+        }
+    };
+
+    record_install(
+        ctx,
+        &target_dir,
+        actual_version,
+        InstallManifest {
+            version: tag,
+            method,
+            source_url,
+            asset_name,
+            asset_digest,
+            verified_by,
+            companions,
+            installed_at: chrono::Utc::now().to_rfc3339(),
+        },
+    )
+    .await
+}
+
+/// Install a pre-downloaded release archive. It is unpacked through the
+/// same verification and extraction as a download; the version comes from
+/// `version` or, when omitted, from the unpacked `dora --version`.
+async fn install_local(
+    ctx: &InstallContext<'_>,
+    archive: &Path,
+    version: Option<&str>,
+) -> Result<InstallResult> {
+    let home = ctx.home;
+    let version = version.map(|v| v.trim().trim_start_matches('v').to_string());
+    let (release, asset_name) =
+        github::local_release(version.as_deref().unwrap_or("local"), archive)?;
+    let asset = release
+        .assets
+        .iter()
+        .find(|a| a.name == asset_name)
+        .ok_or_else(|| anyhow::anyhow!("Cannot read {}", archive.display()))?;
+    if !archive::is_supported_archive(&asset.name) {
+        anyhow::bail!(
+            "{} is not a .tar.gz, .tar.xz or .zip release archive",
+            archive.display()
+        );
+    }
+    progress::report_progress(
+        ctx.op,
+        ctx.progress_tx,
+        InstallPhase::Fetching,
+        &format!("Installing from {}...", archive.display()),
+    );
+
+    let client = crate::http::client(home)?;
+    let fetch = AssetFetch {
+        client: &client,
+        ctx,
+    };
+    let versions_dir = config::versions_dir(home);
+    let staging = versions_dir.join(format!(".local-{}", asset.name));
+    let _ = std::fs::remove_dir_all(&staging);
+    let staged = async {
+        let verified = fetch.unpack(&release, asset, &staging).await?;
+        binary::place_dora_binary(&staging)?;
+        let mut companions = companion::collect(&staging, &staging)?;
+        for extra in companion::missing_assets(&release, &companions) {
+            let nested = staging.join(format!(".{}", extra.name));
+            let found = match fetch.unpack(&release, extra, &nested).await {
+                Ok(_) => companion::collect(&nested, &staging),
+                Err(err) => Err(err),
+            };
+            let _ = std::fs::remove_dir_all(&nested);
+            companions = found?;
+        }
+        let actual_version =
+            smoke_test(ctx, &staging, version.as_deref().unwrap_or("archive")).await?;
+        let tag = version.clone().unwrap_or_else(|| actual_version.clone());
+        Ok::<_, anyhow::Error>((tag, actual_version, verified, companions))
+    }
+    .await;
+    let (tag, actual_version, verified, companions) = match staged {
+        Ok(staged) => staged,
+        Err(err) => {
+            let _ = std::fs::remove_dir_all(&staging);
+            return Err(err);
         }
     };
 
-    let mut cfg = config::load_config(home)?;
-    let set_active = cfg.active_version.is_none();
+    let target_dir = versions_dir.join(&tag);
+    if config::dora_bin_path(&target_dir).exists() {
+        let _ = std::fs::remove_dir_all(&staging);
+        return Ok(already_installed(&target_dir, tag).await);
+    }
+    let _ = std::fs::remove_dir_all(&target_dir);
+    std::fs::rename(&staging, &target_dir)
+        .with_context(|| format!("Failed to move dora {} into place", tag))?;
+
+    record_install(
+        ctx,
+        &target_dir,
+        actual_version,
+        InstallManifest {
+            version: tag,
+            method: InstallMethod::Binary,
+            source_url: archive
+                .canonicalize()
+                .unwrap_or_else(|_| archive.to_path_buf())
+                .display()
+                .to_string(),
+            asset_name: Some(asset.name.clone()),
+            asset_digest: Some(verified.digest),
+            verified_by: verified.verified_by,
+            companions,
+            installed_at: chrono::Utc::now().to_rfc3339(),
+        },
+    )
+    .await
+}
+
+/// Run the post-install smoke test, turning a binary that cannot execute
+/// here into an install error.
+async fn smoke_test(ctx: &InstallContext<'_>, dir: &Path, tag: &str) -> Result<String> {
+    progress::report_progress(
+        ctx.op,
+        ctx.progress_tx,
+        InstallPhase::Verifying,
+        "Checking that dora runs...",
+    );
+    smoke::run(dir).await.map_err(|err| {
+        anyhow::anyhow!(
+            "dora {} was installed but does not run on this machine: {:#}\n\
+             Try `dm install --method source` to build dora for this system.",
+            tag,
+            err
+        )
+    })
+}
+
+/// Result for a version that is already on disk. Its smoke test is
+/// reported but does not fail the install.
+async fn already_installed(target_dir: &Path, tag: String) -> InstallResult {
+    let actual_version = smoke::run(target_dir).await.ok();
+    InstallResult {
+        method: manifest::read_manifest(target_dir)
+            .map(|m| m.method)
+            .unwrap_or(InstallMethod::Binary),
+        version: tag,
+        set_active: false,
+        verified: actual_version.is_some(),
+        actual_version,
+    }
+}
+
+/// Write `install.json`, activate the version when none is active yet and
+/// report completion.
+async fn record_install(
+    ctx: &InstallContext<'_>,
+    target_dir: &Path,
+    actual_version: String,
+    manifest: InstallManifest,
+) -> Result<InstallResult> {
+    let home = ctx.home;
+    manifest::write_manifest(target_dir, &manifest)?;
+    let InstallManifest {
+        version: tag,
+        method,
+        ..
+    } = manifest;
+
+    let set_active = config::modify_config_async(home, |cfg| {
+        let set_active = cfg.active_version.is_none();
+        if set_active {
+            cfg.active_version = Some(tag.clone());
+        }
+        Ok(set_active)
+    })
+    .await?;
     if set_active {
-        cfg.active_version = Some(tag.clone());
-        config::save_config(home, &cfg)?;
+        crate::shim::activate(home, &tag)?;
     }
 
-    progress::send_progress(
-        &progress_tx,
+    progress::report_progress(
+        ctx.op,
+        ctx.progress_tx,
         InstallPhase::Done,
         &format!("dora {} installed successfully.", tag),
     );
@@ -86,16 +424,81 @@ pub async fn install(
         version: tag,
         method,
         set_active,
+        verified: true,
+        actual_version: Some(actual_version),
     })
 }
 
+/// What every asset download of one install shares.
+struct AssetFetch<'a> {
+    client: &'a Client,
+    ctx: &'a InstallContext<'a>,
+}
+
+impl AssetFetch<'_> {
+    /// Download, verify and extract `asset` into `dest`. The archive is
+    /// staged next to the versions so it is never held in memory, and is
+    /// removed whether or not this succeeds.
+    async fn unpack(
+        &self,
+        release: &github::GithubRelease,
+        asset: &github::GithubAsset,
+        dest: &Path,
+    ) -> Result<verify::Verified> {
+        let plan = if self.ctx.install_cfg.verify {
+            Some(
+                verify::VerifyPlan::fetch(self.client, release, asset, self.ctx.install_cfg)
+                    .await?,
+            )
+        } else {
+            None
+        };
+        let versions_dir = config::versions_dir(self.ctx.home);
+        std::fs::create_dir_all(&versions_dir)?;
+        let archive = versions_dir.join(format!(".download-{}", asset.name));
+        let result = async {
+            let digest = binary::download_asset(
+                self.client,
+                asset,
+                &archive,
+                self.ctx.verbose,
+                self.ctx.progress_tx,
+                self.ctx.op,
+            )
+            .await?;
+            let verified = binary::verify_asset(
+                plan.as_ref(),
+                asset,
+                &archive,
+                digest,
+                self.ctx.progress_tx,
+                self.ctx.op,
+            )?;
+            binary::extract_asset(
+                asset,
+                &archive,
+                dest,
+                config::max_extract_bytes(self.ctx.home),
+                self.ctx.progress_tx,
+                self.ctx.op,
+            )?;
+            Ok(verified)
+        }
+        .await;
+        let _ = std::fs::remove_file(&archive);
+        result
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
-    use std::io::{Read, Write};
+    use std::io::Write;
 
     use tempfile::tempdir;
     use tokio::sync::mpsc;
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     use crate::test_support::{clear_path, env_lock};
 
@@ -121,11 +524,20 @@ mod tests {
         assert_eq!(msg.message, "Fetching release info...");
     }
 
+    /// Write archive bytes to a file in `dir` for the extractors.
+    fn archive_file(dir: &std::path::Path, name: &str, data: &[u8]) -> std::path::PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, data).unwrap();
+        path
+    }
+
     #[test]
     fn extract_zip_rejects_invalid_data() {
         let dir = tempdir().unwrap();
+        let data = archive_file(dir.path(), "bad.zip", b"not-a-zip");
+        let target = dir.path().join("target");
 
-        let err = archive::extract_zip(b"not-a-zip", dir.path())
+        let err = archive::extract_zip(&data, &target, 1024)
             .unwrap_err()
             .to_string();
         assert!(!err.is_empty());
@@ -135,13 +547,238 @@ mod tests {
     fn extract_tar_rejects_invalid_data() {
         let _guard = env_lock();
         let dir = tempdir().unwrap();
+        let data = archive_file(dir.path(), "bad.tar.gz", b"not-a-tar");
+        let target = dir.path().join("target");
+        fs::create_dir_all(&target).unwrap();
 
-        let err = archive::extract_tar(b"not-a-tar", dir.path())
+        let err = archive::extract_tar(&data, &target, 1024)
             .unwrap_err()
             .to_string();
         assert!(err.contains("tar extraction failed"));
     }
 
+    /// Zip of `entries`, written to `dir/archive.zip`.
+    fn zip_with(dir: &std::path::Path, entries: &[(&str, &[u8])]) -> std::path::PathBuf {
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        {
+            let mut zip = zip::ZipWriter::new(&mut cursor);
+            let opts: zip::write::SimpleFileOptions = zip::write::FileOptions::default();
+            for (name, data) in entries {
+                zip.start_file(*name, opts).unwrap();
+                zip.write_all(data).unwrap();
+            }
+            zip.finish().unwrap();
+        }
+        archive_file(dir, "archive.zip", &cursor.into_inner())
+    }
+
+    /// Gzipped tar of `stage` members, with names kept verbatim (`-P`).
+    fn tar_with(stage: &std::path::Path, members: &[&str]) -> std::path::PathBuf {
+        let out = stage.join("out.tar.gz");
+        let status = std::process::Command::new("tar")
+            .arg("-czPf")
+            .arg(&out)
+            .arg("-C")
+            .arg(stage.join("root"))
+            .args(members)
+            .status()
+            .unwrap();
+        assert!(status.success());
+        out
+    }
+
+    #[test]
+    fn extract_zip_rejects_path_traversal() {
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("target");
+        fs::create_dir_all(&target).unwrap();
+        let data = zip_with(dir.path(), &[("ok/dora", b"bin"), ("../evil", b"pwned")]);
+
+        let err = archive::extract_zip(&data, &target, 1024 * 1024)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("path traversal"), "{err}");
+        assert!(!dir.path().join("evil").exists());
+        assert!(!target.join("ok").exists());
+
+        let data = zip_with(dir.path(), &[("/etc/evil", b"pwned")]);
+        assert!(archive::extract_zip(&data, &target, 1024 * 1024).is_err());
+        let data = zip_with(dir.path(), &[("..\\evil", b"pwned")]);
+        assert!(archive::extract_zip(&data, &target, 1024 * 1024).is_err());
+    }
+
+    #[test]
+    fn extract_zip_enforces_size_limit() {
+        let dir = tempdir().unwrap();
+        let big = vec![0_u8; 4096];
+        let data = zip_with(dir.path(), &[("a", &big), ("b", &big)]);
+        let target = dir.path().join("target");
+
+        let err = archive::extract_zip(&data, &target, 6000)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("extraction limit"), "{err}");
+
+        archive::extract_zip(&data, &target, 8192).unwrap();
+        assert_eq!(fs::read(target.join("b")).unwrap().len(), 4096);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn extract_tar_rejects_path_traversal() {
+        let _guard = env_lock();
+        let dir = tempdir().unwrap();
+        let stage = dir.path().join("stage");
+        fs::create_dir_all(stage.join("root")).unwrap();
+        fs::write(stage.join("evil"), "pwned").unwrap();
+        let data = tar_with(&stage, &["../evil"]);
+
+        let target = dir.path().join("a").join("target");
+        fs::create_dir_all(&target).unwrap();
+        let err = archive::extract_tar(&data, &target, 1024 * 1024)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("path traversal"), "{err}");
+        assert!(!dir.path().join("a").join("evil").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn extract_tar_rejects_escaping_symlink_and_oversize() {
+        let _guard = env_lock();
+        let dir = tempdir().unwrap();
+        let stage = dir.path().join("stage");
+        let pkg = stage.join("root").join("pkg");
+        fs::create_dir_all(&pkg).unwrap();
+        std::os::unix::fs::symlink("../../outside", pkg.join("link")).unwrap();
+        let data = tar_with(&stage, &["pkg"]);
+
+        let target = dir.path().join("symlink-target");
+        fs::create_dir_all(&target).unwrap();
+        let err = archive::extract_tar(&data, &target, 1024 * 1024)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Refusing link"), "{err}");
+        assert!(!target.exists());
+
+        fs::remove_file(pkg.join("link")).unwrap();
+        fs::write(pkg.join("dora"), vec![0_u8; 8192]).unwrap();
+        let data = tar_with(&stage, &["pkg"]);
+        let target = dir.path().join("size-target");
+        fs::create_dir_all(&target).unwrap();
+        let err = archive::extract_tar(&data, &target, 4096)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("extraction limit"), "{err}");
+
+        fs::create_dir_all(&target).unwrap();
+        archive::extract_tar(&data, &target, 1024 * 1024).unwrap();
+        assert!(target.join("dora").exists());
+    }
+
+    /// Tar of `members` as written, where a `Some` target makes a symlink;
+    /// stages what a real file system cannot hold, like a file under a link.
+    fn tar_of(dir: &std::path::Path, members: &[(&str, Option<&str>)]) -> std::path::PathBuf {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, link) in members {
+            let mut header = tar::Header::new_gnu();
+            header.set_mode(0o755);
+            match link {
+                Some(target) => {
+                    header.set_entry_type(tar::EntryType::Symlink);
+                    header.set_size(0);
+                    builder.append_link(&mut header, name, target).unwrap();
+                }
+                None => {
+                    header.set_size(5);
+                    builder
+                        .append_data(&mut header, name, &b"pwned"[..])
+                        .unwrap();
+                }
+            }
+        }
+        archive_file(dir, "links.tar", &builder.into_inner().unwrap())
+    }
+
+    #[test]
+    fn extract_tar_rejects_chained_symlinks() {
+        let dir = tempdir().unwrap();
+        let data = tar_of(
+            dir.path(),
+            &[
+                ("a/b", Some("..")),
+                ("a/b/c", Some("..")),
+                ("a/b/c/x", None),
+            ],
+        );
+        let target = dir.path().join("nested").join("target");
+        fs::create_dir_all(&target).unwrap();
+
+        let err = archive::extract_tar(&data, &target, 1024 * 1024)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Refusing link 'a/b'"), "{err}");
+        assert!(!dir.path().join("x").exists());
+        assert!(!dir.path().join("nested").join("x").exists());
+    }
+
+    #[test]
+    fn extract_tar_rejects_symlinks_escaping_once_stripped() {
+        let dir = tempdir().unwrap();
+        let data = tar_of(
+            dir.path(),
+            &[("pkg/evil", Some("..")), ("pkg/evil/x", None)],
+        );
+        let target = dir.path().join("nested").join("target");
+        fs::create_dir_all(&target).unwrap();
+
+        let err = archive::extract_tar(&data, &target, 1024 * 1024)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Refusing link 'pkg/evil'"), "{err}");
+        assert!(!dir.path().join("nested").join("x").exists());
+        assert!(!target.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn extract_tar_stops_at_the_member_crossing_the_limit() {
+        let _guard = env_lock();
+        let dir = tempdir().unwrap();
+        let stage = dir.path().join("stage");
+        let pkg = stage.join("root").join("pkg");
+        fs::create_dir_all(&pkg).unwrap();
+        fs::write(pkg.join("a"), vec![0_u8; 4096]).unwrap();
+        fs::write(pkg.join("b"), vec![0_u8; 4096]).unwrap();
+        let data = tar_with(&stage, &["pkg/a", "pkg/b"]);
+
+        let target = dir.path().join("target");
+        fs::create_dir_all(&target).unwrap();
+        let err = archive::extract_tar(&data, &target, 6000)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("at 'pkg/b'"), "{err}");
+        assert!(!target.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn extract_tar_keeps_members_of_flat_archives() {
+        let _guard = env_lock();
+        let dir = tempdir().unwrap();
+        let stage = dir.path().join("stage");
+        fs::create_dir_all(stage.join("root")).unwrap();
+        fs::write(stage.join("root").join("dora"), "bin").unwrap();
+        fs::write(stage.join("root").join("README"), "docs").unwrap();
+        let data = tar_with(&stage, &["dora", "README"]);
+
+        let target = dir.path().join("target");
+        fs::create_dir_all(&target).unwrap();
+        archive::extract_tar(&data, &target, 1024).unwrap();
+        assert!(target.join("dora").is_file());
+        assert!(target.join("README").is_file());
+    }
+
     #[test]
     fn find_dora_binary_finds_nested_binary_and_skips_venv() {
         let dir = tempdir().unwrap();
@@ -177,33 +814,260 @@ mod tests {
             cursor.into_inner()
         };
 
-        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
-        let addr = listener.local_addr().unwrap();
-        let body = zip_bytes.clone();
-        let server = std::thread::spawn(move || {
-            let (mut stream, _) = listener.accept().unwrap();
-            let mut buf = [0_u8; 1024];
-            let _ = stream.read(&mut buf);
-            let header = format!(
-                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nContent-Type: application/zip\r\nConnection: close\r\n\r\n",
-                body.len()
-            );
-            stream.write_all(header.as_bytes()).unwrap();
-            stream.write_all(&body).unwrap();
-        });
+        let server = MockServer::start().await;
+        Mock::given(path("/download.zip"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(zip_bytes.clone()))
+            .expect(1)
+            .mount(&server)
+            .await;
 
         let asset = github::GithubAsset {
             name: "dora-cli-test.zip".to_string(),
-            browser_download_url: format!("http://{}/download.zip", addr),
+            browser_download_url: format!("{}/download.zip", server.uri()),
             size: zip_bytes.len() as u64,
+            digest: Some(manifest::sha256_digest(&zip_bytes)),
         };
 
-        binary::install_from_binary(&reqwest::Client::new(), &asset, &target_dir, false, &None)
-            .await
-            .unwrap();
-        server.join().unwrap();
+        let op = OperationEvent::new(dir.path(), EventSource::Core, "version.install");
+        let archive = dir.path().join(".download-dora-cli-test.zip");
+        let digest =
+            binary::download_asset(&reqwest::Client::new(), &asset, &archive, false, &None, &op)
+                .await
+                .unwrap();
+        assert_eq!(fs::read(&archive).unwrap(), zip_bytes);
+        let plan = verify::VerifyPlan::fetch(
+            &reqwest::Client::new(),
+            &github::GithubRelease {
+                tag_name: "v0.0.0".to_string(),
+                assets: Vec::new(),
+            },
+            &asset,
+            &config::InstallConfig::default(),
+        )
+        .await
+        .unwrap();
+        let verified =
+            binary::verify_asset(Some(&plan), &asset, &archive, digest, &None, &op).unwrap();
+        binary::extract_asset(
+            &asset,
+            &archive,
+            &target_dir,
+            config::DEFAULT_MAX_EXTRACT_MB * 1024 * 1024,
+            &None,
+            &op,
+        )
+        .unwrap();
+        binary::place_dora_binary(&target_dir).unwrap();
 
         assert!(target_dir.join(config::dora_bin_name()).exists());
+        assert_eq!(Some(verified.digest), asset.digest);
+        assert_eq!(verified.verified_by, vec!["github-digest"]);
+
+        let store = crate::events::EventStore::open(dir.path()).unwrap();
+        let progress = store
+            .query(&crate::events::EventFilter {
+                activity: Some("progress".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(progress
+            .iter()
+            .all(|e| e.activity == "version.install.progress"));
+        let phases: Vec<String> = progress
+            .iter()
+            .filter_map(|e| e.attributes.as_deref())
+            .filter_map(|a| serde_json::from_str::<serde_json::Value>(a).ok())
+            .filter_map(|a| a["phase"].as_str().map(str::to_string))
+            .collect();
+        assert!(phases.contains(&"downloading".to_string()));
+        assert!(phases.contains(&"verifying".to_string()));
+        assert!(phases.contains(&"extracting".to_string()));
+    }
+
+    /// A dora release whose binary does not match its `SHA256SUMS` entry.
+    async fn serve_tampered_release() -> MockServer {
+        let asset_name = format!("dora-cli-{}.zip", github::platform_asset_patterns()[0]);
+        let stage = tempdir().unwrap();
+        let zip = fs::read(zip_with(
+            stage.path(),
+            &[("dora", b"#!/bin/sh\necho 'dora-cli 0.4.1'\n")],
+        ))
+        .unwrap();
+        let sums = format!("{}  {}\n", "0".repeat(64), asset_name);
+        let server = MockServer::start().await;
+        let base = server.uri();
+        let release = serde_json::json!({
+            "tag_name": "v0.4.1",
+            "assets": [
+                {
+                    "name": asset_name,
+                    "browser_download_url": format!("{base}/asset.zip"),
+                    "size": zip.len(),
+                },
+                {
+                    "name": "SHA256SUMS",
+                    "browser_download_url": format!("{base}/SHA256SUMS"),
+                    "size": sums.len(),
+                },
+            ],
+        });
+        let routes = [
+            (
+                "/repos/dora-rs/dora/releases/tags/v0.4.1",
+                ResponseTemplate::new(200).set_body_raw(release.to_string(), "application/json"),
+            ),
+            ("/asset.zip", ResponseTemplate::new(200).set_body_bytes(zip)),
+            (
+                "/SHA256SUMS",
+                ResponseTemplate::new(200).set_body_string(sums),
+            ),
+        ];
+        for (at, response) in routes {
+            Mock::given(path(at))
+                .respond_with(response)
+                .mount(&server)
+                .await;
+        }
+        server
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn install_rejects_checksum_mismatch_unless_no_verify() {
+        let _guard = env_lock();
+        let dir = tempdir().unwrap();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let server = rt.block_on(serve_tampered_release());
+        let mut cfg = config::load_config(dir.path()).unwrap();
+
+        cfg.github_api_url = Some(server.uri());
+        config::save_config(dir.path(), &cfg).unwrap();
+        let err = rt
+            .block_on(install(dir.path(), Some("0.4.1".into()), false, None))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("SHA256SUMS"), "{err}");
+        let version_dir = config::versions_dir(dir.path()).join("0.4.1");
+        assert!(!config::dora_bin_path(&version_dir).exists());
+        let leftovers: Vec<_> = fs::read_dir(config::versions_dir(dir.path()))
+            .unwrap()
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().starts_with(".download-"))
+            .collect();
+        assert!(leftovers.is_empty(), "temp download left behind");
+
+        let result = rt
+            .block_on(install_with(
+                dir.path(),
+                Some("0.4.1".into()),
+                None,
+                Some(false),
+                false,
+                None,
+            ))
+            .unwrap();
+        assert_eq!(result.version, "0.4.1");
+        assert!(result.verified);
+        assert_eq!(result.actual_version.as_deref(), Some("0.4.1"));
+        let manifest = read_manifest(&version_dir).unwrap();
+        assert!(manifest.verified_by.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn install_from_file_verifies_sidecar_and_reads_version_from_binary() {
+        let _guard = env_lock();
+        let dir = tempdir().unwrap();
+        let downloads = dir.path().join("downloads");
+        fs::create_dir_all(&downloads).unwrap();
+        let archive = zip_with(
+            &downloads,
+            &[("dora-cli/dora", b"#!/bin/sh\necho 'dora-cli 0.3.9'\n")],
+        );
+        let digest = manifest::sha256_digest(&fs::read(&archive).unwrap());
+        let sidecar = downloads.join("archive.zip.sha256");
+        fs::write(&sidecar, format!("{}  archive.zip\n", &digest[7..])).unwrap();
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let options = InstallOptions {
+            from_file: Some(archive.clone()),
+            ..Default::default()
+        };
+        let result = rt
+            .block_on(install_with_options(
+                dir.path(),
+                None,
+                options.clone(),
+                false,
+                None,
+            ))
+            .unwrap();
+        assert_eq!(result.version, "0.3.9");
+        assert!(result.set_active);
+        let version_dir = config::versions_dir(dir.path()).join("0.3.9");
+        let manifest = read_manifest(&version_dir).unwrap();
+        assert_eq!(manifest.verified_by, ["checksum-file"]);
+        assert_eq!(manifest.asset_digest.as_deref(), Some(digest.as_str()));
+
+        fs::write(&sidecar, format!("{}  archive.zip\n", "0".repeat(64))).unwrap();
+        let err = rt
+            .block_on(install_with_options(
+                dir.path(),
+                Some("0.4.0".into()),
+                options,
+                false,
+                None,
+            ))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("archive.zip.sha256"), "{err}");
+        let leftovers: Vec<_> = fs::read_dir(config::versions_dir(dir.path()))
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(leftovers, ["0.3.9"]);
+    }
+
+    #[test]
+    fn binary_method_refuses_source_fallback() {
+        let _guard = env_lock();
+        let dir = tempdir().unwrap();
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let server = rt.block_on(MockServer::start());
+        rt.block_on(
+            Mock::given(path("/repos/dora-rs/dora/releases/tags/v0.4.1"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .set_body_raw(r#"{"tag_name":"v0.4.1","assets":[]}"#, "application/json"),
+                )
+                .expect(1)
+                .mount(&server),
+        );
+        let mut cfg = config::load_config(dir.path()).unwrap();
+        cfg.github_api_url = Some(server.uri());
+        cfg.install.method = InstallMethodPreference::Binary;
+        config::save_config(dir.path(), &cfg).unwrap();
+
+        let err = rt
+            .block_on(install(dir.path(), Some("0.4.1".into()), false, None))
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("no release binary"), "{err}");
+        assert!(!config::versions_dir(dir.path()).join("0.4.1").exists());
+    }
+
+    #[test]
+    fn install_phase_reports_download_percentage() {
+        let phase = InstallPhase::Downloading {
+            bytes_done: 50,
+            bytes_total: 200,
+        };
+        assert_eq!(phase.name(), "downloading");
+        assert_eq!(phase.pct(), Some(25));
+        assert_eq!(InstallPhase::Fetching.pct(), None);
+        assert_eq!(InstallPhase::Done.pct(), Some(100));
     }
 
     #[test]
@@ -213,7 +1077,12 @@ mod tests {
         let _path = clear_path();
 
         let rt = tokio::runtime::Runtime::new().unwrap();
-        let result = rt.block_on(source::install_from_source("v0.4.1", dir.path(), false));
+        let result = rt.block_on(source::install_from_source(
+            "v0.4.1",
+            dir.path(),
+            false,
+            &OperationEvent::new(dir.path(), EventSource::Core, "version.install"),
+        ));
 
         let err = result.unwrap_err().to_string();
         assert!(err.contains("Rust is not installed"));
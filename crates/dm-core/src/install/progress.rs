@@ -1,5 +1,6 @@
 use tokio::sync::mpsc;
 
+use crate::events::OperationEvent;
 use crate::types::{InstallPhase, InstallProgress};
 
 pub(super) fn send_progress(
@@ -14,3 +15,14 @@ pub(super) fn send_progress(
         });
     }
 }
+
+/// Forward progress to the channel and record it as a `*.progress` event.
+pub(super) fn report_progress(
+    op: &OperationEvent,
+    tx: &Option<mpsc::UnboundedSender<InstallProgress>>,
+    phase: InstallPhase,
+    message: &str,
+) {
+    op.emit_progress(phase.name(), phase.pct(), message);
+    send_progress(tx, phase, message);
+}
@@ -0,0 +1,135 @@
+//! Post-install smoke test: the installed `dora` has to run `dora
+//! --version` and `dora check --help` before an install counts as done, so
+//! a binary built for another libc or CPU fails the install rather than
+//! the first `dm up`.
+
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+use tokio::process::Command;
+
+use crate::config;
+
+const SMOKE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Run the smoke test against the dora binary in `version_dir`. Returns
+/// the version it reports, e.g. `0.3.9` for `dora-cli 0.3.9`.
+pub(super) async fn run(version_dir: &Path) -> Result<String> {
+    let bin = config::dora_bin_path(version_dir);
+    let stdout = run_dora(&bin, &["--version"]).await?;
+    let version = stdout
+        .lines()
+        .next()
+        .and_then(|line| line.split_whitespace().last())
+        .map(|v| v.trim_start_matches('v').to_string())
+        .filter(|v| semver::Version::parse(v).is_ok());
+    let Some(version) = version else {
+        bail!("`dora --version` printed no version: {}", stdout.trim());
+    };
+    run_dora(&bin, &["check", "--help"]).await?;
+    Ok(version)
+}
+
+async fn run_dora(bin: &Path, args: &[&str]) -> Result<String> {
+    let command = format!("dora {}", args.join(" "));
+    let mut cmd = Command::new(bin);
+    cmd.args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let output = match tokio::time::timeout(SMOKE_TIMEOUT, cmd.output()).await {
+        Err(_) => bail!(
+            "`{}` did not finish within {}s",
+            command,
+            SMOKE_TIMEOUT.as_secs()
+        ),
+        Ok(Err(err)) => bail!("`{}` could not start: {}", command, spawn_hint(&err)),
+        Ok(Ok(output)) => output,
+    };
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "`{}` failed ({}): {}{}",
+            command,
+            output.status,
+            stderr.trim(),
+            failure_hint(&stderr)
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Why an existing binary would not start, in terms a user can act on.
+fn spawn_hint(err: &std::io::Error) -> String {
+    // ENOEXEC: not a binary format this kernel runs.
+    if err.raw_os_error() == Some(8) {
+        return format!(
+            "{} (the binary was built for a different CPU architecture or OS)",
+            err
+        );
+    }
+    match err.kind() {
+        std::io::ErrorKind::NotFound => format!(
+            "{} (its dynamic loader is missing; the binary was likely built for a different libc, e.g. glibc vs musl)",
+            err
+        ),
+        std::io::ErrorKind::PermissionDenied => format!(
+            "{} (is the dm home on a filesystem mounted noexec?)",
+            err
+        ),
+        _ => err.to_string(),
+    }
+}
+
+fn failure_hint(stderr: &str) -> &'static str {
+    if stderr.contains("GLIBC_") {
+        " (the binary needs a newer glibc than this system provides)"
+    } else if stderr.contains("error while loading shared libraries") {
+        " (a shared library the binary links against is not installed)"
+    } else {
+        ""
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn smoke_test_reports_version_and_explains_failures() {
+        use dm_test_utils::{MockCommand, MockResponse};
+
+        let dir = tempfile::tempdir().unwrap();
+        let bin = config::dora_bin_path(dir.path());
+        let version = MockResponse::ok("dora-cli 0.3.9");
+        MockCommand::new()
+            .fallback(version.clone())
+            .install_at(&bin)
+            .unwrap();
+        assert_eq!(run(dir.path()).await.unwrap(), "0.3.9");
+
+        MockCommand::new()
+            .fail(
+                "check",
+                1,
+                "dora: /lib/libc.so.6: version `GLIBC_2.39` not found",
+            )
+            .fallback(version)
+            .install_at(&bin)
+            .unwrap();
+        let err = run(dir.path()).await.unwrap_err().to_string();
+        assert!(err.contains("dora check --help"), "{err}");
+        assert!(err.contains("newer glibc"), "{err}");
+
+        std::fs::write(
+            config::dora_bin_path(dir.path()),
+            [0x7f, b'E', b'L', b'F', 0, 0],
+        )
+        .unwrap();
+        assert!(run(dir.path()).await.is_err());
+    }
+}
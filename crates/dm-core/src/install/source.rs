@@ -2,12 +2,14 @@ use std::path::Path;
 
 use anyhow::Result;
 
+use crate::events::OperationEvent;
 use crate::util;
 
 pub(super) async fn install_from_source(
     git_tag: &str,
     target_dir: &Path,
     verbose: bool,
+    op: &OperationEvent,
 ) -> Result<()> {
     if util::check_command("cargo").is_none() {
         anyhow::bail!(
@@ -19,6 +21,11 @@ pub(super) async fn install_from_source(
     std::fs::create_dir_all(target_dir)?;
     let build_dir = target_dir.join("_build");
 
+    op.emit_progress(
+        "cloning",
+        None,
+        format!("Cloning dora-rs/dora at {}", git_tag),
+    );
     let clone_status = tokio::process::Command::new("git")
         .args([
             "clone",
@@ -45,6 +52,7 @@ pub(super) async fn install_from_source(
         anyhow::bail!("Failed to clone dora repository at tag {}", git_tag);
     }
 
+    op.emit_progress("compiling", None, "cargo build --release -p dora-cli");
     let build_status = tokio::process::Command::new("cargo")
         .args(["build", "--release", "-p", "dora-cli"])
         .current_dir(&build_dir)
@@ -91,22 +99,36 @@ mod tests {
     use std::fs;
     use std::path::Path;
 
+    use dm_test_utils::{MockCommand, MockResponse};
     use tempfile::tempdir;
 
     use crate::test_support::{env_lock, set_path};
 
+    use crate::events::{EventSource, OperationEvent};
+
     use super::install_from_source;
 
+    fn test_op(home: &Path) -> OperationEvent {
+        OperationEvent::new(home, EventSource::Core, "version.install")
+    }
+
+    /// A `cargo` that reports its version and runs `build` with `build`.
+    #[cfg(not(target_os = "windows"))]
+    fn fake_cargo(bin_dir: &Path, build: MockResponse) {
+        MockCommand::new()
+            .respond("--version", MockResponse::ok("cargo 1.0"))
+            .fallback(build)
+            .install_at(&bin_dir.join("cargo"))
+            .unwrap();
+    }
+
+    /// A `git` whose clone creates the destination directory.
     #[cfg(not(target_os = "windows"))]
-    fn write_executable(path: &Path, content: &str) {
-        fs::write(path, content).unwrap();
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let mut perms = fs::metadata(path).unwrap().permissions();
-            perms.set_mode(0o755);
-            fs::set_permissions(path, perms).unwrap();
-        }
+    fn fake_git(bin_dir: &Path) {
+        MockCommand::new()
+            .fallback(MockResponse::script("/bin/mkdir -p \"$6\""))
+            .install_at(&bin_dir.join("git"))
+            .unwrap();
     }
 
     #[test]
@@ -117,11 +139,11 @@ mod tests {
         let bin_dir = dir.path().join("bin");
         fs::create_dir_all(&bin_dir).unwrap();
 
-        write_executable(
-            &bin_dir.join("cargo"),
-            "#!/bin/sh\nif [ \"$1\" = \"--version\" ]; then echo cargo 1.0; exit 0; fi\nexit 1\n",
-        );
-        write_executable(&bin_dir.join("git"), "#!/bin/sh\nexit 1\n");
+        fake_cargo(&bin_dir, MockResponse::fail(1, ""));
+        MockCommand::new()
+            .fallback(MockResponse::fail(1, ""))
+            .install_at(&bin_dir.join("git"))
+            .unwrap();
 
         let _path = set_path(bin_dir.clone());
         let rt = tokio::runtime::Runtime::new().unwrap();
@@ -129,6 +151,7 @@ mod tests {
             "v0.4.1",
             dir.path().join("target").as_path(),
             false,
+            &test_op(dir.path()),
         ));
 
         let err = result.unwrap_err().to_string();
@@ -144,18 +167,17 @@ mod tests {
         let target_dir = dir.path().join("target");
         fs::create_dir_all(&bin_dir).unwrap();
 
-        write_executable(
-            &bin_dir.join("cargo"),
-            "#!/bin/sh\nif [ \"$1\" = \"--version\" ]; then echo cargo 1.0; exit 0; fi\nexit 1\n",
-        );
-        write_executable(
-            &bin_dir.join("git"),
-            "#!/bin/sh\n/bin/mkdir -p \"$6\"\nexit 0\n",
-        );
+        fake_cargo(&bin_dir, MockResponse::fail(1, ""));
+        fake_git(&bin_dir);
 
         let _path = set_path(bin_dir.clone());
         let rt = tokio::runtime::Runtime::new().unwrap();
-        let result = rt.block_on(install_from_source("v0.4.1", &target_dir, false));
+        let result = rt.block_on(install_from_source(
+            "v0.4.1",
+            &target_dir,
+            false,
+            &test_op(dir.path()),
+        ));
 
         let err = result.unwrap_err().to_string();
         assert!(err.contains("cargo build failed for dora-cli"));
@@ -171,19 +193,23 @@ mod tests {
         let target_dir = dir.path().join("target");
         fs::create_dir_all(&bin_dir).unwrap();
 
-        write_executable(
-            &bin_dir.join("cargo"),
-            "#!/bin/sh\nif [ \"$1\" = \"--version\" ]; then echo cargo 1.0; exit 0; fi\n/bin/mkdir -p target/release\nprintf '#!/bin/sh\\necho dora\\n' > target/release/dora\n/bin/chmod +x target/release/dora\nexit 0\n",
-        );
-        write_executable(
-            &bin_dir.join("git"),
-            "#!/bin/sh\n/bin/mkdir -p \"$6\"\nexit 0\n",
+        fake_cargo(
+            &bin_dir,
+            MockResponse::script(
+                "/bin/mkdir -p target/release\n/bin/cp \"$0\" target/release/dora",
+            ),
         );
+        fake_git(&bin_dir);
 
         let _path = set_path(bin_dir.clone());
         let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(install_from_source("v0.4.1", &target_dir, false))
-            .unwrap();
+        rt.block_on(install_from_source(
+            "v0.4.1",
+            &target_dir,
+            false,
+            &test_op(dir.path()),
+        ))
+        .unwrap();
 
         assert!(target_dir.join(crate::config::dora_bin_name()).exists());
         assert!(!target_dir.join("_build").exists());
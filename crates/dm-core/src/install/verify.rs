@@ -0,0 +1,304 @@
+//! Integrity checks for downloaded release assets.
+//!
+//! Before extraction an asset is checked against everything the release
+//! publishes for it: GitHub's asset digest, a SHA-256 checksum file
+//! (`<asset>.sha256` or a list such as `SHA256SUMS`) and, when configured,
+//! a minisign or GPG signature. A mismatch aborts the install.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use reqwest::Client;
+
+use crate::config::InstallConfig;
+
+use super::github::{local_path, GithubAsset, GithubRelease};
+use super::manifest::verify_digest;
+
+/// Release-wide checksum lists, matched case-insensitively.
+const CHECKSUM_LISTS: &[&str] = &[
+    "sha256.sum",
+    "sha256sums",
+    "sha256sums.txt",
+    "checksums.txt",
+];
+
+/// What an asset must be checked against, gathered before it is downloaded.
+#[derive(Debug, Default)]
+pub(super) struct VerifyPlan {
+    /// `sha256:<hex>` from GitHub's asset metadata.
+    github_digest: Option<String>,
+    /// Hex digest and the checksum asset it was read from.
+    checksum: Option<(String, String)>,
+    /// minisign public key and the `.minisig` contents.
+    minisign: Option<(String, String)>,
+    /// Detached GPG signature.
+    gpg_signature: Option<Vec<u8>>,
+}
+
+/// Outcome of a successful check.
+#[derive(Debug)]
+pub(super) struct Verified {
+    /// `sha256:<hex>` of the asset.
+    pub digest: String,
+    /// Checks that passed, for `install.json`.
+    pub verified_by: Vec<String>,
+}
+
+impl VerifyPlan {
+    /// Collect checksums and signatures `release` publishes for `asset`.
+    /// Fails when a signature is required by config but missing.
+    pub(super) async fn fetch(
+        client: &Client,
+        release: &GithubRelease,
+        asset: &GithubAsset,
+        cfg: &InstallConfig,
+    ) -> Result<Self> {
+        let mut plan = Self {
+            github_digest: asset.digest.clone(),
+            ..Default::default()
+        };
+
+        let sidecar = format!("{}.sha256", asset.name);
+        if let Some(sum) = find_asset(release, &[sidecar.as_str()]) {
+            let text = fetch_text(client, sum).await?;
+            plan.checksum = parse_checksum(&text, None).map(|hex| (hex, sum.name.clone()));
+        } else if let Some(list) = find_asset(release, CHECKSUM_LISTS) {
+            let text = fetch_text(client, list).await?;
+            plan.checksum =
+                parse_checksum(&text, Some(&asset.name)).map(|hex| (hex, list.name.clone()));
+        }
+
+        if let Some(key) = cfg.minisign_public_key.as_deref() {
+            let name = format!("{}.minisig", asset.name);
+            let Some(sig) = find_asset(release, &[name.as_str()]) else {
+                bail!(
+                    "install.minisign_public_key is set but {} has no {} signature",
+                    release.tag_name,
+                    name
+                );
+            };
+            plan.minisign = Some((key.to_string(), fetch_text(client, sig).await?));
+        }
+
+        if cfg.gpg_verify {
+            let names = [format!("{}.asc", asset.name), format!("{}.sig", asset.name)];
+            let names: Vec<&str> = names.iter().map(String::as_str).collect();
+            let Some(sig) = find_asset(release, &names) else {
+                bail!(
+                    "install.gpg_verify is set but {} has no .asc/.sig signature for {}",
+                    release.tag_name,
+                    asset.name
+                );
+            };
+            plan.gpg_signature = Some(fetch_bytes(client, sig).await?);
+        }
+
+        Ok(plan)
+    }
+
+    /// Whether the release published anything to check against.
+    pub(super) fn is_empty(&self) -> bool {
+        self.github_digest.is_none()
+            && self.checksum.is_none()
+            && self.minisign.is_none()
+            && self.gpg_signature.is_none()
+    }
+
+    /// Check the downloaded `archive` (`asset_name`, hashed to `digest`)
+    /// against every source.
+    pub(super) fn check(
+        &self,
+        archive: &Path,
+        digest: String,
+        asset_name: &str,
+    ) -> Result<Verified> {
+        let mut verified_by = Vec::new();
+
+        if let Some(expected) = self.github_digest.as_deref() {
+            if verify_digest(&digest, expected)?.is_some() {
+                verified_by.push("github-digest".to_string());
+            }
+        }
+
+        if let Some((hex, source)) = &self.checksum {
+            if !digest[7..].eq_ignore_ascii_case(hex) {
+                bail!(
+                    "{} does not match the checksum published in {} (expected sha256:{}, got {})",
+                    asset_name,
+                    source,
+                    hex,
+                    digest
+                );
+            }
+            verified_by.push("checksum-file".to_string());
+        }
+
+        if self.minisign.is_some() || self.gpg_signature.is_some() {
+            let mut sig_files = Vec::new();
+            let result =
+                self.check_signatures(archive, asset_name, &mut sig_files, &mut verified_by);
+            for sig in sig_files {
+                let _ = std::fs::remove_file(sig);
+            }
+            result?;
+        }
+
+        Ok(Verified {
+            digest,
+            verified_by,
+        })
+    }
+
+    /// Signatures are written next to `archive`; their paths are pushed to
+    /// `sig_files` so the caller can clean them up.
+    fn check_signatures(
+        &self,
+        archive: &Path,
+        asset_name: &str,
+        sig_files: &mut Vec<PathBuf>,
+        verified_by: &mut Vec<String>,
+    ) -> Result<()> {
+        if let Some((key, signature)) = &self.minisign {
+            let sig = archive.with_extension("minisig");
+            sig_files.push(sig.clone());
+            std::fs::write(&sig, signature)?;
+            let mut cmd = Command::new("minisign");
+            cmd.arg("-V")
+                .arg("-q")
+                .arg("-P")
+                .arg(key)
+                .arg("-m")
+                .arg(archive)
+                .arg("-x")
+                .arg(&sig);
+            run_verifier(cmd, "minisign", asset_name)?;
+            verified_by.push("minisign".to_string());
+        }
+
+        if let Some(signature) = &self.gpg_signature {
+            let sig = archive.with_extension("sig");
+            sig_files.push(sig.clone());
+            std::fs::write(&sig, signature)?;
+            let mut cmd = Command::new("gpg");
+            cmd.arg("--batch").arg("--verify").arg(&sig).arg(archive);
+            run_verifier(cmd, "gpg", asset_name)?;
+            verified_by.push("gpg".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+fn run_verifier(mut cmd: Command, tool: &str, asset_name: &str) -> Result<()> {
+    let output = match cmd.output() {
+        Ok(output) => output,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => bail!(
+            "Signature verification needs `{}`, which is not installed",
+            tool
+        ),
+        Err(err) => return Err(err).with_context(|| format!("Failed to run {}", tool)),
+    };
+    if !output.status.success() {
+        bail!(
+            "{} signature check failed for {}: {}",
+            tool,
+            asset_name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+fn find_asset<'a>(release: &'a GithubRelease, names: &[&str]) -> Option<&'a GithubAsset> {
+    names.iter().find_map(|name| {
+        release
+            .assets
+            .iter()
+            .find(|a| a.name.eq_ignore_ascii_case(name))
+    })
+}
+
+async fn fetch_bytes(client: &Client, asset: &GithubAsset) -> Result<Vec<u8>> {
+    if let Some(path) = local_path(&asset.browser_download_url) {
+        return std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()));
+    }
+    let resp = client
+        .get(&asset.browser_download_url)
+        .header("User-Agent", "dm/0.1")
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        bail!("Failed to download {} ({})", asset.name, resp.status());
+    }
+    Ok(resp.bytes().await?.to_vec())
+}
+
+async fn fetch_text(client: &Client, asset: &GithubAsset) -> Result<String> {
+    Ok(String::from_utf8_lossy(&fetch_bytes(client, asset).await?).into_owned())
+}
+
+/// Read a hex SHA-256 from `sha256sum`-style output. With `file`, only the
+/// line naming that file counts; without it the first digest is taken.
+fn parse_checksum(text: &str, file: Option<&str>) -> Option<String> {
+    text.lines().find_map(|line| {
+        let mut parts = line.split_whitespace();
+        let hex = parts.next()?;
+        if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        if let Some(file) = file {
+            let name = parts.next()?.trim_start_matches('*');
+            let name = name.rsplit('/').next().unwrap_or(name);
+            if name != file {
+                return None;
+            }
+        }
+        Some(hex.to_lowercase())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::manifest::sha256_digest;
+    use super::*;
+
+    #[test]
+    fn parse_checksum_reads_sidecar_and_lists() {
+        let hex = "a".repeat(64);
+        assert_eq!(
+            parse_checksum(&format!("{hex}  dora.zip\n"), None),
+            Some(hex.clone())
+        );
+        let list = format!(
+            "{}  other.zip\n{}  *dist/dora.tar.gz\n",
+            "b".repeat(64),
+            hex
+        );
+        assert_eq!(parse_checksum(&list, Some("dora.tar.gz")), Some(hex));
+        assert_eq!(parse_checksum(&list, Some("missing.zip")), None);
+        assert_eq!(parse_checksum("not a checksum", None), None);
+    }
+
+    #[test]
+    fn check_rejects_checksum_mismatch() {
+        let good = sha256_digest(b"dora");
+        let plan = VerifyPlan {
+            checksum: Some((good[7..].to_string(), "SHA256SUMS".into())),
+            ..Default::default()
+        };
+        let archive = Path::new("dora.zip");
+
+        let verified = plan.check(archive, good.clone(), "dora.zip").unwrap();
+        assert_eq!(verified.digest, good);
+        assert_eq!(verified.verified_by, vec!["checksum-file"]);
+
+        let err = plan
+            .check(archive, sha256_digest(b"tampered"), "dora.zip")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("SHA256SUMS"), "{err}");
+        assert!(VerifyPlan::default().is_empty());
+    }
+}
@@ -1,12 +1,33 @@
+//! Core library for Dora Manager.
+//!
+//! Every domain has exactly one implementation, living in its module
+//! directory: `node/`, `install/`, `events/`, `dataflow/`, `runs/`. The
+//! crate-root functions (`up`, `status`, `doctor`, ...) are re-exports of
+//! `api/` — add new behaviour there rather than alongside it. All on-disk
+//! state is rooted at the dm home (`~/.dm` by default).
+
 mod api;
 pub mod config;
 pub mod dataflow;
 pub mod dora;
+pub mod encryption;
 pub mod env;
+pub mod error;
 pub mod events;
+pub mod http;
 pub mod install;
+pub mod lock;
+#[cfg(feature = "mock-runtime")]
+pub mod mock_runtime;
 pub mod node;
+pub mod permissions;
+pub mod plugins;
+mod process;
 pub mod runs;
+pub mod runtime_manager;
+pub mod runtime_pids;
+pub mod secrets;
+pub mod shim;
 pub mod types;
 pub mod util;
 
@@ -16,6 +37,11 @@ mod test_support;
 mod tests;
 
 pub use api::{
-    auto_down_if_idle, doctor, down, ensure_runtime_up, is_runtime_running, passthrough, setup,
-    status, uninstall, up, use_version, versions,
+    add_machine, auto_down_if_idle, check_nodes, check_thresholds, doctor, doctor_with_history,
+    doctor_with_options, down, emit_threshold_alerts, ensure_runtime_up, explain, explain_topics,
+    is_runtime_running, machine_statuses, machines, matching_rules, migrate, passthrough,
+    probe_nodes, ps, remove_version_alias, resolve_installed_version, run_rule, set_version_alias,
+    setup, status, uninstall, up, up_with_env, use_version, version_aliases, versions,
+    versions_with, DoctorOptions, RuntimeSupervisor, SupervisorAction, SupervisorOptions,
+    BUILTIN_ALIASES, RESERVED_ALIASES, RUNTIME_RESTART_ACTIVITY,
 };
@@ -0,0 +1,452 @@
+//! Advisory lock files that keep dm-cli and dm-server from interleaving
+//! destructive operations on the same home.
+//!
+//! Each mutating operation takes `<home>/locks/<resource>.lock` for its
+//! duration: `install` for dora versions, `node-<id>` for node installs,
+//! `config` for config.toml read-modify-write cycles (see
+//! [`crate::config::modify_config`]) and `dataflow-<name>` for dataflow
+//! saves. A lock whose owning process has exited is reclaimed
+//! automatically. Contention surfaces as [`ResourceBusy`], which dm-server
+//! answers with 409 Conflict.
+//!
+//! Lock files are only ever removed by renaming them to a tombstone first
+//! and checking the tombstone still names the expected owner (by nonce), so
+//! two processes reclaiming the same stale lock, or a release racing a
+//! forced takeover, never delete a lock someone else just took.
+
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Config writes are short, so they always wait a little for each other.
+pub const CONFIG_LOCK_WAIT: Duration = Duration::from_secs(2);
+
+/// How a busy lock is handled. Set once per process (the CLI maps its
+/// `--wait` / `--force-lock` flags onto it).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LockPolicy {
+    /// How long to wait for a busy lock before giving up.
+    pub wait: Duration,
+    /// Take the lock even if another live process holds it.
+    pub force: bool,
+}
+
+static POLICY: RwLock<LockPolicy> = RwLock::new(LockPolicy {
+    wait: Duration::ZERO,
+    force: false,
+});
+
+pub fn set_policy(policy: LockPolicy) {
+    if let Ok(mut current) = POLICY.write() {
+        *current = policy;
+    }
+}
+
+pub fn policy() -> LockPolicy {
+    POLICY.read().map(|p| *p).unwrap_or_default()
+}
+
+/// Contents of a lock file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockOwner {
+    pub pid: u32,
+    pub operation: String,
+    pub acquired_at: String,
+    /// Unique per acquisition; tells a lock apart from a later one taken
+    /// by the same process
+    #[serde(default)]
+    pub nonce: String,
+}
+
+/// Returned when another process holds the lock. Downcast an
+/// `anyhow::Error` to this to tell contention apart from other failures.
+#[derive(Debug, Clone)]
+pub struct ResourceBusy {
+    pub resource: String,
+    pub owner: LockOwner,
+}
+
+impl std::fmt::Display for ResourceBusy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Another dm operation is in progress: '{}' is held by pid {} ({}, since {}). Retry with --wait, or --force-lock if that process is stuck.",
+            self.resource, self.owner.pid, self.owner.operation, self.owner.acquired_at
+        )
+    }
+}
+
+impl std::error::Error for ResourceBusy {}
+
+/// A held lock; released on drop.
+#[derive(Debug)]
+pub struct HomeLock {
+    path: PathBuf,
+    nonce: String,
+}
+
+/// Outcome of one attempt to create the lock file.
+enum Attempt {
+    Acquired(HomeLock),
+    /// Held by a live process (or mid-write); poll again.
+    Busy(Option<LockOwner>),
+}
+
+impl HomeLock {
+    /// Take the lock for `resource` using the process-wide [`LockPolicy`].
+    pub fn acquire(home: &Path, resource: &str, operation: &str) -> Result<Self> {
+        Self::acquire_with(home, resource, operation, policy())
+    }
+
+    pub fn acquire_with(
+        home: &Path,
+        resource: &str,
+        operation: &str,
+        policy: LockPolicy,
+    ) -> Result<Self> {
+        let request = LockRequest::new(home, resource, operation, policy)?;
+        loop {
+            match request.attempt()? {
+                Attempt::Acquired(lock) => return Ok(lock),
+                Attempt::Busy(holder) => request.check_deadline(holder)?,
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// [`HomeLock::acquire`] for async callers: waits for a busy lock
+    /// without blocking the runtime's worker thread.
+    pub async fn acquire_async(home: &Path, resource: &str, operation: &str) -> Result<Self> {
+        Self::acquire_with_async(home, resource, operation, policy()).await
+    }
+
+    pub async fn acquire_with_async(
+        home: &Path,
+        resource: &str,
+        operation: &str,
+        policy: LockPolicy,
+    ) -> Result<Self> {
+        let request = LockRequest::new(home, resource, operation, policy)?;
+        loop {
+            match request.attempt()? {
+                Attempt::Acquired(lock) => return Ok(lock),
+                Attempt::Busy(holder) => request.check_deadline(holder)?,
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+impl Drop for HomeLock {
+    fn drop(&mut self) {
+        // A forced takeover may have replaced the file; leave theirs alone.
+        remove_if_owned(&self.path, |owner| {
+            owner.is_some_and(|owner| owner.nonce == self.nonce)
+        });
+    }
+}
+
+struct LockRequest {
+    resource: String,
+    path: PathBuf,
+    nonce: String,
+    content: String,
+    policy: LockPolicy,
+    deadline: Instant,
+}
+
+impl LockRequest {
+    fn new(home: &Path, resource: &str, operation: &str, policy: LockPolicy) -> Result<Self> {
+        let dir = locks_dir(home);
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let nonce = uuid::Uuid::new_v4().simple().to_string();
+        let owner = LockOwner {
+            pid: std::process::id(),
+            operation: operation.to_string(),
+            acquired_at: chrono::Utc::now().to_rfc3339(),
+            nonce: nonce.clone(),
+        };
+        Ok(Self {
+            resource: resource.to_string(),
+            path: dir.join(format!("{}.lock", sanitize(resource))),
+            nonce,
+            content: serde_json::to_string(&owner)?,
+            policy,
+            deadline: Instant::now() + policy.wait,
+        })
+    }
+
+    /// Create the lock file, reclaiming it first if its owner is gone (or
+    /// the policy forces a takeover).
+    fn attempt(&self) -> Result<Attempt> {
+        loop {
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&self.path)
+            {
+                Ok(mut file) => {
+                    use std::io::Write;
+                    file.write_all(self.content.as_bytes())
+                        .with_context(|| format!("Failed to write {}", self.path.display()))?;
+                    return Ok(Attempt::Acquired(HomeLock {
+                        path: self.path.clone(),
+                        nonce: self.nonce.clone(),
+                    }));
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {}
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("Failed to create {}", self.path.display()))
+                }
+            }
+
+            let owner = read_owner(&self.path);
+            if owner.is_none() && recently_modified(&self.path) {
+                // Another process is between creating and writing the file.
+                return Ok(Attempt::Busy(None));
+            }
+            let live_holder = owner.clone().filter(|owner| pid_alive(owner.pid));
+            if let Some(holder) = live_holder.filter(|_| !self.policy.force) {
+                return Ok(Attempt::Busy(Some(holder)));
+            }
+            // Stale (owner exited or unreadable) or forced: take it over,
+            // unless someone else already did.
+            remove_if_owned(&self.path, |current| current == owner.as_ref());
+        }
+    }
+
+    fn check_deadline(&self, holder: Option<LockOwner>) -> Result<()> {
+        match holder {
+            Some(owner) if Instant::now() >= self.deadline => Err(ResourceBusy {
+                resource: self.resource.clone(),
+                owner,
+            }
+            .into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Remove the lock file at `path` if `expected` accepts its owner. The file
+/// is renamed to a unique tombstone first, so only one process can take it;
+/// a file that turns out to belong to someone else is linked back in place.
+fn remove_if_owned(path: &Path, expected: impl FnOnce(Option<&LockOwner>) -> bool) {
+    let tombstone = path.with_extension(format!("{}.stale", uuid::Uuid::new_v4().simple()));
+    if std::fs::rename(path, &tombstone).is_err() {
+        return;
+    }
+    if !expected(read_owner(&tombstone).as_ref()) {
+        // `hard_link` fails if a new lock appeared meanwhile; that one wins.
+        let _ = std::fs::hard_link(&tombstone, path);
+    }
+    let _ = std::fs::remove_file(&tombstone);
+}
+
+pub fn locks_dir(home: &Path) -> PathBuf {
+    home.join("locks")
+}
+
+/// Resource a dm-server process holds for as long as it serves the home,
+/// one per process so several servers can share a home.
+pub fn server_resource(pid: u32) -> String {
+    format!("server-{}", pid)
+}
+
+/// Live dm-server processes serving `home` (see [`server_resource`]).
+pub fn running_servers(home: &Path) -> Vec<LockOwner> {
+    let Ok(entries) = std::fs::read_dir(locks_dir(home)) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            name.starts_with("server-") && name.ends_with(".lock")
+        })
+        .filter_map(|entry| read_owner(&entry.path()))
+        .filter(|owner| pid_alive(owner.pid))
+        .collect()
+}
+
+/// Current holder of `resource`, if any live process holds it.
+pub fn holder(home: &Path, resource: &str) -> Option<LockOwner> {
+    let path = locks_dir(home).join(format!("{}.lock", sanitize(resource)));
+    read_owner(&path).filter(|owner| pid_alive(owner.pid))
+}
+
+fn read_owner(path: &Path) -> Option<LockOwner> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn recently_modified(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age < Duration::from_secs(5))
+}
+
+fn sanitize(resource: &str) -> String {
+    resource
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Whether a process with this pid is running.
+#[cfg(unix)]
+pub(crate) fn pid_alive(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(true)
+}
+
+#[cfg(not(unix))]
+pub(crate) fn pid_alive(_pid: u32) -> bool {
+    // No cheap liveness probe; treat the lock as held (`--force-lock` clears it).
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn second_acquire_reports_holder_until_released() {
+        let tmp = tempfile::tempdir().unwrap();
+        let first = HomeLock::acquire_with(
+            tmp.path(),
+            "node-demo",
+            "node install",
+            LockPolicy::default(),
+        )
+        .unwrap();
+
+        let err = HomeLock::acquire_with(
+            tmp.path(),
+            "node-demo",
+            "node install",
+            LockPolicy::default(),
+        )
+        .unwrap_err();
+        let busy = err.downcast_ref::<ResourceBusy>().unwrap();
+        assert_eq!(busy.owner.pid, std::process::id());
+        assert!(err.to_string().contains("held by pid"));
+
+        drop(first);
+        assert!(holder(tmp.path(), "node-demo").is_none());
+        HomeLock::acquire_with(
+            tmp.path(),
+            "node-demo",
+            "node install",
+            LockPolicy::default(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn force_takes_over_a_held_lock() {
+        let tmp = tempfile::tempdir().unwrap();
+        let _held =
+            HomeLock::acquire_with(tmp.path(), "config", "config write", LockPolicy::default())
+                .unwrap();
+
+        let forced = LockPolicy {
+            force: true,
+            ..Default::default()
+        };
+        let taken = HomeLock::acquire_with(tmp.path(), "config", "config write", forced).unwrap();
+
+        // Releasing the displaced lock must not delete the new holder's file.
+        drop(_held);
+        assert!(holder(tmp.path(), "config").is_some());
+        drop(taken);
+        assert!(holder(tmp.path(), "config").is_none());
+    }
+
+    #[test]
+    fn reclaim_leaves_a_lock_replaced_since_it_was_read() {
+        let tmp = tempfile::tempdir().unwrap();
+        let lock = HomeLock::acquire_with(tmp.path(), "install", "install", LockPolicy::default())
+            .unwrap();
+        let stale = LockOwner {
+            pid: 1,
+            operation: "install".to_string(),
+            acquired_at: "2024-01-01T00:00:00Z".to_string(),
+            nonce: "gone".to_string(),
+        };
+
+        remove_if_owned(&lock.path, |current| current == Some(&stale));
+        let current = read_owner(&lock.path).unwrap();
+        assert_eq!(current.nonce, lock.nonce);
+        let leftovers = std::fs::read_dir(locks_dir(tmp.path())).unwrap().count();
+        assert_eq!(leftovers, 1);
+    }
+
+    #[tokio::test]
+    async fn async_acquire_waits_for_release() {
+        let tmp = tempfile::tempdir().unwrap();
+        let held =
+            HomeLock::acquire_with(tmp.path(), "config", "config write", LockPolicy::default())
+                .unwrap();
+        let release = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            drop(held);
+        });
+
+        let wait = LockPolicy {
+            wait: Duration::from_secs(5),
+            ..Default::default()
+        };
+        HomeLock::acquire_with_async(tmp.path(), "config", "config write", wait)
+            .await
+            .unwrap();
+        release.await.unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn stale_lock_of_dead_process_is_reclaimed() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let dead_pid = child.id();
+        child.wait().unwrap();
+
+        std::fs::create_dir_all(locks_dir(tmp.path())).unwrap();
+        std::fs::write(
+            locks_dir(tmp.path()).join("install.lock"),
+            serde_json::to_string(&LockOwner {
+                pid: dead_pid,
+                operation: "install".to_string(),
+                acquired_at: "2024-01-01T00:00:00Z".to_string(),
+                nonce: String::new(),
+            })
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert!(
+            HomeLock::acquire_with(tmp.path(), "install", "install", LockPolicy::default()).is_ok()
+        );
+    }
+}
@@ -0,0 +1,335 @@
+//! In-process stand-in for the dora CLI (`--mock-runtime`, behind the
+//! `mock-runtime` feature).
+//!
+//! When enabled, every dora invocation dm makes is answered here instead of
+//! spawning a binary, so `up`, `down`, `status`, `start` and `stop` work on
+//! machines where dora cannot be installed. Output mimics the real CLI
+//! closely enough for dm's parsers: `list` prints the usual table (or JSON
+//! lines), `start` prints `dataflow start triggered: <uuid>` and writes
+//! per-node `out/<uuid>/log_<node>.txt` files, and node lifecycle is
+//! reported as `node.spawn` / `node.exit` events.
+//!
+//! State lives in `<home>/mock-runtime.json` so separate `dm` invocations
+//! and dm-server see the same fake coordinator.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::events::{try_emit, EventBuilder, EventSource};
+
+/// Version reported by `dora --version` under the mock runtime.
+pub const MOCK_VERSION: &str = "0.0.0-mock";
+/// Setting this to `1` enables the mock runtime, e.g. for dm-server or the
+/// `dm` processes it spawns.
+pub const MOCK_RUNTIME_ENV_KEY: &str = "DM_MOCK_RUNTIME";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+/// Serialises read-modify-write of the state file within one process.
+static STATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Serve dora invocations from the mock runtime for the rest of the process.
+pub fn enable() {
+    ENABLED.store(true, Ordering::SeqCst);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+        || std::env::var(MOCK_RUNTIME_ENV_KEY).is_ok_and(|v| v.trim() == "1")
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct MockState {
+    running: bool,
+    #[serde(default)]
+    dataflows: Vec<MockDataflow>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct MockDataflow {
+    uuid: String,
+    name: String,
+    status: String,
+    nodes: Vec<String>,
+    out_dir: PathBuf,
+}
+
+fn state_path(home: &Path) -> PathBuf {
+    home.join("mock-runtime.json")
+}
+
+fn load_state(home: &Path) -> MockState {
+    std::fs::read_to_string(state_path(home))
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(home: &Path, state: &MockState) -> Result<()> {
+    let text = serde_json::to_string_pretty(state)?;
+    std::fs::write(state_path(home), text).context("Failed to write mock runtime state")
+}
+
+/// Answer `dora <args>` as `(exit_code, stdout, stderr)`.
+pub(crate) fn run(home: &Path, args: &[String]) -> (i32, String, String) {
+    let _guard = STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut state = load_state(home);
+    match dispatch(home, &mut state, args) {
+        Ok((stdout, changed)) => {
+            if changed {
+                if let Err(e) = save_state(home, &state) {
+                    return (1, String::new(), format!("{e:#}"));
+                }
+            }
+            (0, stdout, String::new())
+        }
+        Err(e) => (1, String::new(), format!("{e:#}")),
+    }
+}
+
+/// Returns stdout and whether the state changed.
+fn dispatch(home: &Path, state: &mut MockState, args: &[String]) -> Result<(String, bool)> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["--version", ..] => Ok((format!("dora-cli {MOCK_VERSION}\n"), false)),
+        ["check", ..] => {
+            require_running(state)?;
+            Ok(("Dora Coordinator: ok\nDora Daemon: ok\n".to_string(), false))
+        }
+        ["up", ..] => {
+            state.running = true;
+            Ok((
+                "started mock dora coordinator and daemon\n".to_string(),
+                true,
+            ))
+        }
+        ["destroy", ..] => {
+            require_running(state)?;
+            for flow in state.dataflows.iter_mut().filter(|f| f.status == "Running") {
+                finish(home, flow, "Stopped");
+            }
+            state.running = false;
+            Ok(("destroyed mock dora runtime\n".to_string(), true))
+        }
+        ["list", rest @ ..] => {
+            require_running(state)?;
+            let json = rest.windows(2).any(|w| w == ["--format", "json"]);
+            Ok((list(state, json), false))
+        }
+        ["node", "list", rest @ ..] => {
+            require_running(state)?;
+            let uuid = flag_value(rest, "--dataflow");
+            Ok((node_list(state, uuid), false))
+        }
+        ["start", path, rest @ ..] => {
+            require_running(state)?;
+            let uuid = start(home, state, Path::new(path), flag_value(rest, "--name"))?;
+            Ok((format!("dataflow start triggered: {uuid}\n"), true))
+        }
+        ["stop", uuid, ..] => {
+            require_running(state)?;
+            let Some(flow) = state
+                .dataflows
+                .iter_mut()
+                .find(|f| f.uuid == *uuid && f.status == "Running")
+            else {
+                bail!("no running dataflow with ID `{uuid}`");
+            };
+            finish(home, flow, "Stopped");
+            Ok((format!("dataflow stopped: {uuid}\n"), true))
+        }
+        [command, ..] => bail!("the mock runtime does not support `dora {command}`"),
+        [] => bail!("the mock runtime needs a dora subcommand"),
+    }
+}
+
+fn require_running(state: &MockState) -> Result<()> {
+    if !state.running {
+        bail!("Dora Coordinator: could not connect to mock coordinator (run `dm up`)");
+    }
+    Ok(())
+}
+
+fn flag_value<'a>(args: &[&'a str], flag: &str) -> Option<&'a str> {
+    args.windows(2).find(|w| w[0] == flag).map(|w| w[1])
+}
+
+fn list(state: &MockState, json: bool) -> String {
+    let mut out = String::new();
+    if !json {
+        out.push_str("UUID Name Status Nodes CPU Memory\n");
+    }
+    for flow in &state.dataflows {
+        if json {
+            let line = serde_json::json!({
+                "uuid": flow.uuid,
+                "name": flow.name,
+                "status": flow.status,
+                "nodes": flow.nodes.len(),
+                "cpu": 0.0,
+                "memory": 0.0,
+            });
+            out.push_str(&format!("{line}\n"));
+        } else {
+            out.push_str(&format!(
+                "{} {} {} {} 0.0% 0.0 GB\n",
+                flow.uuid,
+                flow.name,
+                flow.status,
+                flow.nodes.len()
+            ));
+        }
+    }
+    out
+}
+
+fn node_list(state: &MockState, uuid: Option<&str>) -> String {
+    state
+        .dataflows
+        .iter()
+        .filter(|f| f.status == "Running" && uuid.is_none_or(|u| u == f.uuid))
+        .flat_map(|flow| {
+            flow.nodes.iter().map(move |node| {
+                format!(
+                    "{}\n",
+                    serde_json::json!({
+                        "node": node,
+                        "status": "Running",
+                        "pid": std::process::id().to_string(),
+                        "cpu": "0.0%",
+                        "memory": "0 MB",
+                        "dataflow": flow.name,
+                    })
+                )
+            })
+        })
+        .collect()
+}
+
+/// Register a dataflow from the YAML at `path`, with one log file and one
+/// `node.spawn` event per node. The name defaults to the containing
+/// directory, i.e. the run id for dm runs.
+fn start(home: &Path, state: &mut MockState, path: &Path, name: Option<&str>) -> Result<String> {
+    let yaml = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read dataflow {}", path.display()))?;
+    let graph: serde_yaml::Value = serde_yaml::from_str(&yaml)
+        .with_context(|| format!("Failed to parse dataflow {}", path.display()))?;
+    let nodes: Vec<String> = graph
+        .get("nodes")
+        .and_then(|n| n.as_sequence())
+        .map(|nodes| {
+            nodes
+                .iter()
+                .filter_map(|n| n.get("id")?.as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    if nodes.is_empty() {
+        bail!("dataflow {} has no nodes", path.display());
+    }
+
+    let base = path.parent().unwrap_or(Path::new("."));
+    let name = name.map(str::to_string).unwrap_or_else(|| {
+        base.file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "dataflow".to_string())
+    });
+    let uuid = uuid::Uuid::new_v4().to_string();
+    let out_dir = base.join("out").join(&uuid);
+    std::fs::create_dir_all(&out_dir)?;
+
+    let flow = MockDataflow {
+        uuid: uuid.clone(),
+        name,
+        status: "Running".to_string(),
+        nodes,
+        out_dir,
+    };
+    for node in &flow.nodes {
+        log_line(&flow, node, &format!("spawned node `{node}` (mock)"));
+        node_event(home, &flow, node, "node.spawn");
+    }
+    state.dataflows.push(flow);
+    Ok(uuid)
+}
+
+fn finish(home: &Path, flow: &mut MockDataflow, status: &str) {
+    flow.status = status.to_string();
+    for node in &flow.nodes {
+        log_line(flow, node, &format!("node `{node}` finished (mock)"));
+        node_event(home, flow, node, "node.exit");
+    }
+}
+
+/// Append a line in dora's text tracing format to the node's log file.
+fn log_line(flow: &MockDataflow, node: &str, message: &str) {
+    use std::io::Write;
+    let path = flow.out_dir.join(format!("log_{node}.txt"));
+    if let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+    {
+        let now = chrono::Utc::now().to_rfc3339();
+        let _ = writeln!(file, "{now}  INFO dora_mock: {message}");
+    }
+}
+
+fn node_event(home: &Path, flow: &MockDataflow, node: &str, activity: &str) {
+    try_emit(
+        home,
+        EventBuilder::new(EventSource::Dataflow, activity)
+            .case_id(&flow.name)
+            .node_id(node)
+            .attr("dora_uuid", &flow.uuid)
+            .attr("mock", true)
+            .build(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dora(home: &Path, args: &[&str]) -> (i32, String, String) {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        run(home, &args)
+    }
+
+    #[test]
+    fn tracks_runtime_and_dataflow_lifecycle() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        let run_dir = home.join("runs").join("run-1");
+        std::fs::create_dir_all(&run_dir).unwrap();
+        let yaml = run_dir.join("dataflow.transpiled.yml");
+        std::fs::write(&yaml, "nodes:\n  - id: camera\n  - id: detector\n").unwrap();
+
+        assert_eq!(dora(home, &["check"]).0, 1);
+        assert_eq!(dora(home, &["up"]).0, 0);
+        assert_eq!(dora(home, &["check"]).0, 0);
+
+        let (code, stdout, _) = dora(home, &["start", yaml.to_str().unwrap(), "--detach"]);
+        assert_eq!(code, 0);
+        let uuid = crate::runs::runtime::extract_dataflow_id(&stdout).unwrap();
+        assert!(run_dir
+            .join("out")
+            .join(&uuid)
+            .join("log_detector.txt")
+            .exists());
+
+        let (_, table, _) = dora(home, &["list"]);
+        let rows = crate::dora::parse_runtime_infos(&table);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].name.as_deref(), Some("run-1"));
+        assert_eq!(rows[0].status.as_deref(), Some("Running"));
+
+        assert_eq!(dora(home, &["stop", &uuid]).0, 0);
+        assert_eq!(dora(home, &["stop", &uuid]).0, 1);
+        assert_eq!(dora(home, &["destroy"]).0, 0);
+        assert_eq!(dora(home, &["list"]).0, 1);
+    }
+}
@@ -0,0 +1,502 @@
+//! Node config schemas — authoring (`dm.json` `config_schema`) and
+//! validation of config values against them.
+//!
+//! A config schema maps each field name to a JSON Schema (draft 2020-12)
+//! plus the dm extensions `env`, `x-widget` and `"required": true`. Standard
+//! object schemas (`{"type": "object", "properties": ...}`) are accepted and
+//! flattened into that shape; names listed in `required` become
+//! `"required": true`. As in JSON Schema, keys the schema does not declare
+//! are allowed.
+
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::error::DmError;
+use crate::events::{EventSource, OperationEvent};
+
+use super::paths::{resolve_dm_json_path, resolve_node_dir};
+
+const JSON_TYPES: &[&str] = &[
+    "string", "number", "integer", "boolean", "array", "object", "null",
+];
+
+/// Root keywords of an object schema that flattening keeps the meaning of.
+const OBJECT_KEYWORDS: &[&str] = &[
+    "$schema",
+    "type",
+    "title",
+    "description",
+    "properties",
+    "required",
+    "additionalProperties",
+];
+
+/// One value that does not satisfy the schema.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigViolation {
+    /// JSON Pointer (RFC 6901) to the offending value, e.g. `/threshold`.
+    pub pointer: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.pointer.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{} {}", self.pointer, self.message)
+        }
+    }
+}
+
+/// Error from `save_node_config` when the config breaks the node's schema.
+/// Callers that report per-field errors downcast to it.
+#[derive(Debug, Clone)]
+pub struct InvalidConfig {
+    pub node_id: String,
+    pub errors: Vec<ConfigViolation>,
+}
+
+impl std::fmt::Display for InvalidConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let errors: Vec<String> = self.errors.iter().map(ToString::to_string).collect();
+        write!(
+            f,
+            "Config for node '{}' does not match its config_schema: {}",
+            self.node_id,
+            errors.join("; ")
+        )
+    }
+}
+
+impl std::error::Error for InvalidConfig {}
+
+/// Outcome of validating a config object against a node's schema.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ConfigValidation {
+    pub valid: bool,
+    pub errors: Vec<ConfigViolation>,
+}
+
+/// Attach `schema` to a node's `dm.json`, replacing any previous one.
+///
+/// Returns the schema as stored (object schemas are flattened).
+pub fn set_config_schema(home: &Path, id: &str, schema: Value) -> Result<Value> {
+    let op = OperationEvent::new(home, EventSource::Core, "node.schema.set").attr("node_id", id);
+    op.emit_start();
+
+    let result = (|| {
+        let schema = normalize_config_schema(schema)?;
+        let meta_path = resolve_node_dir(home, id)
+            .filter(|dir| dir.exists())
+            .and_then(|_| resolve_dm_json_path(home, id))
+            .filter(|path| path.exists())
+            .ok_or_else(|| DmError::not_found("node", id))?;
+
+        // Edit the raw document so fields this dm version doesn't model survive.
+        let content = std::fs::read_to_string(&meta_path)
+            .with_context(|| format!("Failed to read {}", meta_path.display()))?;
+        let mut meta: Value = serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", meta_path.display()))?;
+        let Some(obj) = meta.as_object_mut() else {
+            bail!("{} is not a JSON object", meta_path.display());
+        };
+        obj.insert("config_schema".to_string(), schema.clone());
+
+        let json = serde_json::to_string_pretty(&meta).context("Failed to serialize dm.json")?;
+        crate::permissions::write_private(&meta_path, json)
+            .with_context(|| format!("Failed to write {}", meta_path.display()))?;
+        Ok(schema)
+    })();
+
+    op.emit_result(&result);
+    result
+}
+
+/// Validate `config` against the `config_schema` of node `id`.
+///
+/// Nodes without a schema accept any config object.
+pub fn validate_node_config(home: &Path, id: &str, config: &Value) -> Result<ConfigValidation> {
+    let node = super::node_status(home, id)?.ok_or_else(|| DmError::not_found("node", id))?;
+    let errors = match &node.config_schema {
+        Some(schema) => validate_config(schema, config),
+        None if config.is_object() => Vec::new(),
+        None => vec![violation("", "config must be a JSON object")],
+    };
+    Ok(ConfigValidation {
+        valid: errors.is_empty(),
+        errors,
+    })
+}
+
+/// Check a schema document and bring it into the field-map shape.
+pub fn normalize_config_schema(schema: Value) -> Result<Value> {
+    let Value::Object(mut root) = schema else {
+        bail!("Config schema must be a JSON object");
+    };
+
+    if root.get("type").and_then(Value::as_str) == Some("object")
+        || root.get("properties").is_some_and(Value::is_object)
+    {
+        for (keyword, value) in &root {
+            if !OBJECT_KEYWORDS.contains(&keyword.as_str()) {
+                bail!(
+                    "Config schema keyword '{}' is not supported at the root; \
+                     constrain each field under 'properties' instead",
+                    keyword
+                );
+            }
+            // Flattening keeps only the fields, so the root stays open.
+            if keyword == "additionalProperties" && value != &Value::Bool(true) {
+                bail!(
+                    "Config schema 'additionalProperties' must be true at the root: \
+                     node configs may carry keys the schema does not declare"
+                );
+            }
+        }
+        let required: Vec<String> = match root.get("required") {
+            Some(Value::Array(names)) => names
+                .iter()
+                .filter_map(|name| name.as_str().map(str::to_string))
+                .collect(),
+            _ => Vec::new(),
+        };
+        root = match root.remove("properties") {
+            Some(Value::Object(properties)) => properties,
+            Some(_) => bail!("Config schema 'properties' must be an object"),
+            None => Map::new(),
+        };
+        for name in required {
+            let field = root
+                .get_mut(&name)
+                .and_then(Value::as_object_mut)
+                .ok_or_else(|| {
+                    anyhow::anyhow!("Required field '{}' is missing from properties", name)
+                })?;
+            field.insert("required".to_string(), Value::Bool(true));
+        }
+    }
+
+    for (name, field) in &root {
+        let Some(field) = field.as_object() else {
+            bail!("Config schema field '{}' must be an object", name);
+        };
+        if let Some(env) = field.get("env") {
+            if !env.is_string() {
+                bail!("Config schema field '{}': 'env' must be a string", name);
+            }
+        }
+        check_types_keyword(field.get("type"))
+            .with_context(|| format!("Config schema field '{}'", name))?;
+        let validator = compile_field(field)
+            .map_err(|e| anyhow::anyhow!("Config schema field '{}': {}", name, e))?;
+        if let Some(default) = field.get("default") {
+            if let Some(err) = check_field(&validator, field, default, "").first() {
+                bail!(
+                    "Config schema field '{}': default {} {}",
+                    name,
+                    default,
+                    err.message
+                );
+            }
+        }
+    }
+
+    Ok(Value::Object(root))
+}
+
+fn check_types_keyword(types: Option<&Value>) -> Result<()> {
+    let names: Vec<&Value> = match types {
+        None => return Ok(()),
+        Some(Value::Array(items)) => items.iter().collect(),
+        Some(other) => vec![other],
+    };
+    for name in names {
+        match name.as_str() {
+            Some(name) if JSON_TYPES.contains(&name) => {}
+            _ => bail!("unknown type {}", name),
+        }
+    }
+    Ok(())
+}
+
+/// Validate a config object against a field-map schema.
+pub fn validate_config(schema: &Value, config: &Value) -> Vec<ConfigViolation> {
+    let Some(fields) = schema.as_object() else {
+        return Vec::new();
+    };
+    let Some(values) = config.as_object() else {
+        return vec![violation("", "config must be a JSON object")];
+    };
+
+    let mut errors = Vec::new();
+    let unconstrained = Map::new();
+    for (name, field) in fields {
+        let field = field.as_object().unwrap_or(&unconstrained);
+        let pointer = format!("/{}", escape_pointer(name));
+        match values.get(name) {
+            // `null` means "unset" throughout dm's config merge.
+            None | Some(Value::Null) => {
+                let required = field.get("required").and_then(Value::as_bool) == Some(true);
+                let has_default = matches!(field.get("default"), Some(d) if !d.is_null());
+                if required && !has_default {
+                    errors.push(violation(&pointer, "is required"));
+                }
+            }
+            Some(value) => match compile_field(field) {
+                Ok(validator) => errors.extend(check_field(&validator, field, value, &pointer)),
+                Err(e) => errors.push(violation(
+                    &pointer,
+                    &format!("has an invalid schema: {}", e),
+                )),
+            },
+        }
+    }
+    errors
+}
+
+/// A validator for one field, without the dm extensions.
+fn compile_field(field: &Map<String, Value>) -> Result<jsonschema::Validator, String> {
+    let mut schema = field.clone();
+    schema.remove("env");
+    schema.remove("x-widget");
+    if schema.get("required").is_some_and(Value::is_boolean) {
+        schema.remove("required");
+    }
+    jsonschema::options()
+        .should_validate_formats(true)
+        .build(&Value::Object(schema))
+        .map_err(|e| e.to_string())
+}
+
+/// Violations of `value` against a field's schema and widget options.
+fn check_field(
+    validator: &jsonschema::Validator,
+    field: &Map<String, Value>,
+    value: &Value,
+    pointer: &str,
+) -> Vec<ConfigViolation> {
+    let mut errors: Vec<ConfigViolation> = validator
+        .iter_errors(value)
+        .map(|e| {
+            violation(
+                &format!("{}{}", pointer, e.instance_path()),
+                &e.masked().to_string(),
+            )
+        })
+        .collect();
+    if let Some(widget) = field.get("x-widget") {
+        errors.extend(check_widget(widget, value, pointer));
+    }
+    errors
+}
+
+/// Values picked through a select/radio/checkbox widget must be offered by it.
+fn check_widget(widget: &Value, value: &Value, pointer: &str) -> Vec<ConfigViolation> {
+    let kind = widget.get("type").and_then(Value::as_str).unwrap_or("");
+    let Some(options) = widget.get("options").and_then(Value::as_array) else {
+        return Vec::new();
+    };
+    let offered = |candidate: &Value| {
+        options.iter().any(|opt| {
+            opt == candidate
+                || opt.get("value") == Some(candidate)
+                // The checkbox widget stores options as strings.
+                || (kind == "checkbox" && candidate.as_str() == Some(opt_label(opt).as_str()))
+        })
+    };
+
+    match (kind, value) {
+        ("select" | "radio", _) if !offered(value) => {
+            vec![violation(
+                pointer,
+                &format!("must be one of {}", list(options)),
+            )]
+        }
+        ("checkbox", Value::Array(items)) => items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| !offered(item))
+            .map(|(idx, _)| {
+                violation(
+                    &format!("{}/{}", pointer, idx),
+                    &format!("must be one of {}", list(options)),
+                )
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn opt_label(opt: &Value) -> String {
+    match opt {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn list(options: &[Value]) -> String {
+    options
+        .iter()
+        .map(Value::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn escape_pointer(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn violation(pointer: &str, message: &str) -> ConfigViolation {
+    ConfigViolation {
+        pointer: pointer.to_string(),
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn schema() -> Value {
+        json!({
+            "threshold": { "type": "number", "minimum": 0, "maximum": 1, "default": 0.5 },
+            "mode": {
+                "default": "fast",
+                "x-widget": { "type": "select", "options": ["fast", "accurate"] }
+            },
+            "labels": { "type": "array", "items": { "type": "string" } },
+            "model": { "type": "string", "required": true }
+        })
+    }
+
+    #[test]
+    fn validate_config_accepts_valid_values() {
+        let config = json!({ "threshold": 0.2, "mode": "accurate", "model": "yolov8n.pt" });
+        assert_eq!(validate_config(&schema(), &config), Vec::new());
+    }
+
+    #[test]
+    fn validate_config_reports_pointer_level_errors() {
+        let config = json!({
+            "threshold": 3,
+            "mode": "slow",
+            "labels": ["person", 7],
+            "a/b": true
+        });
+
+        let errors = validate_config(&schema(), &config);
+        let message = |pointer: &str| {
+            errors
+                .iter()
+                .find(|e| e.pointer == pointer)
+                .map(|e| e.message.clone())
+        };
+
+        assert_eq!(errors.len(), 4);
+        assert_eq!(
+            message("/threshold").as_deref(),
+            Some("value is greater than the maximum of 1")
+        );
+        assert_eq!(
+            message("/labels/1").as_deref(),
+            Some("value is not of type \"string\"")
+        );
+        assert_eq!(message("/model").as_deref(), Some("is required"));
+        assert!(message("/mode").unwrap().contains("\"fast\""));
+        // Undeclared keys are allowed, as in JSON Schema.
+        assert!(message("/a~1b").is_none());
+    }
+
+    #[test]
+    fn validate_config_enforces_every_json_schema_keyword() {
+        let schema = normalize_config_schema(json!({
+            "name": { "type": "string", "pattern": "^[a-z]+$" },
+            "email": { "type": "string", "format": "email" },
+            "source": { "oneOf": [{ "type": "integer" }, { "type": "string", "minLength": 3 }] },
+            "camera": {
+                "type": "object",
+                "properties": { "width": { "type": "integer" } },
+                "additionalProperties": false
+            }
+        }))
+        .unwrap();
+        let valid = json!({
+            "name": "cam",
+            "email": "ops@example.com",
+            "source": 0,
+            "camera": { "width": 640 },
+            "extra": true
+        });
+        assert_eq!(validate_config(&schema, &valid), Vec::new());
+
+        let config = json!({
+            "name": "Cam 1",
+            "email": "not-an-email",
+            "source": "ab",
+            "camera": { "width": 640, "fps": 30 }
+        });
+        let mut pointers: Vec<String> = validate_config(&schema, &config)
+            .into_iter()
+            .map(|e| e.pointer)
+            .collect();
+        pointers.sort();
+        assert_eq!(pointers, ["/camera", "/email", "/name", "/source"]);
+    }
+
+    #[test]
+    fn normalize_flattens_object_schemas_and_marks_required() {
+        let normalized = normalize_config_schema(json!({
+            "type": "object",
+            "properties": {
+                "port": { "type": "integer", "env": "PORT", "default": 8080 }
+            },
+            "required": ["port"]
+        }))
+        .unwrap();
+
+        assert_eq!(normalized["port"]["env"], "PORT");
+        assert_eq!(normalized["port"]["required"], true);
+    }
+
+    #[test]
+    fn normalize_rejects_bad_defaults_and_types() {
+        let err = normalize_config_schema(json!({
+            "port": { "type": "integer", "default": "eighty" }
+        }))
+        .unwrap_err();
+        assert!(err.to_string().contains("not of type \"integer\""), "{err}");
+
+        let err = normalize_config_schema(json!({ "port": { "type": "int" } })).unwrap_err();
+        assert!(format!("{:#}", err).contains("unknown type"));
+
+        let err = normalize_config_schema(json!({ "port": { "minimum": "zero" } })).unwrap_err();
+        assert!(
+            err.to_string().contains("Config schema field 'port'"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn normalize_rejects_root_keywords_it_cannot_keep() {
+        let object = |keyword: &str, value: Value| {
+            let mut schema = json!({ "type": "object", "properties": { "port": {} } });
+            schema[keyword] = value;
+            normalize_config_schema(schema)
+        };
+
+        assert!(object("additionalProperties", json!(true)).is_ok());
+        let err = object("additionalProperties", json!(false)).unwrap_err();
+        assert!(err.to_string().contains("additionalProperties"), "{err}");
+        let err = object("oneOf", json!([{ "required": ["port"] }])).unwrap_err();
+        assert!(
+            err.to_string().contains("'oneOf' is not supported"),
+            "{err}"
+        );
+    }
+}
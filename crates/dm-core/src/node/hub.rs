@@ -1,9 +1,19 @@
 //! Node registry — maps node IDs to their sources.
 //!
-//! The registry is embedded from `registry.json` at compile time.
+//! A snapshot of `registry.json` is embedded at compile time, so lookups,
+//! `dm node search` and graph validation work offline out of the box.
+//! When a remote registry URL is configured (`registry_url` in config.toml
+//! or `DM_REGISTRY_URL`), `refresh_registry()` caches it at
+//! `<home>/registry.json` and its entries override the embedded ones.
+//! `RegistryListing::source` reports which layer answered.
 //! At runtime, `resolve_node_source()` checks:
 //!   1. YAML `source.git` field (highest priority)
-//!   2. This registry (local copy or future remote URL)
+//!   2. This registry (remote cache overlaid on the embedded copy)
+//!
+//! The registry also ships node *collections*, curated sets installable
+//! with `dm node install @name`. `[collections.<name>]` tables in
+//! config.toml add local ones and shadow registry collections of the same
+//! name.
 //!
 //! Registry format:
 //! ```json
@@ -15,25 +25,44 @@
 //!     "some-remote-node": {
 //!       "source": { "type": "git", "url": "https://github.com/..." }
 //!     }
+//!   },
+//!   "collections": {
+//!     "speech-stack": { "description": "...", "nodes": ["dora-vad", "..."] }
 //!   }
 //! }
 //! ```
 
-use serde::Deserialize;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config;
 
 /// Embedded registry JSON from the repo root.
 const REGISTRY_JSON: &str =
     include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/../../registry.json"));
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct Registry {
     nodes: std::collections::BTreeMap<String, RegistryEntry>,
+    #[serde(default)]
+    collections: std::collections::BTreeMap<String, config::CollectionConfig>,
 }
 
 #[derive(Debug, Deserialize)]
 struct RegistryEntry {
     source: RegistrySource,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    version: String,
+    #[serde(default)]
+    runtime: String,
+    #[serde(default)]
+    category: String,
+    #[serde(default)]
+    tags: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -43,35 +72,264 @@ enum RegistrySource {
     Git { url: String },
 }
 
+/// Path of the cached remote registry inside DM_HOME.
+pub fn registry_cache_path(home: &Path) -> PathBuf {
+    home.join("registry.json")
+}
+
+/// Upper bound for an opportunistic registry fetch, so offline use stays snappy.
+const REGISTRY_FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Which registry layer a listing came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegistryOrigin {
+    /// Only the snapshot compiled into dm.
+    Bundled,
+    /// A previously downloaded remote registry overlaid on the snapshot.
+    Cache,
+    /// The remote registry, fetched just now.
+    Remote,
+}
+
+impl RegistryOrigin {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Bundled => "bundled",
+            Self::Cache => "cache",
+            Self::Remote => "remote",
+        }
+    }
+}
+
+/// A registry node as shown by `dm node search` and `/api/registry`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RegistryNode {
+    pub id: String,
+    pub description: String,
+    pub version: String,
+    pub runtime: String,
+    pub category: String,
+    pub tags: Vec<String>,
+    pub source: NodeSource,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RegistryListing {
+    pub source: RegistryOrigin,
+    pub nodes: Vec<RegistryNode>,
+}
+
+impl RegistryListing {
+    /// Keep nodes whose id, description, category or tags contain `query`
+    /// (case-insensitive).
+    pub fn search(mut self, query: &str) -> Self {
+        let query = query.trim().to_lowercase();
+        if !query.is_empty() {
+            self.nodes.retain(|node| {
+                node.id.to_lowercase().contains(&query)
+                    || node.description.to_lowercase().contains(&query)
+                    || node.category.to_lowercase().contains(&query)
+                    || node
+                        .tags
+                        .iter()
+                        .any(|tag| tag.to_lowercase().contains(&query))
+            });
+        }
+        self
+    }
+}
+
+fn load_registry(home: &Path) -> Registry {
+    load_registry_with_origin(home).0
+}
+
+fn load_registry_with_origin(home: &Path) -> (Registry, RegistryOrigin) {
+    let mut registry: Registry = serde_json::from_str(REGISTRY_JSON).unwrap_or_default();
+    let mut origin = RegistryOrigin::Bundled;
+    if let Ok(content) = std::fs::read_to_string(registry_cache_path(home)) {
+        if let Ok(remote) = serde_json::from_str::<Registry>(&content) {
+            registry.nodes.extend(remote.nodes);
+            registry.collections.extend(remote.collections);
+            origin = RegistryOrigin::Cache;
+        }
+    }
+    (registry, origin)
+}
+
+/// List registry nodes without touching the network.
+pub fn registry_listing(home: &Path) -> RegistryListing {
+    let (registry, source) = load_registry_with_origin(home);
+    RegistryListing {
+        source,
+        nodes: registry
+            .nodes
+            .into_iter()
+            .map(|(id, entry)| RegistryNode {
+                id,
+                description: entry.description,
+                version: entry.version,
+                runtime: entry.runtime,
+                category: entry.category,
+                tags: entry.tags,
+                source: entry.source.into(),
+            })
+            .collect(),
+    }
+}
+
+/// List registry nodes, refreshing from the remote registry first when one
+/// is configured and reachable. Falls back to the cache or bundled snapshot.
+pub async fn registry_listing_online(home: &Path) -> RegistryListing {
+    let refreshed = config::registry_url(home).is_some() && refresh_registry(home).await.is_ok();
+    let mut listing = registry_listing(home);
+    if refreshed {
+        listing.source = RegistryOrigin::Remote;
+    }
+    listing
+}
+
+/// Download the configured remote registry and cache it in DM_HOME.
+/// Returns the number of nodes in the remote registry.
+pub async fn refresh_registry(home: &Path) -> Result<usize> {
+    let Some(url) = config::registry_url(home) else {
+        anyhow::bail!(
+            "No registry URL configured. Set `registry_url` in config.toml or DM_REGISTRY_URL."
+        );
+    };
+
+    let resp = crate::http::client_builder(home)?
+        .timeout(REGISTRY_FETCH_TIMEOUT)
+        .build()?
+        .get(&url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to fetch registry from {}", url))?;
+    if !resp.status().is_success() {
+        anyhow::bail!("Registry fetch failed ({}): {}", resp.status(), url);
+    }
+
+    let content = resp.text().await?;
+    let registry: Registry = serde_json::from_str(&content)
+        .with_context(|| format!("Invalid registry JSON at {}", url))?;
+
+    std::fs::create_dir_all(home)?;
+    std::fs::write(registry_cache_path(home), &content)?;
+    Ok(registry.nodes.len())
+}
+
 /// Look up a node in the registry and return its source.
 ///
 /// For `local` sources, returns the absolute path relative to the repo root
 /// (the caller must resolve it against the actual repo/install location).
 /// For `git` sources, returns the git URL.
-pub fn resolve_node_source(node_id: &str) -> Option<NodeSource> {
-    let registry: Registry = serde_json::from_str(REGISTRY_JSON).ok()?;
-    let entry = registry.nodes.get(node_id)?;
-
-    Some(match &entry.source {
-        RegistrySource::Local { path } => NodeSource::Local(path.clone()),
-        RegistrySource::Git { url } => NodeSource::Git(url.clone()),
-    })
+pub fn resolve_node_source(home: &Path, node_id: &str) -> Option<NodeSource> {
+    let mut registry = load_registry(home);
+    let entry = registry.nodes.remove(node_id)?;
+    Some(entry.source.into())
 }
 
 /// List all nodes in the registry.
-pub fn list_registry_nodes() -> Vec<String> {
-    let registry: Registry = serde_json::from_str(REGISTRY_JSON).unwrap_or(Registry {
-        nodes: Default::default(),
-    });
-    registry.nodes.into_keys().collect()
+pub fn list_registry_nodes(home: &Path) -> Vec<String> {
+    load_registry(home).nodes.into_keys().collect()
 }
 
 /// Check if a node exists in the registry.
-pub fn is_in_registry(node_id: &str) -> bool {
-    resolve_node_source(node_id).is_some()
+pub fn is_in_registry(home: &Path, node_id: &str) -> bool {
+    resolve_node_source(home, node_id).is_some()
+}
+
+/// Whether a dataflow `path:` names a downloadable registry node by id
+/// (`path: dora-yolo`) rather than an executable: a bare name that is not
+/// on `PATH` and has a `git` source in the registry. Bundled (`local`)
+/// entries are left alone, since their ids double as dora-hub executables.
+pub fn names_registry_node(home: &Path, path: &str) -> bool {
+    !path.is_empty()
+        && !path.contains(['/', '\\', '.'])
+        && matches!(resolve_node_source(home, path), Some(NodeSource::Git(_)))
+        && which::which(path).is_err()
+}
+
+/// Where a collection is defined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CollectionOrigin {
+    Registry,
+    Local,
+}
+
+/// A named set of nodes, as shown by `dm node collections` and `/api/collections`.
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeCollection {
+    pub name: String,
+    pub description: String,
+    pub nodes: Vec<String>,
+    pub origin: CollectionOrigin,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// Registry collections overlaid with the local ones from config.toml.
+pub fn list_collections(home: &Path) -> Vec<NodeCollection> {
+    let mut collections: std::collections::BTreeMap<String, NodeCollection> = load_registry(home)
+        .collections
+        .into_iter()
+        .map(|(name, c)| {
+            let collection = NodeCollection {
+                name: name.clone(),
+                description: c.description,
+                nodes: c.nodes,
+                origin: CollectionOrigin::Registry,
+            };
+            (name, collection)
+        })
+        .collect();
+    let local = config::load_config(home).unwrap_or_default().collections;
+    for (name, c) in local {
+        collections.insert(
+            name.clone(),
+            NodeCollection {
+                name,
+                description: c.description,
+                nodes: c.nodes,
+                origin: CollectionOrigin::Local,
+            },
+        );
+    }
+    collections.into_values().collect()
+}
+
+pub fn find_collection(home: &Path, name: &str) -> Option<NodeCollection> {
+    list_collections(home).into_iter().find(|c| c.name == name)
+}
+
+/// Expand `@collection` arguments into their node ids, keeping the order
+/// given and dropping duplicates.
+pub fn expand_node_ids(home: &Path, ids: &[String]) -> Result<Vec<String>> {
+    let mut expanded: Vec<String> = Vec::new();
+    for id in ids {
+        let members = match id.strip_prefix('@') {
+            Some(name) => {
+                find_collection(home, name)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Unknown collection '@{}'. See `dm node collections`.",
+                            name
+                        )
+                    })?
+                    .nodes
+            }
+            None => vec![id.clone()],
+        };
+        for member in members {
+            if !expanded.contains(&member) {
+                expanded.push(member);
+            }
+        }
+    }
+    Ok(expanded)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", content = "location", rename_all = "lowercase")]
 pub enum NodeSource {
     /// Local path relative to the dm install/repo root.
     Local(String),
@@ -79,6 +337,15 @@ pub enum NodeSource {
     Git(String),
 }
 
+impl From<RegistrySource> for NodeSource {
+    fn from(source: RegistrySource) -> Self {
+        match source {
+            RegistrySource::Local { path } => NodeSource::Local(path),
+            RegistrySource::Git { url } => NodeSource::Git(url),
+        }
+    }
+}
+
 impl NodeSource {
     /// Resolve a local source to an absolute path given the dm home or repo root.
     pub fn resolve_local(&self, root: &Path) -> Option<PathBuf> {
@@ -98,11 +365,18 @@ impl NodeSource {
 
 #[cfg(test)]
 mod tests {
+    use std::net::TcpListener;
+
+    use tempfile::tempdir;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
     use super::*;
 
     #[test]
     fn registry_loads() {
-        let nodes = list_registry_nodes();
+        let home = tempdir().unwrap();
+        let nodes = list_registry_nodes(home.path());
         assert!(!nodes.is_empty(), "registry should contain nodes");
         assert!(nodes.contains(&"dm-display".to_string()));
         assert!(nodes.contains(&"dora-yolo".to_string()));
@@ -110,7 +384,8 @@ mod tests {
 
     #[test]
     fn resolve_known_node() {
-        let src = resolve_node_source("dm-display");
+        let home = tempdir().unwrap();
+        let src = resolve_node_source(home.path(), "dm-display");
         assert!(src.is_some());
         match src.unwrap() {
             NodeSource::Local(path) => assert!(path.contains("dm-display")),
@@ -118,8 +393,148 @@ mod tests {
         }
     }
 
+    #[test]
+    fn collections_expand_with_local_overrides() {
+        let home = tempdir().unwrap();
+        let bundled = find_collection(home.path(), "speech-stack").unwrap();
+        assert_eq!(bundled.origin, CollectionOrigin::Registry);
+        assert!(bundled.nodes.contains(&"dora-vad".to_string()));
+
+        let mut cfg = config::load_config(home.path()).unwrap();
+        cfg.collections.insert(
+            "speech-stack".into(),
+            config::CollectionConfig {
+                description: "mine".into(),
+                nodes: vec!["dora-vad".into(), "dm-log".into()],
+            },
+        );
+        config::save_config(home.path(), &cfg).unwrap();
+
+        let ids = expand_node_ids(
+            home.path(),
+            &["dm-log".to_string(), "@speech-stack".to_string()],
+        )
+        .unwrap();
+        assert_eq!(ids, vec!["dm-log", "dora-vad"]);
+        assert_eq!(
+            find_collection(home.path(), "speech-stack").unwrap().origin,
+            CollectionOrigin::Local
+        );
+        assert!(expand_node_ids(home.path(), &["@nope".to_string()]).is_err());
+    }
+
     #[test]
     fn resolve_unknown_returns_none() {
-        assert!(resolve_node_source("non-existent-node").is_none());
+        let home = tempdir().unwrap();
+        assert!(resolve_node_source(home.path(), "non-existent-node").is_none());
+    }
+
+    #[tokio::test]
+    async fn refresh_registry_fetches_configured_url_and_overlays_entries() {
+        let home = tempdir().unwrap();
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/registry.json"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(
+                r#"{"nodes":{"remote-node":{"source":{"type":"git","url":"https://git.example.com/remote-node.git"}}}}"#,
+                "application/json",
+            ))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        config::save_config(
+            home.path(),
+            &config::DmConfig {
+                registry_url: Some(format!("{}/registry.json", server.uri())),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let count = refresh_registry(home.path()).await.unwrap();
+        server.verify().await;
+
+        assert_eq!(count, 1);
+        assert_eq!(
+            resolve_node_source(home.path(), "remote-node"),
+            Some(NodeSource::Git(
+                "https://git.example.com/remote-node.git".to_string()
+            ))
+        );
+        assert!(is_in_registry(home.path(), "dm-display"));
+    }
+
+    #[test]
+    fn bundled_listing_works_offline_and_searches_metadata() {
+        let home = tempdir().unwrap();
+        let listing = registry_listing(home.path());
+        assert_eq!(listing.source, RegistryOrigin::Bundled);
+
+        let found = listing.search("interaction");
+        assert!(found.nodes.iter().any(|node| node.id == "dm-button"));
+        assert!(found.nodes.iter().all(|node| node.id != "dm-and"));
+    }
+
+    #[test]
+    fn search_matches_tag_substrings_ignoring_case() {
+        let home = tempdir().unwrap();
+        std::fs::write(
+            registry_cache_path(home.path()),
+            r#"{"nodes":{"tagged-node":{"tags":["Computer-Vision"],"source":{"type":"git","url":"https://git.example.com/t.git"}}}}"#,
+        )
+        .unwrap();
+
+        for query in ["computer-vision", "VISION", "comp"] {
+            let found = registry_listing(home.path()).search(query);
+            assert!(
+                found.nodes.iter().any(|node| node.id == "tagged-node"),
+                "{query} should match the tag"
+            );
+        }
+        let found = registry_listing(home.path()).search("visions");
+        assert!(found.nodes.iter().all(|node| node.id != "tagged-node"));
+    }
+
+    #[test]
+    fn cached_registry_reports_cache_origin() {
+        let home = tempdir().unwrap();
+        std::fs::write(
+            registry_cache_path(home.path()),
+            r#"{"nodes":{"cached-node":{"source":{"type":"git","url":"https://git.example.com/c.git"}}}}"#,
+        )
+        .unwrap();
+
+        let listing = registry_listing(home.path());
+        assert_eq!(listing.source, RegistryOrigin::Cache);
+        assert!(listing.nodes.iter().any(|node| node.id == "cached-node"));
+        assert!(listing.nodes.iter().any(|node| node.id == "dm-display"));
+    }
+
+    #[tokio::test]
+    async fn online_listing_falls_back_when_registry_unreachable() {
+        let home = tempdir().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        drop(listener);
+        config::save_config(
+            home.path(),
+            &config::DmConfig {
+                registry_url: Some(format!("http://{addr}/registry.json")),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let listing = registry_listing_online(home.path()).await;
+        assert_eq!(listing.source, RegistryOrigin::Bundled);
+        assert!(!listing.nodes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn refresh_registry_requires_configured_url() {
+        let home = tempdir().unwrap();
+        let err = refresh_registry(home.path()).await.unwrap_err().to_string();
+        assert!(err.contains("No registry URL configured"));
     }
 }
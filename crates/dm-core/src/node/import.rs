@@ -4,7 +4,9 @@ use std::process::Command;
 use anyhow::{bail, Context, Result};
 use fs_extra::dir::{copy as dir_copy, CopyOptions};
 
+use crate::error::DmError;
 use crate::events::{EventSource, OperationEvent};
+use crate::lock::HomeLock;
 
 use super::init::{init_dm_json, InitHints};
 use super::model::Node;
@@ -25,9 +27,15 @@ pub fn import_local(home: &Path, id: &str, source_dir: &Path) -> Result<Node> {
     op.emit_start();
 
     let result = (|| {
+        let _lock = HomeLock::acquire(home, &format!("node-{}", id), "node import")?;
         let node_path = node_dir(home, id);
         if node_path.exists() {
-            bail!("Node '{}' already exists at {}", id, node_path.display());
+            return Err(DmError::Conflict(format!(
+                "Node '{}' already exists at {}",
+                id,
+                node_path.display()
+            ))
+            .into());
         }
 
         if !source_dir.exists() || !source_dir.is_dir() {
@@ -62,9 +70,15 @@ pub async fn import_git(home: &Path, id: &str, git_url: &str) -> Result<Node> {
     op.emit_start();
 
     let result = async {
+        let _lock = HomeLock::acquire(home, &format!("node-{}", id), "node import")?;
         let node_path = node_dir(home, id);
         if node_path.exists() {
-            bail!("Node '{}' already exists at {}", id, node_path.display());
+            return Err(DmError::Conflict(format!(
+                "Node '{}' already exists at {}",
+                id,
+                node_path.display()
+            ))
+            .into());
         }
 
         std::fs::create_dir_all(&node_path)
@@ -12,6 +12,8 @@ use super::model::{
 pub struct InitHints {
     /// User-provided description (if creating a new node)
     pub description: Option<String>,
+    /// `display.category` for a freshly scaffolded node
+    pub category: Option<String>,
 }
 
 /// Initialize dm.json for a node directory.
@@ -31,7 +33,7 @@ pub fn init_dm_json(id: &str, node_path: &Path, hints: InitHints) -> Result<Node
         // Ensure id matches directory name
         node.id = id.to_string();
         let json = serde_json::to_string_pretty(&node).context("Failed to serialize dm.json")?;
-        std::fs::write(&dm_path, json)
+        crate::permissions::write_private(&dm_path, json)
             .with_context(|| format!("Failed to write dm.json to {}", dm_path.display()))?;
 
         return Ok(node.with_path(node_path.to_path_buf()));
@@ -68,9 +70,9 @@ pub fn init_dm_json(id: &str, node_path: &Path, hints: InitHints) -> Result<Node
         .map(|p| {
             p.authors
                 .iter()
-                .map(|name| NodeMaintainer {
+                .map(|(name, email)| NodeMaintainer {
                     name: name.clone(),
-                    email: None,
+                    email: email.clone(),
                     url: None,
                 })
                 .collect()
@@ -93,7 +95,10 @@ pub fn init_dm_json(id: &str, node_path: &Path, hints: InitHints) -> Result<Node
         repository,
         maintainers,
         license: pyproject.as_ref().and_then(|p| p.license.clone()),
-        display: NodeDisplay::default(),
+        display: NodeDisplay {
+            category: hints.category.unwrap_or_default(),
+            ..NodeDisplay::default()
+        },
         capabilities: Vec::new(),
         runtime,
         ports: Vec::new(),
@@ -101,11 +106,13 @@ pub fn init_dm_json(id: &str, node_path: &Path, hints: InitHints) -> Result<Node
         examples: Vec::new(),
         config_schema: None,
         dynamic_ports: false,
+        pinned: None,
+        last_test: None,
         path: Default::default(),
     };
 
     let json = serde_json::to_string_pretty(&node).context("Failed to serialize dm.json")?;
-    std::fs::write(&dm_path, json)
+    crate::permissions::write_private(&dm_path, json)
         .with_context(|| format!("Failed to write dm.json to {}", dm_path.display()))?;
 
     Ok(node.with_path(node_path.to_path_buf()))
@@ -117,7 +124,8 @@ pub(crate) struct PyProjectInfo {
     pub name: String,
     pub version: Option<String>,
     pub description: Option<String>,
-    pub authors: Vec<String>,
+    /// `(name, email)` of each `[project] authors` entry with a name
+    pub authors: Vec<(String, Option<String>)>,
     pub license: Option<String>,
     pub requires_python: Option<String>,
     pub repository: Option<String>,
@@ -147,6 +155,7 @@ struct PyProjectSection {
 #[derive(Deserialize)]
 struct AuthorEntry {
     name: Option<String>,
+    email: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -170,7 +179,7 @@ struct BuildSystem {
     build_backend: Option<String>,
 }
 
-fn parse_pyproject(node_path: &Path) -> Option<PyProjectInfo> {
+pub(crate) fn parse_pyproject(node_path: &Path) -> Option<PyProjectInfo> {
     let path = node_path.join("pyproject.toml");
     let content = std::fs::read_to_string(&path).ok()?;
     let toml: PyProjectToml = toml::from_str(&content).ok()?;
@@ -185,7 +194,11 @@ fn parse_pyproject(node_path: &Path) -> Option<PyProjectInfo> {
         repository: project
             .urls
             .and_then(|urls| urls.repository.or(urls.repository_lower)),
-        authors: project.authors.into_iter().filter_map(|a| a.name).collect(),
+        authors: project
+            .authors
+            .into_iter()
+            .filter_map(|a| Some((a.name?, a.email)))
+            .collect(),
         build_backend: toml.build_system.and_then(|bs| bs.build_backend),
     })
 }
@@ -285,6 +298,8 @@ fn infer_runtime(
         language: language.to_string(),
         python: pyproject.as_ref().and_then(|py| py.requires_python.clone()),
         platforms: Vec::new(),
+        system_deps: Vec::new(),
+        python_env: Default::default(),
     }
 }
 
@@ -1,18 +1,54 @@
 use std::path::Path;
-use std::process::Command;
 
 use anyhow::{bail, Context, Result};
+use tokio::process::Command;
+use tokio::sync::mpsc;
 
 use crate::events::{EventSource, OperationEvent};
+use crate::lock::HomeLock;
+use crate::types::{InstallPhase, InstallProgress};
 
-use super::model::Node;
+use super::model::{Node, NodeInstallOutcome, PythonEnvStrategy};
 use super::paths::{dm_json_path, resolve_dm_json_path, resolve_node_dir};
 
+/// Knobs for [`install_node_with_options`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NodeInstallOptions {
+    /// Install even when declared `system_deps` are missing
+    pub skip_sysdeps: bool,
+}
+
 pub async fn install_node(home: &Path, id: &str) -> Result<Node> {
+    install_node_with_progress(home, id, None).await
+}
+
+/// Install a node, sending `Building` / `Done` updates through `progress_tx`.
+pub async fn install_node_with_progress(
+    home: &Path,
+    id: &str,
+    progress_tx: Option<mpsc::UnboundedSender<InstallProgress>>,
+) -> Result<Node> {
+    install_node_with_options(home, id, NodeInstallOptions::default(), progress_tx).await
+}
+
+/// Install a node with `options`, reporting progress like
+/// [`install_node_with_progress`].
+pub async fn install_node_with_options(
+    home: &Path,
+    id: &str,
+    options: NodeInstallOptions,
+    progress_tx: Option<mpsc::UnboundedSender<InstallProgress>>,
+) -> Result<Node> {
+    let send = |phase: InstallPhase, message: String| {
+        if let Some(tx) = &progress_tx {
+            let _ = tx.send(InstallProgress { phase, message });
+        }
+    };
     let op = OperationEvent::new(home, EventSource::Core, "node.install").attr("node_id", id);
     op.emit_start();
 
     let result = async {
+        let _lock = HomeLock::acquire(home, &format!("node-{}", id), "node install")?;
         let node_path =
             resolve_node_dir(home, id).unwrap_or_else(|| super::paths::node_dir(home, id));
         let dm_path = resolve_dm_json_path(home, id).unwrap_or_else(|| dm_json_path(home, id));
@@ -26,14 +62,32 @@ pub async fn install_node(home: &Path, id: &str) -> Result<Node> {
         let mut node: Node = serde_json::from_str(&dm_content)
             .with_context(|| format!("Failed to parse dm.json for '{}'", id))?;
 
+        let missing = super::sysdeps::check_system_deps(&node);
+        if !missing.is_empty() {
+            let list = super::sysdeps::describe_missing(&missing);
+            if !options.skip_sysdeps {
+                bail!(
+                    "Node '{}' needs system packages that were not found:\n{}\nInstall them, or retry with --skip-sysdeps.",
+                    id,
+                    list
+                );
+            }
+            let message = format!("Skipping missing system packages:\n{}", list);
+            op.emit_progress("sysdeps", None, message.clone());
+            send(InstallPhase::Building, message);
+        }
+
         let build_type = node.source.build.trim().to_lowercase();
+        let message = format!("Running `{}`", node.source.build);
+        op.emit_progress("building", None, message.clone());
+        send(InstallPhase::Building, message);
         if build_type.starts_with("pip") || build_type.starts_with("uv") {
             let is_local_install = build_type.contains("-e .") || build_type.contains("-e.");
 
             let version = if is_local_install {
-                install_local_python_node(&node_path).await?
+                install_local_python_node(home, &node, &node_path).await?
             } else {
-                install_python_node(&node, &node_path).await?
+                install_python_node(home, &node, &node_path).await?
             };
 
             node.version = version;
@@ -61,10 +115,12 @@ pub async fn install_node(home: &Path, id: &str) -> Result<Node> {
         }
 
         node.installed_at = super::current_timestamp();
+        op.emit_progress("finalizing", Some(100), "Writing dm.json");
 
         let dm_json = serde_json::to_string_pretty(&node).context("Failed to serialize dm.json")?;
-        std::fs::write(&dm_path, dm_json)
+        crate::permissions::write_private(&dm_path, dm_json)
             .with_context(|| format!("Failed to write dm.json to {}", dm_path.display()))?;
+        send(InstallPhase::Done, format!("Installed {}", id));
 
         Ok(node.with_path(node_path))
     }
@@ -74,8 +130,46 @@ pub async fn install_node(home: &Path, id: &str) -> Result<Node> {
     result
 }
 
-async fn install_local_python_node(node_path: &Path) -> Result<String> {
+/// Install `ids` with up to `parallelism` installs running at once (the
+/// `[install] node_parallelism` config when `None`). A failed node does not
+/// stop the others; outcomes are returned in the order of `ids`.
+pub async fn install_nodes(
+    home: &Path,
+    ids: &[String],
+    options: NodeInstallOptions,
+    parallelism: Option<usize>,
+) -> Result<Vec<NodeInstallOutcome>> {
+    use futures_util::StreamExt;
+
+    let parallelism = match parallelism {
+        Some(n) => n,
+        None => crate::config::load_config(home)?.install.node_parallelism,
+    }
+    .max(1);
+
+    Ok(futures_util::stream::iter(ids.iter().cloned())
+        .map(|id: String| async move {
+            match install_node_with_options(home, &id, options, None).await {
+                Ok(node) => NodeInstallOutcome {
+                    node_id: id,
+                    version: Some(node.version),
+                    error: None,
+                },
+                Err(e) => NodeInstallOutcome {
+                    node_id: id,
+                    version: None,
+                    error: Some(format!("{:#}", e)),
+                },
+            }
+        })
+        .buffered(parallelism)
+        .collect()
+        .await)
+}
+
+async fn install_local_python_node(home: &Path, meta: &Node, node_path: &Path) -> Result<String> {
     let venv_path = node_path.join(".venv");
+    let cache_env = shared_cache_env(home, meta)?;
 
     // Remove existing venv to avoid interactive prompt from `uv venv`
     if venv_path.exists() {
@@ -87,17 +181,22 @@ async fn install_local_python_node(node_path: &Path) -> Result<String> {
     let use_uv = Command::new("uv")
         .arg("--version")
         .output()
+        .await
         .map(|output| output.status.success())
         .unwrap_or(false);
 
     let venv_result = if use_uv {
         Command::new("uv")
             .args(["venv", &venv_path.to_string_lossy()])
+            .envs(cache_env.clone())
             .status()
+            .await
     } else {
         Command::new("python3")
             .args(["-m", "venv", &venv_path.to_string_lossy()])
+            .envs(cache_env.clone())
             .status()
+            .await
     };
 
     venv_result
@@ -105,6 +204,7 @@ async fn install_local_python_node(node_path: &Path) -> Result<String> {
         .success()
         .then_some(())
         .ok_or_else(|| anyhow::anyhow!("Failed to create virtual environment"))?;
+    let constraints = constraint_args(home, &venv_path)?;
 
     let install_result = if use_uv {
         Command::new("uv")
@@ -116,13 +216,19 @@ async fn install_local_python_node(node_path: &Path) -> Result<String> {
                 "-e",
                 ".",
             ])
+            .args(&constraints)
             .current_dir(node_path)
+            .envs(cache_env.clone())
             .status()
+            .await
     } else {
         Command::new(format!("{}/bin/pip", venv_path.display()))
             .args(["install", "-e", "."])
+            .args(&constraints)
             .current_dir(node_path)
+            .envs(cache_env.clone())
             .status()
+            .await
     };
 
     match install_result {
@@ -132,8 +238,9 @@ async fn install_local_python_node(node_path: &Path) -> Result<String> {
     }
 }
 
-async fn install_python_node(meta: &Node, node_path: &Path) -> Result<String> {
+async fn install_python_node(home: &Path, meta: &Node, node_path: &Path) -> Result<String> {
     let venv_path = node_path.join(".venv");
+    let cache_env = shared_cache_env(home, meta)?;
 
     // Remove existing venv to avoid interactive prompt from `uv venv`
     if venv_path.exists() {
@@ -145,17 +252,22 @@ async fn install_python_node(meta: &Node, node_path: &Path) -> Result<String> {
     let use_uv = Command::new("uv")
         .arg("--version")
         .output()
+        .await
         .map(|output| output.status.success())
         .unwrap_or(false);
 
     let venv_result = if use_uv {
         Command::new("uv")
             .args(["venv", &venv_path.to_string_lossy()])
+            .envs(cache_env.clone())
             .status()
+            .await
     } else {
         Command::new("python3")
             .args(["-m", "venv", &venv_path.to_string_lossy()])
+            .envs(cache_env.clone())
             .status()
+            .await
     };
 
     venv_result
@@ -168,8 +280,10 @@ async fn install_python_node(meta: &Node, node_path: &Path) -> Result<String> {
         .success()
         .then_some(())
         .ok_or_else(|| anyhow::anyhow!("Failed to create virtual environment"))?;
+    let constraints = constraint_args(home, &venv_path)?;
 
-    let package_spec = package_spec_from_build(meta);
+    let package = package_spec_from_build(meta);
+    let package_spec = super::pin::pinned_package_spec(&package, meta.pinned.as_deref());
     let install_result = if use_uv {
         Command::new("uv")
             .args([
@@ -179,21 +293,55 @@ async fn install_python_node(meta: &Node, node_path: &Path) -> Result<String> {
                 &format!("{}/bin/python", venv_path.display()),
                 &package_spec,
             ])
+            .args(&constraints)
+            .envs(cache_env.clone())
             .status()
+            .await
     } else {
         Command::new(format!("{}/bin/pip", venv_path.display()))
             .args(["install", &package_spec])
+            .args(&constraints)
+            .envs(cache_env.clone())
             .status()
+            .await
     };
 
     match install_result {
-        Ok(status) if status.success() => get_python_package_version(&venv_path, &package_spec),
+        Ok(status) if status.success() => get_python_package_version(&venv_path, &package),
         Ok(_) => bail!("Failed to install package: {}", package_spec),
         Err(err) => bail!("Failed to run pip install: {}", err),
     }
 }
 
-fn package_spec_from_build(meta: &Node) -> String {
+/// `-c <venv>/dm-constraints.txt`, keeping `dora-rs` on the active CLI's
+/// SDK line; empty when no dora version is active.
+fn constraint_args(home: &Path, venv_path: &Path) -> Result<Vec<String>> {
+    Ok(super::sdk::write_constraints(home, venv_path)?
+        .map(|path| vec!["-c".to_string(), path.display().to_string()])
+        .unwrap_or_default())
+}
+
+/// uv/pip environment for `runtime.python_env = "shared"`: the caches live
+/// under `<home>/cache`, on the same filesystem as the node venvs, so uv can
+/// hard-link installed packages instead of copying them.
+fn shared_cache_env(home: &Path, meta: &Node) -> Result<Vec<(&'static str, String)>> {
+    if meta.runtime.python_env != PythonEnvStrategy::Shared {
+        return Ok(Vec::new());
+    }
+    let uv_cache = home.join("cache").join("uv");
+    let pip_cache = home.join("cache").join("pip");
+    for dir in [&uv_cache, &pip_cache] {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    Ok(vec![
+        ("UV_CACHE_DIR", uv_cache.display().to_string()),
+        ("UV_LINK_MODE", "hardlink".to_string()),
+        ("PIP_CACHE_DIR", pip_cache.display().to_string()),
+    ])
+}
+
+pub(crate) fn package_spec_from_build(meta: &Node) -> String {
     let tokens: Vec<&str> = meta.source.build.split_whitespace().collect();
     if tokens.starts_with(&["pip", "install"]) || tokens.starts_with(&["uv", "pip", "install"]) {
         if let Some(last) = tokens.last() {
@@ -209,7 +357,7 @@ fn package_spec_from_build(meta: &Node) -> String {
 }
 
 fn get_python_package_version(venv_path: &Path, package: &str) -> Result<String> {
-    let output = Command::new(format!("{}/bin/python", venv_path.display()))
+    let output = std::process::Command::new(format!("{}/bin/python", venv_path.display()))
         .args([
             "-c",
             &format!(
@@ -236,6 +384,7 @@ async fn install_cargo_node(node: &Node, node_path: &Path) -> Result<String> {
     let cargo_available = Command::new("cargo")
         .arg("--version")
         .output()
+        .await
         .map(|output| output.status.success())
         .unwrap_or(false);
 
@@ -256,10 +405,14 @@ async fn install_cargo_node(node: &Node, node_path: &Path) -> Result<String> {
         command.current_dir(node_path);
     } else {
         command.arg(&package_name);
+        if let Some(version) = &node.pinned {
+            command.arg("--version").arg(version);
+        }
     }
 
     let status = command
         .status()
+        .await
         .with_context(|| "Failed to run cargo install")?;
 
     if !status.success() {
@@ -278,6 +431,7 @@ mod tests {
     use std::fs;
     use std::path::Path;
 
+    use dm_test_utils::{MockCommand, MockCommandHandle, MockResponse};
     use tempfile::tempdir;
 
     use crate::node::{node_dir, NodeDisplay, NodeFiles, NodeRuntime, NodeSource};
@@ -285,19 +439,36 @@ mod tests {
 
     use super::{
         get_python_package_version, install_cargo_node, install_local_python_node, install_node,
-        install_python_node, package_spec_from_build, Node,
+        install_node_with_options, install_nodes, install_python_node, package_spec_from_build,
+        Node, NodeInstallOptions, PythonEnvStrategy,
     };
 
+    /// A `uv` whose `venv` creates `<venv>/bin`, with a `python` reporting
+    /// `python_version` when given, and whose `pip` answers with `pip`.
     #[cfg(not(target_os = "windows"))]
-    fn write_executable(path: &Path, content: &str) {
-        fs::write(path, content).unwrap();
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let mut perms = fs::metadata(path).unwrap().permissions();
-            perms.set_mode(0o755);
-            fs::set_permissions(path, perms).unwrap();
+    fn fake_uv(
+        bin_dir: &Path,
+        python_version: Option<&str>,
+        pip: MockResponse,
+    ) -> MockCommandHandle {
+        let mut venv = "/bin/mkdir -p \"$2/bin\"".to_string();
+        if let Some(version) = python_version {
+            let python = MockCommand::new()
+                .fallback(MockResponse::ok(version))
+                .install_at(&bin_dir.join("python-stub"))
+                .unwrap();
+            venv.push_str(&format!(
+                "\n/bin/cp '{}' \"$2/bin/python\"",
+                python.bin.display()
+            ));
         }
+        MockCommand::new()
+            .respond("--version", MockResponse::ok("uv 0.1"))
+            .respond("venv", MockResponse::script(venv))
+            .respond("pip", pip)
+            .fallback(MockResponse::fail(1, ""))
+            .install_at(&bin_dir.join("uv"))
+            .unwrap()
     }
 
     fn sample_node(id: &str, build: &str) -> Node {
@@ -323,6 +494,8 @@ mod tests {
             examples: Vec::new(),
             config_schema: None,
             dynamic_ports: false,
+            pinned: None,
+            last_test: None,
             path: Default::default(),
         }
     }
@@ -351,9 +524,10 @@ mod tests {
     #[cfg(not(target_os = "windows"))]
     fn get_python_package_version_reads_version_output() {
         let dir = tempdir().unwrap();
-        let python = dir.path().join("bin/python");
-        fs::create_dir_all(python.parent().unwrap()).unwrap();
-        write_executable(&python, "#!/bin/sh\necho 1.2.3\n");
+        MockCommand::new()
+            .fallback(MockResponse::ok("1.2.3"))
+            .install_at(&dir.path().join("bin/python"))
+            .unwrap();
 
         let version = get_python_package_version(dir.path(), "demo").unwrap();
         assert_eq!(version, "1.2.3");
@@ -392,14 +566,17 @@ mod tests {
         fs::create_dir_all(node_path.join(".venv/old")).unwrap();
         fs::write(node_path.join(".venv/old/stale.txt"), "stale").unwrap();
 
-        write_executable(
-            &bin_dir.join("uv"),
-            "#!/bin/sh\nif [ \"$1\" = \"--version\" ]; then echo uv 0.1; exit 0; fi\nif [ \"$1\" = \"venv\" ]; then /bin/mkdir -p \"$2/bin\"; printf '#!/bin/sh\\necho 0.0.0\\n' > \"$2/bin/python\"; /bin/chmod +x \"$2/bin/python\"; exit 0; fi\nif [ \"$1\" = \"pip\" ]; then exit 0; fi\nexit 1\n",
-        );
+        fake_uv(&bin_dir, Some("0.0.0"), MockResponse::ok(""));
 
         let _path = set_path(bin_dir.clone());
         let rt = tokio::runtime::Runtime::new().unwrap();
-        let version = rt.block_on(install_local_python_node(&node_path)).unwrap();
+        let version = rt
+            .block_on(install_local_python_node(
+                dir.path(),
+                &sample_node("demo", "pip install -e ."),
+                &node_path,
+            ))
+            .unwrap();
 
         assert_eq!(version, "0.1.0");
         assert!(!node_path.join(".venv/old/stale.txt").exists());
@@ -416,15 +593,13 @@ mod tests {
         fs::create_dir_all(&bin_dir).unwrap();
         fs::create_dir_all(&node_path).unwrap();
 
-        write_executable(
-            &bin_dir.join("uv"),
-            "#!/bin/sh\nif [ \"$1\" = \"--version\" ]; then echo uv 0.1; exit 0; fi\nif [ \"$1\" = \"venv\" ]; then /bin/mkdir -p \"$2/bin\"; printf '#!/bin/sh\\necho 2.3.4\\n' > \"$2/bin/python\"; /bin/chmod +x \"$2/bin/python\"; exit 0; fi\nif [ \"$1\" = \"pip\" ]; then exit 0; fi\nexit 1\n",
-        );
+        fake_uv(&bin_dir, Some("2.3.4"), MockResponse::ok(""));
 
         let _path = set_path(bin_dir.clone());
         let rt = tokio::runtime::Runtime::new().unwrap();
         let version = rt
             .block_on(install_python_node(
+                dir.path(),
                 &sample_node("demo", "pip install demo-pkg"),
                 &node_path,
             ))
@@ -433,6 +608,114 @@ mod tests {
         assert_eq!(version, "2.3.4");
     }
 
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn install_python_node_uses_the_home_cache_for_shared_envs() {
+        let _guard = env_lock();
+        let dir = tempdir().unwrap();
+        let bin_dir = dir.path().join("bin");
+        let node_path = dir.path().join("node");
+        fs::create_dir_all(&bin_dir).unwrap();
+        fs::create_dir_all(&node_path).unwrap();
+
+        let log = dir.path().join("uv.log");
+        fake_uv(
+            &bin_dir,
+            None,
+            MockResponse::script(format!(
+                "echo \"$UV_CACHE_DIR $UV_LINK_MODE\" > '{}'",
+                log.display()
+            )),
+        );
+
+        let mut node = sample_node("demo", "pip install demo-pkg");
+        node.runtime.python_env = PythonEnvStrategy::Shared;
+        let _path = set_path(bin_dir.clone());
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(install_python_node(dir.path(), &node, &node_path))
+            .unwrap();
+
+        let cache = dir.path().join("cache").join("uv");
+        assert!(cache.is_dir());
+        assert_eq!(
+            fs::read_to_string(&log).unwrap().trim(),
+            format!("{} hardlink", cache.display())
+        );
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn install_python_node_constrains_the_sdk_to_the_active_cli() {
+        let _guard = env_lock();
+        let dir = tempdir().unwrap();
+        let bin_dir = dir.path().join("bin");
+        let node_path = dir.path().join("node");
+        fs::create_dir_all(&bin_dir).unwrap();
+        fs::create_dir_all(&node_path).unwrap();
+        crate::config::save_config(
+            dir.path(),
+            &crate::config::DmConfig {
+                active_version: Some("0.3.9".into()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let uv = fake_uv(&bin_dir, Some("2.3.4"), MockResponse::ok(""));
+
+        let _path = set_path(bin_dir.clone());
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(install_python_node(
+            dir.path(),
+            &sample_node("demo", "pip install demo-pkg"),
+            &node_path,
+        ))
+        .unwrap();
+
+        let constraints = node_path.join(".venv/dm-constraints.txt");
+        assert_eq!(
+            fs::read_to_string(&constraints).unwrap(),
+            "dora-rs==0.3.*\n"
+        );
+        assert!(uv
+            .calls_to("pip")
+            .iter()
+            .any(|args| args.contains(&format!("-c {}", constraints.display()))));
+    }
+
+    #[test]
+    fn install_node_stops_on_missing_system_deps_unless_skipped() {
+        let _guard = env_lock();
+        let dir = tempdir().unwrap();
+        let home = dir.path();
+        let node_path = node_dir(home, "demo");
+        fs::create_dir_all(&node_path).unwrap();
+        let mut node = sample_node("demo", "make install");
+        node.runtime.system_deps =
+            vec![serde_json::from_value(serde_json::json!("dm-no-such-tool")).unwrap()];
+        fs::write(
+            node_path.join("dm.json"),
+            serde_json::to_string_pretty(&node).unwrap(),
+        )
+        .unwrap();
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let err = rt
+            .block_on(install_node(home, "demo"))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("dm-no-such-tool"));
+        assert!(err.contains("--skip-sysdeps"));
+
+        // Skipping the preflight reaches the build step.
+        let options = NodeInstallOptions { skip_sysdeps: true };
+        let err = rt
+            .block_on(install_node_with_options(home, "demo", options, None))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Unsupported build type"));
+    }
+
     #[test]
     #[cfg(not(target_os = "windows"))]
     fn install_node_updates_dm_json_for_local_python_installs() {
@@ -444,10 +727,7 @@ mod tests {
         fs::create_dir_all(&bin_dir).unwrap();
         fs::create_dir_all(&node_path).unwrap();
 
-        write_executable(
-            &bin_dir.join("uv"),
-            "#!/bin/sh\nif [ \"$1\" = \"--version\" ]; then echo uv 0.1; exit 0; fi\nif [ \"$1\" = \"venv\" ]; then /bin/mkdir -p \"$2/bin\"; printf '#!/bin/sh\\necho 0.0.0\\n' > \"$2/bin/python\"; /bin/chmod +x \"$2/bin/python\"; exit 0; fi\nif [ \"$1\" = \"pip\" ]; then exit 0; fi\nexit 1\n",
-        );
+        fake_uv(&bin_dir, Some("0.0.0"), MockResponse::ok(""));
 
         fs::write(
             node_path.join("dm.json"),
@@ -468,6 +748,45 @@ mod tests {
         assert_eq!(persisted.executable, ".venv/bin/demo");
     }
 
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn install_nodes_reports_each_node_in_order() {
+        let _guard = env_lock();
+        let dir = tempdir().unwrap();
+        let home = dir.path();
+        let bin_dir = home.join("bin");
+        fs::create_dir_all(&bin_dir).unwrap();
+        fake_uv(&bin_dir, None, MockResponse::ok(""));
+        for id in ["first", "second"] {
+            let node_path = node_dir(home, id);
+            fs::create_dir_all(&node_path).unwrap();
+            fs::write(
+                node_path.join("dm.json"),
+                serde_json::to_string_pretty(&sample_node(id, "pip install -e .")).unwrap(),
+            )
+            .unwrap();
+        }
+
+        let _path = set_path(bin_dir);
+        let ids = ["first", "ghost", "second"].map(String::from);
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let outcomes = rt
+            .block_on(install_nodes(
+                home,
+                &ids,
+                NodeInstallOptions::default(),
+                Some(2),
+            ))
+            .unwrap();
+
+        let ids: Vec<_> = outcomes.iter().map(|o| o.node_id.as_str()).collect();
+        assert_eq!(ids, ["first", "ghost", "second"]);
+        assert_eq!(outcomes[0].version.as_deref(), Some("0.1.0"));
+        assert!(outcomes[1].error.as_deref().unwrap().contains("not found"));
+        assert!(outcomes[1].version.is_none());
+        assert_eq!(outcomes[2].version.as_deref(), Some("0.1.0"));
+    }
+
     #[test]
     #[cfg(not(target_os = "windows"))]
     fn install_node_supports_local_cargo_path_builds() {
@@ -479,10 +798,22 @@ mod tests {
         fs::create_dir_all(&bin_dir).unwrap();
         fs::create_dir_all(&node_path).unwrap();
 
-        write_executable(
-            &bin_dir.join("cargo"),
-            "#!/bin/sh\nif [ \"$1\" = \"--version\" ]; then echo cargo 1.0; exit 0; fi\nROOT=\"\"\nwhile [ \"$#\" -gt 0 ]; do\n  if [ \"$1\" = \"--root\" ]; then ROOT=\"$2\"; shift 2; continue; fi\n  shift\ndone\n/bin/mkdir -p \"$ROOT/bin\"\nprintf '#!/bin/sh\\necho demo\\n' > \"$ROOT/bin/dora-demo\"\n/bin/chmod +x \"$ROOT/bin/dora-demo\"\nexit 0\n",
-        );
+        MockCommand::new()
+            .respond("--version", MockResponse::ok("cargo 1.0"))
+            .respond(
+                "install",
+                MockResponse::script(
+                    r#"ROOT=""
+while [ "$#" -gt 0 ]; do
+  if [ "$1" = "--root" ]; then ROOT="$2"; shift 2; continue; fi
+  shift
+done
+/bin/mkdir -p "$ROOT/bin"
+/bin/cp "$0" "$ROOT/bin/dora-demo""#,
+                ),
+            )
+            .install_at(&bin_dir.join("cargo"))
+            .unwrap();
 
         fs::write(
             node_path.join("dm.json"),
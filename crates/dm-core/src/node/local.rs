@@ -2,22 +2,55 @@ use std::path::{Component, Path, PathBuf};
 
 use anyhow::{bail, Context, Result};
 
+use crate::error::DmError;
 use crate::events::{EventSource, OperationEvent};
+use crate::lock::HomeLock;
 
 use super::init::{init_dm_json, InitHints};
-use super::model::Node;
+use super::model::{Node, NodeUninstallReport};
 use super::paths::{
     configured_node_dirs, dm_json_path, node_dir, resolve_dm_json_path, resolve_node_dir,
 };
 
+/// Metadata substituted into a new node's scaffold (`pyproject.toml`,
+/// `dm.json`, `README.md`).
+#[derive(Debug, Clone, Default)]
+pub struct NodeScaffold {
+    pub description: String,
+    /// `Name` or `Name <email>`
+    pub author: Option<String>,
+    /// SPDX identifier, e.g. `Apache-2.0`
+    pub license: Option<String>,
+    /// `requires-python`; a bare version such as `3.11` means `>=3.11`
+    pub python: Option<String>,
+    /// `display.category` in dm.json
+    pub category: Option<String>,
+}
+
 pub fn create_node(home: &Path, id: &str, description: &str) -> Result<Node> {
+    create_node_with(
+        home,
+        id,
+        &NodeScaffold {
+            description: description.to_string(),
+            ..Default::default()
+        },
+    )
+}
+
+pub fn create_node_with(home: &Path, id: &str, scaffold: &NodeScaffold) -> Result<Node> {
     let op = OperationEvent::new(home, EventSource::Core, "node.create").attr("node_id", id);
     op.emit_start();
 
     let result = (|| {
         let node_path = node_dir(home, id);
         if node_path.exists() {
-            bail!("Node '{}' already exists at {}", id, node_path.display());
+            return Err(DmError::Conflict(format!(
+                "Node '{}' already exists at {}",
+                id,
+                node_path.display()
+            ))
+            .into());
         }
 
         let module_name = id.replace('-', "_");
@@ -29,17 +62,46 @@ pub fn create_node(home: &Path, id: &str, description: &str) -> Result<Node> {
             )
         })?;
 
+        let description = &scaffold.description;
+        let author = non_empty(&scaffold.author).map(split_author);
+        let license = non_empty(&scaffold.license);
+        let requires_python = non_empty(&scaffold.python)
+            .map(|v| {
+                if v.starts_with(|c: char| c.is_ascii_digit()) {
+                    format!(">={v}")
+                } else {
+                    v.to_string()
+                }
+            })
+            .unwrap_or_else(|| ">=3.10".to_string());
+
+        let mut project_extra = String::new();
+        if let Some(license) = license {
+            project_extra.push_str(&format!("license = \"{}\"\n", toml_escape(license)));
+        }
+        if let Some((name, email)) = &author {
+            let email = email
+                .map(|e| format!(", email = \"{}\"", toml_escape(e)))
+                .unwrap_or_default();
+            project_extra.push_str(&format!(
+                "authors = [{{ name = \"{}\"{} }}]\n",
+                toml_escape(name),
+                email
+            ));
+        }
         let pyproject = format!(
             r#"[project]
 name = "{id}"
 version = "0.1.0"
 description = "{description}"
-requires-python = ">=3.10"
-dependencies = ["dora-rs >= 0.3.9", "pyarrow"]
+requires-python = "{requires_python}"
+{project_extra}dependencies = ["dora-rs >= 0.3.9", "pyarrow"]
 
 [project.scripts]
 {id} = "{module_name}.main:main"
 "#,
+            description = toml_escape(description),
+            requires_python = toml_escape(&requires_python),
         );
         std::fs::write(node_path.join("pyproject.toml"), &pyproject)
             .context("Failed to write pyproject.toml")?;
@@ -65,9 +127,15 @@ if __name__ == "__main__":
         std::fs::write(module_dir.join("__init__.py"), "")
             .context("Failed to write __init__.py")?;
 
-        let readme = format!(
+        let mut readme = format!(
             "# {id}\n\n{description}\n\n## Usage\n\n```yaml\n- id: {id}\n  path: {id}\n  inputs:\n    input: source/output\n  outputs:\n    - output\n```\n",
         );
+        if let Some((name, _)) = &author {
+            readme.push_str(&format!("\n## Author\n\n{name}\n"));
+        }
+        if let Some(license) = license {
+            readme.push_str(&format!("\n## License\n\n{license}\n"));
+        }
         std::fs::write(node_path.join("README.md"), &readme)
             .context("Failed to write README.md")?;
 
@@ -77,6 +145,7 @@ if __name__ == "__main__":
             &node_path,
             InitHints {
                 description: Some(description.to_string()),
+                category: non_empty(&scaffold.category).map(str::to_string),
             },
         )
     })();
@@ -85,6 +154,22 @@ if __name__ == "__main__":
     result
 }
 
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// `Jane Doe <jane@example.com>` → (`Jane Doe`, `Some("jane@example.com")`)
+fn split_author(author: &str) -> (&str, Option<&str>) {
+    match author.split_once('<') {
+        Some((name, rest)) => (name.trim(), Some(rest.trim_end_matches('>').trim())),
+        None => (author, None),
+    }
+}
+
+fn toml_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
 pub fn list_nodes(home: &Path) -> Result<Vec<Node>> {
     let op = OperationEvent::new(home, EventSource::Core, "node.list");
     op.emit_start();
@@ -135,27 +220,54 @@ pub fn list_nodes(home: &Path) -> Result<Vec<Node>> {
     result
 }
 
-pub fn uninstall_node(home: &Path, id: &str) -> Result<()> {
+pub fn uninstall_node(home: &Path, id: &str) -> Result<NodeUninstallReport> {
+    uninstall_node_with(home, id, false)
+}
+
+/// Uninstall node `id` and report saved dataflows that still reference it.
+/// With `tag_dataflows`, each of them is also tagged `missing-node:<id>`.
+pub fn uninstall_node_with(
+    home: &Path,
+    id: &str,
+    tag_dataflows: bool,
+) -> Result<NodeUninstallReport> {
     let op = OperationEvent::new(home, EventSource::Core, "node.uninstall").attr("node_id", id);
     op.emit_start();
 
     let result = (|| {
-        let node_path = node_dir(home, id);
-        if node_path.exists() {
+        {
+            let _lock = HomeLock::acquire(home, &format!("node-{}", id), "node uninstall")?;
+            let node_path = node_dir(home, id);
+            if !node_path.exists() {
+                if resolve_node_dir(home, id).is_some() {
+                    bail!(
+                        "Node '{}' is builtin and cannot be uninstalled from the managed node directory",
+                        id
+                    );
+                }
+                bail!("Node '{}' is not installed", id);
+            }
             std::fs::remove_dir_all(&node_path).with_context(|| {
                 format!("Failed to remove node directory: {}", node_path.display())
             })?;
-            return Ok(());
         }
 
-        if resolve_node_dir(home, id).is_some() {
-            bail!(
-                "Node '{}' is builtin and cannot be uninstalled from the managed node directory",
-                id
-            );
+        // The node is gone either way; a failing scan only loses the report.
+        let referencing_dataflows =
+            crate::dataflow::dataflows_using_node(home, id).unwrap_or_default();
+        let mut tagged_dataflows = Vec::new();
+        if tag_dataflows {
+            for name in &referencing_dataflows {
+                if crate::dataflow::tag_missing_node(home, name, id)? {
+                    tagged_dataflows.push(name.clone());
+                }
+            }
         }
-
-        bail!("Node '{}' is not installed", id);
+        Ok(NodeUninstallReport {
+            node_id: id.to_string(),
+            referencing_dataflows,
+            tagged_dataflows,
+        })
     })();
 
     op.emit_result(&result);
@@ -164,7 +276,7 @@ pub fn uninstall_node(home: &Path, id: &str) -> Result<()> {
 
 pub fn get_node_readme(home: &Path, id: &str) -> Result<String> {
     let readme_path = resolve_node_dir(home, id)
-        .ok_or_else(|| anyhow::anyhow!("Node '{}' does not exist", id))?
+        .ok_or_else(|| DmError::not_found("node", id))?
         .join("README.md");
     std::fs::read_to_string(&readme_path)
         .with_context(|| format!("Failed to read README for node '{}'", id))
@@ -186,8 +298,7 @@ pub fn get_node_config(home: &Path, id: &str) -> Result<serde_json::Value> {
 }
 
 pub fn git_like_file_tree(home: &Path, id: &str) -> Result<Vec<String>> {
-    let node_path = resolve_node_dir(home, id)
-        .ok_or_else(|| anyhow::anyhow!("Node '{}' does not exist", id))?;
+    let node_path = resolve_node_dir(home, id).ok_or_else(|| DmError::not_found("node", id))?;
 
     let mut files = Vec::new();
     collect_node_files(&node_path, &node_path, &mut files)?;
@@ -196,8 +307,7 @@ pub fn git_like_file_tree(home: &Path, id: &str) -> Result<Vec<String>> {
 }
 
 pub fn read_node_file(home: &Path, id: &str, file_path: &str) -> Result<String> {
-    let node_path = resolve_node_dir(home, id)
-        .ok_or_else(|| anyhow::anyhow!("Node '{}' does not exist", id))?;
+    let node_path = resolve_node_dir(home, id).ok_or_else(|| DmError::not_found("node", id))?;
     let root = node_path
         .canonicalize()
         .with_context(|| format!("Failed to resolve node root for '{}'", id))?;
@@ -208,8 +318,7 @@ pub fn read_node_file(home: &Path, id: &str, file_path: &str) -> Result<String>
 }
 
 pub fn read_node_file_bytes(home: &Path, id: &str, file_path: &str) -> Result<Vec<u8>> {
-    let node_path = resolve_node_dir(home, id)
-        .ok_or_else(|| anyhow::anyhow!("Node '{}' does not exist", id))?;
+    let node_path = resolve_node_dir(home, id).ok_or_else(|| DmError::not_found("node", id))?;
     let root = node_path
         .canonicalize()
         .with_context(|| format!("Failed to resolve node root for '{}'", id))?;
@@ -219,18 +328,61 @@ pub fn read_node_file_bytes(home: &Path, id: &str, file_path: &str) -> Result<Ve
         .with_context(|| format!("Failed to read node file '{}'", candidate.display()))
 }
 
+/// Write a node's `config.json`. A config that breaks the node's
+/// `config_schema` is rejected with [`super::InvalidConfig`].
 pub fn save_node_config(home: &Path, id: &str, config: &serde_json::Value) -> Result<()> {
-    let node_path = resolve_node_dir(home, id)
-        .ok_or_else(|| anyhow::anyhow!("Node '{}' does not exist", id))?;
+    let node_path = resolve_node_dir(home, id).ok_or_else(|| DmError::not_found("node", id))?;
     if !node_path.exists() {
-        bail!("Node '{}' does not exist", id);
+        return Err(DmError::not_found("node", id).into());
+    }
+    let validation = super::validate_node_config(home, id, config)?;
+    if !validation.valid {
+        return Err(super::InvalidConfig {
+            node_id: id.to_string(),
+            errors: validation.errors,
+        }
+        .into());
     }
 
     let config_json = serde_json::to_string_pretty(config).context("Failed to serialize config")?;
-    std::fs::write(node_path.join("config.json"), config_json)
+    crate::permissions::write_private(&node_path.join("config.json"), config_json)
         .with_context(|| format!("Failed to write config.json for node '{}'", id))
 }
 
+/// Edit node `id`'s dm.json as a raw document, so fields this dm version
+/// doesn't model survive, and return the updated node. `operation` names
+/// the edit in the lock-conflict error.
+pub(crate) fn edit_dm_json(
+    home: &Path,
+    id: &str,
+    operation: &str,
+    edit: impl FnOnce(&mut serde_json::Map<String, serde_json::Value>),
+) -> Result<Node> {
+    let _lock = HomeLock::acquire(home, &format!("node-{}", id), operation)?;
+    let meta_path = resolve_node_dir(home, id)
+        .filter(|dir| dir.exists())
+        .and_then(|_| resolve_dm_json_path(home, id))
+        .filter(|path| path.exists())
+        .ok_or_else(|| DmError::not_found("node", id))?;
+
+    let content = std::fs::read_to_string(&meta_path)
+        .with_context(|| format!("Failed to read {}", meta_path.display()))?;
+    let mut meta: serde_json::Value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", meta_path.display()))?;
+    let Some(obj) = meta.as_object_mut() else {
+        bail!("{} is not a JSON object", meta_path.display());
+    };
+    edit(obj);
+
+    let json = serde_json::to_string_pretty(&meta).context("Failed to serialize dm.json")?;
+    crate::permissions::write_private(&meta_path, json)
+        .with_context(|| format!("Failed to write {}", meta_path.display()))?;
+
+    let node: Node = serde_json::from_value(meta)
+        .with_context(|| format!("Failed to parse {}", meta_path.display()))?;
+    Ok(node.with_path(resolve_node_dir(home, id).unwrap_or_default()))
+}
+
 pub fn node_status(home: &Path, id: &str) -> Result<Option<Node>> {
     let op = OperationEvent::new(home, EventSource::Core, "node.status").attr("node_id", id);
     op.emit_start();
@@ -2,30 +2,60 @@
 //!
 //! Nodes are installed in `~/.dm/nodes/<id>/` with metadata stored in `dm.json`.
 
+mod config_schema;
 pub mod hub;
 mod import;
 pub(crate) mod init;
 mod install;
 mod local;
 mod model;
+mod outdated;
+mod pack;
 mod paths;
+mod pin;
+mod probe;
 pub mod schema;
+mod sdk;
+mod sysdeps;
+mod venv;
 
 #[cfg(test)]
 mod tests;
 
+pub use config_schema::{
+    normalize_config_schema, set_config_schema, validate_config, validate_node_config,
+    ConfigValidation, ConfigViolation, InvalidConfig,
+};
 pub use import::{import_git, import_local};
-pub use install::install_node;
+pub(crate) use install::package_spec_from_build;
+pub use install::{
+    install_node, install_node_with_options, install_node_with_progress, install_nodes,
+    NodeInstallOptions,
+};
 pub use local::{
-    create_node, get_node_config, get_node_readme, git_like_file_tree, list_nodes, node_status,
-    read_node_file, read_node_file_bytes, save_node_config, uninstall_node,
+    create_node, create_node_with, get_node_config, get_node_readme, git_like_file_tree,
+    list_nodes, node_status, read_node_file, read_node_file_bytes, save_node_config,
+    uninstall_node, uninstall_node_with, NodeScaffold,
 };
 pub use model::{
     Node, NodeCapability, NodeCapabilityBinding, NodeCapabilityDetail, NodeDisplay, NodeExample,
-    NodeFiles, NodeMaintainer, NodePort, NodePortDirection, NodeRepository, NodeRuntime,
-    NodeSource,
+    NodeFiles, NodeInstallOutcome, NodeMaintainer, NodePort, NodePortDirection, NodeRepository,
+    NodeRuntime, NodeSource, NodeTestRecord, NodeUninstallReport, NodeUpdate, NodeVersionCheck,
+    PythonEnvStrategy, SystemDep,
 };
+pub use outdated::{check_outdated_nodes, update_outdated_nodes};
+pub use pack::{import_archive, pack_node, NODE_ARCHIVE_EXTENSION};
+pub(crate) use paths::nodes_dir;
 pub use paths::{dm_json_path, is_managed_node, node_dir, resolve_dm_json_path, resolve_node_dir};
+pub use pin::{ensure_unpinned, pin_node, unpin_node};
+pub(crate) use probe::probe_executable;
+pub use probe::{test_node, DEFAULT_TEST_WINDOW};
+pub use sdk::{
+    check_sdk_versions, default_sdk_specifier, installed_sdk_version, sdk_satisfies, sdk_specifier,
+    DORA_SDK_PACKAGE,
+};
+pub use sysdeps::{check_system_deps, describe_missing, MissingSystemDep};
+pub use venv::{site_packages, venv_python_version};
 
 pub(crate) fn current_timestamp() -> String {
     let now = std::time::SystemTime::now()
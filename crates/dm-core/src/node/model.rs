@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
@@ -153,6 +154,89 @@ pub struct NodeRuntime {
     pub python: Option<String>,
     #[serde(default)]
     pub platforms: Vec<String>,
+    /// System packages the node needs at install and run time (ffmpeg,
+    /// libopencv, ...), checked before `dm node install`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub system_deps: Vec<SystemDep>,
+    /// How the node's `.venv` gets its packages
+    #[serde(default, skip_serializing_if = "PythonEnvStrategy::is_isolated")]
+    pub python_env: PythonEnvStrategy,
+}
+
+/// `isolated` (default) installs into the node's own `.venv` from uv's and
+/// pip's usual caches. `shared` installs from the wheel cache under
+/// `<home>/cache/uv` and hard-links packages into the venv, so nodes that
+/// share heavy dependencies (torch, opencv, ...) keep one copy on disk.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PythonEnvStrategy {
+    #[default]
+    Isolated,
+    Shared,
+}
+
+impl PythonEnvStrategy {
+    pub fn is_isolated(&self) -> bool {
+        *self == Self::Isolated
+    }
+}
+
+/// A system package, written as `"ffmpeg"` or as a table for libraries
+/// that have no executable:
+/// `{ "name": "libopencv", "pkg_config": "opencv4", "install": { "linux": "sudo apt install libopencv-dev" } }`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "SystemDepSpec")]
+pub struct SystemDep {
+    pub name: String,
+    /// Executable to look up on PATH; defaults to `name` unless
+    /// `pkg_config` is set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bin: Option<String>,
+    /// pkg-config module that must exist, e.g. `opencv4`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pkg_config: Option<String>,
+    /// Install command per OS (`linux`, `macos`, `windows`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub install: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SystemDepSpec {
+    Name(String),
+    Full {
+        name: String,
+        #[serde(default)]
+        bin: Option<String>,
+        #[serde(default)]
+        pkg_config: Option<String>,
+        #[serde(default)]
+        install: BTreeMap<String, String>,
+    },
+}
+
+impl From<SystemDepSpec> for SystemDep {
+    fn from(spec: SystemDepSpec) -> Self {
+        match spec {
+            SystemDepSpec::Name(name) => SystemDep {
+                name,
+                bin: None,
+                pkg_config: None,
+                install: BTreeMap::new(),
+            },
+            SystemDepSpec::Full {
+                name,
+                bin,
+                pkg_config,
+                install,
+            } => SystemDep {
+                name,
+                bin,
+                pkg_config,
+                install,
+            },
+        }
+    }
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -179,6 +263,71 @@ pub struct NodeExample {
     pub description: String,
 }
 
+/// What `uninstall_node` left behind outside the node directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NodeUninstallReport {
+    pub node_id: String,
+    /// Saved dataflows whose graph still references the node
+    pub referencing_dataflows: Vec<String>,
+    /// Dataflows tagged `missing-node:<id>` by this uninstall
+    #[serde(default)]
+    pub tagged_dataflows: Vec<String>,
+}
+
+/// An installed node compared against its package index (`dm node outdated`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeVersionCheck {
+    pub node_id: String,
+    /// Package name on the index
+    pub package: String,
+    /// `pypi` or `crates.io`
+    pub index: String,
+    pub installed: String,
+    /// Newest release on the index; `None` when the lookup failed
+    pub latest: Option<String>,
+    pub outdated: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// What `update_outdated_nodes` did with one stale node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeUpdate {
+    pub node_id: String,
+    pub from: String,
+    /// Version after the upgrade; `None` when skipped or failed
+    pub to: Option<String>,
+    /// Why the node was left alone (e.g. pinned)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// What `install_nodes` did with one node of a batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeInstallOutcome {
+    pub node_id: String,
+    /// Installed version; `None` when the install failed
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Outcome of `dm node test`, kept in dm.json as `last_test`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeTestRecord {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    pub detail: String,
+    pub duration_ms: u64,
+    /// RFC 3339 timestamp
+    pub tested_at: String,
+}
+
 /// A managed dora node, persisted as `dm.json` in `~/.dm/nodes/{id}/`.
 ///
 /// This is the single source of truth for node metadata:
@@ -239,6 +388,12 @@ pub struct Node {
     /// for ports not found in `ports`.
     #[serde(default)]
     pub dynamic_ports: bool,
+    /// Version held by `dm node pin`; reinstalls use it and upgrades skip the node.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned: Option<String>,
+    /// Outcome of the latest `dm node test`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_test: Option<NodeTestRecord>,
     /// Runtime-computed absolute path to the node directory.
     /// Not stored in dm.json — populated when loading from disk.
     #[serde(skip_deserializing, default)]
@@ -276,6 +431,8 @@ impl Node {
             examples: Vec::new(),
             config_schema: None,
             dynamic_ports: false,
+            pinned: None,
+            last_test: None,
             path,
         }
     }
@@ -0,0 +1,328 @@
+//! Stale node detection (`dm node outdated`) and bulk upgrades
+//! (`dm node update-all`).
+//!
+//! Nodes installed from PyPI (`pip install <pkg>`) or crates.io
+//! (`cargo install`) are compared against the newest release on their index.
+//! Editable installs (`-e .`, `--path .`) have no index and are skipped.
+//! Pinned nodes are reported but never upgraded.
+
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+
+use crate::events::{EventSource, OperationEvent};
+
+use super::model::{Node, NodeUpdate, NodeVersionCheck};
+
+const PYPI_URL: &str = "https://pypi.org";
+const CRATES_URL: &str = "https://crates.io";
+const INDEX_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Package index lookups; overridable so tests can use a local server.
+pub(crate) struct PackageIndexes {
+    pub pypi: String,
+    pub crates: String,
+}
+
+impl Default for PackageIndexes {
+    fn default() -> Self {
+        Self {
+            pypi: PYPI_URL.to_string(),
+            crates: CRATES_URL.to_string(),
+        }
+    }
+}
+
+/// Compare every installed node with the newest release on its index.
+pub async fn check_outdated_nodes(home: &Path) -> Result<Vec<NodeVersionCheck>> {
+    check_outdated_nodes_with(home, &PackageIndexes::default()).await
+}
+
+/// Reinstall every outdated node that is not pinned.
+pub async fn update_outdated_nodes(home: &Path) -> Result<Vec<NodeUpdate>> {
+    update_outdated_nodes_with(home, &PackageIndexes::default()).await
+}
+
+pub(crate) async fn check_outdated_nodes_with(
+    home: &Path,
+    indexes: &PackageIndexes,
+) -> Result<Vec<NodeVersionCheck>> {
+    let client = crate::http::client_builder(home)?
+        .timeout(INDEX_TIMEOUT)
+        .build()?;
+
+    let mut checks = Vec::new();
+    for node in super::list_nodes(home)? {
+        let Some((index, package)) = index_package(&node) else {
+            continue;
+        };
+        let latest = match index {
+            Index::PyPi => pypi_latest(&client, &indexes.pypi, &package).await,
+            Index::Crates => crates_latest(&client, &indexes.crates, &package).await,
+        };
+        let (latest, error) = match latest {
+            Ok(version) => (Some(version), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+        checks.push(NodeVersionCheck {
+            outdated: latest
+                .as_deref()
+                .is_some_and(|latest| is_newer(latest, &node.version)),
+            node_id: node.id,
+            package,
+            index: index.as_str().to_string(),
+            installed: node.version,
+            latest,
+            pinned: node.pinned,
+            error,
+        });
+    }
+    Ok(checks)
+}
+
+pub(crate) async fn update_outdated_nodes_with(
+    home: &Path,
+    indexes: &PackageIndexes,
+) -> Result<Vec<NodeUpdate>> {
+    let op = OperationEvent::new(home, EventSource::Core, "node.update_all");
+    op.emit_start();
+
+    let result = async {
+        let mut updates = Vec::new();
+        for check in check_outdated_nodes_with(home, indexes).await? {
+            if !check.outdated {
+                continue;
+            }
+            let mut update = NodeUpdate {
+                node_id: check.node_id.clone(),
+                from: check.installed.clone(),
+                to: None,
+                skipped: None,
+                error: None,
+            };
+            if let Some(pinned) = &check.pinned {
+                update.skipped = Some(format!("pinned to {}", pinned));
+            } else {
+                match super::install_node(home, &check.node_id).await {
+                    Ok(node) => update.to = Some(node.version),
+                    Err(err) => update.error = Some(err.to_string()),
+                }
+            }
+            updates.push(update);
+        }
+        Ok(updates)
+    }
+    .await;
+
+    op.emit_result(&result);
+    result
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Index {
+    PyPi,
+    Crates,
+}
+
+impl Index {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::PyPi => "pypi",
+            Self::Crates => "crates.io",
+        }
+    }
+}
+
+/// The index and package a node was installed from, mirroring `install_node`.
+fn index_package(node: &Node) -> Option<(Index, String)> {
+    let build = node.source.build.trim().to_lowercase();
+    if build.starts_with("pip") || build.starts_with("uv") {
+        if build.contains("-e .") || build.contains("-e.") {
+            return None;
+        }
+        let spec = super::package_spec_from_build(node);
+        let name = spec
+            .split(['=', '<', '>', '~', '!', '@', '[', ';', ' '])
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        (!name.is_empty()).then_some((Index::PyPi, name))
+    } else if build.starts_with("cargo") {
+        if build.contains("--path") {
+            return None;
+        }
+        Some((Index::Crates, format!("dora-{}", node.id)))
+    } else {
+        None
+    }
+}
+
+async fn pypi_latest(client: &reqwest::Client, base: &str, package: &str) -> Result<String> {
+    let url = format!("{}/pypi/{}/json", base.trim_end_matches('/'), package);
+    let resp = client.get(&url).send().await?;
+    if !resp.status().is_success() {
+        bail!("PyPI lookup failed ({}): {}", resp.status(), url);
+    }
+    let body: serde_json::Value = resp.json().await?;
+    body["info"]["version"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("PyPI response for '{}' has no version", package))
+}
+
+async fn crates_latest(client: &reqwest::Client, base: &str, package: &str) -> Result<String> {
+    let url = format!("{}/api/v1/crates/{}", base.trim_end_matches('/'), package);
+    let resp = client.get(&url).send().await?;
+    if !resp.status().is_success() {
+        bail!("crates.io lookup failed ({}): {}", resp.status(), url);
+    }
+    let body: serde_json::Value = resp.json().await?;
+    let krate = &body["crate"];
+    krate["max_stable_version"]
+        .as_str()
+        .or_else(|| krate["newest_version"].as_str())
+        .map(str::to_string)
+        .ok_or_else(|| anyhow::anyhow!("crates.io response for '{}' has no version", package))
+}
+
+/// Whether `latest` is a newer release than `installed`. Unknown installed
+/// versions are never reported as outdated.
+fn is_newer(latest: &str, installed: &str) -> bool {
+    if installed.is_empty() || installed == "unknown" {
+        return false;
+    }
+    match (lenient_semver(latest), lenient_semver(installed)) {
+        (Some(latest), Some(installed)) => latest > installed,
+        _ => latest != installed,
+    }
+}
+
+/// Parse `1.2` / `1.2.3` / `v1.2.3`, padding missing components.
+pub(super) fn lenient_semver(version: &str) -> Option<semver::Version> {
+    let version = version.trim().trim_start_matches('v');
+    let parts = version.split('.').count();
+    let padded = match parts {
+        1 => format!("{}.0.0", version),
+        2 => format!("{}.0", version),
+        _ => version.to_string(),
+    };
+    semver::Version::parse(&padded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::matchers::path;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use super::*;
+
+    fn write_node(home: &Path, id: &str, build: &str, version: &str, pinned: Option<&str>) {
+        super::super::create_node(home, id, "").unwrap();
+        let path = super::super::dm_json_path(home, id);
+        let mut meta: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        meta["source"]["build"] = build.into();
+        meta["version"] = version.into();
+        if let Some(pinned) = pinned {
+            meta["pinned"] = pinned.into();
+        }
+        std::fs::write(&path, serde_json::to_string_pretty(&meta).unwrap()).unwrap();
+    }
+
+    async fn serve_json(server: &MockServer, at: &str, body: &str) {
+        Mock::given(path(at))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, "application/json"))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn reports_stale_nodes_and_skips_editable_installs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        write_node(home, "yolo", "pip install dora-yolo", "0.3.1", None);
+        write_node(home, "fresh", "pip install dora-fresh>=1", "1.0", None);
+        write_node(home, "local", "pip install -e .", "0.1.0", None);
+        write_node(home, "queue", "cargo install dora-queue", "unknown", None);
+
+        let server = MockServer::start().await;
+        serve_json(
+            &server,
+            "/pypi/dora-yolo/json",
+            r#"{"info":{"version":"0.4.0"}}"#,
+        )
+        .await;
+        serve_json(
+            &server,
+            "/pypi/dora-fresh/json",
+            r#"{"info":{"version":"1.0.0"}}"#,
+        )
+        .await;
+        serve_json(
+            &server,
+            "/api/v1/crates/dora-queue",
+            r#"{"crate":{"max_stable_version":"0.2.0"}}"#,
+        )
+        .await;
+        let indexes = PackageIndexes {
+            pypi: server.uri(),
+            crates: server.uri(),
+        };
+
+        let mut checks = check_outdated_nodes_with(home, &indexes).await.unwrap();
+        checks.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        let summary: Vec<_> = checks
+            .iter()
+            .map(|c| (c.node_id.as_str(), c.latest.as_deref(), c.outdated))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("fresh", Some("1.0.0"), false),
+                ("queue", Some("0.2.0"), false),
+                ("yolo", Some("0.4.0"), true),
+            ]
+        );
+        assert_eq!(checks[2].index, "pypi");
+    }
+
+    #[tokio::test]
+    async fn update_all_leaves_pinned_nodes_alone() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        write_node(
+            home,
+            "held",
+            "pip install dora-held",
+            "1.0.0",
+            Some("1.0.0"),
+        );
+
+        let server = MockServer::start().await;
+        serve_json(
+            &server,
+            "/pypi/dora-held/json",
+            r#"{"info":{"version":"2.0.0"}}"#,
+        )
+        .await;
+        let indexes = PackageIndexes {
+            pypi: server.uri(),
+            crates: server.uri(),
+        };
+
+        let updates = update_outdated_nodes_with(home, &indexes).await.unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].skipped.as_deref(), Some("pinned to 1.0.0"));
+        assert!(updates[0].to.is_none());
+    }
+
+    #[test]
+    fn version_comparison_is_lenient() {
+        assert!(is_newer("0.4.0", "0.3.9"));
+        assert!(is_newer("1.1", "1.0.5"));
+        assert!(!is_newer("1.0", "1.0.0"));
+        assert!(!is_newer("2.0.0", "unknown"));
+        assert!(is_newer("2024.1b1", "2023.9"));
+    }
+}
@@ -0,0 +1,223 @@
+//! `.dmnode` archives: a node's source tree and `dm.json` as a gzipped
+//! tarball, for moving nodes between machines without a registry.
+//!
+//! The archive holds a single `<id>/` directory. Build output that is
+//! recreated by `dm node install` (`.venv`, `__pycache__`), `.git`, the
+//! saved `config.json` (it may hold credentials) and symlinks (import
+//! refuses them) are left out.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+
+use crate::error::DmError;
+use crate::events::{EventSource, OperationEvent};
+use crate::lock::HomeLock;
+
+use super::init::{init_dm_json, InitHints};
+use super::model::Node;
+use super::paths::{node_dir, resolve_node_dir};
+
+/// File extension of node archives.
+pub const NODE_ARCHIVE_EXTENSION: &str = "dmnode";
+
+/// Left out wherever they appear in the node tree.
+const EXCLUDED: &[&str] = &[".venv", "__pycache__", ".git"];
+/// Left out at the node root only; a nested `config.json` is source.
+const EXCLUDED_AT_ROOT: &[&str] = &["config.json"];
+
+/// Write node `id` to the archive `out`.
+pub fn pack_node(home: &Path, id: &str, out: &Path) -> Result<PathBuf> {
+    let op = OperationEvent::new(home, EventSource::Core, "node.pack")
+        .attr("node_id", id)
+        .attr("out", out.display().to_string());
+    op.emit_start();
+
+    let result = (|| {
+        let node_path = resolve_node_dir(home, id)
+            .filter(|path| path.join("dm.json").exists())
+            .ok_or_else(|| DmError::not_found("node", id))?;
+        let dir_name = node_path
+            .file_name()
+            .context("Node directory has no name")?;
+        if let Some(out_dir) = out.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(out_dir)
+                .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+        }
+
+        let packed = std::fs::File::create(out)
+            .with_context(|| format!("Failed to create {}", out.display()))
+            .and_then(|file| {
+                let gz = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+                let mut tar = tar::Builder::new(gz);
+                append_tree(&mut tar, &node_path, Path::new(dir_name), true)?;
+                tar.into_inner()?.finish()?;
+                Ok(())
+            });
+        if let Err(e) = packed {
+            let _ = std::fs::remove_file(out);
+            return Err(e.context(format!("Failed to pack '{}'", id)));
+        }
+        Ok(out.to_path_buf())
+    })();
+
+    op.emit_result(&result);
+    result
+}
+
+/// Add `dir` to `tar` as `name`, minus the excluded entries and symlinks.
+fn append_tree<W: std::io::Write>(
+    tar: &mut tar::Builder<W>,
+    dir: &Path,
+    name: &Path,
+    root: bool,
+) -> Result<()> {
+    tar.append_dir(name, dir)
+        .with_context(|| format!("Failed to add {}", dir.display()))?;
+    let mut entries = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read {}", dir.display()))?
+        .collect::<std::io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let file_name = entry.file_name();
+        let excluded = EXCLUDED.iter().any(|excluded| file_name == *excluded)
+            || (root
+                && EXCLUDED_AT_ROOT
+                    .iter()
+                    .any(|excluded| file_name == *excluded));
+        let file_type = entry.file_type()?;
+        if excluded || file_type.is_symlink() {
+            continue;
+        }
+        let path = name.join(&file_name);
+        if file_type.is_dir() {
+            append_tree(tar, &entry.path(), &path, false)?;
+        } else {
+            tar.append_path_with_name(entry.path(), &path)
+                .with_context(|| format!("Failed to add {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Unpack a node archive into the managed node directory, as `id` or the
+/// id in its `dm.json`.
+pub fn import_archive(home: &Path, archive: &Path, id: Option<&str>) -> Result<Node> {
+    let op = OperationEvent::new(home, EventSource::Core, "node.import_archive")
+        .attr("archive", archive.display().to_string());
+    op.emit_start();
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let staging = home.join("tmp").join(format!("node-import-{nanos}"));
+
+    let result = (|| {
+        if !archive.is_file() {
+            bail!("Node archive '{}' not found", archive.display());
+        }
+        std::fs::create_dir_all(&staging)
+            .with_context(|| format!("Failed to create {}", staging.display()))?;
+        crate::install::archive::extract_tar(
+            archive,
+            &staging,
+            crate::config::max_extract_bytes(home),
+        )?;
+
+        let dm_path = staging.join("dm.json");
+        if !dm_path.exists() {
+            bail!("'{}' is not a node archive (no dm.json)", archive.display());
+        }
+        let packed: Node = serde_json::from_str(
+            &std::fs::read_to_string(&dm_path).context("Failed to read dm.json")?,
+        )
+        .context("Failed to parse dm.json in archive")?;
+        let id = id.unwrap_or(&packed.id).to_string();
+        if id.is_empty() || id.contains(['/', '\\']) || id.starts_with('.') {
+            bail!("Invalid node id '{}'", id);
+        }
+
+        let _lock = HomeLock::acquire(home, &format!("node-{}", id), "node import")?;
+        let node_path = node_dir(home, &id);
+        if node_path.exists() {
+            return Err(DmError::Conflict(format!(
+                "Node '{}' already exists at {}",
+                id,
+                node_path.display()
+            ))
+            .into());
+        }
+        if let Some(parent) = node_path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::rename(&staging, &node_path)
+            .with_context(|| format!("Failed to move node into {}", node_path.display()))?;
+
+        init_dm_json(&id, &node_path, InitHints::default())
+    })();
+
+    let _ = std::fs::remove_dir_all(&staging);
+    op.emit_result(&result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn pack_and_import_round_trip_without_the_venv() {
+        let source = tempfile::tempdir().unwrap();
+        let target = tempfile::tempdir().unwrap();
+        let node_path = dm_test_utils::fake_node(
+            source.path(),
+            "demo",
+            serde_json::json!({ "source": { "build": "pip install -e ." } }),
+        )
+        .unwrap();
+        std::fs::create_dir_all(node_path.join(".venv/bin")).unwrap();
+        std::fs::create_dir_all(node_path.join("demo")).unwrap();
+        std::fs::write(node_path.join("demo/main.py"), "print('hi')\n").unwrap();
+        std::fs::write(node_path.join(".venv/bin/python"), "").unwrap();
+        std::fs::write(node_path.join("config.json"), r#"{"api_key":"secret"}"#).unwrap();
+        std::fs::write(node_path.join("demo/config.json"), "{}").unwrap();
+        std::os::unix::fs::symlink("/etc", node_path.join("etc")).unwrap();
+
+        let archive = source.path().join("out/demo.dmnode");
+        pack_node(source.path(), "demo", &archive).unwrap();
+
+        let node = import_archive(target.path(), &archive, None).unwrap();
+        assert_eq!(node.id, "demo");
+        let imported = node_dir(target.path(), "demo");
+        assert!(imported.join("demo/main.py").exists());
+        assert!(!imported.join(".venv").exists());
+        assert!(!imported.join("config.json").exists());
+        assert!(imported.join("demo/config.json").exists());
+        assert!(!imported.join("etc").exists());
+
+        let renamed = import_archive(target.path(), &archive, Some("demo-copy")).unwrap();
+        assert_eq!(renamed.id, "demo-copy");
+        let err = import_archive(target.path(), &archive, None).unwrap_err();
+        assert!(err.to_string().contains("already exists"));
+        assert!(!target.path().join("tmp").read_dir().unwrap().any(|_| true));
+    }
+
+    #[test]
+    fn import_refuses_archives_with_links() {
+        let home = tempfile::tempdir().unwrap();
+        let mut tar = tar::Builder::new(Vec::new());
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_size(0);
+        tar.append_link(&mut header, "demo/evil", "..").unwrap();
+        let archive = home.path().join("evil.dmnode");
+        std::fs::write(&archive, tar.into_inner().unwrap()).unwrap();
+
+        let err = import_archive(home.path(), &archive, None).unwrap_err();
+        assert!(err.to_string().contains("Refusing link"), "{err}");
+        assert!(!node_dir(home.path(), "demo").exists());
+    }
+}
@@ -0,0 +1,124 @@
+//! Version holds (`dm node pin`).
+//!
+//! A pin is stored as `"pinned": "<version>"` in the node's dm.json.
+//! Reinstalls of a pinned node install exactly that version, and bulk
+//! upgrades skip it until `dm node unpin`.
+
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
+use crate::events::{EventSource, OperationEvent};
+
+use super::model::Node;
+
+/// Hold node `id` at `version`.
+pub fn pin_node(home: &Path, id: &str, version: &str) -> Result<Node> {
+    let version = version.trim().trim_start_matches('v');
+    let op = OperationEvent::new(home, EventSource::Core, "node.pin")
+        .attr("node_id", id)
+        .attr("version", version);
+    op.emit_start();
+
+    let result = (|| {
+        if version.is_empty() {
+            bail!("Pin version must not be empty");
+        }
+        write_pin(home, id, Some(version))
+    })();
+
+    op.emit_result(&result);
+    result
+}
+
+/// Release the version hold on node `id`.
+pub fn unpin_node(home: &Path, id: &str) -> Result<Node> {
+    let op = OperationEvent::new(home, EventSource::Core, "node.unpin").attr("node_id", id);
+    op.emit_start();
+
+    let result = write_pin(home, id, None);
+
+    op.emit_result(&result);
+    result
+}
+
+/// Fail when `node` is pinned; for operations that would move it to another
+/// version (`operation` names it in the error).
+pub fn ensure_unpinned(node: &Node, operation: &str) -> Result<()> {
+    match &node.pinned {
+        Some(version) => bail!(
+            "Node '{}' is pinned to {}; {} skipped. Run `dm node unpin {}` first.",
+            node.id,
+            version,
+            operation,
+            node.id
+        ),
+        None => Ok(()),
+    }
+}
+
+/// `pkg` → `pkg==<pinned>`; specs that already carry a constraint are kept.
+pub(crate) fn pinned_package_spec(spec: &str, pinned: Option<&str>) -> String {
+    match pinned {
+        Some(version) if !spec.contains(['=', '<', '>', '~', '@', '!']) => {
+            format!("{}=={}", spec, version)
+        }
+        _ => spec.to_string(),
+    }
+}
+
+fn write_pin(home: &Path, id: &str, version: Option<&str>) -> Result<Node> {
+    super::local::edit_dm_json(home, id, "node pin", |meta| {
+        match version {
+            Some(version) => meta.insert("pinned".to_string(), version.into()),
+            None => meta.remove("pinned"),
+        };
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pin_and_unpin_roundtrip_through_dm_json() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        super::super::create_node(home, "held", "Held node").unwrap();
+
+        let pinned = pin_node(home, "held", "v1.2.0").unwrap();
+        assert_eq!(pinned.pinned.as_deref(), Some("1.2.0"));
+        assert!(ensure_unpinned(&pinned, "upgrade")
+            .unwrap_err()
+            .to_string()
+            .contains("pinned to 1.2.0"));
+
+        let listed = super::super::node_status(home, "held").unwrap().unwrap();
+        assert_eq!(listed.pinned.as_deref(), Some("1.2.0"));
+
+        let released = unpin_node(home, "held").unwrap();
+        assert!(released.pinned.is_none());
+        assert!(ensure_unpinned(&released, "upgrade").is_ok());
+        let raw = std::fs::read_to_string(super::super::dm_json_path(home, "held")).unwrap();
+        assert!(!raw.contains("pinned"));
+    }
+
+    #[test]
+    fn pin_missing_node_fails() {
+        let tmp = tempfile::tempdir().unwrap();
+        assert!(pin_node(tmp.path(), "ghost", "1.0").is_err());
+    }
+
+    #[test]
+    fn pinned_package_spec_respects_existing_constraints() {
+        assert_eq!(
+            pinned_package_spec("dora-yolo", Some("0.3.1")),
+            "dora-yolo==0.3.1"
+        );
+        assert_eq!(
+            pinned_package_spec("dora-yolo>=0.3", Some("0.3.1")),
+            "dora-yolo>=0.3"
+        );
+        assert_eq!(pinned_package_spec("dora-yolo", None), "dora-yolo");
+    }
+}
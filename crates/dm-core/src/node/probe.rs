@@ -0,0 +1,268 @@
+//! Launch a node's executable outside dora, to catch missing shared
+//! libraries and broken Python imports before a dataflow run
+//! (`dm node test`, `dm doctor --deep`).
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::Value;
+
+use crate::error::DmError;
+use crate::events::{EventSource, OperationEvent};
+use crate::types::NodeProbe;
+
+use super::model::{Node, NodeTestRecord};
+
+/// How long `dm node test` lets a node run before it counts as started.
+pub const DEFAULT_TEST_WINDOW: Duration = Duration::from_secs(5);
+/// `DM_RUN_ID` a tested node sees.
+const TEST_RUN_ID: &str = "node-test";
+/// Output fragments showing that an executable cannot actually run.
+const BROKEN_NODE_MARKERS: &[&str] = &[
+    "error while loading shared libraries",
+    "not found (required by",
+    "Library not loaded",
+    "bad interpreter",
+    "cannot execute binary file",
+    "ModuleNotFoundError",
+    "ImportError",
+    "No module named",
+];
+
+/// Start node `id` on its own, with the environment a dataflow run would
+/// give it (`DM_RUN_ID`, `DM_NODE_ID`, `DM_RUN_OUT_DIR` and its config
+/// fields) but no dora daemon, and watch it for `window`.
+///
+/// The result is stored as `last_test` in the node's dm.json; a failed test
+/// is also logged as an error event so `dm doctor` lists it.
+pub async fn test_node(home: &Path, id: &str, window: Duration) -> Result<NodeProbe> {
+    let op = OperationEvent::new(home, EventSource::Core, "node.test").attr("node_id", id);
+    op.emit_start();
+
+    let result = async {
+        let node = super::node_status(home, id)?.ok_or_else(|| DmError::not_found("node", id))?;
+        if node.executable.is_empty() {
+            bail!(
+                "Node '{}' is not installed yet; run `dm node install {}` first",
+                id,
+                id
+            );
+        }
+
+        let out_dir =
+            std::env::temp_dir().join(format!("dm-node-test-{}-{}", id, std::process::id()));
+        std::fs::create_dir_all(&out_dir)?;
+        let env = test_env(home, &node, &out_dir);
+        let probe = probe_executable(&node, &[], &env, window).await;
+        let _ = std::fs::remove_dir_all(&out_dir);
+
+        let record = NodeTestRecord {
+            ok: probe.ok,
+            exit_code: probe.exit_code,
+            detail: probe.detail.clone(),
+            duration_ms: probe.duration_ms,
+            tested_at: chrono::Utc::now().to_rfc3339(),
+        };
+        super::local::edit_dm_json(home, id, "node test", |meta| {
+            meta.insert(
+                "last_test".to_string(),
+                serde_json::to_value(&record).unwrap_or(Value::Null),
+            );
+        })?;
+        Ok(probe)
+    }
+    .await;
+
+    match &result {
+        Ok(probe) if !probe.ok => op.emit_result::<()>(&Err(anyhow!(
+            "Node '{}' failed its test: {}",
+            id,
+            probe.detail
+        ))),
+        _ => op.emit_result(&result),
+    }
+    result
+}
+
+/// Runtime env of a dataflow node, with config fields from the node's
+/// `config.json` or their schema defaults.
+fn test_env(home: &Path, node: &Node, out_dir: &Path) -> BTreeMap<String, String> {
+    let mut env = BTreeMap::from([
+        ("DM_RUN_ID".to_string(), TEST_RUN_ID.to_string()),
+        ("DM_NODE_ID".to_string(), node.id.clone()),
+        ("DM_RUN_OUT_DIR".to_string(), out_dir.display().to_string()),
+    ]);
+    let Some(schema) = node.config_schema.as_ref().and_then(Value::as_object) else {
+        return env;
+    };
+    let config = super::get_node_config(home, &node.id).unwrap_or_default();
+    for (key, field) in schema {
+        let Some(name) = field.get("env").and_then(Value::as_str) else {
+            continue;
+        };
+        let value = config
+            .get(key)
+            .or_else(|| field.get("default"))
+            .filter(|value| !value.is_null());
+        if let Some(value) = value {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            env.insert(name.to_string(), value);
+        }
+    }
+    env
+}
+
+/// Launch `node`'s executable with `args` and `env`, outside dora (no
+/// `DORA_NODE_CONFIG`).
+///
+/// A node passes when it exits cleanly, exits with an error that is not a
+/// loader/import failure (most nodes refuse to run without dora), or is
+/// still running after `window`, in which case it is killed.
+pub(crate) async fn probe_executable(
+    node: &Node,
+    args: &[&str],
+    env: &BTreeMap<String, String>,
+    window: Duration,
+) -> NodeProbe {
+    let started = Instant::now();
+    let executable = node.path.join(&node.executable);
+    let launched = if args.is_empty() {
+        "node".to_string()
+    } else {
+        args.join(" ")
+    };
+    let mut probe = NodeProbe {
+        node_id: node.id.clone(),
+        executable: executable.display().to_string(),
+        ok: false,
+        exit_code: None,
+        detail: String::new(),
+        duration_ms: 0,
+    };
+
+    probe.detail = if !executable.exists() {
+        "executable not found; reinstall the node".to_string()
+    } else {
+        let child = tokio::process::Command::new(&executable)
+            .args(args)
+            .envs(env)
+            .current_dir(&node.path)
+            .env_remove("DORA_NODE_CONFIG")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn();
+        match child {
+            Err(e) => format!("failed to start: {e}"),
+            // Dropping the timed-out future kills the node.
+            Ok(child) => match tokio::time::timeout(window, child.wait_with_output()).await {
+                Err(_) => {
+                    probe.ok = true;
+                    format!("still running after {}s", window.as_secs())
+                }
+                Ok(Err(e)) => format!("failed to run: {e}"),
+                Ok(Ok(output)) => {
+                    probe.exit_code = output.status.code();
+                    let text = format!(
+                        "{}\n{}",
+                        String::from_utf8_lossy(&output.stderr),
+                        String::from_utf8_lossy(&output.stdout)
+                    );
+                    match broken_node_line(&text) {
+                        Some(line) => line,
+                        None if output.status.success() => {
+                            probe.ok = true;
+                            format!("{launched} exited cleanly")
+                        }
+                        None => match output.status.code() {
+                            Some(code) => {
+                                probe.ok = true;
+                                format!("exited with {code} outside dora")
+                            }
+                            None => "terminated by a signal".to_string(),
+                        },
+                    }
+                }
+            },
+        }
+    };
+    probe.duration_ms = started.elapsed().as_millis() as u64;
+    probe
+}
+
+/// First output line reporting a loader or import failure.
+fn broken_node_line(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find(|line| BROKEN_NODE_MARKERS.iter().any(|m| line.contains(m)))
+        .map(|line| line.trim().chars().take(300).collect())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    fn installed_node(home: &Path, id: &str, script: &str) {
+        let dir = dm_test_utils::fake_node(
+            home,
+            id,
+            serde_json::json!({
+                "executable": "bin/node",
+                "future_field": true,
+                "config_schema": {
+                    "rate": { "type": "number", "default": 30, "env": "RATE" },
+                },
+            }),
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.join("bin")).unwrap();
+        let bin = dir.join("bin/node");
+        std::fs::write(&bin, format!("#!/bin/sh\n{script}\n")).unwrap();
+        std::fs::set_permissions(&bin, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_node_passes_nodes_that_start_and_records_the_result() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        installed_node(
+            home,
+            "healthy",
+            "[ \"$DM_NODE_ID:$RATE\" = healthy:30 ] || exit 0\necho 'RuntimeError: DORA_NODE_CONFIG is not set' >&2\nexit 1",
+        );
+        installed_node(
+            home,
+            "broken",
+            "echo \"ModuleNotFoundError: No module named 'cv2'\" >&2\nexit 1",
+        );
+
+        let probe = test_node(home, "healthy", DEFAULT_TEST_WINDOW)
+            .await
+            .unwrap();
+        assert!(probe.ok, "{}", probe.detail);
+        assert_eq!(probe.exit_code, Some(1));
+        let node = super::super::node_status(home, "healthy").unwrap().unwrap();
+        assert!(node.last_test.unwrap().ok);
+
+        let probe = test_node(home, "broken", DEFAULT_TEST_WINDOW)
+            .await
+            .unwrap();
+        assert!(!probe.ok);
+        assert!(probe.detail.contains("No module named 'cv2'"));
+        let raw = std::fs::read_to_string(super::super::dm_json_path(home, "broken")).unwrap();
+        assert!(raw.contains("future_field"));
+        let node = super::super::node_status(home, "broken").unwrap().unwrap();
+        assert!(!node.last_test.unwrap().ok);
+
+        assert!(test_node(home, "ghost", DEFAULT_TEST_WINDOW).await.is_err());
+    }
+}
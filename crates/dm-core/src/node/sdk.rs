@@ -0,0 +1,163 @@
+//! Keeping the `dora-rs` Python SDK in node venvs in step with the dora CLI.
+//!
+//! Python node installs are constrained (pip/uv `-c`) to the SDK release
+//! line of the active CLI: `dora-rs==<major>.<minor>.*` unless
+//! `[sdk_constraints]` in config.toml says otherwise for that version. The
+//! constraint only limits which `dora-rs` may be installed; nodes that do
+//! not depend on it are unaffected.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::config;
+use crate::types::SdkMismatch;
+
+/// PyPI name of the dora Python SDK.
+pub const DORA_SDK_PACKAGE: &str = "dora-rs";
+/// Constraints file written into a node's venv for pip/uv `-c`.
+const CONSTRAINTS_FILE: &str = "dm-constraints.txt";
+
+/// `==<major>.<minor>.*` for CLI version `cli_version`.
+pub fn default_sdk_specifier(cli_version: &str) -> Option<String> {
+    let version = super::outdated::lenient_semver(cli_version)?;
+    Some(format!("=={}.{}.*", version.major, version.minor))
+}
+
+/// The `dora-rs` specifier for the active CLI version, e.g. `==0.3.*`, or
+/// `None` when no version is active.
+pub fn sdk_specifier(home: &Path) -> Result<Option<String>> {
+    let cfg = config::load_config(home)?;
+    let Some(active) = cfg.active_version else {
+        return Ok(None);
+    };
+    Ok(match cfg.sdk_constraints.get(&active) {
+        Some(specifier) => Some(specifier.trim().to_string()),
+        None => default_sdk_specifier(&active),
+    })
+}
+
+/// Write the SDK constraints file into `venv`, returning its path, or
+/// `None` when no version is active.
+pub(crate) fn write_constraints(home: &Path, venv: &Path) -> Result<Option<PathBuf>> {
+    let Some(specifier) = sdk_specifier(home)? else {
+        return Ok(None);
+    };
+    let path = venv.join(CONSTRAINTS_FILE);
+    std::fs::write(&path, format!("{}{}\n", DORA_SDK_PACKAGE, specifier))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(Some(path))
+}
+
+/// `dora-rs` version installed in `venv`, read from its dist-info directory.
+pub fn installed_sdk_version(venv: &Path) -> Option<String> {
+    let site_packages = super::venv::site_packages(venv)?;
+    let prefix = format!("{}-", DORA_SDK_PACKAGE.replace('-', "_"));
+    std::fs::read_dir(site_packages)
+        .ok()?
+        .flatten()
+        .find_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            let version = name.strip_prefix(&prefix)?.strip_suffix(".dist-info")?;
+            Some(version.to_string())
+        })
+}
+
+/// Whether `version` meets the PEP 440 `specifier` (`==0.3.*`,
+/// `>=0.3.5,<0.4`, ...). Specifiers this cannot judge count as met.
+pub fn sdk_satisfies(version: &str, specifier: &str) -> bool {
+    let Some(version) = super::outdated::lenient_semver(numeric_prefix(version)) else {
+        return true;
+    };
+    let req = specifier
+        .split(',')
+        .map(|clause| {
+            let clause = clause.trim();
+            match clause.strip_prefix("==") {
+                Some(exact) => format!("={}", exact.trim().trim_end_matches(".*")),
+                None => clause.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ");
+    semver::VersionReq::parse(&req)
+        .map(|req| req.matches(&version))
+        .unwrap_or(true)
+}
+
+/// Installed nodes whose venv holds a `dora-rs` outside the active CLI's
+/// constraint.
+pub fn check_sdk_versions(home: &Path) -> Result<Vec<SdkMismatch>> {
+    let Some(specifier) = sdk_specifier(home)? else {
+        return Ok(Vec::new());
+    };
+    let mut mismatches = Vec::new();
+    for node in super::list_nodes(home)? {
+        let Some(installed) = installed_sdk_version(&node.path.join(".venv")) else {
+            continue;
+        };
+        if !sdk_satisfies(&installed, &specifier) {
+            mismatches.push(SdkMismatch {
+                node_id: node.id,
+                installed,
+                expected: format!("{}{}", DORA_SDK_PACKAGE, specifier),
+            });
+        }
+    }
+    Ok(mismatches)
+}
+
+/// `0.3.10rc1` → `0.3.10`
+fn numeric_prefix(version: &str) -> &str {
+    let end = version
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(version.len());
+    version[..end].trim_end_matches('.')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_specifier_follows_the_cli_release_line() {
+        assert_eq!(default_sdk_specifier("0.3.9").as_deref(), Some("==0.3.*"));
+        assert_eq!(default_sdk_specifier("v0.4").as_deref(), Some("==0.4.*"));
+        assert_eq!(default_sdk_specifier("nightly"), None);
+    }
+
+    #[test]
+    fn sdk_satisfies_understands_pep440_specifiers() {
+        assert!(sdk_satisfies("0.3.11", "==0.3.*"));
+        assert!(!sdk_satisfies("0.4.0", "==0.3.*"));
+        assert!(sdk_satisfies("0.3.10rc1", "==0.3.*"));
+        assert!(sdk_satisfies("0.3.6", ">=0.3.5,<0.4"));
+        assert!(!sdk_satisfies("0.3.4", ">=0.3.5,<0.4"));
+        assert!(sdk_satisfies("0.3.4", "~=weird"));
+    }
+
+    #[test]
+    fn check_sdk_versions_reports_nodes_off_the_cli_line() {
+        let home = tempfile::tempdir().unwrap();
+        let home = home.path();
+        config::save_config(
+            home,
+            &config::DmConfig {
+                active_version: Some("0.4.1".into()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        for (id, sdk) in [("old-node", "0.3.9"), ("new-node", "0.4.0")] {
+            let dir = dm_test_utils::fake_node(home, id, serde_json::json!({})).unwrap();
+            let site = dir.join(".venv/lib/python3.11/site-packages");
+            std::fs::create_dir_all(site.join(format!("dora_rs-{}.dist-info", sdk))).unwrap();
+        }
+
+        let mismatches = check_sdk_versions(home).unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].node_id, "old-node");
+        assert_eq!(mismatches[0].installed, "0.3.9");
+        assert_eq!(mismatches[0].expected, "dora-rs==0.4.*");
+    }
+}
@@ -0,0 +1,126 @@
+//! Preflight for the system packages a node declares in
+//! `runtime.system_deps`, run before `dm node install` builds anything.
+
+use std::process::{Command, Stdio};
+
+use serde::Serialize;
+
+use super::model::{Node, SystemDep};
+
+/// A declared system dependency that is not present on this machine.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MissingSystemDep {
+    pub name: String,
+    /// What was looked for, e.g. `ffmpeg on PATH`
+    pub checked: String,
+    /// Install command for this OS
+    pub hint: String,
+}
+
+/// The system dependencies of `node` that cannot be found.
+pub fn check_system_deps(node: &Node) -> Vec<MissingSystemDep> {
+    node.runtime
+        .system_deps
+        .iter()
+        .filter_map(|dep| {
+            let checked = match (&dep.bin, &dep.pkg_config) {
+                (None, Some(module)) => {
+                    if pkg_config_exists(module) {
+                        return None;
+                    }
+                    format!("pkg-config module {}", module)
+                }
+                (bin, _) => {
+                    let bin = bin.as_deref().unwrap_or(&dep.name);
+                    if crate::util::check_command(bin).is_some() {
+                        return None;
+                    }
+                    format!("{} on PATH", bin)
+                }
+            };
+            Some(MissingSystemDep {
+                name: dep.name.clone(),
+                checked,
+                hint: install_hint(dep, std::env::consts::OS),
+            })
+        })
+        .collect()
+}
+
+/// One line per missing dependency with its install hint.
+pub fn describe_missing(missing: &[MissingSystemDep]) -> String {
+    missing
+        .iter()
+        .map(|dep| format!("  - {} (no {}): {}", dep.name, dep.checked, dep.hint))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn pkg_config_exists(module: &str) -> bool {
+    Command::new("pkg-config")
+        .args(["--exists", module])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// The node's own hint for `os`, else the usual package manager command.
+fn install_hint(dep: &SystemDep, os: &str) -> String {
+    if let Some(hint) = dep.install.get(os) {
+        return hint.clone();
+    }
+    match os {
+        "macos" => format!("brew install {}", dep.name),
+        "windows" => format!("winget install {}", dep.name),
+        _ => format!("sudo apt install {}", dep.name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dep(json: serde_json::Value) -> SystemDep {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn system_deps_accept_names_and_tables() {
+        let short = dep(serde_json::json!("ffmpeg"));
+        assert_eq!(short.name, "ffmpeg");
+        assert!(short.pkg_config.is_none());
+
+        let full = dep(serde_json::json!({
+            "name": "libopencv",
+            "pkg_config": "opencv4",
+            "install": { "linux": "sudo apt install libopencv-dev" }
+        }));
+        assert_eq!(full.pkg_config.as_deref(), Some("opencv4"));
+        assert_eq!(
+            install_hint(&full, "linux"),
+            "sudo apt install libopencv-dev"
+        );
+        assert_eq!(install_hint(&full, "macos"), "brew install libopencv");
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn check_system_deps_reports_only_missing_ones() {
+        let home = tempfile::tempdir().unwrap();
+        dm_test_utils::fake_node(home.path(), "demo", serde_json::json!({})).unwrap();
+        let mut node = super::super::node_status(home.path(), "demo")
+            .unwrap()
+            .unwrap();
+        node.runtime.system_deps = vec![
+            dep(serde_json::json!({ "name": "shell", "bin": "sh" })),
+            dep(serde_json::json!("dm-no-such-tool")),
+        ];
+
+        let missing = check_system_deps(&node);
+        assert_eq!(missing.len(), 1);
+        assert_eq!(missing[0].name, "dm-no-such-tool");
+        assert_eq!(missing[0].checked, "dm-no-such-tool on PATH");
+    }
+}
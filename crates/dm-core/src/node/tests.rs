@@ -73,6 +73,8 @@ fn test_install_and_list_and_uninstall() {
         examples: Vec::new(),
         config_schema: None,
         dynamic_ports: false,
+        pinned: None,
+        last_test: None,
         path: Default::default(),
     };
 
@@ -102,6 +104,24 @@ fn test_builtin_node_cannot_be_uninstalled() {
     assert!(err.to_string().contains("builtin"));
 }
 
+#[test]
+fn test_uninstall_reports_and_tags_referencing_dataflows() {
+    let dir = tempdir().unwrap();
+    let home = dir.path();
+    std::fs::create_dir_all(node_dir(home, "gone-node")).unwrap();
+    crate::dataflow::save(home, "uses-it", "nodes:\n  - id: a\n    node: gone-node\n").unwrap();
+    crate::dataflow::save(home, "unrelated", "nodes:\n  - id: b\n    node: other\n").unwrap();
+
+    let report = uninstall_node_with(home, "gone-node", true).unwrap();
+    assert_eq!(report.referencing_dataflows, vec!["uses-it".to_string()]);
+    assert_eq!(report.tagged_dataflows, vec!["uses-it".to_string()]);
+
+    let meta = crate::dataflow::get_flow_meta(home, "uses-it").unwrap();
+    assert!(meta.tags.contains(&"missing-node:gone-node".to_string()));
+    let meta = crate::dataflow::get_flow_meta(home, "unrelated").unwrap();
+    assert!(meta.tags.is_empty());
+}
+
 #[test]
 fn test_nodes_dir_path() {
     let home = Path::new("/home/user/.dm");
@@ -168,6 +188,8 @@ async fn test_install_node_errors_for_unsupported_build() {
         examples: Vec::new(),
         config_schema: None,
         dynamic_ports: false,
+        pinned: None,
+        last_test: None,
         path: Default::default(),
     };
     std::fs::write(
@@ -0,0 +1,119 @@
+//! Introspection of node virtualenvs: which Python they were created with
+//! and where their `site-packages` lives.
+
+use std::path::{Path, PathBuf};
+
+/// `major.minor` of the interpreter `venv` was created with, read from
+/// `pyvenv.cfg` (`version_info` from uv, `version` from `python -m venv`).
+pub fn venv_python_version(venv: &Path) -> Option<String> {
+    let cfg = std::fs::read_to_string(venv.join("pyvenv.cfg")).ok()?;
+    let value = |key: &str| {
+        cfg.lines().find_map(|line| {
+            let (k, v) = line.split_once('=')?;
+            (k.trim() == key).then(|| v.trim().to_string())
+        })
+    };
+    let full = value("version_info").or_else(|| value("version"))?;
+    let mut parts = full.split('.');
+    let major = parts
+        .next()
+        .filter(|p| p.chars().all(|c| c.is_ascii_digit()))?;
+    let minor: String = parts
+        .next()?
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    (!major.is_empty() && !minor.is_empty()).then(|| format!("{}.{}", major, minor))
+}
+
+/// `site-packages` of `venv`: `Lib/site-packages` on Windows, else
+/// `lib/python<version>/site-packages` for the version in `pyvenv.cfg`,
+/// falling back to the newest `lib/python3.*` that has one.
+pub fn site_packages(venv: &Path) -> Option<PathBuf> {
+    let windows = venv.join("Lib").join("site-packages");
+    if windows.is_dir() {
+        return Some(windows);
+    }
+    let lib = venv.join("lib");
+    if let Some(version) = venv_python_version(venv) {
+        let dir = lib.join(format!("python{}", version)).join("site-packages");
+        if dir.is_dir() {
+            return Some(dir);
+        }
+    }
+    let mut candidates: Vec<(Vec<u32>, PathBuf)> = std::fs::read_dir(&lib)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            let version = name.strip_prefix("python")?;
+            let key = version
+                .split('.')
+                .map(|part| {
+                    part.trim_end_matches(|c: char| !c.is_ascii_digit())
+                        .parse()
+                        .ok()
+                })
+                .collect::<Option<Vec<u32>>>()?;
+            let dir = entry.path().join("site-packages");
+            dir.is_dir().then_some((key, dir))
+        })
+        .collect();
+    candidates.sort();
+    candidates.pop().map(|(_, dir)| dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn venv_with(cfg: Option<&str>, libs: &[&str]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        if let Some(cfg) = cfg {
+            std::fs::write(dir.path().join("pyvenv.cfg"), cfg).unwrap();
+        }
+        for lib in libs {
+            std::fs::create_dir_all(dir.path().join("lib").join(lib).join("site-packages"))
+                .unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn python_version_comes_from_pyvenv_cfg() {
+        let uv = venv_with(Some("home = /usr/bin\nversion_info = 3.11.9\n"), &[]);
+        assert_eq!(venv_python_version(uv.path()).as_deref(), Some("3.11"));
+        let stdlib = venv_with(Some("home = /usr/bin\nversion = 3.10.14\n"), &[]);
+        assert_eq!(venv_python_version(stdlib.path()).as_deref(), Some("3.10"));
+        let rc = venv_with(Some("version_info = 3.13.0rc2\n"), &[]);
+        assert_eq!(venv_python_version(rc.path()).as_deref(), Some("3.13"));
+        assert_eq!(venv_python_version(venv_with(None, &[]).path()), None);
+    }
+
+    #[test]
+    fn site_packages_follows_the_venv_python() {
+        for version in ["3.10", "3.11", "3.12"] {
+            let venv = venv_with(
+                Some(&format!("version_info = {}.2\n", version)),
+                &["python3.10", "python3.11", "python3.12"],
+            );
+            assert_eq!(
+                site_packages(venv.path()).unwrap(),
+                venv.path()
+                    .join("lib")
+                    .join(format!("python{}", version))
+                    .join("site-packages")
+            );
+        }
+    }
+
+    #[test]
+    fn site_packages_falls_back_to_the_newest_lib_dir() {
+        let venv = venv_with(None, &["python3.9", "python3.11", "python3.10"]);
+        assert_eq!(
+            site_packages(venv.path()).unwrap(),
+            venv.path().join("lib/python3.11/site-packages")
+        );
+        assert_eq!(site_packages(venv_with(None, &[]).path()), None);
+    }
+}
@@ -0,0 +1,172 @@
+//! Owner-only permissions for dm home files that may hold secrets.
+//!
+//! config.toml, node `dm.json` / `config.json` (API keys live in node
+//! config) and the SQLite databases are created `0600`, the home itself
+//! `0700`. `dm doctor` flags sensitive files other users can access and
+//! `dm doctor --fix` tightens them. Everything here is a no-op off Unix.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::types::PermissionIssue;
+
+/// Mode for sensitive files.
+pub const PRIVATE_FILE_MODE: u32 = 0o600;
+/// Mode for the dm home directory.
+pub const PRIVATE_DIR_MODE: u32 = 0o700;
+
+/// Databases in the dm home, with their SQLite side files.
+const HOME_DATABASES: &[&str] = &[
+    "events.db",
+    "events.db-wal",
+    "events.db-shm",
+    "secrets.db",
+    "secrets.db-wal",
+    "secrets.db-shm",
+];
+
+/// Like `std::fs::write`, but the file ends up readable by its owner only,
+/// including when it already existed with a wider mode.
+pub fn write_private(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(PRIVATE_FILE_MODE)
+            .open(path)?;
+        // `mode` only applies to newly created files.
+        restrict_file(path)?;
+        file.write_all(contents.as_ref())
+    }
+    #[cfg(not(unix))]
+    {
+        std::fs::write(path, contents)
+    }
+}
+
+/// Set an existing file to `0600`.
+pub fn restrict_file(path: &Path) -> std::io::Result<()> {
+    set_mode(path, PRIVATE_FILE_MODE)
+}
+
+/// `create_dir_all`, then set the directory itself to `0700`.
+pub fn create_private_dir(path: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(path)?;
+    set_mode(path, PRIVATE_DIR_MODE)
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Sensitive paths in `home` with the mode they should have.
+fn sensitive_paths(home: &Path) -> Vec<(PathBuf, u32)> {
+    let mut paths = vec![(home.to_path_buf(), PRIVATE_DIR_MODE)];
+    paths.push((crate::config::config_path(home), PRIVATE_FILE_MODE));
+    for name in HOME_DATABASES {
+        paths.push((home.join(name), PRIVATE_FILE_MODE));
+    }
+    if let Ok(entries) = std::fs::read_dir(home.join("nodes")) {
+        for entry in entries.flatten() {
+            for name in ["dm.json", "config.json"] {
+                paths.push((entry.path().join(name), PRIVATE_FILE_MODE));
+            }
+        }
+    }
+    paths.retain(|(path, _)| path.exists());
+    paths
+}
+
+/// Sensitive files and the home directory that other users can access.
+pub fn check_permissions(home: &Path) -> Vec<PermissionIssue> {
+    sensitive_paths(home)
+        .into_iter()
+        .filter_map(|(path, expected)| {
+            let mode = current_mode(&path)?;
+            (mode & 0o077 != 0).then(|| PermissionIssue {
+                path: path.display().to_string(),
+                mode: format!("{:o}", mode),
+                expected: format!("{:o}", expected),
+            })
+        })
+        .collect()
+}
+
+/// Tighten everything `check_permissions` reports; returns what changed.
+pub fn fix_permissions(home: &Path) -> Result<Vec<PermissionIssue>> {
+    let issues = check_permissions(home);
+    for issue in &issues {
+        let mode = u32::from_str_radix(&issue.expected, 8)?;
+        set_mode(Path::new(&issue.path), mode)
+            .with_context(|| format!("Failed to change permissions of {}", issue.path))?;
+    }
+    Ok(issues)
+}
+
+#[cfg(unix)]
+fn current_mode(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(std::fs::metadata(path).ok()?.permissions().mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn current_mode(_path: &Path) -> Option<u32> {
+    None
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    fn mode(path: &Path) -> u32 {
+        std::fs::metadata(path).unwrap().permissions().mode() & 0o777
+    }
+
+    #[test]
+    fn write_private_tightens_existing_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("config.toml");
+        std::fs::write(&path, "old").unwrap();
+        set_mode(&path, 0o644).unwrap();
+
+        write_private(&path, "new").unwrap();
+        assert_eq!(mode(&path), 0o600);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+    }
+
+    #[test]
+    fn doctor_check_flags_and_fixes_readable_secrets() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path().join("home");
+        create_private_dir(&home).unwrap();
+        let node = home.join("nodes").join("llm");
+        std::fs::create_dir_all(&node).unwrap();
+        std::fs::write(node.join("config.json"), r#"{"api_key":"x"}"#).unwrap();
+        set_mode(&node.join("config.json"), 0o644).unwrap();
+        write_private(&home.join("config.toml"), "").unwrap();
+
+        let issues = check_permissions(&home);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].path.ends_with("config.json"));
+        assert_eq!(issues[0].mode, "644");
+
+        assert_eq!(fix_permissions(&home).unwrap().len(), 1);
+        assert_eq!(mode(&node.join("config.json")), 0o600);
+        assert!(check_permissions(&home).is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+//! External `dm` subcommands. `dm deploy ...` runs the executable
+//! registered as `deploy` under `[plugins]` in config.toml, or else
+//! `dm-deploy` from PATH, with `DM_HOME` set and a JSON [`PluginContext`]
+//! on stdin.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::config;
+
+/// Executable name prefix for plugins discovered on PATH.
+pub const PLUGIN_PREFIX: &str = "dm-";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginOrigin {
+    /// `[plugins]` entry in config.toml
+    Config,
+    /// `dm-<name>` executable on PATH
+    Path,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Plugin {
+    pub name: String,
+    pub path: PathBuf,
+    pub origin: PluginOrigin,
+}
+
+/// What a plugin receives as JSON on stdin.
+#[derive(Debug, Serialize)]
+pub struct PluginContext {
+    pub dm_version: String,
+    pub dm_home: PathBuf,
+    pub dataflows_dir: PathBuf,
+    pub active_version: Option<String>,
+    pub robot_id: Option<String>,
+    pub labels: BTreeMap<String, String>,
+    /// Arguments after the plugin name
+    pub args: Vec<String>,
+}
+
+impl PluginContext {
+    pub fn new(home: &Path, args: &[String]) -> Result<Self> {
+        let cfg = config::load_config(home)?;
+        Ok(Self {
+            dm_version: env!("CARGO_PKG_VERSION").to_string(),
+            dm_home: home.to_path_buf(),
+            dataflows_dir: crate::dataflow::dataflows_dir(home),
+            active_version: cfg.active_version,
+            robot_id: cfg.robot_id,
+            labels: cfg.labels,
+            args: args.to_vec(),
+        })
+    }
+}
+
+/// The plugin `dm <name>` dispatches to, if any. Registered plugins shadow
+/// ones found on PATH.
+pub fn find_plugin(home: &Path, name: &str) -> Result<Option<Plugin>> {
+    let cfg = config::load_config(home)?;
+    if let Some(path) = cfg.plugins.get(name) {
+        return Ok(Some(Plugin {
+            name: name.to_string(),
+            path: resolve_registered(home, path),
+            origin: PluginOrigin::Config,
+        }));
+    }
+    Ok(which::which(format!("{}{}", PLUGIN_PREFIX, name))
+        .ok()
+        .map(|path| Plugin {
+            name: name.to_string(),
+            path,
+            origin: PluginOrigin::Path,
+        }))
+}
+
+/// Every plugin `dm` can dispatch to, sorted by name.
+pub fn list_plugins(home: &Path) -> Result<Vec<Plugin>> {
+    let cfg = config::load_config(home)?;
+    let mut plugins: BTreeMap<String, Plugin> = BTreeMap::new();
+    let path_dirs = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).collect::<Vec<_>>())
+        .unwrap_or_default();
+    for dir in path_dirs {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(name) = plugin_name(&path) else {
+                continue;
+            };
+            // Earlier PATH entries win, as they would for `which`.
+            plugins.entry(name.clone()).or_insert(Plugin {
+                name,
+                path,
+                origin: PluginOrigin::Path,
+            });
+        }
+    }
+    for (name, path) in &cfg.plugins {
+        plugins.insert(
+            name.clone(),
+            Plugin {
+                name: name.clone(),
+                path: resolve_registered(home, path),
+                origin: PluginOrigin::Config,
+            },
+        );
+    }
+    Ok(plugins.into_values().collect())
+}
+
+/// Run `plugin` with `args`, passing the context on stdin, and return its
+/// exit code.
+pub fn run_plugin(home: &Path, plugin: &Plugin, args: &[String]) -> Result<i32> {
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    let context = serde_json::to_vec(&PluginContext::new(home, args)?)?;
+    let mut child = Command::new(&plugin.path)
+        .args(args)
+        .env("DM_HOME", home)
+        .env(crate::util::DM_CLI_BIN_ENV_KEY, current_dm())
+        .stdin(Stdio::piped())
+        .spawn()
+        .with_context(|| {
+            format!(
+                "Failed to run plugin '{}' ({})",
+                plugin.name,
+                plugin.path.display()
+            )
+        })?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Plugins that ignore the context may exit before reading it.
+        let _ = stdin.write_all(&context);
+    }
+    let status = child.wait()?;
+    Ok(status.code().unwrap_or(1))
+}
+
+fn current_dm() -> PathBuf {
+    std::env::current_exe().unwrap_or_else(|_| crate::util::resolve_dm_cli_exe())
+}
+
+/// `~/` expands to the user's home; other relative paths resolve against
+/// the dm home.
+fn resolve_registered(home: &Path, path: &str) -> PathBuf {
+    let path = path.trim();
+    match path.strip_prefix("~/").zip(dirs::home_dir()) {
+        Some((rest, user_home)) => user_home.join(rest),
+        None => home.join(path),
+    }
+}
+
+/// `deploy` for an executable `dm-deploy` (`dm-deploy.exe` on Windows).
+fn plugin_name(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    let stem = file_name
+        .strip_suffix(std::env::consts::EXE_SUFFIX)
+        .unwrap_or(file_name);
+    let name = stem.strip_prefix(PLUGIN_PREFIX)?;
+    if name.is_empty() || name.contains('.') || !is_executable(path) {
+        return None;
+    }
+    Some(name.to_string())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
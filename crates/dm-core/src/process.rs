@@ -0,0 +1,169 @@
+//! Foreground child processes (`dm run`, `dm -- <dora args>`).
+//!
+//! The child gets its own process group, so dora and every node it spawns
+//! can be signalled together. While it runs that group owns the terminal:
+//! it can read stdin, and Ctrl-C from the terminal reaches it directly.
+//! SIGINT / SIGTERM / SIGHUP sent to dm itself (`kill`, a closed terminal)
+//! are forwarded to the group instead of killing dm first and orphaning
+//! dora processes that keep cameras and ports locked. A second signal, or
+//! a child that ignores the first for `STOP_GRACE`, gets the group killed.
+
+use std::process::{Command as StdCommand, ExitStatus};
+#[cfg(unix)]
+use std::time::Duration;
+
+use tokio::process::Child;
+
+/// How long a signalled child may take to shut down before it is killed.
+#[cfg(unix)]
+const STOP_GRACE: Duration = Duration::from_secs(20);
+
+/// Start the command in a new process group led by the child.
+pub(crate) fn own_process_group(cmd: &mut StdCommand) {
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(cmd, 0);
+    #[cfg(not(unix))]
+    let _ = cmd;
+}
+
+/// Wait for `child`, forwarding SIGINT/SIGTERM/SIGHUP to its process group.
+pub(crate) async fn wait_forwarding_signals(child: &mut Child) -> std::io::Result<ExitStatus> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let Some(pgid) = child.id() else {
+            return child.wait().await;
+        };
+        let _terminal = TerminalForeground::hand_to(pgid);
+        let mut interrupt = signal(SignalKind::interrupt())?;
+        let mut terminate = signal(SignalKind::terminate())?;
+        let mut hangup = signal(SignalKind::hangup())?;
+        let mut deadline: Option<tokio::time::Instant> = None;
+
+        loop {
+            let grace = async move {
+                match deadline {
+                    Some(deadline) => tokio::time::sleep_until(deadline).await,
+                    None => std::future::pending().await,
+                }
+            };
+            let forward = tokio::select! {
+                status = child.wait() => {
+                    if deadline.is_some() {
+                        // Reap nodes dora left behind in the group.
+                        signal_group(pgid, "TERM");
+                    }
+                    return status;
+                }
+                _ = interrupt.recv() => "INT",
+                _ = terminate.recv() => "TERM",
+                _ = hangup.recv() => "HUP",
+                _ = grace => "KILL",
+            };
+            let signal = if deadline.is_some() {
+                eprintln!("[dm] Killing dora (process group {})", pgid);
+                "KILL"
+            } else {
+                eprintln!("[dm] Stopping dora... press Ctrl-C again to force");
+                forward
+            };
+            signal_group(pgid, signal);
+            deadline = Some(tokio::time::Instant::now() + STOP_GRACE);
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        // Console Ctrl-C already reaches every process attached to it.
+        child.wait().await
+    }
+}
+
+/// Exit code for `status`, with signal deaths reported shell-style (128+n).
+pub(crate) fn exit_code(status: &ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    status.code().unwrap_or(-1)
+}
+
+/// The controlling terminal handed to a child's process group, taken back
+/// on drop. A background group that reads the terminal is stopped by
+/// SIGTTIN, so passthrough commands that prompt would hang otherwise.
+#[cfg(unix)]
+struct TerminalForeground {
+    /// dm's own group, when it had the terminal to give.
+    restore: Option<libc::pid_t>,
+}
+
+#[cfg(unix)]
+impl TerminalForeground {
+    fn hand_to(pgid: u32) -> Self {
+        let fd = libc::STDIN_FILENO;
+        // SAFETY: plain syscalls on stdin and our own process group.
+        let restore = unsafe {
+            let own = libc::getpgrp();
+            let foreground = libc::isatty(fd) == 1 && libc::tcgetpgrp(fd) == own;
+            if foreground && libc::tcsetpgrp(fd, pgid as libc::pid_t) == 0 {
+                // The child may have hit the terminal before it was its own.
+                libc::kill(-(pgid as libc::pid_t), libc::SIGCONT);
+                Some(own)
+            } else {
+                None
+            }
+        };
+        Self { restore }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for TerminalForeground {
+    fn drop(&mut self) {
+        if let Some(own) = self.restore {
+            // SAFETY: as above. dm is in the background now, so SIGTTOU is
+            // ignored for the call instead of stopping dm.
+            unsafe {
+                let previous = libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+                libc::tcsetpgrp(libc::STDIN_FILENO, own);
+                libc::signal(libc::SIGTTOU, previous);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn signal_group(pgid: u32, signal: &str) {
+    let _ = StdCommand::new("kill")
+        .arg(format!("-{}", signal))
+        .arg("--")
+        .arg(format!("-{}", pgid))
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status();
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn signalled_group_reports_shell_style_exit_code() {
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", "sleep 30 & wait"]);
+        own_process_group(cmd.as_std_mut());
+        let mut child = cmd.spawn().unwrap();
+
+        // Give sh a moment to start its background sleep.
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        signal_group(child.id().unwrap(), "TERM");
+        let status = tokio::time::timeout(Duration::from_secs(5), child.wait())
+            .await
+            .expect("process group ignored SIGTERM")
+            .unwrap();
+        assert_eq!(exit_code(&status), 143);
+    }
+}
@@ -0,0 +1,160 @@
+//! Incremental reads of a growing log file, shared by the run log capture
+//! and dm-server's log tails.
+
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+/// Remembers how far into a log file it has read, so each call only reads
+/// the bytes appended since the previous one.
+#[derive(Debug, Clone, Default)]
+pub struct LogFollower {
+    path: PathBuf,
+    offset: u64,
+    /// Trailing text after the last newline, completed by the next read
+    partial: String,
+    /// Start at most this many bytes before the end of an existing file
+    seed_bytes: Option<u64>,
+}
+
+/// Lines returned by [`LogFollower::read_new_lines`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NewLines {
+    /// The file shrank since the last read (truncated or replaced), so
+    /// reading started over from its beginning.
+    pub restarted: bool,
+    /// Complete lines, without their line ending.
+    pub lines: Vec<String>,
+}
+
+impl LogFollower {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            ..Default::default()
+        }
+    }
+
+    /// On the first read (and after a restart), skip all but the last
+    /// `bytes` of a longer file, dropping the line they start in.
+    pub fn with_seed_bytes(mut self, bytes: u64) -> Self {
+        self.seed_bytes = Some(bytes);
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Byte offset in the file read up to.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Text after the last newline read so far.
+    pub fn partial(&self) -> &str {
+        &self.partial
+    }
+
+    /// Complete lines appended since the last call. A missing file reads as
+    /// empty.
+    pub fn read_new_lines(&mut self) -> Result<NewLines> {
+        let mut new = NewLines::default();
+        let Ok(mut file) = std::fs::File::open(&self.path) else {
+            return Ok(new);
+        };
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            self.offset = 0;
+            self.partial.clear();
+            new.restarted = true;
+        }
+        let mut skip_first_line = false;
+        if let Some(seed) = self.seed_bytes {
+            if self.offset == 0 && len > seed {
+                self.offset = len - seed;
+                skip_first_line = true;
+            }
+        }
+        if len == self.offset {
+            return Ok(new);
+        }
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = Vec::with_capacity((len - self.offset) as usize);
+        file.take(len - self.offset).read_to_end(&mut buf)?;
+        self.offset += buf.len() as u64;
+
+        let text = std::mem::take(&mut self.partial) + &String::from_utf8_lossy(&buf);
+        let mut pieces: Vec<&str> = text.split('\n').collect();
+        self.partial = pieces.pop().unwrap_or_default().to_string();
+        if skip_first_line && !pieces.is_empty() {
+            pieces.remove(0);
+        }
+        new.lines = pieces
+            .into_iter()
+            .map(|line| line.trim_end_matches('\r').to_string())
+            .collect();
+        Ok(new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn append(path: &Path, text: &str) {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap()
+            .write_all(text.as_bytes())
+            .unwrap();
+    }
+
+    #[test]
+    fn reads_appended_lines_and_completes_partial_ones() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("node.txt");
+        let mut follower = LogFollower::new(&path);
+        assert_eq!(follower.read_new_lines().unwrap(), NewLines::default());
+
+        append(&path, "one\r\ntw");
+        assert_eq!(follower.read_new_lines().unwrap().lines, ["one"]);
+        assert_eq!(follower.partial(), "tw");
+
+        append(&path, "o\nthree\n");
+        assert_eq!(follower.read_new_lines().unwrap().lines, ["two", "three"]);
+        assert_eq!(follower.offset(), 15);
+        assert!(follower.read_new_lines().unwrap().lines.is_empty());
+    }
+
+    #[test]
+    fn starts_over_once_the_file_is_truncated() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("node.txt");
+        append(&path, "first run line\nhalf");
+        let mut follower = LogFollower::new(&path);
+        follower.read_new_lines().unwrap();
+
+        std::fs::write(&path, "new\n").unwrap();
+        let new = follower.read_new_lines().unwrap();
+        assert!(new.restarted);
+        assert_eq!(new.lines, ["new"]);
+        assert_eq!(follower.partial(), "");
+    }
+
+    #[test]
+    fn seed_bytes_skip_to_the_tail_of_a_long_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("node.txt");
+        append(&path, "aaaa\nbbbb\ncccc\n");
+        let mut follower = LogFollower::new(&path).with_seed_bytes(7);
+
+        assert_eq!(follower.read_new_lines().unwrap().lines, ["cccc"]);
+    }
+}
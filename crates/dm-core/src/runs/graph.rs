@@ -2,6 +2,7 @@ use std::collections::BTreeMap;
 
 use anyhow::{Context, Result};
 
+use super::model::{NodeRestartSpec, RestartPolicy};
 use super::RunTranspileMetadata;
 
 pub(crate) fn extract_node_ids_from_yaml(yaml: &str) -> Result<Vec<String>> {
@@ -10,6 +11,40 @@ pub(crate) fn extract_node_ids_from_yaml(yaml: &str) -> Result<Vec<String>> {
     Ok(extract_node_ids(&graph))
 }
 
+/// Collect `restart:` policies declared on nodes, keyed by YAML node id.
+/// An explicit `never` is kept so it can override other nodes' policies.
+pub(crate) fn extract_restart_policies(yaml: &str) -> BTreeMap<String, NodeRestartSpec> {
+    let mut policies = BTreeMap::new();
+    let Ok(graph) = serde_yaml::from_str::<serde_yaml::Value>(yaml) else {
+        return policies;
+    };
+    let Some(nodes) = graph.get("nodes").and_then(|value| value.as_sequence()) else {
+        return policies;
+    };
+
+    for node in nodes {
+        let Some(id) = node.get("id").and_then(|value| value.as_str()) else {
+            continue;
+        };
+        let spec = match node.get("restart") {
+            Some(value @ serde_yaml::Value::String(_)) => {
+                serde_yaml::from_value::<RestartPolicy>(value.clone())
+                    .ok()
+                    .map(NodeRestartSpec::from_policy)
+            }
+            Some(value @ serde_yaml::Value::Mapping(_)) => {
+                serde_yaml::from_value::<NodeRestartSpec>(value.clone()).ok()
+            }
+            _ => None,
+        };
+        if let Some(spec) = spec {
+            policies.insert(id.to_string(), spec);
+        }
+    }
+
+    policies
+}
+
 pub(crate) fn build_transpile_metadata(graph: &serde_yaml::Value) -> RunTranspileMetadata {
     let mut resolved_node_paths = BTreeMap::new();
     let working_dir = std::env::current_dir()
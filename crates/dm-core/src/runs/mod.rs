@@ -1,14 +1,18 @@
+mod follow;
 mod graph;
 mod model;
 mod repo;
-mod runtime;
+pub(crate) mod runtime;
 mod service;
 mod state;
 
+pub use follow::{LogFollower, NewLines};
 pub use model::{
-    LogSyncState, NodeMetrics, PaginatedRuns, RunDetail, RunInstance, RunListFilter, RunLogChunk,
-    RunLogSync, RunMetrics, RunNode, RunOutcome, RunSource, RunStatus, RunStopRequest, RunSummary,
-    RunTranspileMetadata, StartConflictStrategy, StartRunResult, TerminationReason,
+    GroupRunEntry, GroupRunReport, LogSyncState, NodeMetricSample, NodeMetrics, NodeRestartSpec,
+    PaginatedRuns, RestartPolicy, RunDetail, RunEventImport, RunInstance, RunIsolation,
+    RunListFilter, RunLogChunk, RunLogSync, RunMetricSample, RunMetrics, RunNode, RunOutcome,
+    RunRestart, RunSource, RunStatus, RunStopRequest, RunSummary, RunTranspileMetadata,
+    StartConflictStrategy, StartRunResult, TerminationReason,
 };
 pub use repo::{
     create_layout, delete_run as delete_run_dir, list_run_instances, load_run, read_run_dataflow,
@@ -17,11 +21,15 @@ pub use repo::{
     runs_dir, save_run,
 };
 pub use service::{
-    clean_runs, collect_all_active_metrics, delete_run, get_active_run, get_run, get_run_metrics,
-    list_active_runs, list_runs, list_runs_filtered, mark_stop_requested, read_run_log,
+    apply_retention, clean_runs, collect_all_active_metrics, delete_run, get_active_run, get_run,
+    get_run_metrics, import_run_events, install_missing_nodes, list_active_runs,
+    list_dataflow_nodes, list_runs, list_runs_filtered, mark_stop_requested, read_run_log,
     read_run_log_chunk, read_run_transpiled, read_run_view, reconcile_stale_running_runs,
-    refresh_run_statuses, start_run_from_file, start_run_from_file_with_source_and_strategy,
-    start_run_from_file_with_strategy, start_run_from_yaml,
-    start_run_from_yaml_with_source_and_strategy, start_run_from_yaml_with_strategy, stop_run,
-    sync_run_outputs,
+    record_run_metrics, refresh_run_statuses, run_metric_history, sample_active_runs,
+    start_run_from_file, start_run_from_file_with_isolation,
+    start_run_from_file_with_source_and_strategy, start_run_from_file_with_strategy,
+    start_run_from_yaml, start_run_from_yaml_with_isolation,
+    start_run_from_yaml_with_source_and_strategy, start_run_from_yaml_with_strategy, start_tagged,
+    stop_run, stop_tagged, supervise_restarts, sync_run_outputs, OutputCapture, RestartAction,
+    RunLogCapture, DORA_LOG_ACTIVITY, DORA_OUTPUT_ACTIVITY, RUN_METRICS_ACTIVITY,
 };
@@ -124,6 +124,86 @@ pub struct RunStopRequest {
     pub last_error: Option<String>,
 }
 
+/// When dm should restart a dataflow after one of its nodes stops.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    #[default]
+    Never,
+    OnFailure,
+    Always,
+}
+
+impl RestartPolicy {
+    pub fn should_restart(&self, status: RunStatus) -> bool {
+        match self {
+            Self::Never => false,
+            Self::OnFailure => status == RunStatus::Failed,
+            Self::Always => !status.is_running(),
+        }
+    }
+}
+
+/// Per-node `restart:` block from the dataflow YAML.
+///
+/// Accepts either a bare policy (`restart: on-failure`) or a mapping with
+/// `policy`, `max_restarts`, `backoff_secs` and `max_backoff_secs`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NodeRestartSpec {
+    #[serde(default)]
+    pub policy: RestartPolicy,
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+    #[serde(default = "default_backoff_secs")]
+    pub backoff_secs: u64,
+    #[serde(default = "default_max_backoff_secs")]
+    pub max_backoff_secs: u64,
+}
+
+impl NodeRestartSpec {
+    pub fn from_policy(policy: RestartPolicy) -> Self {
+        Self {
+            policy,
+            max_restarts: default_max_restarts(),
+            backoff_secs: default_backoff_secs(),
+            max_backoff_secs: default_max_backoff_secs(),
+        }
+    }
+
+    /// Exponential backoff before restart attempt `attempt + 1`.
+    pub fn backoff_for(&self, attempt: u32) -> u64 {
+        self.backoff_secs
+            .saturating_mul(1u64 << attempt.min(32))
+            .min(self.max_backoff_secs)
+    }
+}
+
+fn default_max_restarts() -> u32 {
+    5
+}
+
+fn default_backoff_secs() -> u64 {
+    5
+}
+
+fn default_max_backoff_secs() -> u64 {
+    300
+}
+
+/// Restart lineage of a run, maintained by the restart supervisor.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RunRestart {
+    /// Number of automatic restarts that led to this run.
+    #[serde(default)]
+    pub attempt: u32,
+    /// Run this one was restarted from.
+    #[serde(default)]
+    pub restarted_from: Option<String>,
+    /// Set once the supervisor has acted on this run's termination.
+    #[serde(default)]
+    pub handled: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct RunInstance {
@@ -151,6 +231,7 @@ pub struct RunInstance {
     pub nodes_expected: Vec<String>,
     #[serde(default, alias = "nodes")]
     pub nodes_observed: Vec<String>,
+    pub restart: RunRestart,
 }
 
 impl Default for RunInstance {
@@ -179,6 +260,7 @@ impl Default for RunInstance {
             node_count_observed: 0,
             nodes_expected: Vec::new(),
             nodes_observed: Vec::new(),
+            restart: RunRestart::default(),
         }
     }
 }
@@ -189,10 +271,66 @@ pub struct StartRunResult {
     pub message: String,
 }
 
+/// Outcome of starting or stopping every dataflow tagged with one tag
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupRunReport {
+    pub tag: String,
+    pub results: Vec<GroupRunEntry>,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroupRunEntry {
+    pub dataflow: String,
+    pub ok: bool,
+    /// Runs started or stopped for this dataflow
+    pub run_ids: Vec<String>,
+    /// What happened, or why it failed
+    pub message: String,
+}
+
+impl GroupRunReport {
+    pub(crate) fn new(tag: &str, results: Vec<GroupRunEntry>) -> Self {
+        let succeeded = results.iter().filter(|entry| entry.ok).count();
+        Self {
+            tag: tag.to_string(),
+            failed: results.len() - succeeded,
+            succeeded,
+            results,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StartConflictStrategy {
     Fail,
     StopAndRestart,
+    /// Start another instance alongside any active run with the same name.
+    AllowMultiple,
+}
+
+/// Which dora runtime a new run is started on.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum RunIsolation {
+    /// The shared coordinator/daemon brought up by `dm up`.
+    #[default]
+    Shared,
+    /// A dedicated coordinator/daemon on free ports, shut down with the run.
+    /// `dora_version` picks another installed version than the active one.
+    Dedicated { dora_version: Option<String> },
+}
+
+impl RunIsolation {
+    /// The default for new runs: `isolate_runs` in config.toml.
+    pub fn from_config(home: &std::path::Path) -> Self {
+        let cfg = crate::config::load_config(home).unwrap_or_default();
+        if cfg.isolate_runs.unwrap_or(false) {
+            Self::Dedicated { dora_version: None }
+        } else {
+            Self::Shared
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -232,12 +370,43 @@ pub struct NodeMetrics {
     pub memory: Option<String>,
 }
 
+/// CPU and memory of one node process, measured by dm with sysinfo.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeMetricSample {
+    pub node_id: String,
+    pub pid: u32,
+    /// Percent of one core, so busy multi-threaded nodes can exceed 100
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+}
+
+/// The node processes of a run at one sampling tick, stored as a
+/// `run.metrics` event.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunMetricSample {
+    /// RFC 3339 timestamp
+    pub timestamp: String,
+    pub nodes: Vec<NodeMetricSample>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RunNode {
     pub id: String,
     pub log_size: u64,
 }
 
+/// Result of importing a finished run's dora logs into the event store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunEventImport {
+    pub run_id: String,
+    /// `dora.log` events written, per node
+    pub nodes: BTreeMap<String, usize>,
+    pub imported: usize,
+    /// Nodes whose log exceeded the per-node cap; only the newest lines were kept
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub truncated: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RunLogChunk {
     pub run_id: String,
@@ -4,7 +4,9 @@ use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, Context, Result};
 
-use super::model::RunInstance;
+use super::model::{RunInstance, RunStatus};
+use crate::error::DmError;
+use crate::events::{try_emit, EventBuilder, EventLevel, EventSource};
 
 pub fn runs_dir(home: &Path) -> PathBuf {
     home.join("runs")
@@ -47,20 +49,55 @@ pub fn create_layout(home: &Path, run_id: &str) -> Result<PathBuf> {
 
 pub fn load_run(home: &Path, run_id: &str) -> Result<RunInstance> {
     let path = run_json_path(home, run_id);
+    if !path.exists() {
+        return Err(DmError::not_found("run", run_id).into());
+    }
     let content = fs::read_to_string(&path)
         .with_context(|| format!("Failed to read run metadata {}", path.display()))?;
     serde_json::from_str(&content)
         .with_context(|| format!("Failed to parse run metadata {}", path.display()))
 }
 
+/// Persist `run`. The first save that takes a run out of `Running` emits a
+/// `run.finished` event, whichever code path noticed the run ending.
 pub fn save_run(home: &Path, run: &RunInstance) -> Result<()> {
+    let finished = !run.status.is_running()
+        && load_run(home, &run.run_id).is_ok_and(|old| old.status.is_running());
     let path = run_json_path(home, &run.run_id);
     let content = serde_json::to_string_pretty(run)?;
     let tmp_path = path.with_extension(format!("{}.tmp", std::process::id()));
     fs::write(&tmp_path, &content)
         .with_context(|| format!("Failed to write temp run metadata {}", tmp_path.display()))?;
     fs::rename(&tmp_path, &path)
-        .with_context(|| format!("Failed to rename run metadata {}", path.display()))
+        .with_context(|| format!("Failed to rename run metadata {}", path.display()))?;
+    if finished {
+        emit_run_finished(home, run);
+    }
+    Ok(())
+}
+
+fn emit_run_finished(home: &Path, run: &RunInstance) {
+    let level = if run.status == RunStatus::Failed {
+        EventLevel::Error
+    } else {
+        EventLevel::Info
+    };
+    let mut event = EventBuilder::new(EventSource::Core, "run.finished")
+        .case_id(&run.run_id)
+        .level(level)
+        .message(format!(
+            "{} {}: {}",
+            run.dataflow_name,
+            run.status.as_str(),
+            run.outcome.summary
+        ))
+        .attr("run_id", &run.run_id)
+        .attr("dataflow", &run.dataflow_name)
+        .attr("status", run.status.as_str());
+    if let Some(reason) = run.termination_reason {
+        event = event.attr("termination_reason", reason.as_str());
+    }
+    try_emit(home, event.build());
 }
 
 pub fn list_run_instances(home: &Path) -> Result<Vec<RunInstance>> {
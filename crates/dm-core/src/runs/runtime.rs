@@ -3,10 +3,12 @@ use std::path::Path;
 use std::pin::Pin;
 use std::process::{Command as StdCommand, Stdio};
 
-use anyhow::{bail, Context, Result};
+use anyhow::{Context, Result};
 
 use crate::dora;
+use crate::error::DmError;
 use crate::runs::model::RunStatus;
+use crate::runtime_manager::RuntimeInstance;
 
 type StartResult = (Option<String>, String);
 type BoxFutureResult<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
@@ -47,9 +49,21 @@ impl RuntimeBackend for DoraCliBackend {
         transpiled_path: &'a Path,
     ) -> BoxFutureResult<'a, StartResult> {
         Box::pin(async move {
+            let args = [
+                "start".to_string(),
+                transpiled_path.to_string_lossy().into_owned(),
+                "--detach".to_string(),
+            ];
+            if let Some((code, stdout, stderr)) = dora::mocked(home, &args) {
+                if code != 0 {
+                    return Err(dora_failed(stderr.trim()));
+                }
+                return Ok((extract_dataflow_id(&stdout), stdout.trim().to_string()));
+            }
             let dora_bin = dora::active_dora_bin(home)?;
             let output = tokio::process::Command::new(&dora_bin)
-                .args(["start", &transpiled_path.to_string_lossy(), "--detach"])
+                .args(&args)
+                .args(dora::coordinator_args(home)?)
                 .output()
                 .await
                 .with_context(|| format!("Failed to run dora at {}", dora_bin.display()))?;
@@ -58,7 +72,7 @@ impl RuntimeBackend for DoraCliBackend {
             let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
             if !output.status.success() {
                 let message = if stderr.is_empty() { stdout } else { stderr };
-                bail!(message);
+                return Err(dora_failed(message));
             }
 
             let combined = if stderr.is_empty() {
@@ -93,33 +107,133 @@ impl RuntimeBackend for DoraCliBackend {
                         } else {
                             stderr
                         };
-                        bail!(message.trim().to_string());
+                        return Err(dora_failed(message.trim()));
                     }
                     Ok(())
                 }
                 Ok(Err(e)) => Err(e),
-                Err(_) => bail!(stop_timeout_message()),
+                Err(_) => Err(dora_failed(stop_timeout_message())),
+            }
+        })
+    }
+
+    fn list(&self, home: &Path) -> Result<Vec<RuntimeDataflow>> {
+        if let Some((code, stdout, stderr)) =
+            dora::mocked(home, &dora::LIST_JSON_ARGS.map(String::from))
+        {
+            if code != 0 {
+                return Err(dora_failed(stderr.trim()));
+            }
+            return Ok(parse_runtime_dataflows(&stdout));
+        }
+        list_with(
+            &dora::active_dora_bin(home)?,
+            &dora::coordinator_args(home)?,
+        )
+    }
+}
+
+/// Drives a dedicated runtime from `runtime_manager` instead of the shared one.
+#[derive(Debug, Clone)]
+pub struct IsolatedBackend {
+    pub runtime: RuntimeInstance,
+}
+
+impl IsolatedBackend {
+    pub fn new(runtime: RuntimeInstance) -> Self {
+        Self { runtime }
+    }
+}
+
+impl RuntimeBackend for IsolatedBackend {
+    fn start_detached<'a>(
+        &'a self,
+        home: &'a Path,
+        transpiled_path: &'a Path,
+    ) -> BoxFutureResult<'a, StartResult> {
+        Box::pin(async move {
+            let dora_bin = self.runtime.dora_bin(home);
+            let output = tokio::process::Command::new(&dora_bin)
+                .args(["start", &transpiled_path.to_string_lossy(), "--detach"])
+                .args(self.runtime.cli_args())
+                .output()
+                .await
+                .with_context(|| format!("Failed to run dora at {}", dora_bin.display()))?;
+
+            let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            if !output.status.success() {
+                return Err(dora_failed(if stderr.is_empty() { stdout } else { stderr }));
+            }
+            let combined = format!("{}\n{}", stdout, stderr);
+            Ok((
+                extract_dataflow_id(&combined),
+                if stdout.is_empty() { stderr } else { stdout },
+            ))
+        })
+    }
+
+    fn stop<'a>(&'a self, home: &'a Path, dora_uuid: &'a str) -> BoxFutureResult<'a, ()> {
+        Box::pin(async move {
+            let output = tokio::time::timeout(
+                std::time::Duration::from_secs(STOP_TIMEOUT_SECS),
+                tokio::process::Command::new(self.runtime.dora_bin(home))
+                    .args(["stop", dora_uuid])
+                    .args(self.runtime.cli_args())
+                    .output(),
+            )
+            .await;
+
+            match output {
+                Ok(Ok(output)) if output.status.success() => Ok(()),
+                Ok(Ok(output)) => {
+                    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+                    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+                    Err(dora_failed(if stderr.is_empty() { stdout } else { stderr }))
+                }
+                Ok(Err(e)) => Err(e.into()),
+                Err(_) => Err(dora_failed(stop_timeout_message())),
             }
         })
     }
 
     fn list(&self, home: &Path) -> Result<Vec<RuntimeDataflow>> {
-        let dora_bin = dora::active_dora_bin(home)?;
-        let output = StdCommand::new(&dora_bin)
-            .arg("list")
+        list_with(&self.runtime.dora_bin(home), &self.runtime.cli_args())
+    }
+}
+
+/// `dora list` through `dora_bin`, asking for JSON output first and falling
+/// back to the table for dora releases without `--format`.
+fn list_with(dora_bin: &Path, cli_args: &[String]) -> Result<Vec<RuntimeDataflow>> {
+    let list = |args: &[&str]| {
+        StdCommand::new(dora_bin)
+            .args(args)
+            .args(cli_args)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .output()
-            .with_context(|| format!("Failed to run dora at {}", dora_bin.display()))?;
-
-        if !output.status.success() {
-            bail!(String::from_utf8_lossy(&output.stderr).trim().to_string());
-        }
+            .with_context(|| format!("Failed to run dora at {}", dora_bin.display()))
+    };
+    let mut output = list(&dora::LIST_JSON_ARGS[..])?;
+    if dora::list_format_unsupported(
+        output.status.code().unwrap_or(-1),
+        &String::from_utf8_lossy(&output.stderr),
+    ) {
+        output = list(&["list"])?;
+    }
 
-        Ok(parse_runtime_dataflows(&String::from_utf8_lossy(
-            &output.stdout,
-        )))
+    if !output.status.success() {
+        return Err(dora_failed(String::from_utf8_lossy(&output.stderr).trim()));
     }
+
+    Ok(parse_runtime_dataflows(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// A dora command that ran but reported failure.
+fn dora_failed(message: impl Into<String>) -> anyhow::Error {
+    DmError::External(message.into()).into()
 }
 
 pub(crate) fn stop_timeout_message() -> String {
@@ -143,22 +257,11 @@ pub fn extract_dataflow_id(output: &str) -> Option<String> {
 }
 
 fn parse_runtime_dataflows(stdout: &str) -> Vec<RuntimeDataflow> {
-    stdout
-        .lines()
-        .map(str::trim)
-        .filter(|line| !line.is_empty())
-        .filter(|line| !line.starts_with("UUID"))
-        .filter_map(|line| {
-            let parts: Vec<&str> = line.split_whitespace().collect();
-            let id = parts.first()?.to_string();
-            if uuid::Uuid::parse_str(&id).is_err() {
-                return None;
-            }
-
-            Some(RuntimeDataflow {
-                id,
-                status: map_status(parts.get(2).copied()),
-            })
+    dora::parse_runtime_infos(stdout)
+        .into_iter()
+        .map(|info| RuntimeDataflow {
+            status: map_status(info.status.as_deref()),
+            id: info.id,
         })
         .collect()
 }
@@ -176,6 +279,7 @@ fn map_status(value: Option<&str>) -> RunStatus {
 #[cfg(test)]
 mod tests {
     use super::{extract_dataflow_id, parse_runtime_dataflows};
+    use crate::dora::parse_runtime_infos;
     use crate::runs::model::RunStatus;
 
     #[test]
@@ -231,4 +335,38 @@ not-a-uuid demo Running 1 0.0% 0.0 GB
         assert_eq!(rows[0].id, "019cc181-adad-7654-aa78-63502362337b");
         assert_eq!(rows[0].status, RunStatus::Stopped);
     }
+
+    #[test]
+    fn parses_runtime_json_rows() {
+        let stdout = r#"{"uuid":"019cd2f0-60ce-7e6c-876f-5d42ba350287","name":"bunny","status":"Running","nodes":8,"cpu":23.16,"memory":1.83}
+{"uuid":"019cd2f0-60ce-7e6c-876f-5d42ba350288","name":null,"status":"Failed","nodes":2,"cpu":0.0,"memory":0.0}
+{"uuid":"not-a-uuid","name":"x","status":"Running"}
+"#;
+
+        let rows = parse_runtime_infos(stdout);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].name.as_deref(), Some("bunny"));
+        assert_eq!(rows[0].status.as_deref(), Some("Running"));
+        assert_eq!(rows[0].nodes, Some(8));
+        assert_eq!(rows[0].cpu.as_deref(), Some("23.2%"));
+        assert_eq!(rows[0].memory.as_deref(), Some("1.8 GB"));
+        assert_eq!(rows[1].name, None);
+        assert_eq!(parse_runtime_dataflows(stdout)[1].status, RunStatus::Failed);
+    }
+
+    #[test]
+    fn parses_unnamed_table_rows_and_memory_units() {
+        let stdout = "\
+UUID Name Status Nodes CPU Memory
+019cc181-adad-7654-aa78-63502362337b Running 3 1.5% 0.2 GB
+";
+
+        let rows = parse_runtime_infos(stdout);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].name, None);
+        assert_eq!(rows[0].status.as_deref(), Some("Running"));
+        assert_eq!(rows[0].nodes, Some(3));
+        assert_eq!(rows[0].cpu.as_deref(), Some("1.5%"));
+        assert_eq!(rows[0].memory.as_deref(), Some("0.2 GB"));
+    }
 }
@@ -1,9 +1,17 @@
 #[path = "service_admin.rs"]
 mod service_admin;
+#[path = "service_capture.rs"]
+mod service_capture;
+#[path = "service_group.rs"]
+mod service_group;
+#[path = "service_import.rs"]
+mod service_import;
 #[path = "service_metrics.rs"]
 pub(crate) mod service_metrics;
 #[path = "service_query.rs"]
 mod service_query;
+#[path = "service_restart.rs"]
+mod service_restart;
 #[path = "service_runtime.rs"]
 mod service_runtime;
 #[path = "service_start.rs"]
@@ -18,19 +26,27 @@ use anyhow::Result;
 use super::model::RunInstance;
 use crate::runs::runtime::RuntimeBackend;
 
-pub use self::service_admin::{clean_runs, delete_run};
-pub use self::service_metrics::{collect_all_active_metrics, get_run_metrics};
+pub use self::service_admin::{apply_retention, clean_runs, delete_run};
+pub use self::service_capture::{OutputCapture, RunLogCapture, DORA_OUTPUT_ACTIVITY};
+pub use self::service_group::{start_tagged, stop_tagged};
+pub use self::service_import::{import_run_events, DORA_LOG_ACTIVITY};
+pub use self::service_metrics::{
+    collect_all_active_metrics, get_run_metrics, list_dataflow_nodes, record_run_metrics,
+    run_metric_history, sample_active_runs, RUN_METRICS_ACTIVITY,
+};
 pub use self::service_query::{
     get_active_run, get_run, list_active_runs, list_runs, list_runs_filtered, read_run_log,
     read_run_log_chunk, read_run_transpiled, read_run_view,
 };
+pub use self::service_restart::{supervise_restarts, RestartAction};
 pub use self::service_runtime::{
     mark_stop_requested, reconcile_stale_running_runs, refresh_run_statuses, stop_run,
     sync_run_outputs,
 };
 pub use self::service_start::{
-    start_run_from_file, start_run_from_file_with_source_and_strategy,
-    start_run_from_file_with_strategy, start_run_from_yaml,
+    install_missing_nodes, start_run_from_file, start_run_from_file_with_isolation,
+    start_run_from_file_with_source_and_strategy, start_run_from_file_with_strategy,
+    start_run_from_yaml, start_run_from_yaml_with_isolation,
     start_run_from_yaml_with_source_and_strategy, start_run_from_yaml_with_strategy,
 };
 
@@ -1,7 +1,11 @@
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 
+use crate::config::RetentionConfig;
 use crate::runs::repo;
 
 pub fn delete_run(home: &Path, run_id: &str) -> Result<()> {
@@ -25,3 +29,108 @@ pub fn clean_runs(home: &Path, keep: usize) -> Result<u32> {
     }
     Ok(deleted)
 }
+
+/// Apply the `[retention]` policy of config.toml: remove finished runs and
+/// `dm run` artifact directories beyond `keep_runs` or older than
+/// `max_age_days`. The two are counted separately. Runs still in use are
+/// never touched: recorded runs that are running or whose dedicated runtime
+/// is alive, and `dm run` directories whose owning process is alive.
+/// Returns how many were removed.
+pub fn apply_retention(home: &Path) -> Result<u32> {
+    let policy = crate::config::load_config(home)?.retention;
+    let now = Utc::now();
+    let mut deleted = 0u32;
+
+    let finished = repo::list_run_instances(home)?
+        .into_iter()
+        .filter(|run| !run.status.is_running())
+        .filter(|run| {
+            !crate::runtime_manager::find_for_run(home, &run.run_id)
+                .is_some_and(|runtime| runtime.is_alive())
+        });
+    for (index, run) in finished.enumerate() {
+        let ended = run.stopped_at.as_deref().unwrap_or(&run.started_at);
+        let ended = DateTime::parse_from_rfc3339(ended)
+            .ok()
+            .map(|at| at.with_timezone(&Utc));
+        if !expired(&policy, index, ended, now) {
+            continue;
+        }
+        match delete_run(home, &run.run_id) {
+            Ok(()) => deleted += 1,
+            Err(e) => eprintln!("Warning: failed to clean run {}: {}", run.run_id, e),
+        }
+    }
+
+    let idle = local_runs(home)
+        .into_iter()
+        .filter(|(dir, _)| !local_run_alive(dir));
+    for (index, (dir, modified)) in idle.enumerate() {
+        if !expired(&policy, index, Some(modified), now) {
+            continue;
+        }
+        match fs::remove_dir_all(&dir) {
+            Ok(()) => deleted += 1,
+            Err(e) => eprintln!("Warning: failed to clean {}: {}", dir.display(), e),
+        }
+    }
+    Ok(deleted)
+}
+
+fn expired(
+    policy: &RetentionConfig,
+    index: usize,
+    at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> bool {
+    let too_many = policy.keep_runs > 0 && index >= policy.keep_runs;
+    let too_old = policy.max_age_days > 0
+        && at.is_some_and(|at| {
+            now.signed_duration_since(at).num_days() >= policy.max_age_days as i64
+        });
+    too_many || too_old
+}
+
+/// Artifact directories of every `dm run` invocation, newest first, with the
+/// time anything in them last changed (the output log grows while it runs).
+fn local_runs(home: &Path) -> Vec<(PathBuf, DateTime<Utc>)> {
+    let subdirs = |dir: &Path| -> Vec<PathBuf> {
+        fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect()
+    };
+    let mut runs: Vec<_> = subdirs(&crate::dataflow::local_runs_dir(home))
+        .iter()
+        .flat_map(|dataflow| subdirs(&dataflow.join("runs")))
+        .map(|dir| {
+            let modified = last_modified(&dir);
+            (dir, DateTime::<Utc>::from(modified))
+        })
+        .collect();
+    runs.sort_by_key(|run| std::cmp::Reverse(run.1));
+    runs
+}
+
+/// Whether the `dm run` process owning `dir` is still running.
+fn local_run_alive(dir: &Path) -> bool {
+    fs::read_to_string(crate::dataflow::local_run_pid_path(dir))
+        .ok()
+        .and_then(|content| content.trim().parse().ok())
+        .is_some_and(crate::lock::pid_alive)
+}
+
+fn last_modified(dir: &Path) -> SystemTime {
+    let modified = |path: &Path| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| modified(&entry.path()))
+        .chain(modified(dir))
+        .max()
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use crate::events::{Event, EventBuilder, EventFilter, EventSource, EventStore};
+use crate::runs::follow::LogFollower;
+use crate::runs::repo;
+
+use super::service_import::{log_line_event, parse_log_line, DORA_LOG_ACTIVITY};
+
+/// Activity of events recorded from a dora process's own stdout/stderr
+/// (`dora start`, or `dora run` under `dm run`).
+pub const DORA_OUTPUT_ACTIVITY: &str = "dora.output";
+/// Output lines buffered before they are written to the event store.
+const OUTPUT_BATCH_LINES: usize = 200;
+/// Longest time an output line waits in the buffer.
+const OUTPUT_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Follows the per-node log files of a running run and records new lines
+/// as the same `dora.log` events `import_run_events` produces (case id =
+/// run id), so a later import replaces them instead of duplicating them.
+pub struct RunLogCapture {
+    run_id: String,
+    nodes: HashMap<String, NodeCursor>,
+}
+
+struct NodeCursor {
+    follower: LogFollower,
+    /// Non-empty lines seen so far, including skipped ones
+    line_no: usize,
+    /// Lines already captured before this cursor was created (e.g. by a
+    /// dm-server that has since restarted)
+    skip: usize,
+}
+
+impl RunLogCapture {
+    pub fn new(run_id: &str) -> Self {
+        Self {
+            run_id: run_id.to_string(),
+            nodes: HashMap::new(),
+        }
+    }
+
+    /// Record every complete line appended to the run's node logs since the
+    /// last call. Returns the number of events written.
+    pub fn poll(&mut self, home: &Path, store: &EventStore) -> Result<usize> {
+        let mut events = Vec::new();
+        for node in repo::list_run_nodes(home, &self.run_id)? {
+            if !self.nodes.contains_key(&node.id) {
+                let path = repo::resolve_run_log_path(home, &self.run_id, &node.id)?;
+                let skip = store.count(&EventFilter {
+                    case_id: Some(self.run_id.clone()),
+                    activity: Some(DORA_LOG_ACTIVITY.to_string()),
+                    node_id: Some(node.id.clone()),
+                    ..Default::default()
+                })? as usize;
+                let cursor = NodeCursor {
+                    follower: LogFollower::new(path),
+                    line_no: 0,
+                    skip,
+                };
+                self.nodes.insert(node.id.clone(), cursor);
+            }
+            let cursor = self.nodes.get_mut(&node.id).expect("inserted above");
+            for (line_no, line) in cursor.read_new_lines()? {
+                events.push(log_line_event(&self.run_id, &node.id, line_no, &line));
+            }
+        }
+        store.emit_many(&events)
+    }
+}
+
+impl NodeCursor {
+    /// Complete, non-empty lines appended since the last read, numbered.
+    fn read_new_lines(&mut self) -> Result<Vec<(usize, String)>> {
+        let new = self.follower.read_new_lines()?;
+        if new.restarted {
+            // The log was truncated or replaced, so numbering starts over.
+            self.line_no = 0;
+            self.skip = 0;
+        }
+        let mut lines = Vec::new();
+        for line in new.lines.into_iter().filter(|l| !l.trim().is_empty()) {
+            self.line_no += 1;
+            if self.line_no > self.skip {
+                lines.push((self.line_no, line));
+            }
+        }
+        Ok(lines)
+    }
+}
+
+/// Records the stdout/stderr lines of a dora process as `dora.output`
+/// events. Lines prefixed with the id of a node in the dataflow (`[camera]
+/// ...`, `camera: ...`, or a tracing line whose target is the node) get
+/// that `node_id`. Events are written in batches; call [`flush`] (or drop
+/// the capture) once the process has exited.
+///
+/// [`flush`]: OutputCapture::flush
+pub struct OutputCapture {
+    home: PathBuf,
+    case_id: String,
+    nodes: Vec<String>,
+    pending: Vec<Event>,
+    flushed_at: Instant,
+    log: Option<File>,
+}
+
+impl OutputCapture {
+    pub fn new(home: &Path, case_id: &str, nodes: Vec<String>) -> Self {
+        Self {
+            home: home.to_path_buf(),
+            case_id: case_id.to_string(),
+            nodes,
+            pending: Vec::new(),
+            flushed_at: Instant::now(),
+            log: None,
+        }
+    }
+
+    /// Also append every line, as printed, to the file at `path`.
+    pub fn with_log_file(mut self, path: &Path) -> Self {
+        self.log = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .ok();
+        self
+    }
+
+    /// Node ids of the dataflow at `yaml_path`, for attributing output.
+    pub fn nodes_of(yaml_path: &Path) -> Vec<String> {
+        std::fs::read_to_string(yaml_path)
+            .ok()
+            .and_then(|yaml| serde_yaml::from_str::<serde_yaml::Value>(&yaml).ok())
+            .and_then(|graph| graph.get("nodes")?.as_sequence().cloned())
+            .map(|nodes| {
+                nodes
+                    .iter()
+                    .filter_map(|n| n.get("id")?.as_str().map(str::to_string))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Record one line read from `stream` (`"stdout"` or `"stderr"`).
+    pub fn push(&mut self, stream: &str, line: &str) {
+        let line = strip_ansi(line);
+        if let Some(log) = &mut self.log {
+            let _ = writeln!(log, "{line}");
+        }
+        if line.trim().is_empty() {
+            return;
+        }
+        let (node, rest) = match split_node_prefix(&line, &self.nodes) {
+            Some((node, rest)) => (Some(node.to_string()), rest),
+            None => (None, line.as_str()),
+        };
+        let parsed = parse_log_line(rest);
+        let node = node.or_else(|| parsed.target.filter(|t| self.nodes.contains(t)));
+
+        let mut builder = EventBuilder::new(EventSource::Dataflow, DORA_OUTPUT_ACTIVITY)
+            .case_id(self.case_id.clone())
+            .level(parsed.level)
+            .message(parsed.message)
+            .attr("stream", stream);
+        if let Some(node) = node {
+            builder = builder.node_id(node);
+        }
+        let mut event = builder.build();
+        if let Some(timestamp) = parsed.timestamp {
+            event.timestamp = timestamp;
+        }
+        self.pending.push(event);
+
+        if self.pending.len() >= OUTPUT_BATCH_LINES
+            || self.flushed_at.elapsed() >= OUTPUT_FLUSH_INTERVAL
+        {
+            self.flush();
+        }
+    }
+
+    /// Write buffered events to the event store.
+    pub fn flush(&mut self) {
+        self.flushed_at = Instant::now();
+        if self.pending.is_empty() {
+            return;
+        }
+        if let Ok(store) = EventStore::open(&self.home) {
+            let _ = store.emit_many(&self.pending);
+        }
+        self.pending.clear();
+    }
+}
+
+impl Drop for OutputCapture {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// `[node] rest` or `node: rest` (optionally after a timestamp and level)
+/// where `node` is one of `nodes`.
+fn split_node_prefix<'a>(line: &'a str, nodes: &[String]) -> Option<(&'a str, &'a str)> {
+    let known = |id: &str| nodes.iter().any(|n| n == id);
+    if let Some((id, rest)) = line.strip_prefix('[').and_then(|tail| tail.split_once(']')) {
+        if known(id) {
+            return Some((id, rest.trim_start()));
+        }
+    }
+    let (id, rest) = line.split_once(": ")?;
+    known(id).then_some((id, rest))
+}
+
+/// Remove terminal colour / cursor escape sequences.
+fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            out.push(c);
+            continue;
+        }
+        if chars.next() == Some('[') {
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runs::model::{RunInstance, RunStatus};
+
+    #[test]
+    fn run_log_capture_emits_only_new_complete_lines() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        let run = RunInstance {
+            run_id: "run-live".into(),
+            dora_uuid: Some("uuid-live".into()),
+            status: RunStatus::Running,
+            ..Default::default()
+        };
+        repo::create_layout(home, &run.run_id).unwrap();
+        repo::save_run(home, &run).unwrap();
+        let dir = repo::run_out_dir(home, &run.run_id).join("uuid-live");
+        std::fs::create_dir_all(&dir).unwrap();
+        let log = dir.join("log_camera.txt");
+        std::fs::write(&log, "first\nsecond\npart").unwrap();
+
+        let store = EventStore::open(home).unwrap();
+        let mut capture = RunLogCapture::new(&run.run_id);
+        assert_eq!(capture.poll(home, &store).unwrap(), 2);
+        assert_eq!(capture.poll(home, &store).unwrap(), 0);
+
+        std::fs::write(&log, "first\nsecond\npartial\nthird\n").unwrap();
+        assert_eq!(capture.poll(home, &store).unwrap(), 2);
+
+        // A fresh capture (e.g. after a server restart) does not repeat lines.
+        let mut resumed = RunLogCapture::new(&run.run_id);
+        assert_eq!(resumed.poll(home, &store).unwrap(), 0);
+
+        let events = store
+            .query(&EventFilter {
+                source: Some("dataflow".into()),
+                case_id: Some(run.run_id.clone()),
+                ..Default::default()
+            })
+            .unwrap();
+        let messages: Vec<_> = events
+            .iter()
+            .rev()
+            .filter_map(|e| e.message.clone())
+            .collect();
+        assert_eq!(messages, vec!["first", "second", "partial", "third"]);
+        assert!(events
+            .iter()
+            .all(|e| e.node_id.as_deref() == Some("camera")));
+    }
+
+    #[test]
+    fn output_capture_attributes_node_prefixed_lines() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        let nodes = vec!["camera".to_string(), "detector".to_string()];
+        {
+            let mut capture = OutputCapture::new(home, "demo", nodes);
+            capture.push("stdout", "\u{1b}[32mcamera\u{1b}[0m: frame 1");
+            capture.push("stdout", "[detector] found 2 objects");
+            capture.push(
+                "stderr",
+                "2025-03-01T10:00:00Z ERROR detector: model missing",
+            );
+            capture.push("stderr", "dataflow start triggered: abc");
+            capture.push("stdout", "   ");
+        }
+
+        let events = EventStore::open(home)
+            .unwrap()
+            .query(&EventFilter {
+                case_id: Some("demo".into()),
+                activity: Some(DORA_OUTPUT_ACTIVITY.into()),
+                ..Default::default()
+            })
+            .unwrap();
+        let seen: Vec<_> = events
+            .iter()
+            .rev()
+            .map(|e| {
+                (
+                    e.node_id.clone(),
+                    e.level.clone(),
+                    e.message.clone().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            seen,
+            vec![
+                (Some("camera".into()), "info".into(), "frame 1".into()),
+                (
+                    Some("detector".into()),
+                    "info".into(),
+                    "found 2 objects".into()
+                ),
+                (
+                    Some("detector".into()),
+                    "error".into(),
+                    "model missing".into()
+                ),
+                (None, "info".into(), "dataflow start triggered: abc".into()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! Starting and stopping saved dataflows as a group, selected by a tag in
+//! their meta (`tags: [perception]`). Each dataflow is handled on its own:
+//! one failing does not stop the rest, and the report lists every outcome.
+
+use std::path::Path;
+
+use anyhow::Result;
+
+use crate::error::DmError;
+use crate::events::{EventSource, OperationEvent};
+
+use crate::runs::model::{
+    GroupRunEntry, GroupRunReport, RunIsolation, RunSource, StartConflictStrategy,
+};
+
+/// Start every saved dataflow tagged `tag`, in name order.
+pub async fn start_tagged(
+    home: &Path,
+    tag: &str,
+    source: RunSource,
+    strategy: StartConflictStrategy,
+    isolation: RunIsolation,
+) -> Result<GroupRunReport> {
+    let op =
+        OperationEvent::new(home, EventSource::Dataflow, "dataflow.group_start").attr("tag", tag);
+    op.emit_start();
+
+    let result = async {
+        let names = tagged(home, tag)?;
+        let mut results = Vec::new();
+        for name in names {
+            let started = match crate::dataflow::get(home, &name) {
+                Ok(project) => {
+                    super::start_run_from_yaml_with_isolation(
+                        home,
+                        &project.yaml,
+                        &name,
+                        None,
+                        source,
+                        strategy,
+                        isolation.clone(),
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            };
+            results.push(match started {
+                Ok(started) => GroupRunEntry {
+                    dataflow: name,
+                    ok: true,
+                    run_ids: vec![started.run.run_id],
+                    message: started.message,
+                },
+                Err(e) => GroupRunEntry {
+                    dataflow: name,
+                    ok: false,
+                    run_ids: Vec::new(),
+                    message: format!("{:#}", e),
+                },
+            });
+        }
+        Ok(GroupRunReport::new(tag, results))
+    }
+    .await;
+
+    op.emit_result(&result);
+    result
+}
+
+/// Stop the active runs of every saved dataflow tagged `tag`. Dataflows
+/// with no active run are reported as succeeded.
+pub async fn stop_tagged(home: &Path, tag: &str) -> Result<GroupRunReport> {
+    let op =
+        OperationEvent::new(home, EventSource::Dataflow, "dataflow.group_stop").attr("tag", tag);
+    op.emit_start();
+
+    let result = async {
+        let names = tagged(home, tag)?;
+        let active = super::list_active_runs(home)?;
+        let mut results = Vec::new();
+        for name in names {
+            let mut entry = GroupRunEntry {
+                dataflow: name.clone(),
+                ok: true,
+                run_ids: Vec::new(),
+                message: "not running".to_string(),
+            };
+            let mut errors = Vec::new();
+            for run in active.iter().filter(|run| run.dataflow_name == name) {
+                match super::stop_run(home, &run.run_id).await {
+                    Ok(_) => entry.run_ids.push(run.run_id.clone()),
+                    Err(e) => errors.push(format!("{}: {:#}", run.run_id, e)),
+                }
+            }
+            if !errors.is_empty() {
+                entry.ok = false;
+                entry.message = errors.join("; ");
+            } else if !entry.run_ids.is_empty() {
+                entry.message = format!("stopped {} run(s)", entry.run_ids.len());
+            }
+            results.push(entry);
+        }
+        Ok(GroupRunReport::new(tag, results))
+    }
+    .await;
+
+    op.emit_result(&result);
+    result
+}
+
+fn tagged(home: &Path, tag: &str) -> Result<Vec<String>> {
+    let names = crate::dataflow::dataflows_with_tag(home, tag)?;
+    if names.is_empty() {
+        return Err(DmError::not_found("tag", tag).into());
+    }
+    Ok(names)
+}
@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::{bail, Result};
+
+use crate::events::{EventBuilder, EventLevel, EventSource, EventStore, OperationEvent};
+use crate::runs::model::RunEventImport;
+use crate::runs::repo;
+
+/// Activity of events imported from dora's per-node log files.
+pub const DORA_LOG_ACTIVITY: &str = "dora.log";
+/// Newest lines kept per node; older ones are dropped to bound events.db.
+const MAX_LINES_PER_NODE: usize = 20_000;
+
+/// Import a finished run's dora logs into the event store as `dora.log`
+/// Dataflow events keyed by the run id, so in-graph execution sits next to
+/// dm's own operations for process mining. Importing again replaces the
+/// previous import.
+pub fn import_run_events(home: &Path, run_id: &str) -> Result<RunEventImport> {
+    let op =
+        OperationEvent::new(home, EventSource::Core, "run.import_events").attr("run_id", run_id);
+    op.emit_start();
+
+    let result = (|| {
+        let run = repo::load_run(home, run_id)?;
+        if run.status.is_running() {
+            bail!(
+                "Run '{}' is still running; stop it before importing its logs",
+                run_id
+            );
+        }
+
+        let mut events = Vec::new();
+        let mut report = RunEventImport {
+            run_id: run_id.to_string(),
+            nodes: BTreeMap::new(),
+            imported: 0,
+            truncated: Vec::new(),
+        };
+        for node in repo::list_run_nodes(home, run_id)? {
+            let log = repo::read_run_log_file(home, run_id, &node.id)?;
+            let lines: Vec<&str> = log.lines().filter(|l| !l.trim().is_empty()).collect();
+            let skip = lines.len().saturating_sub(MAX_LINES_PER_NODE);
+            if skip > 0 {
+                report.truncated.push(node.id.clone());
+            }
+            for (index, line) in lines.iter().enumerate().skip(skip) {
+                events.push(log_line_event(run_id, &node.id, index + 1, line));
+            }
+            report.nodes.insert(node.id, lines.len() - skip);
+        }
+
+        let store = EventStore::open(home)?;
+        store.delete_by_case_and_activity(run_id, DORA_LOG_ACTIVITY)?;
+        report.imported = store.emit_many(&events)?;
+        Ok(report)
+    })();
+
+    op.emit_result(&result);
+    result
+}
+
+pub(super) fn log_line_event(
+    run_id: &str,
+    node_id: &str,
+    line_no: usize,
+    line: &str,
+) -> crate::events::Event {
+    let parsed = parse_log_line(line);
+    let mut builder = EventBuilder::new(EventSource::Dataflow, DORA_LOG_ACTIVITY)
+        .case_id(run_id)
+        .node_id(node_id)
+        .level(parsed.level)
+        .message(parsed.message)
+        .attr("line", line_no);
+    if let Some(target) = parsed.target {
+        builder = builder.attr("target", target);
+    }
+    let mut event = builder.build();
+    if let Some(timestamp) = parsed.timestamp {
+        event.timestamp = timestamp;
+    }
+    event
+}
+
+#[derive(Debug, PartialEq)]
+pub(super) struct ParsedLine {
+    pub(super) timestamp: Option<String>,
+    pub(super) level: EventLevel,
+    pub(super) target: Option<String>,
+    pub(super) message: String,
+}
+
+/// Understand the three shapes found in dora log files: JSON tracing
+/// records, text tracing lines (`<rfc3339>  INFO target: message`) and
+/// plain node output, which is kept verbatim at info level.
+pub(super) fn parse_log_line(line: &str) -> ParsedLine {
+    let line = line.trim_end();
+    if line.starts_with('{') {
+        if let Ok(value) = serde_json::from_str::<serde_json::Value>(line) {
+            let text = |v: &serde_json::Value| v.as_str().map(str::to_string);
+            return ParsedLine {
+                timestamp: value
+                    .get("timestamp")
+                    .and_then(text)
+                    .and_then(normalize_timestamp),
+                level: value
+                    .get("level")
+                    .and_then(|v| v.as_str())
+                    .and_then(parse_level)
+                    .unwrap_or(EventLevel::Info),
+                target: value.get("target").and_then(text),
+                message: value
+                    .pointer("/fields/message")
+                    .or_else(|| value.get("message"))
+                    .and_then(text)
+                    .unwrap_or_else(|| line.to_string()),
+            };
+        }
+    }
+
+    let (timestamp, rest) = match line.split_once(char::is_whitespace) {
+        Some((first, rest)) => match normalize_timestamp(first.to_string()) {
+            Some(ts) => (Some(ts), rest.trim_start()),
+            None => (None, line),
+        },
+        None => (None, line),
+    };
+    let (level, rest) = match rest.split_once(char::is_whitespace) {
+        Some((word, rest)) => match parse_level(word) {
+            Some(level) => (Some(level), rest.trim_start()),
+            None => (None, rest),
+        },
+        None => (parse_level(rest), ""),
+    };
+    let Some(level) = level else {
+        return ParsedLine {
+            timestamp: None,
+            level: EventLevel::Info,
+            target: None,
+            message: line.to_string(),
+        };
+    };
+
+    let (target, message) = match rest.split_once(": ") {
+        Some((target, message)) if !target.is_empty() && !target.contains(char::is_whitespace) => {
+            (Some(target.to_string()), message.to_string())
+        }
+        _ => (None, rest.to_string()),
+    };
+    ParsedLine {
+        timestamp,
+        level,
+        target,
+        message,
+    }
+}
+
+fn normalize_timestamp(raw: String) -> Option<String> {
+    chrono::DateTime::parse_from_rfc3339(&raw)
+        .ok()
+        .map(|ts| ts.with_timezone(&chrono::Utc).to_rfc3339())
+}
+
+fn parse_level(word: &str) -> Option<EventLevel> {
+    match word.to_ascii_uppercase().as_str() {
+        "TRACE" => Some(EventLevel::Trace),
+        "DEBUG" => Some(EventLevel::Debug),
+        "INFO" => Some(EventLevel::Info),
+        "WARN" | "WARNING" => Some(EventLevel::Warn),
+        "ERROR" => Some(EventLevel::Error),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventFilter;
+    use crate::runs::model::{RunInstance, RunStatus};
+
+    #[test]
+    fn parse_log_line_reads_text_json_and_plain_output() {
+        let text = parse_log_line("2025-03-01T10:00:00.5Z  WARN dora_daemon::spawn: node slow");
+        assert_eq!(text.level, EventLevel::Warn);
+        assert_eq!(text.target.as_deref(), Some("dora_daemon::spawn"));
+        assert_eq!(text.message, "node slow");
+        assert_eq!(
+            text.timestamp.as_deref(),
+            Some("2025-03-01T10:00:00.500+00:00")
+        );
+
+        let json = parse_log_line(
+            r#"{"timestamp":"2025-03-01T10:00:01Z","level":"ERROR","target":"camera","fields":{"message":"no device"}}"#,
+        );
+        assert_eq!(json.level, EventLevel::Error);
+        assert_eq!(json.message, "no device");
+        assert_eq!(json.target.as_deref(), Some("camera"));
+
+        let plain = parse_log_line("frame 42: 640x480");
+        assert_eq!(plain.level, EventLevel::Info);
+        assert_eq!(plain.timestamp, None);
+        assert_eq!(plain.message, "frame 42: 640x480");
+    }
+
+    #[test]
+    fn import_run_events_replaces_previous_import() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        let run = RunInstance {
+            run_id: "run-import".into(),
+            status: RunStatus::Succeeded,
+            ..Default::default()
+        };
+        repo::create_layout(home, &run.run_id).unwrap();
+        repo::save_run(home, &run).unwrap();
+        let logs = repo::run_logs_dir(home, &run.run_id);
+        std::fs::create_dir_all(&logs).unwrap();
+        std::fs::write(
+            logs.join("camera.log"),
+            "2025-03-01T10:00:00Z ERROR camera: no device\nretrying\n\n",
+        )
+        .unwrap();
+
+        let report = import_run_events(home, &run.run_id).unwrap();
+        assert_eq!(report.imported, 2);
+        assert_eq!(report.nodes.get("camera"), Some(&2));
+
+        import_run_events(home, &run.run_id).unwrap();
+        let events = EventStore::open(home)
+            .unwrap()
+            .query(&EventFilter {
+                case_id: Some(run.run_id.clone()),
+                activity: Some(DORA_LOG_ACTIVITY.into()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|e| e.source == "dataflow"));
+        let error = events.iter().find(|e| e.level == "error").unwrap();
+        assert_eq!(error.node_id.as_deref(), Some("camera"));
+        assert_eq!(error.timestamp, "2025-03-01T10:00:00+00:00");
+    }
+
+    #[test]
+    fn import_run_events_refuses_running_runs() {
+        let tmp = tempfile::tempdir().unwrap();
+        let run = RunInstance {
+            run_id: "run-live".into(),
+            status: RunStatus::Running,
+            ..Default::default()
+        };
+        repo::create_layout(tmp.path(), &run.run_id).unwrap();
+        repo::save_run(tmp.path(), &run).unwrap();
+
+        let err = import_run_events(tmp.path(), &run.run_id).unwrap_err();
+        assert!(err.to_string().contains("still running"));
+    }
+}
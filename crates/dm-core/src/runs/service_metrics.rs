@@ -3,11 +3,20 @@ use std::path::Path;
 use std::process::{Command, Stdio};
 
 use anyhow::{Context, Result};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, System};
 
 use crate::dora;
-use crate::runs::model::{NodeMetrics, RunMetrics};
+use crate::events::{EventBuilder, EventFilter, EventLevel, EventSource, EventStore};
+use crate::runs::model::{NodeMetricSample, NodeMetrics, RunMetricSample, RunMetrics};
 use crate::runs::repo;
 
+/// Activity of the node samples recorded while a run is active (case id =
+/// run id).
+pub const RUN_METRICS_ACTIVITY: &str = "run.metrics";
+/// Samples returned by `run_metric_history` when no limit is given: one
+/// hour at the dm-server sampling interval of 5s.
+const DEFAULT_HISTORY_LIMIT: i64 = 720;
+
 /// Collect runtime metrics for a single running dataflow.
 ///
 /// Returns `Ok(None)` when the run is not currently active in the Dora runtime.
@@ -33,6 +42,11 @@ pub fn get_run_metrics(home: &Path, run_id: &str) -> Result<Option<RunMetrics>>
     }))
 }
 
+/// Nodes of the dataflow `dora_uuid` as reported by `dora node list`.
+pub fn list_dataflow_nodes(home: &Path, dora_uuid: &str) -> Result<Vec<NodeMetrics>> {
+    collect_node_metrics(home, dora_uuid)
+}
+
 /// Collect metrics for all active dataflows in one shot.
 /// Returns a map keyed by dora_uuid.
 pub fn collect_all_active_metrics(home: &Path) -> Result<HashMap<String, RunMetrics>> {
@@ -54,6 +68,124 @@ pub fn collect_all_active_metrics(home: &Path) -> Result<HashMap<String, RunMetr
     Ok(result)
 }
 
+/// Measure the node processes of every running run: pids come from
+/// `dora node list`, CPU and memory from sysinfo. Runs without a live node
+/// process are left out. Returns `(run_id, sample)` pairs.
+pub async fn sample_active_runs(home: &Path) -> Result<Vec<(String, RunMetricSample)>> {
+    let mut targets: Vec<(String, Vec<(String, Pid)>)> = Vec::new();
+    for run in repo::list_run_instances(home)? {
+        let Some(uuid) = run.dora_uuid.as_deref().filter(|_| run.status.is_running()) else {
+            continue;
+        };
+        let pids: Vec<(String, Pid)> = collect_node_metrics(home, uuid)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|node| {
+                let pid = node.pid.as_deref()?.trim().parse::<u32>().ok()?;
+                Some((node.id, Pid::from_u32(pid)))
+            })
+            .collect();
+        if !pids.is_empty() {
+            targets.push((run.run_id, pids));
+        }
+    }
+    if targets.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let all: Vec<Pid> = targets
+        .iter()
+        .flat_map(|(_, pids)| pids.iter().map(|(_, pid)| *pid))
+        .collect();
+    let kind = ProcessRefreshKind::nothing().with_cpu().with_memory();
+    let mut system = System::new();
+    // CPU usage is measured between two refreshes.
+    system.refresh_processes_specifics(ProcessesToUpdate::Some(&all), true, kind);
+    tokio::time::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL).await;
+    system.refresh_processes_specifics(ProcessesToUpdate::Some(&all), true, kind);
+
+    let timestamp = chrono::Utc::now().to_rfc3339();
+    Ok(targets
+        .into_iter()
+        .filter_map(|(run_id, pids)| {
+            let nodes: Vec<NodeMetricSample> = pids
+                .into_iter()
+                .filter_map(|(node_id, pid)| {
+                    let process = system
+                        .process(pid)
+                        .filter(|process| process.status() != ProcessStatus::Zombie)?;
+                    Some(NodeMetricSample {
+                        node_id,
+                        pid: pid.as_u32(),
+                        cpu_percent: process.cpu_usage(),
+                        memory_bytes: process.memory(),
+                    })
+                })
+                .collect();
+            (!nodes.is_empty()).then(|| {
+                (
+                    run_id,
+                    RunMetricSample {
+                        timestamp: timestamp.clone(),
+                        nodes,
+                    },
+                )
+            })
+        })
+        .collect())
+}
+
+/// Store `sample` as a `run.metrics` event of run `run_id`.
+pub fn record_run_metrics(
+    store: &EventStore,
+    run_id: &str,
+    sample: &RunMetricSample,
+) -> Result<i64> {
+    let event = EventBuilder::new(EventSource::Dataflow, RUN_METRICS_ACTIVITY)
+        .case_id(run_id)
+        .level(EventLevel::Debug)
+        .attr("nodes", &sample.nodes)
+        .build();
+    store.emit(&event)
+}
+
+/// Recorded samples of run `run_id`, oldest first: those after `since`
+/// (RFC 3339), at most the newest `limit` of them.
+pub fn run_metric_history(
+    home: &Path,
+    run_id: &str,
+    since: Option<&str>,
+    limit: Option<i64>,
+) -> Result<Vec<RunMetricSample>> {
+    repo::load_run(home, run_id)?;
+    let store = EventStore::open(home)?;
+    let events = store.query(&EventFilter {
+        source: Some(EventSource::Dataflow.to_string()),
+        case_id: Some(run_id.to_string()),
+        activity: Some(RUN_METRICS_ACTIVITY.to_string()),
+        since: since.map(str::to_string),
+        limit: Some(limit.unwrap_or(DEFAULT_HISTORY_LIMIT)),
+        ..Default::default()
+    })?;
+    Ok(events
+        .into_iter()
+        .rev()
+        .filter(|event| event.activity == RUN_METRICS_ACTIVITY)
+        .map(|event| {
+            let nodes = event
+                .attributes
+                .as_deref()
+                .and_then(|attrs| serde_json::from_str::<serde_json::Value>(attrs).ok())
+                .and_then(|attrs| serde_json::from_value(attrs.get("nodes")?.clone()).ok())
+                .unwrap_or_default();
+            RunMetricSample {
+                timestamp: event.timestamp,
+                nodes,
+            }
+        })
+        .collect())
+}
+
 // ─── Internal helpers ───
 
 struct DataflowAggregateMetrics {
@@ -62,9 +194,18 @@ struct DataflowAggregateMetrics {
 }
 
 fn collect_dataflow_metrics(home: &Path) -> Result<HashMap<String, DataflowAggregateMetrics>> {
+    let args = ["list", "--format", "json"].map(String::from);
+    if let Some((code, stdout, _)) = dora::mocked(home, &args) {
+        return if code == 0 {
+            parse_dataflow_metrics_json(&stdout)
+        } else {
+            Ok(HashMap::new())
+        };
+    }
     let dora_bin = dora::active_dora_bin(home)?;
     let output = Command::new(&dora_bin)
-        .args(["list", "--format", "json"])
+        .args(&args)
+        .args(dora::coordinator_args(home)?)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
@@ -79,9 +220,18 @@ fn collect_dataflow_metrics(home: &Path) -> Result<HashMap<String, DataflowAggre
 }
 
 fn collect_node_metrics(home: &Path, dora_uuid: &str) -> Result<Vec<NodeMetrics>> {
+    let args = ["node", "list", "--format", "json", "--dataflow", dora_uuid].map(String::from);
+    if let Some((code, stdout, _)) = dora::mocked(home, &args) {
+        return Ok(if code == 0 {
+            parse_node_metrics_json(&stdout)
+        } else {
+            Vec::new()
+        });
+    }
     let dora_bin = dora::active_dora_bin(home)?;
     let output = Command::new(&dora_bin)
-        .args(["node", "list", "--format", "json", "--dataflow", dora_uuid])
+        .args(&args)
+        .args(dora::coordinator_args(home)?)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
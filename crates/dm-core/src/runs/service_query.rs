@@ -90,10 +90,8 @@ pub fn read_run_log_chunk(
     offset: u64,
 ) -> Result<RunLogChunk> {
     let run = repo::load_run(home, run_id)?;
-    let (content, next_offset) = match repo::read_run_log_chunk(home, run_id, node_id, offset) {
-        Ok(chunk) => chunk,
-        Err(_) => (String::new(), 0),
-    };
+    let (content, next_offset) =
+        repo::read_run_log_chunk(home, run_id, node_id, offset).unwrap_or_default();
 
     Ok(RunLogChunk {
         run_id: run_id.to_string(),
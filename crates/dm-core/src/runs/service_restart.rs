@@ -0,0 +1,231 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::events::{try_emit, EventBuilder, EventLevel, EventSource};
+use crate::runs::graph::extract_restart_policies;
+use crate::runs::model::{
+    NodeRestartSpec, RestartPolicy, RunInstance, RunRestart, StartConflictStrategy,
+    TerminationReason,
+};
+use crate::runs::runtime::RuntimeBackend;
+use crate::runs::{repo, runtime};
+
+/// Terminated runs older than this are never restarted automatically.
+const RESTART_WINDOW_SECS: i64 = 3600;
+
+/// What the restart supervisor did for a terminated run.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum RestartAction {
+    Restarted {
+        from_run_id: String,
+        to_run_id: String,
+        attempt: u32,
+    },
+    Exhausted {
+        run_id: String,
+        attempts: u32,
+    },
+    Failed {
+        run_id: String,
+        error: String,
+    },
+}
+
+/// Restart terminated runs whose dataflow declares a node `restart:` policy.
+///
+/// Meant to be called periodically (dm-server runs it every few seconds).
+/// Only the newest run of each dataflow is considered; runs stopped by the
+/// user are left alone.
+pub async fn supervise_restarts(home: &Path) -> Result<Vec<RestartAction>> {
+    let backend = runtime::default_backend();
+    supervise_restarts_with_backend(home, &backend, true).await
+}
+
+pub(super) async fn supervise_restarts_with_backend<B: RuntimeBackend>(
+    home: &Path,
+    backend: &B,
+    ensure_runtime: bool,
+) -> Result<Vec<RestartAction>> {
+    let mut runs = repo::list_run_instances(home)?;
+    super::service_runtime::refresh_run_statuses_with_backend(home, &mut runs, backend)?;
+    runs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+
+    let now = Utc::now();
+    let mut seen = HashSet::new();
+    let mut actions = Vec::new();
+
+    for mut run in runs {
+        if !seen.insert(run.dataflow_name.clone()) {
+            continue;
+        }
+        if run.status.is_running()
+            || run.restart.handled
+            || run.termination_reason == Some(TerminationReason::StoppedByUser)
+        {
+            continue;
+        }
+        let Some(stopped_at) = run
+            .stopped_at
+            .as_deref()
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+            .map(|ts| ts.with_timezone(&Utc))
+        else {
+            continue;
+        };
+        let elapsed = (now - stopped_at).num_seconds();
+        if elapsed > RESTART_WINDOW_SECS {
+            continue;
+        }
+
+        let Ok(yaml) = repo::read_run_dataflow(home, &run.run_id) else {
+            continue;
+        };
+        let Some(spec) = restart_spec_for_run(&yaml, &run) else {
+            continue;
+        };
+        if !spec.policy.should_restart(run.status) {
+            continue;
+        }
+
+        if run.restart.attempt >= spec.max_restarts {
+            run.restart.handled = true;
+            repo::save_run(home, &run)?;
+            emit_restart_event(
+                home,
+                &run.run_id,
+                EventLevel::Error,
+                format!(
+                    "Dataflow '{}' gave up after {} restart(s)",
+                    run.dataflow_name, run.restart.attempt
+                ),
+            );
+            actions.push(RestartAction::Exhausted {
+                run_id: run.run_id,
+                attempts: run.restart.attempt,
+            });
+            continue;
+        }
+
+        if elapsed < spec.backoff_for(run.restart.attempt) as i64 {
+            continue;
+        }
+
+        // Mark first so a slow start can't be picked up twice.
+        run.restart.handled = true;
+        repo::save_run(home, &run)?;
+        let lineage = RunRestart {
+            attempt: run.restart.attempt + 1,
+            restarted_from: Some(run.run_id.clone()),
+            handled: false,
+        };
+
+        let view_json = repo::read_run_view(home, &run.run_id).ok();
+        let runtime_ready = if ensure_runtime {
+            crate::ensure_runtime_up(home, false).await
+        } else {
+            Ok(())
+        };
+        let started = match runtime_ready {
+            Ok(()) => {
+                super::service_start::start_run_from_yaml_with_source_and_strategy_and_backend(
+                    home,
+                    &yaml,
+                    &run.dataflow_name,
+                    view_json.as_deref(),
+                    run.source,
+                    StartConflictStrategy::Fail,
+                    backend,
+                )
+                .await
+            }
+            Err(e) => Err(e),
+        };
+
+        match started {
+            Ok(result) => {
+                let mut new_run = result.run;
+                new_run.restart = lineage.clone();
+                repo::save_run(home, &new_run)?;
+                emit_restart_event(
+                    home,
+                    &run.run_id,
+                    EventLevel::Warn,
+                    format!(
+                        "Restarted dataflow '{}' as run {} (attempt {}/{})",
+                        run.dataflow_name, new_run.run_id, lineage.attempt, spec.max_restarts
+                    ),
+                );
+                actions.push(RestartAction::Restarted {
+                    from_run_id: run.run_id,
+                    to_run_id: new_run.run_id,
+                    attempt: lineage.attempt,
+                });
+            }
+            Err(e) => {
+                // A failed start may still have recorded a run; carry the
+                // lineage over so the attempt budget keeps counting down.
+                tag_failed_restart(home, &run, &lineage)?;
+                emit_restart_event(
+                    home,
+                    &run.run_id,
+                    EventLevel::Error,
+                    format!("Restart of dataflow '{}' failed: {}", run.dataflow_name, e),
+                );
+                actions.push(RestartAction::Failed {
+                    run_id: run.run_id,
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(actions)
+}
+
+/// Pick the restart spec that applies to a terminated run: only the failing
+/// node's own policy when that node is known, otherwise the strongest
+/// declared policy.
+fn restart_spec_for_run(yaml: &str, run: &RunInstance) -> Option<NodeRestartSpec> {
+    let mut policies = extract_restart_policies(yaml);
+    if let Some(node) = run.failure_node.as_deref() {
+        return policies.remove(node);
+    }
+    policies.into_values().max_by_key(|spec| match spec.policy {
+        RestartPolicy::Never => 0,
+        RestartPolicy::OnFailure => 1,
+        RestartPolicy::Always => 2,
+    })
+}
+
+fn tag_failed_restart(home: &Path, previous: &RunInstance, lineage: &RunRestart) -> Result<()> {
+    let newest = repo::list_run_instances(home)?
+        .into_iter()
+        .filter(|run| {
+            run.dataflow_name == previous.dataflow_name
+                && run.run_id != previous.run_id
+                && run.started_at > previous.started_at
+                && run.restart.restarted_from.is_none()
+        })
+        .max_by(|a, b| a.started_at.cmp(&b.started_at));
+    if let Some(mut run) = newest {
+        run.restart = lineage.clone();
+        repo::save_run(home, &run)?;
+    }
+    Ok(())
+}
+
+fn emit_restart_event(home: &Path, run_id: &str, level: EventLevel, message: String) {
+    try_emit(
+        home,
+        EventBuilder::new(EventSource::Core, "run.restart")
+            .case_id(run_id)
+            .level(level)
+            .message(message)
+            .build(),
+    );
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
@@ -6,7 +6,7 @@ use anyhow::{Context, Result};
 use chrono::Utc;
 
 use crate::runs::model::{LogSyncState, RunInstance, RunStatus, TerminationReason};
-use crate::runs::runtime::{is_stop_timeout_error, RuntimeBackend};
+use crate::runs::runtime::{is_stop_timeout_error, IsolatedBackend, RuntimeBackend};
 use crate::runs::state::{
     apply_terminal_state, build_outcome, infer_failure_details, parse_failure_details,
     TerminalStateUpdate,
@@ -14,8 +14,16 @@ use crate::runs::state::{
 use crate::runs::{repo, runtime};
 
 pub async fn stop_run(home: &Path, run_id: &str) -> Result<RunInstance> {
-    let backend = runtime::default_backend();
-    stop_run_with_backend(home, run_id, &backend).await
+    let Some(mut isolated) = crate::runtime_manager::find_for_run(home, run_id) else {
+        let backend = runtime::default_backend();
+        return stop_run_with_backend(home, run_id, &backend).await;
+    };
+    let backend = IsolatedBackend::new(isolated.clone());
+    let result = stop_run_with_backend(home, run_id, &backend).await;
+    if !repo::load_run(home, run_id)?.status.is_running() {
+        crate::runtime_manager::shutdown(home, &mut isolated);
+    }
+    result
 }
 
 pub fn mark_stop_requested(home: &Path, run_id: &str) -> Result<RunInstance> {
@@ -124,6 +132,7 @@ pub fn refresh_run_statuses(home: &Path) -> Result<Vec<RunInstance>> {
     let mut runs = repo::list_run_instances(home)?;
     let backend = runtime::default_backend();
     refresh_run_statuses_with_backend(home, &mut runs, &backend)?;
+    refresh_isolated_run_statuses(home, &mut runs)?;
     reconcile_stale_running_runs_if_runtime_down(home, &mut runs)?;
 
     runs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
@@ -135,11 +144,61 @@ pub fn reconcile_stale_running_runs(home: &Path) -> Result<usize> {
     reconcile_stale_running_runs_in_memory(home, &mut runs)
 }
 
+/// Refresh runs on the shared runtime; isolated runs are refreshed against
+/// their own runtime by `refresh_isolated_run_statuses`.
 pub(super) fn refresh_run_statuses_with_backend<B: RuntimeBackend>(
     home: &Path,
     runs: &mut [RunInstance],
     backend: &B,
 ) -> Result<()> {
+    let isolated = crate::runtime_manager::isolated_run_ids(home);
+    refresh_runs_against(home, runs, backend, &isolated)
+}
+
+/// Refresh each isolated run against its dedicated runtime and shut the
+/// runtime down once the run is over. A runtime that no longer answers
+/// counts as lost.
+fn refresh_isolated_run_statuses(home: &Path, runs: &mut [RunInstance]) -> Result<()> {
+    for mut isolated in crate::runtime_manager::list(home) {
+        if !isolated.is_active() {
+            continue;
+        }
+        let Some(run) = isolated
+            .run_id
+            .as_deref()
+            .and_then(|run_id| runs.iter_mut().find(|run| run.run_id == run_id))
+        else {
+            continue;
+        };
+        if run.status.is_running() {
+            let backend = IsolatedBackend::new(isolated.clone());
+            if backend.list(home).is_ok() {
+                refresh_runs_against(home, std::slice::from_mut(run), &backend, &HashSet::new())?;
+            } else if !isolated.is_ready_blocking(home) {
+                mark_runtime_lost(home, run, &Utc::now().to_rfc3339())?;
+            }
+        }
+        if !run.status.is_running() {
+            crate::runtime_manager::shutdown(home, &mut isolated);
+        }
+    }
+    Ok(())
+}
+
+fn refresh_runs_against<B: RuntimeBackend>(
+    home: &Path,
+    runs: &mut [RunInstance],
+    backend: &B,
+    skip: &HashSet<String>,
+) -> Result<()> {
+    // The loop below only touches running runs, so without any the
+    // `dora list` round trip would change nothing.
+    if !runs
+        .iter()
+        .any(|run| run.status.is_running() && !skip.contains(&run.run_id))
+    {
+        return Ok(());
+    }
     let runtime_items = match backend.list(home) {
         Ok(items) => items,
         Err(_) => return Ok(()),
@@ -151,7 +210,7 @@ pub(super) fn refresh_run_statuses_with_backend<B: RuntimeBackend>(
     let now = Utc::now().to_rfc3339();
 
     for run in runs {
-        if !run.status.is_running() {
+        if !run.status.is_running() || skip.contains(&run.run_id) {
             continue;
         }
 
@@ -311,43 +370,46 @@ fn reconcile_stale_running_runs_if_runtime_down(
         return Ok(());
     }
 
-    match crate::dora::check_runtime_blocking(home, false) {
-        Ok((false, _)) => {
-            reconcile_stale_running_runs_in_memory(home, runs)?;
-        }
-        Ok((true, _)) | Err(_) => {}
+    if let Ok((false, _)) = crate::dora::check_runtime_blocking(home, false) {
+        reconcile_stale_running_runs_in_memory(home, runs)?;
     }
 
     Ok(())
 }
 
+/// Mark running runs of the shared runtime as lost; isolated runs keep
+/// their own runtime and are left alone.
 fn reconcile_stale_running_runs_in_memory(home: &Path, runs: &mut [RunInstance]) -> Result<usize> {
     let now = Utc::now().to_rfc3339();
+    let isolated = crate::runtime_manager::isolated_run_ids(home);
     let mut updated = 0usize;
 
     for run in runs {
-        if !run.status.is_running() {
+        if !run.status.is_running() || isolated.contains(&run.run_id) {
             continue;
         }
-
-        sync_run_outputs(home, run)?;
-        apply_terminal_state(
-            run,
-            TerminalStateUpdate {
-                status: RunStatus::Stopped,
-                termination_reason: Some(TerminationReason::RuntimeLost),
-                exit_code: run.exit_code.or(Some(0)),
-                failure_reason: Some("runtime_lost".to_string()),
-                failure_node: None,
-                failure_message: Some("Dora runtime no longer reports this dataflow".to_string()),
-                observed_at: Some(now.clone()),
-            },
-        );
-        run.stop_request.requested_at = None;
-        run.stop_request.last_error = None;
-        repo::save_run(home, run)?;
+        mark_runtime_lost(home, run, &now)?;
         updated += 1;
     }
 
     Ok(updated)
 }
+
+fn mark_runtime_lost(home: &Path, run: &mut RunInstance, now: &str) -> Result<()> {
+    sync_run_outputs(home, run)?;
+    apply_terminal_state(
+        run,
+        TerminalStateUpdate {
+            status: RunStatus::Stopped,
+            termination_reason: Some(TerminationReason::RuntimeLost),
+            exit_code: run.exit_code.or(Some(0)),
+            failure_reason: Some("runtime_lost".to_string()),
+            failure_node: None,
+            failure_message: Some("Dora runtime no longer reports this dataflow".to_string()),
+            observed_at: Some(now.to_string()),
+        },
+    );
+    run.stop_request.requested_at = None;
+    run.stop_request.last_error = None;
+    repo::save_run(home, run)
+}
@@ -1,23 +1,28 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::{bail, Context, Result};
 use chrono::Utc;
 use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
+use crate::error::DmError;
+use crate::events::{EventSource, OperationEvent};
+use crate::node::NodeInstallOutcome;
 use crate::runs::graph::{build_transpile_metadata, extract_node_ids_from_yaml};
 use crate::runs::model::{
-    RunInstance, RunLogSync, RunSource, RunStatus, StartConflictStrategy, StartRunResult,
-    TerminationReason,
+    RunInstance, RunIsolation, RunLogSync, RunSource, RunStatus, StartConflictStrategy,
+    StartRunResult, TerminationReason,
 };
-use crate::runs::runtime::RuntimeBackend;
+use crate::runs::runtime::{IsolatedBackend, RuntimeBackend};
 use crate::runs::state::{apply_terminal_state, build_outcome, TerminalStateUpdate};
 use crate::runs::{repo, runtime};
 
+use super::service_capture::OutputCapture;
+
 /// Resolve the git URL to install a missing node from.
 /// Priority: YAML source.git > registry > None
-fn resolve_install_url(node_id: &str, yaml: &str) -> Option<String> {
+fn resolve_install_url(home: &Path, node_id: &str, yaml: &str) -> Option<String> {
     // 1. Check YAML for source.git on this node
     if let Ok(doc) = serde_yaml::from_str::<serde_yaml::Value>(yaml) {
         if let Some(nodes) = doc.get("nodes").and_then(|n| n.as_sequence()) {
@@ -36,12 +41,78 @@ fn resolve_install_url(node_id: &str, yaml: &str) -> Option<String> {
     }
 
     // 2. Check registry
-    crate::node::hub::resolve_node_source(node_id).and_then(|src| match src {
+    crate::node::hub::resolve_node_source(home, node_id).and_then(|src| match src {
         crate::node::hub::NodeSource::Git(url) => Some(url),
         _ => None,
     })
 }
 
+/// Download and install the nodes `yaml` uses that are not installed yet
+/// (`dm start --auto-install`, `auto_install` on `POST /api/runs/start`),
+/// each from its `source.git` or the registry. Progress is reported as
+/// `dataflow.install_nodes.progress` events; a node that fails to install
+/// is reported in its outcome and does not stop the others.
+pub async fn install_missing_nodes(home: &Path, yaml: &str) -> Result<Vec<NodeInstallOutcome>> {
+    let missing = crate::dataflow::inspect_yaml(home, yaml)
+        .summary
+        .missing_nodes;
+    if missing.is_empty() {
+        return Ok(Vec::new());
+    }
+    let op = OperationEvent::new(home, EventSource::Dataflow, "dataflow.install_nodes")
+        .attr("nodes", &missing);
+    op.emit_start();
+
+    let result = async {
+        let unresolved = missing
+            .iter()
+            .any(|node_id| resolve_install_url(home, node_id, yaml).is_none());
+        if unresolved && crate::config::registry_url(home).is_some() {
+            if let Err(e) = crate::node::hub::refresh_registry(home).await {
+                op.emit_progress("registry", None, format!("Registry refresh failed: {e}"));
+            }
+        }
+
+        let mut outcomes = Vec::new();
+        for (done, node_id) in missing.iter().enumerate() {
+            let installed = match resolve_install_url(home, node_id, yaml) {
+                Some(url) => {
+                    op.emit_progress(
+                        "install",
+                        Some((done * 100 / missing.len()) as u8),
+                        format!("Installing {} from {}", node_id, url),
+                    );
+                    match crate::node::import_git(home, node_id, &url).await {
+                        Ok(_) => crate::node::install_node(home, node_id).await,
+                        Err(e) => Err(e),
+                    }
+                }
+                None => Err(anyhow::anyhow!(
+                    "no known source; run `dm node import <git-url>`"
+                )),
+            };
+            outcomes.push(match installed {
+                Ok(node) => NodeInstallOutcome {
+                    node_id: node_id.clone(),
+                    version: Some(node.version),
+                    error: None,
+                },
+                Err(e) => NodeInstallOutcome {
+                    node_id: node_id.clone(),
+                    version: None,
+                    error: Some(format!("{:#}", e)),
+                },
+            });
+        }
+        op.emit_progress("install", Some(100), "Missing nodes processed");
+        Ok(outcomes)
+    }
+    .await;
+
+    op.emit_result(&result);
+    result
+}
+
 pub async fn start_run_from_yaml(
     home: &Path,
     yaml: &str,
@@ -83,63 +154,91 @@ pub async fn start_run_from_yaml_with_source_and_strategy(
     source: RunSource,
     strategy: StartConflictStrategy,
 ) -> Result<StartRunResult> {
-    let backend = runtime::default_backend();
-    start_run_from_yaml_with_source_and_strategy_and_backend(
+    start_run_from_yaml_with_isolation(
         home,
         yaml,
         dataflow_name,
         view_json,
         source,
         strategy,
-        &backend,
+        RunIsolation::from_config(home),
     )
     .await
 }
 
-pub(super) async fn start_run_from_yaml_with_source_and_strategy_and_backend<B: RuntimeBackend>(
+/// Start a run on the shared runtime or on a dedicated one from
+/// `runtime_manager`, which is bound to the run and torn down with it.
+pub async fn start_run_from_yaml_with_isolation(
     home: &Path,
     yaml: &str,
     dataflow_name: &str,
     view_json: Option<&str>,
     source: RunSource,
     strategy: StartConflictStrategy,
-    backend: &B,
+    isolation: RunIsolation,
 ) -> Result<StartRunResult> {
-    let mut executable = crate::dataflow::inspect_yaml(home, yaml);
-
-    // Auto-install missing nodes
-    if !executable.summary.missing_nodes.is_empty() {
-        let mut installed_any = false;
+    let RunIsolation::Dedicated { dora_version } = isolation else {
+        let backend = runtime::default_backend();
+        return start_run_from_yaml_with_source_and_strategy_and_backend(
+            home,
+            yaml,
+            dataflow_name,
+            view_json,
+            source,
+            strategy,
+            &backend,
+        )
+        .await;
+    };
 
-        for node_id in &executable.summary.missing_nodes.clone() {
-            let git_url = resolve_install_url(node_id, yaml);
-            match git_url {
-                Some(url) => {
-                    eprintln!("→ Installing missing node '{}' from {}...", node_id, url);
-                    match crate::node::import_git(home, node_id, &url).await {
-                        Ok(_) => match crate::node::install_node(home, node_id).await {
-                            Ok(_) => {
-                                eprintln!("  ✅ Installed {}", node_id);
-                                installed_any = true;
-                            }
-                            Err(e) => eprintln!("  ⚠ Install failed: {}", e),
-                        },
-                        Err(e) => eprintln!("  ⚠ Import failed: {}", e),
-                    }
-                }
-                None => {
-                    eprintln!(
-                        "→ Missing node '{}': no known source. Run: dm node import <git-url>",
-                        node_id
-                    );
-                }
+    // Resolve name conflicts against every runtime before spawning a new one.
+    if strategy != StartConflictStrategy::AllowMultiple {
+        let active = super::list_active_runs(home)?
+            .into_iter()
+            .find(|run| run.dataflow_name == dataflow_name);
+        if let Some(active) = active {
+            if strategy == StartConflictStrategy::Fail {
+                bail!(
+                    "Dataflow '{}' is already running as run {}. Stop it first, retry with force, or allow multiple instances.",
+                    dataflow_name,
+                    active.run_id
+                );
             }
+            super::service_runtime::stop_run(home, &active.run_id).await?;
         }
+    }
 
-        if installed_any {
-            executable = crate::dataflow::inspect_yaml(home, yaml);
+    let mut isolated = crate::runtime_manager::spawn(home, dora_version.as_deref()).await?;
+    let backend = IsolatedBackend::new(isolated.clone());
+    let result = start_run_from_yaml_with_source_and_strategy_and_backend(
+        home,
+        yaml,
+        dataflow_name,
+        view_json,
+        source,
+        StartConflictStrategy::AllowMultiple,
+        &backend,
+    )
+    .await;
+    match &result {
+        Ok(started) => {
+            crate::runtime_manager::attach_run(home, &mut isolated, &started.run.run_id)?
         }
+        Err(_) => crate::runtime_manager::shutdown(home, &mut isolated),
     }
+    result
+}
+
+pub(super) async fn start_run_from_yaml_with_source_and_strategy_and_backend<B: RuntimeBackend>(
+    home: &Path,
+    yaml: &str,
+    dataflow_name: &str,
+    view_json: Option<&str>,
+    source: RunSource,
+    strategy: StartConflictStrategy,
+    backend: &B,
+) -> Result<StartRunResult> {
+    let executable = crate::dataflow::inspect_yaml(home, yaml);
 
     if !executable.summary.can_run {
         if executable.summary.invalid_yaml {
@@ -155,60 +254,57 @@ pub(super) async fn start_run_from_yaml_with_source_and_strategy_and_backend<B:
             );
         }
         if !executable.summary.missing_nodes.is_empty() {
-            bail!(
-                "Dataflow '{}' is not executable: missing nodes: {}",
+            let missing = executable.summary.missing_nodes.join(" ");
+            return Err(DmError::DependencyMissing(format!(
+                "Dataflow '{}' is not executable: missing nodes: {}. Install them with `dm node install {}`, or start with --auto-install.",
                 dataflow_name,
-                executable.summary.missing_nodes.join(", ")
-            );
+                executable.summary.missing_nodes.join(", "),
+                missing
+            ))
+            .into());
         }
         bail!("Dataflow '{}' is not executable", dataflow_name);
     }
 
-    if let Some(active) = super::find_active_run_by_name_with_backend(home, dataflow_name, backend)?
-    {
+    let active = if strategy == StartConflictStrategy::AllowMultiple {
+        None
+    } else {
+        super::find_active_run_by_name_with_backend(home, dataflow_name, backend)?
+    };
+    if let Some(active) = active {
         match strategy {
-            StartConflictStrategy::Fail => bail!(
-                "Dataflow '{}' is already running as run {}. Stop it first or retry with force.",
-                dataflow_name,
-                active.run_id
-            ),
+            StartConflictStrategy::Fail => {
+                return Err(DmError::Conflict(format!(
+                    "Dataflow '{}' is already running as run {}. Stop it first, retry with force, or allow multiple instances.",
+                    dataflow_name,
+                    active.run_id
+                ))
+                .into())
+            }
             StartConflictStrategy::StopAndRestart => {
                 super::service_runtime::stop_run_with_backend(home, &active.run_id, backend)
                     .await?;
             }
+            StartConflictStrategy::AllowMultiple => {}
         }
     }
 
+    // Every start gets its own run directory, so concurrent starts never share
+    // snapshot/transpiled files. Remove it again if preparation fails before
+    // a run record exists.
     let run_id = Uuid::new_v4().to_string();
-    repo::create_layout(home, &run_id)?;
-
-    let snapshot_path = repo::run_snapshot_path(home, &run_id);
-    fs::write(&snapshot_path, yaml)
-        .with_context(|| format!("Failed to write snapshot {}", snapshot_path.display()))?;
-
-    if let Some(vj) = view_json {
-        let view_json_path = repo::run_view_json_path(home, &run_id);
-        fs::write(&view_json_path, vj)
-            .with_context(|| format!("Failed to write view.json {}", view_json_path.display()))?;
-    }
-
+    let (transpiled_path, transpiled_yaml) =
+        match prepare_run_dir(home, &run_id, yaml, view_json, dataflow_name) {
+            Ok(prepared) => prepared,
+            Err(err) => {
+                let _ = fs::remove_dir_all(repo::run_dir(home, &run_id));
+                return Err(err);
+            }
+        };
     let dataflow_hash = format!("sha256:{:x}", Sha256::digest(yaml.as_bytes()));
-    let transpile_result = crate::dataflow::transpile_graph_for_run(home, &snapshot_path, &run_id)
-        .with_context(|| format!("Failed to transpile '{}'", dataflow_name))?;
-    let transpiled_path = repo::run_transpiled_path(home, &run_id);
-    fs::write(
-        &transpiled_path,
-        serde_yaml::to_string(&transpile_result.yaml)?,
-    )
-    .with_context(|| {
-        format!(
-            "Failed to write transpiled graph {}",
-            transpiled_path.display()
-        )
-    })?;
 
     let nodes_expected = extract_node_ids_from_yaml(yaml)?;
-    let transpile = build_transpile_metadata(&transpile_result.yaml);
+    let transpile = build_transpile_metadata(&transpiled_yaml);
     let mut run = RunInstance {
         schema_version: 1,
         run_id: run_id.clone(),
@@ -233,10 +329,19 @@ pub(super) async fn start_run_from_yaml_with_source_and_strategy_and_backend<B:
         node_count_observed: 0,
         nodes_expected,
         nodes_observed: Vec::new(),
+        restart: Default::default(),
     };
     repo::save_run(home, &run)?;
+    if let Err(e) = super::service_admin::apply_retention(home) {
+        eprintln!("Warning: failed to prune old runs: {}", e);
+    }
 
-    match backend.start_detached(home, &transpiled_path).await {
+    let started = backend.start_detached(home, &transpiled_path).await;
+    match &started {
+        Ok((_, message)) => record_start_output(home, &run, "stdout", message),
+        Err(err) => record_start_output(home, &run, "stderr", &err.to_string()),
+    }
+    match started {
         Ok((Some(dora_uuid), message)) => {
             run.dora_uuid = Some(dora_uuid);
             repo::save_run(home, &run)?;
@@ -282,6 +387,52 @@ pub(super) async fn start_run_from_yaml_with_source_and_strategy_and_backend<B:
     }
 }
 
+/// Keep what `dora start` printed as `dora.output` events of the run; node
+/// logs follow once dm-server's log capture picks the run up.
+fn record_start_output(home: &Path, run: &RunInstance, stream: &str, output: &str) {
+    let mut capture = OutputCapture::new(home, &run.run_id, run.nodes_expected.clone());
+    for line in output.lines() {
+        capture.push(stream, line);
+    }
+}
+
+/// Write the snapshot, optional view.json and transpiled graph for a new run.
+fn prepare_run_dir(
+    home: &Path,
+    run_id: &str,
+    yaml: &str,
+    view_json: Option<&str>,
+    dataflow_name: &str,
+) -> Result<(PathBuf, serde_yaml::Value)> {
+    repo::create_layout(home, run_id)?;
+
+    let snapshot_path = repo::run_snapshot_path(home, run_id);
+    fs::write(&snapshot_path, yaml)
+        .with_context(|| format!("Failed to write snapshot {}", snapshot_path.display()))?;
+
+    if let Some(vj) = view_json {
+        let view_json_path = repo::run_view_json_path(home, run_id);
+        fs::write(&view_json_path, vj)
+            .with_context(|| format!("Failed to write view.json {}", view_json_path.display()))?;
+    }
+
+    let transpile_result = crate::dataflow::transpile_graph_for_run(home, &snapshot_path, run_id)
+        .with_context(|| format!("Failed to transpile '{}'", dataflow_name))?;
+    let transpiled_path = repo::run_transpiled_path(home, run_id);
+    fs::write(
+        &transpiled_path,
+        serde_yaml::to_string(&transpile_result.yaml)?,
+    )
+    .with_context(|| {
+        format!(
+            "Failed to write transpiled graph {}",
+            transpiled_path.display()
+        )
+    })?;
+
+    Ok((transpiled_path, transpile_result.yaml))
+}
+
 pub async fn start_run_from_file(home: &Path, file_path: &Path) -> Result<StartRunResult> {
     start_run_from_file_with_source_and_strategy(
         home,
@@ -314,6 +465,25 @@ pub async fn start_run_from_file_with_source_and_strategy(
     view_json: Option<&str>,
     source: RunSource,
     strategy: StartConflictStrategy,
+) -> Result<StartRunResult> {
+    start_run_from_file_with_isolation(
+        home,
+        file_path,
+        view_json,
+        source,
+        strategy,
+        RunIsolation::from_config(home),
+    )
+    .await
+}
+
+pub async fn start_run_from_file_with_isolation(
+    home: &Path,
+    file_path: &Path,
+    view_json: Option<&str>,
+    source: RunSource,
+    strategy: StartConflictStrategy,
+    isolation: RunIsolation,
 ) -> Result<StartRunResult> {
     let yaml = fs::read_to_string(file_path)
         .with_context(|| format!("Failed to read graph file '{}'", file_path.display()))?;
@@ -322,13 +492,14 @@ pub async fn start_run_from_file_with_source_and_strategy(
         .unwrap_or_default()
         .to_string_lossy()
         .to_string();
-    start_run_from_yaml_with_source_and_strategy(
+    start_run_from_yaml_with_isolation(
         home,
         &yaml,
         &dataflow_name,
         view_json,
         source,
         strategy,
+        isolation,
     )
     .await
 }
@@ -7,15 +7,18 @@ mod tests {
     use std::sync::{Arc, Mutex};
 
     use anyhow::{anyhow, Result};
+    use dm_test_utils::{MockDora, MockResponse};
 
     use crate::config;
     use crate::node::{node_dir, Node, NodeDisplay, NodeFiles, NodeRuntime, NodeSource};
+    use crate::runs::graph::extract_restart_policies;
     use crate::runs::model::{
-        RunInstance, RunSource, RunStatus, StartConflictStrategy, TerminationReason,
+        RestartPolicy, RunInstance, RunIsolation, RunRestart, RunSource, RunStatus,
+        StartConflictStrategy, TerminationReason,
     };
     use crate::runs::repo;
     use crate::runs::runtime::{RuntimeBackend, RuntimeDataflow, STOP_TIMEOUT_SECS};
-    use crate::runs::service::{service_query, service_runtime, service_start};
+    use crate::runs::service::{service_query, service_restart, service_runtime, service_start};
     use crate::runs::state::build_outcome;
 
     #[derive(Clone)]
@@ -85,6 +88,8 @@ mod tests {
             examples: Vec::new(),
             config_schema: None,
             dynamic_ports: false,
+            pinned: None,
+            last_test: None,
             path: Default::default(),
         };
 
@@ -120,31 +125,6 @@ mod tests {
         fs::write(out_dir.join(format!("log_{node_id}.txt")), content).unwrap();
     }
 
-    fn setup_fake_runtime_home(home: &Path, active_version: &str, script: &str) {
-        let version_dir = config::versions_dir(home).join(active_version);
-        fs::create_dir_all(&version_dir).unwrap();
-
-        let bin = version_dir.join(config::dora_bin_name());
-        fs::write(&bin, script).unwrap();
-
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let mut perms = fs::metadata(&bin).unwrap().permissions();
-            perms.set_mode(0o755);
-            fs::set_permissions(&bin, perms).unwrap();
-        }
-
-        config::save_config(
-            home,
-            &config::DmConfig {
-                active_version: Some(active_version.to_string()),
-                ..Default::default()
-            },
-        )
-        .unwrap();
-    }
-
     #[tokio::test]
     async fn start_run_fails_when_runtime_uuid_is_missing() {
         let tmp = tempfile::tempdir().unwrap();
@@ -182,6 +162,60 @@ mod tests {
         assert_eq!(runs[0].dora_uuid, None);
     }
 
+    #[tokio::test]
+    async fn start_run_rejects_missing_nodes_unless_they_are_installed_first() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        setup_managed_node(home, "test-node", ".venv/bin/test-node");
+        fs::write(
+            home.join("registry.json"),
+            serde_json::json!({
+                "nodes": {
+                    "dm-test-remote": {
+                        "source": { "type": "git", "url": home.join("no-such-repo") }
+                    }
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let yaml =
+            "nodes:\n  - id: n1\n    node: test-node\n  - id: n2\n    path: dm-test-remote\n";
+
+        let backend = TestBackend {
+            start_result: Ok((Some("uuid-1".to_string()), "started".to_string())),
+            stop_result: Ok(()),
+            list_result: Ok(Vec::new()),
+            stop_calls: Arc::new(Mutex::new(Vec::new())),
+        };
+        let err = service_start::start_run_from_yaml_with_source_and_strategy_and_backend(
+            home,
+            yaml,
+            "demo",
+            None,
+            RunSource::Cli,
+            StartConflictStrategy::Fail,
+            &backend,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<crate::error::DmError>(),
+            Some(crate::error::DmError::DependencyMissing(_))
+        ));
+        assert!(err.to_string().contains("missing nodes: dm-test-remote"));
+        assert!(err.to_string().contains("--auto-install"));
+        assert!(repo::list_run_instances(home).unwrap().is_empty());
+
+        let outcomes = service_start::install_missing_nodes(home, yaml)
+            .await
+            .unwrap();
+        assert_eq!(outcomes.len(), 1);
+        assert_eq!(outcomes[0].node_id, "dm-test-remote");
+        assert!(outcomes[0].version.is_none());
+        assert!(outcomes[0].error.is_some());
+    }
+
     #[test]
     fn refresh_run_statuses_keeps_running_state_when_runtime_list_fails() {
         let tmp = tempfile::tempdir().unwrap();
@@ -214,26 +248,11 @@ mod tests {
         let tmp = tempfile::tempdir().unwrap();
         let home = tmp.path();
         write_running_run(home, "run-1", Some("uuid-1"));
-        setup_fake_runtime_home(
-            home,
-            "0.4.1",
-            r#"#!/bin/sh
-cmd="$1"
-case "$cmd" in
-  check)
-    echo "Runtime unavailable" >&2
-    exit 1
-    ;;
-  list)
-    echo "Could not connect to dora-coordinator" >&2
-    exit 1
-    ;;
-  *)
-    exit 0
-    ;;
-esac
-"#,
-        );
+        MockDora::new("0.4.1")
+            .runtime_down()
+            .fail("list", 1, "Could not connect to dora-coordinator")
+            .install(home)
+            .unwrap();
 
         let runs = service_runtime::refresh_run_statuses(home).unwrap();
 
@@ -316,6 +335,16 @@ esac
             backend.stop_calls.lock().unwrap().as_slice(),
             &["uuid-stop-ok".to_string()]
         );
+
+        let finished = crate::events::EventStore::open(home)
+            .unwrap()
+            .query(&crate::events::EventFilter {
+                activity: Some("run.finished".into()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(finished.len(), 1);
+        assert_eq!(finished[0].case_id, "run-stop-ok");
     }
 
     #[test]
@@ -713,4 +742,415 @@ esac
             Some(TerminationReason::StoppedByUser)
         );
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn isolated_run_gets_dedicated_runtime_that_stops_with_it() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        setup_managed_node(home, "test-node", ".venv/bin/test-node");
+        let dora = MockDora::new("0.3.9")
+            .respond("coordinator", MockResponse::script("exec sleep 30"))
+            .respond("daemon", MockResponse::script("exec sleep 30"))
+            .install(home)
+            .unwrap();
+
+        let started = service_start::start_run_from_yaml_with_isolation(
+            home,
+            "nodes:\n  - id: n1\n    node: test-node\n",
+            "demo",
+            None,
+            RunSource::Cli,
+            StartConflictStrategy::Fail,
+            RunIsolation::Dedicated { dora_version: None },
+        )
+        .await
+        .unwrap();
+        let run_id = started.run.run_id.clone();
+        let isolated = crate::runtime_manager::find_for_run(home, &run_id).unwrap();
+        assert!(dora
+            .calls_to("start")
+            .iter()
+            .any(|line| line.ends_with(&format!("--coordinator-port {}", isolated.control_port))));
+
+        let runs = service_runtime::refresh_run_statuses(home).unwrap();
+        let run = runs.iter().find(|run| run.run_id == run_id).unwrap();
+        assert_eq!(run.status, RunStatus::Running);
+
+        let stopped = service_runtime::stop_run(home, &run_id).await.unwrap();
+        assert_eq!(stopped.status, RunStatus::Stopped);
+        assert!(crate::runtime_manager::find_for_run(home, &run_id).is_none());
+    }
+
+    fn write_failed_run_with_snapshot(home: &Path, run_id: &str, yaml: &str, restart: RunRestart) {
+        write_run(
+            home,
+            RunInstance {
+                run_id: run_id.to_string(),
+                dora_uuid: Some("uuid-old".to_string()),
+                dataflow_name: "demo".to_string(),
+                dataflow_hash: "sha256:old".to_string(),
+                status: RunStatus::Failed,
+                termination_reason: Some(TerminationReason::NodeFailed),
+                failure_node: Some("n1".to_string()),
+                started_at: (chrono::Utc::now() - chrono::Duration::seconds(120)).to_rfc3339(),
+                stopped_at: Some((chrono::Utc::now() - chrono::Duration::seconds(60)).to_rfc3339()),
+                outcome: build_outcome(RunStatus::Failed, None, None, None),
+                restart,
+                ..RunInstance::default()
+            },
+        );
+        fs::write(repo::run_snapshot_path(home, run_id), yaml).unwrap();
+    }
+
+    fn restart_backend() -> TestBackend {
+        TestBackend {
+            start_result: Ok((Some("uuid-new".to_string()), "started".to_string())),
+            stop_result: Ok(()),
+            list_result: Ok(Vec::new()),
+            stop_calls: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    #[test]
+    fn extract_restart_policies_accepts_shorthand_and_mapping() {
+        let policies = extract_restart_policies(
+            "nodes:\n  - id: a\n    restart: on-failure\n  - id: b\n    restart:\n      policy: always\n      max_restarts: 2\n      backoff_secs: 1\n  - id: c\n    restart: never\n  - id: d\n",
+        );
+
+        assert_eq!(policies.len(), 3);
+        assert_eq!(policies["a"].policy, RestartPolicy::OnFailure);
+        assert_eq!(policies["c"].policy, RestartPolicy::Never);
+        assert_eq!(policies["a"].max_restarts, 5);
+        assert_eq!(policies["b"].policy, RestartPolicy::Always);
+        assert_eq!(policies["b"].max_restarts, 2);
+        assert_eq!(policies["b"].backoff_for(0), 1);
+        assert_eq!(policies["b"].backoff_for(3), 8);
+        assert_eq!(policies["a"].backoff_for(20), 300);
+    }
+
+    #[tokio::test]
+    async fn supervise_restarts_restarts_failed_run_with_on_failure_policy() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        setup_managed_node(home, "test-node", ".venv/bin/test-node");
+        write_failed_run_with_snapshot(
+            home,
+            "old-run",
+            "nodes:\n  - id: n1\n    node: test-node\n    restart: on-failure\n",
+            RunRestart::default(),
+        );
+
+        let actions =
+            service_restart::supervise_restarts_with_backend(home, &restart_backend(), false)
+                .await
+                .unwrap();
+
+        assert_eq!(actions.len(), 1);
+        let service_restart::RestartAction::Restarted {
+            from_run_id,
+            to_run_id,
+            attempt,
+        } = &actions[0]
+        else {
+            panic!("expected restart, got {:?}", actions[0]);
+        };
+        assert_eq!(from_run_id, "old-run");
+        assert_eq!(*attempt, 1);
+
+        let new_run = repo::load_run(home, to_run_id).unwrap();
+        assert_eq!(new_run.restart.restarted_from.as_deref(), Some("old-run"));
+        assert_eq!(new_run.restart.attempt, 1);
+        assert!(repo::load_run(home, "old-run").unwrap().restart.handled);
+
+        let transpiled = fs::read_to_string(repo::run_transpiled_path(home, to_run_id)).unwrap();
+        assert!(!transpiled.contains("restart"));
+
+        // A second pass must not restart the same failure again.
+        let again =
+            service_restart::supervise_restarts_with_backend(home, &restart_backend(), false)
+                .await
+                .unwrap();
+        assert!(again.is_empty());
+    }
+
+    #[tokio::test]
+    async fn supervise_restarts_gives_up_after_max_restarts() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        setup_managed_node(home, "test-node", ".venv/bin/test-node");
+        write_failed_run_with_snapshot(
+            home,
+            "old-run",
+            "nodes:\n  - id: n1\n    node: test-node\n    restart:\n      policy: on-failure\n      max_restarts: 2\n",
+            RunRestart {
+                attempt: 2,
+                restarted_from: Some("older-run".to_string()),
+                handled: false,
+            },
+        );
+
+        let actions =
+            service_restart::supervise_restarts_with_backend(home, &restart_backend(), false)
+                .await
+                .unwrap();
+
+        assert_eq!(
+            actions,
+            vec![service_restart::RestartAction::Exhausted {
+                run_id: "old-run".to_string(),
+                attempts: 2,
+            }]
+        );
+        assert_eq!(repo::list_run_instances(home).unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn supervise_restarts_ignores_dataflows_without_policy() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        setup_managed_node(home, "test-node", ".venv/bin/test-node");
+        write_failed_run_with_snapshot(
+            home,
+            "old-run",
+            "nodes:\n  - id: n1\n    node: test-node\n",
+            RunRestart::default(),
+        );
+
+        let actions =
+            service_restart::supervise_restarts_with_backend(home, &restart_backend(), false)
+                .await
+                .unwrap();
+
+        assert!(actions.is_empty());
+        assert_eq!(repo::list_run_instances(home).unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn supervise_restarts_honours_never_on_the_failing_node() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        setup_managed_node(home, "test-node", ".venv/bin/test-node");
+        write_failed_run_with_snapshot(
+            home,
+            "old-run",
+            "nodes:\n  - id: n1\n    node: test-node\n    restart: never\n  - id: n2\n    node: test-node\n    restart: on-failure\n",
+            RunRestart::default(),
+        );
+
+        let actions =
+            service_restart::supervise_restarts_with_backend(home, &restart_backend(), false)
+                .await
+                .unwrap();
+
+        assert!(actions.is_empty());
+        assert_eq!(repo::list_run_instances(home).unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn group_start_reports_each_tagged_dataflow() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path();
+        for (name, tags) in [("cam", vec!["perception"]), ("arm", vec!["control"])] {
+            crate::dataflow::save(home, name, "nodes:\n  - id: n\n    node: gone-node\n").unwrap();
+            let mut meta = crate::dataflow::get_flow_meta(home, name).unwrap();
+            meta.tags = tags.into_iter().map(String::from).collect();
+            crate::dataflow::save_flow_meta(home, name, &meta).unwrap();
+        }
+
+        let report = super::super::start_tagged(
+            home,
+            "perception",
+            RunSource::Cli,
+            StartConflictStrategy::Fail,
+            RunIsolation::Shared,
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.results.len(), 1);
+        assert_eq!(report.results[0].dataflow, "cam");
+        assert_eq!(report.failed, 1);
+        assert!(report.results[0].message.contains("gone-node"));
+
+        let stopped = super::super::stop_tagged(home, "control").await.unwrap();
+        assert_eq!(stopped.succeeded, 1);
+        assert_eq!(stopped.results[0].message, "not running");
+
+        assert!(super::super::stop_tagged(home, "nothing").await.is_err());
+    }
+
+    #[test]
+    fn apply_retention_prunes_finished_runs_and_local_run_artifacts() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path();
+        config::save_config(
+            home,
+            &config::DmConfig {
+                retention: config::RetentionConfig {
+                    keep_runs: 2,
+                    max_age_days: 30,
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        write_running_run(home, "running-old", Some("uuid-1"));
+        let finished = |run_id: &str, stopped_at: &str| RunInstance {
+            run_id: run_id.to_string(),
+            dataflow_name: "demo".to_string(),
+            status: RunStatus::Stopped,
+            started_at: stopped_at.to_string(),
+            stopped_at: Some(stopped_at.to_string()),
+            ..RunInstance::default()
+        };
+        let recent = chrono::Utc::now();
+        for (index, run_id) in ["newest", "newer", "older"].into_iter().enumerate() {
+            let at = recent - chrono::Duration::minutes(index as i64);
+            write_run(home, finished(run_id, &at.to_rfc3339()));
+        }
+        let expired = recent - chrono::Duration::days(60);
+        write_run(home, finished("expired", &expired.to_rfc3339()));
+
+        for run_id in ["a", "b", "c"] {
+            let artifacts = crate::dataflow::local_run_artifacts_dir(home, "demo", run_id);
+            fs::create_dir_all(&artifacts).unwrap();
+            fs::write(artifacts.join("output.log"), run_id).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+
+        assert_eq!(super::super::apply_retention(home).unwrap(), 3);
+
+        let mut kept: Vec<_> = repo::list_run_instances(home)
+            .unwrap()
+            .into_iter()
+            .map(|run| run.run_id)
+            .collect();
+        kept.sort();
+        assert_eq!(kept, vec!["newer", "newest", "running-old"]);
+        assert!(!crate::dataflow::local_run_artifacts_dir(home, "demo", "a").exists());
+        assert!(crate::dataflow::local_run_artifacts_dir(home, "demo", "c").exists());
+    }
+
+    #[test]
+    fn apply_retention_skips_runs_still_in_use() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path();
+        config::save_config(
+            home,
+            &config::DmConfig {
+                retention: config::RetentionConfig {
+                    keep_runs: 1,
+                    max_age_days: 0,
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        // "busy" is recorded as stopped, but its dedicated runtime is up.
+        let recent = chrono::Utc::now();
+        for (index, run_id) in ["newest", "older", "busy"].into_iter().enumerate() {
+            let at = (recent - chrono::Duration::minutes(index as i64)).to_rfc3339();
+            write_run(
+                home,
+                RunInstance {
+                    run_id: run_id.to_string(),
+                    dataflow_name: "demo".to_string(),
+                    status: RunStatus::Stopped,
+                    started_at: at.clone(),
+                    stopped_at: Some(at),
+                    ..RunInstance::default()
+                },
+            );
+        }
+        let runtime = crate::runtime_manager::RuntimeInstance {
+            id: "rt-busy".to_string(),
+            run_id: Some("busy".to_string()),
+            dora_version: "0.3.9".to_string(),
+            coordinator_port: 1,
+            control_port: 2,
+            daemon_port: 3,
+            coordinator_pid: Some(std::process::id()),
+            daemon_pid: None,
+            started_at: recent.to_rfc3339(),
+            stopped_at: None,
+        };
+        let runtime_dir = runtime.dir(home);
+        fs::create_dir_all(&runtime_dir).unwrap();
+        fs::write(
+            runtime_dir.join("runtime.json"),
+            serde_json::to_string(&runtime).unwrap(),
+        )
+        .unwrap();
+
+        // "a" is the oldest `dm run` directory, but its process is running.
+        for run_id in ["a", "b", "c"] {
+            let artifacts = crate::dataflow::local_run_artifacts_dir(home, "demo", run_id);
+            fs::create_dir_all(&artifacts).unwrap();
+            fs::write(artifacts.join("output.log"), run_id).unwrap();
+            if run_id == "a" {
+                let pid_file = crate::dataflow::local_run_pid_path(&artifacts);
+                fs::write(pid_file, std::process::id().to_string()).unwrap();
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        let busy = crate::dataflow::local_run_artifacts_dir(home, "demo", "a");
+
+        assert_eq!(super::super::apply_retention(home).unwrap(), 2);
+
+        let mut kept: Vec<_> = repo::list_run_instances(home)
+            .unwrap()
+            .into_iter()
+            .map(|run| run.run_id)
+            .collect();
+        kept.sort();
+        assert_eq!(kept, vec!["busy", "newest"]);
+        assert!(busy.exists());
+        assert!(!crate::dataflow::local_run_artifacts_dir(home, "demo", "b").exists());
+        assert!(crate::dataflow::local_run_artifacts_dir(home, "demo", "c").exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn sampled_node_metrics_are_recorded_and_returned_oldest_first() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        // The test process stands in for the node dora reports.
+        let pid = std::process::id();
+        MockDora::new("0.4.1")
+            .respond(
+                "node",
+                MockResponse::ok(format!(
+                    r#"{{"node":"cam","status":"Running","pid":"{pid}","cpu":"0.0%","memory":"1 MB"}}"#
+                )),
+            )
+            .install(home)
+            .unwrap();
+        write_running_run(home, "run-metrics", Some("uuid-metrics"));
+
+        let samples = crate::runs::sample_active_runs(home).await.unwrap();
+        assert_eq!(samples.len(), 1);
+        let (run_id, sample) = &samples[0];
+        assert_eq!(run_id, "run-metrics");
+        assert_eq!(sample.nodes.len(), 1);
+        assert_eq!(sample.nodes[0].node_id, "cam");
+        assert_eq!(sample.nodes[0].pid, pid);
+        assert!(sample.nodes[0].memory_bytes > 0);
+
+        let store = crate::events::EventStore::open(home).unwrap();
+        for _ in 0..3 {
+            crate::runs::record_run_metrics(&store, run_id, sample).unwrap();
+        }
+        let history = crate::runs::run_metric_history(home, run_id, None, Some(2)).unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history[0].timestamp <= history[1].timestamp);
+        assert_eq!(history[1].nodes, sample.nodes);
+
+        let missing = crate::runs::run_metric_history(home, "nope", None, None).unwrap_err();
+        assert_eq!(
+            crate::error::error_code(&missing),
+            Some(crate::error::ErrorCode::NotFound)
+        );
+    }
 }
@@ -0,0 +1,431 @@
+//! Dedicated dora runtimes for isolated runs.
+//!
+//! The shared runtime from `dm up` listens on dora's default ports, so only
+//! one dora version can serve every run. An isolated run instead gets its
+//! own coordinator + daemon on free ports, spawned from any installed dora
+//! version. Each runtime is tracked in `<home>/runtimes/<id>/runtime.json`
+//! next to the coordinator and daemon logs, and is shut down once its run
+//! stops, or as soon as its coordinator or daemon exits on its own.
+
+use std::path::{Path, PathBuf};
+use std::process::{Command as StdCommand, Stdio};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::error::DmError;
+use crate::{config, dora};
+
+const HOST: &str = "127.0.0.1";
+const READY_ATTEMPTS: u32 = 20;
+const READY_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A coordinator + daemon pair owned by dm.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeInstance {
+    pub id: String,
+    /// The run this runtime serves, set once the dataflow has started.
+    #[serde(default)]
+    pub run_id: Option<String>,
+    pub dora_version: String,
+    /// Port daemons connect to.
+    pub coordinator_port: u16,
+    /// Port the dora CLI talks to.
+    pub control_port: u16,
+    pub daemon_port: u16,
+    pub coordinator_pid: Option<u32>,
+    pub daemon_pid: Option<u32>,
+    pub started_at: String,
+    #[serde(default)]
+    pub stopped_at: Option<String>,
+}
+
+impl RuntimeInstance {
+    pub fn dir(&self, home: &Path) -> PathBuf {
+        runtimes_dir(home).join(&self.id)
+    }
+
+    pub fn dora_bin(&self, home: &Path) -> PathBuf {
+        config::dora_bin_path(&config::versions_dir(home).join(&self.dora_version))
+    }
+
+    /// Arguments pointing a dora CLI command at this runtime.
+    pub fn cli_args(&self) -> Vec<String> {
+        vec![
+            "--coordinator-addr".to_string(),
+            HOST.to_string(),
+            "--coordinator-port".to_string(),
+            self.control_port.to_string(),
+        ]
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.stopped_at.is_none()
+    }
+
+    /// Whether the runtime is active and its coordinator or daemon is
+    /// still running.
+    pub fn is_alive(&self) -> bool {
+        self.is_active()
+            && [self.coordinator_pid, self.daemon_pid]
+                .into_iter()
+                .flatten()
+                .any(crate::lock::pid_alive)
+    }
+
+    /// Whether the coordinator answers `dora check`.
+    pub async fn is_ready(&self, home: &Path) -> bool {
+        tokio::process::Command::new(self.dora_bin(home))
+            .arg("check")
+            .args(self.cli_args())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .await
+            .map(|status| status.success())
+            .unwrap_or(false)
+    }
+
+    pub fn is_ready_blocking(&self, home: &Path) -> bool {
+        StdCommand::new(self.dora_bin(home))
+            .arg("check")
+            .args(self.cli_args())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    }
+}
+
+pub fn runtimes_dir(home: &Path) -> PathBuf {
+    home.join("runtimes")
+}
+
+fn record_path(home: &Path, id: &str) -> PathBuf {
+    runtimes_dir(home).join(id).join("runtime.json")
+}
+
+fn save(home: &Path, runtime: &RuntimeInstance) -> Result<()> {
+    let path = record_path(home, &runtime.id);
+    std::fs::write(&path, serde_json::to_string_pretty(runtime)?)
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Every runtime dm has spawned, newest first, including stopped ones.
+pub fn list(home: &Path) -> Vec<RuntimeInstance> {
+    let Ok(entries) = std::fs::read_dir(runtimes_dir(home)) else {
+        return Vec::new();
+    };
+    let mut runtimes: Vec<RuntimeInstance> = entries
+        .flatten()
+        .filter_map(|entry| std::fs::read_to_string(entry.path().join("runtime.json")).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+    runtimes.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    runtimes
+}
+
+/// The active runtime serving `run_id`, if the run is isolated.
+pub fn find_for_run(home: &Path, run_id: &str) -> Option<RuntimeInstance> {
+    list(home)
+        .into_iter()
+        .find(|rt| rt.is_active() && rt.run_id.as_deref() == Some(run_id))
+}
+
+/// Run ids currently served by a dedicated runtime.
+pub fn isolated_run_ids(home: &Path) -> std::collections::HashSet<String> {
+    list(home)
+        .into_iter()
+        .filter(RuntimeInstance::is_active)
+        .filter_map(|rt| rt.run_id)
+        .collect()
+}
+
+/// Start a coordinator + daemon on free ports using `dora_version`, or the
+/// active version when `None`, and wait until it accepts commands.
+pub async fn spawn(home: &Path, dora_version: Option<&str>) -> Result<RuntimeInstance> {
+    if crate::dora::mock_enabled() {
+        bail!("Dedicated runtimes are not available with the mock runtime");
+    }
+    let dora_version = match dora_version {
+        Some(version) => crate::resolve_installed_version(home, version)?,
+        None => config::load_config(home)?.active_version.ok_or_else(|| {
+            DmError::DependencyMissing("No active dora version. Run `dm install` first.".into())
+        })?,
+    };
+    let bin = config::dora_bin_path(&config::versions_dir(home).join(&dora_version));
+    if !bin.exists() {
+        return Err(DmError::DependencyMissing(format!(
+            "dora {} is not installed. Run `dm install {}` first.",
+            dora_version, dora_version
+        ))
+        .into());
+    }
+
+    let [coordinator_port, control_port, daemon_port] = free_ports()?;
+    let mut runtime = RuntimeInstance {
+        id: uuid::Uuid::new_v4().to_string(),
+        run_id: None,
+        dora_version,
+        coordinator_port,
+        control_port,
+        daemon_port,
+        coordinator_pid: None,
+        daemon_pid: None,
+        started_at: chrono::Utc::now().to_rfc3339(),
+        stopped_at: None,
+    };
+    let dir = runtime.dir(home);
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create runtime dir {}", dir.display()))?;
+
+    let env = dora::RuntimeEnv::from_config(home).with_companions(&bin);
+    let (coordinator_bin, coordinator_args) = component_command(
+        &bin,
+        "coordinator",
+        [
+            "--interface",
+            HOST,
+            "--port",
+            &coordinator_port.to_string(),
+            "--control-interface",
+            HOST,
+            "--control-port",
+            &control_port.to_string(),
+        ],
+    );
+    let coordinator = spawn_logged(
+        &coordinator_bin,
+        &dir,
+        "coordinator",
+        &env,
+        &coordinator_args,
+    )?;
+    runtime.coordinator_pid = Some(coordinator.id());
+    save(home, &runtime)?;
+    reap_on_exit(home, &runtime.id, coordinator);
+    let (daemon_bin, daemon_args) = component_command(
+        &bin,
+        "daemon",
+        [
+            "--coordinator-addr",
+            HOST,
+            "--coordinator-port",
+            &coordinator_port.to_string(),
+            "--local-listen-port",
+            &daemon_port.to_string(),
+        ],
+    );
+    let daemon = spawn_logged(&daemon_bin, &dir, "daemon", &env, &daemon_args)?;
+    runtime.daemon_pid = Some(daemon.id());
+    save(home, &runtime)?;
+    reap_on_exit(home, &runtime.id, daemon);
+
+    for _ in 0..READY_ATTEMPTS {
+        tokio::time::sleep(READY_INTERVAL).await;
+        if runtime.is_ready(home).await {
+            return Ok(runtime);
+        }
+    }
+    shutdown(home, &mut runtime);
+    bail!(
+        "Dedicated dora runtime did not become ready; see the logs in {}",
+        dir.display()
+    )
+}
+
+/// Bind the runtime to the run it serves.
+pub fn attach_run(home: &Path, runtime: &mut RuntimeInstance, run_id: &str) -> Result<()> {
+    runtime.run_id = Some(run_id.to_string());
+    save(home, runtime)
+}
+
+/// Tear the runtime down: `dora destroy`, then kill whatever is left.
+/// Logs stay in the runtime dir.
+pub fn shutdown(home: &Path, runtime: &mut RuntimeInstance) {
+    if runtime.stopped_at.is_some() {
+        return;
+    }
+    let _ = StdCommand::new(runtime.dora_bin(home))
+        .arg("destroy")
+        .args(runtime.cli_args())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    for pid in [runtime.daemon_pid, runtime.coordinator_pid]
+        .into_iter()
+        .flatten()
+    {
+        kill(pid);
+    }
+    runtime.stopped_at = Some(chrono::Utc::now().to_rfc3339());
+    let _ = save(home, runtime);
+}
+
+/// Binary and arguments that start `component` (`coordinator`, `daemon`):
+/// the `dora-<component>` companion when the version ships one, otherwise
+/// the `dora <component>` subcommand.
+fn component_command<const N: usize>(
+    dora_bin: &Path,
+    component: &str,
+    args: [&str; N],
+) -> (PathBuf, Vec<String>) {
+    let args = args.iter().map(|arg| arg.to_string());
+    let companion = dora_bin
+        .parent()
+        .map(|dir| config::companion_bin_path(dir, &format!("dora-{}", component)))
+        .filter(|path| path.exists());
+    match companion {
+        Some(path) => (path, args.collect()),
+        None => (
+            dora_bin.to_path_buf(),
+            std::iter::once(component.to_string()).chain(args).collect(),
+        ),
+    }
+}
+
+fn spawn_logged(
+    bin: &Path,
+    dir: &Path,
+    name: &str,
+    env: &dora::RuntimeEnv,
+    args: &[String],
+) -> Result<std::process::Child> {
+    let log_path = dir.join(format!("{}.log", name));
+    let log = std::fs::File::create(&log_path)
+        .with_context(|| format!("Failed to create {}", log_path.display()))?;
+    let mut cmd = StdCommand::new(bin);
+    env.apply(&mut cmd);
+    cmd.args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+    // Keep the runtime alive when the terminal that started it sends SIGINT.
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut cmd, 0);
+    cmd.spawn()
+        .with_context(|| format!("Failed to spawn dora {} at {}", name, bin.display()))
+}
+
+/// Wait for a runtime process on a background thread, so that inside a
+/// long-running dm-server it does not linger as a zombie (which still
+/// looks alive to `kill -0`), and tear the runtime down once it exits.
+fn reap_on_exit(home: &Path, id: &str, mut child: std::process::Child) {
+    let home = home.to_path_buf();
+    let id = id.to_string();
+    std::thread::spawn(move || {
+        let _ = child.wait();
+        let record = std::fs::read_to_string(record_path(&home, &id)).ok();
+        if let Some(mut runtime) =
+            record.and_then(|content| serde_json::from_str::<RuntimeInstance>(&content).ok())
+        {
+            shutdown(&home, &mut runtime);
+        }
+    });
+}
+
+fn free_ports<const N: usize>() -> Result<[u16; N]> {
+    // Hold every listener until all ports are picked so they are distinct.
+    let listeners = (0..N)
+        .map(|_| std::net::TcpListener::bind((HOST, 0)))
+        .collect::<std::io::Result<Vec<_>>>()
+        .context("Failed to reserve ports for a dedicated dora runtime")?;
+    let mut ports = [0u16; N];
+    for (port, listener) in ports.iter_mut().zip(&listeners) {
+        *port = listener.local_addr()?.port();
+    }
+    Ok(ports)
+}
+
+#[cfg(unix)]
+fn kill(pid: u32) {
+    let _ = StdCommand::new("kill")
+        .arg(pid.to_string())
+        .stderr(Stdio::null())
+        .status();
+}
+
+#[cfg(not(unix))]
+fn kill(pid: u32) {
+    let _ = StdCommand::new("taskkill")
+        .args(["/PID", &pid.to_string(), "/F"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use dm_test_utils::{MockDora, MockResponse};
+
+    use super::*;
+
+    fn install_fake_dora(home: &Path, version: &str) {
+        // A daemon of version "crash" exits right away.
+        let daemon = if version == "crash" {
+            MockResponse::fail(3, "")
+        } else {
+            MockResponse::script("exec sleep 30")
+        };
+        MockDora::new(version)
+            .respond("coordinator", MockResponse::script("exec sleep 30"))
+            .respond("daemon", daemon)
+            .install(home)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn spawn_tracks_runtime_until_shutdown() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        install_fake_dora(home, "0.3.9");
+
+        let mut runtime = spawn(home, Some("0.3.9")).await.unwrap();
+        let ports = [
+            runtime.coordinator_port,
+            runtime.control_port,
+            runtime.daemon_port,
+        ];
+        assert!(ports.iter().all(|port| *port != 0));
+        assert_ne!(ports[0], ports[1]);
+        assert_ne!(ports[1], ports[2]);
+        assert!(runtime.dir(home).join("coordinator.log").exists());
+
+        attach_run(home, &mut runtime, "run-1").unwrap();
+        assert_eq!(find_for_run(home, "run-1"), Some(runtime.clone()));
+        assert!(isolated_run_ids(home).contains("run-1"));
+
+        shutdown(home, &mut runtime);
+        assert!(find_for_run(home, "run-1").is_none());
+        assert!(list(home)[0].stopped_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn runtime_is_stopped_once_a_process_exits() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        install_fake_dora(home, "crash");
+
+        let runtime = spawn(home, Some("crash")).await.unwrap();
+        for _ in 0..40 {
+            if list(home)[0].stopped_at.is_some() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        let stopped = &list(home)[0];
+        assert_eq!(stopped.id, runtime.id);
+        assert!(stopped.stopped_at.is_some());
+        assert!(!stopped.is_alive());
+    }
+
+    #[tokio::test]
+    async fn spawn_requires_installed_version() {
+        let tmp = tempfile::tempdir().unwrap();
+        let err = spawn(tmp.path(), Some("9.9.9")).await.unwrap_err();
+        assert!(err.to_string().contains("dm install 9.9.9"));
+    }
+}
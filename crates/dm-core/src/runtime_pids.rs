@@ -0,0 +1,184 @@
+//! PID files for the shared dora runtime.
+//!
+//! `dora up` forks the coordinator and daemon and exits, so once it reports
+//! success dm looks the two processes up and records their pids in
+//! `<home>/run/<role>.pid`. `status()` reads them back to report per-process
+//! health; `down` removes them.
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessStatus, ProcessesToUpdate, System, UpdateKind};
+
+use crate::types::RuntimeProcess;
+
+/// Runtime components tracked through PID files.
+pub const ROLES: [&str; 2] = ["coordinator", "daemon"];
+
+pub fn run_dir(home: &Path) -> PathBuf {
+    home.join("run")
+}
+
+pub fn pid_file(home: &Path, role: &str) -> PathBuf {
+    run_dir(home).join(format!("{}.pid", role))
+}
+
+/// The pid recorded for `role`, if any.
+pub fn read_pid(home: &Path, role: &str) -> Option<u32> {
+    std::fs::read_to_string(pid_file(home, role))
+        .ok()
+        .and_then(|content| content.trim().parse().ok())
+}
+
+pub fn write_pid(home: &Path, role: &str, pid: u32) -> Result<()> {
+    let dir = run_dir(home);
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let path = pid_file(home, role);
+    std::fs::write(&path, format!("{}\n", pid))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Remove every PID file.
+pub fn clear(home: &Path) {
+    for role in ROLES {
+        let _ = std::fs::remove_file(pid_file(home, role));
+    }
+}
+
+/// Find the coordinator and daemon the shared runtime is made of and
+/// record their pids, replacing older files. Returns the roles found.
+pub fn record(home: &Path) -> Result<Vec<&'static str>> {
+    // Dedicated runtimes run the same binaries; leave their processes out.
+    let isolated: Vec<u32> = crate::runtime_manager::list(home)
+        .into_iter()
+        .filter(|rt| rt.is_active())
+        .flat_map(|rt| [rt.coordinator_pid, rt.daemon_pid])
+        .flatten()
+        .collect();
+
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::All,
+        true,
+        ProcessRefreshKind::nothing().with_cmd(UpdateKind::OnlyIfNotSet),
+    );
+
+    clear(home);
+    let mut found = Vec::new();
+    for role in ROLES {
+        let newest = system
+            .processes()
+            .iter()
+            .filter(|(pid, _)| !isolated.contains(&pid.as_u32()))
+            .filter(|(_, process)| is_role(process.cmd(), role))
+            .max_by_key(|(_, process)| process.start_time());
+        if let Some((pid, _)) = newest {
+            write_pid(home, role, pid.as_u32())?;
+            found.push(role);
+        }
+    }
+    Ok(found)
+}
+
+/// Liveness, uptime, CPU and memory of every process with a PID file.
+pub async fn inspect(home: &Path) -> Vec<RuntimeProcess> {
+    let recorded: Vec<(&str, u32)> = ROLES
+        .into_iter()
+        .filter_map(|role| read_pid(home, role).map(|pid| (role, pid)))
+        .collect();
+    if recorded.is_empty() {
+        return Vec::new();
+    }
+
+    let pids: Vec<Pid> = recorded
+        .iter()
+        .map(|(_, pid)| Pid::from_u32(*pid))
+        .collect();
+    let kind = ProcessRefreshKind::nothing()
+        .with_cpu()
+        .with_memory()
+        .with_cmd(UpdateKind::OnlyIfNotSet);
+    let mut system = System::new();
+    // CPU usage is measured between two refreshes.
+    system.refresh_processes_specifics(ProcessesToUpdate::Some(&pids), true, kind);
+    tokio::time::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL).await;
+    system.refresh_processes_specifics(ProcessesToUpdate::Some(&pids), true, kind);
+
+    recorded
+        .into_iter()
+        .map(|(role, pid)| {
+            // A reused pid running something else counts as dead.
+            let process = system
+                .process(Pid::from_u32(pid))
+                .filter(|process| process.status() != ProcessStatus::Zombie)
+                .filter(|process| is_role(process.cmd(), role));
+            RuntimeProcess {
+                role: role.to_string(),
+                pid,
+                alive: process.is_some(),
+                uptime_secs: process.map(|process| process.run_time()),
+                cpu_percent: process.map(|process| process.cpu_usage()),
+                memory_bytes: process.map(|process| process.memory()),
+            }
+        })
+        .collect()
+}
+
+/// Whether `cmd` starts `role`: either `dora <role> ...` or the
+/// `dora-<role>` companion binary.
+fn is_role(cmd: &[OsString], role: &str) -> bool {
+    let Some(program) = cmd
+        .first()
+        .and_then(|arg| Path::new(arg).file_stem())
+        .and_then(|stem| stem.to_str())
+    else {
+        return false;
+    };
+    if program == format!("dora-{}", role) {
+        return true;
+    }
+    program == "dora" && cmd.get(1).is_some_and(|arg| arg == role)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cmd(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn is_role_matches_subcommands_and_companions() {
+        assert!(is_role(
+            &cmd(&["/opt/dora/dora", "coordinator"]),
+            "coordinator"
+        ));
+        assert!(is_role(&cmd(&["dora", "daemon", "--quiet"]), "daemon"));
+        assert!(is_role(&cmd(&["/opt/dora/dora-daemon"]), "daemon"));
+        assert!(!is_role(&cmd(&["dora", "daemon"]), "coordinator"));
+        assert!(!is_role(&cmd(&["dora", "up"]), "daemon"));
+        assert!(!is_role(&cmd(&["python", "daemon"]), "daemon"));
+        assert!(!is_role(&[], "daemon"));
+    }
+
+    #[tokio::test]
+    async fn inspect_reports_recorded_pids_and_their_liveness() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        assert!(inspect(home).await.is_empty());
+
+        // The test binary is no dora coordinator, so its pid reads as dead.
+        write_pid(home, "coordinator", std::process::id()).unwrap();
+        assert_eq!(read_pid(home, "coordinator"), Some(std::process::id()));
+        let processes = inspect(home).await;
+        assert_eq!(processes.len(), 1);
+        assert_eq!(processes[0].role, "coordinator");
+        assert!(!processes[0].alive);
+        assert_eq!(processes[0].memory_bytes, None);
+
+        clear(home);
+        assert!(inspect(home).await.is_empty());
+    }
+}
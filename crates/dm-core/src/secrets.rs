@@ -0,0 +1,166 @@
+//! Named secrets stored in `<home>/secrets.db`.
+//!
+//! Values are referenced from `runtime_env` as `secret:NAME` so tokens
+//! never land in config.toml. The database is encrypted along with
+//! events.db when `[encryption] enabled = true`.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// Prefix marking a `runtime_env` value as a reference into the store.
+pub const SECRET_REF_PREFIX: &str = "secret:";
+
+pub fn secrets_db_path(home: &Path) -> PathBuf {
+    home.join("secrets.db")
+}
+
+pub struct SecretStore {
+    conn: Connection,
+}
+
+impl SecretStore {
+    /// Open (or create) the secret store of `home`.
+    pub fn open(home: &Path) -> Result<Self> {
+        std::fs::create_dir_all(home)?;
+        let path = secrets_db_path(home);
+        let conn = crate::encryption::open_db(home, &path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS secrets (
+                name        TEXT PRIMARY KEY,
+                value       TEXT NOT NULL,
+                updated_at  TEXT NOT NULL
+             );",
+        )?;
+        Ok(Self { conn })
+    }
+
+    pub fn set(&self, name: &str, value: &str) -> Result<()> {
+        validate_name(name)?;
+        self.conn.execute(
+            "INSERT INTO secrets (name, value, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(name) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+            params![name, value, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT value FROM secrets WHERE name = ?1",
+                params![name],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Remove `name`; returns whether it existed.
+    pub fn remove(&self, name: &str) -> Result<bool> {
+        Ok(self
+            .conn
+            .execute("DELETE FROM secrets WHERE name = ?1", params![name])?
+            > 0)
+    }
+
+    pub fn names(&self) -> Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT name FROM secrets ORDER BY name")?;
+        let names = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(names)
+    }
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+    {
+        bail!(
+            "Invalid secret name '{}': use letters, digits, '_', '-' or '.'",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// Replace `secret:NAME` values with the stored secret. References that
+/// cannot be resolved are dropped with a warning rather than passed on
+/// literally.
+pub fn resolve_env_refs(
+    home: &Path,
+    vars: std::collections::BTreeMap<String, String>,
+) -> std::collections::BTreeMap<String, String> {
+    if !vars.values().any(|v| v.starts_with(SECRET_REF_PREFIX)) {
+        return vars;
+    }
+    let store = SecretStore::open(home);
+    vars.into_iter()
+        .filter_map(|(key, value)| {
+            let Some(name) = value.strip_prefix(SECRET_REF_PREFIX) else {
+                return Some((key, value));
+            };
+            let resolved = match &store {
+                Ok(store) => store.get(name).ok().flatten(),
+                Err(_) => None,
+            };
+            if resolved.is_none() {
+                eprintln!(
+                    "[dm] warning: secret '{}' for {} is not available",
+                    name, key
+                );
+            }
+            resolved.map(|value| (key, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn set_get_remove_roundtrip() {
+        let tmp = tempfile::tempdir().unwrap();
+        let store = SecretStore::open(tmp.path()).unwrap();
+        store.set("HF_TOKEN", "abc").unwrap();
+        store.set("HF_TOKEN", "def").unwrap();
+        store.set("api.key", "xyz").unwrap();
+
+        assert_eq!(store.get("HF_TOKEN").unwrap().as_deref(), Some("def"));
+        assert_eq!(store.names().unwrap(), vec!["HF_TOKEN", "api.key"]);
+        assert!(store.remove("HF_TOKEN").unwrap());
+        assert!(!store.remove("HF_TOKEN").unwrap());
+        assert_eq!(store.get("HF_TOKEN").unwrap(), None);
+        assert!(store.set("bad name", "x").is_err());
+    }
+
+    #[test]
+    fn env_refs_resolve_from_store() {
+        let tmp = tempfile::tempdir().unwrap();
+        SecretStore::open(tmp.path())
+            .unwrap()
+            .set("hf", "token-123")
+            .unwrap();
+
+        let vars = BTreeMap::from([
+            ("HF_TOKEN".to_string(), "secret:hf".to_string()),
+            ("MISSING".to_string(), "secret:nope".to_string()),
+            ("RUST_LOG".to_string(), "debug".to_string()),
+        ]);
+        let resolved = resolve_env_refs(tmp.path(), vars);
+        assert_eq!(
+            resolved.get("HF_TOKEN").map(String::as_str),
+            Some("token-123")
+        );
+        assert_eq!(resolved.get("RUST_LOG").map(String::as_str), Some("debug"));
+        assert!(!resolved.contains_key("MISSING"));
+    }
+}
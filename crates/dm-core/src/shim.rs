@@ -0,0 +1,162 @@
+//! `dora` shim in `<home>/bin` that runs the active version directly.
+//!
+//! On Unix `<home>/active` is a symlink to `versions/<active>` and the shim
+//! `bin/dora` links to `../active/dora`, so switching versions only swaps
+//! the `active` link (write a temporary link, then rename it over the old
+//! one). Windows has no unprivileged symlinks; there the shim is a
+//! `dora.cmd` script that is rewritten, the same way, on every switch.
+//!
+//! The shim runs dora without dm's `runtime_env`; use `dm --` for that.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::config;
+use crate::types::ShimStatus;
+
+/// Directory holding the shim, to be put on `PATH`.
+pub fn shim_dir(home: &Path) -> PathBuf {
+    home.join("bin")
+}
+
+/// Path of the shim itself.
+pub fn shim_path(home: &Path) -> PathBuf {
+    if cfg!(windows) {
+        shim_dir(home).join("dora.cmd")
+    } else {
+        shim_dir(home).join("dora")
+    }
+}
+
+/// Create the shim and point it at the active version.
+pub fn install_shim(home: &Path) -> Result<ShimStatus> {
+    let dir = shim_dir(home);
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    #[cfg(unix)]
+    replace_symlink(
+        &Path::new("..").join("active").join(config::dora_bin_name()),
+        &shim_path(home),
+    )
+    .context("Failed to create the dora shim")?;
+    // Windows writes the shim script here; on Unix this refreshes `active`.
+    let active = config::load_config(home)?.active_version;
+    write_active(home, active.as_deref(), true)?;
+    Ok(shim_status(home))
+}
+
+/// Delete the shim; returns whether it existed.
+pub fn remove_shim(home: &Path) -> Result<bool> {
+    let path = shim_path(home);
+    if std::fs::symlink_metadata(&path).is_err() {
+        return Ok(false);
+    }
+    std::fs::remove_file(&path).with_context(|| format!("Failed to remove {}", path.display()))?;
+    Ok(true)
+}
+
+/// Where the shim is, what it runs and whether its directory is on `PATH`.
+pub fn shim_status(home: &Path) -> ShimStatus {
+    let path = shim_path(home);
+    let installed = std::fs::symlink_metadata(&path).is_ok();
+    let active = config::load_config(home)
+        .ok()
+        .and_then(|cfg| cfg.active_version);
+    let target = active
+        .map(|version| config::dora_bin_path(&config::versions_dir(home).join(version)))
+        .filter(|bin| installed && bin.exists())
+        .map(|bin| bin.display().to_string());
+    let dir = shim_dir(home);
+    let on_path = std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|p| p == dir))
+        .unwrap_or(false);
+    ShimStatus {
+        path: path.display().to_string(),
+        dir: dir.display().to_string(),
+        installed,
+        target,
+        on_path,
+    }
+}
+
+/// Point `active` (and on Windows the shim, when installed) at `version`.
+pub(crate) fn activate(home: &Path, version: &str) -> Result<()> {
+    write_active(home, Some(version), false)
+}
+
+fn write_active(home: &Path, version: Option<&str>, create_shim: bool) -> Result<()> {
+    #[cfg(unix)]
+    {
+        let _ = create_shim;
+        if let Some(version) = version {
+            replace_symlink(
+                &Path::new("versions").join(version),
+                &config::active_link(home),
+            )
+            .context("Failed to update the active version link")?;
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let path = shim_path(home);
+        if create_shim || path.exists() {
+            let script = match version {
+                Some(version) => format!(
+                    "@echo off\r\n\"{}\" %*\r\n",
+                    config::dora_bin_path(&config::versions_dir(home).join(version)).display()
+                ),
+                None => "@echo off\r\necho No active dora version. Run `dm install` first. 1>&2\r\nexit /b 1\r\n"
+                    .to_string(),
+            };
+            let tmp = path.with_extension("cmd.tmp");
+            std::fs::write(&tmp, script)?;
+            std::fs::rename(&tmp, &path).context("Failed to update the dora shim")?;
+        }
+    }
+    Ok(())
+}
+
+/// Atomically make `link` a symlink to `target`.
+#[cfg(unix)]
+fn replace_symlink(target: &Path, link: &Path) -> std::io::Result<()> {
+    let name = link.file_name().unwrap_or_default().to_string_lossy();
+    let tmp = link.with_file_name(format!(".{}.tmp-{}", name, std::process::id()));
+    let _ = std::fs::remove_file(&tmp);
+    std::os::unix::fs::symlink(target, &tmp)?;
+    std::fs::rename(&tmp, link)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn fake_version(home: &Path, version: &str) {
+        let dir = config::versions_dir(home).join(version);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(config::dora_bin_path(&dir), version).unwrap();
+    }
+
+    #[test]
+    fn shim_follows_version_switches() {
+        let tmp = tempfile::tempdir().unwrap();
+        let home = tmp.path();
+        fake_version(home, "0.3.9");
+        fake_version(home, "0.4.1");
+        let cfg = config::DmConfig {
+            active_version: Some("0.3.9".into()),
+            ..Default::default()
+        };
+        config::save_config(home, &cfg).unwrap();
+
+        let status = install_shim(home).unwrap();
+        assert!(status.installed);
+        assert_eq!(std::fs::read_to_string(shim_path(home)).unwrap(), "0.3.9");
+
+        activate(home, "0.4.1").unwrap();
+        assert_eq!(std::fs::read_to_string(shim_path(home)).unwrap(), "0.4.1");
+
+        assert!(remove_shim(home).unwrap());
+        assert!(!remove_shim(home).unwrap());
+        assert!(!shim_status(home).installed);
+    }
+}
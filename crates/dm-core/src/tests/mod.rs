@@ -7,6 +7,8 @@ mod tests_dataflow;
 #[cfg(test)]
 mod tests_env;
 #[cfg(test)]
+mod tests_layout;
+#[cfg(test)]
 mod tests_node;
 #[cfg(test)]
 mod tests_types;
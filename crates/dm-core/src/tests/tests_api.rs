@@ -1,3 +1,4 @@
+use dm_test_utils::{MockDora, MockResponse};
 use tempfile::TempDir;
 
 use crate::config;
@@ -104,6 +105,65 @@ async fn doctor_active_but_missing_binary() {
     assert!(!report.all_ok);
 }
 
+#[cfg(unix)]
+#[tokio::test]
+async fn doctor_deep_probes_installed_nodes() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let tmp = TempDir::new().unwrap();
+    let home = tmp.path().to_path_buf();
+    let node = |id: &str, script: Option<&str>| {
+        let dir = dm_test_utils::fake_node(
+            &home,
+            id,
+            serde_json::json!({ "source": { "build": "local" }, "executable": "run.sh" }),
+        )
+        .unwrap();
+        if let Some(script) = script {
+            let exe = dir.join("run.sh");
+            std::fs::write(&exe, format!("#!/bin/sh\n{script}\n")).unwrap();
+            std::fs::set_permissions(&exe, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+    };
+    node(
+        "healthy",
+        Some("echo 'DORA_NODE_CONFIG is not set' >&2; exit 1"),
+    );
+    node(
+        "broken",
+        Some("echo 'run.sh: error while loading shared libraries: libfoo.so' >&2; exit 127"),
+    );
+    node("waiting", Some("sleep 30"));
+    node("missing", None);
+
+    let report = crate::doctor_with_options(
+        &home,
+        crate::DoctorOptions {
+            history: false,
+            deep: true,
+        },
+    )
+    .await
+    .unwrap();
+    let probes: Vec<_> = report
+        .node_probes
+        .iter()
+        .filter(|p| ["broken", "healthy", "missing", "waiting"].contains(&p.node_id.as_str()))
+        .collect();
+    let results: Vec<(&str, bool)> = probes.iter().map(|p| (p.node_id.as_str(), p.ok)).collect();
+    assert_eq!(
+        results,
+        vec![
+            ("broken", false),
+            ("healthy", true),
+            ("missing", false),
+            ("waiting", true),
+        ]
+    );
+    assert!(probes[0].detail.contains("libfoo.so"));
+    assert!(!report.all_ok);
+}
+
 // ─── versions ───
 
 #[tokio::test]
@@ -267,6 +327,84 @@ async fn use_version_same_version() {
     assert_eq!(cfg.active_version, Some("0.4.1".into()));
 }
 
+#[tokio::test]
+async fn use_version_resolves_builtin_and_custom_aliases() {
+    let tmp = setup_fake_home(&["0.3.9", "0.4.1", "0.5.0-rc.1"], Some("0.3.9"));
+    let home = tmp.path().to_path_buf();
+
+    crate::use_version(&home, "latest").await.unwrap();
+    let cfg = config::load_config(&home).unwrap();
+    assert_eq!(cfg.active_version, Some("0.5.0-rc.1".into()));
+
+    crate::use_version(&home, "stable").await.unwrap();
+    let cfg = config::load_config(&home).unwrap();
+    assert_eq!(cfg.active_version, Some("0.4.1".into()));
+
+    crate::set_version_alias(&home, "demo", "v0.3.9").unwrap();
+    crate::use_version(&home, "demo").await.unwrap();
+    let cfg = config::load_config(&home).unwrap();
+    assert_eq!(cfg.active_version, Some("0.3.9".into()));
+    assert_eq!(cfg.version_aliases.get("demo"), Some(&"0.3.9".to_string()));
+
+    let err = crate::uninstall(&home, "demo")
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(
+        err.contains("Cannot uninstall active version 0.3.9"),
+        "{err}"
+    );
+}
+
+#[test]
+fn version_aliases_reject_reserved_and_version_like_names() {
+    let tmp = TempDir::new().unwrap();
+    let home = tmp.path();
+
+    for name in ["latest", "0.4", "v1", "bad name", ""] {
+        assert!(
+            crate::set_version_alias(home, name, "0.3.9").is_err(),
+            "{name}"
+        );
+    }
+    assert!(crate::set_version_alias(home, "demo", "stable").is_err());
+
+    crate::set_version_alias(home, "demo", "0.3.9").unwrap();
+    assert!(crate::remove_version_alias(home, "demo").unwrap());
+    assert!(!crate::remove_version_alias(home, "demo").unwrap());
+    assert!(crate::version_aliases(home).unwrap().is_empty());
+
+    let err = crate::resolve_installed_version(home, "stable")
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("No installed dora version"), "{err}");
+    assert_eq!(
+        crate::resolve_installed_version(home, "v0.4.1").unwrap(),
+        "0.4.1"
+    );
+}
+
+#[test]
+fn installed_versions_resolve_ranges_and_a_pinned_stable() {
+    let tmp = TempDir::new().unwrap();
+    let home = tmp.path();
+    for version in ["0.3.8", "0.3.9", "0.4.0-rc.1", "0.4.1"] {
+        std::fs::create_dir_all(config::versions_dir(home).join(version)).unwrap();
+    }
+
+    let resolve = |spec: &str| crate::resolve_installed_version(home, spec).unwrap();
+    assert_eq!(resolve("^0.3"), "0.3.9");
+    assert_eq!(resolve("0.3"), "0.3.9");
+    assert_eq!(resolve("~0.3.8"), "0.3.9");
+    assert_eq!(resolve(">=0.3, <0.3.9"), "0.3.8");
+    assert_eq!(resolve("0.3.8"), "0.3.8");
+    assert_eq!(resolve("stable"), "0.4.1");
+    assert!(crate::resolve_installed_version(home, "^0.5").is_err());
+
+    crate::set_version_alias(home, "stable", "0.3.8").unwrap();
+    assert_eq!(resolve("stable"), "0.3.8");
+}
+
 // ─── status ───
 
 #[tokio::test]
@@ -296,6 +434,114 @@ async fn status_with_active_version() {
     assert!(report.dm_home.contains(tmp.path().to_str().unwrap()));
 }
 
+#[cfg(unix)]
+#[tokio::test]
+async fn ps_lists_running_dataflows_with_their_nodes() {
+    let tmp = setup_fake_home(&["0.4.1"], Some("0.4.1"));
+    let home = tmp.path();
+    MockDora::new("0.4.1")
+        .respond(
+            "list",
+            MockResponse::ok(concat!(
+                r#"{"uuid":"019cc181-adad-7654-aa78-63502362337b","name":"cam","status":"Running","nodes":1,"cpu":1.5,"memory":0.2}"#,
+                "\n",
+                r#"{"uuid":"019cc181-adad-7654-aa78-635023623380","name":"old","status":"Finished","nodes":1,"cpu":0.0,"memory":0.0}"#,
+            )),
+        )
+        .respond(
+            "node",
+            MockResponse::ok(
+                r#"{"node":"camera","status":"Running","pid":"42","cpu":"1.5%","memory":"20 MB"}"#,
+            ),
+        )
+        .install(home)
+        .unwrap();
+
+    let processes = crate::ps(home, false).await.unwrap();
+    assert_eq!(processes.len(), 1);
+    assert_eq!(processes[0].dataflow.dataflow_name, "cam");
+    assert_eq!(processes[0].dataflow.cpu.as_deref(), Some("1.5%"));
+    assert_eq!(processes[0].dataflow.run_id, None);
+    assert_eq!(processes[0].nodes.len(), 1);
+    assert_eq!(processes[0].nodes[0].id, "camera");
+    assert_eq!(processes[0].nodes[0].pid.as_deref(), Some("42"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn supervisor_restarts_a_dead_runtime_and_records_it() {
+    let tmp = setup_fake_home(&["0.4.1"], Some("0.4.1"));
+    let home = tmp.path();
+    let marker = home.join("runtime-up");
+    MockDora::new("0.4.1")
+        .respond(
+            "check",
+            MockResponse::script(format!("test -f '{}'", marker.display())),
+        )
+        .respond(
+            "up",
+            MockResponse::script(format!("touch '{}'", marker.display())),
+        )
+        .install(home)
+        .unwrap();
+
+    let mut supervisor = crate::RuntimeSupervisor::new(
+        home,
+        false,
+        crate::SupervisorOptions {
+            interval: std::time::Duration::from_secs(7),
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        supervisor.check().await.unwrap(),
+        crate::SupervisorAction::Restarted { attempt: 1 }
+    );
+    assert_eq!(
+        supervisor.check().await.unwrap(),
+        crate::SupervisorAction::Healthy
+    );
+    assert_eq!(
+        supervisor.next_check_in(),
+        std::time::Duration::from_secs(7)
+    );
+
+    // A runtime that cannot come back is retried with growing delays.
+    std::fs::remove_file(&marker).unwrap();
+    MockDora::new("0.4.1")
+        .fail("check", 1, "address in use")
+        .fail("up", 1, "address in use")
+        .install(home)
+        .unwrap();
+    for (attempt, secs) in [(1, 1), (2, 2)] {
+        match supervisor.check().await.unwrap() {
+            crate::SupervisorAction::RestartFailed {
+                attempt: got,
+                message,
+                retry_in,
+            } => {
+                assert_eq!(got, attempt);
+                assert!(message.contains("address in use"), "{message}");
+                assert_eq!(retry_in, std::time::Duration::from_secs(secs));
+            }
+            other => panic!("expected a failed restart, got {other:?}"),
+        }
+    }
+
+    let restarts: Vec<_> = read_all_events(home)
+        .into_iter()
+        .filter(|e| e.activity == crate::RUNTIME_RESTART_ACTIVITY)
+        .collect();
+    // The identical failures collapse into one error event counting both.
+    assert_eq!(restarts.len(), 2);
+    let failed: i64 = restarts
+        .iter()
+        .filter(|e| e.level == "error")
+        .map(|e| e.repeat_count())
+        .sum();
+    assert_eq!(failed, 2);
+}
+
 // ─── dora module ───
 
 #[tokio::test]
@@ -336,3 +582,135 @@ async fn active_dora_bin_found() {
     assert!(bin.exists());
     assert!(bin.ends_with(crate::config::dora_bin_name()));
 }
+
+fn dora_exec_events(home: &std::path::Path) -> Vec<crate::events::Event> {
+    read_all_events(home)
+        .into_iter()
+        .filter(|e| e.activity == "dora.exec")
+        .collect()
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn run_dora_traces_invocation() {
+    let tmp = setup_fake_home(&["0.4.1"], Some("0.4.1"));
+    let home = tmp.path();
+
+    let args = vec!["start".to_string(), "flow.yml".to_string()];
+    let (code, _, _) = crate::dora::run_dora(home, &args, false).await.unwrap();
+    assert_eq!(code, 0);
+
+    let events = dora_exec_events(home);
+    assert_eq!(events.len(), 1);
+    let attrs: serde_json::Value =
+        serde_json::from_str(events[0].attributes.as_deref().unwrap()).unwrap();
+    assert_eq!(attrs["argv"], serde_json::json!(["start", "flow.yml"]));
+    assert_eq!(attrs["exit_code"], 0);
+    assert!(attrs["duration_ms"].is_u64());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn run_dora_trace_keeps_stderr_tail_of_failures() {
+    let tmp = setup_fake_home(&["0.4.1"], Some("0.4.1"));
+    let home = tmp.path();
+    MockDora::new("0.4.1")
+        .fail("list", 3, "head-of-noise\nboom")
+        .install(home)
+        .unwrap();
+    let mut cfg = config::load_config(home).unwrap();
+    cfg.telemetry.trace_stderr_bytes = 4;
+    config::save_config(home, &cfg).unwrap();
+
+    let (code, _, _) = crate::dora::run_dora(home, &["list".to_string()], false)
+        .await
+        .unwrap();
+    assert_eq!(code, 3);
+
+    let events = dora_exec_events(home);
+    assert_eq!(events.len(), 1, "failed quiet commands are still traced");
+    assert_eq!(events[0].level, "warn");
+    let attrs: serde_json::Value =
+        serde_json::from_str(events[0].attributes.as_deref().unwrap()).unwrap();
+    assert_eq!(attrs["exit_code"], 3);
+    assert_eq!(attrs["stderr"], "boom");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn run_dora_skips_quiet_and_disabled_traces() {
+    let tmp = setup_fake_home(&["0.4.1"], Some("0.4.1"));
+    let home = tmp.path();
+
+    crate::dora::run_dora(home, &["list".to_string()], false)
+        .await
+        .unwrap();
+    assert!(dora_exec_events(home).is_empty());
+
+    let mut cfg = config::load_config(home).unwrap();
+    cfg.telemetry.trace_dora = false;
+    config::save_config(home, &cfg).unwrap();
+    crate::dora::run_dora(home, &["up".to_string()], false)
+        .await
+        .unwrap();
+    assert!(dora_exec_events(home).is_empty());
+}
+
+// ─── remote machines ───
+
+#[cfg(unix)]
+#[tokio::test]
+async fn dora_commands_target_the_configured_coordinator() {
+    let tmp = setup_fake_home(&["0.4.1"], Some("0.4.1"));
+    let home = tmp.path();
+    let echo = MockResponse::script("echo \"$@\"");
+    MockDora::new("0.4.1")
+        .respond("check", echo.clone())
+        .respond("--version", echo.clone())
+        .respond("list", echo)
+        .install(home)
+        .unwrap();
+    let mut cfg = config::load_config(home).unwrap();
+    cfg.coordinator_addr = Some("10.0.0.5:6012".to_string());
+    config::save_config(home, &cfg).unwrap();
+
+    let (_, stdout, _) = crate::dora::run_dora(home, &["check".to_string()], false)
+        .await
+        .unwrap();
+    assert_eq!(
+        stdout.trim(),
+        "check --coordinator-addr 10.0.0.5 --coordinator-port 6012"
+    );
+
+    let (_, stdout, _) = crate::dora::run_dora(home, &["--version".to_string()], false)
+        .await
+        .unwrap();
+    assert_eq!(stdout.trim(), "--version");
+
+    cfg.coordinator_addr = Some("coordinator.local".to_string());
+    config::save_config(home, &cfg).unwrap();
+    assert!(crate::dora::run_dora(home, &["list".to_string()], false)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn machines_are_validated_and_probed() {
+    let tmp = TempDir::new().unwrap();
+    let home = tmp.path();
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+
+    assert!(crate::add_machine(home, "arm", "not-an-ip").is_err());
+    assert!(crate::add_machine(home, "bad name", "10.0.0.7").is_err());
+    crate::add_machine(home, "arm", &addr).unwrap();
+    crate::add_machine(home, "cam", "10.0.0.7").unwrap();
+    assert_eq!(crate::machines(home).unwrap().len(), 2);
+
+    let machines = crate::machine_statuses(home).await;
+    assert_eq!(machines.len(), 2);
+    assert_eq!(machines[0].name, "arm");
+    assert_eq!(machines[0].reachable, Some(true));
+    assert_eq!(machines[1].addr, "10.0.0.7");
+    assert_eq!(machines[1].reachable, None);
+}
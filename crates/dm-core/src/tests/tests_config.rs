@@ -145,3 +145,249 @@ fn config_toml_format_is_valid() {
     assert!(content.contains("active_version"));
     assert!(content.contains("0.4.1"));
 }
+
+#[test]
+fn github_api_url_prefers_env_then_config_then_default() {
+    let _guard = crate::test_support::env_lock();
+    let tmp = TempDir::new().unwrap();
+    let home = tmp.path().to_path_buf();
+    std::env::remove_var("DM_GITHUB_API_URL");
+
+    assert_eq!(github_api_url(&home), DEFAULT_GITHUB_API_URL);
+
+    let cfg = DmConfig {
+        github_api_url: Some("https://ghe.example.com/api/v3/".into()),
+        ..Default::default()
+    };
+    save_config(&home, &cfg).unwrap();
+    assert_eq!(github_api_url(&home), "https://ghe.example.com/api/v3");
+
+    std::env::set_var("DM_GITHUB_API_URL", "http://127.0.0.1:9999");
+    assert_eq!(github_api_url(&home), "http://127.0.0.1:9999");
+    std::env::remove_var("DM_GITHUB_API_URL");
+}
+
+#[test]
+fn registry_url_is_unset_by_default() {
+    let _guard = crate::test_support::env_lock();
+    let tmp = TempDir::new().unwrap();
+    std::env::remove_var("DM_REGISTRY_URL");
+
+    assert!(registry_url(tmp.path()).is_none());
+
+    let cfg = DmConfig {
+        registry_url: Some("https://registry.example.com/registry.json".into()),
+        ..Default::default()
+    };
+    save_config(tmp.path(), &cfg).unwrap();
+    assert_eq!(
+        registry_url(tmp.path()).as_deref(),
+        Some("https://registry.example.com/registry.json")
+    );
+}
+
+#[test]
+fn runtime_env_round_trips_and_merges_overrides() {
+    let tmp = TempDir::new().unwrap();
+    let cfg = DmConfig {
+        runtime_inherit_env: Some(false),
+        runtime_env: [("RUST_LOG".to_string(), "info".to_string())]
+            .into_iter()
+            .collect(),
+        ..Default::default()
+    };
+    save_config(tmp.path(), &cfg).unwrap();
+
+    let env = crate::dora::RuntimeEnv::from_config(tmp.path());
+    assert!(!env.inherit);
+    assert_eq!(env.vars.get("RUST_LOG").map(String::as_str), Some("info"));
+
+    let overrides = [("RUST_LOG".to_string(), "debug".to_string())]
+        .into_iter()
+        .collect();
+    let env = env.with_overrides(&overrides, Some(true));
+    assert!(env.inherit);
+    assert_eq!(env.vars.get("RUST_LOG").map(String::as_str), Some("debug"));
+}
+
+#[test]
+fn companion_binaries_put_version_dir_first_on_path() {
+    let tmp = TempDir::new().unwrap();
+    let version_dir = versions_dir(tmp.path()).join("0.4.1");
+    std::fs::create_dir_all(&version_dir).unwrap();
+    let bin = dora_bin_path(&version_dir);
+    let env = crate::dora::RuntimeEnv::default();
+
+    let plain = env.clone().with_companions(&bin);
+    assert!(!plain.vars.contains_key("PATH"));
+
+    std::fs::write(companion_bin_path(&version_dir, "dora-daemon"), "").unwrap();
+    let env = env.with_companions(&bin);
+    let path = env.vars.get("PATH").unwrap();
+    let first = std::env::split_paths(path).next().unwrap();
+    assert_eq!(first, version_dir);
+}
+
+#[test]
+fn parse_env_assignment_requires_key_and_equals() {
+    use crate::dora::parse_env_assignment;
+
+    assert_eq!(
+        parse_env_assignment("DORA_LOG=a=b").unwrap(),
+        ("DORA_LOG".to_string(), "a=b".to_string())
+    );
+    assert_eq!(
+        parse_env_assignment("EMPTY=").unwrap(),
+        ("EMPTY".to_string(), String::new())
+    );
+    assert!(parse_env_assignment("NOVALUE").is_err());
+    assert!(parse_env_assignment("=value").is_err());
+}
+
+#[test]
+fn install_method_defaults_to_auto_and_parses_from_config() {
+    let tmp = TempDir::new().unwrap();
+    assert_eq!(
+        load_config(tmp.path()).unwrap().install.method,
+        InstallMethodPreference::Auto
+    );
+
+    std::fs::write(config_path(tmp.path()), "[install]\nmethod = \"source\"\n").unwrap();
+    assert_eq!(
+        load_config(tmp.path()).unwrap().install.method,
+        InstallMethodPreference::Source
+    );
+
+    assert_eq!(
+        "binary".parse::<InstallMethodPreference>().unwrap(),
+        InstallMethodPreference::Binary
+    );
+    assert!("docker".parse::<InstallMethodPreference>().is_err());
+}
+
+#[test]
+fn ingest_config_maps_tokens_to_sources() {
+    let tmp = TempDir::new().unwrap();
+    let ingest = load_config(tmp.path()).unwrap().ingest;
+    assert_eq!(ingest.allowed_sources(None).unwrap(), ["frontend"]);
+    assert!(ingest.allowed_sources(Some("anything")).is_none());
+
+    std::fs::write(
+        config_path(tmp.path()),
+        "[ingest]\nanonymous_sources = []\n\n[[ingest.tokens]]\nname = \"ci\"\ntoken = \"ci-secret\"\nsources = [\"ci\"]\n",
+    )
+    .unwrap();
+    let ingest = load_config(tmp.path()).unwrap().ingest;
+    assert!(ingest.allowed_sources(None).unwrap().is_empty());
+    assert_eq!(ingest.allowed_sources(Some("ci-secret")).unwrap(), ["ci"]);
+    assert!(ingest.allowed_sources(Some("")).is_none());
+}
+
+#[test]
+fn update_config_merges_and_rejects_unknown_keys() {
+    let tmp = TempDir::new().unwrap();
+    update_config(
+        tmp.path(),
+        &serde_json::json!({ "install": { "verify": false }, "robot_id": "rover-1" }),
+    )
+    .unwrap();
+    let cfg = update_config(tmp.path(), &serde_json::json!({ "robot_id": null })).unwrap();
+    assert!(!cfg.install.verify);
+    assert_eq!(cfg.robot_id, None);
+    assert_eq!(
+        cfg.install.node_parallelism,
+        load_config(tmp.path()).unwrap().install.node_parallelism
+    );
+
+    let err = update_config(
+        tmp.path(),
+        &serde_json::json!({ "install": { "verfy": true } }),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("install.verfy"), "{}", err);
+    assert!(update_config(tmp.path(), &serde_json::json!({ "verbose": "loud" })).is_err());
+    assert!(!load_config(tmp.path()).unwrap().install.verify);
+}
+
+#[test]
+fn set_config_value_parses_typed_values_and_keeps_strings() {
+    let tmp = TempDir::new().unwrap();
+    set_config_value(tmp.path(), "verbose", "true").unwrap();
+    set_config_value(tmp.path(), "proxy.https", "http://proxy:3128").unwrap();
+    set_config_value(tmp.path(), "active_version", "0.4.1").unwrap();
+    let cfg = load_config(tmp.path()).unwrap();
+    assert_eq!(cfg.verbose, Some(true));
+    assert_eq!(cfg.proxy.https.as_deref(), Some("http://proxy:3128"));
+    assert_eq!(cfg.active_version.as_deref(), Some("0.4.1"));
+    assert_eq!(
+        config_value(tmp.path(), "verbose").unwrap(),
+        Some(serde_json::json!(true))
+    );
+
+    unset_config_value(tmp.path(), "verbose").unwrap();
+    assert_eq!(load_config(tmp.path()).unwrap().verbose, None);
+}
+
+#[test]
+fn config_entries_redact_the_github_token() {
+    let tmp = TempDir::new().unwrap();
+    set_config_value(tmp.path(), "github_token", "ghp_secret").unwrap();
+    let entries = config_entries(tmp.path()).unwrap();
+    let token = entries
+        .iter()
+        .find(|(key, _)| key == "github_token")
+        .unwrap();
+    assert_eq!(token.1, serde_json::json!(REDACTED));
+
+    // Echoing the redacted value back must not overwrite the stored token.
+    update_config(tmp.path(), &serde_json::json!({ "github_token": REDACTED })).unwrap();
+    assert_eq!(
+        load_config(tmp.path()).unwrap().github_token.as_deref(),
+        Some("ghp_secret")
+    );
+}
+
+#[test]
+fn config_version_is_stamped_and_newer_versions_are_refused() {
+    let tmp = TempDir::new().unwrap();
+    save_config(tmp.path(), &DmConfig::default()).unwrap();
+    let text = std::fs::read_to_string(config_path(tmp.path())).unwrap();
+    assert!(text.contains(&format!("config_version = {}", CONFIG_VERSION)));
+
+    std::fs::write(
+        config_path(tmp.path()),
+        format!("config_version = {}\n", CONFIG_VERSION + 1),
+    )
+    .unwrap();
+    assert!(load_config(tmp.path()).is_err());
+}
+
+#[test]
+fn modify_config_serializes_concurrent_updates() {
+    let tmp = TempDir::new().unwrap();
+    let home = tmp.path().to_path_buf();
+    let writers: Vec<_> = (0..8)
+        .map(|i| {
+            let home = home.clone();
+            std::thread::spawn(move || {
+                modify_config(&home, |cfg| {
+                    cfg.labels.insert(format!("k{}", i), i.to_string());
+                    Ok(())
+                })
+                .unwrap();
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+    assert_eq!(load_config(&home).unwrap().labels.len(), 8);
+
+    let err = modify_config::<()>(&home, |cfg| {
+        cfg.labels.clear();
+        anyhow::bail!("nope")
+    });
+    assert!(err.is_err());
+    assert_eq!(load_config(&home).unwrap().labels.len(), 8);
+    assert!(!home.join(".config.toml.tmp").exists());
+}
@@ -1,11 +1,16 @@
+use std::collections::BTreeMap;
 use std::fs;
 
 use tempfile::tempdir;
 
-use crate::dataflow::{transpile_graph, transpile_graph_for_run};
+use crate::dataflow::{
+    check_golden, prepare_local_run, preview_node_env, render, render_golden, transpile_graph,
+    transpile_graph_for_run, transpile_graph_with_variables, DiagnosticKind, TranspileError,
+    PREVIEW_RUN_ID,
+};
 use crate::node::{
     node_dir, Node, NodeCapability, NodeCapabilityBinding, NodeCapabilityDetail, NodeDisplay,
-    NodeFiles, NodeRuntime, NodeSource,
+    NodeFiles, NodePort, NodePortDirection, NodeRuntime, NodeSource,
 };
 
 fn setup_managed_node(home: &std::path::Path, id: &str, executable: &str) {
@@ -39,6 +44,8 @@ fn setup_managed_node(home: &std::path::Path, id: &str, executable: &str) {
         examples: Vec::new(),
         config_schema: None,
         dynamic_ports: false,
+        pinned: None,
+        last_test: None,
         path: Default::default(),
     };
 
@@ -61,6 +68,25 @@ fn set_node_capabilities(home: &std::path::Path, id: &str, capabilities: Vec<Nod
     .unwrap();
 }
 
+fn set_node_ports(home: &std::path::Path, id: &str, ports: &[(&str, NodePortDirection)]) {
+    let dir = node_dir(home, id);
+    let mut meta: Node =
+        serde_json::from_str(&fs::read_to_string(dir.join("dm.json")).unwrap()).unwrap();
+    meta.ports = ports
+        .iter()
+        .map(|(port, direction)| NodePort {
+            id: port.to_string(),
+            direction: *direction,
+            ..Default::default()
+        })
+        .collect();
+    fs::write(
+        dir.join("dm.json"),
+        serde_json::to_string_pretty(&meta).unwrap(),
+    )
+    .unwrap();
+}
+
 #[test]
 #[cfg(not(target_os = "windows"))]
 fn transpile_graph_resolves_executable_path() {
@@ -153,6 +179,55 @@ nodes:
     );
 }
 
+#[test]
+fn transpile_graph_applies_port_adapters() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path();
+    setup_managed_node(home, "camera", ".venv/bin/camera");
+    setup_managed_node(home, "viewer", ".venv/bin/viewer");
+    set_node_ports(home, "camera", &[("image", NodePortDirection::Output)]);
+    set_node_ports(home, "viewer", &[("image", NodePortDirection::Input)]);
+
+    let yaml_path = home.join("graph.yml");
+    fs::write(
+        &yaml_path,
+        r#"
+nodes:
+  - id: cam
+    node: camera
+    outputs: [frame]
+    adapter:
+      outputs:
+        image: frame
+  - id: view
+    node: viewer
+    inputs:
+      rgb: cam/frame
+    adapter:
+      inputs:
+        image: rgb
+  - id: recorder
+    path: ./recorder.py
+    inputs:
+      frame:
+        source: cam/frame
+        queue_size: 1
+"#,
+    )
+    .unwrap();
+
+    let out = transpile_graph(home, &yaml_path).unwrap().yaml;
+    let nodes = out["nodes"].as_sequence().unwrap();
+    assert_eq!(nodes[0]["outputs"][0].as_str(), Some("image"));
+    assert!(nodes[0].get("adapter").is_none());
+    assert_eq!(nodes[1]["inputs"]["image"].as_str(), Some("cam/image"));
+    assert!(nodes[1]["inputs"].get("rgb").is_none());
+    assert_eq!(
+        nodes[2]["inputs"]["frame"]["source"].as_str(),
+        Some("cam/image")
+    );
+}
+
 #[test]
 fn transpile_graph_auto_injects_hidden_dm_bridge_for_v0_bindings() {
     let tmp = tempdir().unwrap();
@@ -378,9 +453,137 @@ nodes:
 }
 
 #[test]
-fn transpile_graph_leaves_unknown_node_path_unchanged() {
+fn check_yaml_reports_inline_config_outside_the_schema() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path();
+    setup_managed_node(home, "test-node", ".venv/bin/test-node");
+
+    let dir = node_dir(home, "test-node");
+    let mut meta: Node =
+        serde_json::from_str(&fs::read_to_string(dir.join("dm.json")).unwrap()).unwrap();
+    meta.config_schema = Some(serde_json::json!({
+        "rate": { "type": "integer", "env": "RATE" }
+    }));
+    fs::write(
+        dir.join("dm.json"),
+        serde_json::to_string_pretty(&meta).unwrap(),
+    )
+    .unwrap();
+
+    let diags = crate::dataflow::check_yaml(
+        home,
+        "nodes:\n  - id: n1\n    node: test-node\n    config:\n      rate: fast\n",
+    )
+    .unwrap();
+    assert_eq!(diags.len(), 1);
+    assert!(diags[0].contains("invalid config: /rate"), "{}", diags[0]);
+
+    let diags = crate::dataflow::check_yaml(
+        home,
+        "nodes:\n  - id: n1\n    node: test-node\n    config:\n      rate: 30\n",
+    )
+    .unwrap();
+    assert!(diags.is_empty(), "{:?}", diags);
+}
+
+#[test]
+fn transpile_refuses_a_saved_config_that_saving_now_rejects() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path();
+    setup_managed_node(home, "test-node", ".venv/bin/test-node");
+    // Saved before the schema was attached, so nothing checked it.
+    let config = serde_json::json!({ "device": "camera0" });
+    fs::write(
+        node_dir(home, "test-node").join("config.json"),
+        config.to_string(),
+    )
+    .unwrap();
+    crate::node::set_config_schema(
+        home,
+        "test-node",
+        serde_json::json!({
+            "device": { "type": "string", "pattern": "^/dev/video[0-9]+$", "env": "DEVICE" }
+        }),
+    )
+    .unwrap();
+
+    let err = crate::node::save_node_config(home, "test-node", &config).unwrap_err();
+    assert!(err.downcast_ref::<crate::node::InvalidConfig>().is_some());
+
+    let yaml_path = home.join("graph.yml");
+    fs::write(&yaml_path, "nodes:\n  - id: n1\n    node: test-node\n").unwrap();
+    let err = transpile_graph(home, &yaml_path).unwrap_err();
+    let invalid = err.downcast_ref::<TranspileError>().unwrap();
+    assert_eq!(invalid.diagnostics.len(), 1);
+    assert!(matches!(
+        invalid.diagnostics[0].kind,
+        DiagnosticKind::InvalidConfig { .. }
+    ));
+    assert!(invalid.diagnostics[0].to_string().contains("/device"));
+}
+
+#[test]
+fn preview_node_env_matches_transpile_injection() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path();
+    setup_managed_node(home, "test-node", ".venv/bin/test-node");
+
+    let dir = node_dir(home, "test-node");
+    fs::write(dir.join(".venv/pyvenv.cfg"), "home = /usr/bin\n").unwrap();
+    let mut meta: Node =
+        serde_json::from_str(&fs::read_to_string(dir.join("dm.json")).unwrap()).unwrap();
+    meta.config_schema = Some(serde_json::json!({
+        "label": {
+            "default": "Hello",
+            "env": "LABEL"
+        }
+    }));
+    fs::write(
+        dir.join("dm.json"),
+        serde_json::to_string_pretty(&meta).unwrap(),
+    )
+    .unwrap();
+
+    let preview = preview_node_env(home, "test-node").unwrap().unwrap();
+    assert_eq!(
+        preview.executable.as_deref(),
+        Some(dir.join(".venv/bin/test-node").to_string_lossy().as_ref())
+    );
+    assert_eq!(
+        preview.venv.as_deref(),
+        Some(dir.join(".venv").to_string_lossy().as_ref())
+    );
+    assert_eq!(
+        preview.node_env.get("LABEL").map(String::as_str),
+        Some("Hello")
+    );
+    assert_eq!(
+        preview.node_env.get("DM_NODE_ID").map(String::as_str),
+        Some("test-node")
+    );
+    assert_eq!(
+        preview.node_env.get("DM_RUN_ID").map(String::as_str),
+        Some(PREVIEW_RUN_ID)
+    );
+    assert!(preview.warnings.is_empty());
+
+    assert!(preview_node_env(home, "missing-node").unwrap().is_none());
+}
+
+#[test]
+fn transpile_graph_rejects_unknown_and_uninstalled_nodes() {
     let tmp = tempdir().unwrap();
     let home = tmp.path();
+    setup_managed_node(home, "downloaded", "bin/downloaded");
+    let dir = node_dir(home, "downloaded");
+    let mut meta: Node =
+        serde_json::from_str(&fs::read_to_string(dir.join("dm.json")).unwrap()).unwrap();
+    meta.executable.clear();
+    fs::write(
+        dir.join("dm.json"),
+        serde_json::to_string_pretty(&meta).unwrap(),
+    )
+    .unwrap();
     let yaml_path = home.join("graph.yml");
 
     fs::write(
@@ -389,15 +592,181 @@ fn transpile_graph_leaves_unknown_node_path_unchanged() {
 nodes:
   - id: n1
     node: unknown-node
+  - id: n2
+    node: downloaded
 "#,
     )
     .unwrap();
 
-    let out = transpile_graph(home, &yaml_path).unwrap().yaml;
-    // Unknown node: `node:` stays as-is (no path resolution)
-    assert_eq!(out["nodes"][0]["node"].as_str(), Some("unknown-node"));
-    assert!(out["nodes"][0]["path"].is_null());
-    assert!(out["nodes"][0]["custom"].is_null());
+    let err = transpile_graph(home, &yaml_path).unwrap_err();
+    let invalid = err.downcast_ref::<TranspileError>().unwrap();
+    assert_eq!(invalid.diagnostics.len(), 2);
+    assert!(matches!(
+        invalid.diagnostics[0].kind,
+        DiagnosticKind::NodeNotInstalled
+    ));
+    assert_eq!(invalid.diagnostics[1].yaml_id, "n2");
+    assert!(matches!(
+        invalid.diagnostics[1].kind,
+        DiagnosticKind::MissingExecutable
+    ));
+    let text = err.to_string();
+    assert!(text.contains("2 problem(s)"), "{}", text);
+    assert!(text.contains("dm node install downloaded"), "{}", text);
+}
+
+#[test]
+fn transpile_graph_rejects_inputs_from_missing_nodes_and_ports() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path();
+    setup_managed_node(home, "camera", ".venv/bin/camera");
+    setup_managed_node(home, "viewer", ".venv/bin/viewer");
+    set_node_ports(home, "camera", &[("image", NodePortDirection::Output)]);
+    set_node_ports(home, "viewer", &[("image", NodePortDirection::Input)]);
+
+    let yaml_path = home.join("graph.yml");
+    fs::write(
+        &yaml_path,
+        r#"
+nodes:
+  - id: cam
+    node: camera
+    inputs:
+      tick: dora/timer/millis/20
+  - id: view
+    node: viewer
+    inputs:
+      image: cam/depth
+      depth:
+        source: lidar/points
+"#,
+    )
+    .unwrap();
+
+    let err = transpile_graph(home, &yaml_path).unwrap_err();
+    let invalid = err.downcast_ref::<TranspileError>().unwrap();
+    let found: Vec<_> = invalid
+        .diagnostics
+        .iter()
+        .map(|d| match &d.kind {
+            DiagnosticKind::UndeclaredPort { port_id, direction } => {
+                format!("{} {} {}", d.yaml_id, direction, port_id)
+            }
+            DiagnosticKind::UnknownInputSource { input_port, source } => {
+                format!("{} {} <- {}", d.yaml_id, input_port, source)
+            }
+            other => panic!("unexpected diagnostic {:?}", other),
+        })
+        .collect();
+    // `cam` declares no inputs, so its timer input is not checked.
+    assert_eq!(
+        found,
+        vec![
+            "cam output depth",
+            "view input depth",
+            "view depth <- lidar/points",
+        ]
+    );
+
+    // The same graph wired to declared ports transpiles.
+    fs::write(
+        &yaml_path,
+        "nodes:\n  - id: cam\n    node: camera\n  - id: view\n    node: viewer\n    inputs:\n      image: cam/image\n",
+    )
+    .unwrap();
+    assert!(transpile_graph(home, &yaml_path).is_ok());
+}
+
+/// Install the nodes referenced by `tests/fixtures/transpile`.
+fn setup_golden_fixture_nodes(home: &std::path::Path) {
+    setup_managed_node(home, "fixture-detector", ".venv/bin/fixture-detector");
+    setup_managed_node(home, "fixture-queue", "bin/fixture-queue");
+    setup_managed_node(home, "fixture-vad", ".venv/bin/fixture-vad");
+
+    let dir = node_dir(home, "fixture-vad");
+    let mut meta: Node =
+        serde_json::from_str(&fs::read_to_string(dir.join("dm.json")).unwrap()).unwrap();
+    meta.config_schema = Some(serde_json::json!({
+        "model": { "default": "silero", "env": "VAD_MODEL" },
+        "threshold": { "default": 0.5, "env": "VAD_THRESHOLD" },
+        "window": { "default": null, "env": "VAD_WINDOW" }
+    }));
+    fs::write(
+        dir.join("dm.json"),
+        serde_json::to_string_pretty(&meta).unwrap(),
+    )
+    .unwrap();
+    fs::write(dir.join("config.json"), r#"{"model":"silero-v5"}"#).unwrap();
+}
+
+/// Golden transpile outputs; regenerate with `DM_UPDATE_GOLDEN=1`.
+#[test]
+#[cfg(not(target_os = "windows"))]
+fn transpile_output_matches_golden_fixtures() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path();
+    setup_golden_fixture_nodes(home);
+
+    let fixtures =
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/transpile");
+    let mut graphs: Vec<_> = fs::read_dir(&fixtures)
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_string_lossy();
+            name.ends_with(".yml") && !name.ends_with(".golden.yml")
+        })
+        .collect();
+    graphs.sort();
+    assert!(graphs.len() >= 5, "missing transpile fixtures");
+
+    let update = std::env::var("DM_UPDATE_GOLDEN").is_ok_and(|v| v == "1");
+    let mut failures = Vec::new();
+    for graph in &graphs {
+        let golden = graph.with_extension("golden.yml");
+        if update {
+            fs::write(&golden, render_golden(home, graph).unwrap()).unwrap();
+            continue;
+        }
+        let check = check_golden(home, graph, &golden).unwrap();
+        if !check.matches() {
+            failures.push(format!("{}:\n{}", golden.display(), check.diff.join("\n")));
+        }
+    }
+    assert!(
+        failures.is_empty(),
+        "transpile output changed (rerun with DM_UPDATE_GOLDEN=1 if intended):\n{}",
+        failures.join("\n\n")
+    );
+}
+
+#[test]
+fn golden_rendering_hides_home_and_run_id() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path();
+    setup_managed_node(home, "test-node", "bin/test-node");
+    let yaml_path = home.join("graph.yml");
+    fs::write(&yaml_path, "nodes:\n  - id: n1\n    node: test-node\n").unwrap();
+
+    let rendered = render_golden(home, &yaml_path).unwrap();
+    assert!(!rendered.contains(&home.display().to_string()));
+    assert!(rendered.contains("$DM_HOME/runs/golden-run/out"));
+
+    let golden = home.join("graph.golden.yml");
+    fs::write(
+        &golden,
+        rendered.replace("DM_NODE_ID: n1", "DM_NODE_ID: n2"),
+    )
+    .unwrap();
+    let check = check_golden(home, &yaml_path, &golden).unwrap();
+    assert!(!check.matches());
+    assert_eq!(check.diff.len(), 2);
+    assert!(
+        check.diff[1].ends_with("+     DM_NODE_ID: n1"),
+        "{:?}",
+        check.diff
+    );
 }
 
 #[test]
@@ -421,6 +790,88 @@ fn transpile_graph_errors_on_missing_file() {
     assert!(err.contains("Failed to read graph yaml"));
 }
 
+fn vars(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+const TEMPLATE: &str = r#"
+variables:
+  camera: /dev/video0
+  fps: 30
+  model: null
+nodes:
+  - id: cam
+    path: opencv-video-capture
+    env:
+      CAPTURE_PATH: ${camera}
+      FPS: ${fps}
+      LABEL: cam-${fps}fps
+  - id: detect
+    path: ${model}
+"#;
+
+#[test]
+fn render_substitutes_defaults_and_overrides() {
+    let rendered = render(TEMPLATE, &vars(&[("model", "yolov8n.pt"), ("fps", "15")])).unwrap();
+    let graph: serde_yaml::Value = serde_yaml::from_str(&rendered).unwrap();
+
+    assert!(graph.get("variables").is_none());
+    let env = &graph["nodes"][0]["env"];
+    assert_eq!(env["CAPTURE_PATH"], "/dev/video0");
+    assert_eq!(env["FPS"], serde_yaml::Value::from(15));
+    assert_eq!(env["LABEL"], "cam-15fps");
+    assert_eq!(graph["nodes"][1]["path"], "yolov8n.pt");
+    // Already rendered: a second pass changes nothing.
+    assert_eq!(render(&rendered, &BTreeMap::new()).unwrap(), rendered);
+}
+
+#[test]
+fn render_rejects_undefined_and_unknown_variables() {
+    let err = format!("{:#}", render(TEMPLATE, &BTreeMap::new()).unwrap_err());
+    assert!(err.contains("Undefined variable(s): model"), "{err}");
+
+    let yaml = "variables:\n  fps: 30\nnodes:\n  - id: a\n    path: ${camera}\n";
+    let err = format!("{:#}", render(yaml, &BTreeMap::new()).unwrap_err());
+    assert!(err.contains("Undefined variable(s): camera"), "{err}");
+
+    let err = render(TEMPLATE, &vars(&[("model", "m"), ("gpu", "0")])).unwrap_err();
+    assert!(err.to_string().contains("Unknown variable 'gpu'"));
+}
+
+#[test]
+fn render_leaves_graphs_without_variables_untouched() {
+    let yaml = "nodes:\n  - id: a\n    path: ./a.py\n    env:\n      HOME_DIR: ${HOME}\n";
+    assert_eq!(render(yaml, &BTreeMap::new()).unwrap(), yaml);
+
+    let err = render(yaml, &vars(&[("camera", "/dev/video1")])).unwrap_err();
+    assert!(err.to_string().contains("declares no `variables:`"));
+}
+
+#[test]
+fn transpile_graph_with_variables_renders_before_transpiling() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path();
+    let yaml_path = home.join("flow.yml");
+    fs::write(
+        &yaml_path,
+        "variables:\n  script: null\nnodes:\n  - id: a\n    path: ${script}\n",
+    )
+    .unwrap();
+
+    let err = transpile_graph(home, &yaml_path).unwrap_err();
+    assert!(format!("{:#}", err).contains("Undefined variable(s): script"));
+
+    let result =
+        transpile_graph_with_variables(home, &yaml_path, "run-1", &vars(&[("script", "./a.py")]))
+            .unwrap();
+    let yaml = serde_yaml::to_string(&result.yaml).unwrap();
+    assert!(yaml.contains("./a.py"));
+    assert!(!yaml.contains("variables"));
+}
+
 #[test]
 fn test_dataflow_crud() {
     let tmp = tempdir().unwrap();
@@ -457,10 +908,12 @@ fn test_dataflow_crud() {
     assert!(list3.is_empty());
 
     // 7. Get should fail
-    let err = crate::dataflow::get(home, "my_flow")
-        .unwrap_err()
-        .to_string();
-    assert!(err.contains("Failed to read dataflow"));
+    let err = crate::dataflow::get(home, "my_flow").unwrap_err();
+    assert_eq!(err.to_string(), "Dataflow 'my_flow' not found");
+    assert_eq!(
+        crate::error::error_code(&err),
+        Some(crate::error::ErrorCode::NotFound)
+    );
 }
 
 #[test]
@@ -481,6 +934,44 @@ fn test_dataflow_save_creates_history_snapshot() {
     assert_eq!(snapshot, "nodes: []\n");
 }
 
+#[test]
+fn test_dataflow_history_keeps_rapid_saves_and_diffs_versions() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path();
+
+    for yaml in [
+        "nodes: []\n",
+        "nodes:\n  - id: a\n",
+        "nodes:\n  - id: b\n",
+        "nodes: []\n",
+    ] {
+        crate::dataflow::save(home, "rapid", yaml).unwrap();
+    }
+
+    let history = crate::dataflow::list_history(home, "rapid").unwrap();
+    assert_eq!(history.len(), 3);
+    let (newest, oldest) = (&history[0].version, &history[2].version);
+    assert_eq!(
+        crate::dataflow::get_history_version(home, "rapid", newest).unwrap(),
+        "nodes:\n  - id: b\n"
+    );
+
+    let against_current =
+        crate::dataflow::diff_history_version(home, "rapid", oldest, None).unwrap();
+    assert!(against_current.diff.is_empty());
+
+    let between =
+        crate::dataflow::diff_history_version(home, "rapid", oldest, Some(newest)).unwrap();
+    assert_eq!((between.added, between.removed), (2, 1));
+    assert!(between.diff.contains("+  - id: b\n"));
+
+    let err = crate::dataflow::get_history_version(home, "rapid", "../flow").unwrap_err();
+    assert_eq!(
+        crate::error::error_code(&err),
+        Some(crate::error::ErrorCode::NotFound)
+    );
+}
+
 #[test]
 fn test_migrate_legacy_dataflow_layout() {
     let tmp = tempdir().unwrap();
@@ -498,6 +989,82 @@ fn test_migrate_legacy_dataflow_layout() {
     assert!(home.join("dataflows/demo/flow.json").exists());
 }
 
+fn git(dir: &std::path::Path, args: &[&str]) -> String {
+    let output = std::process::Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {:?}: {:?}", args, output);
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn dataflows_dir_can_point_outside_home() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path().join("home");
+    let shared = tmp.path().join("shared");
+    let cfg = crate::config::DmConfig {
+        dataflows_dir: Some(shared.display().to_string()),
+        ..Default::default()
+    };
+    crate::config::save_config(&home, &cfg).unwrap();
+
+    crate::dataflow::save(&home, "demo", "nodes: []\n").unwrap();
+    assert!(shared.join("demo/dataflow.yml").exists());
+    assert!(!home.join("dataflows").exists());
+    assert_eq!(crate::dataflow::list(&home).unwrap().len(), 1);
+}
+
+#[test]
+fn dataflow_sync_commits_and_pushes_to_remote() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path().join("home");
+    let remote = tmp.path().join("remote.git");
+    let checkout = tmp.path().join("checkout");
+    fs::create_dir_all(&remote).unwrap();
+    git(&remote, &["init", "--quiet", "--bare"]);
+    git(
+        tmp.path(),
+        &["clone", "--quiet", remote.to_str().unwrap(), "checkout"],
+    );
+    git(&checkout, &["config", "user.name", "dm test"]);
+    git(&checkout, &["config", "user.email", "dm@example.invalid"]);
+    git(&checkout, &["config", "commit.gpgsign", "false"]);
+
+    let cfg = crate::config::DmConfig {
+        dataflows_dir: Some(checkout.join("flows").display().to_string()),
+        ..Default::default()
+    };
+    crate::config::save_config(&home, &cfg).unwrap();
+    crate::dataflow::save(&home, "demo", "nodes: []\n").unwrap();
+
+    let report = crate::dataflow::sync(&home, Some("Add demo"), true).unwrap();
+    assert!(report
+        .committed
+        .contains(&"flows/demo/dataflow.yml".to_string()));
+    assert!(report.pushed);
+    assert!(!report.pulled);
+    let log = git(&remote, &["log", "--oneline", "--all"]);
+    assert!(log.contains("Add demo"), "{log}");
+
+    let report = crate::dataflow::sync(&home, None, true).unwrap();
+    assert!(report.committed.is_empty());
+    assert!(report.pulled);
+}
+
+#[test]
+fn dataflow_sync_requires_git_checkout() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path();
+    crate::dataflow::save(home, "demo", "nodes: []\n").unwrap();
+
+    let err = crate::dataflow::sync(home, None, true)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("not inside a git repository"), "{err}");
+}
+
 #[test]
 fn test_import_dataflow_from_local_yaml_file() {
     let tmp = tempdir().unwrap();
@@ -579,6 +1146,8 @@ fn test_inspect_config_aggregates_schema_and_effective_values() {
             }
         })),
         dynamic_ports: false,
+        pinned: None,
+        last_test: None,
         path: Default::default(),
     };
     std::fs::write(
@@ -633,3 +1202,363 @@ nodes:
         Some(serde_json::json!(0.5))
     );
 }
+
+fn mark_python_node(home: &std::path::Path, id: &str, pyproject: Option<&str>) {
+    let dir = node_dir(home, id);
+    let mut meta: Node =
+        serde_json::from_str(&fs::read_to_string(dir.join("dm.json")).unwrap()).unwrap();
+    meta.runtime.language = "python".to_string();
+    meta.runtime.python = Some(">=3.10".to_string());
+    fs::write(
+        dir.join("dm.json"),
+        serde_json::to_string_pretty(&meta).unwrap(),
+    )
+    .unwrap();
+    if let Some(pyproject) = pyproject {
+        fs::write(dir.join("pyproject.toml"), pyproject).unwrap();
+    }
+}
+
+#[test]
+#[cfg(not(target_os = "windows"))]
+fn prepare_local_run_with_uv_consolidates_python_nodes() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path();
+    setup_managed_node(home, "hub-node", ".venv/bin/dora-hub-node");
+    mark_python_node(home, "hub-node", None);
+    setup_managed_node(home, "local-node", ".venv/bin/local-node");
+    mark_python_node(
+        home,
+        "local-node",
+        Some("[project]\nname = \"local-node\"\nversion = \"0.1.0\"\n"),
+    );
+    setup_managed_node(home, "rust-node", "bin/rust-node");
+
+    let yaml_path = home.join("demo.yml");
+    fs::write(
+        &yaml_path,
+        r#"
+nodes:
+  - id: a
+    node: hub-node
+  - id: b
+    node: local-node
+  - id: c
+    node: rust-node
+"#,
+    )
+    .unwrap();
+
+    let plan = prepare_local_run(home, &yaml_path, true, &BTreeMap::new()).unwrap();
+
+    assert_eq!(plan.packages, vec!["dora-test-node", "local-node"]);
+    let pyproject: toml::Value =
+        toml::from_str(&fs::read_to_string(plan.dir.join("pyproject.toml")).unwrap()).unwrap();
+    assert_eq!(
+        pyproject["project"]["requires-python"].as_str(),
+        Some(">=3.10")
+    );
+    assert_eq!(pyproject["tool"]["uv"]["package"].as_bool(), Some(false));
+    assert_eq!(
+        pyproject["tool"]["uv"]["sources"]["local-node"]["path"].as_str(),
+        Some(node_dir(home, "local-node").to_str().unwrap())
+    );
+
+    let out: serde_yaml::Value =
+        serde_yaml::from_str(&fs::read_to_string(&plan.dataflow).unwrap()).unwrap();
+    let venv_bin = plan.dir.join(".venv/bin");
+    assert_eq!(
+        out["nodes"][0]["path"].as_str(),
+        Some(venv_bin.join("dora-hub-node").to_str().unwrap())
+    );
+    assert_eq!(
+        out["nodes"][1]["path"].as_str(),
+        Some(venv_bin.join("local-node").to_str().unwrap())
+    );
+    assert_eq!(
+        out["nodes"][2]["path"].as_str(),
+        Some(
+            node_dir(home, "rust-node")
+                .join("bin/rust-node")
+                .to_str()
+                .unwrap()
+        )
+    );
+}
+
+#[test]
+fn prepare_local_run_with_uv_requires_python_nodes() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path();
+    setup_managed_node(home, "rust-node", "bin/rust-node");
+    let yaml_path = home.join("demo.yml");
+    fs::write(&yaml_path, "nodes:\n  - id: c\n    node: rust-node\n").unwrap();
+
+    let err = prepare_local_run(home, &yaml_path, true, &BTreeMap::new()).unwrap_err();
+    assert!(err.to_string().contains("without --uv"));
+    assert!(!home.join("local-runs").exists());
+    assert!(prepare_local_run(home, &yaml_path, false, &BTreeMap::new()).is_ok());
+}
+
+#[test]
+#[cfg(not(target_os = "windows"))]
+fn prepare_local_run_with_uv_accepts_python_nodes_that_are_not_installed() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path();
+    setup_managed_node(home, "py-node", "unused");
+    mark_python_node(home, "py-node", None);
+    let dm_json = node_dir(home, "py-node").join("dm.json");
+    let mut meta: Node = serde_json::from_str(&fs::read_to_string(&dm_json).unwrap()).unwrap();
+    meta.executable = String::new();
+    fs::write(&dm_json, serde_json::to_string_pretty(&meta).unwrap()).unwrap();
+    let yaml_path = home.join("demo.yml");
+    fs::write(&yaml_path, "nodes:\n  - id: a\n    node: py-node\n").unwrap();
+
+    let err = prepare_local_run(home, &yaml_path, false, &BTreeMap::new()).unwrap_err();
+    assert!(format!("{:#}", err).contains("empty executable"));
+
+    let plan = prepare_local_run(home, &yaml_path, true, &BTreeMap::new()).unwrap();
+    assert_eq!(plan.packages, vec!["dora-test-node"]);
+    let out: serde_yaml::Value =
+        serde_yaml::from_str(&fs::read_to_string(&plan.dataflow).unwrap()).unwrap();
+    assert_eq!(
+        out["nodes"][0]["path"].as_str(),
+        Some(plan.dir.join(".venv/bin/dora-test-node").to_str().unwrap())
+    );
+}
+
+#[test]
+fn prepare_local_run_gives_each_invocation_its_own_artifacts() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path();
+    setup_managed_node(home, "rust-node", "bin/rust-node");
+    let yaml_path = home.join("demo.yml");
+    let yaml = "nodes:\n  - id: c\n    node: rust-node\n";
+    fs::write(&yaml_path, yaml).unwrap();
+
+    let first = prepare_local_run(home, &yaml_path, false, &BTreeMap::new()).unwrap();
+    let second = prepare_local_run(home, &yaml_path, false, &BTreeMap::new()).unwrap();
+
+    assert_eq!(first.dir, second.dir);
+    assert_ne!(first.run_dir, second.run_dir);
+    for plan in [&first, &second] {
+        assert!(plan.run_dir.starts_with(&plan.dir));
+        assert!(plan.dataflow.starts_with(&plan.run_dir));
+        assert_eq!(
+            fs::read_to_string(plan.run_dir.join("dataflow.yml")).unwrap(),
+            yaml
+        );
+        assert!(fs::read_to_string(&plan.dataflow)
+            .unwrap()
+            .contains("bin/rust-node"));
+    }
+}
+
+#[test]
+fn validate_graph_reports_structural_errors_and_cycles() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path();
+    setup_managed_node(home, "test-node", ".venv/bin/test-node");
+
+    let report = crate::dataflow::validate_graph(
+        home,
+        r#"
+nodes:
+  - id: a
+    node: test-node
+    inputs:
+      tick: dora/timer/millis/10
+      back: b/out
+    outputs: [out]
+  - id: b
+    node: test-node
+    inputs:
+      in: a/out
+      extra: a/missing
+      lost: ghost/out
+    outputs: [out]
+  - id: b
+    node: nowhere-node
+"#,
+    );
+
+    assert!(!report.valid);
+    let errors: Vec<_> = report
+        .errors
+        .iter()
+        .map(|issue| {
+            format!(
+                "{}: {}",
+                issue.node.as_deref().unwrap_or("-"),
+                issue.message
+            )
+        })
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            "b: duplicate node id 'b'",
+            "b: node 'nowhere-node' is not installed and not in the registry",
+            "b: input 'extra' reads 'a/missing', but 'a' does not list 'missing' in its outputs",
+            "b: input 'lost' reads from 'ghost/out', which is not a node in this graph",
+        ]
+    );
+    assert_eq!(
+        report.warnings,
+        vec![crate::dataflow::GraphIssue {
+            node: Some("a".to_string()),
+            message: "cycle: a → b → a".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn validate_graph_accepts_editor_json() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path();
+    setup_managed_node(home, "test-node", ".venv/bin/test-node");
+
+    let editor = serde_json::json!({
+        "nodes": [
+            {
+                "id": "__virtual_dora_timer_millis_100",
+                "type": "dmNode",
+                "position": { "x": 0, "y": 0 },
+                "data": {
+                    "label": "Timer 100ms",
+                    "nodeType": "dora/timer/millis/100",
+                    "inputs": [],
+                    "outputs": ["tick"],
+                    "isVirtual": true,
+                    "virtualKind": "timer"
+                }
+            },
+            {
+                "id": "src",
+                "data": { "nodeType": "test-node", "inputs": ["tick"], "outputs": ["out"] }
+            },
+            {
+                "id": "sink",
+                "data": { "nodeType": "test-node", "inputs": ["in"], "outputs": [] }
+            }
+        ],
+        "edges": [
+            {
+                "source": "__virtual_dora_timer_millis_100",
+                "target": "src",
+                "sourceHandle": "out-tick",
+                "targetHandle": "in-tick"
+            },
+            { "source": "src", "target": "sink", "sourceHandle": "out-out", "targetHandle": "in-in" }
+        ]
+    });
+    let report = crate::dataflow::validate_graph(home, &editor.to_string());
+    assert!(report.valid, "{:?}", report.errors);
+    assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+
+    let dataflow = crate::dataflow::parse_graph(&editor.to_string()).unwrap();
+    assert_eq!(
+        dataflow["nodes"][0]["inputs"]["tick"].as_str(),
+        Some("dora/timer/millis/100")
+    );
+    assert_eq!(
+        dataflow["nodes"][1]["inputs"]["in"].as_str(),
+        Some("src/out")
+    );
+    assert!(crate::dataflow::detect_cycles(&dataflow).is_empty());
+
+    let report = crate::dataflow::validate_graph(home, "nodes: [");
+    assert!(!report.valid);
+    assert!(report.errors[0]
+        .message
+        .starts_with("Failed to parse graph"));
+}
+
+#[test]
+fn dataflow_graph_round_trips_through_dora_yaml() {
+    let yaml = r#"
+communication:
+  _unstable_local: UnixDomain
+nodes:
+  - id: camera
+    path: opencv-video-capture
+    inputs:
+      tick: dora/timer/millis/20
+    outputs:
+      - image
+    env:
+      CAPTURE_PATH: 0
+  - id: detector
+    node: dora-yolo
+    config:
+      model: yolov8n
+    inputs:
+      image: camera/image
+      threshold: panel/threshold
+    outputs:
+      - bbox
+"#;
+    let graph = crate::dataflow::DataflowGraph::from_dora_yaml(yaml).unwrap();
+
+    let ids: Vec<_> = graph.nodes.iter().map(|node| node.id.as_str()).collect();
+    assert_eq!(
+        ids,
+        vec![
+            "camera",
+            "detector",
+            "__virtual_dora_timer_millis_20",
+            "__virtual_panel"
+        ]
+    );
+    assert_eq!(graph.nodes[2].data.label, "Timer 20ms");
+    assert_eq!(graph.nodes[3].data.outputs, vec!["threshold"]);
+    assert_eq!(graph.nodes[1].data.inputs, vec!["image", "threshold"]);
+    assert_eq!(graph.edges.len(), 3);
+    assert_eq!(graph.edges[1].id, "e-camera-image-detector-image");
+    assert_eq!(graph.edges[1].source_handle.as_deref(), Some("out-image"));
+
+    let back: serde_yaml::Value = serde_yaml::from_str(&graph.to_dora_yaml().unwrap()).unwrap();
+    let original: serde_yaml::Value = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(back["communication"], original["communication"]);
+    for index in 0..2 {
+        let (back, original) = (&back["nodes"][index], &original["nodes"][index]);
+        for key in ["id", "path", "node", "inputs", "outputs", "env", "config"] {
+            assert_eq!(back[key], original[key], "nodes[{}].{}", index, key);
+        }
+    }
+
+    let json = crate::dataflow::convert_graph(yaml, crate::dataflow::GraphFormat::Json).unwrap();
+    let parsed: crate::dataflow::DataflowGraph = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, graph);
+    let yaml_again =
+        crate::dataflow::convert_graph(&json, crate::dataflow::GraphFormat::Yaml).unwrap();
+    assert_eq!(yaml_again, graph.to_dora_yaml().unwrap());
+    assert!("toml".parse::<crate::dataflow::GraphFormat>().is_err());
+}
+
+#[test]
+fn validate_all_reports_missing_nodes_and_runtime() {
+    let tmp = tempdir().unwrap();
+    let home = tmp.path();
+    setup_managed_node(home, "test-node", ".venv/bin/test-node");
+    crate::dataflow::save(home, "good", "nodes:\n  - id: n1\n    node: test-node\n").unwrap();
+    crate::dataflow::save(home, "stale", "nodes:\n  - id: n1\n    node: gone-node\n").unwrap();
+
+    let report = crate::dataflow::validate_all(home).unwrap();
+
+    assert!(report.runtime_problem.is_some());
+    assert_eq!(report.failing, 1);
+    let good = report.dataflows.iter().find(|f| f.name == "good").unwrap();
+    assert!(good.ok, "{:?}", good.problems);
+    let stale = report.dataflows.iter().find(|f| f.name == "stale").unwrap();
+    assert_eq!(stale.problems, vec!["node 'gone-node' is not installed"]);
+
+    let events = crate::events::EventStore::open(home)
+        .unwrap()
+        .query(&crate::events::EventFilter {
+            activity: Some("dataflow.invalid".into()),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(events.len(), 1);
+}
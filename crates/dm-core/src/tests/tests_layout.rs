@@ -0,0 +1,72 @@
+//! Guards against the module layout drifting apart again: each path helper
+//! and public entry point must agree on where state lives.
+
+use tempfile::TempDir;
+
+use crate::events::{EventFilter, EventStore};
+
+#[test]
+fn all_state_paths_are_rooted_at_dm_home() {
+    let tmp = TempDir::new().unwrap();
+    let home = tmp.path();
+
+    let paths = [
+        crate::config::config_path(home),
+        crate::config::versions_dir(home),
+        crate::node::node_dir(home, "n"),
+        crate::node::dm_json_path(home, "n"),
+        crate::runs::runs_dir(home),
+        crate::runs::run_dir(home, "r"),
+    ];
+    for path in paths {
+        assert!(path.starts_with(home), "{} escapes dm home", path.display());
+    }
+    assert_eq!(
+        crate::node::node_dir(home, "n"),
+        home.join("nodes").join("n")
+    );
+}
+
+#[test]
+fn node_entry_points_read_the_same_metadata() {
+    let tmp = TempDir::new().unwrap();
+    let home = tmp.path();
+    crate::node::create_node(home, "parity-node", "parity").unwrap();
+
+    let listed = crate::node::list_nodes(home)
+        .unwrap()
+        .into_iter()
+        .find(|node| node.id == "parity-node")
+        .unwrap();
+    let status = crate::node::node_status(home, "parity-node")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        crate::node::resolve_dm_json_path(home, "parity-node"),
+        Some(crate::node::dm_json_path(home, "parity-node"))
+    );
+    assert_eq!(listed.path, status.path);
+    assert_eq!(listed.description, status.description);
+    assert_eq!(
+        serde_json::to_value(&listed).unwrap(),
+        serde_json::to_value(&status).unwrap()
+    );
+}
+
+#[test]
+fn operations_log_to_the_single_event_store() {
+    let tmp = TempDir::new().unwrap();
+    let home = tmp.path();
+    crate::node::create_node(home, "evt-node", "events").unwrap();
+
+    let events = EventStore::open(home)
+        .unwrap()
+        .query(&EventFilter {
+            activity: Some("node.create".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+    assert!(!events.is_empty());
+    assert!(home.join("events.db").exists());
+}
@@ -1,9 +1,10 @@
 //! Tests for the node module
 
 use crate::node::{
-    create_node, dm_json_path, get_node_config, get_node_readme, git_like_file_tree, install_node,
-    list_nodes, node_dir, node_status, read_node_file, save_node_config, uninstall_node, Node,
-    NodeDisplay, NodeFiles, NodeRuntime, NodeSource,
+    create_node, create_node_with, dm_json_path, get_node_config, get_node_readme,
+    git_like_file_tree, install_node, list_nodes, node_dir, node_status, read_node_file,
+    save_node_config, uninstall_node, Node, NodeDisplay, NodeFiles, NodeRuntime, NodeScaffold,
+    NodeSource,
 };
 use tempfile::tempdir;
 
@@ -79,6 +80,8 @@ fn test_node_struct() {
         examples: Vec::new(),
         config_schema: None,
         dynamic_ports: false,
+        pinned: None,
+        last_test: None,
         path: std::path::PathBuf::from("/test/path"),
     };
 
@@ -151,6 +154,37 @@ fn test_create_node_generates_scaffold() {
     assert!(err.to_string().contains("already exists"));
 }
 
+#[test]
+fn test_create_node_substitutes_scaffold_metadata() {
+    let dir = tempdir().unwrap();
+    let home = dir.path();
+
+    let scaffold = NodeScaffold {
+        description: "Says \"hi\"".to_string(),
+        author: Some("Jane Doe <jane@example.com>".to_string()),
+        license: Some("Apache-2.0".to_string()),
+        python: Some("3.11".to_string()),
+        category: Some("Audio".to_string()),
+    };
+    let node = create_node_with(home, "meta-node", &scaffold).unwrap();
+
+    assert_eq!(node.description, "Says \"hi\"");
+    assert_eq!(node.license.as_deref(), Some("Apache-2.0"));
+    assert_eq!(node.maintainers.len(), 1);
+    assert_eq!(node.maintainers[0].name, "Jane Doe");
+    assert_eq!(
+        node.maintainers[0].email.as_deref(),
+        Some("jane@example.com")
+    );
+    assert_eq!(node.runtime.python.as_deref(), Some(">=3.11"));
+    assert_eq!(node.display.category, "Audio");
+
+    let node_path = node_dir(home, "meta-node");
+    let readme = std::fs::read_to_string(node_path.join("README.md")).unwrap();
+    assert!(readme.contains("## License\n\nApache-2.0"));
+    assert!(readme.contains("Jane Doe"));
+}
+
 #[test]
 fn test_config_crud() {
     let dir = tempdir().unwrap();
@@ -169,6 +203,36 @@ fn test_config_crud() {
     assert_eq!(config["api_key"], "sk-123");
 }
 
+#[test]
+fn save_node_config_rejects_values_outside_the_schema() {
+    let dir = tempdir().unwrap();
+    let home = dir.path();
+
+    create_node(home, "typed-cfg", "test").unwrap();
+    crate::node::set_config_schema(
+        home,
+        "typed-cfg",
+        serde_json::json!({ "threshold": { "type": "number", "maximum": 1 } }),
+    )
+    .unwrap();
+
+    let err =
+        save_node_config(home, "typed-cfg", &serde_json::json!({ "threshold": 3 })).unwrap_err();
+    let invalid = err.downcast_ref::<crate::node::InvalidConfig>().unwrap();
+    assert_eq!(invalid.errors.len(), 1);
+    assert_eq!(invalid.errors[0].pointer, "/threshold");
+    assert_eq!(
+        get_node_config(home, "typed-cfg").unwrap(),
+        serde_json::json!({})
+    );
+
+    save_node_config(home, "typed-cfg", &serde_json::json!({ "threshold": 0.5 })).unwrap();
+    assert_eq!(
+        get_node_config(home, "typed-cfg").unwrap()["threshold"],
+        0.5
+    );
+}
+
 #[test]
 fn test_get_node_readme_returns_local_content() {
     let dir = tempdir().unwrap();
@@ -230,7 +294,14 @@ fn test_save_node_config_requires_existing_node() {
     let home = dir.path();
 
     let err = save_node_config(home, "missing-node", &serde_json::json!({ "a": 1 })).unwrap_err();
-    assert!(err.to_string().contains("does not exist"));
+    assert_eq!(err.to_string(), "Node 'missing-node' not found");
+    assert!(matches!(
+        err.downcast_ref::<crate::error::DmError>(),
+        Some(crate::error::DmError::NotFound {
+            resource: "node",
+            ..
+        })
+    ));
 }
 
 #[tokio::test]
@@ -272,6 +343,8 @@ async fn test_install_node_errors_for_unsupported_build() {
         examples: Vec::new(),
         config_schema: None,
         dynamic_ports: false,
+        pinned: None,
+        last_test: None,
         path: Default::default(),
     };
     std::fs::write(
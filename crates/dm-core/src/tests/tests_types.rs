@@ -64,15 +64,32 @@ fn doctor_report_serialization() {
             InstalledVersion {
                 version: "0.4.1".into(),
                 active: true,
+                manifest: None,
             },
             InstalledVersion {
                 version: "0.3.9".into(),
                 active: false,
+                manifest: None,
             },
         ],
         active_version: Some("0.4.1".into()),
         active_binary_ok: true,
         all_ok: false,
+        recent_problems: Vec::new(),
+        threshold_alerts: Vec::new(),
+        permission_issues: Vec::new(),
+        node_probes: Vec::new(),
+        sdk_mismatches: Vec::new(),
+        nodes: vec![NodeHealth {
+            node_id: "dora-yolo".into(),
+            kind: "python".into(),
+            ok: false,
+            checks: vec![NodeCheck {
+                name: "venv".into(),
+                ok: false,
+                detail: ".venv missing".into(),
+            }],
+        }],
     };
     let json = serde_json::to_string_pretty(&report).unwrap();
     let parsed: DoctorReport = serde_json::from_str(&json).unwrap();
@@ -82,6 +99,7 @@ fn doctor_report_serialization() {
     assert_eq!(parsed.active_version, Some("0.4.1".into()));
     assert!(parsed.active_binary_ok);
     assert!(!parsed.all_ok); // uv not found
+    assert_eq!(parsed.nodes, report.nodes);
 }
 
 #[test]
@@ -90,17 +108,21 @@ fn versions_report_serialization() {
         installed: vec![InstalledVersion {
             version: "0.4.1".into(),
             active: true,
+            manifest: None,
         }],
         available: vec![
             AvailableVersion {
                 tag: "0.4.1".into(),
                 installed: true,
+                published_at: Some("2025-02-01T00:00:00Z".into()),
             },
             AvailableVersion {
                 tag: "0.4.0".into(),
                 installed: false,
+                published_at: None,
             },
         ],
+        more_available: true,
     };
     let json = serde_json::to_string(&report).unwrap();
     let parsed: VersionsReport = serde_json::from_str(&json).unwrap();
@@ -108,6 +130,11 @@ fn versions_report_serialization() {
     assert_eq!(parsed.available.len(), 2);
     assert!(parsed.available[0].installed);
     assert!(!parsed.available[1].installed);
+    assert_eq!(
+        parsed.available[0].published_at.as_deref(),
+        Some("2025-02-01T00:00:00Z")
+    );
+    assert!(parsed.more_available);
 }
 
 #[test]
@@ -143,6 +170,8 @@ fn install_result_serialization() {
         version: "0.4.1".into(),
         method: InstallMethod::Binary,
         set_active: true,
+        verified: true,
+        actual_version: None,
     };
     let json = serde_json::to_string(&result).unwrap();
     let parsed: InstallResult = serde_json::from_str(&json).unwrap();
@@ -160,6 +189,8 @@ fn install_result_source_method() {
         version: "0.3.9".into(),
         method: InstallMethod::Source,
         set_active: false,
+        verified: true,
+        actual_version: None,
     };
     let json = serde_json::to_string(&result).unwrap();
     let parsed: InstallResult = serde_json::from_str(&json).unwrap();
@@ -187,6 +218,8 @@ fn status_report_serialization() {
         active_version: Some("0.4.1".into()),
         actual_version: Some("0.4.1".into()),
         dm_home: "/home/user/.dm".into(),
+        robot_id: Some("rover-7".into()),
+        labels: Default::default(),
         runtime_running: false,
         runtime_output: String::new(),
         active_runs: vec![StatusRunEntry {
@@ -199,6 +232,8 @@ fn status_report_serialization() {
             observed_nodes: 2,
             dora_uuid: Some("019cc181-adad-7654-aa78-63502362337b".into()),
             outcome_summary: "Running".into(),
+            source: "web".into(),
+            uptime_secs: Some(120),
         }],
         recent_runs: vec![StatusRunEntry {
             run_id: "ec7155dd-a357-4261-993b-72f18e304ea5".into(),
@@ -210,7 +245,10 @@ fn status_report_serialization() {
             observed_nodes: 4,
             dora_uuid: Some("019cc181-adad-7654-aa78-635023623380".into()),
             outcome_summary: "Stopped by user".into(),
+            source: "cli".into(),
+            uptime_secs: None,
         }],
+        untracked_dataflows: Vec::new(),
         dora_probe: vec![RuntimeDataflowStatus {
             id: "019cc181-adad-7654-aa78-63502362337b".into(),
             dataflow_name: "flow1".into(),
@@ -220,29 +258,54 @@ fn status_report_serialization() {
             observed_nodes: 2,
             cpu: Some("0.0%".into()),
             memory: Some("0.0".into()),
+            run_id: Some("5c49b211-5575-4a7e-a666-cf32c198ea5e".into()),
+            source: Some("web".into()),
+        }],
+        processes: vec![RuntimeProcess {
+            role: "coordinator".into(),
+            pid: 4242,
+            alive: true,
+            uptime_secs: Some(60),
+            cpu_percent: Some(0.5),
+            memory_bytes: Some(12_582_912),
         }],
+        coordinator_addr: None,
+        machines: Vec::new(),
     };
     let json = serde_json::to_string(&report).unwrap();
     let parsed: StatusReport = serde_json::from_str(&json).unwrap();
     assert_eq!(parsed.active_runs.len(), 1);
     assert_eq!(parsed.recent_runs.len(), 1);
     assert_eq!(parsed.dora_probe.len(), 1);
+    assert_eq!(parsed.processes[0].pid, 4242);
+    assert_eq!(parsed.robot_id.as_deref(), Some("rover-7"));
     assert!(!parsed.runtime_running);
 }
 
 #[test]
 fn setup_report_serialization() {
+    let phase = |phase, status| SetupPhaseReport {
+        phase,
+        status,
+        duration_ms: 5,
+        detail: None,
+        error: None,
+    };
     let report = SetupReport {
-        python_installed: true,
-        uv_installed: false,
-        dora_installed: true,
+        phases: vec![
+            phase(SetupPhase::Python, SetupStatus::Found),
+            phase(SetupPhase::Uv, SetupStatus::Failed),
+            phase(SetupPhase::Dora, SetupStatus::Installed),
+        ],
         dora_version: Some("0.4.1".into()),
     };
     let json = serde_json::to_string(&report).unwrap();
+    assert!(json.contains("\"status\":\"installed\""));
     let parsed: SetupReport = serde_json::from_str(&json).unwrap();
-    assert!(parsed.python_installed);
-    assert!(!parsed.uv_installed);
-    assert!(parsed.dora_installed);
+    assert!(parsed.ok(SetupPhase::Python));
+    assert!(!parsed.ok(SetupPhase::Uv));
+    assert!(parsed.ok(SetupPhase::Dora));
+    assert!(!parsed.all_ok());
     assert_eq!(parsed.dora_version, Some("0.4.1".into()));
 }
 
@@ -254,9 +317,14 @@ fn all_install_phases_serialize() {
             bytes_done: 0,
             bytes_total: 1024,
         },
+        InstallPhase::Verifying,
         InstallPhase::Extracting,
         InstallPhase::Building,
         InstallPhase::Done,
+        InstallPhase::Setup {
+            phase: SetupPhase::Uv,
+            status: SetupStatus::Running,
+        },
     ];
     for phase in phases {
         let progress = InstallProgress {
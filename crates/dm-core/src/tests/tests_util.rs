@@ -59,18 +59,13 @@ fn resolve_dm_cli_exe_prefers_env_override() {
 #[test]
 #[cfg(not(target_os = "windows"))]
 fn resolve_dm_cli_exe_uses_path_before_fallback() {
-    use std::os::unix::fs::PermissionsExt;
-
     let _guard = env_lock();
     let original_dm_cli_bin = std::env::var_os(util::DM_CLI_BIN_ENV_KEY);
     std::env::remove_var(util::DM_CLI_BIN_ENV_KEY);
 
     let tmp = tempfile::TempDir::new().unwrap();
     let dm = tmp.path().join("dm");
-    std::fs::write(&dm, "#!/bin/sh\nexit 0\n").unwrap();
-    let mut perms = std::fs::metadata(&dm).unwrap().permissions();
-    perms.set_mode(0o755);
-    std::fs::set_permissions(&dm, perms).unwrap();
+    dm_test_utils::MockCommand::new().install_at(&dm).unwrap();
     let _path_guard = crate::test_support::set_path(tmp.path().as_os_str());
 
     assert_eq!(util::resolve_dm_cli_exe(), dm);
@@ -82,6 +77,20 @@ fn resolve_dm_cli_exe_uses_path_before_fallback() {
     }
 }
 
+#[test]
+fn dm_cli_exe_prefers_the_one_set_for_the_home() {
+    let _guard = env_lock();
+    let served = tempfile::TempDir::new().unwrap();
+    let other = tempfile::TempDir::new().unwrap();
+    util::set_dm_cli_exe(served.path(), "/opt/app/dm".into());
+
+    assert_eq!(
+        util::dm_cli_exe(served.path()),
+        std::path::PathBuf::from("/opt/app/dm")
+    );
+    assert_eq!(util::dm_cli_exe(other.path()), util::resolve_dm_cli_exe());
+}
+
 #[test]
 fn is_valid_dora_binary_nonexistent() {
     let path = std::path::PathBuf::from("/tmp/nonexistent-dora-binary-xyz");
@@ -99,7 +108,7 @@ fn is_valid_dora_binary_directory() {
 fn is_valid_dora_binary_file() {
     let tmp = tempfile::TempDir::new().unwrap();
     let file = tmp.path().join("dora");
-    std::fs::write(&file, "#!/bin/sh\necho hello").unwrap();
+    dm_test_utils::MockCommand::new().install_at(&file).unwrap();
     assert!(util::is_valid_dora_binary(&file));
 }
 
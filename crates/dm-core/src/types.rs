@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 // ─── Environment Check ───
@@ -22,6 +24,142 @@ pub struct DoctorReport {
     pub active_version: Option<String>,
     pub active_binary_ok: bool,
     pub all_ok: bool,
+    /// Failures recorded in the event store over the last 24h, grouped by cause
+    #[serde(default)]
+    pub recent_problems: Vec<RecentProblem>,
+    /// Configured `[thresholds]` that are currently exceeded
+    #[serde(default)]
+    pub threshold_alerts: Vec<ThresholdAlert>,
+    /// Sensitive files other users can read; `dm doctor --fix` tightens them
+    #[serde(default)]
+    pub permission_issues: Vec<PermissionIssue>,
+    /// Per-node launch probes, only filled by `dm doctor --deep`
+    #[serde(default)]
+    pub node_probes: Vec<NodeProbe>,
+    /// Node venvs whose `dora-rs` is outside the active CLI's SDK constraint
+    #[serde(default)]
+    pub sdk_mismatches: Vec<SdkMismatch>,
+    /// Environment checks of every installed node
+    #[serde(default)]
+    pub nodes: Vec<NodeHealth>,
+}
+
+/// Environment health of an installed node
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeHealth {
+    pub node_id: String,
+    /// `python`, `cargo` or `other`, from the node's build command
+    pub kind: String,
+    /// Whether every check passed
+    pub ok: bool,
+    pub checks: Vec<NodeCheck>,
+}
+
+/// One check of a node's environment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeCheck {
+    /// `executable`, `venv`, `pip_check` or `version`
+    pub name: String,
+    pub ok: bool,
+    pub detail: String,
+}
+
+/// A node venv holding a `dora-rs` SDK that does not match the active CLI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SdkMismatch {
+    pub node_id: String,
+    /// `dora-rs` version in the node's venv
+    pub installed: String,
+    /// Requirement for the active CLI, e.g. `dora-rs==0.3.*`
+    pub expected: String,
+}
+
+/// Outcome of briefly launching an installed node's executable
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeProbe {
+    pub node_id: String,
+    pub executable: String,
+    pub ok: bool,
+    /// Exit code, or `None` when the node was still running (and was
+    /// killed) at the end of the probe or could not be started at all
+    pub exit_code: Option<i32>,
+    /// What happened, e.g. the loader error from stderr
+    pub detail: String,
+    pub duration_ms: u64,
+}
+
+/// A sensitive file (or the dm home) with a wider mode than dm creates it with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionIssue {
+    pub path: String,
+    /// Current mode in octal, e.g. `644`
+    pub mode: String,
+    /// Mode dm expects, `600` for files and `700` for the home
+    pub expected: String,
+}
+
+/// A `[thresholds]` limit from config.toml that is currently exceeded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdAlert {
+    /// `disk_free_pct`, `events_db_mb` or `versions`
+    pub metric: String,
+    pub value: f64,
+    pub limit: f64,
+    pub message: String,
+}
+
+/// A group of similar recent failures with a suggested fix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentProblem {
+    /// `explain` code when the cause is known, else the failing activity
+    pub code: String,
+    pub activity: String,
+    pub count: usize,
+    pub summary: String,
+    pub suggestion: String,
+    pub last_seen: String,
+}
+
+// ─── Explain ───
+
+/// Troubleshooting guidance returned by `explain()`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Explanation {
+    pub code: String,
+    pub title: String,
+    pub summary: String,
+    pub steps: Vec<String>,
+    /// Cause inferred from the most recent matching failure event
+    pub diagnosis: Option<String>,
+    pub event_id: Option<i64>,
+    pub event_at: Option<String>,
+}
+
+/// An error code known to `explain()`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplainTopic {
+    pub code: String,
+    pub title: String,
+    pub activity: String,
+}
+
+// ─── Migrate ───
+
+/// Result of `migrate()`: what was (or would be) upgraded in a dm home
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub dry_run: bool,
+    /// Where originals were copied before being rewritten
+    pub backup_dir: Option<String>,
+    pub changes: Vec<MigrationChange>,
+}
+
+/// One upgrade applied to a file in the dm home
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationChange {
+    /// Path relative to the dm home, e.g. `nodes/dora-yolo/dm.json`
+    pub path: String,
+    pub description: String,
 }
 
 // ─── Version Management ───
@@ -30,12 +168,18 @@ pub struct DoctorReport {
 pub struct InstalledVersion {
     pub version: String,
     pub active: bool,
+    /// `versions/<tag>/install.json`, absent for installs made before it existed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<InstallManifest>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AvailableVersion {
     pub tag: String,
     pub installed: bool,
+    /// RFC 3339 publish time of the GitHub release
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_at: Option<String>,
 }
 
 /// Report returned by `versions()`
@@ -43,6 +187,9 @@ pub struct AvailableVersion {
 pub struct VersionsReport {
     pub installed: Vec<InstalledVersion>,
     pub available: Vec<AvailableVersion>,
+    /// Older releases exist beyond `available`; list them with `all`
+    #[serde(default)]
+    pub more_available: bool,
 }
 
 // ─── Install ───
@@ -51,10 +198,48 @@ pub struct VersionsReport {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum InstallPhase {
     Fetching,
-    Downloading { bytes_done: u64, bytes_total: u64 },
+    Downloading {
+        bytes_done: u64,
+        bytes_total: u64,
+    },
+    Verifying,
     Extracting,
     Building,
     Done,
+    /// A `dm setup` phase started or finished
+    Setup {
+        phase: SetupPhase,
+        status: SetupStatus,
+    },
+}
+
+impl InstallPhase {
+    /// Stable phase name used in `*.progress` events.
+    pub fn name(&self) -> &'static str {
+        match self {
+            InstallPhase::Fetching => "fetching",
+            InstallPhase::Downloading { .. } => "downloading",
+            InstallPhase::Verifying => "verifying",
+            InstallPhase::Extracting => "extracting",
+            InstallPhase::Building => "building",
+            InstallPhase::Done => "done",
+            InstallPhase::Setup { .. } => "setup",
+        }
+    }
+
+    /// Completion percentage, when the phase can measure it.
+    pub fn pct(&self) -> Option<u8> {
+        match self {
+            InstallPhase::Downloading {
+                bytes_done,
+                bytes_total,
+            } if *bytes_total > 0 => {
+                Some(((*bytes_done).min(*bytes_total) * 100 / bytes_total) as u8)
+            }
+            InstallPhase::Done => Some(100),
+            _ => None,
+        }
+    }
 }
 
 /// Progress message sent during installation
@@ -71,24 +256,56 @@ pub enum InstallMethod {
     Source,
 }
 
+/// Provenance of an installed version, stored as `versions/<tag>/install.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstallManifest {
+    pub version: String,
+    pub method: InstallMethod,
+    /// Download URL of the release asset, or the git tag for source builds
+    pub source_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset_name: Option<String>,
+    /// `sha256:<hex>` of the downloaded asset, checked against GitHub's
+    /// published digest when the release provides one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset_digest: Option<String>,
+    /// Checks the asset passed before extraction (`github-digest`,
+    /// `checksum-file`, `minisign`, `gpg`); empty when unverified
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub verified_by: Vec<String>,
+    /// Companion runtime binaries installed next to `dora`
+    /// (`dora-coordinator`, `dora-daemon`, `dora-runtime`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub companions: Vec<String>,
+    pub installed_at: String,
+}
+
 /// Result of a successful install
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InstallResult {
     pub version: String,
     pub method: InstallMethod,
     pub set_active: bool,
+    /// The installed binary ran `dora --version` and `dora check --help`
+    #[serde(default)]
+    pub verified: bool,
+    /// Version reported by the installed `dora --version`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actual_version: Option<String>,
 }
 
 // ─── Runtime ───
 
 /// Result of up/down commands
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct RuntimeResult {
     pub success: bool,
     pub message: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct RuntimeDataflowStatus {
     pub id: String,
     pub dataflow_name: String,
@@ -98,9 +315,16 @@ pub struct RuntimeDataflowStatus {
     pub observed_nodes: u32,
     pub cpu: Option<String>,
     pub memory: Option<String>,
+    /// dm run tracking this dora UUID, if any
+    #[serde(default)]
+    pub run_id: Option<String>,
+    /// Where the tracked run was started from (cli, server, web)
+    #[serde(default)]
+    pub source: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct StatusRunEntry {
     pub run_id: String,
     pub dataflow_name: String,
@@ -111,28 +335,168 @@ pub struct StatusRunEntry {
     pub observed_nodes: u32,
     pub dora_uuid: Option<String>,
     pub outcome_summary: String,
+    /// Where the run was started from (cli, server, web)
+    #[serde(default)]
+    pub source: String,
+    /// Seconds since start, for runs that are still running
+    #[serde(default)]
+    pub uptime_secs: Option<i64>,
+}
+
+/// A dataflow running in dora together with its nodes, as listed by `ps()`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataflowProcess {
+    #[serde(flatten)]
+    pub dataflow: RuntimeDataflowStatus,
+    pub nodes: Vec<crate::runs::NodeMetrics>,
+}
+
+/// The dora coordinator or daemon of the shared runtime, as recorded in its
+/// PID file under `<home>/run/`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct RuntimeProcess {
+    /// `coordinator` or `daemon`
+    pub role: String,
+    pub pid: u32,
+    pub alive: bool,
+    pub uptime_secs: Option<u64>,
+    /// CPU usage in percent of one core
+    pub cpu_percent: Option<f32>,
+    /// Resident memory in bytes
+    pub memory_bytes: Option<u64>,
+}
+
+/// A remote dora daemon registered with `dm machine add`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
+pub struct MachineStatus {
+    /// Machine id the daemon was started with (`--machine-id`)
+    pub name: String,
+    pub addr: String,
+    /// Whether a TCP connection succeeded; `None` when `addr` has no port
+    pub reachable: Option<bool>,
 }
 
 /// Status report returned by `status()`
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(utoipa::ToSchema))]
 pub struct StatusReport {
     pub active_version: Option<String>,
     pub actual_version: Option<String>,
     pub dm_home: String,
+    /// `robot_id` from config.toml
+    #[serde(default)]
+    pub robot_id: Option<String>,
+    /// `labels` from config.toml
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
     pub runtime_running: bool,
     pub runtime_output: String,
     pub active_runs: Vec<StatusRunEntry>,
     pub recent_runs: Vec<StatusRunEntry>,
+    /// Dataflows reported by `dora list` that no dm run tracks
+    #[serde(default)]
+    pub untracked_dataflows: Vec<RuntimeDataflowStatus>,
     pub dora_probe: Vec<RuntimeDataflowStatus>,
+    /// Coordinator and daemon recorded by `up`
+    #[serde(default)]
+    pub processes: Vec<RuntimeProcess>,
+    /// `coordinator_addr` from config.toml, when dora talks to a remote
+    /// coordinator
+    #[serde(default)]
+    pub coordinator_addr: Option<String>,
+    /// Remote daemons from `[machines]` in config.toml
+    #[serde(default)]
+    pub machines: Vec<MachineStatus>,
 }
 
 // ─── Setup ───
 
-/// Report returned by `setup()`
+/// Report returned by `setup()`: one entry per phase, in the order run
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SetupReport {
-    pub python_installed: bool,
-    pub uv_installed: bool,
-    pub dora_installed: bool,
+    pub phases: Vec<SetupPhaseReport>,
+    /// Active dora version once setup finished
     pub dora_version: Option<String>,
 }
+
+impl SetupReport {
+    /// Whether `phase` ended usable (already present or installed).
+    pub fn ok(&self, phase: SetupPhase) -> bool {
+        self.phases
+            .iter()
+            .any(|report| report.phase == phase && report.status.is_ok())
+    }
+
+    pub fn all_ok(&self) -> bool {
+        self.phases.iter().all(|report| report.status.is_ok())
+    }
+}
+
+/// Steps of `dm setup`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupPhase {
+    Python,
+    Uv,
+    Dora,
+}
+
+impl SetupPhase {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SetupPhase::Python => "Python",
+            SetupPhase::Uv => "uv",
+            SetupPhase::Dora => "dora",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupStatus {
+    /// In progress; only sent through the progress channel
+    Running,
+    /// Already present
+    Found,
+    /// Installed by this setup
+    Installed,
+    /// Not attempted because an earlier phase failed
+    Skipped,
+    Failed,
+}
+
+impl SetupStatus {
+    pub fn is_ok(&self) -> bool {
+        matches!(self, SetupStatus::Found | SetupStatus::Installed)
+    }
+}
+
+/// Outcome of one `dm setup` phase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetupPhaseReport {
+    pub phase: SetupPhase,
+    pub status: SetupStatus,
+    pub duration_ms: u64,
+    /// Version or path found, e.g. `Python 3.11.4`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// ─── Shim ───
+
+/// State of the `dora` shim returned by `shim::shim_status()`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShimStatus {
+    /// Path of the shim (`<home>/bin/dora`)
+    pub path: String,
+    /// Directory that must be on `PATH`
+    pub dir: String,
+    pub installed: bool,
+    /// Binary the shim currently runs, when installed and resolvable
+    pub target: Option<String>,
+    pub on_path: bool,
+}
@@ -1,7 +1,36 @@
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 
 pub const DM_CLI_BIN_ENV_KEY: &str = "DM_CLI_BIN";
 
+/// `dm` CLI binaries set with [`set_dm_cli_exe`], by home.
+fn dm_cli_exes() -> &'static Mutex<HashMap<PathBuf, PathBuf>> {
+    static EXES: OnceLock<Mutex<HashMap<PathBuf, PathBuf>>> = OnceLock::new();
+    EXES.get_or_init(Default::default)
+}
+
+/// Use `exe` as the `dm` CLI for everything this process does in `home`,
+/// ahead of [`resolve_dm_cli_exe`]. dm-server sets the binary it resolved
+/// at startup this way.
+pub fn set_dm_cli_exe(home: &Path, exe: PathBuf) {
+    dm_cli_exes()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(home.to_path_buf(), exe);
+}
+
+/// The `dm` CLI binary for `home`: the one set with [`set_dm_cli_exe`], or
+/// else [`resolve_dm_cli_exe`].
+pub fn dm_cli_exe(home: &Path) -> PathBuf {
+    let set = dm_cli_exes()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(home)
+        .cloned();
+    set.unwrap_or_else(resolve_dm_cli_exe)
+}
+
 /// Check if a command exists in PATH, returns its full path.
 pub fn check_command(name: &str) -> Option<String> {
     which::which(name)
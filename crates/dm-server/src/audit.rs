@@ -0,0 +1,323 @@
+//! Audit middleware: every mutating API call listed in [`AUDITED_ROUTES`]
+//! is recorded as an `audit.<action>` event once its handler responds.
+
+use std::net::SocketAddr;
+
+use axum::body::{to_bytes, Body};
+use axum::extract::{ConnectInfo, MatchedPath, Request, State};
+use axum::http::{Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::Response;
+use dm_core::events::{record_audit, AuditEntry};
+
+use crate::handlers::{bearer_token, error_response};
+use crate::state::AppState;
+
+/// Largest JSON body buffered to read an audit target from; matches
+/// axum's default `Json` limit.
+const AUDIT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// Where the audited call names what it changes.
+#[derive(Clone, Copy)]
+enum Target {
+    None,
+    /// A `{param}` of the route
+    Path(&'static str),
+    /// JSON body fields, joined with spaces; arrays are joined with commas
+    Body(&'static [&'static str]),
+    /// Top-level keys of a JSON body, e.g. the sections of a config patch
+    BodyKeys,
+}
+
+/// Method, route, action and target of each call that changes dm state.
+/// Validation, previews, probes and event ingestion are left out.
+static AUDITED_ROUTES: &[(Method, &str, &str, Target)] = &[
+    (
+        Method::POST,
+        "/api/config",
+        "config.update",
+        Target::BodyKeys,
+    ),
+    (
+        Method::POST,
+        "/api/install",
+        "runtime.install",
+        Target::Body(&["version"]),
+    ),
+    (
+        Method::POST,
+        "/api/uninstall",
+        "runtime.uninstall",
+        Target::Body(&["version"]),
+    ),
+    (
+        Method::POST,
+        "/api/use",
+        "runtime.use",
+        Target::Body(&["version"]),
+    ),
+    (Method::POST, "/api/up", "runtime.up", Target::None),
+    (Method::POST, "/api/down", "runtime.down", Target::None),
+    (
+        Method::POST,
+        "/api/media/install",
+        "media.install",
+        Target::None,
+    ),
+    (
+        Method::POST,
+        "/api/jobs",
+        "job.start",
+        Target::Body(&["kind", "version", "id"]),
+    ),
+    (
+        Method::POST,
+        "/api/nodes/install",
+        "node.install",
+        Target::Body(&["id"]),
+    ),
+    (
+        Method::POST,
+        "/api/nodes/install-batch",
+        "node.install",
+        Target::Body(&["ids"]),
+    ),
+    (
+        Method::POST,
+        "/api/nodes/create",
+        "node.create",
+        Target::Body(&["id"]),
+    ),
+    (
+        Method::POST,
+        "/api/nodes/import",
+        "node.import",
+        Target::Body(&["id", "source"]),
+    ),
+    (
+        Method::POST,
+        "/api/nodes/import-archive",
+        "node.import",
+        Target::None,
+    ),
+    (
+        Method::POST,
+        "/api/nodes/uninstall",
+        "node.uninstall",
+        Target::Body(&["id"]),
+    ),
+    (
+        Method::POST,
+        "/api/nodes/{id}/config",
+        "node.config",
+        Target::Path("id"),
+    ),
+    (
+        Method::PUT,
+        "/api/nodes/{id}/config",
+        "node.config",
+        Target::Path("id"),
+    ),
+    (
+        Method::PUT,
+        "/api/nodes/{id}/schema",
+        "node.schema",
+        Target::Path("id"),
+    ),
+    (
+        Method::POST,
+        "/api/nodes/{id}/pin",
+        "node.pin",
+        Target::Path("id"),
+    ),
+    (
+        Method::POST,
+        "/api/nodes/{id}/unpin",
+        "node.unpin",
+        Target::Path("id"),
+    ),
+    (
+        Method::POST,
+        "/api/dataflows/import",
+        "dataflow.import",
+        Target::None,
+    ),
+    (
+        Method::POST,
+        "/api/dataflows/import-bundle",
+        "dataflow.import",
+        Target::Body(&["name"]),
+    ),
+    (
+        Method::POST,
+        "/api/dataflows/upload",
+        "dataflow.import",
+        Target::None,
+    ),
+    (
+        Method::POST,
+        "/api/dataflows/sync",
+        "dataflow.sync",
+        Target::None,
+    ),
+    (
+        Method::POST,
+        "/api/dataflows/{name}",
+        "dataflow.save",
+        Target::Path("name"),
+    ),
+    (
+        Method::POST,
+        "/api/dataflows/{name}/meta",
+        "dataflow.save_meta",
+        Target::Path("name"),
+    ),
+    (
+        Method::POST,
+        "/api/dataflows/{name}/view",
+        "dataflow.save_view",
+        Target::Path("name"),
+    ),
+    (
+        Method::POST,
+        "/api/dataflows/{name}/history/{version}/restore",
+        "dataflow.restore",
+        Target::Path("name"),
+    ),
+    (
+        Method::POST,
+        "/api/dataflows/{name}/delete",
+        "dataflow.delete",
+        Target::Path("name"),
+    ),
+    (
+        Method::POST,
+        "/api/dataflows/start",
+        "run.start",
+        Target::None,
+    ),
+    (
+        Method::POST,
+        "/api/dataflows/stop",
+        "run.stop",
+        Target::None,
+    ),
+    (
+        Method::POST,
+        "/api/dataflow/start",
+        "run.start",
+        Target::Body(&["name"]),
+    ),
+    (Method::POST, "/api/dataflow/stop", "run.stop", Target::None),
+    (
+        Method::POST,
+        "/api/runs/start",
+        "run.start",
+        Target::Body(&["name"]),
+    ),
+    (
+        Method::POST,
+        "/api/runs/{id}/stop",
+        "run.stop",
+        Target::Path("id"),
+    ),
+    (Method::POST, "/api/runs/delete", "run.delete", Target::None),
+];
+
+/// Record the call as an audit event if its route is audited. The
+/// response is passed through unchanged; a failure to record is logged.
+pub(crate) async fn audit_mutations(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .and_then(|matched| {
+            AUDITED_ROUTES.iter().find(|(method, path, _, _)| {
+                method == request.method() && *path == matched.as_str()
+            })
+        });
+    let Some(&(_, template, action, target)) = route else {
+        return next.run(request).await;
+    };
+
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let client_ip = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string());
+    let user = bearer_token(request.headers()).map(|token| {
+        dm_core::config::load_config(&state.home)
+            .ok()
+            .and_then(|config| config.ingest.token(token).map(|t| t.name.clone()))
+            .unwrap_or_else(|| "unknown-token".to_string())
+    });
+
+    let (request, target) = match target {
+        Target::None => (request, None),
+        Target::Path(param) => (request, path_param(template, &path, param)),
+        Target::Body(_) | Target::BodyKeys => {
+            let (parts, body) = request.into_parts();
+            let Ok(bytes) = to_bytes(body, AUDIT_BODY_LIMIT).await else {
+                return error_response(StatusCode::PAYLOAD_TOO_LARGE, "Request body too large");
+            };
+            let target = body_target(target, &bytes);
+            (Request::from_parts(parts, Body::from(bytes)), target)
+        }
+    };
+
+    let response = next.run(request).await;
+    let entry = AuditEntry {
+        action: action.to_string(),
+        target,
+        user,
+        client_ip,
+        method,
+        path,
+        status: response.status().as_u16(),
+        ..Default::default()
+    };
+    if let Err(e) = record_audit(&state.events, &entry) {
+        eprintln!("[dm-server] failed to record audit entry: {e}");
+    }
+    response
+}
+
+/// The segment of `path` at the position of `{param}` in `template`.
+fn path_param(template: &str, path: &str, param: &str) -> Option<String> {
+    let placeholder = format!("{{{param}}}");
+    template
+        .split('/')
+        .zip(path.split('/'))
+        .find(|(segment, _)| *segment == placeholder)
+        .map(|(_, value)| value.to_string())
+}
+
+fn body_target(target: Target, body: &[u8]) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_slice(body).ok()?;
+    let (parts, separator): (Vec<String>, &str) = match target {
+        Target::Body(fields) => (
+            fields
+                .iter()
+                .filter_map(|field| match &json[*field] {
+                    serde_json::Value::String(value) => Some(value.clone()),
+                    serde_json::Value::Array(values) => Some(
+                        values
+                            .iter()
+                            .filter_map(|value| value.as_str())
+                            .collect::<Vec<_>>()
+                            .join(","),
+                    ),
+                    _ => None,
+                })
+                .collect(),
+            " ",
+        ),
+        Target::BodyKeys => (json.as_object()?.keys().cloned().collect(), ","),
+        Target::None | Target::Path(_) => return None,
+    };
+    (!parts.is_empty()).then(|| parts.join(separator))
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use serde::Deserialize;
@@ -123,7 +123,7 @@ pub async fn bridge_socket_loop(
 }
 
 fn handle_push(
-    home: &PathBuf,
+    home: &Path,
     run_id: &str,
     messages_tx: &broadcast::Sender<MessageNotification>,
     from: &str,
@@ -132,14 +132,9 @@ fn handle_push(
     timestamp: Option<i64>,
 ) {
     let result = (|| {
-        let normalized = if tag == "input" {
-            payload.clone()
-        } else {
-            payload.clone()
-        };
         let service = MessageService::open(home, run_id)?;
         let ts = timestamp.unwrap_or_else(services::now_ts);
-        let seq = service.push(from, tag, &normalized, ts)?;
+        let seq = service.push(from, tag, payload, ts)?;
         Ok::<i64, anyhow::Error>(seq)
     })();
 
@@ -158,7 +153,7 @@ fn handle_push(
     }
 }
 
-fn lookup_input(home: &PathBuf, run_id: &str, seq: i64) -> Option<services::message::Message> {
+fn lookup_input(home: &Path, run_id: &str, seq: i64) -> Option<services::message::Message> {
     let service = MessageService::open(home, run_id).ok()?;
     let filter = services::message::MessageFilter {
         after_seq: Some(seq - 1),
@@ -1,10 +1,12 @@
-use axum::extract::{Path, State};
-use axum::http::StatusCode;
+use std::collections::BTreeMap;
+
+use axum::extract::{Multipart, Path, Query, State};
+use axum::http::{header, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
 use serde::Deserialize;
 
-use crate::handlers::{err, runs::StartRunRequest};
+use crate::handlers::{err, err_with, error_response, runs::StartRunRequest};
 use crate::state::AppState;
 
 use utoipa::ToSchema;
@@ -14,7 +16,7 @@ use utoipa::ToSchema;
 pub async fn list_dataflows(State(state): State<AppState>) -> impl IntoResponse {
     match dm_core::dataflow::list(&state.home) {
         Ok(result) => Json(result).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
 
@@ -26,18 +28,7 @@ pub async fn get_dataflow(
 ) -> impl IntoResponse {
     match dm_core::dataflow::get(&state.home, &name) {
         Ok(project) => Json(project).into_response(),
-        Err(e) => {
-            if let Some(io_err) = e.downcast_ref::<std::io::Error>() {
-                if io_err.kind() == std::io::ErrorKind::NotFound {
-                    return (
-                        StatusCode::NOT_FOUND,
-                        format!("Dataflow '{}' not found", name),
-                    )
-                        .into_response();
-                }
-            }
-            err(e).into_response()
-        }
+        Err(e) => err(e),
     }
 }
 
@@ -60,7 +51,7 @@ pub async fn save_dataflow(
 ) -> impl IntoResponse {
     match dm_core::dataflow::save(&state.home, &name, &req.yaml) {
         Ok(project) => Json(project).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
 
@@ -96,6 +87,235 @@ pub async fn import_dataflows(
     (status, Json(report)).into_response()
 }
 
+/// POST /api/dataflows/upload
+#[utoipa::path(post, path = "/api/dataflows/upload", request_body(content_type = "multipart/form-data", description = "`file`: the dataflow YAML; optional `name` (default: the file name) and `overwrite` (`true` to replace an existing dataflow)"), responses((status = 200, description = "Saved dataflow"), (status = 400, description = "Missing file or invalid YAML"), (status = 409, description = "A dataflow with that name exists")))]
+pub async fn upload_dataflow(State(state): State<AppState>, mut form: Multipart) -> Response {
+    let mut file: Option<(Option<String>, String)> = None;
+    let mut name = None;
+    let mut overwrite = false;
+    loop {
+        let field = match form.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => return err_with(e, StatusCode::BAD_REQUEST),
+        };
+        let field_name = field.name().unwrap_or_default().to_string();
+        let file_name = field.file_name().map(str::to_string);
+        let text = match field.text().await {
+            Ok(text) => text,
+            Err(e) => return err_with(e, StatusCode::BAD_REQUEST),
+        };
+        match field_name.as_str() {
+            "file" => file = Some((file_name, text)),
+            "name" => name = Some(text.trim().to_string()).filter(|n| !n.is_empty()),
+            "overwrite" => overwrite = matches!(text.trim(), "true" | "1"),
+            _ => {}
+        }
+    }
+
+    let Some((file_name, yaml)) = file else {
+        return error_response(StatusCode::BAD_REQUEST, "Missing `file` field");
+    };
+    let name = name.unwrap_or_else(|| {
+        dm_core::dataflow::infer_import_name(file_name.as_deref().unwrap_or("dataflow.yml"))
+    });
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            format!("Invalid dataflow name '{}'", name),
+        );
+    }
+    if let Err(e) = serde_yaml::from_str::<serde_yaml::Mapping>(&yaml) {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            format!("Uploaded file is not a YAML dataflow: {}", e),
+        );
+    }
+    if !overwrite && dm_core::dataflow::dataflow_dir(&state.home, &name).exists() {
+        return error_response(
+            StatusCode::CONFLICT,
+            format!("Dataflow '{}' already exists", name),
+        );
+    }
+
+    match dm_core::dataflow::save(&state.home, &name, &yaml) {
+        Ok(project) => Json(project).into_response(),
+        Err(e) => err(e),
+    }
+}
+
+/// GET /api/dataflows/:name/download
+#[utoipa::path(get, path = "/api/dataflows/{name}/download", params(("name" = String, Path)), responses((status = 200, description = "Dataflow YAML as an attachment", content_type = "application/yaml"), (status = 404, description = "Dataflow not found")))]
+pub async fn download_dataflow(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+) -> Response {
+    if !dm_core::dataflow::dataflow_dir(&state.home, &name).exists() {
+        return err(dm_core::error::DmError::not_found("dataflow", &name));
+    }
+    match dm_core::dataflow::get(&state.home, &name) {
+        Ok(project) => (
+            [
+                (header::CONTENT_TYPE, "application/yaml".to_string()),
+                (header::CONTENT_DISPOSITION, attachment(&name, "yml")),
+            ],
+            project.yaml,
+        )
+            .into_response(),
+        Err(e) => err(e),
+    }
+}
+
+/// GET /api/dataflows/:name/export
+#[utoipa::path(get, path = "/api/dataflows/{name}/export", params(("name" = String, Path)), responses((status = 200, description = "`.dmflow` bundle: the YAML plus node versions, pins, sources and configs without secrets", content_type = "application/json"), (status = 404, description = "Dataflow not found")))]
+pub async fn export_dataflow(State(state): State<AppState>, Path(name): Path<String>) -> Response {
+    match dm_core::dataflow::export_bundle(&state.home, &name) {
+        Ok(bundle) => (
+            [
+                (header::CONTENT_TYPE, "application/json".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    attachment(&name, dm_core::dataflow::DATAFLOW_BUNDLE_EXTENSION),
+                ),
+            ],
+            Json(bundle),
+        )
+            .into_response(),
+        Err(e) => dataflow_not_found_or_err(e, &name),
+    }
+}
+
+/// `Content-Disposition` value for downloading `name.ext`.
+fn attachment(name: &str, ext: &str) -> String {
+    let file_name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_graphic() && c != '"' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("attachment; filename=\"{}.{}\"", file_name, ext)
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ImportBundleRequest {
+    /// Contents of a `.dmflow` file
+    #[schema(value_type = Object)]
+    pub bundle: dm_core::dataflow::DataflowBundle,
+    /// Dataflow name (default: the name it was exported under)
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// POST /api/dataflows/import-bundle
+#[utoipa::path(post, path = "/api/dataflows/import-bundle", request_body = ImportBundleRequest, responses((status = 200, description = "Registered dataflow, installed nodes, applied configs and unset secrets"), (status = 409, description = "A dataflow with that name exists")))]
+pub async fn import_dataflow_bundle(
+    State(state): State<AppState>,
+    Json(req): Json<ImportBundleRequest>,
+) -> impl IntoResponse {
+    match dm_core::dataflow::import_bundle(&state.home, &req.bundle, req.name.as_deref()).await {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => err(e),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SyncDataflowsRequest {
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(default)]
+    pub no_push: bool,
+}
+
+/// POST /api/dataflows/sync
+#[utoipa::path(post, path = "/api/dataflows/sync", request_body = SyncDataflowsRequest, responses((status = 200, description = "Sync result")))]
+pub async fn sync_dataflows(
+    State(state): State<AppState>,
+    Json(req): Json<SyncDataflowsRequest>,
+) -> impl IntoResponse {
+    let home = state.home.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        dm_core::dataflow::sync(&home, req.message.as_deref(), !req.no_push)
+    })
+    .await;
+    match result {
+        Ok(Ok(report)) => Json(report).into_response(),
+        Ok(Err(e)) => err(e),
+        Err(e) => err(e),
+    }
+}
+
+/// POST /api/dataflows/validate-all
+#[utoipa::path(post, path = "/api/dataflows/validate-all", responses((status = 200, description = "Per-dataflow problems that would stop it from starting; a Warn event is emitted for each failing dataflow")))]
+pub async fn validate_all_dataflows(State(state): State<AppState>) -> impl IntoResponse {
+    let home = state.home.clone();
+    match tokio::task::spawn_blocking(move || dm_core::dataflow::validate_all(&home)).await {
+        Ok(Ok(report)) => Json(report).into_response(),
+        Ok(Err(e)) => err(e),
+        Err(e) => err(e),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct GraphRequest {
+    /// Dora YAML or editor JSON as text, or the editor graph object itself
+    pub graph: serde_json::Value,
+}
+
+impl GraphRequest {
+    fn text(&self) -> String {
+        match &self.graph {
+            serde_json::Value::String(text) => text.clone(),
+            other => other.to_string(),
+        }
+    }
+}
+
+/// POST /api/graph/validate
+#[utoipa::path(post, path = "/api/graph/validate", request_body = GraphRequest, responses((status = 200, description = "Errors and warnings for the graph; `valid` is false when there are errors")))]
+pub async fn validate_graph(
+    State(state): State<AppState>,
+    Json(req): Json<GraphRequest>,
+) -> impl IntoResponse {
+    let home = state.home.clone();
+    let text = req.text();
+    match tokio::task::spawn_blocking(move || dm_core::dataflow::validate_graph(&home, &text)).await
+    {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => err(e),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ConvertGraphRequest {
+    /// Dora YAML or editor JSON as text, or the editor graph object itself
+    pub graph: serde_json::Value,
+    /// `yaml` or `json`
+    #[schema(value_type = String)]
+    pub to: dm_core::dataflow::GraphFormat,
+}
+
+/// POST /api/graph/convert
+#[utoipa::path(post, path = "/api/graph/convert", request_body = ConvertGraphRequest, responses((status = 200, description = "`{ yaml }` for `to: yaml`, `{ graph }` (editor nodes/edges) for `to: json`"), (status = 400, description = "Graph could not be parsed")))]
+pub async fn convert_graph(Json(req): Json<ConvertGraphRequest>) -> impl IntoResponse {
+    let text = GraphRequest { graph: req.graph }.text();
+    let converted = dm_core::dataflow::convert_graph(&text, req.to).and_then(|out| {
+        Ok(match req.to {
+            dm_core::dataflow::GraphFormat::Yaml => serde_json::json!({ "yaml": out }),
+            dm_core::dataflow::GraphFormat::Json => {
+                serde_json::json!({ "graph": serde_json::from_str::<serde_json::Value>(&out)? })
+            }
+        })
+    });
+    match converted {
+        Ok(body) => Json(body).into_response(),
+        Err(e) => err_with(e, StatusCode::BAD_REQUEST),
+    }
+}
+
 /// POST /api/dataflows/:name/delete
 #[utoipa::path(post, path = "/api/dataflows/{name}/delete", params(("name" = String, Path)), responses((status = 200, description = "Deletion result")))]
 pub async fn delete_dataflow(
@@ -104,22 +324,12 @@ pub async fn delete_dataflow(
 ) -> impl IntoResponse {
     match dm_core::dataflow::delete(&state.home, &name) {
         Ok(()) => Json(serde_json::json!({ "message": "Deleted successfully" })).into_response(),
-        Err(e) => {
-            if let Some(io_err) = e.downcast_ref::<std::io::Error>() {
-                if io_err.kind() == std::io::ErrorKind::NotFound {
-                    return (
-                        StatusCode::NOT_FOUND,
-                        format!("Dataflow '{}' not found", name),
-                    )
-                        .into_response();
-                }
-            }
-            err(e).into_response()
-        }
+        Err(e) => dataflow_not_found_or_err(e, &name),
     }
 }
 
 /// GET /api/dataflows/:name/meta
+#[utoipa::path(get, path = "/api/dataflows/{name}/meta", params(("name" = String, Path)), responses((status = 200, description = "Dataflow metadata (title, description, tags)"), (status = 404, description = "Dataflow not found")))]
 pub async fn get_dataflow_meta(
     State(state): State<AppState>,
     Path(name): Path<String>,
@@ -131,6 +341,7 @@ pub async fn get_dataflow_meta(
 }
 
 /// POST /api/dataflows/:name/meta
+#[utoipa::path(post, path = "/api/dataflows/{name}/meta", params(("name" = String, Path)), responses((status = 200, description = "Metadata saved")))]
 pub async fn save_dataflow_meta(
     State(state): State<AppState>,
     Path(name): Path<String>,
@@ -138,11 +349,12 @@ pub async fn save_dataflow_meta(
 ) -> impl IntoResponse {
     match dm_core::dataflow::save_flow_meta(&state.home, &name, &meta) {
         Ok(()) => Json(serde_json::json!({ "message": "Saved successfully" })).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
 
 /// GET /api/dataflows/:name/config-schema
+#[utoipa::path(get, path = "/api/dataflows/{name}/config-schema", params(("name" = String, Path)), responses((status = 200, description = "Config schema and current values of every node in the dataflow"), (status = 404, description = "Dataflow not found")))]
 pub async fn get_dataflow_config_schema(
     State(state): State<AppState>,
     Path(name): Path<String>,
@@ -154,6 +366,7 @@ pub async fn get_dataflow_config_schema(
 }
 
 /// GET /api/dataflows/:name/history
+#[utoipa::path(get, path = "/api/dataflows/{name}/history", params(("name" = String, Path)), responses((status = 200, description = "Saved versions, newest first"), (status = 404, description = "Dataflow not found")))]
 pub async fn list_dataflow_history(
     State(state): State<AppState>,
     Path(name): Path<String>,
@@ -165,17 +378,43 @@ pub async fn list_dataflow_history(
 }
 
 /// GET /api/dataflows/:name/history/:version
+#[utoipa::path(get, path = "/api/dataflows/{name}/history/{version}", params(("name" = String, Path), ("version" = String, Path)), responses((status = 200, description = "`{ yaml }` of the saved version"), (status = 404, description = "Dataflow or version not found")))]
 pub async fn get_dataflow_history_version(
     State(state): State<AppState>,
     Path((name, version)): Path<(String, String)>,
 ) -> impl IntoResponse {
     match dm_core::dataflow::get_history_version(&state.home, &name, &version) {
         Ok(yaml) => Json(serde_json::json!({ "yaml": yaml })).into_response(),
-        Err(e) => dataflow_not_found_or_err(e, &name).into_response(),
+        Err(e) => err(e),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct HistoryDiffParams {
+    /// Another saved version; defaults to the current YAML
+    pub against: Option<String>,
+}
+
+/// GET /api/dataflows/:name/history/:version/diff
+#[utoipa::path(get, path = "/api/dataflows/{name}/history/{version}/diff", params(("name" = String, Path), ("version" = String, Path), ("against" = Option<String>, Query, description = "Version to compare with; the current YAML when omitted")), responses((status = 200, description = "Unified diff from the saved version to `against`"), (status = 404, description = "Dataflow or version not found")))]
+pub async fn diff_dataflow_history_version(
+    State(state): State<AppState>,
+    Path((name, version)): Path<(String, String)>,
+    Query(query): Query<HistoryDiffParams>,
+) -> impl IntoResponse {
+    match dm_core::dataflow::diff_history_version(
+        &state.home,
+        &name,
+        &version,
+        query.against.as_deref(),
+    ) {
+        Ok(diff) => Json(diff).into_response(),
+        Err(e) => err(e),
     }
 }
 
 /// GET /api/dataflows/:name/inspect
+#[utoipa::path(get, path = "/api/dataflows/{name}/inspect", params(("name" = String, Path)), responses((status = 200, description = "Nodes of the dataflow and whether each is installed"), (status = 404, description = "Dataflow not found")))]
 pub async fn inspect_dataflow(
     State(state): State<AppState>,
     Path(name): Path<String>,
@@ -187,19 +426,25 @@ pub async fn inspect_dataflow(
 }
 
 /// POST /api/dataflows/:name/history/:version/restore
+#[utoipa::path(post, path = "/api/dataflows/{name}/history/{version}/restore", params(("name" = String, Path), ("version" = String, Path)), responses((status = 200, description = "Version restored as the current dataflow"), (status = 404, description = "Dataflow or version not found")))]
 pub async fn restore_dataflow_history_version(
     State(state): State<AppState>,
     Path((name, version)): Path<(String, String)>,
 ) -> impl IntoResponse {
     match dm_core::dataflow::restore_history_version(&state.home, &name, &version) {
         Ok(()) => Json(serde_json::json!({ "message": "Restored successfully" })).into_response(),
-        Err(e) => dataflow_not_found_or_err(e, &name).into_response(),
+        Err(e) => err(e),
     }
 }
 
 #[derive(Deserialize, ToSchema)]
 pub struct RunDataflowRequest {
     pub yaml: String,
+    #[serde(default)]
+    pub allow_multiple: Option<bool>,
+    /// Overrides for the dataflow's `variables:` defaults
+    #[serde(default)]
+    pub variables: Option<BTreeMap<String, String>>,
 }
 
 /// POST /api/dataflow/start
@@ -215,6 +460,11 @@ pub async fn start_dataflow(
             name: None,
             force: None,
             view_json: None,
+            allow_multiple: req.allow_multiple,
+            isolated: None,
+            dora_version: None,
+            variables: req.variables,
+            auto_install: None,
         }),
     )
     .await
@@ -228,29 +478,73 @@ pub async fn stop_dataflow(State(state): State<AppState>) -> Response {
         Ok(Some(run)) => crate::handlers::runs::stop_run(State(state), Path(run.run_id))
             .await
             .into_response(),
-        Ok(None) => (
-            StatusCode::NOT_FOUND,
-            Json(serde_json::json!({ "error": "No active run found." })),
-        )
-            .into_response(),
-        Err(e) => err(e).into_response(),
+        Ok(None) => error_response(StatusCode::NOT_FOUND, "No active run found."),
+        Err(e) => err(e),
     }
 }
 
-fn dataflow_not_found_or_err(e: anyhow::Error, name: &str) -> Response {
-    if let Some(io_err) = e.downcast_ref::<std::io::Error>() {
-        if io_err.kind() == std::io::ErrorKind::NotFound {
-            return (
-                StatusCode::NOT_FOUND,
-                format!("Dataflow '{}' not found", name),
-            )
-                .into_response();
+#[derive(Deserialize, ToSchema)]
+pub struct TagParams {
+    /// Tag in the dataflows' meta, e.g. `perception`
+    pub tag: String,
+    /// Restart dataflows that are already running (start only)
+    pub force: Option<bool>,
+}
+
+/// POST /api/dataflows/start?tag=perception
+#[utoipa::path(post, path = "/api/dataflows/start", params(("tag" = String, Query), ("force" = Option<bool>, Query)), responses((status = 200, description = "Per-dataflow start results"), (status = 404, description = "No dataflow has the tag")))]
+pub async fn start_tagged_dataflows(
+    State(state): State<AppState>,
+    Query(params): Query<TagParams>,
+) -> Response {
+    let isolation = dm_core::runs::RunIsolation::from_config(&state.home);
+    if isolation == dm_core::runs::RunIsolation::Shared {
+        if let Err(e) = dm_core::ensure_runtime_up(&state.home, false).await {
+            return err(e.context("Failed to auto-start dora runtime"));
         }
     }
-    err(e).into_response()
+    let strategy = if params.force.unwrap_or(false) {
+        dm_core::runs::StartConflictStrategy::StopAndRestart
+    } else {
+        dm_core::runs::StartConflictStrategy::Fail
+    };
+    let result = dm_core::runs::start_tagged(
+        &state.home,
+        &params.tag,
+        dm_core::runs::RunSource::Server,
+        strategy,
+        isolation,
+    )
+    .await;
+    group_response(result)
+}
+
+/// POST /api/dataflows/stop?tag=perception
+#[utoipa::path(post, path = "/api/dataflows/stop", params(("tag" = String, Query)), responses((status = 200, description = "Per-dataflow stop results"), (status = 404, description = "No dataflow has the tag")))]
+pub async fn stop_tagged_dataflows(
+    State(state): State<AppState>,
+    Query(params): Query<TagParams>,
+) -> Response {
+    group_response(dm_core::runs::stop_tagged(&state.home, &params.tag).await)
+}
+
+fn group_response(result: anyhow::Result<dm_core::runs::GroupRunReport>) -> Response {
+    match result {
+        Ok(report) => Json(report).into_response(),
+        Err(e) => err(e),
+    }
+}
+
+/// Missing files of a dataflow project surface as a missing dataflow.
+fn dataflow_not_found_or_err(e: anyhow::Error, name: &str) -> Response {
+    if dm_core::error::error_code(&e) == Some(dm_core::error::ErrorCode::NotFound) {
+        return err(dm_core::error::DmError::not_found("dataflow", name));
+    }
+    err(e)
 }
 
 /// GET /api/dataflows/:name/view
+#[utoipa::path(get, path = "/api/dataflows/{name}/view", params(("name" = String, Path)), responses((status = 200, description = "Editor layout of the dataflow"), (status = 404, description = "Dataflow not found")))]
 pub async fn get_dataflow_view(
     State(state): State<AppState>,
     Path(name): Path<String>,
@@ -262,6 +556,7 @@ pub async fn get_dataflow_view(
 }
 
 /// POST /api/dataflows/:name/view
+#[utoipa::path(post, path = "/api/dataflows/{name}/view", params(("name" = String, Path)), responses((status = 200, description = "View saved")))]
 pub async fn save_dataflow_view(
     State(state): State<AppState>,
     Path(name): Path<String>,
@@ -269,6 +564,6 @@ pub async fn save_dataflow_view(
 ) -> impl IntoResponse {
     match dm_core::dataflow::save_flow_view(&state.home, &name, &view) {
         Ok(()) => Json(serde_json::json!({ "message": "View saved" })).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
@@ -1,51 +1,276 @@
+use std::convert::Infallible;
+use std::time::Duration;
+
+use async_stream::stream;
 use axum::extract::{Query, State};
 use axum::http::header::CONTENT_TYPE;
-use axum::response::IntoResponse;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
 use axum::Json;
+use dm_core::events::{AuditFilter, EventFilter, EventGroupBy, EventStore};
+use futures_util::Stream;
+use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
+use utoipa::IntoParams;
 
-use crate::handlers::err;
+use crate::handlers::{bearer_token, err, err_with, error_response};
 use crate::state::AppState;
 
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EventPageParams {
+    /// Page back from the newest event: empty for the first page, then the
+    /// previous page's `next_cursor`
+    pub cursor: Option<String>,
+}
+
 /// GET /api/events?source=core&case_id=...&limit=100
+///
+/// Returns the matching events, newest first. With `cursor`, `before_id` or
+/// `after_id` it returns `{ events, total, next_cursor }` instead; pass
+/// `next_cursor` back as `cursor` to load older events.
+#[utoipa::path(get, path = "/api/events", params(EventPageParams, EventFilter), responses((status = 200, description = "Matching events, or `{ events, total, next_cursor }` when paging"), (status = 400, description = "Invalid cursor")))]
 pub async fn query_events(
     State(state): State<AppState>,
-    Query(filter): Query<dm_core::events::EventFilter>,
+    Query(params): Query<EventPageParams>,
+    Query(mut filter): Query<dm_core::events::EventFilter>,
 ) -> impl IntoResponse {
-    match state.events.query(&filter) {
-        Ok(events) => Json(events).into_response(),
-        Err(e) => err(e).into_response(),
+    let paged = params.cursor.is_some() || filter.before_id.is_some() || filter.after_id.is_some();
+    if let Some(cursor) = params.cursor.as_deref().filter(|c| !c.is_empty()) {
+        match cursor.parse() {
+            Ok(id) => filter.before_id = Some(id),
+            Err(_) => {
+                return err_with(
+                    anyhow::anyhow!("Invalid cursor '{cursor}'"),
+                    StatusCode::BAD_REQUEST,
+                )
+            }
+        }
+    }
+    if !paged {
+        return match state.events.query(&filter) {
+            Ok(events) => Json(events).into_response(),
+            Err(e) => err(e),
+        };
+    }
+    match state.events.query_page(&filter) {
+        Ok(page) => Json(page).into_response(),
+        Err(e) => err(e),
     }
 }
 
+/// GET /api/events/stream?source=dataflow&case_id=...&after_id=...
+///
+/// Server-sent `event` messages for every new event matching the filter,
+/// as it is stored by this server. With `after_id`, stored events after
+/// that id are replayed first (up to `limit`, default 500), so a client can
+/// resume from the last id it saw. A `lagged` message reports how many
+/// events a slow client missed.
+#[utoipa::path(get, path = "/api/events/stream", params(EventFilter), responses((status = 200, description = "Server-sent events as they are stored", content_type = "text/event-stream")))]
+pub async fn stream_events(
+    State(state): State<AppState>,
+    Query(filter): Query<EventFilter>,
+) -> impl IntoResponse {
+    Sse::new(build_event_stream(state, filter)).keep_alive(
+        KeepAlive::new()
+            .interval(Duration::from_secs(15))
+            .text(": keep-alive"),
+    )
+}
+
+fn build_event_stream(
+    state: AppState,
+    filter: EventFilter,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream! {
+        // Subscribe before replaying so nothing falls in between.
+        let mut rx = EventStore::subscribe();
+        let mut last_id = filter.after_id.unwrap_or(0);
+        if filter.after_id.is_some() {
+            let backlog = EventFilter { before_id: None, ..filter.clone() };
+            match state.events.query(&backlog) {
+                Ok(events) => {
+                    for event in events.into_iter().rev() {
+                        last_id = last_id.max(event.id);
+                        yield Ok(sse_event(&event));
+                    }
+                }
+                Err(e) => yield Ok(Event::default().event("error").data(e.to_string())),
+            }
+        }
+        loop {
+            match rx.recv().await {
+                Ok(event) if event.id > last_id && filter.matches(&event) => {
+                    yield Ok(sse_event(&event));
+                }
+                Ok(_) => {}
+                Err(RecvError::Lagged(missed)) => {
+                    yield Ok(Event::default().event("lagged").data(missed.to_string()));
+                }
+                Err(RecvError::Closed) => return,
+            }
+        }
+    }
+}
+
+fn sse_event(event: &dm_core::events::Event) -> Event {
+    Event::default()
+        .event("event")
+        .id(event.id.to_string())
+        .json_data(event)
+        .unwrap_or_else(|e| Event::default().event("error").data(e.to_string()))
+}
+
 /// GET /api/events/count?source=core&case_id=...
+#[utoipa::path(get, path = "/api/events/count", params(EventFilter), responses((status = 200, description = "`{ count }` of matching events")))]
 pub async fn count_events(
     State(state): State<AppState>,
     Query(filter): Query<dm_core::events::EventFilter>,
 ) -> impl IntoResponse {
     match state.events.count(&filter) {
         Ok(c) => Json(serde_json::json!({ "count": c })).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
+    }
+}
+
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StatsParams {
+    /// Comma-separated dimensions: source, activity, level, hour
+    pub group_by: Option<String>,
+}
+
+/// GET /api/events/stats?group_by=hour,source&since=...
+///
+/// Event counts, error counts and error rates per group, for dashboards.
+/// Takes the same filters as `GET /api/events`; paging fields are ignored.
+#[utoipa::path(get, path = "/api/events/stats", params(StatsParams, EventFilter), responses((status = 200, description = "`{ group_by, buckets }` with event and error counts per group"), (status = 400, description = "Unknown group_by dimension")))]
+pub async fn event_stats(
+    State(state): State<AppState>,
+    Query(params): Query<StatsParams>,
+    Query(filter): Query<EventFilter>,
+) -> impl IntoResponse {
+    let group_by = match EventGroupBy::parse_list(params.group_by.as_deref().unwrap_or_default()) {
+        Ok(group_by) => group_by,
+        Err(e) => return err_with(e, StatusCode::BAD_REQUEST),
+    };
+    match state.events.aggregate(&filter, &group_by) {
+        Ok(buckets) => Json(serde_json::json!({
+            "group_by": group_by,
+            "buckets": buckets,
+        }))
+        .into_response(),
+        Err(e) => err(e),
+    }
+}
+
+/// GET /api/events/dfg?source=core&since=...
+///
+/// Directly-follows graph and per-case duration statistics of the matching
+/// events (newest `limit`, default 50 000), for rendering a process map.
+#[utoipa::path(get, path = "/api/events/dfg", params(EventFilter), responses((status = 200, description = "Directly-follows graph of the matching events")))]
+pub async fn event_dfg(
+    State(state): State<AppState>,
+    Query(filter): Query<EventFilter>,
+) -> impl IntoResponse {
+    match dm_core::events::mining::discover_dfg(&state.events, &filter) {
+        Ok(graph) => Json(graph).into_response(),
+        Err(e) => err(e),
     }
 }
 
 /// POST /api/events
+///
+/// Each source may only be written by the clients `[ingest]` in
+/// config.toml allows: anonymous requests, or a matching
+/// `Authorization: Bearer <token>`. Anything else is rejected with 403.
+#[utoipa::path(post, path = "/api/events", request_body = dm_core::events::Event, responses((status = 200, description = "`{ id }` of the stored event"), (status = 403, description = "Source not writable by this client")))]
 pub async fn ingest_event(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(event): Json<dm_core::events::Event>,
 ) -> impl IntoResponse {
+    if let Err(resp) = authorize_sources(&state, &headers, std::slice::from_ref(&event)) {
+        return *resp;
+    }
     match state.events.emit(&event) {
         Ok(id) => Json(serde_json::json!({ "id": id })).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
+    }
+}
+
+/// POST /api/events/batch — insert many events in one request
+#[utoipa::path(post, path = "/api/events/batch", request_body = Vec<dm_core::events::Event>, responses((status = 200, description = "`{ inserted }` count"), (status = 403, description = "A source is not writable by this client")))]
+pub async fn ingest_events_batch(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(events): Json<Vec<dm_core::events::Event>>,
+) -> impl IntoResponse {
+    if let Err(resp) = authorize_sources(&state, &headers, &events) {
+        return *resp;
+    }
+    match state.events.emit_many(&events) {
+        Ok(count) => Json(serde_json::json!({ "inserted": count })).into_response(),
+        Err(e) => err(e),
+    }
+}
+
+/// Reject the request unless its token (or lack of one) may write every
+/// event's source. A batch is all-or-nothing.
+fn authorize_sources(
+    state: &AppState,
+    headers: &HeaderMap,
+    events: &[dm_core::events::Event],
+) -> Result<(), Box<Response>> {
+    let config = dm_core::config::load_config(&state.home).map_err(|e| Box::new(err(e)))?;
+    let token = bearer_token(headers);
+    let Some(allowed) = config.ingest.allowed_sources(token) else {
+        return Err(Box::new(error_response(
+            StatusCode::FORBIDDEN,
+            "Unknown ingest token",
+        )));
+    };
+    let permitted = |source: &str| allowed.iter().any(|s| s == "*" || s == source);
+    if let Some(event) = events.iter().find(|event| !permitted(&event.source)) {
+        let client = match token {
+            Some(_) => "this token",
+            None => "anonymous clients",
+        };
+        return Err(Box::new(error_response(
+            StatusCode::FORBIDDEN,
+            format!(
+                "Source '{}' may not be written by {} (see [ingest] in config.toml)",
+                event.source, client
+            ),
+        )));
+    }
+    Ok(())
+}
+
+/// GET /api/audit?action=node&user=ci&since=...
+///
+/// Audited mutating calls, newest first: who (client address and token
+/// name) changed what, when, and with which status.
+#[utoipa::path(get, path = "/api/audit", params(AuditFilter), responses((status = 200, description = "Audit entries, newest first", body = Vec<dm_core::events::AuditEntry>)))]
+pub async fn query_audit(
+    State(state): State<AppState>,
+    Query(filter): Query<AuditFilter>,
+) -> impl IntoResponse {
+    match dm_core::events::query_audit(&state.events, &filter) {
+        Ok(entries) => Json(entries).into_response(),
+        Err(e) => err(e),
     }
 }
 
 /// GET /api/events/export?source=dataflow&format=xes
+#[utoipa::path(get, path = "/api/events/export", params(EventFilter), responses((status = 200, description = "Matching events as an XES log", content_type = "application/xml")))]
 pub async fn export_events(
     State(state): State<AppState>,
     Query(filter): Query<dm_core::events::EventFilter>,
 ) -> impl IntoResponse {
     match state.events.export_xes(&filter) {
         Ok(xes) => ([(CONTENT_TYPE, "application/xml")], xes).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
@@ -0,0 +1,272 @@
+use std::convert::Infallible;
+use std::time::Duration;
+
+use async_stream::stream;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use futures_util::Stream;
+use serde::Deserialize;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::JoinHandle;
+use utoipa::ToSchema;
+
+use dm_core::config::InstallMethodPreference;
+
+use crate::handlers::error_response;
+use crate::services::jobs::{JobKind, JobStatus, JobSubscription, JobSummary, JobUpdate};
+use crate::state::AppState;
+
+#[derive(Deserialize, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StartJobRequest {
+    /// Install a dora version (binary or source build).
+    Install {
+        version: Option<String>,
+        /// `auto`, `binary` or `source`; defaults to `install.method` in config
+        #[serde(default)]
+        #[schema(value_type = Option<String>)]
+        method: Option<InstallMethodPreference>,
+        /// Skip checksum and signature verification (`install.verify`)
+        #[serde(default)]
+        no_verify: bool,
+    },
+    /// Build and install a downloaded node.
+    NodeInstall {
+        id: String,
+        /// Install even when declared system packages are missing
+        #[serde(default)]
+        skip_sysdeps: bool,
+    },
+}
+
+/// POST /api/jobs — start an install in the background
+#[utoipa::path(post, path = "/api/jobs", request_body = StartJobRequest, responses((status = 202, description = "Job started")))]
+pub async fn start_job(
+    State(state): State<AppState>,
+    Json(req): Json<StartJobRequest>,
+) -> impl IntoResponse {
+    let (job, _) = spawn_job(&state, req);
+    (StatusCode::ACCEPTED, Json(job))
+}
+
+/// Register a job for `req` and run it in the background. The handle
+/// resolves to the same result that is recorded on the job.
+pub(crate) fn spawn_job(
+    state: &AppState,
+    req: StartJobRequest,
+) -> (JobSummary, JoinHandle<anyhow::Result<serde_json::Value>>) {
+    let (kind, target) = match &req {
+        StartJobRequest::Install { version, .. } => (
+            JobKind::Install,
+            version.clone().unwrap_or_else(|| "latest".to_string()),
+        ),
+        StartJobRequest::NodeInstall { id, .. } => (JobKind::NodeInstall, id.clone()),
+    };
+    let job = state.jobs.create(kind, &target);
+
+    let jobs = state.jobs.clone();
+    let home = state.home.clone();
+    let job_id = job.id.clone();
+    let handle = tokio::spawn(async move {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let forward = {
+            let jobs = jobs.clone();
+            let job_id = job_id.clone();
+            tokio::spawn(async move {
+                while let Some(progress) = rx.recv().await {
+                    jobs.progress(&job_id, &progress);
+                }
+            })
+        };
+
+        let result = match req {
+            StartJobRequest::Install {
+                version,
+                method,
+                no_verify,
+            } => {
+                let verify = no_verify.then_some(false);
+                dm_core::install::install_with(&home, version, method, verify, false, Some(tx))
+                    .await
+                    .map(|r| serde_json::to_value(r).unwrap_or_default())
+            }
+            StartJobRequest::NodeInstall { id, skip_sysdeps } => {
+                let options = dm_core::node::NodeInstallOptions { skip_sysdeps };
+                dm_core::node::install_node_with_options(&home, &id, options, Some(tx))
+                    .await
+                    .map(|n| serde_json::to_value(n).unwrap_or_default())
+            }
+        };
+        // The sender is dropped with the install future, so this drains.
+        let _ = forward.await;
+        jobs.finish(
+            &job_id,
+            result.as_ref().map(Clone::clone).map_err(|e| e.to_string()),
+        );
+        result
+    });
+
+    (job, handle)
+}
+
+/// GET /api/jobs
+#[utoipa::path(get, path = "/api/jobs", responses((status = 200, description = "Known jobs, newest first")))]
+pub async fn list_jobs(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.jobs.list())
+}
+
+/// GET /api/jobs/:id — job state with its full progress history
+#[utoipa::path(get, path = "/api/jobs/{id}", params(("id" = String, Path, description = "Job ID")), responses((status = 200, description = "Job snapshot"), (status = 404, description = "Unknown job")))]
+pub async fn get_job(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
+    match state.jobs.get(&id) {
+        Some(job) => Json(job).into_response(),
+        None => error_response(StatusCode::NOT_FOUND, format!("Job '{}' not found", id)),
+    }
+}
+
+/// GET /api/jobs/:id/events — SSE `job` events: the replayed history,
+/// then live updates until the job finishes.
+#[utoipa::path(get, path = "/api/jobs/{id}/events", params(("id" = String, Path, description = "Job ID")), responses((status = 200, description = "Server-sent job updates"), (status = 404, description = "Unknown job")))]
+pub async fn job_events(State(state): State<AppState>, Path(id): Path<String>) -> Response {
+    match state.jobs.subscribe(&id) {
+        Some(subscription) => job_sse(subscription),
+        None => error_response(StatusCode::NOT_FOUND, format!("Job '{}' not found", id)),
+    }
+}
+
+/// GET /api/install/progress — SSE progress of the running dora install,
+/// or of the most recent one when none is running.
+#[utoipa::path(get, path = "/api/install/progress", responses((status = 200, description = "Server-sent install job updates"), (status = 404, description = "No install has been started")))]
+pub async fn install_progress(State(state): State<AppState>) -> Response {
+    match state
+        .jobs
+        .latest(JobKind::Install)
+        .and_then(|id| state.jobs.subscribe(&id))
+    {
+        Some(subscription) => job_sse(subscription),
+        None => error_response(StatusCode::NOT_FOUND, "No install has been started"),
+    }
+}
+
+fn job_sse(subscription: JobSubscription) -> Response {
+    Sse::new(job_update_stream(subscription))
+        .keep_alive(
+            KeepAlive::new()
+                .interval(Duration::from_secs(10))
+                .text(": keep-alive"),
+        )
+        .into_response()
+}
+
+fn job_update_stream(
+    subscription: JobSubscription,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    let JobSubscription {
+        history,
+        finished,
+        mut updates,
+    } = subscription;
+    stream! {
+        let mut last_seq = 0;
+        for update in history {
+            last_seq = update.seq;
+            yield Ok(job_event(&update));
+        }
+        if finished {
+            return;
+        }
+        loop {
+            let update = match updates.recv().await {
+                Ok(update) => update,
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return,
+            };
+            if update.seq <= last_seq {
+                continue;
+            }
+            last_seq = update.seq;
+            yield Ok(job_event(&update));
+            if update.status != JobStatus::Running {
+                return;
+            }
+        }
+    }
+}
+
+fn job_event(update: &JobUpdate) -> Event {
+    Event::default()
+        .event("job")
+        .id(update.seq.to_string())
+        .json_data(update)
+        .unwrap_or_else(|e| Event::default().event("error").data(e.to_string()))
+}
+
+/// GET /api/jobs/:id/ws — replay history, then stream live updates until
+/// the job finishes.
+pub async fn job_ws(
+    ws: WebSocketUpgrade,
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Response {
+    match state.jobs.subscribe(&id) {
+        Some(subscription) => ws.on_upgrade(move |socket| handle_job_ws(socket, subscription)),
+        None => error_response(StatusCode::NOT_FOUND, format!("Job '{}' not found", id)),
+    }
+}
+
+async fn handle_job_ws(mut socket: WebSocket, subscription: JobSubscription) {
+    let JobSubscription {
+        history,
+        finished,
+        mut updates,
+    } = subscription;
+
+    let mut last_seq = 0;
+    for update in history {
+        last_seq = update.seq;
+        if send_update(&mut socket, &update).await.is_err() {
+            return;
+        }
+    }
+
+    if !finished {
+        loop {
+            tokio::select! {
+                recv = socket.recv() => {
+                    match recv {
+                        Some(Ok(Message::Close(_))) | None | Some(Err(_)) => return,
+                        Some(Ok(_)) => {}
+                    }
+                }
+                update = updates.recv() => {
+                    let update = match update {
+                        Ok(update) => update,
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    };
+                    if update.seq <= last_seq {
+                        continue;
+                    }
+                    last_seq = update.seq;
+                    if send_update(&mut socket, &update).await.is_err() {
+                        return;
+                    }
+                    if update.status != JobStatus::Running {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+async fn send_update(socket: &mut WebSocket, update: &JobUpdate) -> Result<(), axum::Error> {
+    let payload = serde_json::to_string(update).unwrap_or_default();
+    socket.send(Message::Text(payload.into())).await
+}
@@ -3,9 +3,10 @@ use axum::extract::{Path as AxumPath, Query, State};
 use axum::http::{header, HeaderValue, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::Json;
+use dm_core::error::DmError;
 use serde::Deserialize;
 
-use crate::handlers::err;
+use crate::handlers::{err, err_with, error_response};
 use crate::services;
 use crate::services::media::{MediaBackendStatus, MediaStatus};
 use crate::services::message::{MessageFilter, MessageService, StreamDescriptor, StreamViewer};
@@ -55,7 +56,7 @@ pub async fn get_interaction(
 
     match result {
         Ok(snapshot) => Json(snapshot).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
 
@@ -92,7 +93,7 @@ pub async fn push_message(
             });
             Json(serde_json::json!({ "seq": seq })).into_response()
         }
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
 
@@ -130,7 +131,7 @@ pub async fn list_messages(
 
     match result {
         Ok(resp) => Json(resp).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
 
@@ -148,7 +149,7 @@ pub async fn get_snapshots(
 
     match result {
         Ok(resp) => Json(resp).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
 
@@ -174,7 +175,7 @@ pub async fn list_streams(
 
     match result {
         Ok(streams) => Json(serde_json::json!({ "streams": streams })).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
 
@@ -207,16 +208,13 @@ pub async fn get_stream(
                     .and_then(serde_json::Value::as_str)
                     == Some(stream_id.as_str())
             })
-            .ok_or_else(|| anyhow::anyhow!("Stream not found"))?;
+            .ok_or_else(|| DmError::not_found("stream", &stream_id))?;
         stream_descriptor_from_snapshot(&state, &media_status, snapshot)
     })();
 
     match result {
         Ok(stream) => Json(stream).into_response(),
-        Err(e) if e.to_string() == "Stream not found" => {
-            (StatusCode::NOT_FOUND, e.to_string()).into_response()
-        }
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
 
@@ -533,12 +531,12 @@ pub async fn serve_artifact_file(
 ) -> impl IntoResponse {
     let relative = match services::normalize_relative_path(&relative_path) {
         Ok(path) => path,
-        Err(err) => return (StatusCode::BAD_REQUEST, err.to_string()).into_response(),
+        Err(err) => return err_with(err, StatusCode::BAD_REQUEST),
     };
 
     let full_path = dm_core::runs::run_out_dir(&state.home, &run_id).join(&relative);
     if !full_path.exists() {
-        return (StatusCode::NOT_FOUND, "Artifact file not found").into_response();
+        return error_response(StatusCode::NOT_FOUND, "Artifact file not found");
     }
 
     match tokio::fs::read(&full_path).await {
@@ -552,6 +550,6 @@ pub async fn serve_artifact_file(
             );
             resp
         }
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
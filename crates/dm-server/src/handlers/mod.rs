@@ -1,6 +1,7 @@
 pub(crate) mod bridge_socket;
 pub(crate) mod dataflow;
 pub(crate) mod events;
+pub(crate) mod jobs;
 pub(crate) mod messages;
 pub(crate) mod nodes;
 pub(crate) mod run_ws;
@@ -9,37 +10,99 @@ pub(crate) mod runtime;
 pub(crate) mod system;
 pub(crate) mod web;
 
-use axum::http::StatusCode;
-use axum::response::IntoResponse;
+use axum::http::header::AUTHORIZATION;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use dm_core::error::{ErrorCode, ErrorReport};
 
 pub use dataflow::{
-    delete_dataflow, get_dataflow, get_dataflow_config_schema, get_dataflow_history_version,
-    get_dataflow_meta, get_dataflow_view, import_dataflows, inspect_dataflow,
-    list_dataflow_history, list_dataflows, restore_dataflow_history_version, save_dataflow,
-    save_dataflow_meta, save_dataflow_view, start_dataflow, stop_dataflow,
+    convert_graph, delete_dataflow, diff_dataflow_history_version, download_dataflow,
+    export_dataflow, get_dataflow, get_dataflow_config_schema, get_dataflow_history_version,
+    get_dataflow_meta, get_dataflow_view, import_dataflow_bundle, import_dataflows,
+    inspect_dataflow, list_dataflow_history, list_dataflows, restore_dataflow_history_version,
+    save_dataflow, save_dataflow_meta, save_dataflow_view, start_dataflow, start_tagged_dataflows,
+    stop_dataflow, stop_tagged_dataflows, sync_dataflows, upload_dataflow, validate_all_dataflows,
+    validate_graph,
 };
-pub use events::{count_events, export_events, ingest_event, query_events};
+pub use events::{
+    count_events, event_dfg, event_stats, export_events, ingest_event, ingest_events_batch,
+    query_audit, query_events, stream_events,
+};
+pub use jobs::{get_job, install_progress, job_events, job_ws, list_jobs, start_job};
 pub use messages::{
     get_interaction, get_snapshots, get_stream, list_messages, list_streams, messages_ws, node_ws,
     push_message, serve_artifact_file,
 };
 pub use nodes::{
-    create_node, get_node_config, get_node_file_content, get_node_files, import_node, install_node,
-    list_nodes, node_readme, node_status, open_node, save_node_config, serve_node_artifact_file,
-    uninstall_node,
+    create_node, export_node, get_node_config, get_node_file_content, get_node_files, import_node,
+    import_node_archive, install_node, install_nodes, list_collections, list_nodes, list_registry,
+    node_env_preview, node_readme, node_status, open_node, outdated_nodes, pin_node,
+    save_node_config, serve_node_artifact_file, set_node_schema, test_node, uninstall_node,
+    unpin_node, validate_node_config,
 };
 pub use run_ws::run_ws;
 pub use runs::{
     delete_runs, get_active_run, get_run, get_run_dataflow, get_run_logs, get_run_metrics,
-    get_run_transpiled, get_run_view, list_runs, start_run, stop_run, stream_run_logs,
-    tail_run_logs,
+    get_run_metrics_history, get_run_transpiled, get_run_view, import_run_events, list_runs,
+    start_run, stop_run, stream_run_logs, tail_node_log, tail_run_logs,
 };
 pub use runtime::{down, install, uninstall, up, use_version};
 pub use system::{
-    doctor, get_config, install_media, media_status, status, update_config, versions,
+    doctor, get_config, install_media, media_status, status, status_stream, update_config, versions,
 };
 pub use web::serve_web;
 
-pub(crate) fn err(e: impl std::fmt::Display) -> impl IntoResponse {
-    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+/// Error response carrying a [`ErrorReport`] (`{ code, error, details }`)
+/// with the status of the error's category; uncategorized failures are 500.
+pub(crate) fn err(e: impl Into<anyhow::Error>) -> Response {
+    err_with(e, StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Like [`err`], but failures without a category get `status` — e.g. 400
+/// for operations whose remaining failures are bad input.
+pub(crate) fn err_with(e: impl Into<anyhow::Error>, status: StatusCode) -> Response {
+    let mut report = ErrorReport::from_error(&e.into());
+    let status = if report.code == ErrorCode::Internal {
+        report.code = code_for(status);
+        status
+    } else {
+        status_for(report.code)
+    };
+    (status, Json(report)).into_response()
+}
+
+/// Error response for a request the handler itself rejects.
+pub(crate) fn error_response(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(ErrorReport::new(code_for(status), message))).into_response()
+}
+
+/// Token of an `Authorization: Bearer <token>` header.
+pub(crate) fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+/// HTTP status of each error category.
+pub(crate) fn status_for(code: ErrorCode) -> StatusCode {
+    match code {
+        ErrorCode::NotFound => StatusCode::NOT_FOUND,
+        ErrorCode::Conflict => StatusCode::CONFLICT,
+        ErrorCode::DependencyMissing => StatusCode::FAILED_DEPENDENCY,
+        ErrorCode::External => StatusCode::BAD_GATEWAY,
+        ErrorCode::Invalid => StatusCode::BAD_REQUEST,
+        ErrorCode::Io | ErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn code_for(status: StatusCode) -> ErrorCode {
+    match status {
+        StatusCode::NOT_FOUND => ErrorCode::NotFound,
+        StatusCode::CONFLICT => ErrorCode::Conflict,
+        status if status.is_client_error() => ErrorCode::Invalid,
+        _ => ErrorCode::Internal,
+    }
 }
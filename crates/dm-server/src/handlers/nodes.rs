@@ -1,12 +1,13 @@
-use axum::extract::{Path, State};
+use axum::extract::{Multipart, Path, Query, State};
 use axum::http::header::{self, HeaderValue};
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::Json;
+use dm_core::error::DmError;
 use serde::Deserialize;
 use std::process::Command;
 
-use crate::handlers::err;
+use crate::handlers::{err, err_with, error_response};
 use crate::state::AppState;
 
 use utoipa::ToSchema;
@@ -16,7 +17,7 @@ use utoipa::ToSchema;
 pub async fn list_nodes(State(state): State<AppState>) -> impl IntoResponse {
     match dm_core::node::list_nodes(&state.home) {
         Ok(nodes) => Json(nodes).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
 
@@ -28,14 +29,54 @@ pub async fn node_status(
 ) -> impl IntoResponse {
     match dm_core::node::node_status(&state.home, &id) {
         Ok(Some(entry)) => Json(entry).into_response(),
-        Ok(None) => (StatusCode::NOT_FOUND, format!("Node '{}' not found", id)).into_response(),
-        Err(e) => err(e).into_response(),
+        Ok(None) => err(DmError::not_found("node", &id)),
+        Err(e) => err(e),
     }
 }
 
+#[derive(Deserialize, ToSchema)]
+pub struct RegistryParams {
+    /// Case-insensitive match on id, description, category or tags
+    pub q: Option<String>,
+    /// Try the configured remote registry before falling back (default true)
+    pub refresh: Option<bool>,
+}
+
+/// GET /api/registry?q=camera
+#[utoipa::path(get, path = "/api/registry", params(("q" = Option<String>, Query), ("refresh" = Option<bool>, Query)), responses((status = 200, description = "Registry nodes and the layer they came from (`source`: bundled, cache or remote)")))]
+pub async fn list_registry(
+    State(state): State<AppState>,
+    Query(params): Query<RegistryParams>,
+) -> impl IntoResponse {
+    let listing = if params.refresh.unwrap_or(true) {
+        dm_core::node::hub::registry_listing_online(&state.home).await
+    } else {
+        dm_core::node::hub::registry_listing(&state.home)
+    };
+    Json(listing.search(params.q.as_deref().unwrap_or(""))).into_response()
+}
+
+/// GET /api/nodes/outdated
+#[utoipa::path(get, path = "/api/nodes/outdated", responses((status = 200, description = "Installed nodes compared with the newest release on PyPI / crates.io")))]
+pub async fn outdated_nodes(State(state): State<AppState>) -> impl IntoResponse {
+    match dm_core::node::check_outdated_nodes(&state.home).await {
+        Ok(checks) => Json(checks).into_response(),
+        Err(e) => err(e),
+    }
+}
+
+/// GET /api/collections
+#[utoipa::path(get, path = "/api/collections", responses((status = 200, description = "Node collections from the registry and config.toml, installable as `dm node install @name`")))]
+pub async fn list_collections(State(state): State<AppState>) -> impl IntoResponse {
+    Json(dm_core::node::hub::list_collections(&state.home)).into_response()
+}
+
 #[derive(Deserialize, ToSchema)]
 pub struct InstallNodeRequest {
     pub id: String,
+    /// Install even when declared system packages are missing
+    #[serde(default)]
+    pub skip_sysdeps: bool,
 }
 
 /// POST /api/nodes/install
@@ -44,9 +85,42 @@ pub async fn install_node(
     State(state): State<AppState>,
     Json(req): Json<InstallNodeRequest>,
 ) -> impl IntoResponse {
-    match dm_core::node::install_node(&state.home, &req.id).await {
+    let options = dm_core::node::NodeInstallOptions {
+        skip_sysdeps: req.skip_sysdeps,
+    };
+    match dm_core::node::install_node_with_options(&state.home, &req.id, options, None).await {
         Ok(entry) => Json(entry).into_response(),
-        Err(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        Err(e) => err_with(e, StatusCode::BAD_REQUEST),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct InstallNodesRequest {
+    /// Node id(s) or @collection(s)
+    pub ids: Vec<String>,
+    /// Install even when declared system packages are missing
+    #[serde(default)]
+    pub skip_sysdeps: bool,
+    /// Nodes to install at once (default: `install.node_parallelism`)
+    pub parallelism: Option<usize>,
+}
+
+/// POST /api/nodes/install-batch — per-node outcomes, in request order
+#[utoipa::path(post, path = "/api/nodes/install-batch", request_body = InstallNodesRequest, responses((status = 200, description = "Outcome of each node install")))]
+pub async fn install_nodes(
+    State(state): State<AppState>,
+    Json(req): Json<InstallNodesRequest>,
+) -> impl IntoResponse {
+    let options = dm_core::node::NodeInstallOptions {
+        skip_sysdeps: req.skip_sysdeps,
+    };
+    let ids = match dm_core::node::hub::expand_node_ids(&state.home, &req.ids) {
+        Ok(ids) => ids,
+        Err(e) => return err_with(e, StatusCode::BAD_REQUEST),
+    };
+    match dm_core::node::install_nodes(&state.home, &ids, options, req.parallelism).await {
+        Ok(outcomes) => Json(outcomes).into_response(),
+        Err(e) => err(e),
     }
 }
 
@@ -97,13 +171,101 @@ pub async fn import_node(
 
     match result {
         Ok(node) => Json(node).into_response(),
-        Err(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        Err(e) => err_with(e, StatusCode::BAD_REQUEST),
+    }
+}
+
+/// GET /api/nodes/:id/export — the node as a `.dmnode` archive
+#[utoipa::path(get, path = "/api/nodes/{id}/export", params(("id" = String, Path)), responses((status = 200, description = "Node archive as an attachment", content_type = "application/gzip"), (status = 404, description = "Node not found")))]
+pub async fn export_node(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    if dm_core::node::resolve_node_dir(&state.home, &id).is_none() {
+        return err(DmError::not_found("node", &id));
+    }
+    let file_name = format!("{}.{}", id, dm_core::node::NODE_ARCHIVE_EXTENSION);
+    let out = state.home.join("tmp").join(&file_name);
+    let packed = dm_core::node::pack_node(&state.home, &id, &out).and_then(|path| {
+        let bytes = std::fs::read(&path)?;
+        let _ = std::fs::remove_file(&path);
+        Ok(bytes)
+    });
+    match packed {
+        Ok(bytes) => (
+            [
+                (header::CONTENT_TYPE, "application/gzip".to_string()),
+                (
+                    header::CONTENT_DISPOSITION,
+                    format!("attachment; filename=\"{}\"", file_name),
+                ),
+            ],
+            bytes,
+        )
+            .into_response(),
+        Err(e) => err(e),
+    }
+}
+
+/// POST /api/nodes/import-archive
+#[utoipa::path(post, path = "/api/nodes/import-archive", request_body(content_type = "multipart/form-data", description = "`file`: a `.dmnode` archive; optional `id` (default: the id in its dm.json)"), responses((status = 200, description = "Imported node"), (status = 400, description = "Missing file or invalid archive"), (status = 409, description = "Node is locked")))]
+pub async fn import_node_archive(
+    State(state): State<AppState>,
+    mut form: Multipart,
+) -> impl IntoResponse {
+    let mut file = None;
+    let mut id = None;
+    loop {
+        let field = match form.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => return err_with(e, StatusCode::BAD_REQUEST),
+        };
+        match field.name().unwrap_or_default() {
+            "file" => match field.bytes().await {
+                Ok(bytes) => file = Some(bytes),
+                Err(e) => return err_with(e, StatusCode::BAD_REQUEST),
+            },
+            "id" => match field.text().await {
+                Ok(text) => id = Some(text.trim().to_string()).filter(|id| !id.is_empty()),
+                Err(e) => return err_with(e, StatusCode::BAD_REQUEST),
+            },
+            _ => {}
+        }
+    }
+    let Some(bytes) = file else {
+        return error_response(StatusCode::BAD_REQUEST, "Missing `file` field");
+    };
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    let upload = state.home.join("tmp").join(format!(
+        "upload-{}.{}",
+        nanos,
+        dm_core::node::NODE_ARCHIVE_EXTENSION
+    ));
+    let result = upload
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&upload, &bytes))
+        .map_err(anyhow::Error::from)
+        .and_then(|_| dm_core::node::import_archive(&state.home, &upload, id.as_deref()));
+    let _ = std::fs::remove_file(&upload);
+
+    match result {
+        Ok(node) => Json(node).into_response(),
+        Err(e) => err_with(e, StatusCode::BAD_REQUEST),
     }
 }
 
 #[derive(Deserialize, ToSchema)]
 pub struct UninstallNodeRequest {
     pub id: String,
+    /// Tag saved dataflows that still use the node as `missing-node:<id>`
+    #[serde(default)]
+    pub tag_dataflows: bool,
 }
 
 /// POST /api/nodes/uninstall
@@ -112,10 +274,14 @@ pub async fn uninstall_node(
     State(state): State<AppState>,
     Json(req): Json<UninstallNodeRequest>,
 ) -> impl IntoResponse {
-    match dm_core::node::uninstall_node(&state.home, &req.id) {
-        Ok(()) => Json(serde_json::json!({ "message": format!("Uninstalled node '{}'", req.id) }))
-            .into_response(),
-        Err(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    match dm_core::node::uninstall_node_with(&state.home, &req.id, req.tag_dataflows) {
+        Ok(report) => Json(serde_json::json!({
+            "message": format!("Uninstalled node '{}'", req.id),
+            "referencing_dataflows": report.referencing_dataflows,
+            "tagged_dataflows": report.tagged_dataflows,
+        }))
+        .into_response(),
+        Err(e) => err_with(e, StatusCode::BAD_REQUEST),
     }
 }
 
@@ -124,6 +290,13 @@ pub struct CreateNodeRequest {
     pub id: String,
     #[serde(default)]
     pub description: String,
+    /// `Name` or `Name <email>`
+    pub author: Option<String>,
+    /// SPDX license identifier
+    pub license: Option<String>,
+    /// `requires-python`, e.g. `3.11` or `>=3.10`
+    pub python: Option<String>,
+    pub category: Option<String>,
 }
 
 /// POST /api/nodes/create
@@ -132,12 +305,21 @@ pub async fn create_node(
     State(state): State<AppState>,
     Json(req): Json<CreateNodeRequest>,
 ) -> impl IntoResponse {
-    match dm_core::node::create_node(&state.home, &req.id, &req.description) {
+    let scaffold = dm_core::node::NodeScaffold {
+        description: req.description,
+        author: req.author,
+        license: req.license,
+        python: req.python,
+        category: req.category,
+    };
+    match dm_core::node::create_node_with(&state.home, &req.id, &scaffold) {
         Ok(entry) => Json(entry).into_response(),
-        Err(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        Err(e) => err_with(e, StatusCode::BAD_REQUEST),
     }
 }
 
+/// GET /api/nodes/:id/readme
+#[utoipa::path(get, path = "/api/nodes/{id}/readme", params(("id" = String, Path, description = "Node ID")), responses((status = 200, description = "README of the node as text", content_type = "text/plain")))]
 pub async fn node_readme(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -158,12 +340,25 @@ pub async fn get_node_config(
 ) -> impl IntoResponse {
     match dm_core::node::get_node_config(&state.home, &id) {
         Ok(config) => Json(config).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
 
-/// POST /api/nodes/:id/config
-#[utoipa::path(post, path = "/api/nodes/{id}/config", params(("id" = String, Path, description = "Node ID")), responses((status = 200, description = "Config saved")))]
+/// GET /api/nodes/:id/env-preview
+#[utoipa::path(get, path = "/api/nodes/{id}/env-preview", params(("id" = String, Path, description = "Node ID")), responses((status = 200, description = "Executable, PATH/PYTHONPATH and env vars transpile would give the node"), (status = 404, description = "Node not installed")))]
+pub async fn node_env_preview(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match dm_core::dataflow::preview_node_env(&state.home, &id) {
+        Ok(Some(preview)) => Json(preview).into_response(),
+        Ok(None) => err(DmError::not_found("node", &id)),
+        Err(e) => err(e),
+    }
+}
+
+/// POST|PUT /api/nodes/:id/config
+#[utoipa::path(put, path = "/api/nodes/{id}/config", params(("id" = String, Path, description = "Node ID")), responses((status = 200, description = "Config saved"), (status = 404, description = "Unknown node"), (status = 422, description = "Config breaks the node's config_schema; body lists each violation")))]
 pub async fn save_node_config(
     State(state): State<AppState>,
     Path(id): Path<String>,
@@ -171,33 +366,128 @@ pub async fn save_node_config(
 ) -> impl IntoResponse {
     match dm_core::node::save_node_config(&state.home, &id, &config) {
         Ok(()) => Json(serde_json::json!({ "message": "Config saved" })).into_response(),
-        Err(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        Err(e) => match e.downcast::<dm_core::node::InvalidConfig>() {
+            Ok(invalid) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                Json(dm_core::node::ConfigValidation {
+                    valid: false,
+                    errors: invalid.errors,
+                }),
+            )
+                .into_response(),
+            Err(e) => err_with(e, StatusCode::BAD_REQUEST),
+        },
+    }
+}
+
+/// PUT /api/nodes/:id/schema
+#[utoipa::path(put, path = "/api/nodes/{id}/schema", params(("id" = String, Path, description = "Node ID")), responses((status = 200, description = "Config schema stored in dm.json"), (status = 400, description = "Invalid schema or unknown node")))]
+pub async fn set_node_schema(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(schema): Json<serde_json::Value>,
+) -> impl IntoResponse {
+    match dm_core::node::set_config_schema(&state.home, &id, schema) {
+        Ok(schema) => Json(schema).into_response(),
+        Err(e) => err_with(e, StatusCode::BAD_REQUEST),
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct PinNodeRequest {
+    pub version: String,
+}
+
+/// POST /api/nodes/:id/pin
+#[utoipa::path(post, path = "/api/nodes/{id}/pin", params(("id" = String, Path, description = "Node ID")), request_body = PinNodeRequest, responses((status = 200, description = "Node pinned"), (status = 400, description = "Unknown node or empty version")))]
+pub async fn pin_node(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(req): Json<PinNodeRequest>,
+) -> impl IntoResponse {
+    match dm_core::node::pin_node(&state.home, &id, &req.version) {
+        Ok(node) => Json(node).into_response(),
+        Err(e) => err_with(e, StatusCode::BAD_REQUEST),
+    }
+}
+
+/// POST /api/nodes/:id/unpin
+#[utoipa::path(post, path = "/api/nodes/{id}/unpin", params(("id" = String, Path, description = "Node ID")), responses((status = 200, description = "Node unpinned"), (status = 400, description = "Unknown node")))]
+pub async fn unpin_node(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match dm_core::node::unpin_node(&state.home, &id) {
+        Ok(node) => Json(node).into_response(),
+        Err(e) => err_with(e, StatusCode::BAD_REQUEST),
+    }
+}
+
+#[derive(Deserialize, Default, ToSchema)]
+pub struct TestNodeRequest {
+    /// Seconds the node must keep running (or exit cleanly) to pass; 5 by default
+    pub timeout_secs: Option<u64>,
+}
+
+/// POST /api/nodes/:id/test
+#[utoipa::path(post, path = "/api/nodes/{id}/test", params(("id" = String, Path, description = "Node ID")), request_body = TestNodeRequest, responses((status = 200, description = "Probe result; `ok` is false when the node cannot start"), (status = 404, description = "Unknown node"), (status = 400, description = "Node not installed")))]
+pub async fn test_node(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    req: Option<Json<TestNodeRequest>>,
+) -> impl IntoResponse {
+    let window = req
+        .and_then(|Json(req)| req.timeout_secs)
+        .map(std::time::Duration::from_secs)
+        .unwrap_or(dm_core::node::DEFAULT_TEST_WINDOW);
+    match dm_core::node::test_node(&state.home, &id, window).await {
+        Ok(probe) => Json(probe).into_response(),
+        Err(e) => err_with(e, StatusCode::BAD_REQUEST),
+    }
+}
+
+/// POST /api/nodes/:id/config/validate
+#[utoipa::path(post, path = "/api/nodes/{id}/config/validate", params(("id" = String, Path, description = "Node ID")), responses((status = 200, description = "Config is valid"), (status = 422, description = "Config violates the node's schema; errors carry JSON pointers")))]
+pub async fn validate_node_config(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Json(config): Json<serde_json::Value>,
+) -> impl IntoResponse {
+    match dm_core::node::validate_node_config(&state.home, &id, &config) {
+        Ok(report) if report.valid => Json(report).into_response(),
+        Ok(report) => (StatusCode::UNPROCESSABLE_ENTITY, Json(report)).into_response(),
+        Err(e) => err_with(e, StatusCode::NOT_FOUND),
     }
 }
 
 /// GET /api/nodes/:id/files
+#[utoipa::path(get, path = "/api/nodes/{id}/files", params(("id" = String, Path, description = "Node ID")), responses((status = 200, description = "File tree of the node"), (status = 404, description = "Node not found")))]
 pub async fn get_node_files(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
     match dm_core::node::git_like_file_tree(&state.home, &id) {
         Ok(files) => Json(files).into_response(),
-        Err(e) => node_file_err(e, &id).into_response(),
+        // Whatever is left uncategorized is a rejected file path.
+        Err(e) => err_with(e, StatusCode::BAD_REQUEST),
     }
 }
 
 /// GET /api/nodes/:id/files/{*path}
+#[utoipa::path(get, path = "/api/nodes/{id}/files/{path}", params(("id" = String, Path, description = "Node ID"), ("path" = String, Path, description = "File path within the node")), responses((status = 200, description = "File content", content_type = "text/plain"), (status = 404, description = "Node or file not found")))]
 pub async fn get_node_file_content(
     State(state): State<AppState>,
     Path((id, file_path)): Path<(String, String)>,
 ) -> impl IntoResponse {
     match dm_core::node::read_node_file(&state.home, &id, &file_path) {
         Ok(content) => content.into_response(),
-        Err(e) => node_file_err(e, &id).into_response(),
+        // Whatever is left uncategorized is a rejected file path.
+        Err(e) => err_with(e, StatusCode::BAD_REQUEST),
     }
 }
 
 /// GET /api/nodes/:id/artifacts/{*path}
+#[utoipa::path(get, path = "/api/nodes/{id}/artifacts/{path}", params(("id" = String, Path, description = "Node ID"), ("path" = String, Path, description = "File path within the node")), responses((status = 200, description = "Raw file with a content type guessed from its name"), (status = 404, description = "Node or file not found")))]
 pub async fn serve_node_artifact_file(
     State(state): State<AppState>,
     Path((id, file_path)): Path<(String, String)>,
@@ -213,25 +503,11 @@ pub async fn serve_node_artifact_file(
             );
             resp
         }
-        Err(e) => node_file_err(e, &id).into_response(),
+        // Whatever is left uncategorized is a rejected file path.
+        Err(e) => err_with(e, StatusCode::BAD_REQUEST),
     }
 }
 
-fn node_file_err(e: anyhow::Error, id: &str) -> (StatusCode, String) {
-    let message = e.to_string();
-    if message.contains("Invalid node file path") {
-        return (StatusCode::BAD_REQUEST, message);
-    }
-    if message.contains("does not exist")
-        || message.contains("No such file or directory")
-        || message == format!("Node '{}' not found", id)
-    {
-        return (StatusCode::NOT_FOUND, message);
-    }
-
-    (StatusCode::INTERNAL_SERVER_ERROR, message)
-}
-
 #[derive(Deserialize, ToSchema)]
 pub struct OpenNodeRequest {
     pub target: String,
@@ -245,7 +521,7 @@ pub async fn open_node(
     Json(req): Json<OpenNodeRequest>,
 ) -> impl IntoResponse {
     let Some(node_path) = dm_core::node::resolve_node_dir(&state.home, &id) else {
-        return (StatusCode::NOT_FOUND, format!("Node '{}' not found", id)).into_response();
+        return err(DmError::not_found("node", &id));
     };
 
     let result = match req.target.as_str() {
@@ -261,11 +537,10 @@ pub async fn open_node(
             .arg(&node_path)
             .status(),
         _ => {
-            return (
+            return error_response(
                 StatusCode::BAD_REQUEST,
                 format!("Unsupported open target '{}'", req.target),
-            )
-                .into_response();
+            );
         }
     };
 
@@ -274,15 +549,13 @@ pub async fn open_node(
             "message": format!("Opened '{}' in {}", id, req.target)
         }))
         .into_response(),
-        Ok(status) => (
+        Ok(status) => error_response(
             StatusCode::BAD_REQUEST,
             format!("Failed to open '{}': launcher exited with {}", id, status),
-        )
-            .into_response(),
-        Err(e) => (
+        ),
+        Err(e) => error_response(
             StatusCode::BAD_REQUEST,
             format!("Failed to open '{}': {}", id, e),
-        )
-            .into_response(),
+        ),
     }
 }
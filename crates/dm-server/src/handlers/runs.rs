@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::convert::Infallible;
 use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
@@ -10,10 +11,11 @@ use axum::http::StatusCode;
 use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::response::IntoResponse;
 use axum::Json;
+use dm_core::error::{ErrorCode, ErrorReport};
 use futures_util::Stream;
 use serde::Deserialize;
 
-use crate::handlers::err;
+use crate::handlers::{err, err_with, error_response, status_for};
 use crate::services::media::MediaBackendStatus;
 use crate::state::AppState;
 
@@ -32,6 +34,12 @@ pub struct LogTailParams {
     pub offset: Option<u64>,
 }
 
+#[derive(Deserialize, ToSchema)]
+pub struct NodeTailParams {
+    /// Number of lines, default 200, at most 1000
+    pub lines: Option<usize>,
+}
+
 #[derive(Deserialize)]
 pub struct LogStreamParams {
     pub tail_lines: Option<usize>,
@@ -43,6 +51,21 @@ pub struct StartRunRequest {
     pub name: Option<String>,
     pub force: Option<bool>,
     pub view_json: Option<String>,
+    /// Start even if a run with the same name is already active
+    #[serde(default)]
+    pub allow_multiple: Option<bool>,
+    /// Run on a dedicated coordinator/daemon (default: `isolate_runs`)
+    #[serde(default)]
+    pub isolated: Option<bool>,
+    /// Installed dora version for the dedicated runtime; implies `isolated`
+    #[serde(default)]
+    pub dora_version: Option<String>,
+    /// Overrides for the dataflow's `variables:` defaults
+    #[serde(default)]
+    pub variables: Option<BTreeMap<String, String>>,
+    /// Download and install nodes the dataflow uses that are not installed yet
+    #[serde(default)]
+    pub auto_install: Option<bool>,
 }
 
 #[derive(Deserialize, ToSchema)]
@@ -70,7 +93,7 @@ pub async fn list_runs(
 
     match dm_core::runs::list_runs_filtered(&state.home, limit, offset, &filter) {
         Ok(result) => Json(result).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
 
@@ -101,7 +124,7 @@ pub async fn get_active_run(
             }
             Json(result.runs).into_response()
         }
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
 
@@ -114,40 +137,64 @@ pub async fn get_run_metrics(
     match dm_core::runs::get_run_metrics(&state.home, &id) {
         Ok(Some(metrics)) => Json(metrics).into_response(),
         Ok(None) => Json(dm_core::runs::RunMetrics::default()).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct MetricsHistoryParams {
+    /// Only samples after this RFC 3339 timestamp
+    pub since: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// GET /api/runs/:id/metrics/history
+#[utoipa::path(get, path = "/api/runs/{id}/metrics/history", params(("id" = String, Path), ("since" = Option<String>, Query, description = "Only samples after this RFC 3339 timestamp"), ("limit" = Option<i64>, Query, description = "Newest samples to return (default 720)")), responses((status = 200, description = "Per-node CPU and memory samples, oldest first"), (status = 404, description = "Run not found")))]
+pub async fn get_run_metrics_history(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+    Query(params): Query<MetricsHistoryParams>,
+) -> impl IntoResponse {
+    match dm_core::runs::run_metric_history(&state.home, &id, params.since.as_deref(), params.limit)
+    {
+        Ok(samples) => Json(samples).into_response(),
+        Err(e) => err(e),
     }
 }
 
 /// GET /api/runs/:id/dataflow
+#[utoipa::path(get, path = "/api/runs/{id}/dataflow", params(("id" = String, Path)), responses((status = 200, description = "Dataflow YAML the run was started from", content_type = "text/plain"), (status = 404, description = "Run not found")))]
 pub async fn get_run_dataflow(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
     match dm_core::runs::read_run_dataflow(&state.home, &id) {
         Ok(content) => content.into_response(),
-        Err(e) => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+        Err(e) => err_with(e, StatusCode::NOT_FOUND),
     }
 }
 
 /// GET /api/runs/:id/transpiled
+#[utoipa::path(get, path = "/api/runs/{id}/transpiled", params(("id" = String, Path)), responses((status = 200, description = "Transpiled YAML handed to dora", content_type = "text/plain"), (status = 404, description = "Run not found")))]
 pub async fn get_run_transpiled(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
     match dm_core::runs::read_run_transpiled(&state.home, &id) {
         Ok(content) => content.into_response(),
-        Err(e) => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+        Err(e) => err_with(e, StatusCode::NOT_FOUND),
     }
 }
 
 /// GET /api/runs/:id/view
+#[utoipa::path(get, path = "/api/runs/{id}/view", params(("id" = String, Path)), responses((status = 200, description = "Editor layout saved with the run"), (status = 404, description = "Run not found")))]
 pub async fn get_run_view(
     State(state): State<AppState>,
     Path(id): Path<String>,
 ) -> impl IntoResponse {
     match dm_core::runs::read_run_view(&state.home, &id) {
         Ok(content) => content.into_response(),
-        Err(e) => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+        Err(e) => err_with(e, StatusCode::NOT_FOUND),
     }
 }
 
@@ -172,22 +219,24 @@ pub async fn get_run(
             }
             Json(detail).into_response()
         }
-        Err(e) => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+        Err(e) => err_with(e, StatusCode::NOT_FOUND),
     }
 }
 
 /// GET /api/runs/:id/logs/:node_id
+#[utoipa::path(get, path = "/api/runs/{id}/logs/{node_id}", params(("id" = String, Path), ("node_id" = String, Path)), responses((status = 200, description = "Full node log", content_type = "text/plain"), (status = 404, description = "Unknown run or node log")))]
 pub async fn get_run_logs(
     State(state): State<AppState>,
     Path((id, node_id)): Path<(String, String)>,
 ) -> impl IntoResponse {
     match dm_core::runs::read_run_log(&state.home, &id, &node_id) {
         Ok(content) => content.into_response(),
-        Err(e) => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+        Err(e) => err_with(e, StatusCode::NOT_FOUND),
     }
 }
 
 /// GET /api/runs/:id/logs/:node_id/stream?tail_lines=500
+#[utoipa::path(get, path = "/api/runs/{id}/logs/{node_id}/stream", params(("id" = String, Path), ("node_id" = String, Path), ("tail_lines" = Option<usize>, Query)), responses((status = 200, description = "Server-sent log lines, starting with the last `tail_lines`", content_type = "text/event-stream")))]
 pub async fn stream_run_logs(
     State(state): State<AppState>,
     Path((id, node_id)): Path<(String, String)>,
@@ -204,6 +253,7 @@ pub async fn stream_run_logs(
 }
 
 /// GET /api/runs/:id/logs/:node_id/tail?offset=0
+#[utoipa::path(get, path = "/api/runs/{id}/logs/{node_id}/tail", params(("id" = String, Path), ("node_id" = String, Path), ("offset" = Option<u64>, Query)), responses((status = 200, description = "Log content after `offset` and the next offset"), (status = 404, description = "Unknown run or node log")))]
 pub async fn tail_run_logs(
     State(state): State<AppState>,
     Path((id, node_id)): Path<(String, String)>,
@@ -212,7 +262,22 @@ pub async fn tail_run_logs(
     match dm_core::runs::read_run_log_chunk(&state.home, &id, &node_id, params.offset.unwrap_or(0))
     {
         Ok(chunk) => Json(chunk).into_response(),
-        Err(e) => (StatusCode::NOT_FOUND, e.to_string()).into_response(),
+        Err(e) => err_with(e, StatusCode::NOT_FOUND),
+    }
+}
+
+/// GET /api/runs/:id/nodes/:node_id/tail?lines=200 — recent lines from the
+/// node's in-memory ring buffer
+#[utoipa::path(get, path = "/api/runs/{id}/nodes/{node_id}/tail", params(("id" = String, Path), ("node_id" = String, Path), ("lines" = Option<usize>, Query)), responses((status = 200, description = "Most recent log lines"), (status = 404, description = "Unknown run or node log")))]
+pub async fn tail_node_log(
+    State(state): State<AppState>,
+    Path((id, node_id)): Path<(String, String)>,
+    Query(params): Query<NodeTailParams>,
+) -> impl IntoResponse {
+    let lines = params.lines.unwrap_or(200);
+    match state.log_tails.tail(&state.home, &id, &node_id, lines) {
+        Ok(tail) => Json(tail).into_response(),
+        Err(e) => err_with(e, StatusCode::NOT_FOUND),
     }
 }
 
@@ -311,53 +376,75 @@ fn read_tail_text(path: &FsPath, tail_lines: usize) -> anyhow::Result<(String, u
 }
 
 /// POST /api/runs/start
-#[utoipa::path(post, path = "/api/runs/start", request_body = StartRunRequest, responses((status = 200, description = "Run started")))]
+#[utoipa::path(post, path = "/api/runs/start", request_body = StartRunRequest, responses((status = 200, description = "Run started"), (status = 422, description = "Graph failed transpile validation; body lists each diagnostic")))]
 pub async fn start_run(
     State(state): State<AppState>,
     Json(req): Json<StartRunRequest>,
 ) -> impl IntoResponse {
-    let executable = dm_core::dataflow::inspect_yaml(&state.home, &req.yaml);
+    let yaml = match dm_core::dataflow::render(&req.yaml, &req.variables.unwrap_or_default()) {
+        Ok(yaml) => yaml,
+        Err(e) => return err_with(e, StatusCode::BAD_REQUEST),
+    };
+    let installed_nodes = if req.auto_install.unwrap_or(false) {
+        match dm_core::runs::install_missing_nodes(&state.home, &yaml).await {
+            Ok(outcomes) => outcomes,
+            Err(e) => return err(e),
+        }
+    } else {
+        Vec::new()
+    };
+    let executable = dm_core::dataflow::inspect_yaml(&state.home, &yaml);
     if executable.summary.requires_media_backend {
         let media_status = state.media.status().await;
         if !matches!(media_status.status, MediaBackendStatus::Ready) {
-            return (
-                StatusCode::BAD_REQUEST,
-                Json(serde_json::json!({
-                    "error": "This dataflow requires dm-server media support, but the media backend is not ready.",
-                    "media_status": media_status,
-                    "media_nodes": executable.summary.media_nodes,
-                    "guidance": "Open Settings > Media, enable MediaMTX or configure a binary path, then restart dm-server."
-                })),
-            )
-                .into_response();
+            let mut report = ErrorReport::new(
+                ErrorCode::DependencyMissing,
+                "This dataflow requires dm-server media support, but the media backend is not ready.",
+            );
+            report.details = Some(serde_json::json!({
+                "media_status": media_status,
+                "media_nodes": executable.summary.media_nodes,
+                "guidance": "Open Settings > Media, enable MediaMTX or configure a binary path, then restart dm-server."
+            }));
+            return (status_for(report.code), Json(report)).into_response();
         }
     }
 
-    if let Err(e) = dm_core::ensure_runtime_up(&state.home, false).await {
-        return (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(serde_json::json!({
-                "error": format!("Failed to auto-start dora runtime: {}", e)
-            })),
-        )
-            .into_response();
+    let isolation = if req.isolated.unwrap_or(false) || req.dora_version.is_some() {
+        dm_core::runs::RunIsolation::Dedicated {
+            dora_version: req.dora_version.clone(),
+        }
+    } else if req.isolated == Some(false) {
+        dm_core::runs::RunIsolation::Shared
+    } else {
+        dm_core::runs::RunIsolation::from_config(&state.home)
+    };
+
+    // A dedicated runtime is spawned by the start itself.
+    if isolation == dm_core::runs::RunIsolation::Shared {
+        if let Err(e) = dm_core::ensure_runtime_up(&state.home, false).await {
+            return err(e.context("Failed to auto-start dora runtime"));
+        }
     }
 
     let dataflow_name = req.name.unwrap_or_else(|| "web-dataflow".to_string());
 
     let strategy = if req.force.unwrap_or(false) {
         dm_core::runs::StartConflictStrategy::StopAndRestart
+    } else if req.allow_multiple.unwrap_or(false) {
+        dm_core::runs::StartConflictStrategy::AllowMultiple
     } else {
         dm_core::runs::StartConflictStrategy::Fail
     };
 
-    match dm_core::runs::start_run_from_yaml_with_source_and_strategy(
+    match dm_core::runs::start_run_from_yaml_with_isolation(
         &state.home,
-        &req.yaml,
+        &yaml,
         &dataflow_name,
         req.view_json.as_deref(),
         dm_core::runs::RunSource::Server,
         strategy,
+        isolation,
     )
     .await
     {
@@ -366,14 +453,16 @@ pub async fn start_run(
             "message": result.message,
             "run_id": result.run.run_id,
             "run": result.run,
+            "installed_nodes": installed_nodes,
         }))
         .into_response(),
         Err(e) => {
-            let text = e.to_string();
-            if text.contains("already running as run") {
-                (StatusCode::CONFLICT, text).into_response()
+            if let Some(invalid) = e.downcast_ref::<dm_core::dataflow::TranspileError>() {
+                let mut report = ErrorReport::new(ErrorCode::Invalid, format!("{:#}", e));
+                report.details = Some(serde_json::json!({ "diagnostics": invalid.diagnostics }));
+                (StatusCode::UNPROCESSABLE_ENTITY, Json(report)).into_response()
             } else {
-                err(e).into_response()
+                err(e)
             }
         }
     }
@@ -384,13 +473,7 @@ pub async fn start_run(
 pub async fn stop_run(State(state): State<AppState>, Path(id): Path<String>) -> impl IntoResponse {
     let run = match dm_core::runs::mark_stop_requested(&state.home, &id) {
         Ok(run) => run,
-        Err(_) => {
-            return (
-                StatusCode::NOT_FOUND,
-                Json(serde_json::json!({ "error": format!("Run '{}' not found", id) })),
-            )
-                .into_response();
-        }
+        Err(e) => return err(e),
     };
 
     // Fire-and-forget: run the stop in background so the HTTP response returns immediately.
@@ -411,6 +494,32 @@ pub async fn stop_run(State(state): State<AppState>, Path(id): Path<String>) ->
     .into_response()
 }
 
+/// POST /api/runs/:id/events/import — ingest a finished run's dora logs
+/// into the event store as `dora.log` events keyed by the run id
+#[utoipa::path(post, path = "/api/runs/{id}/events/import", params(("id" = String, Path)), responses((status = 200, description = "Imported event counts per node"), (status = 404, description = "Run not found"), (status = 409, description = "Run is still running")))]
+pub async fn import_run_events(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match dm_core::runs::load_run(&state.home, &id) {
+        Ok(run) if run.status.is_running() => {
+            return error_response(
+                StatusCode::CONFLICT,
+                format!("Run '{}' is still running", id),
+            );
+        }
+        Ok(_) => {}
+        Err(e) => return err(e),
+    }
+
+    let home = state.home.clone();
+    match tokio::task::spawn_blocking(move || dm_core::runs::import_run_events(&home, &id)).await {
+        Ok(Ok(report)) => Json(report).into_response(),
+        Ok(Err(e)) => err(e),
+        Err(e) => err(e),
+    }
+}
+
 /// POST /api/runs/delete
 #[utoipa::path(post, path = "/api/runs/delete", request_body = DeleteRunsRequest, responses((status = 200, description = "Deletion result")))]
 pub async fn delete_runs(
@@ -418,13 +527,7 @@ pub async fn delete_runs(
     Json(req): Json<DeleteRunsRequest>,
 ) -> impl IntoResponse {
     if req.run_ids.is_empty() {
-        return (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({
-                "error": "run_ids must not be empty"
-            })),
-        )
-            .into_response();
+        return error_response(StatusCode::BAD_REQUEST, "run_ids must not be empty");
     }
 
     let total = req.run_ids.len();
@@ -433,7 +536,10 @@ pub async fn delete_runs(
 
     for run_id in req.run_ids {
         match dm_core::runs::delete_run(&state.home, &run_id) {
-            Ok(()) => deleted.push(run_id),
+            Ok(()) => {
+                state.log_tails.forget_run(&run_id);
+                deleted.push(run_id)
+            }
             Err(e) => failed.push(serde_json::json!({
                 "run_id": run_id,
                 "error": e.to_string(),
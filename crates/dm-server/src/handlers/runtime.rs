@@ -2,9 +2,11 @@ use axum::extract::State;
 use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::Json;
+use dm_core::config::InstallMethodPreference;
 use serde::Deserialize;
 
-use crate::handlers::err;
+use crate::handlers::jobs::{spawn_job, StartJobRequest};
+use crate::handlers::{err, err_with};
 use crate::state::AppState;
 
 use utoipa::ToSchema;
@@ -12,17 +14,41 @@ use utoipa::ToSchema;
 #[derive(Deserialize, ToSchema)]
 pub struct InstallRequest {
     pub version: Option<String>,
+    /// `auto`, `binary` or `source`; defaults to `install.method` in config
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub method: Option<InstallMethodPreference>,
+    /// Skip checksum and signature verification (`install.verify`)
+    #[serde(default)]
+    pub no_verify: bool,
+    /// Return the install job right away (202) instead of waiting; follow
+    /// it with `GET /api/jobs/{id}/events`
+    #[serde(default)]
+    pub background: bool,
 }
 
-/// POST /api/install
-#[utoipa::path(post, path = "/api/install", request_body = InstallRequest, responses((status = 200, description = "Installation result")))]
+/// POST /api/install — runs as an install job either way, so progress can
+/// be followed with `GET /api/install/progress`
+#[utoipa::path(post, path = "/api/install", request_body = InstallRequest, responses((status = 200, description = "Installation result"), (status = 202, description = "Install job started")))]
 pub async fn install(
     State(state): State<AppState>,
     Json(req): Json<InstallRequest>,
 ) -> impl IntoResponse {
-    match dm_core::install::install(&state.home, req.version, false, None).await {
-        Ok(result) => Json(result).into_response(),
-        Err(e) => err(e).into_response(),
+    let (job, handle) = spawn_job(
+        &state,
+        StartJobRequest::Install {
+            version: req.version,
+            method: req.method,
+            no_verify: req.no_verify,
+        },
+    );
+    if req.background {
+        return (StatusCode::ACCEPTED, Json(job)).into_response();
+    }
+    match handle.await {
+        Ok(Ok(result)) => Json(result).into_response(),
+        Ok(Err(e)) => err(e),
+        Err(e) => err(e),
     }
 }
 
@@ -40,7 +66,7 @@ pub async fn uninstall(
     match dm_core::uninstall(&state.home, &req.version).await {
         Ok(()) => Json(serde_json::json!({ "message": format!("Uninstalled {}", req.version) }))
             .into_response(),
-        Err(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        Err(e) => err_with(e, StatusCode::BAD_REQUEST),
     }
 }
 
@@ -61,24 +87,24 @@ pub async fn use_version(
             "actual_version": actual_ver,
         }))
         .into_response(),
-        Err(e) => (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+        Err(e) => err_with(e, StatusCode::BAD_REQUEST),
     }
 }
 
 /// POST /api/up
-#[utoipa::path(post, path = "/api/up", responses((status = 200, description = "Dora runtime started")))]
+#[utoipa::path(post, path = "/api/up", responses((status = 200, description = "Dora runtime started", body = dm_core::types::RuntimeResult)))]
 pub async fn up(State(state): State<AppState>) -> impl IntoResponse {
     match dm_core::up(&state.home, false).await {
         Ok(result) => Json(result).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
 
 /// POST /api/down
-#[utoipa::path(post, path = "/api/down", responses((status = 200, description = "Dora runtime stopped")))]
+#[utoipa::path(post, path = "/api/down", responses((status = 200, description = "Dora runtime stopped", body = dm_core::types::RuntimeResult)))]
 pub async fn down(State(state): State<AppState>) -> impl IntoResponse {
     match dm_core::down(&state.home, false).await {
         Ok(result) => Json(result).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
@@ -1,38 +1,103 @@
-use axum::extract::State;
+use std::convert::Infallible;
+use std::time::Duration;
+
+use async_stream::stream;
+use axum::extract::{Query, State};
 use axum::http::StatusCode;
+use axum::response::sse::{Event, KeepAlive, Sse};
 use axum::response::IntoResponse;
 use axum::Json;
+use futures_util::Stream;
 use serde::Deserialize;
 
-use crate::handlers::err;
+use crate::handlers::{err, err_with};
 use crate::state::AppState;
 
 use utoipa::ToSchema;
 
+#[derive(Deserialize, ToSchema)]
+pub struct DoctorParams {
+    /// Include "recent problems" from the last 24h of events (default true)
+    pub history: Option<bool>,
+    /// Launch each installed node briefly to check it can start (default false)
+    pub deep: Option<bool>,
+}
+
 /// GET /api/doctor
-#[utoipa::path(get, path = "/api/doctor", responses((status = 200, description = "System health report")))]
-pub async fn doctor(State(state): State<AppState>) -> impl IntoResponse {
-    match dm_core::doctor(&state.home).await {
+#[utoipa::path(get, path = "/api/doctor", params(("history" = Option<bool>, Query, description = "Include recent problems from the event history"), ("deep" = Option<bool>, Query, description = "Launch each installed node briefly to check it can start")), responses((status = 200, description = "System health report")))]
+pub async fn doctor(
+    State(state): State<AppState>,
+    Query(params): Query<DoctorParams>,
+) -> impl IntoResponse {
+    let options = dm_core::DoctorOptions {
+        history: params.history.unwrap_or(true),
+        deep: params.deep.unwrap_or(false),
+    };
+    match dm_core::doctor_with_options(&state.home, options).await {
         Ok(report) => Json(report).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
 
+#[derive(Deserialize, ToSchema)]
+pub struct VersionsParams {
+    /// List every release instead of only the most recent ones (default false)
+    pub all: Option<bool>,
+}
+
 /// GET /api/versions
-#[utoipa::path(get, path = "/api/versions", responses((status = 200, description = "Installed dora versions")))]
-pub async fn versions(State(state): State<AppState>) -> impl IntoResponse {
-    match dm_core::versions(&state.home).await {
+#[utoipa::path(get, path = "/api/versions", params(("all" = Option<bool>, Query, description = "List every dora release, not just the most recent")), responses((status = 200, description = "Installed dora versions")))]
+pub async fn versions(
+    State(state): State<AppState>,
+    Query(params): Query<VersionsParams>,
+) -> impl IntoResponse {
+    match dm_core::versions_with(&state.home, params.all.unwrap_or(false)).await {
         Ok(report) => Json(report).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err(e),
     }
 }
 
 /// GET /api/status
-#[utoipa::path(get, path = "/api/status", responses((status = 200, description = "Runtime and run status")))]
+#[utoipa::path(get, path = "/api/status", responses((status = 200, description = "Runtime and run status", body = dm_core::types::StatusReport)))]
 pub async fn status(State(state): State<AppState>) -> impl IntoResponse {
     match dm_core::status(&state.home, false).await {
-        Ok(report) => Json(report).into_response(),
-        Err(e) => err(e).into_response(),
+        Ok(report) => {
+            state.status.publish(report.clone());
+            Json(report).into_response()
+        }
+        Err(e) => err(e),
+    }
+}
+
+/// GET /api/status/stream — SSE `status` event with the current report,
+/// then again whenever the background poller sees a change.
+#[utoipa::path(get, path = "/api/status/stream", responses((status = 200, description = "Server-sent status reports whenever they change", content_type = "text/event-stream")))]
+pub async fn status_stream(State(state): State<AppState>) -> impl IntoResponse {
+    Sse::new(build_status_stream(state)).keep_alive(
+        KeepAlive::new()
+            .interval(Duration::from_secs(15))
+            .text(": keep-alive"),
+    )
+}
+
+fn build_status_stream(state: AppState) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream! {
+        let mut rx = state.status.subscribe();
+        if rx.borrow().is_none() {
+            state.status.refresh(&state.home).await;
+        }
+        loop {
+            let snapshot = rx.borrow_and_update().clone();
+            if let Some(report) = snapshot {
+                match Event::default().event("status").json_data(&report) {
+                    Ok(event) => yield Ok(event),
+                    Err(e) => yield Ok(Event::default().event("error").data(e.to_string())),
+                }
+            }
+            if rx.changed().await.is_err() {
+                return;
+            }
+        }
     }
 }
 
@@ -47,61 +112,32 @@ pub async fn media_status(State(state): State<AppState>) -> impl IntoResponse {
 pub async fn install_media(State(state): State<AppState>) -> impl IntoResponse {
     match state.media.install().await {
         Ok(status) => Json(status).into_response(),
-        Err(e) => (
-            StatusCode::BAD_REQUEST,
-            Json(serde_json::json!({ "error": e.to_string() })),
-        )
-            .into_response(),
+        Err(e) => err_with(e, StatusCode::BAD_REQUEST),
     }
 }
 
-/// GET /api/config
+/// GET /api/config — the config with secrets redacted
 #[utoipa::path(get, path = "/api/config", responses((status = 200, description = "DM configuration")))]
 pub async fn get_config(State(state): State<AppState>) -> impl IntoResponse {
     match dm_core::config::load_config(&state.home) {
-        Ok(cfg) => Json(cfg).into_response(),
-        Err(e) => err(e).into_response(),
+        Ok(cfg) => Json(cfg.redacted()).into_response(),
+        Err(e) => err(e),
     }
 }
 
-#[derive(Deserialize, ToSchema)]
-pub struct ConfigUpdate {
-    pub active_version: Option<String>,
-    pub media: Option<serde_json::Value>,
-}
-
-/// POST /api/config
-#[utoipa::path(post, path = "/api/config", request_body = ConfigUpdate, responses((status = 200, description = "Updated configuration")))]
+/// POST /api/config — a JSON merge patch of config.toml, e.g.
+/// `{"install": {"verify": false}, "robot_id": null}`. `null` resets a key
+/// to its default; unknown keys and ill-typed values are rejected with 400.
+#[utoipa::path(post, path = "/api/config", request_body(content = Object, description = "JSON merge patch of config.toml"), responses((status = 200, description = "Updated configuration"), (status = 400, description = "Unknown key or invalid value")))]
 pub async fn update_config(
     State(state): State<AppState>,
-    Json(req): Json<ConfigUpdate>,
+    Json(patch): Json<serde_json::Value>,
 ) -> impl IntoResponse {
-    let mut cfg = match dm_core::config::load_config(&state.home) {
-        Ok(c) => c,
-        Err(e) => return err(e).into_response(),
-    };
-
-    if let Some(ver) = req.active_version {
-        cfg.active_version = Some(ver);
-    }
-
-    if let Some(media) = req.media {
-        match serde_json::from_value::<dm_core::config::MediaConfig>(media) {
-            Ok(media) => cfg.media = media,
-            Err(err) => {
-                return (
-                    StatusCode::BAD_REQUEST,
-                    Json(serde_json::json!({
-                        "error": format!("invalid media config: {}", err)
-                    })),
-                )
-                    .into_response()
-            }
+    match dm_core::config::update_config_async(&state.home, &patch).await {
+        Ok(cfg) => {
+            state.events.set_identity(cfg.identity());
+            Json(cfg.redacted()).into_response()
         }
-    }
-
-    match dm_core::config::save_config(&state.home, &cfg) {
-        Ok(()) => Json(cfg).into_response(),
-        Err(e) => err(e).into_response(),
+        Err(e) => err_with(e, StatusCode::BAD_REQUEST),
     }
 }
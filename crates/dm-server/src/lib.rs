@@ -0,0 +1,635 @@
+//! Dora Manager HTTP API as a library.
+//!
+//! [`serve`] runs the API on the caller's tokio runtime, so a desktop shell
+//! or an agent can embed it and mount its own routes next to `/api/*`:
+//!
+//! ```no_run
+//! # async fn run() -> anyhow::Result<()> {
+//! let config = dm_server::ServeConfig::new("/home/me/.dm")
+//!     .addr("127.0.0.1:0")
+//!     .extend(|router| router.route("/agent/ping", axum::routing::get(|| async { "pong" })));
+//! dm_server::serve(config).await
+//! # }
+//! ```
+mod audit;
+mod handlers;
+pub mod services;
+pub mod state;
+#[cfg(test)]
+mod tests;
+
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::{env, sync::Arc};
+
+use anyhow::{Context, Result};
+
+use axum::routing::{get, post, put};
+use axum::Router;
+use rust_embed::Embed;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tower_http::cors::CorsLayer;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+use dm_core::events::EventStore;
+use dm_core::lock::{HomeLock, LockPolicy};
+pub use state::{AppState, MessageNotification};
+
+#[derive(Embed)]
+#[folder = "../../web/build"]
+struct WebAssets;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        // System
+        handlers::system::doctor,
+        handlers::system::versions,
+        handlers::system::status,
+        handlers::system::status_stream,
+        handlers::system::media_status,
+        handlers::system::install_media,
+        handlers::system::get_config,
+        handlers::system::update_config,
+        // Runtime
+        handlers::runtime::install,
+        handlers::runtime::uninstall,
+        handlers::runtime::use_version,
+        handlers::runtime::up,
+        handlers::runtime::down,
+        // Jobs
+        handlers::jobs::start_job,
+        handlers::jobs::list_jobs,
+        handlers::jobs::get_job,
+        handlers::jobs::job_events,
+        handlers::jobs::install_progress,
+        // Nodes
+        handlers::nodes::list_nodes,
+        handlers::nodes::list_registry,
+        handlers::nodes::list_collections,
+        handlers::nodes::outdated_nodes,
+        handlers::nodes::node_status,
+        handlers::nodes::node_readme,
+        handlers::nodes::get_node_files,
+        handlers::nodes::get_node_file_content,
+        handlers::nodes::serve_node_artifact_file,
+        handlers::nodes::install_node,
+        handlers::nodes::install_nodes,
+        handlers::nodes::export_node,
+        handlers::nodes::import_node_archive,
+        handlers::nodes::import_node,
+        handlers::nodes::uninstall_node,
+        handlers::nodes::create_node,
+        handlers::nodes::open_node,
+        handlers::nodes::get_node_config,
+        handlers::nodes::node_env_preview,
+        handlers::nodes::save_node_config,
+        handlers::nodes::set_node_schema,
+        handlers::nodes::validate_node_config,
+        handlers::nodes::pin_node,
+        handlers::nodes::unpin_node,
+        handlers::nodes::test_node,
+        // Dataflows
+        handlers::dataflow::list_dataflows,
+        handlers::dataflow::get_dataflow,
+        handlers::dataflow::save_dataflow,
+        handlers::dataflow::import_dataflows,
+        handlers::dataflow::upload_dataflow,
+        handlers::dataflow::download_dataflow,
+        handlers::dataflow::export_dataflow,
+        handlers::dataflow::import_dataflow_bundle,
+        handlers::dataflow::inspect_dataflow,
+        handlers::dataflow::get_dataflow_meta,
+        handlers::dataflow::save_dataflow_meta,
+        handlers::dataflow::get_dataflow_config_schema,
+        handlers::dataflow::list_dataflow_history,
+        handlers::dataflow::get_dataflow_history_version,
+        handlers::dataflow::diff_dataflow_history_version,
+        handlers::dataflow::restore_dataflow_history_version,
+        handlers::dataflow::get_dataflow_view,
+        handlers::dataflow::save_dataflow_view,
+        handlers::dataflow::sync_dataflows,
+        handlers::dataflow::validate_all_dataflows,
+        handlers::dataflow::validate_graph,
+        handlers::dataflow::convert_graph,
+        handlers::dataflow::start_tagged_dataflows,
+        handlers::dataflow::stop_tagged_dataflows,
+        handlers::dataflow::delete_dataflow,
+        handlers::dataflow::start_dataflow,
+        handlers::dataflow::stop_dataflow,
+        // Runs
+        handlers::runs::list_runs,
+        handlers::runs::get_active_run,
+        handlers::runs::get_run,
+        handlers::runs::get_run_metrics,
+        handlers::runs::get_run_metrics_history,
+        handlers::runs::start_run,
+        handlers::runs::stop_run,
+        handlers::runs::get_run_dataflow,
+        handlers::runs::get_run_transpiled,
+        handlers::runs::get_run_view,
+        handlers::runs::get_run_logs,
+        handlers::runs::stream_run_logs,
+        handlers::runs::tail_run_logs,
+        handlers::runs::delete_runs,
+        handlers::runs::import_run_events,
+        handlers::runs::tail_node_log,
+        // Interaction
+        handlers::messages::get_interaction,
+        handlers::messages::push_message,
+        handlers::messages::list_messages,
+        handlers::messages::get_snapshots,
+        handlers::messages::list_streams,
+        handlers::messages::get_stream,
+        handlers::messages::serve_artifact_file,
+        // Events
+        handlers::events::query_events,
+        handlers::events::stream_events,
+        handlers::events::count_events,
+        handlers::events::event_stats,
+        handlers::events::event_dfg,
+        handlers::events::ingest_event,
+        handlers::events::ingest_events_batch,
+        handlers::events::export_events,
+        handlers::events::query_audit,
+    ),
+    // Body of every error response.
+    components(schemas(dm_core::error::ErrorReport, dm_core::error::ErrorCode))
+)]
+struct ApiDoc;
+
+/// Where the OpenAPI document of the HTTP API is served; `/api-docs/openapi.json`
+/// is kept as an alias for older clients.
+pub const OPENAPI_PATH: &str = "/api/openapi.json";
+
+/// Address the `dm-server` binary listens on.
+pub const DEFAULT_ADDR: &str = "127.0.0.1:3210";
+
+type RouterExtension = Box<dyn FnOnce(Router<AppState>) -> Router<AppState> + Send>;
+type ShutdownSignal = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// What [`serve`] runs: the dm home, where to listen, and any routes the
+/// embedding application adds.
+pub struct ServeConfig {
+    home: PathBuf,
+    addr: String,
+    background_services: bool,
+    dm_cli: Option<PathBuf>,
+    extensions: Vec<RouterExtension>,
+    shutdown: Option<ShutdownSignal>,
+}
+
+impl ServeConfig {
+    pub fn new(home: impl Into<PathBuf>) -> Self {
+        Self {
+            home: home.into(),
+            addr: DEFAULT_ADDR.to_string(),
+            background_services: true,
+            dm_cli: None,
+            extensions: Vec::new(),
+            shutdown: None,
+        }
+    }
+
+    /// Listen address for [`serve`]; defaults to [`DEFAULT_ADDR`].
+    pub fn addr(mut self, addr: impl Into<String>) -> Self {
+        self.addr = addr.into();
+        self
+    }
+
+    /// Whether to run the idle monitor, pollers, restart supervisor and
+    /// bridge socket next to the HTTP API (default `true`).
+    pub fn background_services(mut self, enabled: bool) -> Self {
+        self.background_services = enabled;
+        self
+    }
+
+    /// `dm` CLI binary that bridge nodes and `dm` rule actions run. Defaults
+    /// to `DM_CLI_BIN`, then `dm` on `PATH` or next to the current executable.
+    pub fn dm_cli(mut self, exe: impl Into<PathBuf>) -> Self {
+        self.dm_cli = Some(exe.into());
+        self
+    }
+
+    /// Add routes to the API router. They get [`AppState`] and sit behind
+    /// the same CORS layer as the built-in routes; extensions run in the
+    /// order they were added.
+    pub fn extend(
+        mut self,
+        extension: impl FnOnce(Router<AppState>) -> Router<AppState> + Send + 'static,
+    ) -> Self {
+        self.extensions.push(Box::new(extension));
+        self
+    }
+
+    /// Stop serving, gracefully, once `signal` completes.
+    pub fn shutdown(mut self, signal: impl Future<Output = ()> + Send + 'static) -> Self {
+        self.shutdown = Some(Box::pin(signal));
+        self
+    }
+}
+
+/// Bind the configured address and serve the API until the shutdown signal.
+pub async fn serve(config: ServeConfig) -> Result<()> {
+    let listener = TcpListener::bind(&config.addr)
+        .await
+        .with_context(|| format!("Failed to bind {}", config.addr))?;
+    serve_listener(listener, config).await
+}
+
+/// Serve the API on an already bound listener, e.g. one on port 0.
+/// Background services are stopped when this returns.
+pub async fn serve_listener(listener: TcpListener, config: ServeConfig) -> Result<()> {
+    if let Some(exe) = resolve_dm_cli(config.dm_cli.clone()) {
+        dm_core::util::set_dm_cli_exe(&config.home, exe);
+    }
+    // Marks the home as served (see `dm_core::lock::running_servers`); a
+    // leftover lock under our own pid is from a dead process, so take it.
+    let _server_lock = HomeLock::acquire_with(
+        &config.home,
+        &dm_core::lock::server_resource(std::process::id()),
+        "dm-server",
+        LockPolicy {
+            force: true,
+            ..Default::default()
+        },
+    )?;
+    let state = open_state(config.home).await?;
+    let app = router(state.clone(), config.extensions);
+    let tasks = if config.background_services {
+        spawn_background_services(&state)
+    } else {
+        Vec::new()
+    };
+
+    let shutdown = config
+        .shutdown
+        .unwrap_or_else(|| Box::pin(std::future::pending()));
+    let app = app.into_make_service_with_connect_info::<SocketAddr>();
+    let result = axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown)
+        .await
+        .context("Server error");
+
+    for task in tasks {
+        task.abort();
+    }
+    let _ = tokio::task::spawn_blocking(dm_core::events::flush_exports).await;
+    result
+}
+
+async fn open_state(home: PathBuf) -> Result<AppState> {
+    let events = EventStore::open(&home).context("Failed to open event store")?;
+    let config = dm_core::config::load_config(&home).context("Failed to load dm config")?;
+    let media = services::media::MediaRuntime::new(&home, config);
+    if let Err(err) = media.initialize().await {
+        eprintln!("[dm-server] media runtime init failed: {err}");
+    }
+
+    Ok(AppState {
+        home: Arc::new(home),
+        events: Arc::new(events),
+        messages: broadcast::channel(512).0,
+        media,
+        jobs: services::jobs::JobManager::new(),
+        status: services::status::StatusHub::new(),
+        log_tails: services::log_tail::LogTailHub::new(),
+    })
+}
+
+fn router(state: AppState, extensions: Vec<RouterExtension>) -> Router {
+    let mut api = Router::new()
+        // ─── Environment Management ───
+        .route("/api/doctor", get(handlers::doctor))
+        .route("/api/versions", get(handlers::versions))
+        .route("/api/status", get(handlers::status))
+        .route("/api/status/stream", get(handlers::status_stream))
+        .route("/api/media/status", get(handlers::media_status))
+        .route("/api/media/install", post(handlers::install_media))
+        .route("/api/config", get(handlers::get_config))
+        .route("/api/config", post(handlers::update_config))
+        .route("/api/install", post(handlers::install))
+        .route("/api/install/progress", get(handlers::install_progress))
+        .route("/api/uninstall", post(handlers::uninstall))
+        .route("/api/use", post(handlers::use_version))
+        .route("/api/up", post(handlers::up))
+        .route("/api/down", post(handlers::down))
+        // ─── Background Jobs ───
+        .route("/api/jobs", get(handlers::list_jobs))
+        .route("/api/jobs", post(handlers::start_job))
+        .route("/api/jobs/{id}", get(handlers::get_job))
+        .route("/api/jobs/{id}/ws", get(handlers::job_ws))
+        .route("/api/jobs/{id}/events", get(handlers::job_events))
+        // ─── Node Management ───
+        .route("/api/nodes", get(handlers::list_nodes))
+        .route("/api/registry", get(handlers::list_registry))
+        .route("/api/collections", get(handlers::list_collections))
+        .route("/api/nodes/outdated", get(handlers::outdated_nodes))
+        .route("/api/nodes/install", post(handlers::install_node))
+        .route("/api/nodes/install-batch", post(handlers::install_nodes))
+        .route("/api/nodes/create", post(handlers::create_node))
+        .route("/api/nodes/import", post(handlers::import_node))
+        .route(
+            "/api/nodes/import-archive",
+            post(handlers::import_node_archive),
+        )
+        .route("/api/nodes/{id}", get(handlers::node_status))
+        .route("/api/nodes/{id}/open", post(handlers::open_node))
+        .route("/api/nodes/{id}/readme", get(handlers::node_readme))
+        .route("/api/nodes/{id}/export", get(handlers::export_node))
+        .route("/api/nodes/{id}/files", get(handlers::get_node_files))
+        .route(
+            "/api/nodes/{id}/files/{*path}",
+            get(handlers::get_node_file_content),
+        )
+        .route(
+            "/api/nodes/{id}/artifacts/{*path}",
+            get(handlers::serve_node_artifact_file),
+        )
+        .route("/api/nodes/{id}/config", get(handlers::get_node_config))
+        .route(
+            "/api/nodes/{id}/config",
+            post(handlers::save_node_config).put(handlers::save_node_config),
+        )
+        .route(
+            "/api/nodes/{id}/config/validate",
+            post(handlers::validate_node_config),
+        )
+        .route(
+            "/api/nodes/{id}/env-preview",
+            get(handlers::node_env_preview),
+        )
+        .route("/api/nodes/{id}/schema", put(handlers::set_node_schema))
+        .route("/api/nodes/{id}/pin", post(handlers::pin_node))
+        .route("/api/nodes/{id}/unpin", post(handlers::unpin_node))
+        .route("/api/nodes/{id}/test", post(handlers::test_node))
+        .route("/api/nodes/uninstall", post(handlers::uninstall_node))
+        // ─── Dataflow Management ───
+        .route("/api/dataflows", get(handlers::list_dataflows))
+        .route("/api/dataflows/import", post(handlers::import_dataflows))
+        .route(
+            "/api/dataflows/import-bundle",
+            post(handlers::import_dataflow_bundle),
+        )
+        .route("/api/dataflows/sync", post(handlers::sync_dataflows))
+        .route("/api/dataflows/upload", post(handlers::upload_dataflow))
+        .route(
+            "/api/dataflows/validate-all",
+            post(handlers::validate_all_dataflows),
+        )
+        .route("/api/graph/validate", post(handlers::validate_graph))
+        .route("/api/graph/convert", post(handlers::convert_graph))
+        .route(
+            "/api/dataflows/start",
+            post(handlers::start_tagged_dataflows),
+        )
+        .route("/api/dataflows/stop", post(handlers::stop_tagged_dataflows))
+        .route("/api/dataflows/{name}", get(handlers::get_dataflow))
+        .route("/api/dataflows/{name}", post(handlers::save_dataflow))
+        .route(
+            "/api/dataflows/{name}/download",
+            get(handlers::download_dataflow),
+        )
+        .route(
+            "/api/dataflows/{name}/export",
+            get(handlers::export_dataflow),
+        )
+        .route(
+            "/api/dataflows/{name}/inspect",
+            get(handlers::inspect_dataflow),
+        )
+        .route(
+            "/api/dataflows/{name}/meta",
+            get(handlers::get_dataflow_meta),
+        )
+        .route(
+            "/api/dataflows/{name}/meta",
+            post(handlers::save_dataflow_meta),
+        )
+        .route(
+            "/api/dataflows/{name}/config-schema",
+            get(handlers::get_dataflow_config_schema),
+        )
+        .route(
+            "/api/dataflows/{name}/history",
+            get(handlers::list_dataflow_history),
+        )
+        .route(
+            "/api/dataflows/{name}/history/{version}",
+            get(handlers::get_dataflow_history_version),
+        )
+        .route(
+            "/api/dataflows/{name}/history/{version}/diff",
+            get(handlers::diff_dataflow_history_version),
+        )
+        .route(
+            "/api/dataflows/{name}/history/{version}/restore",
+            post(handlers::restore_dataflow_history_version),
+        )
+        .route(
+            "/api/dataflows/{name}/delete",
+            post(handlers::delete_dataflow),
+        )
+        .route(
+            "/api/dataflows/{name}/view",
+            get(handlers::get_dataflow_view),
+        )
+        .route(
+            "/api/dataflows/{name}/view",
+            post(handlers::save_dataflow_view),
+        )
+        // ─── Dataflow Execution ───
+        .route("/api/dataflow/start", post(handlers::start_dataflow))
+        .route("/api/dataflow/stop", post(handlers::stop_dataflow))
+        // ─── Execution History (Runs) ───
+        .route("/api/runs", get(handlers::list_runs))
+        .route("/api/runs/start", post(handlers::start_run))
+        .route("/api/runs/active", get(handlers::get_active_run))
+        .route("/api/runs/{id}", get(handlers::get_run))
+        .route("/api/runs/{id}/metrics", get(handlers::get_run_metrics))
+        .route(
+            "/api/runs/{id}/metrics/history",
+            get(handlers::get_run_metrics_history),
+        )
+        .route("/api/runs/{id}/stop", post(handlers::stop_run))
+        .route("/api/runs/{id}/dataflow", get(handlers::get_run_dataflow))
+        .route(
+            "/api/runs/{id}/transpiled",
+            get(handlers::get_run_transpiled),
+        )
+        .route("/api/runs/{id}/view", get(handlers::get_run_view))
+        .route("/api/runs/delete", post(handlers::delete_runs))
+        .route(
+            "/api/runs/{id}/events/import",
+            post(handlers::import_run_events),
+        )
+        .route("/api/runs/{id}/logs/{node_id}", get(handlers::get_run_logs))
+        .route(
+            "/api/runs/{id}/logs/{node_id}/stream",
+            get(handlers::stream_run_logs),
+        )
+        .route(
+            "/api/runs/{id}/logs/{node_id}/tail",
+            get(handlers::tail_run_logs),
+        )
+        .route(
+            "/api/runs/{id}/nodes/{node_id}/tail",
+            get(handlers::tail_node_log),
+        )
+        .route("/api/runs/{id}/interaction", get(handlers::get_interaction))
+        .route("/api/runs/{id}/messages", get(handlers::list_messages))
+        .route("/api/runs/{id}/messages", post(handlers::push_message))
+        .route(
+            "/api/runs/{id}/messages/snapshots",
+            get(handlers::get_snapshots),
+        )
+        .route("/api/runs/{id}/streams", get(handlers::list_streams))
+        .route(
+            "/api/runs/{id}/streams/{stream_id}",
+            get(handlers::get_stream),
+        )
+        .route("/api/runs/{id}/messages/ws", get(handlers::messages_ws))
+        .route(
+            "/api/runs/{id}/messages/ws/{node_id}",
+            get(handlers::node_ws),
+        )
+        .route(
+            "/api/runs/{id}/artifacts/{*path}",
+            get(handlers::serve_artifact_file),
+        )
+        .route("/api/runs/{id}/ws", get(handlers::run_ws))
+        // ─── Events / Observability ───
+        .route("/api/events/count", get(handlers::count_events))
+        .route("/api/events/stats", get(handlers::event_stats))
+        .route("/api/events/dfg", get(handlers::event_dfg))
+        .route("/api/events/stream", get(handlers::stream_events))
+        .route("/api/events/export", get(handlers::export_events))
+        .route("/api/events", get(handlers::query_events))
+        .route("/api/events", post(handlers::ingest_event))
+        .route("/api/events/batch", post(handlers::ingest_events_batch))
+        .route("/api/audit", get(handlers::query_audit));
+    for extend in extensions {
+        api = extend(api);
+    }
+
+    api
+        // ─── Middleware ───
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            audit::audit_mutations,
+        ))
+        .layer(CorsLayer::permissive())
+        .with_state(state)
+        // ─── OpenAPI document + Swagger UI ───
+        .merge(SwaggerUi::new("/swagger-ui").url(OPENAPI_PATH, ApiDoc::openapi()))
+        .route(
+            "/api-docs/openapi.json",
+            get(|| async { axum::Json(ApiDoc::openapi()) }),
+        )
+        // ─── Static Frontend Assets ───
+        .fallback(axum::routing::get(handlers::serve_web))
+}
+
+fn spawn_background_services(state: &AppState) -> Vec<JoinHandle<()>> {
+    let mut tasks = Vec::new();
+
+    // Background idle monitor: auto-down dora when no active runs remain
+    let monitor_home = state.home.clone();
+    tasks.push(tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(30)).await;
+            dm_core::auto_down_if_idle(&monitor_home, false).await;
+        }
+    }));
+
+    // Status poller: feeds `/api/status/stream` subscribers on change
+    tasks.push(tokio::spawn(services::status::poll_status(
+        state.home.clone(),
+        state.status.clone(),
+    )));
+
+    // Threshold alerts: disk free, events.db size, installed versions
+    tasks.push(tokio::spawn(services::thresholds::poll_thresholds(
+        state.home.clone(),
+    )));
+
+    // Log capture: node logs of active runs as live `dora.log` events
+    tasks.push(tokio::spawn(services::log_capture::capture_run_logs(
+        state.home.clone(),
+    )));
+
+    // Metrics sampler: per-node CPU/memory of active runs as `run.metrics` events
+    tasks.push(tokio::spawn(services::metrics::sample_run_metrics(
+        state.home.clone(),
+    )));
+
+    // Rules engine: `[[rules]]` actions triggered by stored events
+    tasks.push(tokio::spawn(services::rules::watch_rules(
+        state.home.clone(),
+    )));
+
+    // Startup validation: warn about saved dataflows that no longer start
+    let validate_home = state.home.clone();
+    tasks.push(tokio::task::spawn_blocking(move || {
+        match dm_core::dataflow::validate_all(&validate_home) {
+            Ok(report) if report.failing > 0 => eprintln!(
+                "[dm-server] warning: {} saved dataflow(s) would fail to start; see POST /api/dataflows/validate-all",
+                report.failing
+            ),
+            Ok(_) => {}
+            Err(e) => eprintln!("[dm-server] dataflow validation error: {e}"),
+        }
+    }));
+
+    // Restart supervisor: re-start dataflows whose nodes declare `restart:`
+    let supervisor_home = state.home.clone();
+    tasks.push(tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            if let Err(e) = dm_core::runs::supervise_restarts(&supervisor_home).await {
+                eprintln!("[dm-server] restart supervisor error: {e}");
+            }
+        }
+    }));
+
+    // Unix domain socket for bridge IPC
+    let bridge_sock_path = state.home.join("bridge.sock");
+    let _ = std::fs::remove_file(&bridge_sock_path);
+    match tokio::net::UnixListener::bind(&bridge_sock_path) {
+        Ok(unix_listener) => {
+            let sock_home = state.home.clone();
+            let sock_tx = state.messages.clone();
+            tasks.push(tokio::spawn(async move {
+                handlers::bridge_socket::bridge_socket_loop(sock_home, sock_tx, unix_listener)
+                    .await;
+            }));
+        }
+        Err(e) => eprintln!("[dm-server] warning: could not create bridge.sock: {e}"),
+    }
+
+    tasks
+}
+
+fn resolve_dm_cli(configured: Option<PathBuf>) -> Option<PathBuf> {
+    let from_env = env::var(dm_core::util::DM_CLI_BIN_ENV_KEY)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from);
+    let exe = configured
+        .or(from_env)
+        .or_else(dm_core::util::resolve_dm_cli_exe_from_path_or_sibling);
+    match &exe {
+        Some(path) => eprintln!("[dm-server] using {} for bridge nodes", path.display()),
+        None => eprintln!(
+            "[dm-server] warning: dm CLI binary was not found in PATH or next to dm-server; dataflows with interaction bridge capabilities may fail to start. Install dm or set {}=/absolute/path/to/dm.",
+            dm_core::util::DM_CLI_BIN_ENV_KEY
+        ),
+    }
+    exe
+}
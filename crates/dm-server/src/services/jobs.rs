@@ -0,0 +1,236 @@
+//! In-memory registry of background jobs (dora installs, node installs).
+//!
+//! Each job keeps its full progress history so a client that subscribes
+//! late (e.g. after a page refresh) replays every update before following
+//! the live stream.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+
+use dm_core::types::{InstallPhase, InstallProgress};
+
+/// Finished jobs kept around for late subscribers.
+const MAX_FINISHED_JOBS: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Install,
+    NodeInstall,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// One progress step of a job.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct JobUpdate {
+    pub seq: u64,
+    pub status: JobStatus,
+    pub phase: String,
+    pub pct: Option<u8>,
+    pub bytes_done: Option<u64>,
+    pub bytes_total: Option<u64>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct JobSummary {
+    pub id: String,
+    pub kind: JobKind,
+    /// Version or node id the job operates on.
+    pub target: String,
+    pub status: JobStatus,
+    pub created_at: String,
+    pub finished_at: Option<String>,
+    /// Serialized result of a succeeded job.
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct JobSnapshot {
+    #[serde(flatten)]
+    pub summary: JobSummary,
+    pub history: Vec<JobUpdate>,
+}
+
+/// Replayed history plus a receiver for everything after it.
+pub struct JobSubscription {
+    pub history: Vec<JobUpdate>,
+    pub finished: bool,
+    pub updates: broadcast::Receiver<JobUpdate>,
+}
+
+struct JobEntry {
+    summary: JobSummary,
+    history: Vec<JobUpdate>,
+    tx: broadcast::Sender<JobUpdate>,
+}
+
+impl JobEntry {
+    fn push(&mut self, mut update: JobUpdate) {
+        update.seq = self.history.len() as u64 + 1;
+        self.history.push(update.clone());
+        let _ = self.tx.send(update);
+    }
+}
+
+#[derive(Default)]
+pub struct JobManager {
+    next_id: AtomicU64,
+    jobs: Mutex<HashMap<String, JobEntry>>,
+}
+
+impl JobManager {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Register a running job and return its id.
+    pub fn create(&self, kind: JobKind, target: &str) -> JobSummary {
+        let id = format!(
+            "job-{}-{}",
+            chrono::Utc::now().timestamp_millis(),
+            self.next_id.fetch_add(1, Ordering::Relaxed)
+        );
+        let summary = JobSummary {
+            id: id.clone(),
+            kind,
+            target: target.to_string(),
+            status: JobStatus::Running,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            finished_at: None,
+            result: None,
+            error: None,
+        };
+        let mut jobs = self.lock();
+        prune_finished(&mut jobs);
+        jobs.insert(
+            id,
+            JobEntry {
+                summary: summary.clone(),
+                history: Vec::new(),
+                tx: broadcast::channel(256).0,
+            },
+        );
+        summary
+    }
+
+    /// Record an install progress message for `id`.
+    pub fn progress(&self, id: &str, progress: &InstallProgress) {
+        let (bytes_done, bytes_total) = match progress.phase {
+            InstallPhase::Downloading {
+                bytes_done,
+                bytes_total,
+            } => (Some(bytes_done), Some(bytes_total)),
+            _ => (None, None),
+        };
+        if let Some(job) = self.lock().get_mut(id) {
+            job.push(JobUpdate {
+                seq: 0,
+                status: JobStatus::Running,
+                phase: progress.phase.name().to_string(),
+                pct: progress.phase.pct(),
+                bytes_done,
+                bytes_total,
+                message: progress.message.clone(),
+            });
+        }
+    }
+
+    /// Mark `id` finished and publish the terminal update.
+    pub fn finish(&self, id: &str, result: Result<serde_json::Value, String>) {
+        let mut jobs = self.lock();
+        let Some(job) = jobs.get_mut(id) else {
+            return;
+        };
+        let (status, message) = match &result {
+            Ok(_) => (JobStatus::Succeeded, "Completed".to_string()),
+            Err(e) => (JobStatus::Failed, e.clone()),
+        };
+        job.summary.status = status;
+        job.summary.finished_at = Some(chrono::Utc::now().to_rfc3339());
+        match result {
+            Ok(value) => job.summary.result = Some(value),
+            Err(e) => job.summary.error = Some(e),
+        }
+        job.push(JobUpdate {
+            seq: 0,
+            status,
+            phase: "finished".to_string(),
+            pct: (status == JobStatus::Succeeded).then_some(100),
+            bytes_done: None,
+            bytes_total: None,
+            message,
+        });
+    }
+
+    pub fn get(&self, id: &str) -> Option<JobSnapshot> {
+        self.lock().get(id).map(|job| JobSnapshot {
+            summary: job.summary.clone(),
+            history: job.history.clone(),
+        })
+    }
+
+    /// All known jobs, newest first.
+    pub fn list(&self) -> Vec<JobSummary> {
+        let mut jobs: Vec<JobSummary> = self.lock().values().map(|j| j.summary.clone()).collect();
+        jobs.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        jobs
+    }
+
+    /// The running job of `kind`, else the most recently created one.
+    pub fn latest(&self, kind: JobKind) -> Option<String> {
+        self.lock()
+            .values()
+            .filter(|job| job.summary.kind == kind)
+            .max_by_key(|job| {
+                (
+                    job.summary.status == JobStatus::Running,
+                    job.summary.created_at.clone(),
+                )
+            })
+            .map(|job| job.summary.id.clone())
+    }
+
+    /// Subscribe to `id`. History and receiver are taken under one lock, so
+    /// no update is lost or delivered twice between replay and live stream.
+    pub fn subscribe(&self, id: &str) -> Option<JobSubscription> {
+        self.lock().get(id).map(|job| JobSubscription {
+            history: job.history.clone(),
+            finished: job.summary.status != JobStatus::Running,
+            updates: job.tx.subscribe(),
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, JobEntry>> {
+        self.jobs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn prune_finished(jobs: &mut HashMap<String, JobEntry>) {
+    let mut finished: Vec<(String, String)> = jobs
+        .iter()
+        .filter(|(_, job)| job.summary.status != JobStatus::Running)
+        .map(|(id, job)| (job.summary.created_at.clone(), id.clone()))
+        .collect();
+    if finished.len() < MAX_FINISHED_JOBS {
+        return;
+    }
+    finished.sort();
+    let excess = finished.len() + 1 - MAX_FINISHED_JOBS;
+    for (_, id) in finished.into_iter().take(excess) {
+        jobs.remove(&id);
+    }
+}
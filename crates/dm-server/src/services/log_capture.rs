@@ -0,0 +1,44 @@
+//! Live capture of node logs into the event store.
+//!
+//! Every `LOG_CAPTURE_INTERVAL` the per-node log files of active runs are
+//! read from where the previous poll stopped, and new lines are stored as
+//! `dora.log` Dataflow events with the run id as case id, so
+//! `GET /api/events?source=dataflow&case_id=<run>` follows a run while it
+//! is still going. A run that stops gets one last poll before it is
+//! dropped.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dm_core::events::EventStore;
+use dm_core::runs::RunLogCapture;
+
+const LOG_CAPTURE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Capture node logs of active runs, forever.
+pub async fn capture_run_logs(home: Arc<PathBuf>) {
+    let mut captures: HashMap<String, RunLogCapture> = HashMap::new();
+    loop {
+        tokio::time::sleep(LOG_CAPTURE_INTERVAL).await;
+        let Ok(store) = EventStore::open(&home) else {
+            continue;
+        };
+        let active: Vec<String> = dm_core::runs::list_active_runs(&home)
+            .map(|runs| runs.into_iter().map(|run| run.run_id).collect())
+            .unwrap_or_default();
+        for run_id in &active {
+            captures
+                .entry(run_id.clone())
+                .or_insert_with(|| RunLogCapture::new(run_id));
+        }
+
+        captures.retain(|run_id, capture| {
+            if let Err(e) = capture.poll(&home, &store) {
+                eprintln!("[dm-server] log capture for run '{run_id}' failed: {e}");
+            }
+            active.contains(run_id)
+        });
+    }
+}
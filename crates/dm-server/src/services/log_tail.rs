@@ -0,0 +1,125 @@
+//! Per-node ring buffers of recent log lines backing
+//! `GET /api/runs/{id}/nodes/{node}/tail`.
+//!
+//! Each buffer follows the node's log file with a [`LogFollower`], so a
+//! request only reads bytes appended since the previous one instead of
+//! rescanning the whole log.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use anyhow::Result;
+use dm_core::runs::LogFollower;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Lines kept per node.
+pub const LOG_TAIL_CAPACITY: usize = 1_000;
+/// Node buffers kept before the least recently used one is dropped.
+const MAX_BUFFERS: usize = 256;
+/// How far back an empty buffer starts reading an existing log.
+const SEED_BYTES: u64 = 256 * 1024;
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct NodeLogTail {
+    pub run_id: String,
+    pub node_id: String,
+    pub lines: Vec<String>,
+    /// Byte offset in the log file the buffer has read up to
+    pub offset: u64,
+}
+
+struct NodeBuffer {
+    follower: LogFollower,
+    lines: VecDeque<String>,
+    touched: Instant,
+}
+
+impl NodeBuffer {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            follower: LogFollower::new(path).with_seed_bytes(SEED_BYTES),
+            lines: VecDeque::with_capacity(LOG_TAIL_CAPACITY),
+            touched: Instant::now(),
+        }
+    }
+
+    /// Read whatever was appended to the log since the last call.
+    fn refresh(&mut self) -> Result<()> {
+        let new = self.follower.read_new_lines()?;
+        if new.restarted {
+            self.lines.clear();
+        }
+        for line in new.lines {
+            if self.lines.len() == LOG_TAIL_CAPACITY {
+                self.lines.pop_front();
+            }
+            self.lines.push_back(line);
+        }
+        Ok(())
+    }
+
+    fn last(&self, n: usize) -> Vec<String> {
+        let mut lines: Vec<String> = self.lines.iter().cloned().collect();
+        if !self.follower.partial().is_empty() {
+            lines.push(self.follower.partial().to_string());
+        }
+        let start = lines.len().saturating_sub(n);
+        lines.split_off(start)
+    }
+}
+
+#[derive(Default)]
+pub struct LogTailHub {
+    buffers: Mutex<HashMap<(String, String), NodeBuffer>>,
+}
+
+impl LogTailHub {
+    pub fn new() -> std::sync::Arc<Self> {
+        std::sync::Arc::new(Self::default())
+    }
+
+    /// The last `n` (at most [`LOG_TAIL_CAPACITY`]) lines of `node_id`'s log
+    /// in run `run_id`.
+    pub fn tail(&self, home: &Path, run_id: &str, node_id: &str, n: usize) -> Result<NodeLogTail> {
+        let path = dm_core::runs::resolve_run_log_path(home, run_id, node_id)?;
+        let key = (run_id.to_string(), node_id.to_string());
+
+        let mut buffers = self.buffers.lock().unwrap_or_else(|e| e.into_inner());
+        if !buffers.contains_key(&key) && buffers.len() >= MAX_BUFFERS {
+            if let Some(oldest) = buffers
+                .iter()
+                .min_by_key(|(_, buffer)| buffer.touched)
+                .map(|(key, _)| key.clone())
+            {
+                buffers.remove(&oldest);
+            }
+        }
+        let buffer = buffers
+            .entry(key)
+            .or_insert_with(|| NodeBuffer::new(path.clone()));
+        if buffer.follower.path() != path {
+            // The run moved from legacy logs to a dora output dir.
+            *buffer = NodeBuffer::new(path);
+        }
+        buffer.refresh()?;
+        buffer.touched = Instant::now();
+
+        Ok(NodeLogTail {
+            run_id: run_id.to_string(),
+            node_id: node_id.to_string(),
+            lines: buffer.last(n.min(LOG_TAIL_CAPACITY)),
+            offset: buffer.follower.offset(),
+        })
+    }
+
+    /// Drop the buffers of a deleted run.
+    pub fn forget_run(&self, run_id: &str) {
+        self.buffers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|(run, _), _| run != run_id);
+    }
+}
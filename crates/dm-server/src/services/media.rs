@@ -1,4 +1,3 @@
-use std::env;
 use std::fs;
 use std::io::Cursor;
 use std::path::{Path, PathBuf};
@@ -86,7 +85,10 @@ impl MediaRuntime {
         Arc::new(Self {
             home: home.to_path_buf(),
             config,
-            client: Client::new(),
+            client: dm_core::http::client(home).unwrap_or_else(|e| {
+                eprintln!("[dm-server] media downloads use default HTTP settings: {e:#}");
+                Client::new()
+            }),
             inner: Mutex::new(MediaRuntimeState {
                 status: MediaStatus {
                     backend: "mediamtx".to_string(),
@@ -310,10 +312,8 @@ impl MediaRuntime {
             .header("User-Agent", MEDIAMTX_USER_AGENT)
             .header("Accept", "application/vnd.github+json");
 
-        if let Ok(token) = env::var("GITHUB_TOKEN") {
-            if !token.is_empty() {
-                req = req.header("Authorization", format!("Bearer {token}"));
-            }
+        if let Some(token) = dm_core::config::github_token(&self.home) {
+            req = req.header("Authorization", format!("Bearer {token}"));
         }
 
         let response = req.send().await?;
@@ -338,20 +338,17 @@ impl MediaRuntime {
         asset: &GithubAsset,
         target_dir: &Path,
     ) -> Result<()> {
+        let url = dm_core::http::github_asset_url(&self.home, &asset.browser_download_url);
         let response = self
             .client
-            .get(&asset.browser_download_url)
+            .get(&url)
             .header("User-Agent", MEDIAMTX_USER_AGENT)
             .send()
             .await
-            .with_context(|| format!("failed to download {}", asset.browser_download_url))?;
+            .with_context(|| format!("failed to download {}", url))?;
 
         if !response.status().is_success() {
-            anyhow::bail!(
-                "failed to download {}: {}",
-                asset.browser_download_url,
-                response.status()
-            );
+            anyhow::bail!("failed to download {}: {}", url, response.status());
         }
 
         let mut bytes = Vec::with_capacity(asset.size as usize);
@@ -109,7 +109,7 @@ impl MessageService {
     pub fn open(home: &Path, run_id: &str) -> Result<Self> {
         ensure_run_exists(home, run_id)?;
         let db_path = super::db_path(home, run_id);
-        let conn = Connection::open(&db_path)
+        let conn = dm_core::encryption::open_db(home, &db_path)
             .with_context(|| format!("Failed to open interaction db at {}", db_path.display()))?;
         conn.execute_batch(
             "PRAGMA journal_mode=WAL;
@@ -0,0 +1,37 @@
+//! Per-node CPU and memory samples of active runs.
+//!
+//! Every `METRICS_SAMPLE_INTERVAL` the node processes of running runs are
+//! measured and stored as one `run.metrics` Dataflow event per run (case
+//! id = run id), which `GET /api/runs/{id}/metrics/history` returns for
+//! charting. Samples are deleted together with their run.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dm_core::events::EventStore;
+
+const METRICS_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Sample the nodes of active runs, forever.
+pub async fn sample_run_metrics(home: Arc<PathBuf>) {
+    loop {
+        tokio::time::sleep(METRICS_SAMPLE_INTERVAL).await;
+        let samples = match dm_core::runs::sample_active_runs(&home).await {
+            Ok(samples) if !samples.is_empty() => samples,
+            Ok(_) => continue,
+            Err(e) => {
+                eprintln!("[dm-server] metrics sampling failed: {e}");
+                continue;
+            }
+        };
+        let Ok(store) = EventStore::open(&home) else {
+            continue;
+        };
+        for (run_id, sample) in &samples {
+            if let Err(e) = dm_core::runs::record_run_metrics(&store, run_id, sample) {
+                eprintln!("[dm-server] recording metrics for run '{run_id}' failed: {e}");
+            }
+        }
+    }
+}
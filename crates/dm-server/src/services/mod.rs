@@ -1,5 +1,12 @@
+pub mod jobs;
+pub mod log_capture;
+pub mod log_tail;
 pub mod media;
 pub mod message;
+pub mod metrics;
+pub mod rules;
+pub mod status;
+pub mod thresholds;
 
 use std::path::{Component, Path, PathBuf};
 
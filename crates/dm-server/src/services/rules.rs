@@ -0,0 +1,65 @@
+//! `[[rules]]` evaluation against the live event stream.
+//!
+//! Only events stored by this server are seen, which includes everything
+//! its own handlers and pollers do (e.g. `run.finished` once the status
+//! poller notices a run ended), but not events written by a separate `dm`
+//! CLI process. Rules are re-read from config.toml at most once per
+//! `RULES_RELOAD_INTERVAL`, and each rule waits out its `cooldown_secs`
+//! before it can fire again.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use dm_core::config::RuleConfig;
+use dm_core::events::EventStore;
+use tokio::sync::broadcast::error::RecvError;
+
+const RULES_RELOAD_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Run matching rules for every stored event, forever.
+pub async fn watch_rules(home: Arc<PathBuf>) {
+    let mut rx = EventStore::subscribe();
+    let mut rules: Vec<RuleConfig> = Vec::new();
+    let mut loaded_at: Option<Instant> = None;
+    let mut last_fired: HashMap<String, Instant> = HashMap::new();
+
+    loop {
+        let event = match rx.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(missed)) => {
+                eprintln!("[dm-server] rules engine skipped {missed} events");
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+
+        if loaded_at.is_none_or(|at| at.elapsed() >= RULES_RELOAD_INTERVAL) {
+            rules = dm_core::config::load_config(&home)
+                .map(|cfg| cfg.rules)
+                .unwrap_or_default();
+            loaded_at = Some(Instant::now());
+        }
+
+        for rule in dm_core::matching_rules(&rules, &event) {
+            let cooldown = Duration::from_secs(rule.cooldown_secs);
+            if last_fired
+                .get(&rule.name)
+                .is_some_and(|at| at.elapsed() < cooldown)
+            {
+                continue;
+            }
+            last_fired.insert(rule.name.clone(), Instant::now());
+
+            let home = home.clone();
+            let rule = rule.clone();
+            let event = event.clone();
+            tokio::spawn(async move {
+                if let Err(e) = dm_core::run_rule(&home, &rule, &event).await {
+                    eprintln!("[dm-server] rule '{}' failed: {e}", rule.name);
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Background status poller backing `GET /api/status/stream`.
+//!
+//! The poller runs `dm_core::status` on an interval and only publishes a
+//! snapshot when something a dashboard shows has changed (runtime up/down,
+//! version, runs or the `dora list` output), not on every tick.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+use dm_core::types::StatusReport;
+
+pub const STATUS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+pub struct StatusHub {
+    tx: watch::Sender<Option<StatusReport>>,
+}
+
+impl StatusHub {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self {
+            tx: watch::channel(None).0,
+        })
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Option<StatusReport>> {
+        self.tx.subscribe()
+    }
+
+    /// Store `report` and wake subscribers if it differs from the last
+    /// published snapshot. Returns whether it was published.
+    pub fn publish(&self, report: StatusReport) -> bool {
+        self.tx.send_if_modified(|current| {
+            if current.as_ref().map(fingerprint) == Some(fingerprint(&report)) {
+                return false;
+            }
+            *current = Some(report);
+            true
+        })
+    }
+
+    /// Poll once and publish the result.
+    pub async fn refresh(&self, home: &Path) -> bool {
+        match dm_core::status(home, false).await {
+            Ok(report) => self.publish(report),
+            Err(e) => {
+                eprintln!("[dm-server] status poll failed: {e}");
+                false
+            }
+        }
+    }
+}
+
+/// Poll status forever, publishing changes to `hub`.
+pub async fn poll_status(home: Arc<std::path::PathBuf>, hub: Arc<StatusHub>) {
+    loop {
+        hub.refresh(&home).await;
+        tokio::time::sleep(STATUS_POLL_INTERVAL).await;
+    }
+}
+
+/// The parts of a report whose change is worth pushing. Uptimes and
+/// resource figures move on every poll and are left out.
+fn fingerprint(report: &StatusReport) -> serde_json::Value {
+    let runs = |runs: &[dm_core::types::StatusRunEntry]| -> Vec<serde_json::Value> {
+        runs.iter()
+            .map(|run| {
+                serde_json::json!([
+                    run.run_id,
+                    run.status,
+                    run.observed_nodes,
+                    run.expected_nodes,
+                    run.outcome_summary
+                ])
+            })
+            .collect()
+    };
+    let dataflows: Vec<serde_json::Value> = report
+        .untracked_dataflows
+        .iter()
+        .map(|df| serde_json::json!([df.id, df.status]))
+        .collect();
+    let processes: Vec<serde_json::Value> = report
+        .processes
+        .iter()
+        .map(|process| serde_json::json!([process.role, process.pid, process.alive]))
+        .collect();
+    let machines: Vec<serde_json::Value> = report
+        .machines
+        .iter()
+        .map(|machine| serde_json::json!([machine.name, machine.addr, machine.reachable]))
+        .collect();
+
+    serde_json::json!({
+        "active_version": report.active_version,
+        "actual_version": report.actual_version,
+        "robot_id": report.robot_id,
+        "labels": report.labels,
+        "runtime_running": report.runtime_running,
+        "active_runs": runs(&report.active_runs),
+        "recent_runs": runs(&report.recent_runs),
+        "untracked_dataflows": dataflows,
+        "processes": processes,
+        "coordinator_addr": report.coordinator_addr,
+        "machines": machines,
+    })
+}
@@ -0,0 +1,43 @@
+//! Periodic `[thresholds]` check (disk free, events.db size, version count).
+//!
+//! Alerts are emitted once when a limit is first crossed and again only
+//! after it has recovered and been crossed anew, so a full disk produces one
+//! warning (and one webhook call) rather than one per poll.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dm_core::types::ThresholdAlert;
+
+/// Check forever at `thresholds.check_interval_secs`, alerting on new breaches.
+pub async fn poll_thresholds(home: Arc<PathBuf>) {
+    let mut exceeded = BTreeSet::new();
+    loop {
+        let alerts = dm_core::check_thresholds(&home);
+        let fresh = newly_exceeded(&mut exceeded, alerts);
+        dm_core::emit_threshold_alerts(&home, &fresh).await;
+
+        let secs = dm_core::config::load_config(&home)
+            .map(|cfg| cfg.thresholds.check_interval_secs)
+            .unwrap_or_default()
+            .max(10);
+        tokio::time::sleep(Duration::from_secs(secs)).await;
+    }
+}
+
+/// Alerts whose metric was not already exceeded; `exceeded` is updated to
+/// the current set.
+pub fn newly_exceeded(
+    exceeded: &mut BTreeSet<String>,
+    alerts: Vec<ThresholdAlert>,
+) -> Vec<ThresholdAlert> {
+    let current: BTreeSet<String> = alerts.iter().map(|a| a.metric.clone()).collect();
+    let fresh = alerts
+        .into_iter()
+        .filter(|a| !exceeded.contains(&a.metric))
+        .collect();
+    *exceeded = current;
+    fresh
+}
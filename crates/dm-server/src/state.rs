@@ -5,7 +5,10 @@ use tokio::sync::broadcast;
 
 use dm_core::events::EventStore;
 
+use crate::services::jobs::JobManager;
+use crate::services::log_tail::LogTailHub;
 use crate::services::media::MediaRuntime;
+use crate::services::status::StatusHub;
 
 #[derive(Clone)]
 pub struct AppState {
@@ -13,6 +16,9 @@ pub struct AppState {
     pub events: Arc<EventStore>,
     pub messages: broadcast::Sender<MessageNotification>,
     pub media: Arc<MediaRuntime>,
+    pub jobs: Arc<JobManager>,
+    pub status: Arc<StatusHub>,
+    pub log_tails: Arc<LogTailHub>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
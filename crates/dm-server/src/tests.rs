@@ -2,9 +2,10 @@ use std::sync::Arc;
 
 use axum::body::to_bytes;
 use axum::extract::{Path, Query, State};
-use axum::http::Uri;
+use axum::http::{HeaderMap, Uri};
 use axum::response::IntoResponse;
 use axum::Json;
+use dm_test_utils::{MockDora, MockResponse, FAKE_DORA_UUID};
 use tempfile::TempDir;
 use tokio::sync::broadcast;
 
@@ -13,8 +14,6 @@ use crate::handlers::runs::StartRunRequest;
 use crate::services::media::MediaRuntime;
 use crate::state::AppState;
 
-const FAKE_DORA_UUID: &str = "019cc181-adad-7654-aa78-63502362337b";
-
 fn test_state() -> (TempDir, AppState) {
     let tmp = TempDir::new().unwrap();
     let home = tmp.path().to_path_buf();
@@ -24,199 +23,54 @@ fn test_state() -> (TempDir, AppState) {
         events: Arc::new(events),
         messages: broadcast::channel(64).0,
         media: MediaRuntime::new(tmp.path(), dm_core::config::DmConfig::default()),
+        jobs: crate::services::jobs::JobManager::new(),
+        status: crate::services::status::StatusHub::new(),
+        log_tails: crate::services::log_tail::LogTailHub::new(),
     };
     (tmp, state)
 }
 
 fn setup_fake_dora_home(home: &std::path::Path, active_version: &str) {
-    let version_dir = dm_core::config::versions_dir(home).join(active_version);
-    std::fs::create_dir_all(&version_dir).unwrap();
-
-    let bin = version_dir.join("dora");
-    std::fs::write(
-        &bin,
-        format!(
-            r#"#!/bin/sh
-cmd="$1"
-case "$cmd" in
-  --version)
-    echo "dora-cli 0.4.1"
-    ;;
-  check)
-    echo "Runtime OK"
-    ;;
-  list)
-    echo "UUID Name Status Nodes CPU Memory"
-    echo "019cc181-adad-7654-aa78-63502362337b flow-a Running 1 0.0% 0.0"
-    echo "019cc181-adad-7654-aa78-635023623380 flow-b Succeeded 2 0.0% 0.0"
-    ;;
-  up)
-    echo "started"
-    ;;
-  destroy)
-    echo "stopped"
-    ;;
-  start)
-    echo "dataflow started: {fake_uuid}"
-    ;;
-  stop)
-    echo "dataflow stopped"
-    ;;
-  *)
-    echo "unknown command: $cmd" >&2
-    exit 1
-    ;;
-esac
-"#,
-            fake_uuid = FAKE_DORA_UUID,
-        ),
-    )
-    .unwrap();
-
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = std::fs::metadata(&bin).unwrap().permissions();
-        perms.set_mode(0o755);
-        std::fs::set_permissions(&bin, perms).unwrap();
-    }
-
-    dm_core::config::save_config(
-        home,
-        &dm_core::config::DmConfig {
-            active_version: Some(active_version.to_string()),
-            ..Default::default()
-        },
-    )
-    .unwrap();
+    MockDora::new(active_version)
+        .respond(
+            "list",
+            MockResponse::ok(
+                "UUID Name Status Nodes CPU Memory\n\
+                 019cc181-adad-7654-aa78-63502362337b flow-a Running 1 0.0% 0.0\n\
+                 019cc181-adad-7654-aa78-635023623380 flow-b Succeeded 2 0.0% 0.0",
+            ),
+        )
+        .respond(
+            "start",
+            MockResponse::ok(format!("dataflow started: {}", FAKE_DORA_UUID)),
+        )
+        .respond("stop", MockResponse::ok("dataflow stopped"))
+        .fallback(MockResponse::fail(1, "unknown command"))
+        .install(home)
+        .unwrap();
 }
 
 fn setup_fake_dora_home_with_active_file(
     home: &std::path::Path,
     active_version: &str,
 ) -> std::path::PathBuf {
-    let version_dir = dm_core::config::versions_dir(home).join(active_version);
-    std::fs::create_dir_all(&version_dir).unwrap();
-    let state_file = home.join("active_dataflow_id");
-
-    let bin = version_dir.join("dora");
-    std::fs::write(
-        &bin,
-        format!(
-            r#"#!/bin/sh
-cmd="$1"
-case "$cmd" in
-  --version)
-    echo "dora-cli 0.4.1"
-    ;;
-  check)
-    echo "Runtime OK"
-    ;;
-  list)
-    if [ -f "{state_file}" ]; then
-      echo "UUID Name Status Nodes CPU Memory"
-      printf "%s test-flow Running 1 0.0%% 0.0\\ GB\\n" "$(cat "{state_file}")"
-    fi
-    ;;
-  start)
-    run_yaml="$2"
-    run_dir="$(dirname "$run_yaml")"
-    mkdir -p "$run_dir/out/{fake_uuid}"
-    echo "worker log line" > "$run_dir/out/{fake_uuid}/log_worker.txt"
-    echo "{fake_uuid}" > "{state_file}"
-    echo "dataflow started: {fake_uuid}"
-    ;;
-  stop)
-    rm -f "{state_file}"
-    echo "dataflow stopped"
-    ;;
-  *)
-    exit 0
-    ;;
-esac
-"#,
-            state_file = state_file.display(),
-            fake_uuid = FAKE_DORA_UUID
-        ),
-    )
-    .unwrap();
-
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = std::fs::metadata(&bin).unwrap().permissions();
-        perms.set_mode(0o755);
-        std::fs::set_permissions(&bin, perms).unwrap();
-    }
-
-    dm_core::config::save_config(
-        home,
-        &dm_core::config::DmConfig {
-            active_version: Some(active_version.to_string()),
-            ..Default::default()
-        },
-    )
-    .unwrap();
-
-    state_file
+    MockDora::new(active_version)
+        .install(home)
+        .unwrap()
+        .state_file
 }
 
 fn setup_fake_dora_home_runtime_down(
     home: &std::path::Path,
     active_version: &str,
 ) -> std::path::PathBuf {
-    let version_dir = dm_core::config::versions_dir(home).join(active_version);
-    std::fs::create_dir_all(&version_dir).unwrap();
-    let state_file = home.join("active_dataflow_id");
-
-    let bin = version_dir.join("dora");
-    std::fs::write(
-        &bin,
-        format!(
-            r#"#!/bin/sh
-cmd="$1"
-case "$cmd" in
-  --version)
-    echo "dora-cli 0.4.1"
-    ;;
-  check)
-    echo "Runtime unavailable" >&2
-    exit 1
-    ;;
-  list)
-    if [ -f "{state_file}" ]; then
-      echo "UUID Name Status Nodes CPU Memory"
-      printf "%s test-flow Running 1 0.0%% 0.0\\ GB\\n" "$(cat "{state_file}")"
-    fi
-    ;;
-  *)
-    exit 0
-    ;;
-esac
-"#,
-            state_file = state_file.display(),
-        ),
-    )
-    .unwrap();
-
-    #[cfg(unix)]
-    {
-        use std::os::unix::fs::PermissionsExt;
-        let mut perms = std::fs::metadata(&bin).unwrap().permissions();
-        perms.set_mode(0o755);
-        std::fs::set_permissions(&bin, perms).unwrap();
-    }
-
-    dm_core::config::save_config(
-        home,
-        &dm_core::config::DmConfig {
-            active_version: Some(active_version.to_string()),
-            ..Default::default()
-        },
-    )
-    .unwrap();
-
-    state_file
+    MockDora::new(active_version)
+        .runtime_down()
+        .respond("start", MockResponse::ok(""))
+        .respond("stop", MockResponse::ok(""))
+        .install(home)
+        .unwrap()
+        .state_file
 }
 
 fn setup_installed_node(home: &std::path::Path, id: &str) {
@@ -224,37 +78,10 @@ fn setup_installed_node(home: &std::path::Path, id: &str) {
 }
 
 fn setup_node_with_build(home: &std::path::Path, id: &str, build: &str) {
-    let node_dir = dm_core::node::node_dir(home, id);
-    std::fs::create_dir_all(&node_dir).unwrap();
-    let meta = dm_core::node::Node {
-        id: id.to_string(),
-        name: String::new(),
-        version: "1.0.0".to_string(),
-        installed_at: "1234567890".to_string(),
-        source: dm_core::node::NodeSource {
-            build: build.to_string(),
-            github: None,
-        },
-        description: String::new(),
-        executable: String::new(),
-        repository: None,
-        maintainers: Vec::new(),
-        license: None,
-        display: dm_core::node::NodeDisplay::default(),
-        dm: None,
-        capabilities: Vec::new(),
-        runtime: dm_core::node::NodeRuntime::default(),
-        ports: Vec::new(),
-        dynamic_ports: false,
-        files: dm_core::node::NodeFiles::default(),
-        examples: Vec::new(),
-        config_schema: None,
-        interaction: None,
-        path: Default::default(),
-    };
-    std::fs::write(
-        dm_core::node::dm_json_path(home, id),
-        serde_json::to_string_pretty(&meta).unwrap(),
+    dm_test_utils::fake_node(
+        home,
+        id,
+        serde_json::json!({ "installed_at": "1234567890", "source": { "build": build } }),
     )
     .unwrap();
 }
@@ -300,7 +127,15 @@ async fn get_config_returns_default_config() {
 async fn doctor_handler_returns_ok_json() {
     let (_tmp, state) = test_state();
 
-    let resp = handlers::doctor(State(state)).await.into_response();
+    let resp = handlers::doctor(
+        State(state),
+        Query(handlers::system::DoctorParams {
+            history: None,
+            deep: None,
+        }),
+    )
+    .await
+    .into_response();
     assert_eq!(resp.status(), axum::http::StatusCode::OK);
 
     let body = body_text(resp).await;
@@ -308,6 +143,7 @@ async fn doctor_handler_returns_ok_json() {
     assert!(json.get("python").is_some());
     assert!(json.get("uv").is_some());
     assert!(json.get("rust").is_some());
+    assert!(json["recent_problems"].as_array().is_some());
 }
 
 #[tokio::test]
@@ -371,6 +207,49 @@ async fn update_config_persists_media_settings() {
     assert_eq!(cfg.media.mediamtx.path.as_deref(), Some("/tmp/mediamtx"));
 }
 
+#[tokio::test]
+async fn update_config_sets_identity_stamped_on_new_events() {
+    let (_tmp, state) = test_state();
+
+    let resp = handlers::update_config(
+        State(state.clone()),
+        Json(
+            serde_json::from_value(serde_json::json!({
+                "robot_id": "rover-7",
+                "labels": { "site": "lab-a" }
+            }))
+            .unwrap(),
+        ),
+    )
+    .await
+    .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+
+    let cfg = dm_core::config::load_config(&state.home).unwrap();
+    assert_eq!(cfg.robot_id.as_deref(), Some("rover-7"));
+    assert_eq!(cfg.labels.get("site").map(String::as_str), Some("lab-a"));
+
+    state
+        .events
+        .emit(
+            &dm_core::events::EventBuilder::new(dm_core::events::EventSource::Server, "probe")
+                .case_id("identity")
+                .build(),
+        )
+        .unwrap();
+    let events = state
+        .events
+        .query(&dm_core::events::EventFilter {
+            case_id: Some("identity".into()),
+            ..Default::default()
+        })
+        .unwrap();
+    let attrs: serde_json::Value =
+        serde_json::from_str(events[0].attributes.as_deref().unwrap()).unwrap();
+    assert_eq!(attrs["robot_id"], "rover-7");
+    assert_eq!(attrs["labels"]["site"], "lab-a");
+}
+
 #[tokio::test]
 async fn status_handler_uses_fake_dora_binary() {
     let (_tmp, state) = test_state();
@@ -390,6 +269,49 @@ async fn status_handler_uses_fake_dora_binary() {
     assert!(json["dora_probe"].as_array().unwrap().is_empty());
 }
 
+#[tokio::test]
+async fn status_stream_sends_current_report_first() {
+    use futures_util::StreamExt;
+
+    let (_tmp, state) = test_state();
+    setup_fake_dora_home(&state.home, "0.4.1");
+
+    let resp = handlers::status_stream(State(state)).await.into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+
+    let mut body = resp.into_body().into_data_stream();
+    let frame = tokio::time::timeout(std::time::Duration::from_secs(5), body.next())
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    let frame = String::from_utf8(frame.to_vec()).unwrap();
+    assert!(frame.starts_with("event: status"), "{frame}");
+    assert!(frame.contains("\"active_version\":\"0.4.1\""), "{frame}");
+}
+
+#[tokio::test]
+async fn status_hub_publishes_only_meaningful_changes() {
+    let (_tmp, state) = test_state();
+    setup_fake_dora_home(&state.home, "0.4.1");
+    let mut rx = state.status.subscribe();
+
+    let report = dm_core::status(&state.home, false).await.unwrap();
+    assert!(state.status.publish(report.clone()));
+    assert!(rx.has_changed().unwrap());
+    rx.borrow_and_update();
+
+    let mut same = report.clone();
+    same.runtime_output = "Runtime OK (again)".to_string();
+    assert!(!state.status.publish(same));
+    assert!(!rx.has_changed().unwrap());
+
+    let mut down = report;
+    down.runtime_running = false;
+    assert!(state.status.publish(down));
+    assert!(rx.has_changed().unwrap());
+}
+
 #[tokio::test]
 async fn node_status_returns_404_for_missing_node() {
     let (_tmp, state) = test_state();
@@ -419,16 +341,38 @@ async fn node_status_returns_entry_for_installed_node() {
     assert_eq!(json["version"], "1.0.0");
 }
 
+#[tokio::test]
+async fn node_env_preview_reports_injected_env() {
+    let (_tmp, state) = test_state();
+    setup_installed_node(&state.home, "demo-node");
+
+    let missing =
+        handlers::node_env_preview(State(state.clone()), Path("missing-node".to_string()))
+            .await
+            .into_response();
+    assert_eq!(missing.status(), axum::http::StatusCode::NOT_FOUND);
+
+    let resp = handlers::node_env_preview(State(state), Path("demo-node".to_string()))
+        .await
+        .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    assert_eq!(json["node_env"]["DM_NODE_ID"], "demo-node");
+    assert_eq!(
+        json["node_env"]["DM_RUN_ID"],
+        dm_core::dataflow::PREVIEW_RUN_ID
+    );
+}
+
 #[tokio::test]
 async fn node_status_returns_structured_capabilities_for_bindings() {
     let (_tmp, state) = test_state();
     let id = "dm-bound-node";
-    let node_dir = dm_core::node::node_dir(&state.home, id);
-    std::fs::create_dir_all(&node_dir).unwrap();
-    std::fs::write(
-        dm_core::node::dm_json_path(&state.home, id),
+    dm_test_utils::fake_node(
+        &state.home,
+        id,
         serde_json::json!({
-            "id": id,
             "name": "DM Bound Node",
             "version": "0.1.0",
             "installed_at": "1234567890",
@@ -449,8 +393,7 @@ async fn node_status_returns_structured_capabilities_for_bindings() {
                     ]
                 }
             ]
-        })
-        .to_string(),
+        }),
     )
     .unwrap();
 
@@ -484,6 +427,52 @@ async fn list_nodes_returns_builtin_entries() {
         .any(|node| node["id"] == "dm-test-audio-capture"));
 }
 
+#[tokio::test]
+async fn list_registry_reports_bundled_source_offline() {
+    let (_tmp, state) = test_state();
+
+    let resp = handlers::list_registry(
+        State(state),
+        Query(handlers::nodes::RegistryParams {
+            q: Some("display".to_string()),
+            refresh: Some(false),
+        }),
+    )
+    .await
+    .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    assert_eq!(json["source"], "bundled");
+    let nodes = json["nodes"].as_array().unwrap();
+    assert!(nodes.iter().any(|node| node["id"] == "dm-display"));
+    assert!(nodes.iter().all(|node| node["id"] != "dm-and"));
+}
+
+#[tokio::test]
+async fn list_collections_returns_registry_bundles() {
+    let (_tmp, state) = test_state();
+
+    let resp = handlers::list_collections(State(state))
+        .await
+        .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    let speech = json
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["name"] == "speech-stack")
+        .expect("speech-stack collection");
+    assert_eq!(speech["origin"], "registry");
+    assert!(speech["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|node| node == "dora-vad"));
+}
+
 #[tokio::test]
 async fn list_dataflows_returns_empty_array() {
     let (_tmp, state) = test_state();
@@ -496,6 +485,138 @@ async fn list_dataflows_returns_empty_array() {
     assert!(dataflows.is_empty());
 }
 
+#[tokio::test]
+async fn validate_all_dataflows_flags_missing_nodes() {
+    let (_tmp, state) = test_state();
+    dm_core::dataflow::save(&state.home, "empty-flow", "nodes: []").unwrap();
+    dm_core::dataflow::save(
+        &state.home,
+        "broken-flow",
+        "nodes:\n  - id: cam\n    node: not-installed\n",
+    )
+    .unwrap();
+
+    let resp = handlers::validate_all_dataflows(State(state))
+        .await
+        .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    assert_eq!(json["failing"], 1);
+    let flows = json["dataflows"].as_array().unwrap();
+    let broken = flows.iter().find(|f| f["name"] == "broken-flow").unwrap();
+    assert_eq!(broken["ok"], false);
+    assert!(broken["problems"][0]
+        .as_str()
+        .unwrap()
+        .contains("not-installed"));
+}
+
+#[tokio::test]
+async fn validate_graph_accepts_yaml_text_and_editor_json() {
+    let (_tmp, state) = test_state();
+
+    let resp = handlers::validate_graph(
+        State(state.clone()),
+        Json(
+            serde_json::from_value(serde_json::json!({
+                "graph": "nodes:\n  - id: a\n    path: ./a.py\n    inputs:\n      x: b/y\n"
+            }))
+            .unwrap(),
+        ),
+    )
+    .await
+    .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    assert_eq!(json["valid"], false);
+    assert_eq!(json["errors"][0]["node"], "a");
+    assert!(json["errors"][0]["message"]
+        .as_str()
+        .unwrap()
+        .contains("not a node in this graph"));
+
+    let resp = handlers::validate_graph(
+        State(state),
+        Json(
+            serde_json::from_value(serde_json::json!({
+                "graph": {
+                    "nodes": [
+                        { "id": "a", "data": { "nodeType": "./a.py", "outputs": ["y"] } },
+                        { "id": "b", "data": { "nodeType": "./b.py" } }
+                    ],
+                    "edges": [
+                        { "source": "a", "target": "b", "sourceHandle": "out-y", "targetHandle": "in-x" }
+                    ]
+                }
+            }))
+            .unwrap(),
+        ),
+    )
+    .await
+    .into_response();
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    assert_eq!(json["valid"], true, "{}", json);
+}
+
+#[tokio::test]
+async fn convert_graph_switches_between_yaml_and_editor_json() {
+    let resp = handlers::convert_graph(Json(
+        serde_json::from_value(serde_json::json!({
+            "graph": "nodes:\n  - id: a\n    node: demo\n    outputs: [y]\n  - id: b\n    node: demo\n    inputs:\n      x: a/y\n",
+            "to": "json"
+        }))
+        .unwrap(),
+    ))
+    .await
+    .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    let graph = json["graph"].clone();
+    assert_eq!(graph["nodes"][1]["type"], "dmNode");
+    assert_eq!(graph["edges"][0]["targetHandle"], "in-x");
+
+    let resp = handlers::convert_graph(Json(
+        serde_json::from_value(serde_json::json!({ "graph": graph, "to": "yaml" })).unwrap(),
+    ))
+    .await
+    .into_response();
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    let yaml: serde_yaml::Value = serde_yaml::from_str(json["yaml"].as_str().unwrap()).unwrap();
+    assert_eq!(yaml["nodes"][1]["inputs"]["x"].as_str(), Some("a/y"));
+
+    let resp = handlers::convert_graph(Json(
+        serde_json::from_value(serde_json::json!({ "graph": "nodes: [", "to": "yaml" })).unwrap(),
+    ))
+    .await
+    .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn stop_tagged_dataflows_reports_each_match() {
+    let (_tmp, state) = test_state();
+    dm_core::dataflow::save(&state.home, "cam", "nodes: []").unwrap();
+    let mut meta = dm_core::dataflow::get_flow_meta(&state.home, "cam").unwrap();
+    meta.tags = vec!["perception".to_string()];
+    dm_core::dataflow::save_flow_meta(&state.home, "cam", &meta).unwrap();
+
+    let params = |tag: &str| {
+        Query(handlers::dataflow::TagParams {
+            tag: tag.to_string(),
+            force: None,
+        })
+    };
+    let resp = handlers::stop_tagged_dataflows(State(state.clone()), params("perception")).await;
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    assert_eq!(json["results"][0]["dataflow"], "cam");
+    assert_eq!(json["failed"], 0);
+
+    let missing = handlers::stop_tagged_dataflows(State(state), params("control")).await;
+    assert_eq!(missing.status(), axum::http::StatusCode::NOT_FOUND);
+}
+
 #[tokio::test]
 async fn dataflow_crud_handlers_roundtrip() {
     let (_tmp, state) = test_state();
@@ -547,6 +668,127 @@ async fn dataflow_crud_handlers_roundtrip() {
     assert_eq!(missing_resp.status(), axum::http::StatusCode::NOT_FOUND);
 }
 
+async fn multipart_upload(fields: &[(&str, Option<&str>, &str)]) -> axum::extract::Multipart {
+    use axum::extract::FromRequest;
+
+    let boundary = "dm-test-boundary";
+    let mut body = String::new();
+    for (name, file_name, value) in fields {
+        body.push_str(&format!("--{boundary}\r\n"));
+        match file_name {
+            Some(file_name) => body.push_str(&format!(
+                "Content-Disposition: form-data; name=\"{name}\"; filename=\"{file_name}\"\r\n\r\n"
+            )),
+            None => body.push_str(&format!(
+                "Content-Disposition: form-data; name=\"{name}\"\r\n\r\n"
+            )),
+        }
+        body.push_str(value);
+        body.push_str("\r\n");
+    }
+    body.push_str(&format!("--{boundary}--\r\n"));
+    let request = axum::http::Request::builder()
+        .method("POST")
+        .header(
+            axum::http::header::CONTENT_TYPE,
+            format!("multipart/form-data; boundary={boundary}"),
+        )
+        .body(axum::body::Body::from(body))
+        .unwrap();
+    axum::extract::Multipart::from_request(request, &())
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn dataflow_upload_and_download_roundtrip() {
+    let (_tmp, state) = test_state();
+    let yaml = "nodes:\n  - id: timer\n    path: dora/timer\n";
+
+    let resp = handlers::upload_dataflow(
+        State(state.clone()),
+        multipart_upload(&[("file", Some("camera-demo.yaml"), yaml)]).await,
+    )
+    .await;
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    let project: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    assert_eq!(project["name"], "camera-demo");
+
+    let again = handlers::upload_dataflow(
+        State(state.clone()),
+        multipart_upload(&[("file", Some("camera-demo.yaml"), yaml)]).await,
+    )
+    .await;
+    assert_eq!(again.status(), axum::http::StatusCode::CONFLICT);
+
+    let invalid = handlers::upload_dataflow(
+        State(state.clone()),
+        multipart_upload(&[
+            ("file", Some("broken.yml"), "nodes: [unclosed"),
+            ("name", None, "broken"),
+        ])
+        .await,
+    )
+    .await;
+    assert_eq!(invalid.status(), axum::http::StatusCode::BAD_REQUEST);
+
+    let download =
+        handlers::download_dataflow(State(state.clone()), Path("camera-demo".to_string())).await;
+    assert_eq!(download.status(), axum::http::StatusCode::OK);
+    assert_eq!(
+        download.headers()[axum::http::header::CONTENT_DISPOSITION],
+        "attachment; filename=\"camera-demo.yml\""
+    );
+    assert_eq!(body_text(download).await, yaml);
+
+    let missing = handlers::download_dataflow(State(state), Path("nope".to_string())).await;
+    assert_eq!(missing.status(), axum::http::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn export_and_import_bundle_handlers_roundtrip() {
+    let (_tmp, state) = test_state();
+    dm_core::dataflow::save(&state.home, "shared", "nodes: []\n").unwrap();
+
+    let export = handlers::export_dataflow(State(state.clone()), Path("shared".to_string())).await;
+    assert_eq!(export.status(), axum::http::StatusCode::OK);
+    assert_eq!(
+        export.headers()[axum::http::header::CONTENT_DISPOSITION],
+        "attachment; filename=\"shared.dmflow\""
+    );
+    let bundle: serde_json::Value = serde_json::from_str(&body_text(export).await).unwrap();
+    assert_eq!(bundle["yaml"], "nodes: []\n");
+
+    let import = handlers::import_dataflow_bundle(
+        State(state.clone()),
+        Json(
+            serde_json::from_value(serde_json::json!({ "bundle": bundle, "name": "copy" }))
+                .unwrap(),
+        ),
+    )
+    .await
+    .into_response();
+    assert_eq!(import.status(), axum::http::StatusCode::OK);
+    assert_eq!(
+        dm_core::dataflow::get(&state.home, "copy").unwrap().yaml,
+        "nodes: []\n"
+    );
+
+    let again = handlers::import_dataflow_bundle(
+        State(state.clone()),
+        Json(
+            serde_json::from_value(serde_json::json!({ "bundle": bundle, "name": "copy" }))
+                .unwrap(),
+        ),
+    )
+    .await
+    .into_response();
+    assert_eq!(again.status(), axum::http::StatusCode::CONFLICT);
+
+    let missing = handlers::export_dataflow(State(state), Path("nope".to_string())).await;
+    assert_eq!(missing.status(), axum::http::StatusCode::NOT_FOUND);
+}
+
 #[tokio::test]
 async fn dataflow_meta_and_config_handlers_roundtrip() {
     let (_tmp, state) = test_state();
@@ -645,19 +887,40 @@ async fn dataflow_history_handlers_roundtrip() {
     let version_json: serde_json::Value = serde_json::from_str(&version_body).unwrap();
     assert_eq!(version_json["yaml"], "nodes: []\n");
 
-    let restore_resp = handlers::restore_dataflow_history_version(
+    let diff_resp = handlers::diff_dataflow_history_version(
         State(state.clone()),
-        Path(("demo-flow".to_string(), version)),
+        Path(("demo-flow".to_string(), version.clone())),
+        Query(handlers::dataflow::HistoryDiffParams { against: None }),
     )
     .await
     .into_response();
-    assert_eq!(restore_resp.status(), axum::http::StatusCode::OK);
+    assert_eq!(diff_resp.status(), axum::http::StatusCode::OK);
+    let diff_json: serde_json::Value = serde_json::from_str(&body_text(diff_resp).await).unwrap();
+    assert_eq!(diff_json["against"], "current");
+    assert_eq!(diff_json["added"], 2);
+    assert_eq!(diff_json["removed"], 1);
 
-    let get_resp = handlers::get_dataflow(State(state), Path("demo-flow".to_string()))
-        .await
-        .into_response();
-    let get_body = body_text(get_resp).await;
-    let get_json: serde_json::Value = serde_json::from_str(&get_body).unwrap();
+    let escape_resp = handlers::get_dataflow_history_version(
+        State(state.clone()),
+        Path(("demo-flow".to_string(), "../dataflow".to_string())),
+    )
+    .await
+    .into_response();
+    assert_eq!(escape_resp.status(), axum::http::StatusCode::NOT_FOUND);
+
+    let restore_resp = handlers::restore_dataflow_history_version(
+        State(state.clone()),
+        Path(("demo-flow".to_string(), version)),
+    )
+    .await
+    .into_response();
+    assert_eq!(restore_resp.status(), axum::http::StatusCode::OK);
+
+    let get_resp = handlers::get_dataflow(State(state), Path("demo-flow".to_string()))
+        .await
+        .into_response();
+    let get_body = body_text(get_resp).await;
+    let get_json: serde_json::Value = serde_json::from_str(&get_body).unwrap();
     assert_eq!(get_json["yaml"], "nodes: []\n");
 }
 
@@ -729,7 +992,7 @@ async fn get_dataflow_config_schema_returns_aggregated_fields() {
         State(state.clone()),
         Path("cfg-node".to_string()),
         Json(serde_json::json!({
-            "mode": "node-mode"
+            "mode": "flow-mode"
         })),
     )
     .await
@@ -748,7 +1011,7 @@ async fn get_dataflow_config_schema_returns_aggregated_fields() {
     );
     assert_eq!(
         json["nodes"][0]["fields"]["mode"]["effective_value"],
-        "node-mode"
+        "flow-mode"
     );
     assert_eq!(json["executable"]["can_run"], true);
 }
@@ -837,6 +1100,63 @@ async fn uninstall_node_returns_success_for_existing_node() {
     assert!(!dm_core::node::node_dir(&state.home, "demo-node").exists());
 }
 
+#[tokio::test]
+async fn uninstall_node_reports_and_tags_referencing_dataflows() {
+    let (_tmp, state) = test_state();
+    setup_installed_node(&state.home, "demo-node");
+    dm_core::dataflow::save(
+        &state.home,
+        "demo-flow",
+        "nodes:\n  - id: demo\n    node: demo-node\n",
+    )
+    .unwrap();
+
+    let resp = handlers::uninstall_node(
+        State(state.clone()),
+        Json(
+            serde_json::from_value(serde_json::json!({
+                "id": "demo-node",
+                "tag_dataflows": true
+            }))
+            .unwrap(),
+        ),
+    )
+    .await
+    .into_response();
+
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    let body: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    assert_eq!(
+        body["referencing_dataflows"],
+        serde_json::json!(["demo-flow"])
+    );
+    assert_eq!(body["tagged_dataflows"], serde_json::json!(["demo-flow"]));
+}
+
+#[tokio::test]
+async fn uninstall_node_returns_conflict_while_node_is_locked() {
+    let (_tmp, state) = test_state();
+    setup_installed_node(&state.home, "demo-node");
+    let _held =
+        dm_core::lock::HomeLock::acquire(&state.home, "node-demo-node", "node install").unwrap();
+
+    let resp = handlers::uninstall_node(
+        State(state.clone()),
+        Json(
+            serde_json::from_value(serde_json::json!({
+                "id": "demo-node"
+            }))
+            .unwrap(),
+        ),
+    )
+    .await
+    .into_response();
+
+    assert_eq!(resp.status(), axum::http::StatusCode::CONFLICT);
+    assert!(body_text(resp).await.contains("held by pid"));
+    assert!(dm_core::node::node_dir(&state.home, "demo-node").exists());
+}
+
 #[tokio::test]
 async fn create_node_returns_success_and_duplicate_returns_bad_request() {
     let (_tmp, state) = test_state();
@@ -846,7 +1166,10 @@ async fn create_node_returns_success_and_duplicate_returns_bad_request() {
         Json(
             serde_json::from_value(serde_json::json!({
                 "id": "new-node",
-                "description": "test node"
+                "description": "test node",
+                "author": "Jane Doe",
+                "license": "MIT",
+                "category": "Utility"
             }))
             .unwrap(),
         ),
@@ -854,6 +1177,10 @@ async fn create_node_returns_success_and_duplicate_returns_bad_request() {
     .await
     .into_response();
     assert_eq!(create_resp.status(), axum::http::StatusCode::OK);
+    let created: serde_json::Value = serde_json::from_str(&body_text(create_resp).await).unwrap();
+    assert_eq!(created["license"], "MIT");
+    assert_eq!(created["maintainers"][0]["name"], "Jane Doe");
+    assert_eq!(created["display"]["category"], "Utility");
 
     let duplicate_resp = handlers::create_node(
         State(state),
@@ -867,7 +1194,7 @@ async fn create_node_returns_success_and_duplicate_returns_bad_request() {
     )
     .await
     .into_response();
-    assert_eq!(duplicate_resp.status(), axum::http::StatusCode::BAD_REQUEST);
+    assert_eq!(duplicate_resp.status(), axum::http::StatusCode::CONFLICT);
 }
 
 #[tokio::test]
@@ -896,7 +1223,152 @@ async fn node_config_handlers_roundtrip() {
 }
 
 #[tokio::test]
-async fn save_node_config_returns_bad_request_for_missing_node() {
+async fn node_schema_put_then_validate_reports_pointer_errors() {
+    let (_tmp, state) = test_state();
+    let home = state.home.clone();
+    dm_core::node::create_node(&home, "cfg-node", "configurable").unwrap();
+
+    let put_resp = handlers::set_node_schema(
+        State(state.clone()),
+        Path("cfg-node".to_string()),
+        Json(serde_json::json!({
+            "type": "object",
+            "properties": {
+                "threshold": { "type": "number", "maximum": 1, "env": "THRESHOLD" }
+            },
+            "required": ["threshold"]
+        })),
+    )
+    .await
+    .into_response();
+    assert_eq!(put_resp.status(), axum::http::StatusCode::OK);
+
+    let node = dm_core::node::node_status(&home, "cfg-node")
+        .unwrap()
+        .unwrap();
+    assert_eq!(node.config_schema.unwrap()["threshold"]["required"], true);
+
+    let ok_resp = handlers::validate_node_config(
+        State(state.clone()),
+        Path("cfg-node".to_string()),
+        Json(serde_json::json!({ "threshold": 0.4 })),
+    )
+    .await
+    .into_response();
+    assert_eq!(ok_resp.status(), axum::http::StatusCode::OK);
+
+    let bad_resp = handlers::validate_node_config(
+        State(state),
+        Path("cfg-node".to_string()),
+        Json(serde_json::json!({ "threshold": 4 })),
+    )
+    .await
+    .into_response();
+    assert_eq!(
+        bad_resp.status(),
+        axum::http::StatusCode::UNPROCESSABLE_ENTITY
+    );
+    let json: serde_json::Value = serde_json::from_str(&body_text(bad_resp).await).unwrap();
+    assert_eq!(json["valid"], false);
+    assert_eq!(json["errors"][0]["pointer"], "/threshold");
+}
+
+#[tokio::test]
+async fn save_node_config_rejects_schema_violations_with_pointers() {
+    let (_tmp, state) = test_state();
+    dm_core::node::create_node(&state.home, "cfg-node", "configurable").unwrap();
+    dm_core::node::set_config_schema(
+        &state.home,
+        "cfg-node",
+        serde_json::json!({ "threshold": { "type": "number", "maximum": 1 } }),
+    )
+    .unwrap();
+
+    let resp = handlers::save_node_config(
+        State(state.clone()),
+        Path("cfg-node".to_string()),
+        Json(serde_json::json!({ "threshold": 4, "extra": true })),
+    )
+    .await
+    .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::UNPROCESSABLE_ENTITY);
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    assert_eq!(json["valid"], false);
+    let pointers: Vec<_> = json["errors"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["pointer"].as_str().unwrap().to_string())
+        .collect();
+    // Undeclared keys are allowed, as in JSON Schema.
+    assert_eq!(pointers, ["/threshold"]);
+    assert_eq!(
+        dm_core::node::get_node_config(&state.home, "cfg-node").unwrap(),
+        serde_json::json!({})
+    );
+}
+
+#[tokio::test]
+async fn set_node_schema_rejects_invalid_schema() {
+    let (_tmp, state) = test_state();
+    dm_core::node::create_node(&state.home, "cfg-node", "configurable").unwrap();
+
+    let resp = handlers::set_node_schema(
+        State(state),
+        Path("cfg-node".to_string()),
+        Json(serde_json::json!({ "threshold": { "type": "float" } })),
+    )
+    .await
+    .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn pin_and_unpin_node_handlers_update_node_status() {
+    let (_tmp, state) = test_state();
+    setup_installed_node(&state.home, "demo-node");
+
+    let resp = handlers::pin_node(
+        State(state.clone()),
+        Path("demo-node".to_string()),
+        Json(serde_json::from_value(serde_json::json!({ "version": "1.0.0" })).unwrap()),
+    )
+    .await
+    .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    let node = dm_core::node::node_status(&state.home, "demo-node")
+        .unwrap()
+        .unwrap();
+    assert_eq!(node.pinned.as_deref(), Some("1.0.0"));
+
+    let resp = handlers::unpin_node(State(state.clone()), Path("demo-node".to_string()))
+        .await
+        .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    let node = dm_core::node::node_status(&state.home, "demo-node")
+        .unwrap()
+        .unwrap();
+    assert!(node.pinned.is_none());
+}
+
+#[tokio::test]
+async fn test_node_handler_rejects_unknown_and_uninstalled_nodes() {
+    let (_tmp, state) = test_state();
+    setup_installed_node(&state.home, "demo-node");
+
+    let resp = handlers::test_node(State(state.clone()), Path("missing-node".to_string()), None)
+        .await
+        .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::NOT_FOUND);
+
+    let resp = handlers::test_node(State(state), Path("demo-node".to_string()), None)
+        .await
+        .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn save_node_config_returns_not_found_for_missing_node() {
     let (_tmp, state) = test_state();
 
     let resp = handlers::save_node_config(
@@ -906,7 +1378,7 @@ async fn save_node_config_returns_bad_request_for_missing_node() {
     )
     .await
     .into_response();
-    assert_eq!(resp.status(), axum::http::StatusCode::BAD_REQUEST);
+    assert_eq!(resp.status(), axum::http::StatusCode::NOT_FOUND);
 }
 
 #[tokio::test]
@@ -928,6 +1400,114 @@ async fn install_node_returns_bad_request_for_missing_node() {
     assert_eq!(resp.status(), axum::http::StatusCode::BAD_REQUEST);
 }
 
+#[tokio::test]
+async fn install_nodes_reports_each_failure() {
+    let (_tmp, state) = test_state();
+
+    let resp = handlers::install_nodes(
+        State(state),
+        Json(
+            serde_json::from_value(serde_json::json!({
+                "ids": ["missing-a", "missing-b"],
+                "parallelism": 2
+            }))
+            .unwrap(),
+        ),
+    )
+    .await
+    .into_response();
+
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    let outcomes: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    let outcomes = outcomes.as_array().unwrap();
+    assert_eq!(outcomes.len(), 2);
+    assert_eq!(outcomes[0]["node_id"], "missing-a");
+    assert_eq!(outcomes[1]["node_id"], "missing-b");
+    assert!(outcomes[1]["version"].is_null());
+    assert!(outcomes[1]["error"].as_str().unwrap().contains("not found"));
+}
+
+#[tokio::test]
+async fn node_install_job_records_failure_and_replays_history() {
+    let (_tmp, state) = test_state();
+
+    let resp = handlers::start_job(
+        State(state.clone()),
+        Json(
+            serde_json::from_value(serde_json::json!({
+                "kind": "node_install",
+                "id": "missing-node"
+            }))
+            .unwrap(),
+        ),
+    )
+    .await
+    .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::ACCEPTED);
+    let job: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    let job_id = job["id"].as_str().unwrap().to_string();
+    assert_eq!(job["kind"], "node_install");
+
+    let mut snapshot = None;
+    for _ in 0..50 {
+        let resp = handlers::get_job(State(state.clone()), Path(job_id.clone()))
+            .await
+            .into_response();
+        let body: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+        if body["status"] != "running" {
+            snapshot = Some(body);
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    let snapshot = snapshot.expect("job did not finish");
+    assert_eq!(snapshot["status"], "failed");
+    assert!(snapshot["error"].as_str().unwrap().contains("not found"));
+
+    let subscription = state.jobs.subscribe(&job_id).unwrap();
+    assert!(subscription.finished);
+    let last = subscription.history.last().unwrap();
+    assert_eq!(last.phase, "finished");
+    assert_eq!(last.seq, subscription.history.len() as u64);
+}
+
+#[test]
+fn job_subscription_replays_progress_with_bytes() {
+    use crate::services::jobs::{JobKind, JobManager, JobStatus};
+    use dm_core::types::{InstallPhase, InstallProgress};
+
+    let jobs = JobManager::new();
+    let job = jobs.create(JobKind::Install, "0.4.1");
+    jobs.progress(
+        &job.id,
+        &InstallProgress {
+            phase: InstallPhase::Downloading {
+                bytes_done: 50,
+                bytes_total: 200,
+            },
+            message: "Downloading".to_string(),
+        },
+    );
+
+    let live = jobs.subscribe(&job.id).unwrap();
+    assert!(!live.finished);
+    assert_eq!(live.history.len(), 1);
+    assert_eq!(live.history[0].pct, Some(25));
+    assert_eq!(live.history[0].bytes_total, Some(200));
+
+    jobs.finish(&job.id, Ok(serde_json::json!({ "version": "0.4.1" })));
+    let mut updates = live.updates;
+    let terminal = updates.try_recv().unwrap();
+    assert_eq!(terminal.seq, 2);
+    assert_eq!(terminal.status, JobStatus::Succeeded);
+
+    let late = jobs.subscribe(&job.id).unwrap();
+    assert!(late.finished);
+    assert_eq!(late.history.len(), 2);
+    assert_eq!(jobs.list()[0].status, JobStatus::Succeeded);
+    assert!(jobs.get("job-unknown").is_none());
+}
+
 #[tokio::test]
 async fn install_node_returns_bad_request_for_unsupported_build() {
     let (_tmp, state) = test_state();
@@ -982,6 +1562,34 @@ async fn import_node_imports_local_directory_with_inferred_id() {
     assert!(dm_core::node::node_dir(&state.home, "relative-node").exists());
 }
 
+#[tokio::test]
+#[cfg(not(target_os = "windows"))]
+async fn export_node_returns_a_dmnode_attachment() {
+    let (_tmp, state) = test_state();
+    dm_test_utils::fake_node(
+        &state.home,
+        "demo",
+        serde_json::json!({ "source": { "build": "pip install -e ." } }),
+    )
+    .unwrap();
+
+    let resp = handlers::export_node(State(state.clone()), Path("demo".to_string()))
+        .await
+        .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    assert_eq!(
+        resp.headers()[axum::http::header::CONTENT_DISPOSITION],
+        "attachment; filename=\"demo.dmnode\""
+    );
+    let bytes = to_bytes(resp.into_body(), usize::MAX).await.unwrap();
+    assert_eq!(&bytes[..2], &[0x1f, 0x8b]);
+
+    let missing = handlers::export_node(State(state), Path("ghost".to_string()))
+        .await
+        .into_response();
+    assert_eq!(missing.status(), axum::http::StatusCode::NOT_FOUND);
+}
+
 #[tokio::test]
 async fn import_node_returns_bad_request_for_missing_source() {
     let (_tmp, state) = test_state();
@@ -1063,6 +1671,8 @@ async fn node_file_handlers_map_traversal_and_missing_paths() {
     .await
     .into_response();
     assert_eq!(bad_resp.status(), axum::http::StatusCode::BAD_REQUEST);
+    let bad: serde_json::Value = serde_json::from_str(&body_text(bad_resp).await).unwrap();
+    assert_eq!(bad["code"], "invalid");
 
     let missing_resp = handlers::get_node_file_content(
         State(state),
@@ -1071,6 +1681,45 @@ async fn node_file_handlers_map_traversal_and_missing_paths() {
     .await
     .into_response();
     assert_eq!(missing_resp.status(), axum::http::StatusCode::NOT_FOUND);
+    let missing: serde_json::Value = serde_json::from_str(&body_text(missing_resp).await).unwrap();
+    assert_eq!(missing["code"], "not_found");
+    assert_eq!(
+        missing["details"],
+        serde_json::json!({ "resource": "node", "id": "missing-node" })
+    );
+}
+
+#[tokio::test]
+async fn errors_are_reported_as_coded_json() {
+    let (_tmp, state) = test_state();
+
+    let resp = handlers::get_run(
+        State(state.clone()),
+        Path("no-such-run".to_string()),
+        Query(handlers::runs::RunDetailParams {
+            include_metrics: None,
+        }),
+    )
+    .await
+    .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::NOT_FOUND);
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "code": "not_found",
+            "error": "Run 'no-such-run' not found",
+            "details": { "resource": "run", "id": "no-such-run" },
+        })
+    );
+
+    let resp = handlers::get_job(State(state), Path("no-such-job".to_string()))
+        .await
+        .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::NOT_FOUND);
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    assert_eq!(json["code"], "not_found");
+    assert_eq!(json["error"], "Job 'no-such-job' not found");
 }
 
 #[tokio::test]
@@ -1111,47 +1760,225 @@ async fn up_and_down_handlers_use_fake_dora_binary() {
 }
 
 #[tokio::test]
-async fn ingest_and_query_events_roundtrip() {
+async fn ingest_events_batch_inserts_all_events() {
+    let (_tmp, state) = test_state();
+
+    let events = (0..3)
+        .map(|i| {
+            dm_core::events::EventBuilder::new(dm_core::events::EventSource::Frontend, "ui.log")
+                .case_id("batch_test")
+                .message(format!("line {i}"))
+                .build()
+        })
+        .collect();
+
+    let resp = handlers::ingest_events_batch(State(state.clone()), HeaderMap::new(), Json(events))
+        .await
+        .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    assert_eq!(json["inserted"], 3);
+
+    let count = state
+        .events
+        .count(&dm_core::events::EventFilter {
+            case_id: Some("batch_test".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(count, 3);
+}
+
+#[tokio::test]
+async fn ingest_and_query_events_roundtrip() {
+    let (_tmp, state) = test_state();
+
+    let event =
+        dm_core::events::EventBuilder::new(dm_core::events::EventSource::Frontend, "ui.click")
+            .case_id("session_test")
+            .message("clicked")
+            .attr("button", "run")
+            .build();
+
+    let ingest_resp = handlers::ingest_event(State(state.clone()), HeaderMap::new(), Json(event))
+        .await
+        .into_response();
+    assert_eq!(ingest_resp.status(), axum::http::StatusCode::OK);
+
+    let query_resp = handlers::query_events(
+        State(state),
+        Query(Default::default()),
+        Query(dm_core::events::EventFilter {
+            case_id: Some("session_test".to_string()),
+            ..Default::default()
+        }),
+    )
+    .await
+    .into_response();
+    assert_eq!(query_resp.status(), axum::http::StatusCode::OK);
+
+    let body = body_text(query_resp).await;
+    let events: Vec<dm_core::events::Event> = serde_json::from_str(&body).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].activity, "ui.click");
+    assert_eq!(events[0].source, "frontend");
+}
+
+#[tokio::test]
+async fn ingest_rejects_sources_outside_the_allowlist() {
+    let (_tmp, state) = test_state();
+    std::fs::write(
+        dm_core::config::config_path(&state.home),
+        "[[ingest.tokens]]\nname = \"ci\"\ntoken = \"ci-secret\"\nsources = [\"ci\"]\n",
+    )
+    .unwrap();
+    let event = |source| {
+        dm_core::events::EventBuilder::new(source, "test.run")
+            .case_id("ingest_acl")
+            .build()
+    };
+    let bearer = |token: &str| {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            axum::http::header::AUTHORIZATION,
+            format!("Bearer {token}").parse().unwrap(),
+        );
+        headers
+    };
+    let anonymous_core = handlers::ingest_event(
+        State(state.clone()),
+        HeaderMap::new(),
+        Json(event(dm_core::events::EventSource::Core)),
+    )
+    .await
+    .into_response();
+    assert_eq!(anonymous_core.status(), axum::http::StatusCode::FORBIDDEN);
+
+    let ci = handlers::ingest_event(
+        State(state.clone()),
+        bearer("ci-secret"),
+        Json(event(dm_core::events::EventSource::Ci)),
+    )
+    .await
+    .into_response();
+    assert_eq!(ci.status(), axum::http::StatusCode::OK);
+
+    let mixed_batch = handlers::ingest_events_batch(
+        State(state.clone()),
+        bearer("ci-secret"),
+        Json(vec![
+            event(dm_core::events::EventSource::Ci),
+            event(dm_core::events::EventSource::Frontend),
+        ]),
+    )
+    .await
+    .into_response();
+    assert_eq!(mixed_batch.status(), axum::http::StatusCode::FORBIDDEN);
+
+    let unknown = handlers::ingest_event(
+        State(state.clone()),
+        bearer("guess"),
+        Json(event(dm_core::events::EventSource::Ci)),
+    )
+    .await
+    .into_response();
+    assert_eq!(unknown.status(), axum::http::StatusCode::FORBIDDEN);
+
+    let stored = state
+        .events
+        .count(&dm_core::events::EventFilter {
+            case_id: Some("ingest_acl".to_string()),
+            ..Default::default()
+        })
+        .unwrap();
+    assert_eq!(stored, 1);
+}
+
+#[tokio::test]
+async fn event_stats_groups_counts_and_rejects_unknown_dimensions() {
     let (_tmp, state) = test_state();
-
-    let event =
-        dm_core::events::EventBuilder::new(dm_core::events::EventSource::Frontend, "ui.click")
-            .case_id("session_test")
-            .message("clicked")
-            .attr("button", "run")
-            .build();
-
-    let ingest_resp = handlers::ingest_event(State(state.clone()), Json(event))
-        .await
-        .into_response();
-    assert_eq!(ingest_resp.status(), axum::http::StatusCode::OK);
-
-    let query_resp = handlers::query_events(
+    let events: Vec<_> = [
+        dm_core::events::EventLevel::Info,
+        dm_core::events::EventLevel::Error,
+    ]
+    .into_iter()
+    .map(|level| {
+        dm_core::events::EventBuilder::new(dm_core::events::EventSource::Core, "node.install")
+            .level(level)
+            .build()
+    })
+    .collect();
+    state.events.emit_many(&events).unwrap();
+
+    let resp = handlers::event_stats(
+        State(state.clone()),
+        Query(handlers::events::StatsParams {
+            group_by: Some("source,hour".to_string()),
+        }),
+        Query(dm_core::events::EventFilter::default()),
+    )
+    .await
+    .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    let bucket = &json["buckets"][0];
+    assert_eq!(bucket["source"], "core");
+    assert_eq!(bucket["count"], 2);
+    assert_eq!(bucket["errors"], 1);
+    assert_eq!(bucket["error_rate"], 0.5);
+    assert!(bucket["hour"].as_str().unwrap().ends_with(":00:00Z"));
+
+    let resp = handlers::event_stats(
         State(state),
-        Query(dm_core::events::EventFilter {
-            case_id: Some("session_test".to_string()),
-            ..Default::default()
+        Query(handlers::events::StatsParams {
+            group_by: Some("robot".to_string()),
         }),
+        Query(dm_core::events::EventFilter::default()),
     )
     .await
     .into_response();
-    assert_eq!(query_resp.status(), axum::http::StatusCode::OK);
+    assert_eq!(resp.status(), axum::http::StatusCode::BAD_REQUEST);
+}
 
-    let body = body_text(query_resp).await;
-    let events: Vec<dm_core::events::Event> = serde_json::from_str(&body).unwrap();
-    assert_eq!(events.len(), 1);
-    assert_eq!(events[0].activity, "ui.click");
-    assert_eq!(events[0].source, "frontend");
+#[tokio::test]
+async fn event_dfg_links_activities_within_cases() {
+    let (_tmp, state) = test_state();
+    for case_id in ["case_a", "case_b"] {
+        for activity in ["dataflow.start", "dataflow.stop"] {
+            state
+                .events
+                .emit(
+                    &dm_core::events::EventBuilder::new(
+                        dm_core::events::EventSource::Core,
+                        activity,
+                    )
+                    .case_id(case_id)
+                    .build(),
+                )
+                .unwrap();
+        }
+    }
+
+    let resp = handlers::event_dfg(State(state), Query(dm_core::events::EventFilter::default()))
+        .await
+        .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    assert_eq!(json["cases"]["count"], 2);
+    assert_eq!(json["edges"][0]["from"], "dataflow.start");
+    assert_eq!(json["edges"][0]["to"], "dataflow.stop");
+    assert_eq!(json["edges"][0]["count"], 2);
 }
 
 #[tokio::test]
 async fn count_events_returns_count() {
     let (_tmp, state) = test_state();
 
-    let event = dm_core::events::EventBuilder::new(dm_core::events::EventSource::Core, "doctor")
-        .case_id("session_count")
-        .build();
-    let _ = handlers::ingest_event(State(state.clone()), Json(event))
+    let event =
+        dm_core::events::EventBuilder::new(dm_core::events::EventSource::Frontend, "ui.click")
+            .case_id("session_count")
+            .build();
+    let _ = handlers::ingest_event(State(state.clone()), HeaderMap::new(), Json(event))
         .await
         .into_response();
 
@@ -1210,6 +2037,7 @@ async fn query_events_returns_empty_list_when_no_match() {
 
     let resp = handlers::query_events(
         State(state),
+        Query(Default::default()),
         Query(dm_core::events::EventFilter {
             case_id: Some("missing_case".to_string()),
             ..Default::default()
@@ -1220,8 +2048,52 @@ async fn query_events_returns_empty_list_when_no_match() {
 
     assert_eq!(resp.status(), axum::http::StatusCode::OK);
     let body = body_text(resp).await;
-    let events: Vec<dm_core::events::Event> = serde_json::from_str(&body).unwrap();
-    assert!(events.is_empty());
+    assert_eq!(body, "[]");
+}
+
+#[tokio::test]
+async fn query_events_pages_with_keyset_cursor() {
+    let (_tmp, state) = test_state();
+    for i in 0..5 {
+        let event =
+            dm_core::events::EventBuilder::new(dm_core::events::EventSource::Core, "doctor")
+                .case_id("session_pages")
+                .message(format!("event {i}"))
+                .build();
+        state.events.emit(&event).unwrap();
+    }
+
+    let filter = dm_core::events::EventFilter {
+        case_id: Some("session_pages".to_string()),
+        limit: Some(2),
+        ..Default::default()
+    };
+    let mut cursor = String::new();
+    let mut seen = Vec::new();
+    loop {
+        let resp = handlers::query_events(
+            State(state.clone()),
+            Query(handlers::events::EventPageParams {
+                cursor: Some(cursor),
+            }),
+            Query(filter.clone()),
+        )
+        .await
+        .into_response();
+        let page: dm_core::events::EventPage =
+            serde_json::from_str(&body_text(resp).await).unwrap();
+        assert_eq!(page.total, 5);
+        seen.extend(page.events.into_iter().filter_map(|event| event.message));
+        match page.next_cursor {
+            Some(next) => cursor = next.to_string(),
+            None => break,
+        }
+    }
+
+    assert_eq!(
+        seen,
+        vec!["event 4", "event 3", "event 2", "event 1", "event 0"]
+    );
 }
 
 #[tokio::test]
@@ -1240,10 +2112,11 @@ async fn start_dataflow_returns_error_when_auto_up_fails() {
     .await
     .into_response();
 
-    // No dora binary configured, so auto-up fails → 500
-    assert_eq!(resp.status(), axum::http::StatusCode::INTERNAL_SERVER_ERROR);
-    let body = body_text(resp).await;
-    assert!(body.contains("auto-start"));
+    // No dora binary configured, so auto-up fails on the missing dependency
+    assert_eq!(resp.status(), axum::http::StatusCode::FAILED_DEPENDENCY);
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    assert_eq!(json["code"], "dependency_missing");
+    assert!(json["error"].as_str().unwrap().contains("auto-start"));
 }
 
 #[tokio::test]
@@ -1288,15 +2161,93 @@ nodes:
             name: Some("media-flow".to_string()),
             force: Some(false),
             view_json: None,
+            allow_multiple: None,
+            isolated: None,
+            dora_version: None,
+            variables: None,
+            auto_install: None,
         }),
     )
     .await
     .into_response();
 
+    assert_eq!(resp.status(), axum::http::StatusCode::FAILED_DEPENDENCY);
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    assert_eq!(json["code"], "dependency_missing");
+    assert!(json["error"]
+        .as_str()
+        .unwrap()
+        .contains("requires dm-server media support"));
+    assert_eq!(
+        json["details"]["media_nodes"],
+        serde_json::json!(["dm-stream-publish"])
+    );
+}
+
+#[tokio::test]
+async fn start_run_returns_transpile_diagnostics_for_unrunnable_graph() {
+    let (_tmp, state) = test_state();
+    setup_fake_dora_home_with_active_file(&state.home, "0.4.1");
+    // Downloaded but never installed: dm.json has no executable.
+    setup_installed_node(&state.home, "demo-node");
+
+    let resp = handlers::start_run(
+        State(state.clone()),
+        Json(
+            serde_json::from_value(serde_json::json!({
+                "yaml": "nodes:\n  - id: a\n    node: demo-node\n    inputs:\n      tick: clock/tick\n",
+                "name": "demo"
+            }))
+            .unwrap(),
+        ),
+    )
+    .await
+    .into_response();
+
+    assert_eq!(resp.status(), axum::http::StatusCode::UNPROCESSABLE_ENTITY);
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    assert_eq!(json["code"], "invalid");
+    let kinds: Vec<_> = json["details"]["diagnostics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|d| d["kind"].as_str().unwrap())
+        .collect();
+    assert_eq!(kinds, vec!["missing_executable", "unknown_input_source"]);
+    assert_eq!(json["details"]["diagnostics"][1]["source"], "clock/tick");
+    assert!(json["error"]
+        .as_str()
+        .unwrap()
+        .contains("Failed to transpile 'demo'"));
+    assert!(dm_core::runs::list_runs(&state.home, 10, 0)
+        .unwrap()
+        .runs
+        .is_empty());
+}
+
+#[tokio::test]
+async fn start_dataflow_rejects_undefined_variables() {
+    let (_tmp, state) = test_state();
+
+    let resp = handlers::start_dataflow(
+        State(state.clone()),
+        Json(
+            serde_json::from_value(serde_json::json!({
+                "yaml": "variables:\n  camera: null\nnodes:\n  - id: cam\n    path: ${camera}\n",
+                "variables": { "fps": "30" }
+            }))
+            .unwrap(),
+        ),
+    )
+    .await
+    .into_response();
+
     assert_eq!(resp.status(), axum::http::StatusCode::BAD_REQUEST);
-    let body = body_text(resp).await;
-    assert!(body.contains("requires dm-server media support"));
-    assert!(body.contains("dm-stream-publish"));
+    assert!(body_text(resp).await.contains("Unknown variable 'fps'"));
+    assert!(dm_core::runs::list_runs(&state.home, 10, 0)
+        .unwrap()
+        .runs
+        .is_empty());
 }
 
 #[tokio::test]
@@ -1336,6 +2287,72 @@ async fn start_run_returns_conflict_for_same_active_dataflow() {
     assert!(body.contains("already running as run"));
 }
 
+#[tokio::test]
+async fn start_run_allow_multiple_starts_second_instance() {
+    let (_tmp, state) = test_state();
+    setup_fake_dora_home_with_active_file(&state.home, "0.4.1");
+
+    for allow_multiple in [false, true] {
+        let resp = handlers::start_run(
+            State(state.clone()),
+            Json(
+                serde_json::from_value(serde_json::json!({
+                    "yaml": "nodes: []",
+                    "name": "demo",
+                    "allow_multiple": allow_multiple
+                }))
+                .unwrap(),
+            ),
+        )
+        .await
+        .into_response();
+        assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    }
+
+    let runs = dm_core::runs::list_runs(&state.home, 10, 0).unwrap();
+    assert_eq!(runs.runs.len(), 2);
+}
+
+#[tokio::test]
+async fn concurrent_start_dataflow_requests_get_separate_run_dirs_under_state_home() {
+    let (_tmp, state) = test_state();
+    setup_fake_dora_home_with_active_file(&state.home, "0.4.1");
+
+    let start = |yaml: &'static str| {
+        handlers::start_dataflow(
+            State(state.clone()),
+            Json(
+                serde_json::from_value(serde_json::json!({
+                    "yaml": yaml,
+                    "allow_multiple": true
+                }))
+                .unwrap(),
+            ),
+        )
+    };
+    let (first, second) = tokio::join!(
+        start("nodes: []\n# first\n"),
+        start("nodes: []\n# second\n")
+    );
+    assert_eq!(first.status(), axum::http::StatusCode::OK);
+    assert_eq!(second.status(), axum::http::StatusCode::OK);
+
+    let runs = dm_core::runs::list_runs(&state.home, 10, 0).unwrap();
+    assert_eq!(runs.runs.len(), 2);
+    let mut snapshots: Vec<String> = runs
+        .runs
+        .iter()
+        .map(|run| {
+            std::fs::read_to_string(dm_core::runs::run_snapshot_path(&state.home, &run.id)).unwrap()
+        })
+        .collect();
+    snapshots.sort();
+    assert_eq!(
+        snapshots,
+        vec!["nodes: []\n# first\n", "nodes: []\n# second\n"]
+    );
+}
+
 #[tokio::test]
 async fn list_runs_refreshes_stale_running_status() {
     let (_tmp, state) = test_state();
@@ -1652,7 +2669,9 @@ async fn status_prefers_run_metadata_for_active_runs() {
     assert_eq!(json["active_runs"][0]["dataflow_name"], "demo-flow");
     assert_eq!(json["active_runs"][0]["status"], "running");
     assert_eq!(json["active_runs"][0]["expected_nodes"], 0);
+    assert!(json["active_runs"][0]["uptime_secs"].as_i64().is_some());
     assert_eq!(json["dora_probe"].as_array().unwrap().len(), 0);
+    assert_eq!(json["untracked_dataflows"].as_array().unwrap().len(), 0);
 }
 
 #[tokio::test]
@@ -2037,3 +3056,319 @@ async fn serve_artifact_file_reads_run_out_and_rejects_traversal() {
     let body = body_text(ok).await;
     assert_eq!(body, "{\"ok\":true}");
 }
+
+#[test]
+fn threshold_alerts_fire_once_until_recovered() {
+    use crate::services::thresholds::newly_exceeded;
+
+    let alert = |metric: &str| dm_core::types::ThresholdAlert {
+        metric: metric.to_string(),
+        value: 1.0,
+        limit: 0.0,
+        message: String::new(),
+    };
+    let mut exceeded = std::collections::BTreeSet::new();
+
+    assert_eq!(
+        newly_exceeded(&mut exceeded, vec![alert("versions")]).len(),
+        1
+    );
+    assert!(newly_exceeded(&mut exceeded, vec![alert("versions")]).is_empty());
+    let fresh = newly_exceeded(
+        &mut exceeded,
+        vec![alert("versions"), alert("disk_free_pct")],
+    );
+    assert_eq!(fresh, vec![alert("disk_free_pct")]);
+
+    assert!(newly_exceeded(&mut exceeded, Vec::new()).is_empty());
+    assert_eq!(
+        newly_exceeded(&mut exceeded, vec![alert("versions")]).len(),
+        1
+    );
+}
+
+#[tokio::test]
+async fn tail_node_log_returns_recent_lines_and_follows_appends() {
+    use std::io::Write;
+
+    let (_tmp, state) = test_state();
+    setup_run(&state.home, "run-tail");
+    let log_path = dm_core::runs::run_logs_dir(&state.home, "run-tail").join("camera.log");
+    std::fs::create_dir_all(log_path.parent().unwrap()).unwrap();
+    let body: String = (1..=300).map(|i| format!("line {i}\n")).collect();
+    std::fs::write(&log_path, body).unwrap();
+
+    let tail = |lines: usize| {
+        handlers::tail_node_log(
+            State(state.clone()),
+            Path(("run-tail".to_string(), "camera".to_string())),
+            Query(serde_json::from_value(serde_json::json!({ "lines": lines })).unwrap()),
+        )
+    };
+
+    let resp = tail(3).await.into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    assert_eq!(
+        json["lines"],
+        serde_json::json!(["line 298", "line 299", "line 300"])
+    );
+
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(&log_path)
+        .unwrap();
+    file.write_all(b"line 301\n").unwrap();
+    let json: serde_json::Value =
+        serde_json::from_str(&body_text(tail(2).await.into_response()).await).unwrap();
+    assert_eq!(json["lines"], serde_json::json!(["line 300", "line 301"]));
+
+    let missing = handlers::tail_node_log(
+        State(state.clone()),
+        Path(("run-tail".to_string(), "ghost".to_string())),
+        Query(serde_json::from_value(serde_json::json!({})).unwrap()),
+    )
+    .await
+    .into_response();
+    assert_eq!(missing.status(), axum::http::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn import_run_events_rejects_running_and_imports_finished_runs() {
+    let (_tmp, state) = test_state();
+    setup_run(&state.home, "run-import");
+
+    let resp = handlers::import_run_events(State(state.clone()), Path("run-import".to_string()))
+        .await
+        .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::CONFLICT);
+
+    let mut run = dm_core::runs::load_run(&state.home, "run-import").unwrap();
+    run.status = dm_core::runs::RunStatus::Failed;
+    dm_core::runs::save_run(&state.home, &run).unwrap();
+    let logs = dm_core::runs::run_logs_dir(&state.home, "run-import");
+    std::fs::create_dir_all(&logs).unwrap();
+    std::fs::write(logs.join("camera.log"), "opened\nERROR no device\n").unwrap();
+
+    let resp = handlers::import_run_events(State(state.clone()), Path("run-import".to_string()))
+        .await
+        .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    let json: serde_json::Value = serde_json::from_str(&body_text(resp).await).unwrap();
+    assert_eq!(json["imported"], 2);
+    assert_eq!(json["nodes"]["camera"], 2);
+
+    let resp = handlers::import_run_events(State(state), Path("missing".to_string()))
+        .await
+        .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn install_progress_streams_latest_install_job_as_sse() {
+    use crate::services::jobs::JobKind;
+    use dm_core::types::{InstallPhase, InstallProgress};
+
+    let (_tmp, state) = test_state();
+    let resp = handlers::install_progress(State(state.clone())).await;
+    assert_eq!(resp.status(), axum::http::StatusCode::NOT_FOUND);
+
+    state.jobs.create(JobKind::NodeInstall, "some-node");
+    let job = state.jobs.create(JobKind::Install, "0.4.1");
+    state.jobs.progress(
+        &job.id,
+        &InstallProgress {
+            phase: InstallPhase::Downloading {
+                bytes_done: 10,
+                bytes_total: 100,
+            },
+            message: "Downloading".to_string(),
+        },
+    );
+    state
+        .jobs
+        .finish(&job.id, Ok(serde_json::json!({ "version": "0.4.1" })));
+
+    let resp = handlers::install_progress(State(state.clone())).await;
+    assert_eq!(resp.status(), axum::http::StatusCode::OK);
+    let body = body_text(resp).await;
+    assert_eq!(body.matches("event: job").count(), 2);
+    assert!(body.contains("\"bytes_total\":100"));
+    assert!(body.contains("\"phase\":\"finished\""));
+
+    let resp = handlers::job_events(State(state), Path("job-unknown".to_string())).await;
+    assert_eq!(resp.status(), axum::http::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn serve_mounts_extension_routes_and_stops_on_shutdown() {
+    let tmp = TempDir::new().unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+    let config = crate::ServeConfig::new(tmp.path())
+        .background_services(false)
+        .extend(|router| router.route("/agent/ping", axum::routing::get(|| async { "pong" })))
+        .shutdown(async {
+            let _ = stop_rx.await;
+        });
+    let server = tokio::spawn(crate::serve_listener(listener, config));
+
+    let ping = reqwest::get(format!("http://{addr}/agent/ping"))
+        .await
+        .unwrap();
+    assert_eq!(ping.text().await.unwrap(), "pong");
+    let dataflows = reqwest::get(format!("http://{addr}/api/dataflows"))
+        .await
+        .unwrap();
+    assert!(dataflows.status().is_success());
+    assert_eq!(dm_core::lock::running_servers(tmp.path()).len(), 1);
+
+    stop_tx.send(()).unwrap();
+    server.await.unwrap().unwrap();
+    assert!(dm_core::lock::running_servers(tmp.path()).is_empty());
+}
+
+#[tokio::test]
+async fn serve_publishes_the_openapi_document() {
+    let tmp = TempDir::new().unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+    let config = crate::ServeConfig::new(tmp.path())
+        .background_services(false)
+        .shutdown(async {
+            let _ = stop_rx.await;
+        });
+    let server = tokio::spawn(crate::serve_listener(listener, config));
+
+    let spec: serde_json::Value = reqwest::get(format!("http://{addr}{}", crate::OPENAPI_PATH))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let paths = spec["paths"].as_object().unwrap();
+    for path in [
+        "/api/status",
+        "/api/events",
+        "/api/dataflows/{name}/history",
+        "/api/runs/{id}/logs/{node_id}",
+    ] {
+        assert!(paths.contains_key(path), "{path} missing from the spec");
+    }
+    assert!(spec["components"]["schemas"]["StatusReport"].is_object());
+    assert!(spec["components"]["schemas"]["ErrorReport"].is_object());
+
+    let legacy = reqwest::get(format!("http://{addr}/api-docs/openapi.json"))
+        .await
+        .unwrap();
+    assert!(legacy.status().is_success());
+
+    stop_tx.send(()).unwrap();
+    server.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn update_config_rejects_unknown_keys() {
+    let (_tmp, state) = test_state();
+
+    let resp = handlers::update_config(
+        State(state.clone()),
+        Json(serde_json::json!({ "media": { "enabeld": true } })),
+    )
+    .await
+    .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::BAD_REQUEST);
+    assert!(
+        !dm_core::config::load_config(&state.home)
+            .unwrap()
+            .media
+            .enabled
+    );
+}
+
+#[tokio::test]
+async fn run_metrics_history_returns_404_for_missing_run() {
+    let (_tmp, state) = test_state();
+
+    let resp = handlers::get_run_metrics_history(
+        State(state),
+        Path("missing-run".to_string()),
+        Query(handlers::runs::MetricsHistoryParams {
+            since: None,
+            limit: None,
+        }),
+    )
+    .await
+    .into_response();
+    assert_eq!(resp.status(), axum::http::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn mutating_calls_are_audited_with_client_and_token_name() {
+    let tmp = TempDir::new().unwrap();
+    std::fs::write(
+        dm_core::config::config_path(tmp.path()),
+        "[[ingest.tokens]]\nname = \"ci\"\ntoken = \"ci-secret\"\nsources = [\"ci\"]\n",
+    )
+    .unwrap();
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel::<()>();
+    let config = crate::ServeConfig::new(tmp.path())
+        .background_services(false)
+        .shutdown(async {
+            let _ = stop_rx.await;
+        });
+    let server = tokio::spawn(crate::serve_listener(listener, config));
+
+    let client = reqwest::Client::new();
+    let saved = client
+        .post(format!("http://{addr}/api/dataflows/demo"))
+        .bearer_auth("ci-secret")
+        .json(&serde_json::json!({ "yaml": "nodes: []\n" }))
+        .send()
+        .await
+        .unwrap();
+    assert!(saved.status().is_success());
+    let rejected = client
+        .post(format!("http://{addr}/api/config"))
+        .json(&serde_json::json!({ "no_such_key": 1 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(rejected.status(), axum::http::StatusCode::BAD_REQUEST);
+    // Reads are not audited.
+    reqwest::get(format!("http://{addr}/api/dataflows"))
+        .await
+        .unwrap();
+
+    let entries: serde_json::Value = reqwest::get(format!("http://{addr}/api/audit"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let entries = entries.as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["action"], "config.update");
+    assert_eq!(entries[0]["target"], "no_such_key");
+    assert_eq!(entries[0]["status"], 400);
+    assert!(entries[0]["user"].is_null());
+    assert_eq!(entries[1]["action"], "dataflow.save");
+    assert_eq!(entries[1]["target"], "demo");
+    assert_eq!(entries[1]["user"], "ci");
+    assert_eq!(entries[1]["client_ip"], "127.0.0.1");
+
+    let by_ci: serde_json::Value = reqwest::get(format!("http://{addr}/api/audit?user=ci"))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(by_ci.as_array().unwrap().len(), 1);
+
+    stop_tx.send(()).unwrap();
+    server.await.unwrap().unwrap();
+}
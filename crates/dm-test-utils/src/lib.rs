@@ -0,0 +1,418 @@
+//! Test helpers for Dora Manager.
+//!
+//! The main entry point is [`MockDora`], a builder that writes a scriptable
+//! fake `dora` binary into a dm home and marks it as the active version.
+//! Every invocation is appended to a call log so tests can assert on the
+//! exact commands dm issued. [`MockCommand`] does the same for the other
+//! tools dm shells out to (`cargo`, `git`, `uv`, ...), and [`fake_node`]
+//! writes an installed node's `dm.json`.
+//!
+//! ```no_run
+//! use dm_test_utils::{MockDora, MockResponse};
+//!
+//! let home = std::env::temp_dir().join("dm-example");
+//! let dora = MockDora::new("0.4.1")
+//!     .respond("destroy", MockResponse::fail(1, "cannot connect to coordinator"))
+//!     .install(&home)
+//!     .unwrap();
+//! assert!(dora.calls().is_empty());
+//! ```
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+
+/// Dataflow UUID reported by the default `start` / `list` responses.
+pub const FAKE_DORA_UUID: &str = "019cc181-adad-7654-aa78-63502362337b";
+
+/// Dataflow name reported by the default stateful `list` response.
+pub const FAKE_DATAFLOW_NAME: &str = "test-flow";
+
+/// Scripted reply for a single dora subcommand.
+#[derive(Debug, Clone, Default)]
+pub struct MockResponse {
+    stdout: String,
+    stderr: String,
+    exit_code: i32,
+    delay: Option<Duration>,
+    script: Option<String>,
+}
+
+impl MockResponse {
+    /// Print `stdout` and exit 0.
+    pub fn ok(stdout: impl Into<String>) -> Self {
+        Self {
+            stdout: stdout.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Print `stderr` and exit with `exit_code`.
+    pub fn fail(exit_code: i32, stderr: impl Into<String>) -> Self {
+        Self {
+            stderr: stderr.into(),
+            exit_code,
+            ..Self::default()
+        }
+    }
+
+    /// Run a raw `sh` snippet. `$@` holds the full dora argument list.
+    pub fn script(body: impl Into<String>) -> Self {
+        Self {
+            script: Some(body.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_stderr(mut self, stderr: impl Into<String>) -> Self {
+        self.stderr = stderr.into();
+        self
+    }
+
+    /// Sleep before answering, to simulate a slow coordinator.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    fn render(&self) -> String {
+        let mut out = String::new();
+        if let Some(delay) = self.delay {
+            out.push_str(&format!("    sleep {}\n", delay.as_secs_f64()));
+        }
+        if let Some(script) = &self.script {
+            for line in script.lines() {
+                out.push_str("    ");
+                out.push_str(line);
+                out.push('\n');
+            }
+            return out;
+        }
+        if !self.stdout.is_empty() {
+            out.push_str(&print(&self.stdout, ""));
+        }
+        if !self.stderr.is_empty() {
+            out.push_str(&print(&self.stderr, " >&2"));
+        }
+        out.push_str(&format!("    exit {}\n", self.exit_code));
+        out
+    }
+}
+
+/// `printf` is a shell builtin, so replies work even when a test empties
+/// `PATH`.
+fn print(text: &str, redirect: &str) -> String {
+    format!(
+        "    printf '%s\\n' '{}'{redirect}\n",
+        text.trim_end_matches('\n').replace('\'', "'\\''")
+    )
+}
+
+/// Builder for a fake `dora` CLI installed under `<home>/versions/<version>/`.
+///
+/// The defaults behave like a healthy runtime with one stateful dataflow:
+/// `start` records [`FAKE_DORA_UUID`] in `<home>/active_dataflow_id` and
+/// writes a worker log under the run's `out/` directory, `list` reports it
+/// as running, and `stop` clears it.
+#[derive(Debug, Clone)]
+pub struct MockDora {
+    version: String,
+    command: MockCommand,
+}
+
+impl MockDora {
+    pub fn new(version: impl Into<String>) -> Self {
+        let version = version.into();
+        let command = MockCommand::new()
+            .respond(
+                "--version",
+                MockResponse::ok(format!("dora-cli {}", version)),
+            )
+            .respond("check", MockResponse::ok("Runtime OK"))
+            .respond("up", MockResponse::ok("started"))
+            .respond("destroy", MockResponse::ok("stopped"))
+            .respond(
+                "list",
+                MockResponse::script(format!(
+                    r#"if [ -f "$DM_MOCK_STATE" ]; then
+  echo "UUID Name Status Nodes CPU Memory"
+  printf "%s {FAKE_DATAFLOW_NAME} Running 1 0.0%% 0.0\\ GB\\n" "$(cat "$DM_MOCK_STATE")"
+fi
+exit 0"#
+                )),
+            )
+            .respond(
+                "start",
+                MockResponse::script(format!(
+                    r#"run_dir="$(dirname "$2")"
+mkdir -p "$run_dir/out/{FAKE_DORA_UUID}"
+echo "worker log line" > "$run_dir/out/{FAKE_DORA_UUID}/log_worker.txt"
+echo "{FAKE_DORA_UUID}" > "$DM_MOCK_STATE"
+echo "dataflow started: {FAKE_DORA_UUID}"
+exit 0"#
+                )),
+            )
+            .respond(
+                "stop",
+                MockResponse::script("rm -f \"$DM_MOCK_STATE\"\necho \"dataflow stopped\"\nexit 0"),
+            );
+
+        Self { version, command }
+    }
+
+    /// Replace the reply for `command` (the first dora argument).
+    pub fn respond(mut self, command: &str, response: MockResponse) -> Self {
+        self.command = self.command.respond(command, response);
+        self
+    }
+
+    /// Shorthand for making `command` fail with the given exit code.
+    pub fn fail(self, command: &str, exit_code: i32, stderr: &str) -> Self {
+        self.respond(command, MockResponse::fail(exit_code, stderr))
+    }
+
+    /// Reply used for commands without an explicit response.
+    pub fn fallback(mut self, response: MockResponse) -> Self {
+        self.command = self.command.fallback(response);
+        self
+    }
+
+    /// Add a fixed delay before every invocation.
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.command = self.command.latency(latency);
+        self
+    }
+
+    /// Simulate a coordinator that is not reachable: `check` fails.
+    pub fn runtime_down(self) -> Self {
+        self.fail("check", 1, "Runtime unavailable")
+    }
+
+    /// Write the fake binary into `home` and make it the active version.
+    pub fn install(self, home: &Path) -> Result<MockDoraHandle> {
+        let version_dir = dm_core::config::versions_dir(home).join(&self.version);
+        std::fs::create_dir_all(&version_dir)
+            .with_context(|| format!("Failed to create {}", version_dir.display()))?;
+
+        let bin = version_dir.join(dm_core::config::dora_bin_name());
+        let state_file = home.join("active_dataflow_id");
+        let command = self.command.write(
+            &bin,
+            &format!("DM_MOCK_STATE=\"{}\"\n", state_file.display()),
+        )?;
+
+        let mut cfg = dm_core::config::load_config(home)?;
+        cfg.active_version = Some(self.version.clone());
+        dm_core::config::save_config(home, &cfg)?;
+
+        Ok(MockDoraHandle {
+            bin,
+            state_file,
+            command,
+        })
+    }
+}
+
+/// Builder for a fake CLI that answers by its first argument.
+///
+/// ```no_run
+/// use dm_test_utils::{MockCommand, MockResponse};
+///
+/// let bin = std::env::temp_dir().join("bin").join("cargo");
+/// let cargo = MockCommand::new()
+///     .respond("--version", MockResponse::ok("cargo 1.0"))
+///     .fallback(MockResponse::fail(1, "build failed"))
+///     .install_at(&bin)
+///     .unwrap();
+/// assert!(cargo.calls().is_empty());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockCommand {
+    responses: BTreeMap<String, MockResponse>,
+    fallback: MockResponse,
+    latency: Option<Duration>,
+}
+
+impl MockCommand {
+    /// A command that prints nothing and exits 0 whatever it is asked.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the reply for `command` (the first argument).
+    pub fn respond(mut self, command: &str, response: MockResponse) -> Self {
+        self.responses.insert(command.to_string(), response);
+        self
+    }
+
+    /// Shorthand for making `command` fail with the given exit code.
+    pub fn fail(self, command: &str, exit_code: i32, stderr: &str) -> Self {
+        self.respond(command, MockResponse::fail(exit_code, stderr))
+    }
+
+    /// Reply used for commands without an explicit response.
+    pub fn fallback(mut self, response: MockResponse) -> Self {
+        self.fallback = response;
+        self
+    }
+
+    /// Add a fixed delay before every invocation.
+    pub fn latency(mut self, latency: Duration) -> Self {
+        self.latency = Some(latency);
+        self
+    }
+
+    /// Write the fake binary to `bin`, creating its directory. Calls are
+    /// logged to `<bin>.calls.log`.
+    pub fn install_at(&self, bin: &Path) -> Result<MockCommandHandle> {
+        self.write(bin, "")
+    }
+
+    fn write(&self, bin: &Path, preamble: &str) -> Result<MockCommandHandle> {
+        if let Some(dir) = bin.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        let mut calls_file = bin.as_os_str().to_owned();
+        calls_file.push(".calls.log");
+        let calls_file = PathBuf::from(calls_file);
+
+        std::fs::write(bin, self.render(preamble, &calls_file))
+            .with_context(|| format!("Failed to write mock command to {}", bin.display()))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = std::fs::metadata(bin)?.permissions();
+            perms.set_mode(0o755);
+            std::fs::set_permissions(bin, perms)?;
+        }
+
+        Ok(MockCommandHandle {
+            bin: bin.to_path_buf(),
+            calls_file,
+        })
+    }
+
+    fn render(&self, preamble: &str, calls_file: &Path) -> String {
+        let mut script = String::from("#!/bin/sh\n");
+        script.push_str(preamble);
+        script.push_str(&format!(
+            "printf '%s\\n' \"$*\" >> \"{}\"\n",
+            calls_file.display()
+        ));
+        if let Some(latency) = self.latency {
+            script.push_str(&format!("sleep {}\n", latency.as_secs_f64()));
+        }
+        script.push_str("case \"$1\" in\n");
+        for (command, response) in &self.responses {
+            script.push_str(&format!("  {})\n", command));
+            script.push_str(&response.render());
+            script.push_str("    ;;\n");
+        }
+        script.push_str("  *)\n");
+        script.push_str(&self.fallback.render());
+        script.push_str("    ;;\nesac\n");
+        script
+    }
+}
+
+/// An installed [`MockCommand`].
+#[derive(Debug, Clone)]
+pub struct MockCommandHandle {
+    /// Path to the fake binary.
+    pub bin: PathBuf,
+    calls_file: PathBuf,
+}
+
+impl MockCommandHandle {
+    /// Every invocation so far, one space-joined argument list per call.
+    pub fn calls(&self) -> Vec<String> {
+        std::fs::read_to_string(&self.calls_file)
+            .map(|content| content.lines().map(str::to_string).collect())
+            .unwrap_or_default()
+    }
+
+    /// Invocations whose first argument is `command`.
+    pub fn calls_to(&self, command: &str) -> Vec<String> {
+        self.calls()
+            .into_iter()
+            .filter(|call| call.split_whitespace().next() == Some(command))
+            .collect()
+    }
+}
+
+/// An installed [`MockDora`].
+#[derive(Debug, Clone)]
+pub struct MockDoraHandle {
+    /// Path to the fake binary.
+    pub bin: PathBuf,
+    /// File whose presence makes the default `list` report a running dataflow.
+    pub state_file: PathBuf,
+    command: MockCommandHandle,
+}
+
+impl MockDoraHandle {
+    /// Every invocation so far, one space-joined argument list per call.
+    pub fn calls(&self) -> Vec<String> {
+        self.command.calls()
+    }
+
+    /// Invocations whose first argument is `command`.
+    pub fn calls_to(&self, command: &str) -> Vec<String> {
+        self.command.calls_to(command)
+    }
+
+    /// Pretend a dataflow with `uuid` is running.
+    pub fn set_active_dataflow(&self, uuid: &str) -> Result<()> {
+        std::fs::write(&self.state_file, uuid)?;
+        Ok(())
+    }
+
+    /// Pretend no dataflow is running.
+    pub fn clear_active_dataflow(&self) -> Result<()> {
+        if self.state_file.exists() {
+            std::fs::remove_file(&self.state_file)?;
+        }
+        Ok(())
+    }
+}
+
+/// Write `dm.json` for node `id` under `home`: a minimal valid manifest with
+/// the keys of `fields` (a JSON object) replacing or extending it. Returns
+/// the node's directory.
+///
+/// ```no_run
+/// let home = std::env::temp_dir().join("dm-example");
+/// let dir = dm_test_utils::fake_node(
+///     &home,
+///     "camera",
+///     serde_json::json!({ "executable": "bin/camera" }),
+/// )
+/// .unwrap();
+/// assert!(dir.join("dm.json").exists());
+/// ```
+pub fn fake_node(home: &Path, id: &str, fields: serde_json::Value) -> Result<PathBuf> {
+    let mut meta = serde_json::json!({
+        "id": id,
+        "version": "1.0.0",
+        "installed_at": "0",
+        "source": { "build": "pip install demo" },
+    });
+    let Some(fields) = fields.as_object() else {
+        anyhow::bail!("fake_node fields must be a JSON object, got {fields}");
+    };
+    for (key, value) in fields {
+        meta[key] = value.clone();
+    }
+
+    let dir = dm_core::node::node_dir(home, id);
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    std::fs::write(
+        dm_core::node::dm_json_path(home, id),
+        serde_json::to_string_pretty(&meta)?,
+    )?;
+    Ok(dir)
+}
@@ -0,0 +1,190 @@
+#![cfg(unix)]
+
+use std::time::Duration;
+
+use dm_test_utils::{MockDora, MockResponse, FAKE_DATAFLOW_NAME, FAKE_DORA_UUID};
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn status_reports_running_runtime_from_mock() {
+    let home = tempdir().unwrap();
+    let dora = MockDora::new("0.4.1").install(home.path()).unwrap();
+    dora.set_active_dataflow(FAKE_DORA_UUID).unwrap();
+
+    let report = dm_core::status(home.path(), true).await.unwrap();
+
+    assert_eq!(report.active_version.as_deref(), Some("0.4.1"));
+    assert!(report.runtime_running);
+    assert_eq!(report.runtime_output, "Runtime OK");
+    assert_eq!(report.dora_probe.len(), 1);
+    assert_eq!(report.dora_probe[0].id, FAKE_DORA_UUID);
+    assert_eq!(
+        report.dora_probe[0].runtime_name.as_deref(),
+        Some(FAKE_DATAFLOW_NAME)
+    );
+    assert_eq!(dora.calls_to("check").len(), 1);
+    assert_eq!(dora.calls_to("list").len(), 1);
+}
+
+#[tokio::test]
+async fn status_reports_stopped_runtime_when_check_fails() {
+    let home = tempdir().unwrap();
+    MockDora::new("0.4.1")
+        .runtime_down()
+        .install(home.path())
+        .unwrap();
+
+    let report = dm_core::status(home.path(), false).await.unwrap();
+
+    assert!(!report.runtime_running);
+    assert_eq!(report.runtime_output, "Runtime unavailable");
+}
+
+#[tokio::test]
+async fn up_succeeds_once_runtime_answers_check() {
+    let home = tempdir().unwrap();
+    let dora = MockDora::new("0.4.1").install(home.path()).unwrap();
+
+    let result = dm_core::up(home.path(), false).await.unwrap();
+
+    assert!(result.success, "{}", result.message);
+    assert_eq!(dora.calls_to("up").len(), 1);
+}
+
+#[tokio::test]
+async fn up_surfaces_injected_failure() {
+    let home = tempdir().unwrap();
+    MockDora::new("0.4.1")
+        .runtime_down()
+        .fail("up", 1, "address already in use")
+        .install(home.path())
+        .unwrap();
+
+    let result = dm_core::up(home.path(), false).await.unwrap();
+
+    assert!(!result.success);
+    assert!(result.message.contains("address already in use"));
+}
+
+#[tokio::test]
+async fn down_destroys_running_runtime() {
+    let home = tempdir().unwrap();
+    let dora = MockDora::new("0.4.1")
+        .respond(
+            "check",
+            MockResponse::script(
+                "if [ -f \"$DM_MOCK_STATE.down\" ]; then echo 'Runtime unavailable' >&2; exit 1; fi\necho 'Runtime OK'",
+            ),
+        )
+        .respond(
+            "destroy",
+            MockResponse::script("touch \"$DM_MOCK_STATE.down\"\necho 'destroyed'"),
+        )
+        .install(home.path())
+        .unwrap();
+
+    let result = dm_core::down(home.path(), false).await.unwrap();
+
+    assert!(result.success, "{}", result.message);
+    assert_eq!(result.message, "destroyed");
+    assert_eq!(dora.calls_to("destroy").len(), 1);
+}
+
+#[tokio::test]
+async fn down_is_noop_when_runtime_already_stopped() {
+    let home = tempdir().unwrap();
+    let dora = MockDora::new("0.4.1")
+        .runtime_down()
+        .install(home.path())
+        .unwrap();
+
+    let result = dm_core::down(home.path(), false).await.unwrap();
+
+    assert!(result.success);
+    assert!(result.message.contains("already stopped"));
+    assert!(dora.calls_to("destroy").is_empty());
+}
+
+#[tokio::test]
+async fn latency_is_applied_to_every_call() {
+    let home = tempdir().unwrap();
+    MockDora::new("0.4.1")
+        .latency(Duration::from_millis(200))
+        .install(home.path())
+        .unwrap();
+
+    let started = std::time::Instant::now();
+    assert!(dm_core::is_runtime_running(home.path(), false).await);
+    assert!(started.elapsed() >= Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn up_passes_configured_and_cli_env_to_dora() {
+    let home = tempdir().unwrap();
+    let dora = MockDora::new("0.4.1")
+        .respond(
+            "up",
+            MockResponse::script(
+                "printf '%s %s' \"$RUST_LOG\" \"$DORA_EXTRA\" > \"$DM_MOCK_STATE.env\"\necho started",
+            ),
+        )
+        .install(home.path())
+        .unwrap();
+    let mut cfg = dm_core::config::load_config(home.path()).unwrap();
+    cfg.runtime_env.insert("RUST_LOG".into(), "info".into());
+    cfg.runtime_env.insert("DORA_EXTRA".into(), "config".into());
+    dm_core::config::save_config(home.path(), &cfg).unwrap();
+
+    let cli_env = [("DORA_EXTRA".to_string(), "cli".to_string())]
+        .into_iter()
+        .collect();
+    let result = dm_core::up_with_env(home.path(), false, &cli_env, None)
+        .await
+        .unwrap();
+
+    assert!(result.success, "{}", result.message);
+    let seen = std::fs::read_to_string(dora.state_file.with_extension("env")).unwrap();
+    assert_eq!(seen, "info cli");
+}
+
+#[tokio::test]
+async fn status_lists_dataflows_no_run_tracks() {
+    let home = tempdir().unwrap();
+    let dora = MockDora::new("0.4.1").install(home.path()).unwrap();
+    dora.set_active_dataflow(FAKE_DORA_UUID).unwrap();
+
+    let report = dm_core::status(home.path(), false).await.unwrap();
+
+    assert!(report.dora_probe.is_empty());
+    assert_eq!(report.untracked_dataflows.len(), 1);
+    assert_eq!(report.untracked_dataflows[0].id, FAKE_DORA_UUID);
+    assert!(report.untracked_dataflows[0].run_id.is_none());
+}
+
+#[tokio::test]
+async fn run_starts_and_stops_through_the_mock() {
+    let home = tempdir().unwrap();
+    let dora = MockDora::new("0.4.1").install(home.path()).unwrap();
+
+    let started = dm_core::runs::start_run_from_yaml(
+        home.path(),
+        "nodes:\n  - id: n1\n    path: /bin/true\n",
+        "demo",
+    )
+    .await
+    .unwrap();
+    let run_id = started.run.run_id.clone();
+
+    assert_eq!(started.run.dora_uuid.as_deref(), Some(FAKE_DORA_UUID));
+    assert_eq!(dora.calls_to("start").len(), 1);
+    let runs = dm_core::runs::refresh_run_statuses(home.path()).unwrap();
+    let run = runs.iter().find(|run| run.run_id == run_id).unwrap();
+    assert_eq!(run.status, dm_core::runs::RunStatus::Running);
+
+    let stopped = dm_core::runs::stop_run(home.path(), &run_id).await.unwrap();
+
+    assert_eq!(stopped.status, dm_core::runs::RunStatus::Stopped);
+    assert_eq!(dora.calls_to("stop").len(), 1);
+    assert!(dora.calls_to("stop")[0].contains(FAKE_DORA_UUID));
+    assert!(!dora.state_file.exists());
+}
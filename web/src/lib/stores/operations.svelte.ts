@@ -0,0 +1,65 @@
+import { get } from '$lib/api';
+
+// In-flight long-running operations, derived from `*.progress` events.
+export type Operation = {
+    caseId: string;
+    activity: string;
+    phase: string;
+    pct: number | null;
+    message: string;
+    timestamp: string;
+};
+
+const STALE_MS = 60_000;
+
+let operations = $state<Operation[]>([]);
+let timer: ReturnType<typeof setInterval> | null = null;
+
+async function poll() {
+    const since = new Date(Date.now() - STALE_MS).toISOString();
+    const events: any[] = await get(
+        `/events?activity=progress&since=${encodeURIComponent(since)}&limit=200`,
+    ).catch(() => []);
+
+    const latest = new Map<string, Operation>();
+    for (const event of events) {
+        const attrs = event.attributes ? JSON.parse(event.attributes) : {};
+        const current = latest.get(event.case_id);
+        if (current && current.timestamp >= event.timestamp) continue;
+        latest.set(event.case_id, {
+            caseId: event.case_id,
+            activity: event.activity.replace(/\.progress$/, ''),
+            phase: attrs.phase ?? '',
+            pct: typeof attrs.pct === 'number' ? attrs.pct : null,
+            message: event.message ?? '',
+            timestamp: event.timestamp,
+        });
+    }
+    const finished = await Promise.all([...latest.values()].map(isFinished));
+    operations = [...latest.values()].filter((_, i) => !finished[i]);
+}
+
+// The result event is the last one an operation writes, as in
+// `dm_core::events::in_flight_operations`.
+async function isFinished(op: Operation): Promise<boolean> {
+    const events: any[] = await get(
+        `/events?case_id=${encodeURIComponent(op.caseId)}&limit=1`,
+    ).catch(() => []);
+    const last = events[0];
+    return !!last && last.activity === op.activity && last.message !== 'START';
+}
+
+export function useOperations() {
+    return {
+        get operations() { return operations; },
+        start(intervalMs = 2000) {
+            if (timer) return;
+            poll();
+            timer = setInterval(poll, intervalMs);
+        },
+        stop() {
+            if (timer) clearInterval(timer);
+            timer = null;
+        },
+    };
+}
@@ -24,6 +24,31 @@ async function refresh(showSkeleton = false) {
     }
 }
 
+// Live status pushed by the server whenever runtime/runs change.
+let source: EventSource | null = null;
+let subscribers = 0;
+
+function subscribe() {
+    subscribers += 1;
+    if (!source) {
+        source = new EventSource('/api/status/stream');
+        source.addEventListener('status', (event) => {
+            try {
+                status = JSON.parse((event as MessageEvent).data);
+            } catch {
+                // Ignore malformed frames; the next change resends the full report.
+            }
+        });
+    }
+    return () => {
+        subscribers -= 1;
+        if (subscribers === 0 && source) {
+            source.close();
+            source = null;
+        }
+    };
+}
+
 export function useStatus() {
     return {
         get status() { return status; },
@@ -31,5 +56,6 @@ export function useStatus() {
         get nodes() { return nodes; },
         get loading() { return loading; },
         refresh,
+        subscribe,
     };
 }